@@ -16,7 +16,7 @@
 -----------------------------------------------------------------------*/
 use chrono::Local;
 use chrono::{Days, NaiveDate, NaiveTime};
-use csv::ReaderBuilder;
+use fin_import::qif::QifAccountType;
 use inquire::Confirm;
 use inquire::Select;
 use inquire::Text;
@@ -47,12 +47,17 @@ use rustyline::Helper;
 use rustyline::Highlighter;
 use rustyline::Hinter;
 use rustyline::Validator;
-use shared_lib::{FlatLedgerEntry, LedgerEntry, StockInfo};
+use shared_lib::{LedgerEntry, StockInfo};
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::path::Path;
 use std::{option, rc};
 
+use super::base::export::{select_export_format, write_export_file};
+use super::base::import::{
+    apply_merges, confirm_import, read_import_file, save_import_settings, select_import_format,
+    ImportedEntry,
+};
 use crate::accounts::base::budget::Budget;
 use crate::accounts::base::liquid_account::LiquidAccount;
 #[cfg(feature = "ratatui_support")]
@@ -62,6 +67,7 @@ use crate::app::app::{App, DisplayValue, LineChart};
 #[cfg(feature = "ratatui_support")]
 use crate::app::screen::ledger_table_constraint_len_calculator;
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::tui::get_analysis_period_dates;
 use crate::tui::query_user_for_analysis_period;
 use crate::types::accounts::AccountInfo;
@@ -76,7 +82,7 @@ use crate::types::participants;
 use crate::types::participants::ParticipantType;
 #[cfg(feature = "ratatui_support")]
 use crate::ui::{centered_rect, float_range};
-use shared_lib::TransferType;
+use shared_lib::{Money, TransferType};
 
 use super::base::fixed_account::FixedAccount;
 use super::base::Account;
@@ -110,7 +116,7 @@ struct FilePathHelper {
 }
 
 impl BankAccount {
-    pub fn new(uid: u32, id: u32, db: &DbConn) -> Self {
+    pub fn new(uid: u32, id: u32, db: &DbConn) -> Result<Self> {
         let mut acct: BankAccount = Self {
             uid: uid,
             id: id,
@@ -120,19 +126,19 @@ impl BankAccount {
             budget: None,
         };
 
-        let mut ledger = acct.get_ledger();
+        let mut ledger = acct.get_ledger()?;
         if !ledger.is_empty() {
             ledger.sort_by(|l1, l2| (&l1.info.date).cmp(&l2.info.date));
-            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d").unwrap();
+            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d")?;
         }
         if acct.has_budget() {
             acct.budget = Some(Budget::new(uid, id, db));
         }
 
-        acct
+        Ok(acct)
     }
 
-    pub fn get_linechart(&self, app: &mut App) -> Option<LineChart> {
+    pub fn get_linechart(&self, app: &mut App) -> Result<Option<LineChart>> {
         let (start, end) = (app.analysis_start, app.analysis_end);
         let starting_amount_opt = self
             .db
@@ -156,7 +162,7 @@ impl BankAccount {
                 id: 0,
                 info: LedgerInfo {
                     date: start.checked_add_days(Days::new(1)).unwrap().to_string(),
-                    amount: 0.0,
+                    amount: Money::ZERO,
                     transfer_type: TransferType::ZeroSumChange,
                     participant: 0,
                     category_id: 0,
@@ -164,12 +170,12 @@ impl BankAccount {
                 },
             }]
         };
-        entries.append(&mut self.get_ledger_within_dates(start, end));
-        if !(entries.len() == 1) {
+        entries.append(&mut self.get_ledger_within_dates(start, end)?);
+        Ok(if !(entries.len() == 1) {
             entries.reverse();
             let last = entries.pop().unwrap();
 
-            let mut aggregate: f64 = last.info.amount as f64;
+            let mut aggregate: f64 = last.info.amount.to_f64();
             let starting_date = NaiveDate::parse_from_str(&last.info.date, "%Y-%m-%d").unwrap();
             let mut min_total = aggregate;
             let mut max_total = aggregate;
@@ -189,11 +195,11 @@ impl BankAccount {
                     aggregate = match record.info.transfer_type {
                         TransferType::DepositFromExternalAccount
                         | TransferType::DepositFromInternalAccount => {
-                            aggregate + record.info.amount as f64
+                            aggregate + record.info.amount.to_f64()
                         }
                         TransferType::WithdrawalToExternalAccount
                         | TransferType::WithdrawalToInternalAccount => {
-                            aggregate - record.info.amount as f64
+                            aggregate - record.info.amount.to_f64()
                         }
                         TransferType::ZeroSumChange => aggregate,
                     };
@@ -231,12 +237,12 @@ impl BankAccount {
             })
         } else {
             None
-        }
+        })
     }
 }
 
 impl AccountCreation for BankAccount {
-    fn create(uid: u32, name: String, _db: &DbConn) -> AccountRecord {
+    fn create(uid: u32, name: String, _db: &DbConn) -> Result<AccountRecord> {
         let has_bank = true;
         let has_stocks = false;
         let has_ledger = false;
@@ -251,8 +257,8 @@ impl AccountCreation for BankAccount {
             has_budget: has_budget,
         };
 
-        let aid = _db.add_account(uid, &account).unwrap();
-        let acct = Self::new(uid, aid, _db);
+        let aid = _db.add_account(uid, &account)?;
+        let acct = Self::new(uid, aid, _db)?;
 
         let initialize_account =
             Confirm::new("Would you like to open the account with an initial deposit?")
@@ -260,7 +266,7 @@ impl AccountCreation for BankAccount {
                 .unwrap();
 
         if initialize_account {
-            acct.fixed.deposit(None, false);
+            acct.fixed.deposit(None, false)?;
         }
 
         let add_budget = Confirm::new("Would you like to associate a budget to this account?")
@@ -269,19 +275,16 @@ impl AccountCreation for BankAccount {
             .unwrap();
         if add_budget {
             let budget = Budget::new(uid, aid, _db);
-            budget.create_budget();
+            budget.create_budget()?;
             acct.set_budget();
         }
 
-        return AccountRecord {
-            id: aid,
-            info: account,
-        };
+        return Ok(AccountRecord::owned(uid, aid, account));
     }
 }
 
 impl AccountOperations for BankAccount {
-    fn record(&mut self) {
+    fn record(&mut self) -> Result<()> {
         const RECORD_OPTIONS: [&'static str; 4] = ["Deposit", "Withdrawal", "Budget", "None"];
         loop {
             let action = Select::new(
@@ -293,10 +296,10 @@ impl AccountOperations for BankAccount {
             .to_string();
             match action.as_str() {
                 "Deposit" => {
-                    self.fixed.deposit(None, false);
+                    self.fixed.deposit(None, false)?;
                 }
                 "Withdrawal" => {
-                    self.fixed.withdrawal(None, false);
+                    self.fixed.withdrawal(None, false)?;
                 }
                 "Budget" => {
                     if self.budget.is_none() {
@@ -311,11 +314,11 @@ impl AccountOperations for BankAccount {
                         self.budget = Some(budget);
                     }
                     if let Some(budget) = &self.budget {
-                        budget.record();
+                        budget.record()?;
                     }
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!");
@@ -325,12 +328,17 @@ impl AccountOperations for BankAccount {
                 .prompt()
                 .unwrap();
             if !record_again {
-                return;
+                return Ok(());
             }
         }
     }
 
-    fn import(&mut self) {
+    fn import(&mut self) -> Result<()> {
+        let format = match select_import_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
+
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -351,10 +359,13 @@ impl AccountOperations for BankAccount {
         let mut csv: String = String::new();
         loop {
             csv = rl
-                .readline("Enter path to CSV file (or none to quit): ")
+                .readline(&format!(
+                    "Enter path to {} file (or none to quit): ",
+                    format
+                ))
                 .unwrap();
             if csv.to_string() == "none" {
-                return;
+                return Ok(());
             }
             bad_path = match Path::new(&csv).try_exists() {
                 Ok(true) => false,
@@ -372,26 +383,28 @@ impl AccountOperations for BankAccount {
             } else {
                 let try_again = Confirm::new("Continue import?").prompt().unwrap();
                 if !try_again {
-                    return;
+                    return Ok(());
                 }
             }
         }
         fp = Path::new(&csv);
 
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(fp)
-            .unwrap();
-
-        let mut ledger_entries = Vec::new();
-        for result in rdr.deserialize::<LedgerEntry>() {
-            ledger_entries.push(result.unwrap());
+        let batch = read_import_file(&self.db, self.uid, self.id, format, fp)?;
+        if !confirm_import(&self.db, self.uid, self.id, &batch)? {
+            return Ok(());
         }
-        ledger_entries.sort_by(|x, y| {
-            (NaiveDate::parse_from_str(&x.date, "%Y-%m-%d").unwrap())
-                .cmp(&NaiveDate::parse_from_str(&y.date, "%Y-%m-%d").unwrap())
-        });
-        for rcrd in ledger_entries {
+
+        // write every entry or, should one fail, none of them
+        let transaction = self.db.begin_transaction()?;
+        apply_merges(&self.db, self.uid, self.id, &batch.merges)?;
+        save_import_settings(&self.db, self.uid, self.id, &batch)?;
+        let ledger_entries = batch.entries;
+        for ImportedEntry {
+            entry: rcrd,
+            external_id,
+            ..
+        } in ledger_entries
+        {
             let ptype = if rcrd.transfer_type == TransferType::WithdrawalToExternalAccount {
                 ParticipantType::Payee
             } else if rcrd.transfer_type == TransferType::WithdrawalToInternalAccount {
@@ -402,8 +415,7 @@ impl AccountOperations for BankAccount {
                 ParticipantType::Payer
             };
             let entry: LedgerInfo = LedgerInfo {
-                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")
-                    .unwrap()
+                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")?
                     .format("%Y-%m-%d")
                     .to_string(),
                 amount: rcrd.amount,
@@ -414,19 +426,25 @@ impl AccountOperations for BankAccount {
                     rcrd.participant,
                     ptype,
                     false,
-                ),
+                )?,
                 category_id: self.db.check_and_add_category(
                     self.uid,
                     self.id,
                     rcrd.category.to_ascii_uppercase(),
-                ),
+                )?,
                 description: rcrd.description,
             };
-            let _lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry).unwrap();
+            let lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry)?;
+            if let Some(external_id) = external_id {
+                self.db
+                    .add_external_transaction_id(self.uid, self.id, lid, &external_id)?;
+            }
         }
+        transaction.commit()?;
+        Ok(())
     }
 
-    fn modify(&mut self) {
+    fn modify(&mut self) -> Result<()> {
         const MODIFY_OPTIONS: [&'static str; 4] = ["Ledger", "Categories", "People", "None"];
         const MODIFY_OPTIONS_WITH_BUDGET: [&'static str; 5] =
             ["Ledger", "Categories", "People", "Budget", "None"];
@@ -446,7 +464,7 @@ impl AccountOperations for BankAccount {
                         break;
                     }
                     let selected_record = record_or_none.unwrap();
-                    self.fixed.modify(selected_record);
+                    self.fixed.modify(selected_record)?;
                     let go_again = Confirm::new("Modify additional records? (y/n)")
                         .prompt()
                         .unwrap();
@@ -456,7 +474,7 @@ impl AccountOperations for BankAccount {
                 },
                 "Categories" => {
                     loop {
-                        let records = self.db.get_categories(self.uid, self.id).unwrap();
+                        let records = self.db.get_categories(self.uid, self.id)?;
                         let mut choices: Vec<String> = records
                             .iter()
                             .map(|x| x.category.name.clone())
@@ -486,18 +504,15 @@ impl AccountOperations for BankAccount {
                                     self.id,
                                     chosen_category,
                                     new_name,
-                                );
+                                )?;
                             }
                             "Remove" => {
                                 // check if category is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_category(
-                                        self.uid,
-                                        self.id,
-                                        chosen_category.clone(),
-                                    )
-                                    .unwrap();
+                                let is_referenced = self.db.check_if_ledger_references_category(
+                                    self.uid,
+                                    self.id,
+                                    chosen_category.clone(),
+                                )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -506,13 +521,11 @@ impl AccountOperations for BankAccount {
                                             "{} | {} | {} | {} ",
                                             record.info.date,
                                             chosen_category.clone(),
-                                            self.db
-                                                .get_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.participant
-                                                )
-                                                .unwrap(),
+                                            self.db.get_participant(
+                                                self.uid,
+                                                self.id,
+                                                record.info.participant
+                                            )?,
                                             record.info.amount
                                         );
                                         print!("\t{}", v);
@@ -528,7 +541,7 @@ impl AccountOperations for BankAccount {
                                         self.uid,
                                         self.id,
                                         chosen_category.clone(),
-                                    );
+                                    )?;
                                 }
                             }
                             "None" => {
@@ -561,8 +574,7 @@ impl AccountOperations for BankAccount {
                                 panic!("Unrecognized input: {}", selected_ptype);
                             }
                         };
-                        let participants =
-                            self.db.get_participants(self.uid, self.id, ptype).unwrap();
+                        let participants = self.db.get_participants(self.uid, self.id, ptype)?;
                         let mut people = participants
                             .iter()
                             .map(|x| x.participant.name.clone())
@@ -592,27 +604,23 @@ impl AccountOperations for BankAccount {
                                     .prompt()
                                     .unwrap()
                                     .to_string();
-                                self.db
-                                    .update_participant_name(
-                                        self.uid,
-                                        self.id,
-                                        ptype,
-                                        chosen_person.clone(),
-                                        new_name,
-                                    )
-                                    .unwrap();
+                                self.db.update_participant_name(
+                                    self.uid,
+                                    self.id,
+                                    ptype,
+                                    chosen_person.clone(),
+                                    new_name,
+                                )?;
                             }
                             "Remove" => {
                                 // check if participant is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_participant(
+                                let is_referenced =
+                                    self.db.check_if_ledger_references_participant(
                                         self.uid,
                                         self.id,
                                         ptype,
                                         chosen_person.clone(),
-                                    )
-                                    .unwrap();
+                                    )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -620,13 +628,11 @@ impl AccountOperations for BankAccount {
                                         let v = format!(
                                             "{} | {} | {} | {} ",
                                             record.info.date,
-                                            self.db
-                                                .get_category_name(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.category_id
-                                                )
-                                                .unwrap(),
+                                            self.db.get_category_name(
+                                                self.uid,
+                                                self.id,
+                                                record.info.category_id
+                                            )?,
                                             chosen_person.clone(),
                                             record.info.amount
                                         );
@@ -640,42 +646,34 @@ impl AccountOperations for BankAccount {
                                 if delete {
                                     match ptype {
                                         ParticipantType::Payee => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         ParticipantType::Payer => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         _ => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                     }
                                 }
@@ -697,7 +695,7 @@ impl AccountOperations for BankAccount {
                 }
                 "Budget" => {
                     if let Some(budget) = &self.budget {
-                        budget.modify();
+                        budget.modify()?;
                     } else {
                         let add_budget = Confirm::new("A budget for this account does not exist, would you like to create one (y/n)?")
                             .with_default(false)
@@ -705,13 +703,13 @@ impl AccountOperations for BankAccount {
                             .unwrap();
                         if add_budget {
                             let budget = Budget::new(self.uid, self.id, &self.db);
-                            budget.create_budget();
+                            budget.create_budget()?;
                             self.budget = Some(budget);
                         }
                     }
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!")
@@ -725,9 +723,14 @@ impl AccountOperations for BankAccount {
                 break;
             }
         }
+        Ok(())
     }
 
-    fn export(&self) {
+    fn export(&self) -> Result<()> {
+        let format = match select_export_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -743,33 +746,38 @@ impl AccountOperations for BankAccount {
         let mut rl = Editor::with_config(config).unwrap();
         rl.set_helper(Some(g));
 
-        let mut wtr =
-            csv::Writer::from_path(rl.readline("Enter path to CSV file: ").unwrap()).unwrap();
-        let ledger = self.get_ledger();
+        let path = rl
+            .readline(&format!("Enter path to {} file: ", format))
+            .unwrap();
+        let mut entries = Vec::new();
+        let ledger = self.get_ledger()?;
         if !ledger.is_empty() {
             for record in ledger {
                 let csv_ledger_record: shared_lib::LedgerEntry = LedgerEntry {
                     date: record.info.date,
                     amount: record.info.amount,
                     transfer_type: record.info.transfer_type,
-                    participant: self
-                        .db
-                        .get_participant(self.uid, self.id, record.info.participant)
-                        .unwrap(),
-                    category: self
-                        .db
-                        .get_category_name(self.uid, self.id, record.info.category_id)
-                        .unwrap(),
+                    participant: self.db.get_participant(
+                        self.uid,
+                        self.id,
+                        record.info.participant,
+                    )?,
+                    category: self.db.get_category_name(
+                        self.uid,
+                        self.id,
+                        record.info.category_id,
+                    )?,
                     description: record.info.description,
                     stock_info: None,
                 };
-                let flattened = FlatLedgerEntry::from(csv_ledger_record);
-                wtr.serialize(flattened).unwrap();
+                entries.push(csv_ledger_record);
             }
         }
+        write_export_file(format, QifAccountType::Bank, Path::new(&path), entries)?;
+        Ok(())
     }
 
-    fn report(&self) {
+    fn report(&self) -> Result<()> {
         const REPORT_OPTIONS: [&'static str; 3] = ["Total Value", "Simple Growth Rate", "None"];
         let choice: String =
             Select::new("What would you like to report: ", REPORT_OPTIONS.to_vec())
@@ -778,7 +786,7 @@ impl AccountOperations for BankAccount {
                 .to_string();
         match choice.as_str() {
             "Total Value" => {
-                let value = self.fixed.get_current_value();
+                let value = self.fixed.get_current_value()?;
                 println!("\tTotal Account Value: {}", value);
             }
             "Simple Growth Rate" => {
@@ -788,15 +796,18 @@ impl AccountOperations for BankAccount {
                 println!("\tRate of return: {}%", rate);
             }
             "None" => {
-                return;
+                return Ok(());
             }
             _ => {
                 panic!("Unrecognized input!");
             }
         }
+        Ok(())
     }
 
-    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Option<u32> {
+    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Result<Option<u32>> {
+        let transaction = self.db.begin_transaction()?;
+
         let from_account;
         let to_account;
 
@@ -812,18 +823,16 @@ impl AccountOperations for BankAccount {
                     self.uid,
                     self.id,
                     "Withdrawal".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payee,
                     true,
-                );
+                )?;
                 (
                     TransferType::WithdrawalToExternalAccount,
                     format!(
@@ -840,18 +849,16 @@ impl AccountOperations for BankAccount {
                     self.uid,
                     self.id,
                     "Deposit".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payer,
                     true,
-                );
+                )?;
                 (
                     TransferType::DepositFromExternalAccount,
                     format!(
@@ -861,7 +868,7 @@ impl AccountOperations for BankAccount {
                 )
             }
             _ => {
-                return None;
+                return Ok(None);
             }
         };
 
@@ -876,16 +883,12 @@ impl AccountOperations for BankAccount {
 
         let (from_ledger_id, to_ledger_id) = match new_ttype {
             TransferType::WithdrawalToExternalAccount => (
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
                 entry.id,
             ),
             TransferType::DepositFromExternalAccount => (
                 entry.id,
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
             ),
             _ => {
                 panic!("Unrecognized input!")
@@ -899,11 +902,12 @@ impl AccountOperations for BankAccount {
             to_ledger: to_ledger_id,
         };
 
-        return Some(
-            self.db
-                .add_account_transaction(self.uid, transaction_record)
-                .unwrap(),
-        );
+        let id = self
+            .db
+            .add_account_transaction(self.uid, transaction_record)?;
+        transaction.commit()?;
+
+        return Ok(Some(id));
     }
 }
 
@@ -1027,26 +1031,32 @@ impl AccountData for BankAccount {
     fn get_id(&self) -> u32 {
         return self.id;
     }
-    fn get_name(&self) -> String {
-        return self.db.get_account_name(self.uid, self.id).unwrap();
+    fn get_uid(&self) -> u32 {
+        return self.uid;
     }
-    fn get_ledger(&self) -> Vec<LedgerRecord> {
-        return self.db.get_ledger(self.uid, self.id).unwrap();
+    fn get_name(&self) -> Result<String> {
+        Ok(self.db.get_account_name(self.uid, self.id)?)
     }
-    fn get_ledger_within_dates(&self, start: NaiveDate, end: NaiveDate) -> Vec<LedgerRecord> {
-        return self
+    fn get_ledger(&self) -> Result<Vec<LedgerRecord>> {
+        Ok(self.db.get_ledger(self.uid, self.id)?)
+    }
+    fn get_ledger_within_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<LedgerRecord>> {
+        Ok(self
             .db
-            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)
-            .unwrap();
+            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)?)
     }
-    fn get_displayable_ledger(&self) -> Vec<DisplayableLedgerRecord> {
-        return self.db.get_displayable_ledger(self.uid, self.id).unwrap();
+    fn get_displayable_ledger(&self) -> Result<Vec<DisplayableLedgerRecord>> {
+        Ok(self.db.get_displayable_ledger(self.uid, self.id)?)
     }
-    fn get_value(&self) -> f32 {
-        return self.fixed.get_current_value();
+    fn get_value(&self) -> Result<Money> {
+        self.fixed.get_current_value()
     }
-    fn get_value_on_day(&self, day: NaiveDate) -> f32 {
-        return self.fixed.get_value_on_day(day);
+    fn get_value_on_day(&self, day: NaiveDate) -> Result<Money> {
+        self.fixed.get_value_on_day(day)
     }
     fn get_open_date(&self) -> NaiveDate {
         return self.open_date;
@@ -1054,13 +1064,13 @@ impl AccountData for BankAccount {
 }
 
 impl LiquidAccount for BankAccount {
-    fn get_positive_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32 {
-        let ledger = self.get_ledger_within_dates(start, end);
+    fn get_positive_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money> {
+        let ledger = self.get_ledger_within_dates(start, end)?;
         if ledger.is_empty() {
-            return 0.0;
+            return Ok(Money::ZERO);
         }
 
-        let mut amt = 0.0;
+        let mut amt = Money::ZERO;
         for txn in ledger {
             if !txn.info.transfer_type.is_deposit() {
                 continue;
@@ -1070,14 +1080,10 @@ impl LiquidAccount for BankAccount {
                 .db
                 .check_and_get_account_transaction_record_matching_to_ledger_id(
                     self.uid, self.id, txn.id,
-                )
-                .unwrap()
+                )?
             {
                 // if linked, checked to see that the account is not another liquid account (if liquid, then skip because cash is still available)
-                let peer_account = self
-                    .db
-                    .get_account(self.uid, link.info.from_account)
-                    .unwrap();
+                let peer_account = self.db.get_account(self.uid, link.info.from_account)?;
                 if peer_account.is_liquid_account() {
                     continue;
                 }
@@ -1085,16 +1091,16 @@ impl LiquidAccount for BankAccount {
             amt = amt + txn.info.amount;
         }
 
-        amt
+        Ok(amt)
     }
 
-    fn get_negative_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32 {
-        let ledger = self.get_ledger_within_dates(start, end);
+    fn get_negative_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money> {
+        let ledger = self.get_ledger_within_dates(start, end)?;
         if ledger.is_empty() {
-            return 0.0;
+            return Ok(Money::ZERO);
         }
 
-        let mut amt = 0.0;
+        let mut amt = Money::ZERO;
         for txn in ledger {
             if !txn.info.transfer_type.is_withdrawal() {
                 continue;
@@ -1104,14 +1110,10 @@ impl LiquidAccount for BankAccount {
                 .db
                 .check_and_get_account_transaction_record_matching_to_ledger_id(
                     self.uid, self.id, txn.id,
-                )
-                .unwrap()
+                )?
             {
                 // if linked, checked to see that the account is not another liquid account (if liquid, then skip because cash is still available)
-                let peer_account = self
-                    .db
-                    .get_account(self.uid, link.info.from_account)
-                    .unwrap();
+                let peer_account = self.db.get_account(self.uid, link.info.from_account)?;
                 if peer_account.is_liquid_account() {
                     continue;
                 }
@@ -1119,22 +1121,22 @@ impl LiquidAccount for BankAccount {
             amt = amt + txn.info.amount;
         }
 
-        amt
+        Ok(amt)
     }
 
-    fn get_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32 {
-        return self.get_positive_cash_flow(start, end) - self.get_negative_cash_flow(start, end);
+    fn get_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money> {
+        Ok(self.get_positive_cash_flow(start, end)? - self.get_negative_cash_flow(start, end)?)
     }
 }
 
 #[cfg(feature = "ratatui_support")]
 impl AccountUI for BankAccount {
-    fn populate_page_cache_f32(&self, app: &mut App) {
+    fn populate_page_cache_f32(&self, app: &mut App) -> Result<()> {
         let mut kv: HashMap<String, DisplayValue> = HashMap::new();
 
         kv.insert(
             KEY_TOTAL_VALUE.into(),
-            DisplayValue::Float(self.get_value()),
+            DisplayValue::Money(self.get_value()?),
         );
         kv.insert(
             KEY_GROWTH.into(),
@@ -1146,9 +1148,10 @@ impl AccountUI for BankAccount {
 
         app.page_cache_f32 = Some(kv);
 
-        app.ledger_entries = Some(self.get_displayable_ledger());
-        app.linechart_cache = self.get_linechart(app);
+        app.ledger_entries = Some(self.get_displayable_ledger()?);
+        app.linechart_cache = self.get_linechart(app)?;
         app.barchart_cache = None;
+        Ok(())
     }
     fn render(&self, frame: &mut Frame, area: Rect, app: &mut App) {
         let chunk = Layout::default()
@@ -21,10 +21,12 @@ use std::{rc, string};
 use crate::accounts::base::AnalysisPeriod;
 use crate::database::budget::{BudgetItem, BudgetRecord};
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::types::categories::CategoryAutoCompleter;
 use chrono::{Duration, NaiveDate};
 use inquire::autocompletion::Replacement;
 use inquire::*;
+use shared_lib::{Money, Quantity};
 
 pub struct Budget {
     uid: u32,
@@ -42,10 +44,10 @@ impl Budget {
         budget
     }
 
-    pub fn create_budget(&self) {
+    pub fn create_budget(&self) -> Result<()> {
         loop {
             let item = self.prompt_new_budget_item();
-            self.db.add_budget_item(self.uid, self.aid, item);
+            self.db.add_budget_item(self.uid, self.aid, item)?;
             let another = Confirm::new("Add another budget category?")
                 .with_default(false)
                 .prompt()
@@ -54,6 +56,7 @@ impl Budget {
                 break;
             }
         }
+        Ok(())
     }
 
     pub fn prompt_new_budget_item(&self) -> BudgetItem {
@@ -75,9 +78,9 @@ impl Budget {
         };
     }
 
-    pub fn set_budget_value(&self, category: String) -> f32 {
+    pub fn set_budget_value(&self, category: String) -> Money {
         let prompt = format!("Enter budgeted amount [{}]", category);
-        let value = CustomType::<f32>::new(prompt.as_str())
+        let value = CustomType::<Money>::new(prompt.as_str())
             .with_placeholder("00000.00")
             .with_error_message("Please type a valid amount!")
             .prompt()
@@ -136,14 +139,14 @@ impl Budget {
         }
     }
 
-    pub fn record(&self) {
+    pub fn record(&self) -> Result<()> {
         const OPTIONS: [&'static str; 3] = ["Full Budget", "Budget Item", "None"];
         let record_choice = Select::new("What would you like to do:", OPTIONS.to_vec())
             .prompt()
             .unwrap();
         match record_choice {
             "Full Budget" => {
-                let budget_opt = self.db.get_budget(self.uid, self.aid).unwrap();
+                let budget_opt = self.db.get_budget(self.uid, self.aid)?;
                 if let Some(budget) = budget_opt {
                     if !budget.is_empty() {
                         let go_ahead = Confirm::new("This action will delete current budget data. Do you want to continue (y/n)?")
@@ -152,15 +155,15 @@ impl Budget {
                             .unwrap();
 
                         if !go_ahead {
-                            return;
+                            return Ok(());
                         }
 
                         for item in budget {
-                            self.db.remove_budget_item(self.uid, self.aid, item.id);
+                            self.db.remove_budget_item(self.uid, self.aid, item.id)?;
                         }
                     }
                 }
-                self.create_budget();
+                self.create_budget()?;
             }
             "Budget Item" => {
                 let new = self.prompt_new_budget_item();
@@ -171,9 +174,10 @@ impl Budget {
                 panic!("Unrecognized input: '{}'!", record_choice);
             }
         }
+        Ok(())
     }
 
-    pub fn modify(&self) {
+    pub fn modify(&self) -> Result<()> {
         const OPTIONS: [&'static str; 3] = ["Update", "Remove", "None"];
         loop {
             let record_opt = self.select_budget_element();
@@ -184,15 +188,11 @@ impl Budget {
 
                 match modify_choice {
                     "Update" => {
-                        let updated_value = self.set_budget_value(
-                            self.db
-                                .get_category_name(
-                                    self.uid,
-                                    self.aid,
-                                    selected_record.item.category_id,
-                                )
-                                .unwrap(),
-                        );
+                        let updated_value = self.set_budget_value(self.db.get_category_name(
+                            self.uid,
+                            self.aid,
+                            selected_record.item.category_id,
+                        )?);
                         let updated_record = BudgetRecord {
                             id: selected_record.id,
                             item: BudgetItem {
@@ -201,11 +201,11 @@ impl Budget {
                             },
                         };
                         self.db
-                            .update_budget_item(self.uid, self.aid, updated_record);
+                            .update_budget_item(self.uid, self.aid, updated_record)?;
                     }
                     "Remove" => {
                         self.db
-                            .remove_budget_item(self.uid, self.aid, selected_record.id);
+                            .remove_budget_item(self.uid, self.aid, selected_record.id)?;
                     }
                     "None" => {}
                     _ => {
@@ -221,9 +221,10 @@ impl Budget {
                     break;
                 }
             } else {
-                return;
+                return Ok(());
             }
         }
+        Ok(())
     }
 
     pub fn get_budget(&self) -> Vec<BudgetRecord> {
@@ -245,7 +246,11 @@ impl Budget {
     }
 }
 
-pub fn scale_budget_value_to_analysis_period(value: f32, start: NaiveDate, end: NaiveDate) -> f32 {
-    let diff = (end - start).num_days() as f32;
-    return value * diff / 31.;
+pub fn scale_budget_value_to_analysis_period(
+    value: Money,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Money {
+    let diff = (end - start).num_days() as f64;
+    return value * Quantity::from_f64(diff / 31.);
 }
@@ -15,17 +15,18 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::tui::{decode_and_init_account_type, prompt_and_create_new_account};
 use crate::types::accounts::AccountRecord;
 use crate::types::categories::CategoryAutoCompleter;
-use crate::types::labels::LabelAutoCompleter;
+use crate::types::labels::LabelSelection;
 use crate::types::ledger::{LedgerInfo, LedgerRecord};
 use crate::types::participants::{ParticipantAutoCompleter, ParticipantType};
 use chrono::{Datelike, NaiveDate};
 use core::panic;
 use inquire::validator::MinLengthValidator;
 use inquire::*;
-use shared_lib::{LedgerEntry, TransferType};
+use shared_lib::{LedgerEntry, Money, TransferType};
 use std::collections::HashMap;
 use std::hash::Hash;
 
@@ -47,13 +48,13 @@ impl ChargeAccount {
         acct
     }
 
-    pub fn charge(&self, initial_opt: Option<LedgerRecord>, overwrite: bool) {
+    pub fn charge(&self, initial_opt: Option<LedgerRecord>, overwrite: bool) -> Result<()> {
         let default_to_use: bool;
         let mut initial = LedgerRecord {
             id: 0,
             info: LedgerInfo {
                 date: "1970-01-01".to_string(),
-                amount: 0.0,
+                amount: Money::ZERO,
                 transfer_type: TransferType::WithdrawalToExternalAccount,
                 participant: 0,
                 category_id: 0,
@@ -71,7 +72,7 @@ impl ChargeAccount {
         let date_prompt = "Enter date of charge:";
         let date_input = if default_to_use {
             DateSelect::new(date_prompt)
-                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d").unwrap())
+                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d")?)
                 .prompt()
                 .unwrap()
                 .format("%Y-%m-%d")
@@ -85,23 +86,22 @@ impl ChargeAccount {
         };
 
         let amount_prompt = "Enter amount charged:";
-        let amount_input: f32 = if default_to_use {
-            CustomType::<f32>::new(amount_prompt)
+        let amount_input: Money = if default_to_use {
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
                 .with_default(initial.info.amount)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(amount_prompt)
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
-                .with_default(00000.00)
+                .with_default(Money::ZERO)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
-        let cid;
         let category_prompt = "Enter category:";
         let selected_category = if default_to_use {
             Text::new(category_prompt)
@@ -113,8 +113,7 @@ impl ChargeAccount {
                 })
                 .with_default(
                     self.db
-                        .get_category_name(self.uid, self.id, initial.info.category_id)
-                        .unwrap()
+                        .get_category_name(self.uid, self.id, initial.info.category_id)?
                         .as_str(),
                 )
                 .prompt()
@@ -133,10 +132,6 @@ impl ChargeAccount {
                 .to_ascii_uppercase()
         };
 
-        cid = self
-            .db
-            .check_and_add_category(self.uid, self.id, selected_category);
-
         let description_prompt = "Enter description:";
         let description_input = if default_to_use {
             Text::new(description_prompt)
@@ -148,12 +143,8 @@ impl ChargeAccount {
             Text::new(description_prompt).prompt().unwrap().to_string()
         };
 
-        let selected_payee;
-        let mut acct: Box<dyn Account>;
-        let pid;
         let payee_prompt = "Enter payee:";
-
-        selected_payee = if default_to_use {
+        let selected_payee = if default_to_use {
             Text::new(payee_prompt)
                 .with_autocomplete(ParticipantAutoCompleter {
                     uid: self.uid,
@@ -166,8 +157,7 @@ impl ChargeAccount {
                 })
                 .with_default(
                     self.db
-                        .get_participant(self.uid, self.id, initial.info.participant)
-                        .unwrap()
+                        .get_participant(self.uid, self.id, initial.info.participant)?
                         .as_str(),
                 )
                 .prompt()
@@ -186,13 +176,24 @@ impl ChargeAccount {
                 .prompt()
                 .unwrap()
         };
-        pid = self.db.check_and_add_participant(
+        let labels = LabelSelection::prompt(self.uid, &self.db, "charge", overwrite);
+
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        if default_to_use && overwrite {
+            self.remove_linked_entry(&initial)?;
+        }
+        let cid = self
+            .db
+            .check_and_add_category(self.uid, self.id, selected_category)?;
+        let pid = self.db.check_and_add_participant(
             self.uid,
             self.id,
             selected_payee,
             ParticipantType::Payee,
             false,
-        );
+        )?;
 
         let withdrawal = LedgerInfo {
             date: date_input,
@@ -204,76 +205,31 @@ impl ChargeAccount {
         };
 
         let id = if default_to_use && overwrite {
-            self.db
-                .update_ledger_item(
-                    self.uid,
-                    self.id,
-                    LedgerRecord {
-                        id: initial.id,
-                        info: withdrawal.clone(),
-                    },
-                )
-                .unwrap()
+            self.db.update_ledger_item(
+                self.uid,
+                self.id,
+                LedgerRecord {
+                    id: initial.id,
+                    info: withdrawal.clone(),
+                },
+            )?
         } else {
             self.db
-                .add_ledger_entry(self.uid, self.id, withdrawal.clone())
-                .unwrap()
+                .add_ledger_entry(self.uid, self.id, withdrawal.clone())?
         };
+        labels.apply(self.uid, self.id, &self.db, id)?;
 
-        if overwrite {
-            let maintain_labels =
-                Confirm::new("Would you like to maintain all prior labels (y/n)?")
-                    .prompt()
-                    .unwrap();
-            if !maintain_labels {
-                let mapped_labels = self
-                    .db
-                    .check_and_get_label_mapping_matching_ledger_id(self.uid, self.id, id)
-                    .unwrap();
-                if !mapped_labels.is_empty() {
-                    for label in mapped_labels {
-                        self.db
-                            .remove_label_mapping(self.uid, self.id, label.id)
-                            .unwrap();
-                    }
-                }
-            }
-        }
-
-        // add labels for transaction
-        let add_label_prompt = Confirm::new("Add labels to charge (y/n)?")
-            .prompt()
-            .unwrap();
-        if add_label_prompt == true {
-            loop {
-                let label = Text::new("Enter label:")
-                    .with_autocomplete(LabelAutoCompleter {
-                        uid: self.uid,
-                        db: self.db.clone(),
-                    })
-                    .prompt()
-                    .unwrap()
-                    .to_ascii_uppercase();
-                let label_id = self.db.check_and_add_label(self.uid, label).unwrap();
-                self.db
-                    .add_label_mapping(self.uid, self.id, label_id, id)
-                    .unwrap();
-
-                let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
-                if !continue_prompt {
-                    break;
-                }
-            }
-        }
+        transaction.commit()?;
+        Ok(())
     }
 
-    pub fn pay(&self, initial_opt: Option<LedgerRecord>, overwrite: bool) {
+    pub fn pay(&self, initial_opt: Option<LedgerRecord>, overwrite: bool) -> Result<()> {
         let default_to_use: bool;
         let mut initial = LedgerRecord {
             id: 0,
             info: LedgerInfo {
                 date: "1970-01-01".to_string(),
-                amount: 0.0,
+                amount: Money::ZERO,
                 transfer_type: TransferType::DepositFromExternalAccount,
                 participant: 0,
                 category_id: 0,
@@ -291,7 +247,7 @@ impl ChargeAccount {
         let date_prompt = "Enter date of payment:";
         let date_input = if default_to_use {
             DateSelect::new(date_prompt)
-                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d").unwrap())
+                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d")?)
                 .prompt()
                 .unwrap()
                 .format("%Y-%m-%d")
@@ -305,23 +261,22 @@ impl ChargeAccount {
         };
 
         let amount_prompt = "Enter amount paid:";
-        let amount_input: f32 = if default_to_use {
-            CustomType::<f32>::new(amount_prompt)
+        let amount_input: Money = if default_to_use {
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
                 .with_default(initial.info.amount)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(amount_prompt)
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
-                .with_default(00000.00)
+                .with_default(Money::ZERO)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
-        let cid;
         let category_validator =
             MinLengthValidator::new(3).with_message("Category cannot be empty!");
         let category_prompt = "Enter category:";
@@ -335,8 +290,7 @@ impl ChargeAccount {
                 })
                 .with_default(
                     self.db
-                        .get_category_name(self.uid, self.id, initial.info.category_id)
-                        .unwrap()
+                        .get_category_name(self.uid, self.id, initial.info.category_id)?
                         .as_str(),
                 )
                 .with_validator(category_validator)
@@ -361,10 +315,6 @@ impl ChargeAccount {
                 .to_string()
         };
 
-        cid = self
-            .db
-            .check_and_add_category(self.uid, self.id, selected_category);
-
         let description_prompt = "Enter description:";
         let description_input = if default_to_use {
             Text::new(description_prompt)
@@ -388,8 +338,7 @@ impl ChargeAccount {
             .unwrap();
 
         let selected_payer;
-        let mut acct: Box<dyn Account>;
-        let pid;
+        let mut linked_acct: Option<Box<dyn Account>> = None;
         let participant_validator =
             MinLengthValidator::new(1).with_message("Payer cannot be empty!");
         if !link {
@@ -406,8 +355,7 @@ impl ChargeAccount {
                     })
                     .with_default(
                         self.db
-                            .get_participant(self.uid, self.id, initial.info.participant)
-                            .unwrap()
+                            .get_participant(self.uid, self.id, initial.info.participant)?
                             .as_str(),
                     )
                     .with_validator(participant_validator)
@@ -432,190 +380,92 @@ impl ChargeAccount {
                     .trim()
                     .to_string()
             };
-            pid = self.db.check_and_add_participant(
-                self.uid,
-                self.id,
-                selected_payer,
-                ParticipantType::Payer,
-                false,
-            );
-
-            let deposit = LedgerInfo {
-                date: date_input,
-                amount: amount_input,
-                transfer_type: TransferType::DepositFromExternalAccount,
-                participant: pid,
-                category_id: cid,
-                description: description_input,
-            };
-
-            let id = if default_to_use && overwrite {
-                self.db
-                    .update_ledger_item(
-                        self.uid,
-                        self.id,
-                        LedgerRecord {
-                            id: initial.id,
-                            info: deposit.clone(),
-                        },
-                    )
-                    .unwrap()
-            } else {
-                self.db
-                    .add_ledger_entry(self.uid, self.id, deposit.clone())
-                    .unwrap()
-            };
-
-            if overwrite {
-                let maintain_labels =
-                    Confirm::new("Would you like to maintain all prior labels (y/n)?")
-                        .prompt()
-                        .unwrap();
-                if !maintain_labels {
-                    let mapped_labels = self
-                        .db
-                        .check_and_get_label_mapping_matching_ledger_id(self.uid, self.id, id)
-                        .unwrap();
-                    if !mapped_labels.is_empty() {
-                        for label in mapped_labels {
-                            self.db
-                                .remove_label_mapping(self.uid, self.id, label.id)
-                                .unwrap();
-                        }
-                    }
-                }
-            }
-
-            // add labels for transaction
-            let add_label_prompt = Confirm::new("Add labels to payment (y/n)?")
-                .prompt()
-                .unwrap();
-            if add_label_prompt == true {
-                loop {
-                    let label = Text::new("Enter label:")
-                        .with_autocomplete(LabelAutoCompleter {
-                            uid: self.uid,
-                            db: self.db.clone(),
-                        })
-                        .prompt()
-                        .unwrap()
-                        .to_ascii_uppercase();
-                    let label_id = self.db.check_and_add_label(self.uid, label).unwrap();
-                    self.db
-                        .add_label_mapping(self.uid, self.id, label_id, id)
-                        .unwrap();
-
-                    let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
-                    if !continue_prompt {
-                        break;
-                    }
-                }
-            }
         } else {
             let initial_account_opt = if default_to_use {
-                self.db
-                    .get_participant(self.uid, self.id, initial.info.participant)
+                Some(
+                    self.db
+                        .get_participant(self.uid, self.id, initial.info.participant)?,
+                )
             } else {
                 None
             };
 
-            let user_input = self.link_transaction(initial_account_opt);
+            let user_input = self.link_transaction(initial_account_opt)?;
             if user_input.is_none() {
-                return;
+                return Ok(());
             }
-            (acct, selected_payer) = user_input.unwrap();
-            pid = self.db.check_and_add_participant(
+            let (acct, account_name) = user_input.unwrap();
+            linked_acct = Some(acct);
+            selected_payer = account_name;
+        }
+        let labels = LabelSelection::prompt(self.uid, &self.db, "payment", overwrite);
+
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        if default_to_use && overwrite {
+            self.remove_linked_entry(&initial)?;
+        }
+        let cid = self
+            .db
+            .check_and_add_category(self.uid, self.id, selected_category)?;
+        let pid = if link {
+            self.db.check_and_add_participant(
                 self.uid,
                 self.id,
                 selected_payer,
                 ParticipantType::Both,
                 true,
-            );
-
-            let deposit = LedgerInfo {
-                date: date_input,
-                amount: amount_input,
-                transfer_type: TransferType::DepositFromExternalAccount,
-                participant: pid,
-                category_id: cid,
-                description: description_input,
-            };
-
-            let id = if default_to_use && overwrite {
-                self.db
-                    .update_ledger_item(
-                        self.uid,
-                        self.id,
-                        LedgerRecord {
-                            id: initial.id,
-                            info: deposit.clone(),
-                        },
-                    )
-                    .unwrap()
-            } else {
-                self.db
-                    .add_ledger_entry(self.uid, self.id, deposit.clone())
-                    .unwrap()
-            };
-
-            let entry = LedgerRecord {
-                id: id,
-                info: deposit,
-            };
-
-            if overwrite {
-                let maintain_labels =
-                    Confirm::new("Would you like to maintain all prior labels (y/n)?")
-                        .prompt()
-                        .unwrap();
-                if !maintain_labels {
-                    let mapped_labels = self
-                        .db
-                        .check_and_get_label_mapping_matching_ledger_id(self.uid, self.id, id)
-                        .unwrap();
-                    if !mapped_labels.is_empty() {
-                        for label in mapped_labels {
-                            self.db
-                                .remove_label_mapping(self.uid, self.id, label.id)
-                                .unwrap();
-                        }
-                    }
-                }
-            }
+            )?
+        } else {
+            self.db.check_and_add_participant(
+                self.uid,
+                self.id,
+                selected_payer,
+                ParticipantType::Payer,
+                false,
+            )?
+        };
 
-            // add labels for transaction
-            let add_label_prompt = Confirm::new("Add labels to payment (y/n)?")
-                .prompt()
-                .unwrap();
-            if add_label_prompt == true {
-                loop {
-                    let label = Text::new("Enter label:")
-                        .with_autocomplete(LabelAutoCompleter {
-                            uid: self.uid,
-                            db: self.db.clone(),
-                        })
-                        .prompt()
-                        .unwrap()
-                        .to_ascii_uppercase();
-                    let label_id = self.db.check_and_add_label(self.uid, label).unwrap();
-                    self.db
-                        .add_label_mapping(self.uid, self.id, label_id, id)
-                        .unwrap();
+        let deposit = LedgerInfo {
+            date: date_input,
+            amount: amount_input,
+            transfer_type: TransferType::DepositFromExternalAccount,
+            participant: pid,
+            category_id: cid,
+            description: description_input,
+        };
 
-                    let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
-                    if !continue_prompt {
-                        break;
-                    }
-                }
-            }
+        let id = if default_to_use && overwrite {
+            self.db.update_ledger_item(
+                self.uid,
+                self.id,
+                LedgerRecord {
+                    id: initial.id,
+                    info: deposit.clone(),
+                },
+            )?
+        } else {
+            self.db
+                .add_ledger_entry(self.uid, self.id, deposit.clone())?
+        };
+        labels.apply(self.uid, self.id, &self.db, id)?;
 
-            if link {
-                acct.link(self.id, entry);
-            }
+        if let Some(mut acct) = linked_acct {
+            acct.link(
+                self.id,
+                LedgerRecord {
+                    id: id,
+                    info: deposit,
+                },
+            )?;
         }
+
+        transaction.commit()?;
+        Ok(())
     }
 
-    pub fn modify(&self, selected_record: LedgerRecord) -> LedgerRecord {
+    pub fn modify(&self, selected_record: LedgerRecord) -> Result<LedgerRecord> {
         let was_payment = match selected_record.info.transfer_type.clone() {
             TransferType::DepositFromExternalAccount | TransferType::DepositFromInternalAccount => {
                 true
@@ -624,7 +474,7 @@ impl ChargeAccount {
             | TransferType::WithdrawalToExternalAccount => false,
             TransferType::ZeroSumChange => {
                 println!("Unable to modify a zero-sum change!");
-                return selected_record;
+                return Ok(selected_record);
             }
         };
 
@@ -634,55 +484,16 @@ impl ChargeAccount {
             .unwrap();
         match modify_choice {
             "Update" => {
-                let account_transaction_opt: Option<
-                    crate::types::accounts::AccountTransactionRecord,
-                >;
+                // the entry and what it is linked to are replaced once its
+                // new values are entered
                 if was_payment {
-                    account_transaction_opt = self
-                        .db
-                        .check_and_get_account_transaction_record_matching_to_ledger_id(
-                            self.uid,
-                            self.id,
-                            selected_record.id,
-                        )
-                        .unwrap();
-                    if account_transaction_opt.is_some() {
-                        let account_transaction = account_transaction_opt.unwrap();
-                        self.db
-                            .remove_account_transaction(self.uid, account_transaction.id)
-                            .unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.from_account,
-                                account_transaction.info.from_ledger,
-                            )
-                            .unwrap();
-                    }
-                    self.pay(Some(selected_record.clone()), true);
+                    self.pay(Some(selected_record.clone()), true)?;
                 } else {
-                    account_transaction_opt = self
-                        .db
-                        .check_and_get_account_transaction_record_matching_from_ledger_id(
-                            self.uid,
-                            self.id,
-                            selected_record.id,
-                        )
-                        .unwrap();
-                    if account_transaction_opt.is_some() {
-                        let account_transaction = account_transaction_opt.unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.to_account,
-                                account_transaction.info.to_ledger,
-                            )
-                            .unwrap();
-                    }
-                    self.charge(Some(selected_record.clone()), true);
+                    self.charge(Some(selected_record.clone()), true)?;
                 }
             }
             "Remove" => {
+                let transaction = self.db.begin_transaction()?;
                 let account_transaction_opt: Option<
                     crate::types::accounts::AccountTransactionRecord,
                 >;
@@ -693,20 +504,16 @@ impl ChargeAccount {
                             self.uid,
                             self.id,
                             selected_record.id,
-                        )
-                        .unwrap();
+                        )?;
                     if account_transaction_opt.is_some() {
                         let account_transaction = account_transaction_opt.unwrap();
                         self.db
-                            .remove_account_transaction(self.uid, account_transaction.id)
-                            .unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.from_account,
-                                account_transaction.info.from_ledger,
-                            )
-                            .unwrap();
+                            .remove_account_transaction(self.uid, account_transaction.id)?;
+                        self.db.remove_ledger_item(
+                            self.uid,
+                            account_transaction.info.from_account,
+                            account_transaction.info.from_ledger,
+                        )?;
                     }
                 } else {
                     account_transaction_opt = self
@@ -715,35 +522,66 @@ impl ChargeAccount {
                             self.uid,
                             self.id,
                             selected_record.id,
-                        )
-                        .unwrap();
+                        )?;
                     if account_transaction_opt.is_some() {
                         let account_transaction = account_transaction_opt.unwrap();
                         self.db
-                            .remove_account_transaction(self.uid, account_transaction.id)
-                            .unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.to_account,
-                                account_transaction.info.to_ledger,
-                            )
-                            .unwrap();
+                            .remove_account_transaction(self.uid, account_transaction.id)?;
+                        self.db.remove_ledger_item(
+                            self.uid,
+                            account_transaction.info.to_account,
+                            account_transaction.info.to_ledger,
+                        )?;
                     }
                 }
                 self.db
-                    .remove_ledger_item(self.uid, self.id, selected_record.id.clone())
-                    .unwrap();
+                    .remove_ledger_item(self.uid, self.id, selected_record.id.clone())?;
+                transaction.commit()?;
             }
             "None" => {
-                return selected_record.clone();
+                return Ok(selected_record.clone());
             }
             _ => {
                 panic!("Unrecognized input!");
             }
         }
 
-        return selected_record;
+        return Ok(selected_record);
+    }
+
+    // Removes the entry in the other account that an entry being updated was
+    // linked to, since the update links it anew.
+    fn remove_linked_entry(&self, record: &LedgerRecord) -> Result<()> {
+        if record.info.transfer_type.is_deposit() {
+            let account_transaction_opt = self
+                .db
+                .check_and_get_account_transaction_record_matching_to_ledger_id(
+                    self.uid, self.id, record.id,
+                )?;
+            if let Some(account_transaction) = account_transaction_opt {
+                self.db
+                    .remove_account_transaction(self.uid, account_transaction.id)?;
+                self.db.remove_ledger_item(
+                    self.uid,
+                    account_transaction.info.from_account,
+                    account_transaction.info.from_ledger,
+                )?;
+            }
+        } else {
+            let account_transaction_opt = self
+                .db
+                .check_and_get_account_transaction_record_matching_from_ledger_id(
+                    self.uid, self.id, record.id,
+                )?;
+            if let Some(account_transaction) = account_transaction_opt {
+                self.db.remove_ledger_item(
+                    self.uid,
+                    account_transaction.info.to_account,
+                    account_transaction.info.to_ledger,
+                )?;
+            }
+        }
+        Ok(())
     }
 
     // returns uid of selected ledger entry
@@ -795,7 +633,7 @@ impl ChargeAccount {
     pub fn link_transaction(
         &self,
         initial_opt: Option<String>,
-    ) -> Option<(Box<dyn Account>, String)> {
+    ) -> Result<Option<(Box<dyn Account>, String)>> {
         let default_to_use;
         let mut initial_account = String::new();
         if initial_opt.is_some() {
@@ -805,7 +643,7 @@ impl ChargeAccount {
             default_to_use = false;
         }
 
-        let accounts = self.db.get_user_accounts(self.uid).unwrap();
+        let accounts = self.db.get_owned_accounts(self.uid)?;
         let mut account_map: HashMap<String, AccountRecord> = HashMap::new();
         let mut account_names: Vec<String> = Vec::new();
         for account in accounts.iter() {
@@ -844,15 +682,15 @@ impl ChargeAccount {
         };
 
         if selected_account.clone() == "None" {
-            return None;
+            return Ok(None);
         }
 
         let acct: Box<dyn Account>;
         let record: AccountRecord;
         if selected_account.clone() == "New Account".to_ascii_uppercase().to_string() {
-            let user_input = prompt_and_create_new_account(self.uid, &self.db);
+            let user_input = prompt_and_create_new_account(self.uid, &self.db)?;
             if user_input.is_none() {
-                return None;
+                return Ok(None);
             }
             (acct, record) = user_input.unwrap();
             selected_account = record.info.name;
@@ -860,35 +698,30 @@ impl ChargeAccount {
             let acctx = account_map
                 .get(&selected_account)
                 .expect("Account not found!");
-            acct = decode_and_init_account_type(self.uid, &self.db, acctx);
+            acct = decode_and_init_account_type(&self.db, acctx)?;
         }
 
-        return Some((acct, selected_account.clone()));
+        return Ok(Some((acct, selected_account.clone())));
     }
 
-    pub fn get_current_balance(&self) -> f32 {
+    pub fn get_current_balance(&self) -> Result<Money> {
         // because a credit card is debt, return it as a positive balance.
-        return -self.db.get_current_value(self.uid, self.id).unwrap();
+        Ok(-self.db.get_current_value(self.uid, self.id)?)
     }
 
-    pub fn get_balance_on_day(&self, day: NaiveDate) -> f32 {
-        if let Some(value) = self
+    pub fn get_balance_on_day(&self, day: NaiveDate) -> Result<Money> {
+        let value_opt = self
             .db
-            .get_cumulative_total_of_ledger_on_date(self.uid, self.id, day)
-            .unwrap()
-        {
-            -value
-        } else {
-            0.0
-        }
+            .get_cumulative_total_of_ledger_on_date(self.uid, self.id, day)?;
+        Ok(value_opt.map_or(Money::ZERO, |value| -value))
     }
 
-    pub fn get_credit_line(&self) -> f32 {
-        let credit_card = self.db.get_credit_card(self.uid, self.id).unwrap();
-        return credit_card.info.credit_line;
+    pub fn get_credit_line(&self) -> Result<Money> {
+        let credit_card = self.db.get_credit_card(self.uid, self.id)?;
+        Ok(credit_card.info.credit_line)
     }
 
-    pub fn get_remaining_in_credit_line(&self) -> f32 {
-        return self.get_credit_line() - self.get_current_balance();
+    pub fn get_remaining_in_credit_line(&self) -> Result<Money> {
+        Ok(self.get_credit_line()? - self.get_current_balance()?)
     }
 }
@@ -0,0 +1,76 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use fin_import::qif::{write_qif, QifAccountType};
+use inquire::Select;
+use shared_lib::{FlatLedgerEntry, LedgerEntry};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::error::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum ExportFormat {
+    #[strum(to_string = "fintool CSV")]
+    Csv,
+    // Quicken Interchange Format, for moving data into older software
+    #[strum(to_string = "QIF")]
+    Qif,
+}
+
+pub fn select_export_format() -> Option<ExportFormat> {
+    let formats: Vec<ExportFormat> = ExportFormat::iter().collect();
+    let mut options: Vec<String> = formats
+        .iter()
+        .map(|format| format!("Export {} file", format))
+        .collect();
+    options.push("None".to_string());
+    let selected = Select::new("What would you like to export:", options)
+        .raw_prompt()
+        .unwrap();
+    formats.get(selected.index).copied()
+}
+
+// Writes the account's entries to the file. A QIF file holds the transactions
+// of one kind of account, given by qif_type.
+pub fn write_export_file(
+    format: ExportFormat,
+    qif_type: QifAccountType,
+    path: &Path,
+    entries: Vec<LedgerEntry>,
+) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            let mut wtr = csv::Writer::from_path(path)?;
+            for entry in entries {
+                wtr.serialize(FlatLedgerEntry::from(entry))?;
+            }
+            wtr.flush()?;
+        }
+        ExportFormat::Qif => {
+            let skipped = write_qif(&entries, qif_type, BufWriter::new(File::create(path)?))?;
+            if skipped > 0 {
+                println!(
+                    "{} stock splits cannot be written to QIF and were left out.",
+                    skipped
+                );
+            }
+        }
+    }
+    Ok(())
+}
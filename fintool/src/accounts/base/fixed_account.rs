@@ -15,17 +15,18 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::tui::{decode_and_init_account_type, prompt_and_create_new_account};
 use crate::types::accounts::AccountRecord;
 use crate::types::categories::CategoryAutoCompleter;
-use crate::types::labels::LabelAutoCompleter;
+use crate::types::labels::LabelSelection;
 use crate::types::ledger::{LedgerInfo, LedgerRecord};
 use crate::types::participants::{ParticipantAutoCompleter, ParticipantType};
 use chrono::{Datelike, NaiveDate};
 use core::panic;
 use inquire::validator::MinLengthValidator;
 use inquire::*;
-use shared_lib::{LedgerEntry, TransferType};
+use shared_lib::{LedgerEntry, Money, TransferType};
 use std::collections::HashMap;
 use std::hash::Hash;
 
@@ -49,13 +50,17 @@ impl FixedAccount {
         acct
     }
 
-    pub fn withdrawal(&self, initial_opt: Option<LedgerRecord>, overwrite: bool) -> LedgerRecord {
+    pub fn withdrawal(
+        &self,
+        initial_opt: Option<LedgerRecord>,
+        overwrite: bool,
+    ) -> Result<LedgerRecord> {
         let default_to_use: bool;
         let mut initial = LedgerRecord {
             id: 0,
             info: LedgerInfo {
                 date: "1970-01-01".to_string(),
-                amount: 0.0,
+                amount: Money::ZERO,
                 transfer_type: TransferType::WithdrawalToExternalAccount,
                 participant: 0,
                 category_id: 0,
@@ -73,7 +78,7 @@ impl FixedAccount {
         let date_prompt = "Enter date of withdrawal:";
         let date_input = if default_to_use {
             DateSelect::new(date_prompt)
-                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d").unwrap())
+                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d")?)
                 .prompt()
                 .unwrap()
                 .format("%Y-%m-%d")
@@ -87,23 +92,22 @@ impl FixedAccount {
         };
 
         let amount_prompt = "Enter amount withdrew:";
-        let amount_input: f32 = if default_to_use {
-            CustomType::<f32>::new(amount_prompt)
+        let amount_input: Money = if default_to_use {
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
                 .with_default(initial.info.amount)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(amount_prompt)
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
-                .with_default(00000.00)
+                .with_default(Money::ZERO)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
-        let cid;
         let category_prompt = "Enter category:";
         let selected_category = if default_to_use {
             Text::new(category_prompt)
@@ -115,8 +119,7 @@ impl FixedAccount {
                 })
                 .with_default(
                     self.db
-                        .get_category_name(self.uid, self.id, initial.info.category_id)
-                        .unwrap()
+                        .get_category_name(self.uid, self.id, initial.info.category_id)?
                         .as_str(),
                 )
                 .prompt()
@@ -135,10 +138,6 @@ impl FixedAccount {
                 .to_ascii_uppercase()
         };
 
-        cid = self
-            .db
-            .check_and_add_category(self.uid, self.id, selected_category);
-
         let description_prompt = "Enter description:";
         let description_input = if default_to_use {
             Text::new(description_prompt)
@@ -155,8 +154,7 @@ impl FixedAccount {
             .unwrap();
 
         let selected_payee;
-        let mut acct: Box<dyn Account>;
-        let pid;
+        let mut linked_acct: Option<Box<dyn Account>> = None;
         let payee_prompt = "Enter payee:";
         if !link {
             selected_payee = if default_to_use {
@@ -172,8 +170,7 @@ impl FixedAccount {
                     })
                     .with_default(
                         self.db
-                            .get_participant(self.uid, self.id, initial.info.participant)
-                            .unwrap()
+                            .get_participant(self.uid, self.id, initial.info.participant)?
                             .as_str(),
                     )
                     .prompt()
@@ -192,203 +189,100 @@ impl FixedAccount {
                     .prompt()
                     .unwrap()
             };
-            pid = self.db.check_and_add_participant(
-                self.uid,
-                self.id,
-                selected_payee,
-                ParticipantType::Payee,
-                false,
-            );
-
-            let withdrawal = LedgerInfo {
-                date: date_input,
-                amount: amount_input,
-                transfer_type: TransferType::WithdrawalToExternalAccount,
-                participant: pid,
-                category_id: cid,
-                description: description_input,
-            };
-
-            let id = if default_to_use && overwrite {
-                self.db
-                    .update_ledger_item(
-                        self.uid,
-                        self.id,
-                        LedgerRecord {
-                            id: initial.id,
-                            info: withdrawal.clone(),
-                        },
-                    )
-                    .unwrap()
-            } else {
-                self.db
-                    .add_ledger_entry(self.uid, self.id, withdrawal.clone())
-                    .unwrap()
-            };
-
-            if overwrite {
-                let maintain_labels =
-                    Confirm::new("Would you like to maintain all prior labels (y/n)?")
-                        .prompt()
-                        .unwrap();
-                if !maintain_labels {
-                    let mapped_labels = self
-                        .db
-                        .check_and_get_label_mapping_matching_ledger_id(self.uid, self.id, id)
-                        .unwrap();
-                    if !mapped_labels.is_empty() {
-                        for label in mapped_labels {
-                            self.db
-                                .remove_label_mapping(self.uid, self.id, label.id)
-                                .unwrap();
-                        }
-                    }
-                }
-            }
-
-            // add labels for transaction
-            let add_label_prompt = Confirm::new("Add labels to withdrawal (y/n)?")
-                .prompt()
-                .unwrap();
-            if add_label_prompt == true {
-                loop {
-                    let label = Text::new("Enter label:")
-                        .with_autocomplete(LabelAutoCompleter {
-                            uid: self.uid,
-                            db: self.db.clone(),
-                        })
-                        .prompt()
-                        .unwrap()
-                        .to_ascii_uppercase();
-                    let label_id = self.db.check_and_add_label(self.uid, label).unwrap();
-                    self.db
-                        .add_label_mapping(self.uid, self.id, label_id, id)
-                        .unwrap();
-
-                    let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
-                    if !continue_prompt {
-                        break;
-                    }
-                }
-            }
-
-            return LedgerRecord {
-                id: id,
-                info: withdrawal,
-            };
         } else {
             let initial_account_opt = if default_to_use {
-                self.db
-                    .get_participant(self.uid, self.id, initial.info.participant)
+                Some(
+                    self.db
+                        .get_participant(self.uid, self.id, initial.info.participant)?,
+                )
             } else {
                 None
             };
 
-            let user_input = self.link_transaction(initial_account_opt);
+            let user_input = self.link_transaction(initial_account_opt)?;
             if user_input.is_none() {
-                return initial;
+                return Ok(initial);
             }
-            (acct, selected_payee) = user_input.unwrap();
-            pid = self.db.check_and_add_participant(
+            let (acct, account_name) = user_input.unwrap();
+            linked_acct = Some(acct);
+            selected_payee = account_name;
+        }
+        let labels = LabelSelection::prompt(self.uid, &self.db, "withdrawal", overwrite);
+
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        if default_to_use && overwrite {
+            self.remove_linked_entry(&initial)?;
+        }
+        let cid = self
+            .db
+            .check_and_add_category(self.uid, self.id, selected_category)?;
+        let pid = if link {
+            self.db.check_and_add_participant(
                 self.uid,
                 self.id,
                 selected_payee,
                 ParticipantType::Both,
                 true,
-            );
-
-            let withdrawal = LedgerInfo {
-                date: date_input,
-                amount: amount_input,
-                transfer_type: TransferType::WithdrawalToExternalAccount,
-                participant: pid,
-                category_id: cid,
-                description: description_input,
-            };
-
-            let id = if default_to_use && overwrite {
-                self.db
-                    .update_ledger_item(
-                        self.uid,
-                        self.id,
-                        LedgerRecord {
-                            id: initial.id,
-                            info: withdrawal.clone(),
-                        },
-                    )
-                    .unwrap()
-            } else {
-                self.db
-                    .add_ledger_entry(self.uid, self.id, withdrawal.clone())
-                    .unwrap()
-            };
-
-            let entry = LedgerRecord {
-                id: id,
-                info: withdrawal,
-            };
-
-            if overwrite {
-                let maintain_labels =
-                    Confirm::new("Would you like to maintain all prior labels (y/n)?")
-                        .prompt()
-                        .unwrap();
-                if !maintain_labels {
-                    let mapped_labels = self
-                        .db
-                        .check_and_get_label_mapping_matching_ledger_id(self.uid, self.id, id)
-                        .unwrap();
-                    if !mapped_labels.is_empty() {
-                        for label in mapped_labels {
-                            self.db
-                                .remove_label_mapping(self.uid, self.id, label.id)
-                                .unwrap();
-                        }
-                    }
-                }
-            }
-
-            // add labels for transaction
-            let add_label_prompt = Confirm::new("Add labels to withdrawal (y/n)?")
-                .prompt()
-                .unwrap();
-            if add_label_prompt == true {
-                loop {
-                    let label = Text::new("Enter label:")
-                        .with_autocomplete(LabelAutoCompleter {
-                            uid: self.uid,
-                            db: self.db.clone(),
-                        })
-                        .prompt()
-                        .unwrap()
-                        .to_ascii_uppercase();
-                    let label_id = self.db.check_and_add_label(self.uid, label).unwrap();
-                    self.db
-                        .add_label_mapping(self.uid, self.id, label_id, entry.id)
-                        .unwrap();
+            )?
+        } else {
+            self.db.check_and_add_participant(
+                self.uid,
+                self.id,
+                selected_payee,
+                ParticipantType::Payee,
+                false,
+            )?
+        };
 
-                    let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
-                    if !continue_prompt {
-                        break;
-                    }
-                }
-            }
+        let withdrawal = LedgerInfo {
+            date: date_input,
+            amount: amount_input,
+            transfer_type: TransferType::WithdrawalToExternalAccount,
+            participant: pid,
+            category_id: cid,
+            description: description_input,
+        };
 
-            if link {
-                acct.link(self.id, entry.clone());
-            }
+        let id = if default_to_use && overwrite {
+            self.db.update_ledger_item(
+                self.uid,
+                self.id,
+                LedgerRecord {
+                    id: initial.id,
+                    info: withdrawal.clone(),
+                },
+            )?
+        } else {
+            self.db
+                .add_ledger_entry(self.uid, self.id, withdrawal.clone())?
+        };
+        labels.apply(self.uid, self.id, &self.db, id)?;
 
-            return entry;
+        let entry = LedgerRecord {
+            id: id,
+            info: withdrawal,
+        };
+        if let Some(mut acct) = linked_acct {
+            acct.link(self.id, entry.clone())?;
         }
+
+        transaction.commit()?;
+        return Ok(entry);
     }
 
-    pub fn deposit(&self, initial_opt: Option<LedgerRecord>, overwrite: bool) -> LedgerRecord {
+    pub fn deposit(
+        &self,
+        initial_opt: Option<LedgerRecord>,
+        overwrite: bool,
+    ) -> Result<LedgerRecord> {
         let default_to_use: bool;
         let mut initial = LedgerRecord {
             id: 0,
             info: LedgerInfo {
                 date: "1970-01-01".to_string(),
-                amount: 0.0,
+                amount: Money::ZERO,
                 transfer_type: TransferType::DepositFromExternalAccount,
                 participant: 0,
                 category_id: 0,
@@ -406,7 +300,7 @@ impl FixedAccount {
         let date_prompt = "Enter date of deposit:";
         let date_input = if default_to_use {
             DateSelect::new(date_prompt)
-                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d").unwrap())
+                .with_default(NaiveDate::parse_from_str(&initial.info.date, "%Y-%m-%d")?)
                 .prompt()
                 .unwrap()
                 .format("%Y-%m-%d")
@@ -420,23 +314,22 @@ impl FixedAccount {
         };
 
         let amount_prompt = "Enter amount deposited:";
-        let amount_input: f32 = if default_to_use {
-            CustomType::<f32>::new(amount_prompt)
+        let amount_input: Money = if default_to_use {
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
                 .with_default(initial.info.amount)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(amount_prompt)
+            CustomType::<Money>::new(amount_prompt)
                 .with_placeholder("00000.00")
-                .with_default(00000.00)
+                .with_default(Money::ZERO)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
-        let cid;
         let category_validator =
             MinLengthValidator::new(3).with_message("Category cannot be empty!");
         let category_prompt = "Enter category:";
@@ -450,8 +343,7 @@ impl FixedAccount {
                 })
                 .with_default(
                     self.db
-                        .get_category_name(self.uid, self.id, initial.info.category_id)
-                        .unwrap()
+                        .get_category_name(self.uid, self.id, initial.info.category_id)?
                         .as_str(),
                 )
                 .with_validator(category_validator)
@@ -476,10 +368,6 @@ impl FixedAccount {
                 .to_string()
         };
 
-        cid = self
-            .db
-            .check_and_add_category(self.uid, self.id, selected_category);
-
         let description_prompt = "Enter description:";
         let description_input = if default_to_use {
             Text::new(description_prompt)
@@ -503,8 +391,7 @@ impl FixedAccount {
             .unwrap();
 
         let selected_payer;
-        let mut acct: Box<dyn Account>;
-        let pid;
+        let mut linked_acct: Option<Box<dyn Account>> = None;
         let participant_validator =
             MinLengthValidator::new(1).with_message("Payer cannot be empty!");
         if !link {
@@ -521,8 +408,7 @@ impl FixedAccount {
                     })
                     .with_default(
                         self.db
-                            .get_participant(self.uid, self.id, initial.info.participant)
-                            .unwrap()
+                            .get_participant(self.uid, self.id, initial.info.participant)?
                             .as_str(),
                     )
                     .with_validator(participant_validator)
@@ -547,200 +433,93 @@ impl FixedAccount {
                     .trim()
                     .to_string()
             };
-            pid = self.db.check_and_add_participant(
-                self.uid,
-                self.id,
-                selected_payer,
-                ParticipantType::Payer,
-                false,
-            );
-
-            let deposit = LedgerInfo {
-                date: date_input,
-                amount: amount_input,
-                transfer_type: TransferType::DepositFromExternalAccount,
-                participant: pid,
-                category_id: cid,
-                description: description_input,
-            };
-
-            let id = if default_to_use && overwrite {
-                self.db
-                    .update_ledger_item(
-                        self.uid,
-                        self.id,
-                        LedgerRecord {
-                            id: initial.id,
-                            info: deposit.clone(),
-                        },
-                    )
-                    .unwrap()
-            } else {
-                self.db
-                    .add_ledger_entry(self.uid, self.id, deposit.clone())
-                    .unwrap()
-            };
-
-            if overwrite {
-                let maintain_labels =
-                    Confirm::new("Would you like to maintain all prior labels (y/n)?")
-                        .prompt()
-                        .unwrap();
-                if !maintain_labels {
-                    let mapped_labels = self
-                        .db
-                        .check_and_get_label_mapping_matching_ledger_id(self.uid, self.id, id)
-                        .unwrap();
-                    if !mapped_labels.is_empty() {
-                        for label in mapped_labels {
-                            self.db
-                                .remove_label_mapping(self.uid, self.id, label.id)
-                                .unwrap();
-                        }
-                    }
-                }
-            }
-
-            // add labels for transaction
-            let add_label_prompt = Confirm::new("Add labels to deposit (y/n)?")
-                .prompt()
-                .unwrap();
-            if add_label_prompt == true {
-                loop {
-                    let label = Text::new("Enter label:")
-                        .with_autocomplete(LabelAutoCompleter {
-                            uid: self.uid,
-                            db: self.db.clone(),
-                        })
-                        .prompt()
-                        .unwrap()
-                        .to_ascii_uppercase();
-                    let label_id = self.db.check_and_add_label(self.uid, label).unwrap();
-                    self.db
-                        .add_label_mapping(self.uid, self.id, label_id, id)
-                        .unwrap();
-
-                    let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
-                    if !continue_prompt {
-                        break;
-                    }
-                }
-            }
-
-            return LedgerRecord {
-                id: id,
-                info: deposit,
-            };
         } else {
             let initial_account_opt = if default_to_use {
-                self.db
-                    .get_participant(self.uid, self.id, initial.info.participant)
+                Some(
+                    self.db
+                        .get_participant(self.uid, self.id, initial.info.participant)?,
+                )
             } else {
                 None
             };
 
-            let user_input = self.link_transaction(initial_account_opt);
+            let user_input = self.link_transaction(initial_account_opt)?;
             if user_input.is_none() {
-                return initial;
+                return Ok(initial);
             }
-            (acct, selected_payer) = user_input.unwrap();
-            pid = self.db.check_and_add_participant(
+            let (acct, account_name) = user_input.unwrap();
+            linked_acct = Some(acct);
+            selected_payer = account_name;
+        }
+        let labels = LabelSelection::prompt(self.uid, &self.db, "deposit", overwrite);
+
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        if default_to_use && overwrite {
+            self.remove_linked_entry(&initial)?;
+        }
+        let cid = self
+            .db
+            .check_and_add_category(self.uid, self.id, selected_category)?;
+        let pid = if link {
+            self.db.check_and_add_participant(
                 self.uid,
                 self.id,
                 selected_payer,
                 ParticipantType::Both,
                 true,
-            );
-
-            let deposit = LedgerInfo {
-                date: date_input,
-                amount: amount_input,
-                transfer_type: TransferType::DepositFromExternalAccount,
-                participant: pid,
-                category_id: cid,
-                description: description_input,
-            };
-
-            let id = if default_to_use && overwrite {
-                self.db
-                    .update_ledger_item(
-                        self.uid,
-                        self.id,
-                        LedgerRecord {
-                            id: initial.id,
-                            info: deposit.clone(),
-                        },
-                    )
-                    .unwrap()
-            } else {
-                self.db
-                    .add_ledger_entry(self.uid, self.id, deposit.clone())
-                    .unwrap()
-            };
-
-            let entry = LedgerRecord {
-                id: id,
-                info: deposit,
-            };
-
-            if overwrite {
-                let maintain_labels =
-                    Confirm::new("Would you like to maintain all prior labels (y/n)?")
-                        .prompt()
-                        .unwrap();
-                if !maintain_labels {
-                    let mapped_labels = self
-                        .db
-                        .check_and_get_label_mapping_matching_ledger_id(self.uid, self.id, id)
-                        .unwrap();
-                    if !mapped_labels.is_empty() {
-                        for label in mapped_labels {
-                            self.db
-                                .remove_label_mapping(self.uid, self.id, label.id)
-                                .unwrap();
-                        }
-                    }
-                }
-            }
-
-            // add labels for transaction
-            let add_label_prompt = Confirm::new("Add labels to deposit (y/n)?")
-                .prompt()
-                .unwrap();
-            if add_label_prompt == true {
-                loop {
-                    let label = Text::new("Enter label:")
-                        .with_autocomplete(LabelAutoCompleter {
-                            uid: self.uid,
-                            db: self.db.clone(),
-                        })
-                        .prompt()
-                        .unwrap()
-                        .to_ascii_uppercase();
-                    let label_id = self.db.check_and_add_label(self.uid, label).unwrap();
-                    self.db
-                        .add_label_mapping(self.uid, self.id, label_id, entry.id)
-                        .unwrap();
+            )?
+        } else {
+            self.db.check_and_add_participant(
+                self.uid,
+                self.id,
+                selected_payer,
+                ParticipantType::Payer,
+                false,
+            )?
+        };
 
-                    let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
-                    if !continue_prompt {
-                        break;
-                    }
-                }
-            }
+        let deposit = LedgerInfo {
+            date: date_input,
+            amount: amount_input,
+            transfer_type: TransferType::DepositFromExternalAccount,
+            participant: pid,
+            category_id: cid,
+            description: description_input,
+        };
 
-            if link {
-                acct.link(self.id, entry.clone());
-            }
+        let id = if default_to_use && overwrite {
+            self.db.update_ledger_item(
+                self.uid,
+                self.id,
+                LedgerRecord {
+                    id: initial.id,
+                    info: deposit.clone(),
+                },
+            )?
+        } else {
+            self.db
+                .add_ledger_entry(self.uid, self.id, deposit.clone())?
+        };
+        labels.apply(self.uid, self.id, &self.db, id)?;
 
-            return entry;
+        let entry = LedgerRecord {
+            id: id,
+            info: deposit,
+        };
+        if let Some(mut acct) = linked_acct {
+            acct.link(self.id, entry.clone())?;
         }
+
+        transaction.commit()?;
+        return Ok(entry);
     }
 
-    pub fn modify(&self, selected_record: LedgerRecord) -> LedgerRecord {
+    pub fn modify(&self, selected_record: LedgerRecord) -> Result<LedgerRecord> {
         if selected_record.info.transfer_type == TransferType::ZeroSumChange {
             println!("Unable to modify a zero-sum change!");
-            return selected_record;
+            return Ok(selected_record);
         }
 
         let was_deposit = selected_record.info.transfer_type.is_deposit();
@@ -751,56 +530,16 @@ impl FixedAccount {
             .unwrap();
         match modify_choice {
             "Update" => {
-                let account_transaction_opt: Option<
-                    crate::types::accounts::AccountTransactionRecord,
-                >;
-                let updated_record = if was_deposit {
-                    account_transaction_opt = self
-                        .db
-                        .check_and_get_account_transaction_record_matching_to_ledger_id(
-                            self.uid,
-                            self.id,
-                            selected_record.id,
-                        )
-                        .unwrap();
-                    if account_transaction_opt.is_some() {
-                        let account_transaction = account_transaction_opt.unwrap();
-                        self.db
-                            .remove_account_transaction(self.uid, account_transaction.id)
-                            .unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.from_account,
-                                account_transaction.info.from_ledger,
-                            )
-                            .unwrap();
-                    }
-                    self.deposit(Some(selected_record.clone()), true)
+                // the entry and what it is linked to are replaced once its
+                // new values are entered
+                if was_deposit {
+                    return self.deposit(Some(selected_record.clone()), true);
                 } else {
-                    account_transaction_opt = self
-                        .db
-                        .check_and_get_account_transaction_record_matching_from_ledger_id(
-                            self.uid,
-                            self.id,
-                            selected_record.id,
-                        )
-                        .unwrap();
-                    if account_transaction_opt.is_some() {
-                        let account_transaction = account_transaction_opt.unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.to_account,
-                                account_transaction.info.to_ledger,
-                            )
-                            .unwrap();
-                    }
-                    self.withdrawal(Some(selected_record.clone()), true)
-                };
-                return updated_record;
+                    return self.withdrawal(Some(selected_record.clone()), true);
+                }
             }
             "Remove" => {
+                let transaction = self.db.begin_transaction()?;
                 let account_transaction_opt: Option<
                     crate::types::accounts::AccountTransactionRecord,
                 >;
@@ -811,20 +550,16 @@ impl FixedAccount {
                             self.uid,
                             self.id,
                             selected_record.id,
-                        )
-                        .unwrap();
+                        )?;
                     if account_transaction_opt.is_some() {
                         let account_transaction = account_transaction_opt.unwrap();
                         self.db
-                            .remove_account_transaction(self.uid, account_transaction.id)
-                            .unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.from_account,
-                                account_transaction.info.from_ledger,
-                            )
-                            .unwrap();
+                            .remove_account_transaction(self.uid, account_transaction.id)?;
+                        self.db.remove_ledger_item(
+                            self.uid,
+                            account_transaction.info.from_account,
+                            account_transaction.info.from_ledger,
+                        )?;
                     }
                 } else {
                     account_transaction_opt = self
@@ -833,35 +568,66 @@ impl FixedAccount {
                             self.uid,
                             self.id,
                             selected_record.id,
-                        )
-                        .unwrap();
+                        )?;
                     if account_transaction_opt.is_some() {
                         let account_transaction = account_transaction_opt.unwrap();
                         self.db
-                            .remove_account_transaction(self.uid, account_transaction.id)
-                            .unwrap();
-                        self.db
-                            .remove_ledger_item(
-                                self.uid,
-                                account_transaction.info.to_account,
-                                account_transaction.info.to_ledger,
-                            )
-                            .unwrap();
+                            .remove_account_transaction(self.uid, account_transaction.id)?;
+                        self.db.remove_ledger_item(
+                            self.uid,
+                            account_transaction.info.to_account,
+                            account_transaction.info.to_ledger,
+                        )?;
                     }
                 }
                 self.db
-                    .remove_ledger_item(self.uid, self.id, selected_record.id.clone())
-                    .unwrap();
+                    .remove_ledger_item(self.uid, self.id, selected_record.id.clone())?;
+                transaction.commit()?;
             }
             "None" => {
-                return selected_record.clone();
+                return Ok(selected_record.clone());
             }
             _ => {
                 panic!("Unrecognized input!");
             }
         }
 
-        return selected_record;
+        return Ok(selected_record);
+    }
+
+    // Removes the entry in the other account that an entry being updated was
+    // linked to, since the update links it anew.
+    fn remove_linked_entry(&self, record: &LedgerRecord) -> Result<()> {
+        if record.info.transfer_type.is_deposit() {
+            let account_transaction_opt = self
+                .db
+                .check_and_get_account_transaction_record_matching_to_ledger_id(
+                    self.uid, self.id, record.id,
+                )?;
+            if let Some(account_transaction) = account_transaction_opt {
+                self.db
+                    .remove_account_transaction(self.uid, account_transaction.id)?;
+                self.db.remove_ledger_item(
+                    self.uid,
+                    account_transaction.info.from_account,
+                    account_transaction.info.from_ledger,
+                )?;
+            }
+        } else {
+            let account_transaction_opt = self
+                .db
+                .check_and_get_account_transaction_record_matching_from_ledger_id(
+                    self.uid, self.id, record.id,
+                )?;
+            if let Some(account_transaction) = account_transaction_opt {
+                self.db.remove_ledger_item(
+                    self.uid,
+                    account_transaction.info.to_account,
+                    account_transaction.info.to_ledger,
+                )?;
+            }
+        }
+        Ok(())
     }
 
     // returns uid of selected ledger entry
@@ -913,7 +679,7 @@ impl FixedAccount {
     pub fn link_transaction(
         &self,
         initial_opt: Option<String>,
-    ) -> Option<(Box<dyn Account>, String)> {
+    ) -> Result<Option<(Box<dyn Account>, String)>> {
         let default_to_use;
         let mut initial_account = String::new();
         if initial_opt.is_some() {
@@ -923,7 +689,7 @@ impl FixedAccount {
             default_to_use = false;
         }
 
-        let accounts = self.db.get_user_accounts(self.uid).unwrap();
+        let accounts = self.db.get_owned_accounts(self.uid)?;
         let mut account_map: HashMap<String, AccountRecord> = HashMap::new();
         let mut account_names: Vec<String> = Vec::new();
         for account in accounts.iter() {
@@ -962,15 +728,15 @@ impl FixedAccount {
         };
 
         if selected_account.clone() == "None" {
-            return None;
+            return Ok(None);
         }
 
         let acct: Box<dyn Account>;
         let record: AccountRecord;
         if selected_account.clone() == "New Account".to_ascii_uppercase().to_string() {
-            let user_input = prompt_and_create_new_account(self.uid, &self.db);
+            let user_input = prompt_and_create_new_account(self.uid, &self.db)?;
             if user_input.is_none() {
-                return None;
+                return Ok(None);
             }
             (acct, record) = user_input.unwrap();
             selected_account = record.info.name;
@@ -978,14 +744,14 @@ impl FixedAccount {
             let acctx = account_map
                 .get(&selected_account)
                 .expect("Account not found!");
-            acct = decode_and_init_account_type(self.uid, &self.db, acctx);
+            acct = decode_and_init_account_type(&self.db, acctx)?;
         }
 
-        return Some((acct, selected_account.clone()));
+        return Ok(Some((acct, selected_account.clone())));
     }
 
-    pub fn get_current_value(&self) -> f32 {
-        return self.db.get_current_value(self.uid, self.id).unwrap();
+    pub fn get_current_value(&self) -> Result<Money> {
+        Ok(self.db.get_current_value(self.uid, self.id)?)
         // return self.ledger.iter().map(|x| {
         //     match x.info.transfer_type {
         //         TransferType::DepositFromExternalAccount|TransferType::DepositFromInternalAccount => {
@@ -1001,16 +767,11 @@ impl FixedAccount {
         // }).sum()
     }
 
-    pub fn get_value_on_day(&self, day: NaiveDate) -> f32 {
+    pub fn get_value_on_day(&self, day: NaiveDate) -> Result<Money> {
         let value_opt = self
             .db
-            .get_cumulative_total_of_ledger_before_date(self.uid, self.id, day)
-            .unwrap();
-        if let Some(value) = value_opt {
-            return value;
-        } else {
-            return 0.0;
-        }
+            .get_cumulative_total_of_ledger_before_date(self.uid, self.id, day)?;
+        Ok(value_opt.unwrap_or(Money::ZERO))
 
         // return self.ledger.iter().filter(|x|
         //     NaiveDate::parse_from_str(x.info.date.as_str(), "%Y-%m-%d").expect("Unable to parse date!") <= day )
@@ -1051,7 +812,7 @@ impl FixedAccount {
         } else {
             return f32::NAN;
         }
-        rate = (ending_amount - starting_amount) / (starting_amount);
+        rate = ((ending_amount - starting_amount).to_f64() / starting_amount.to_f64()) as f32;
         return rate;
     }
 
@@ -1139,7 +900,8 @@ impl FixedAccount {
         let date_diff: i32 = end_date.num_days_from_ce() - start_date.num_days_from_ce();
         let year_diff: f32 = date_diff as f32 / 365.0;
 
-        rate = (ending_amount / starting_amount).powf(1 as f32 / year_diff);
+        rate = (ending_amount.to_f64() / starting_amount.to_f64()) as f32;
+        rate = rate.powf(1 as f32 / year_diff);
         return rate;
     }
 }
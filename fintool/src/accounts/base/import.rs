@@ -0,0 +1,806 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::{Duration, NaiveDate};
+use csv::ReaderBuilder;
+use fin_import::mapping::{CsvMapping, SignConvention};
+use fin_import::ofx::{parse_statements, OfxConverter, Statement, StatementAccount, StatementKind};
+use fin_import::qif::QifConverter;
+use fin_import::{csv_headers, detect_converter, Converter};
+use inquire::{Confirm, Select, Text};
+use shared_lib::{LedgerEntry, Money};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicI64, Ordering};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::database::DbConn;
+use crate::error::{FintoolError, Result};
+use crate::types::ledger::LedgerRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum ImportFormat {
+    // any of the formats below, or an institution's own export
+    #[strum(to_string = "any supported")]
+    Detect,
+    #[strum(to_string = "fintool CSV")]
+    Csv,
+    #[strum(to_string = "OFX/QFX")]
+    Ofx,
+    #[strum(to_string = "QIF")]
+    Qif,
+    // a CSV export read with a column mapping saved for the account
+    #[strum(to_string = "institution CSV")]
+    Mapped,
+}
+
+const DATE_FORMATS: [&str; 5] = ["%m/%d/%Y", "%Y-%m-%d", "%d/%m/%Y", "%m/%d/%y", "%d.%m.%Y"];
+const PREVIEW_LEN: usize = 5;
+
+// how many days apart an imported entry and one already in the ledger may be
+// dated and still be taken for the same transaction
+pub const DEFAULT_DUPLICATE_WINDOW_DAYS: i64 = 3;
+static DUPLICATE_WINDOW_DAYS: AtomicI64 = AtomicI64::new(DEFAULT_DUPLICATE_WINDOW_DAYS);
+
+pub fn set_duplicate_window(days: i64) {
+    DUPLICATE_WINDOW_DAYS.store(days, Ordering::Relaxed);
+}
+
+// a ledger entry read from a file, along with the institution's id for it
+pub struct ImportedEntry {
+    pub entry: LedgerEntry,
+    pub external_id: Option<String>,
+    // line of the file the entry was read from, when it is known
+    pub line: Option<u64>,
+}
+
+pub struct ImportError {
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "Line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// an imported entry that is folded into the ledger entry it duplicates
+pub struct MergedEntry {
+    pub lid: u32,
+    pub imported: ImportedEntry,
+}
+
+// The entries read from a file, before anything is written. Lines that cannot
+// be read are reported rather than aborting the import.
+pub struct ImportBatch {
+    pub entries: Vec<ImportedEntry>,
+    pub errors: Vec<ImportError>,
+    pub merges: Vec<MergedEntry>,
+    // duplicates of ledger entries that are left out
+    pub skipped: usize,
+    // the column mapping and the statement's account chosen while reading the
+    // file, which are only saved when the import goes ahead
+    pub mapping: Option<CsvMapping>,
+    pub statement_account: Option<StatementAccount>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DuplicateChoice {
+    Skip,
+    Merge,
+    Keep,
+    SkipAll,
+}
+
+pub fn select_import_format() -> Option<ImportFormat> {
+    let formats: Vec<ImportFormat> = ImportFormat::iter().collect();
+    let mut options: Vec<String> = formats
+        .iter()
+        .map(|format| format!("Import {} file", format))
+        .collect();
+    options.push("None".to_string());
+    let selected = Select::new("What would you like to import:", options)
+        .raw_prompt()
+        .unwrap();
+    formats.get(selected.index).copied()
+}
+
+// Reads the entries to import into the account from the file, checked and
+// sorted by date.
+pub fn read_import_file(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    format: ImportFormat,
+    path: &Path,
+) -> Result<ImportBatch> {
+    let mut batch = ImportBatch {
+        entries: Vec::new(),
+        errors: Vec::new(),
+        merges: Vec::new(),
+        skipped: 0,
+        mapping: None,
+        statement_account: None,
+    };
+    let read = match format {
+        ImportFormat::Detect => read_detected(db, uid, aid, path, &mut batch)?,
+        ImportFormat::Csv => read_csv(path, &mut batch.errors)?,
+        ImportFormat::Ofx => read_ofx(db, uid, aid, &fs::read_to_string(path)?, &mut batch)?,
+        ImportFormat::Qif => {
+            read_converted(&QifConverter, &fs::read_to_string(path)?, &mut batch.errors)
+        }
+        ImportFormat::Mapped => read_mapped(db, uid, aid, path, &mut batch)?,
+    };
+
+    for imported in read {
+        match validate_entry(&imported.entry) {
+            Ok(()) => batch.entries.push(imported),
+            Err(message) => batch.errors.push(ImportError {
+                line: imported.line,
+                // statement transactions have no line but an id
+                message: match (imported.line, &imported.external_id) {
+                    (None, Some(external_id)) => {
+                        format!("transaction {}: {}", external_id, message)
+                    }
+                    _ => message,
+                },
+            }),
+        }
+    }
+    batch.entries.sort_by(|x, y| {
+        (NaiveDate::parse_from_str(&x.entry.date, "%Y-%m-%d").unwrap())
+            .cmp(&NaiveDate::parse_from_str(&y.entry.date, "%Y-%m-%d").unwrap())
+    });
+    batch.errors.sort_by_key(|error| error.line);
+    resolve_duplicates(db, uid, aid, &mut batch)?;
+    Ok(batch)
+}
+
+// Looks for entries that are already in the ledger: exactly, by the
+// institution's transaction id, or by date, amount and participant. The user
+// decides for each whether to skip it, merge it into the existing entry or
+// keep both.
+fn resolve_duplicates(db: &DbConn, uid: u32, aid: u32, batch: &mut ImportBatch) -> Result<()> {
+    let window = Duration::days(DUPLICATE_WINDOW_DAYS.load(Ordering::Relaxed));
+    let mut matched: HashSet<u32> = HashSet::new();
+    let mut skip_all = false;
+    let mut kept = Vec::new();
+    for imported in std::mem::take(&mut batch.entries) {
+        let (existing, exact) = match find_duplicate(db, uid, aid, &imported, window, &matched)? {
+            Some(duplicate) => duplicate,
+            None => {
+                kept.push(imported);
+                continue;
+            }
+        };
+        let choice = if skip_all {
+            DuplicateChoice::Skip
+        } else {
+            ask_duplicate(db, uid, aid, &imported, &existing, exact)?
+        };
+        match choice {
+            DuplicateChoice::Skip => batch.skipped += 1,
+            DuplicateChoice::SkipAll => {
+                skip_all = true;
+                batch.skipped += 1;
+            }
+            DuplicateChoice::Merge => {
+                matched.insert(existing.id);
+                batch.merges.push(MergedEntry {
+                    lid: existing.id,
+                    imported,
+                });
+            }
+            DuplicateChoice::Keep => kept.push(imported),
+        }
+    }
+    batch.entries = kept;
+    Ok(())
+}
+
+// Returns the ledger entry the imported entry duplicates and whether the match
+// is exact.
+fn find_duplicate(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    imported: &ImportedEntry,
+    window: Duration,
+    matched: &HashSet<u32>,
+) -> Result<Option<(LedgerRecord, bool)>> {
+    if let Some(external_id) = &imported.external_id {
+        if let Some(lid) = db.get_ledger_id_by_external_id(uid, aid, external_id)? {
+            return Ok(Some((db.get_ledger_entry(uid, aid, lid)?, true)));
+        }
+    }
+
+    let entry = &imported.entry;
+    let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d")?;
+    let participant = normalize_name(&entry.participant);
+    let mut closest: Option<(LedgerRecord, i64)> = None;
+    for record in db.get_ledger_entries_within_timestamps(uid, aid, date - window, date + window)? {
+        if matched.contains(&record.id)
+            || record.info.amount != entry.amount
+            || record.info.transfer_type.is_deposit() != entry.transfer_type.is_deposit()
+        {
+            continue;
+        }
+        // the institution says these are different transactions
+        if let (Some(imported_id), Some(existing_id)) = (
+            &imported.external_id,
+            db.get_external_transaction_id(uid, aid, record.id)?,
+        ) {
+            if *imported_id != existing_id {
+                continue;
+            }
+        }
+        let existing = normalize_name(&db.get_participant(uid, aid, record.info.participant)?);
+        let similar = existing == participant
+            || (!existing.is_empty()
+                && !participant.is_empty()
+                && (existing.contains(&participant) || participant.contains(&existing)));
+        if !similar {
+            continue;
+        }
+        let distance = NaiveDate::parse_from_str(&record.info.date, "%Y-%m-%d")
+            .map(|d| (d - date).num_days().abs())
+            .unwrap_or(i64::MAX);
+        if closest.as_ref().is_none_or(|(_, d)| distance < *d) {
+            closest = Some((record, distance));
+        }
+    }
+    Ok(closest.map(|(record, _)| (record, false)))
+}
+
+// Compares participants regardless of case, spacing and punctuation, which
+// institutions write differently from one export to the next.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn ask_duplicate(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    imported: &ImportedEntry,
+    existing: &LedgerRecord,
+    exact: bool,
+) -> Result<DuplicateChoice> {
+    let entry = &imported.entry;
+    if exact {
+        println!("\nThis transaction has already been imported:");
+    } else {
+        println!("\nThis transaction may already be in the ledger:");
+    }
+    println!(
+        "  Importing: {}  {:>12}  {}  {}",
+        entry.date, entry.amount, entry.participant, entry.description
+    );
+    println!(
+        "  Existing:  {}  {:>12}  {}  {}",
+        existing.info.date,
+        existing.info.amount,
+        db.get_participant(uid, aid, existing.info.participant)?,
+        existing.info.description
+    );
+
+    let mut choices = vec![
+        ("Skip it", DuplicateChoice::Skip),
+        ("Merge it into the existing entry", DuplicateChoice::Merge),
+    ];
+    // an exact match cannot be kept, as the transaction id is recorded once
+    if !exact {
+        choices.push(("Keep both", DuplicateChoice::Keep));
+    }
+    choices.push((
+        "Skip it and all remaining duplicates",
+        DuplicateChoice::SkipAll,
+    ));
+    let options: Vec<&str> = choices.iter().map(|(label, _)| *label).collect();
+    let selected = Select::new("What would you like to do:", options)
+        .raw_prompt()
+        .unwrap();
+    Ok(choices[selected.index].1)
+}
+
+// Folds merged entries into the ledger entries they duplicate: the existing
+// entry takes the imported category and description, and the institution's
+// transaction id so that it is matched exactly on later imports.
+pub fn apply_merges(db: &DbConn, uid: u32, aid: u32, merges: &[MergedEntry]) -> Result<()> {
+    for merge in merges {
+        let entry = &merge.imported.entry;
+        let mut record = db.get_ledger_entry(uid, aid, merge.lid)?;
+        record.info.category_id =
+            db.check_and_add_category(uid, aid, entry.category.to_ascii_uppercase())?;
+        if !entry.description.trim().is_empty() {
+            record.info.description = entry.description.clone();
+        }
+        db.update_ledger_item(uid, aid, record)?;
+        if let Some(external_id) = &merge.imported.external_id {
+            if db
+                .get_external_transaction_id(uid, aid, merge.lid)?
+                .is_none()
+            {
+                db.add_external_transaction_id(uid, aid, merge.lid, external_id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Saves the column mapping and the link to the institution's account chosen
+// while reading the file, now that the import goes ahead.
+pub fn save_import_settings(db: &DbConn, uid: u32, aid: u32, batch: &ImportBatch) -> Result<()> {
+    if let Some(mapping) = &batch.mapping {
+        db.set_csv_mapping(uid, aid, mapping)?;
+    }
+    if let Some(account) = &batch.statement_account {
+        db.set_external_account(uid, &account.institution_id, &account.account_id, aid)?;
+    }
+    Ok(())
+}
+
+fn validate_entry(entry: &LedgerEntry) -> std::result::Result<(), String> {
+    if let Err(e) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
+        return Err(format!("date {} is not valid: {}", entry.date, e));
+    }
+    if entry.amount.is_negative() {
+        return Err(format!(
+            "amount {} is negative; the transfer type gives the direction",
+            entry.amount
+        ));
+    }
+    if entry.participant.trim().is_empty() {
+        return Err("participant is missing".to_string());
+    }
+    Ok(())
+}
+
+// Shows what the import would do and asks whether to go ahead with it.
+pub fn confirm_import(db: &DbConn, uid: u32, aid: u32, batch: &ImportBatch) -> Result<bool> {
+    println!("\nImport summary");
+    println!("  Entries to import: {}", batch.entries.len());
+    if !batch.errors.is_empty() {
+        println!(
+            "  Lines with errors, which will be skipped: {}",
+            batch.errors.len()
+        );
+        for error in batch.errors.iter() {
+            println!("    {}", error);
+        }
+    }
+    if batch.skipped > 0 {
+        println!("  Duplicates skipped: {}", batch.skipped);
+    }
+    if !batch.merges.is_empty() {
+        println!(
+            "  Duplicates merged into existing entries: {}",
+            batch.merges.len()
+        );
+    }
+    if batch.entries.is_empty() && batch.merges.is_empty() {
+        println!("Nothing to import.");
+        return Ok(false);
+    }
+
+    let mut new_participants: Vec<String> = Vec::new();
+    let mut new_categories: Vec<String> = Vec::new();
+    let mut deposits = Money::ZERO;
+    let mut withdrawals = Money::ZERO;
+    for imported in batch.entries.iter() {
+        let entry = &imported.entry;
+        if !new_participants.contains(&entry.participant)
+            && !db.participant_exists(uid, aid, &entry.participant)?
+        {
+            new_participants.push(entry.participant.clone());
+        }
+        let category = entry.category.to_ascii_uppercase();
+        if !new_categories.contains(&category)
+            && db.get_category_id(aid, category.clone(), uid).is_err()
+        {
+            new_categories.push(category);
+        }
+        if entry.transfer_type.is_deposit() {
+            deposits += entry.amount;
+        } else if entry.transfer_type.is_withdrawal() {
+            withdrawals += entry.amount;
+        }
+    }
+    if !new_participants.is_empty() {
+        println!("  New participants: {}", new_participants.join(", "));
+    }
+    if !new_categories.is_empty() {
+        println!("  New categories: {}", new_categories.join(", "));
+    }
+    println!(
+        "  Net balance change: {} (deposits {}, withdrawals {})",
+        deposits - withdrawals,
+        deposits,
+        withdrawals
+    );
+
+    let mut prompt = format!("Import {} entries", batch.entries.len());
+    if !batch.merges.is_empty() {
+        prompt.push_str(&format!(", merge {} duplicates", batch.merges.len()));
+    }
+    if !batch.errors.is_empty() {
+        prompt.push_str(&format!(", skip {} lines with errors", batch.errors.len()));
+    }
+    prompt.push('?');
+    Ok(Confirm::new(&prompt).with_default(false).prompt().unwrap())
+}
+
+// Works out the format from the file's headers and content. fintool's own CSV
+// layout has no headers, so it is assumed when no other format matches.
+fn read_detected(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    path: &Path,
+    batch: &mut ImportBatch,
+) -> Result<Vec<ImportedEntry>> {
+    let contents = fs::read_to_string(path)?;
+    let converter: Box<dyn Converter> = match detect_converter(&contents) {
+        Some(converter) => converter,
+        None => match db.get_csv_mapping(uid, aid)? {
+            Some(mapping) if mapping.detect(&contents) => Box::new(mapping),
+            _ => return read_csv(path, &mut batch.errors),
+        },
+    };
+    println!("Reading {} as {}.", path.display(), converter.name());
+    if converter.id() == OfxConverter.id() {
+        return read_ofx(db, uid, aid, &contents, batch);
+    }
+    Ok(read_converted(
+        converter.as_ref(),
+        &contents,
+        &mut batch.errors,
+    ))
+}
+
+// Converts the file record by record. Records that cannot be converted are
+// reported with their line and the rest are still read.
+fn read_converted(
+    converter: &dyn Converter,
+    contents: &str,
+    errors: &mut Vec<ImportError>,
+) -> Vec<ImportedEntry> {
+    let mut entries = Vec::new();
+    for record in converter.convert_records(contents) {
+        match record.entries {
+            Ok(converted) => entries.extend(converted.into_iter().map(|entry| ImportedEntry {
+                entry,
+                external_id: None,
+                line: record.line,
+            })),
+            Err(e) => errors.push(ImportError {
+                line: record.line,
+                message: e.to_string(),
+            }),
+        }
+    }
+    entries
+}
+
+fn read_csv(path: &Path, errors: &mut Vec<ImportError>) -> Result<Vec<ImportedEntry>> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)?;
+    let mut entries = Vec::new();
+    for result in rdr.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(ImportError {
+                    line: e.position().map(|p| p.line()),
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line());
+        match record.deserialize::<LedgerEntry>(None) {
+            Ok(entry) => entries.push(ImportedEntry {
+                entry,
+                external_id: None,
+                line,
+            }),
+            Err(e) => errors.push(ImportError {
+                line,
+                message: e.to_string(),
+            }),
+        }
+    }
+    Ok(entries)
+}
+
+// Reads a CSV export with the account's saved column mapping, or with one the
+// user sets up when there is none or it does not fit the file.
+fn read_mapped(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    path: &Path,
+    batch: &mut ImportBatch,
+) -> Result<Vec<ImportedEntry>> {
+    let contents = fs::read_to_string(path)?;
+    let headers = csv_headers(&contents);
+    if headers.is_empty() {
+        return Err(FintoolError::Import(
+            "the file does not have a header row".to_string(),
+        ));
+    }
+
+    let saved = db
+        .get_csv_mapping(uid, aid)?
+        .filter(|mapping| mapping.detect(&contents));
+    let use_saved = saved.is_some()
+        && Confirm::new("Use the column mapping saved for this account?")
+            .with_default(true)
+            .prompt()
+            .unwrap();
+    let mapping = match saved {
+        Some(mapping) if use_saved => mapping,
+        _ => match map_columns(&headers, &contents) {
+            Some(mapping) => {
+                batch.mapping = Some(mapping.clone());
+                mapping
+            }
+            None => return Ok(Vec::new()),
+        },
+    };
+
+    Ok(read_converted(&mapping, &contents, &mut batch.errors))
+}
+
+// Asks which columns hold what until the preview of the file looks right.
+fn map_columns(headers: &[String], contents: &str) -> Option<CsvMapping> {
+    loop {
+        let date_column = select_column("Which column holds the date?", headers, false)?;
+        let mut formats: Vec<String> = DATE_FORMATS.iter().map(|f| f.to_string()).collect();
+        formats.push("Other".to_string());
+        let mut date_format = Select::new("How are the dates written?", formats)
+            .prompt()
+            .unwrap();
+        if date_format == "Other" {
+            date_format = Text::new("Enter the date format (e.g., %b %d, %Y):")
+                .prompt()
+                .unwrap();
+        }
+
+        let amount_column = select_column(
+            "Which column holds the amount (None if split into debit and credit)?",
+            headers,
+            true,
+        );
+        let (debit_column, credit_column) = match amount_column {
+            Some(_) => (None, None),
+            None => (
+                select_column("Which column holds debits?", headers, false),
+                select_column("Which column holds credits?", headers, false),
+            ),
+        };
+        let sign_convention = if amount_column.is_some() {
+            let conventions = vec![
+                "Negative amounts are withdrawals",
+                "Negative amounts are deposits",
+            ];
+            match Select::new("How are the amounts signed?", conventions)
+                .raw_prompt()
+                .unwrap()
+                .index
+            {
+                0 => SignConvention::NegativeIsWithdrawal,
+                _ => SignConvention::NegativeIsDeposit,
+            }
+        } else {
+            SignConvention::NegativeIsWithdrawal
+        };
+
+        let mapping = CsvMapping {
+            date_column,
+            date_format,
+            amount_column,
+            debit_column,
+            credit_column,
+            sign_convention,
+            payee_column: select_column("Which column holds the payee?", headers, true),
+            category_column: select_column("Which column holds the category?", headers, true),
+            description_column: select_column("Which column holds the description?", headers, true),
+        };
+
+        let mut errors = Vec::new();
+        let entries = read_converted(&mapping, contents, &mut errors);
+        println!("The first entries of {} read as:", entries.len());
+        for ImportedEntry { entry, .. } in entries.iter().take(PREVIEW_LEN) {
+            println!(
+                "  {}  {:>12}  {:<10}  {}  [{}]",
+                entry.date,
+                entry.amount,
+                if entry.transfer_type.is_withdrawal() {
+                    "Withdrawal"
+                } else {
+                    "Deposit"
+                },
+                entry.participant,
+                entry.category
+            );
+        }
+        if let Some(error) = errors.first() {
+            println!(
+                "{} lines cannot be read with this mapping. {}",
+                errors.len(),
+                error
+            );
+        }
+
+        let choices = vec!["Save mapping and import", "Change mapping", "Cancel"];
+        match Select::new("What would you like to do:", choices)
+            .raw_prompt()
+            .unwrap()
+            .index
+        {
+            0 => return Some(mapping),
+            1 => continue,
+            _ => return None,
+        }
+    }
+}
+
+fn select_column(prompt: &str, headers: &[String], optional: bool) -> Option<String> {
+    let mut options = headers.to_vec();
+    if optional {
+        options.push("None".to_string());
+    }
+    let selected = Select::new(prompt, options).raw_prompt().unwrap();
+    headers.get(selected.index).cloned()
+}
+
+// Reads the account's statement from an OFX or QFX file. Transactions whose
+// FITID was imported before are left out, so that overlapping statements can
+// be imported without doubling entries.
+fn read_ofx(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    text: &str,
+    batch: &mut ImportBatch,
+) -> Result<Vec<ImportedEntry>> {
+    let statements = parse_statements(text).map_err(|e| FintoolError::Import(e.to_string()))?;
+    let statement = match select_statement(db, uid, aid, statements)? {
+        Some((statement, linked)) => {
+            if !linked {
+                batch.statement_account = Some(statement.account.clone());
+            }
+            statement
+        }
+        None => return Ok(Vec::new()),
+    };
+
+    // a transaction listed twice in the file is only imported once
+    let mut entries = Vec::new();
+    let mut seen = HashSet::new();
+    for statement_entry in statement.entries {
+        if !seen.insert(statement_entry.fitid.clone()) {
+            continue;
+        }
+        entries.push(ImportedEntry {
+            entry: statement_entry.entry,
+            external_id: Some(statement_entry.fitid),
+            line: None,
+        });
+    }
+    Ok(entries)
+}
+
+// Finds the statement of the account among those in the file, and whether its
+// account is linked already. Each account at the institution is linked to a
+// fintool account the first time one of its statements is imported, and found
+// by that link afterwards.
+fn select_statement(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    statements: Vec<Statement>,
+) -> Result<Option<(Statement, bool)>> {
+    if statements.is_empty() {
+        return Err(FintoolError::Import(
+            "the file does not hold any statements".to_string(),
+        ));
+    }
+
+    let mut unlinked = Vec::new();
+    for statement in statements {
+        let account = &statement.account;
+        match db.get_external_account(uid, &account.institution_id, &account.account_id)? {
+            Some(linked) if linked == aid => return Ok(Some((statement, true))),
+            Some(_) => {}
+            None => unlinked.push(statement),
+        }
+    }
+    if unlinked.is_empty() {
+        println!("The statements in the file belong to other accounts.");
+        return Ok(None);
+    }
+
+    let mut options: Vec<String> = unlinked.iter().map(describe_statement).collect();
+    options.push("None".to_string());
+    let selected = Select::new("Which statement belongs to this account:", options)
+        .raw_prompt()
+        .unwrap();
+    if selected.index >= unlinked.len() {
+        return Ok(None);
+    }
+    Ok(Some((unlinked.swap_remove(selected.index), false)))
+}
+
+fn describe_statement(statement: &Statement) -> String {
+    let account = &statement.account;
+    let kind = match account.kind {
+        StatementKind::Bank => "Bank account",
+        StatementKind::Investment => "Investment account",
+    };
+    format!(
+        "{} {} at {} ({} transactions)",
+        kind,
+        account.account_id,
+        account.institution_id,
+        statement.entries.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_that_cannot_be_converted_are_reported() {
+        let mapping = CsvMapping {
+            date_column: "Date".to_string(),
+            date_format: "%m/%d/%Y".to_string(),
+            amount_column: Some("Amount".to_string()),
+            debit_column: None,
+            credit_column: None,
+            sign_convention: SignConvention::NegativeIsWithdrawal,
+            payee_column: Some("Payee".to_string()),
+            category_column: None,
+            description_column: None,
+        };
+        let contents = "Date,Amount,Payee\n\
+            01/02/2024,-5.00,Grocer\n\
+            yesterday,-1.00,Cafe\n\
+            01/03/2024,ten,Employer\n\
+            01/04/2024,100.00,Employer\n";
+
+        let mut errors = Vec::new();
+        let entries = read_converted(&mapping, contents, &mut errors);
+        let lines: Vec<Option<u64>> = entries.iter().map(|imported| imported.line).collect();
+        assert_eq!(lines, vec![Some(2), Some(5)]);
+        let lines: Vec<Option<u64>> = errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, vec![Some(3), Some(4)]);
+        assert!(errors[0].to_string().starts_with("Line 3: date yesterday"));
+    }
+}
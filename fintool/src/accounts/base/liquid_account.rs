@@ -15,9 +15,12 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use chrono::NaiveDate;
+use shared_lib::Money;
+
+use crate::error::Result;
 
 pub trait LiquidAccount {
-    fn get_positive_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32;
-    fn get_negative_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32;
-    fn get_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32;
+    fn get_positive_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money>;
+    fn get_negative_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money>;
+    fn get_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money>;
 }
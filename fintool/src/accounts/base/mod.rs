@@ -19,6 +19,7 @@ use crate::app::app::{App, DisplayValue};
 #[cfg(feature = "ratatui_support")]
 use crate::app::screen::ledger_table_constraint_len_calculator;
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::types::accounts::AccountRecord;
 use crate::types::accounts::AccountType;
 use crate::types::ledger::{DisplayableLedgerRecord, LedgerRecord};
@@ -39,13 +40,16 @@ use ratatui::{
     Frame,
 };
 use rusqlite::config::DbConfig;
+use shared_lib::{Money, Quantity};
 use std::any::Any;
 use strum::{Display, EnumIter, EnumString, FromRepr};
 use yahoo_finance_api::Quote;
 
 pub mod budget;
 pub mod charge_account;
+pub mod export;
 pub mod fixed_account;
+pub mod import;
 pub mod liquid_account;
 pub mod variable_account;
 
@@ -53,32 +57,38 @@ pub const KEY_TOTAL_VALUE: &str = "Current Value";
 pub const KEY_GROWTH: &str = "Growth";
 
 pub trait AccountCreation {
-    fn create(uid: u32, name: String, _db: &DbConn) -> AccountRecord;
+    fn create(uid: u32, name: String, _db: &DbConn) -> Result<AccountRecord>;
 }
 
 pub trait AccountOperations {
-    fn import(&mut self);
-    fn record(&mut self);
-    fn modify(&mut self);
-    fn export(&self);
-    fn report(&self);
-    fn link(&self, transacting_account: u32, ledger: LedgerRecord) -> Option<u32>;
+    fn import(&mut self) -> Result<()>;
+    fn record(&mut self) -> Result<()>;
+    fn modify(&mut self) -> Result<()>;
+    fn export(&self) -> Result<()>;
+    fn report(&self) -> Result<()>;
+    fn link(&self, transacting_account: u32, ledger: LedgerRecord) -> Result<Option<u32>>;
 }
 
 pub trait AccountData {
     fn get_id(&self) -> u32;
-    fn get_name(&self) -> String;
-    fn get_ledger(&self) -> Vec<LedgerRecord>;
-    fn get_ledger_within_dates(&self, start: NaiveDate, end: NaiveDate) -> Vec<LedgerRecord>;
-    fn get_displayable_ledger(&self) -> Vec<DisplayableLedgerRecord>;
-    fn get_value(&self) -> f32;
-    fn get_value_on_day(&self, day: NaiveDate) -> f32;
+    // the owner of the account, whose rows hold its data
+    fn get_uid(&self) -> u32;
+    fn get_name(&self) -> Result<String>;
+    fn get_ledger(&self) -> Result<Vec<LedgerRecord>>;
+    fn get_ledger_within_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<LedgerRecord>>;
+    fn get_displayable_ledger(&self) -> Result<Vec<DisplayableLedgerRecord>>;
+    fn get_value(&self) -> Result<Money>;
+    fn get_value_on_day(&self, day: NaiveDate) -> Result<Money>;
     fn get_open_date(&self) -> NaiveDate;
 }
 
 #[cfg(feature = "ratatui_support")]
 pub trait AccountUI: AccountData {
-    fn populate_page_cache_f32(&self, app: &mut App);
+    fn populate_page_cache_f32(&self, app: &mut App) -> Result<()>;
 
     fn render(&self, frame: &mut Frame, area: Rect, app: &mut App);
 
@@ -205,7 +215,7 @@ pub trait AccountUI: AccountData {
             .as_ref()
             .expect("Account's page has not been cached!")
             .get(KEY_TOTAL_VALUE)
-            .and_then(DisplayValue::as_f32)
+            .and_then(DisplayValue::as_money)
             .expect("Could not find current value!");
 
         let value = ratatuiText::styled(
@@ -297,5 +307,5 @@ struct StockData {
 #[derive(Debug, Clone)]
 struct SharesOwned {
     date: NaiveDate,
-    shares: f32,
+    shares: Quantity,
 }
@@ -20,9 +20,10 @@ use std::collections::HashMap;
 use std::io::Read;
 use std::sync::Arc;
 
-use chrono::{Date, Days, Local, NaiveDate, NaiveDateTime};
+use chrono::{Date, Days, Local, Months, NaiveDate, NaiveDateTime};
 use chrono::{Datelike, NaiveTime};
 use csv::DeserializeError;
+use inquire::validator::Validation;
 use inquire::*;
 use rusqlite::types::Value;
 use rustyline::validate::Validator;
@@ -32,20 +33,29 @@ use yahoo_finance_api::YahooError;
 
 use crate::accounts::base::{SharesOwned, StockData};
 use crate::database::DbConn;
+use crate::error::{FintoolError, Result};
 use crate::types::investments::{
-    SaleAllocationInfo, SaleAllocationRecord, StockInfo, StockRecord, StockSplitAllocationInfo,
-    StockSplitInfo, StockSplitRecord,
+    SaleAllocationInfo, SaleAllocationRecord, SaleMethod, StockInfo, StockRecord,
+    StockSplitAllocationInfo, StockSplitInfo, StockSplitRecord,
 };
 use crate::types::ledger::{LedgerInfo, LedgerRecord};
 use crate::types::participants::ParticipantAutoCompleter;
 use crate::types::participants::ParticipantType;
 use crate::types::stock_prices::StockPriceInfo;
 use crate::types::stock_prices::StockPriceRecord;
+use shared_lib::calendar::TradingCalendar;
 use shared_lib::stocks::{self, get_stock_history};
-use shared_lib::{LedgerEntry, TransferType};
+use shared_lib::{
+    DistributionType, LedgerEntry, Money, Quantity, TransferType, REINVESTMENT_CATEGORY,
+};
+use strum::IntoEnumIterator;
 
 use super::fixed_account::FixedAccount;
 
+// the shares of a sale taken from each lot, and the cost per share they carry
+// when it is not their lot's cost basis
+type SalePlan = (Vec<(StockRecord, Quantity)>, Option<Quantity>);
+
 pub struct VariableAccount {
     pub id: u32,
     pub uid: u32,
@@ -56,7 +66,7 @@ pub struct VariableAccount {
 }
 
 impl VariableAccount {
-    pub fn new(uid: u32, id: u32, db: &DbConn, open_date: NaiveDate) -> Self {
+    pub fn new(uid: u32, id: u32, db: &DbConn, open_date: NaiveDate) -> Result<Self> {
         let mut acct: VariableAccount = Self {
             id: id,
             uid: uid,
@@ -65,18 +75,17 @@ impl VariableAccount {
             buffer: None,
             open_date: open_date,
         };
-        acct.initialize_buffer();
-        acct
+        acct.initialize_buffer()?;
+        Ok(acct)
     }
 
-    pub fn initialize_buffer(&mut self) {
+    pub fn initialize_buffer(&mut self) -> Result<()> {
         // this is a quick hack to update the buffer after a stock has been purchased, sold or split
         let earliest_date = self.open_date;
         let latest_date = Local::now().date_naive();
 
-        let x = self.db.get_positions_by_ledger(self.id, self.uid).unwrap();
-        if x.is_some() {
-            let x = x.unwrap();
+        let x = self.db.get_positions_by_ledger(self.id, self.uid)?;
+        if let Some(x) = x {
             let mut tickers = x.iter().map(|x| x.0.clone()).collect::<Vec<String>>();
             tickers.dedup();
             let mut data: Vec<StockData> = Vec::new();
@@ -91,45 +100,46 @@ impl VariableAccount {
                 let date_shares = x
                     .iter()
                     .filter(|data| data.0 == ticker)
-                    .map(|x: &(String, String, f32)| {
-                        (SharesOwned {
-                            date: NaiveDate::parse_from_str(&&x.1, "%Y-%m-%d")
-                                .expect(format!("Unable to decode {}", &x.1).as_str()),
-                            shares: x.2.clone(),
+                    .map(|x: &(String, String, Quantity)| {
+                        Ok(SharesOwned {
+                            date: NaiveDate::parse_from_str(&x.1, "%Y-%m-%d")?,
+                            shares: x.2,
                         })
                     })
-                    .collect::<Vec<SharesOwned>>();
-                let quotes = buffer
+                    .collect::<Result<Vec<SharesOwned>>>()?;
+                let buffered = buffer
                     .iter()
                     .find(|x| x.ticker == ticker)
-                    .and_then(|x| Some(x.quotes.clone()));
-                let quotes = quotes
-                    .or_else(|| {
-                        Some({
-                            let pid = self
-                                .db
-                                .get_participant_id(
-                                    self.uid,
-                                    self.id,
-                                    ticker.clone(),
-                                    ParticipantType::Payee,
-                                )
-                                .unwrap();
-                            let manual_prices = self
-                                .db
-                                .check_and_get_stock_price_record_matching_from_participant_id(
-                                    self.uid, self.id, pid,
-                                )
-                                .unwrap();
-                            if manual_prices.is_empty() {
-                                get_stock_history(ticker.clone(), earliest_date, latest_date)
-                                    .unwrap()
-                            } else {
-                                Self::convert_stock_price_record_to_quotes(&manual_prices)
-                            }
-                        })
-                    })
-                    .unwrap();
+                    .map(|x| x.quotes.clone());
+                let quotes = match buffered {
+                    Some(quotes) => quotes,
+                    None => {
+                        let pid = self
+                            .db
+                            .get_participant_id(
+                                self.uid,
+                                self.id,
+                                ticker.clone(),
+                                ParticipantType::Payee,
+                            )?
+                            .ok_or_else(|| {
+                                FintoolError::Validation(format!(
+                                    "tracked ticker {} is missing from the people table",
+                                    ticker
+                                ))
+                            })?;
+                        let manual_prices = self
+                            .db
+                            .check_and_get_stock_price_record_matching_from_participant_id(
+                                self.uid, self.id, pid,
+                            )?;
+                        if manual_prices.is_empty() {
+                            get_stock_history(ticker.clone(), earliest_date, latest_date)?
+                        } else {
+                            Self::convert_stock_price_record_to_quotes(&manual_prices)
+                        }
+                    }
+                };
                 data.push(StockData {
                     ticker: ticker.clone(),
                     quotes: quotes,
@@ -138,21 +148,22 @@ impl VariableAccount {
             }
             self.buffer = Some(data);
         }
+        Ok(())
     }
 
     pub fn purchase_stock(
         &mut self,
         initial_opt: Option<StockRecord>,
         overwrite_entry: bool,
-    ) -> Option<LedgerRecord> {
+    ) -> Result<Option<LedgerRecord>> {
         let purchase: LedgerInfo;
         let defaults_to_use: bool;
         let mut initial: StockRecord = StockRecord {
             id: 0,
             info: StockInfo {
-                shares: 0.0,
-                costbasis: 0.0,
-                remaining: 0.0,
+                shares: Quantity::ZERO,
+                costbasis: Quantity::ZERO,
+                remaining: Quantity::ZERO,
                 ledger_id: 0,
             },
             txn_opt: None,
@@ -172,7 +183,7 @@ impl VariableAccount {
                 .txn_opt
                 .expect("Ledger information not populated!")
                 .participant;
-            let initial_ticker = self.db.get_participant(self.uid, self.id, pid).unwrap();
+            let initial_ticker = self.db.get_participant(self.uid, self.id, pid)?;
             let entered_ticker = Text::new(ticker_msg)
                 .with_default(initial_ticker.as_str())
                 .with_autocomplete(ParticipantAutoCompleter {
@@ -219,16 +230,18 @@ impl VariableAccount {
                 self.id,
                 ticker.clone(),
                 ParticipantType::Payee,
-            );
+            )?;
             if let Some(pid) = pid_opt {
                 let stock_is_tracked = self
                     .db
                     .check_and_get_stock_price_record_matching_from_participant_id(
                         self.uid, self.id, pid,
-                    )
-                    .unwrap();
+                    )?;
                 if stock_is_tracked.is_empty() {
-                    panic!("Non-public ticker does not have a stock price record!");
+                    return Err(FintoolError::Validation(format!(
+                        "Non-public ticker {} does not have a stock price record!",
+                        ticker
+                    )));
                 }
             } else {
                 let manual_entry = Confirm::new(
@@ -239,7 +252,7 @@ impl VariableAccount {
 
                 if !manual_entry {
                     println!("Stock was not purchased!");
-                    return None;
+                    return Ok(None);
                 }
             }
             true
@@ -247,14 +260,6 @@ impl VariableAccount {
             false
         };
 
-        let pid = self.db.check_and_add_participant(
-            self.uid,
-            self.id,
-            ticker.clone(),
-            ParticipantType::Payee,
-            false,
-        );
-
         let date_msg = "Enter date of purchase:";
         let date_input = if defaults_to_use {
             let initial_date = initial
@@ -263,7 +268,7 @@ impl VariableAccount {
                 .expect("Ledger information not populated!")
                 .date;
             DateSelect::new(date_msg)
-                .with_default(NaiveDate::parse_from_str(&initial_date, "%Y-%m-%d").unwrap())
+                .with_default(NaiveDate::parse_from_str(&initial_date, "%Y-%m-%d")?)
                 .prompt()
                 .unwrap()
                 .format("%Y-%m-%d")
@@ -277,46 +282,57 @@ impl VariableAccount {
         };
 
         let shares_msg = "Enter number of shares purchased:";
-        let shares: f32 = if defaults_to_use {
-            CustomType::<f32>::new(shares_msg)
+        let shares: Quantity = if defaults_to_use {
+            CustomType::<Quantity>::new(shares_msg)
                 .with_placeholder("0.00")
                 .with_default(initial.info.shares)
                 .with_error_message("Please enter a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(shares_msg)
+            CustomType::<Quantity>::new(shares_msg)
                 .with_placeholder("0.00")
-                .with_default(0.00)
+                .with_default(Quantity::ZERO)
                 .with_error_message("Please enter a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
         let costbasis_msg = "Enter cost basis of shares purchased:";
-        let costbasis: f32 = if defaults_to_use {
-            CustomType::<f32>::new(costbasis_msg)
+        let costbasis: Quantity = if defaults_to_use {
+            CustomType::<Quantity>::new(costbasis_msg)
                 .with_placeholder("0.00")
                 .with_default(initial.info.costbasis)
                 .with_error_message("Please enter a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(costbasis_msg)
+            CustomType::<Quantity>::new(costbasis_msg)
                 .with_placeholder("0.00")
-                .with_default(0.00)
+                .with_default(Quantity::ZERO)
                 .with_error_message("Please enter a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        let pid = self.db.check_and_add_participant(
+            self.uid,
+            self.id,
+            ticker.clone(),
+            ParticipantType::Payee,
+            false,
+        )?;
+
         let cid = self
             .db
-            .check_and_add_category(self.uid, self.id, "buy".to_ascii_uppercase());
+            .check_and_add_category(self.uid, self.id, "buy".to_ascii_uppercase())?;
 
         purchase = LedgerInfo {
             date: date_input.clone(),
-            amount: shares * costbasis,
+            amount: shares.value_at(costbasis),
             transfer_type: TransferType::WithdrawalToInternalAccount,
             participant: pid.clone(),
             category_id: cid,
@@ -331,19 +347,18 @@ impl VariableAccount {
 
         let ledger_id = if defaults_to_use && overwrite_entry {
             self.db
-                .update_ledger_item(
-                    self.uid,
-                    self.id,
-                    LedgerRecord {
-                        id: initial.info.ledger_id,
-                        info: purchase.clone(),
-                    },
-                )
-                .unwrap()
+                .remove_stock_purchase(self.uid, self.id, initial.id)?;
+            self.db.update_ledger_item(
+                self.uid,
+                self.id,
+                LedgerRecord {
+                    id: initial.info.ledger_id,
+                    info: purchase.clone(),
+                },
+            )?
         } else {
             self.db
-                .add_ledger_entry(self.uid, self.id, purchase.clone())
-                .unwrap()
+                .add_ledger_entry(self.uid, self.id, purchase.clone())?
         };
 
         let stock_record = StockInfo {
@@ -361,34 +376,32 @@ impl VariableAccount {
             };
 
             self.db
-                .add_stock_price(self.uid, self.id, stock_price_info)
-                .unwrap();
+                .add_stock_price(self.uid, self.id, stock_price_info)?;
         }
 
         self.db
-            .add_stock_purchase(self.uid, self.id, stock_record)
-            .unwrap();
-
-        self.initialize_buffer();
+            .add_stock_purchase(self.uid, self.id, stock_record)?;
 
-        return Some(LedgerRecord {
+        transaction.commit()?;
+        self.initialize_buffer()?;
+        return Ok(Some(LedgerRecord {
             id: ledger_id,
             info: purchase.clone(),
-        });
+        }));
     }
 
     pub fn sell_stock(
         &mut self,
         initial_opt: Option<StockRecord>,
         overwrite_entry: bool,
-    ) -> Option<LedgerRecord> {
+    ) -> Result<Option<LedgerRecord>> {
         let defaults_to_use: bool;
         let mut initial: StockRecord = StockRecord {
             id: 0,
             info: StockInfo {
-                shares: 0.0,
-                costbasis: 0.0,
-                remaining: 0.0,
+                shares: Quantity::ZERO,
+                costbasis: Quantity::ZERO,
+                remaining: Quantity::ZERO,
                 ledger_id: 0,
             },
             txn_opt: None,
@@ -403,7 +416,7 @@ impl VariableAccount {
 
         let ticker: String;
         let ticker_msg = "Select which stock you would like to record a sale of:";
-        let mut tickers_undup = self.db.get_stock_tickers(self.uid, self.id).unwrap();
+        let mut tickers_undup = self.db.get_stock_tickers(self.uid, self.id)?;
         tickers_undup.sort();
         tickers_undup.dedup();
         let tickers = tickers_undup;
@@ -413,7 +426,7 @@ impl VariableAccount {
                 .txn_opt
                 .expect("Ledger information not populated!")
                 .participant;
-            let initial_ticker = self.db.get_participant(self.uid, self.id, pid).unwrap();
+            let initial_ticker = self.db.get_participant(self.uid, self.id, pid)?;
             Select::new(ticker_msg, tickers)
                 .with_starting_filter_input(initial_ticker.as_str())
                 .prompt()
@@ -428,14 +441,6 @@ impl VariableAccount {
                 .to_ascii_uppercase()
         };
 
-        let pid = self.db.check_and_add_participant(
-            self.uid,
-            self.id,
-            ticker.clone(),
-            ParticipantType::Payer,
-            false,
-        );
-
         let date_msg = "Enter date of sale:";
         let sale_date = if defaults_to_use {
             let initial_date = initial
@@ -444,7 +449,7 @@ impl VariableAccount {
                 .expect("Ledger information not populated!")
                 .date;
             DateSelect::new(date_msg)
-                .with_default(NaiveDate::parse_from_str(&initial_date, "%Y-%m-%d").unwrap())
+                .with_default(NaiveDate::parse_from_str(&initial_date, "%Y-%m-%d")?)
                 .prompt()
                 .unwrap()
                 .format("%Y-%m-%d")
@@ -456,43 +461,89 @@ impl VariableAccount {
         };
 
         let price_msg = "Enter sale price (per share):";
-        let sale_price: f32 = if defaults_to_use {
-            CustomType::<f32>::new(price_msg)
+        let sale_price: Quantity = if defaults_to_use {
+            CustomType::<Quantity>::new(price_msg)
                 .with_placeholder("00000.00")
                 .with_default(initial.info.costbasis)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(price_msg)
+            CustomType::<Quantity>::new(price_msg)
                 .with_placeholder("00000.00")
-                .with_default(00000.00)
+                .with_default(Quantity::ZERO)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
         let shares_msg = "Enter quantity sold:";
-        let number_of_shares_sale: f32 = if defaults_to_use {
-            CustomType::<f32>::new(shares_msg)
+        let number_of_shares_sale: Quantity = if defaults_to_use {
+            CustomType::<Quantity>::new(shares_msg)
                 .with_placeholder("00000.00")
                 .with_default(initial.info.shares)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(shares_msg)
+            CustomType::<Quantity>::new(shares_msg)
                 .with_placeholder("00000.00")
-                .with_default(00000.00)
+                .with_default(Quantity::ZERO)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         };
 
-        let value_received = number_of_shares_sale * sale_price;
+        // an edited sale is allocated the way it was before unless the user
+        // chooses otherwise
+        let mut previous_method = None;
+        let mut previous_lots = Vec::new();
+        if defaults_to_use && overwrite_entry {
+            previous_method = Some(
+                self.db
+                    .get_stock_sale_method(self.uid, self.id, initial.id)?,
+            );
+            previous_lots = match self
+                .db
+                .get_stock_sale_allocation_for_sale_id(self.uid, self.id, initial.id)
+            {
+                Ok(records) => records.into_iter().map(|record| record.info).collect(),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Vec::new(),
+                Err(error) => return Err(error.into()),
+            };
+        }
+
+        let sale_methods: Vec<SaleMethod> = SaleMethod::iter().collect();
+        let starting_method = sale_methods
+            .iter()
+            .position(|method| Some(*method) == previous_method)
+            .unwrap_or(0);
+        let sell_method = Select::new("Select sale methodology:", sale_methods)
+            .with_starting_cursor(starting_method)
+            .prompt()
+            .unwrap();
+        let lots = self.plan_sale_lots(
+            ticker.clone(),
+            number_of_shares_sale,
+            sell_method,
+            &previous_lots,
+        )?;
+
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        let pid = self.db.check_and_add_participant(
+            self.uid,
+            self.id,
+            ticker.clone(),
+            ParticipantType::Payer,
+            false,
+        )?;
+
+        let value_received = number_of_shares_sale.value_at(sale_price);
         let stock_cid =
             self.db
-                .check_and_add_category(self.uid, self.id, "sale".to_ascii_uppercase());
+                .check_and_add_category(self.uid, self.id, "sale".to_ascii_uppercase())?;
 
         let sale = LedgerInfo {
             date: sale_date.to_string(),
@@ -510,66 +561,57 @@ impl VariableAccount {
         };
 
         let ledger_id: u32 = if defaults_to_use && overwrite_entry {
-            self.db
-                .update_ledger_item(
-                    self.uid,
-                    self.id,
-                    LedgerRecord {
-                        id: initial.info.ledger_id,
-                        info: sale.clone(),
-                    },
-                )
-                .unwrap()
+            self.db.update_ledger_item(
+                self.uid,
+                self.id,
+                LedgerRecord {
+                    id: initial.info.ledger_id,
+                    info: sale.clone(),
+                },
+            )?
         } else {
-            self.db
-                .add_ledger_entry(self.uid, self.id, sale.clone())
-                .unwrap()
+            self.db.add_ledger_entry(self.uid, self.id, sale.clone())?
         };
 
         let sale_record = StockInfo {
             shares: number_of_shares_sale,
             costbasis: sale_price,
-            remaining: 0.0,
+            remaining: Quantity::ZERO,
             ledger_id: ledger_id,
         };
 
+        // an edited sale is taken back from its lots before it is allocated
+        // again
+        if defaults_to_use && overwrite_entry {
+            self.deallocate_sale_stock(initial.id)?;
+            self.db
+                .remove_stock_sale(self.uid, self.id, initial.info.ledger_id)?;
+        }
+
         let sale_id = self
             .db
-            .add_stock_sale(self.uid, self.id, sale_record.clone())
-            .unwrap();
+            .add_stock_sale(self.uid, self.id, sale_record.clone())?;
 
-        let sale_info = StockRecord {
-            id: sale_id,
-            info: sale_record.clone(),
-            txn_opt: Some(sale.clone()),
-        };
+        self.apply_sale_lots(sale_id, sell_method, lots)?;
 
-        const SALE_METHOD_OPTIONS: [&'static str; 2] = ["LIFO", "FIFO"];
-        let sell_method: String =
-            Select::new("Select sale methodology:", SALE_METHOD_OPTIONS.to_vec())
-                .prompt()
-                .unwrap()
-                .to_string();
-
-        self.allocate_sale_stock(sale_info, sell_method);
-        self.initialize_buffer();
-
-        return Some(LedgerRecord {
+        transaction.commit()?;
+        self.initialize_buffer()?;
+        return Ok(Some(LedgerRecord {
             id: ledger_id,
             info: sale.clone(),
-        });
+        }));
     }
 
     pub fn split_stock(
         &mut self,
         initial_opt: Option<StockSplitRecord>,
         overwrite_entry: bool,
-    ) -> Option<LedgerRecord> {
+    ) -> Result<Option<LedgerRecord>> {
         let defaults_to_use: bool;
         let mut initial: StockSplitRecord = StockSplitRecord {
             id: 0,
             info: StockSplitInfo {
-                split: 0.0,
+                split: Quantity::ZERO,
                 ledger_id: 0,
             },
             txn_opt: None,
@@ -582,7 +624,7 @@ impl VariableAccount {
             defaults_to_use = false;
         }
 
-        let mut tickers_undup = self.db.get_stock_tickers(self.uid, self.id).unwrap();
+        let mut tickers_undup = self.db.get_stock_tickers(self.uid, self.id)?;
         tickers_undup.sort();
         tickers_undup.dedup();
         tickers_undup.push("None".to_string());
@@ -594,7 +636,7 @@ impl VariableAccount {
                 .txn_opt
                 .expect("Ledger information not populated!")
                 .participant;
-            let initial_ticker: String = self.db.get_participant(self.uid, self.id, pid).unwrap();
+            let initial_ticker: String = self.db.get_participant(self.uid, self.id, pid)?;
             Select::new(ticker_msg, tickers)
                 .with_starting_filter_input(&initial_ticker.as_str())
                 .prompt()
@@ -608,18 +650,18 @@ impl VariableAccount {
         };
 
         if ticker == "None" {
-            return None;
+            return Ok(None);
         }
 
         let split_msg = "Enter split factor:";
-        let split: f32 = if defaults_to_use {
-            CustomType::<f32>::new(split_msg)
+        let split: Quantity = if defaults_to_use {
+            CustomType::<Quantity>::new(split_msg)
                 .with_default(initial.info.split)
                 .with_error_message("Please type a valid amount!")
                 .prompt()
                 .unwrap()
         } else {
-            CustomType::<f32>::new(split_msg)
+            CustomType::<Quantity>::new(split_msg)
                 .with_placeholder("2.0")
                 .with_error_message("Please type a valid amount!")
                 .prompt()
@@ -650,34 +692,43 @@ impl VariableAccount {
                 .to_string()
         };
 
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        // an edited split is undone before it is applied again
+        if defaults_to_use && overwrite_entry {
+            self.deallocate_stock_split(initial.clone())?;
+        }
+
         let pid = self.db.check_and_add_participant(
             self.uid,
             self.id,
             ticker.clone(),
             ParticipantType::Both,
             false,
-        );
+        )?;
         let cid = self.db.check_and_add_category(
             self.uid,
             self.id,
             "stock dividend/split".to_ascii_uppercase(),
-        );
+        )?;
 
         // if split is for a manually entered stock,
         // then all previous stock price records
         // need to be updated
         let price_records = self
             .db
-            .check_and_get_stock_price_record_matching_from_participant_id(self.uid, self.id, pid)
-            .unwrap();
+            .check_and_get_stock_price_record_matching_from_participant_id(
+                self.uid, self.id, pid,
+            )?;
         if !price_records.is_empty() {
             self.db
-                .apply_stock_split_to_stock_prices(self.uid, self.id, pid, split);
+                .apply_stock_split_to_stock_prices(self.uid, self.id, pid, split)?;
         }
 
         let ledger_entry = LedgerInfo {
             date: split_date.clone(),
-            amount: 0.0,
+            amount: Money::ZERO,
             transfer_type: TransferType::ZeroSumChange,
             participant: pid,
             category_id: cid,
@@ -690,26 +741,22 @@ impl VariableAccount {
         };
 
         let lid = if defaults_to_use && overwrite_entry {
-            self.db
-                .update_ledger_item(
-                    self.uid,
-                    self.id,
-                    LedgerRecord {
-                        id: initial.info.ledger_id,
-                        info: ledger_entry.clone(),
-                    },
-                )
-                .unwrap()
+            self.db.update_ledger_item(
+                self.uid,
+                self.id,
+                LedgerRecord {
+                    id: initial.info.ledger_id,
+                    info: ledger_entry.clone(),
+                },
+            )?
         } else {
             self.db
-                .add_ledger_entry(self.uid, self.id, ledger_entry.clone())
-                .unwrap()
+                .add_ledger_entry(self.uid, self.id, ledger_entry.clone())?
         };
 
         let stock_split_id = self
             .db
-            .add_stock_split(self.uid, self.id, split.clone(), lid)
-            .unwrap();
+            .add_stock_split(self.uid, self.id, split.clone(), lid)?;
 
         let stock_split_record = StockSplitRecord {
             id: stock_split_id,
@@ -720,30 +767,251 @@ impl VariableAccount {
             txn_opt: Some(ledger_entry.clone()),
         };
 
-        self.allocate_stock_split(stock_split_record);
-        self.initialize_buffer();
+        self.allocate_stock_split(stock_split_record)?;
 
-        return Some(LedgerRecord {
+        transaction.commit()?;
+        self.initialize_buffer()?;
+        return Ok(Some(LedgerRecord {
             id: lid,
             info: ledger_entry,
-        });
+        }));
     }
 
-    pub fn modify(&mut self, record: LedgerRecord) -> Option<LedgerRecord> {
+    // Records a dividend, capital gain distribution or interest paid by a
+    // holding and, if it was reinvested, the purchase it paid for.
+    pub fn record_distribution(&mut self) -> Result<Option<LedgerRecord>> {
+        let kind = Select::new(
+            "What kind of distribution would you like to record?",
+            DistributionType::iter().collect(),
+        )
+        .prompt()
+        .unwrap();
+
+        let mut tickers = match self.db.get_stock_tickers(self.uid, self.id) {
+            Ok(tickers) => tickers,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                println!("No holdings found to record a distribution of!");
+                return Ok(None);
+            }
+            Err(error) => return Err(error.into()),
+        };
+        tickers.sort();
+        tickers.dedup();
+        tickers.push("None".to_string());
+        let ticker = Select::new("Select which holding paid the distribution:", tickers)
+            .prompt()
+            .unwrap();
+        if ticker == "None" {
+            return Ok(None);
+        }
+
+        let date = DateSelect::new("Enter date of distribution:")
+            .prompt()
+            .unwrap()
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let amount = CustomType::<Money>::new("Enter amount of distribution:")
+            .with_placeholder("0.00")
+            .with_default(Money::ZERO)
+            .with_error_message("Please enter a valid amount!")
+            .prompt()
+            .unwrap();
+
+        let reinvest = Confirm::new(format!("Was the {} reinvested in {}?", kind, ticker).as_str())
+            .with_default(false)
+            .prompt()
+            .unwrap();
+        let reinvestment = if reinvest {
+            let price = CustomType::<Quantity>::new("Enter price per share reinvested at:")
+                .with_placeholder("0.00")
+                .with_error_message("Please enter a valid amount!")
+                .prompt()
+                .unwrap();
+            let estimated_shares = if price.is_zero() {
+                Quantity::ZERO
+            } else {
+                Quantity::from(amount)
+                    .checked_div(price)
+                    .unwrap_or(Quantity::ZERO)
+            };
+            let shares = CustomType::<Quantity>::new("Enter number of shares purchased:")
+                .with_placeholder("0.00")
+                .with_default(estimated_shares)
+                .with_error_message("Please enter a valid amount!")
+                .prompt()
+                .unwrap();
+            Some((shares, price, self.confirm_public_ticker(ticker.clone())))
+        } else {
+            None
+        };
+
+        // nothing is written until all input has been collected, so that the
+        // database is not locked while waiting on the user
+        let transaction = self.db.begin_transaction()?;
+        let payer = self.db.check_and_add_participant(
+            self.uid,
+            self.id,
+            ticker.clone(),
+            ParticipantType::Payer,
+            false,
+        )?;
+        let cid = self
+            .db
+            .check_and_add_category(self.uid, self.id, kind.category())?;
+        let distribution = LedgerInfo {
+            date: date.clone(),
+            amount,
+            transfer_type: TransferType::DepositFromInternalAccount,
+            participant: payer,
+            category_id: cid,
+            description: format!(
+                "[Internal] {} of ${} from {} on {}.",
+                kind, amount, ticker, date
+            ),
+        };
+        let distribution_lid = self
+            .db
+            .add_ledger_entry(self.uid, self.id, distribution.clone())?;
+        self.db
+            .add_stock_distribution(self.uid, self.id, distribution_lid, kind)?;
+
+        if let Some((shares, price, public_ticker)) = reinvestment {
+            self.reinvest_distribution(
+                distribution_lid,
+                &ticker,
+                &date,
+                amount,
+                shares,
+                price,
+                public_ticker,
+            )?;
+        }
+
+        transaction.commit()?;
+        self.initialize_buffer()?;
+        Ok(Some(LedgerRecord {
+            id: distribution_lid,
+            info: distribution,
+        }))
+    }
+
+    // Buys shares of the holding with the distribution at distribution_lid.
+    fn reinvest_distribution(
+        &self,
+        distribution_lid: u32,
+        ticker: &String,
+        date: &String,
+        amount: Money,
+        shares: Quantity,
+        price: Quantity,
+        public_ticker: bool,
+    ) -> Result<u32> {
+        let payee = self.db.check_and_add_participant(
+            self.uid,
+            self.id,
+            ticker.clone(),
+            ParticipantType::Payee,
+            false,
+        )?;
+        let cid =
+            self.db
+                .check_and_add_category(self.uid, self.id, REINVESTMENT_CATEGORY.to_string())?;
+        let purchase = LedgerInfo {
+            date: date.clone(),
+            amount,
+            transfer_type: TransferType::WithdrawalToInternalAccount,
+            participant: payee,
+            category_id: cid,
+            description: format!(
+                "[Internal] Reinvest in {} shares of {} at ${} on {}.",
+                shares, ticker, price, date
+            ),
+        };
+        let lid = self.db.add_ledger_entry(self.uid, self.id, purchase)?;
+        self.db.add_stock_purchase(
+            self.uid,
+            self.id,
+            StockInfo {
+                shares,
+                costbasis: price,
+                remaining: shares,
+                ledger_id: lid,
+            },
+        )?;
+        self.db
+            .add_stock_reinvestment(self.uid, self.id, lid, distribution_lid)?;
+
+        if !public_ticker {
+            self.db.add_stock_price(
+                self.uid,
+                self.id,
+                StockPriceInfo {
+                    date: date.clone(),
+                    stock_ticker_peer_id: payee,
+                    price_per_unit_share: price,
+                },
+            )?;
+        }
+        Ok(lid)
+    }
+
+    // Called for every imported entry that is not a stock purchase, sale or
+    // split, to remember the deposits that are distributions of a holding.
+    pub fn record_imported_distribution(
+        &self,
+        lid: u32,
+        transfer_type: TransferType,
+        category: &str,
+    ) -> Result<()> {
+        if transfer_type != TransferType::DepositFromInternalAccount {
+            return Ok(());
+        }
+        if let Some(kind) = DistributionType::from_category(category) {
+            self.db
+                .add_stock_distribution(self.uid, self.id, lid, kind)?;
+        }
+        Ok(())
+    }
+
+    // Called for every imported purchase, to link the reinvestments to the
+    // distribution of the same holding and day that paid for them.
+    pub fn link_imported_reinvestment(
+        &self,
+        lid: u32,
+        ticker: &str,
+        date: &str,
+        category: &str,
+    ) -> Result<()> {
+        if !category.trim().eq_ignore_ascii_case(REINVESTMENT_CATEGORY) {
+            return Ok(());
+        }
+        if let Some(distribution_lid) = self
+            .db
+            .get_unreinvested_distribution(self.uid, self.id, ticker, date)?
+        {
+            self.db
+                .add_stock_reinvestment(self.uid, self.id, lid, distribution_lid)?;
+        }
+        Ok(())
+    }
+
+    pub fn modify(&mut self, record: LedgerRecord) -> Result<Option<LedgerRecord>> {
         let was_stock_purchase_opt = self
             .db
             .check_and_get_stock_purchase_record_matching_from_ledger_id(
                 self.uid, self.id, record.id,
-            )
-            .unwrap();
+            )?;
         let was_stock_sale_opt = self
             .db
-            .check_and_get_stock_sale_record_matching_from_ledger_id(self.uid, self.id, record.id)
-            .unwrap();
+            .check_and_get_stock_sale_record_matching_from_ledger_id(
+                self.uid, self.id, record.id,
+            )?;
         let was_stock_split_opt = self
             .db
-            .check_and_get_stock_split_record_matching_from_ledger_id(self.uid, self.id, record.id)
-            .unwrap();
+            .check_and_get_stock_split_record_matching_from_ledger_id(
+                self.uid, self.id, record.id,
+            )?;
 
         let mut is_stock_purchase: bool = false;
         let mut is_stock_sale: bool = false;
@@ -751,9 +1019,9 @@ impl VariableAccount {
         let mut stock_record: StockRecord = StockRecord {
             id: 0,
             info: StockInfo {
-                shares: 0.0,
-                costbasis: 0.0,
-                remaining: 0.0,
+                shares: Quantity::ZERO,
+                costbasis: Quantity::ZERO,
+                remaining: Quantity::ZERO,
                 ledger_id: 0,
             },
             txn_opt: None,
@@ -761,7 +1029,7 @@ impl VariableAccount {
         let mut split_record: StockSplitRecord = StockSplitRecord {
             id: 0,
             info: StockSplitInfo {
-                split: 0.0,
+                split: Quantity::ZERO,
                 ledger_id: 0,
             },
             txn_opt: None,
@@ -770,7 +1038,7 @@ impl VariableAccount {
             && was_stock_sale_opt.is_none()
             && was_stock_split_opt.is_none()
         {
-            return Some(self.fixed.modify(record));
+            return Ok(Some(self.fixed.modify(record)?));
         }
 
         if was_stock_purchase_opt.is_some() {
@@ -793,43 +1061,34 @@ impl VariableAccount {
             .unwrap();
         match modify_choice {
             "Update" => {
+                // the edited entry is replaced once its new values are entered
                 if is_stock_purchase {
-                    self.db
-                        .remove_stock_purchase(self.uid, self.id, stock_record.id)
-                        .unwrap();
                     return self.purchase_stock(Some(stock_record), true);
                 } else if is_stock_sale {
-                    self.deallocate_sale_stock(stock_record.id);
-                    self.db
-                        .remove_stock_sale(self.uid, self.id, stock_record.info.ledger_id)
-                        .unwrap();
                     return self.sell_stock(Some(stock_record), true);
                 } else {
-                    // split stock
-                    self.deallocate_stock_split(split_record.clone());
                     return self.split_stock(Some(split_record.clone()), true);
                 }
             }
             "Remove" => {
+                let transaction = self.db.begin_transaction()?;
                 if is_stock_purchase {
                     self.db
-                        .remove_ledger_item(self.uid, self.id, stock_record.info.ledger_id)
-                        .unwrap();
+                        .remove_ledger_item(self.uid, self.id, stock_record.info.ledger_id)?;
                 } else if is_stock_sale {
-                    self.deallocate_sale_stock(stock_record.clone().id);
+                    self.deallocate_sale_stock(stock_record.clone().id)?;
                     self.db
-                        .remove_ledger_item(self.uid, self.id, stock_record.info.ledger_id)
-                        .unwrap();
+                        .remove_ledger_item(self.uid, self.id, stock_record.info.ledger_id)?;
                 } else {
-                    self.deallocate_stock_split(split_record.clone());
+                    self.deallocate_stock_split(split_record.clone())?;
                     self.db
-                        .remove_ledger_item(self.uid, self.id, split_record.info.ledger_id)
-                        .unwrap();
+                        .remove_ledger_item(self.uid, self.id, split_record.info.ledger_id)?;
                 }
-                return Some(record);
+                transaction.commit()?;
+                return Ok(Some(record));
             }
             "None" => {
-                return Some(record);
+                return Ok(Some(record));
             }
             _ => {
                 panic!("Input not recognized!");
@@ -837,99 +1096,218 @@ impl VariableAccount {
         }
     }
 
-    pub fn allocate_sale_stock(&self, record: StockRecord, method: String) {
-        let stocks: Vec<StockRecord>;
-        let ticker = self
-            .db
-            .get_participant(
-                self.uid,
-                self.id,
-                record
-                    .txn_opt
-                    .expect("Transaction required but not found!")
-                    .participant,
-            )
-            .unwrap();
-        match method.as_str() {
-            "LIFO" => {
-                stocks = self
-                    .db
-                    .get_stock_history_ascending(self.uid, self.id, ticker)
-                    .unwrap();
+    pub fn allocate_sale_stock(&self, record: StockRecord, method: SaleMethod) -> Result<()> {
+        let ticker = self.db.get_participant(
+            self.uid,
+            self.id,
+            record
+                .txn_opt
+                .expect("Transaction required but not found!")
+                .participant,
+        )?;
+        let lots = self.plan_sale_lots(ticker, record.info.shares, method, &[])?;
+        self.apply_sale_lots(record.id, method, lots)
+    }
+
+    // Works out how many shares of a sale of the ticker come from each open
+    // lot, in the order of the method, and what they cost when it is not
+    // their lot's cost basis. The lots an edited sale was allocated from
+    // before are counted as open again, and specific identification offers
+    // them as the defaults.
+    fn plan_sale_lots(
+        &self,
+        ticker: String,
+        shares: Quantity,
+        method: SaleMethod,
+        previous: &[SaleAllocationInfo],
+    ) -> Result<SalePlan> {
+        let mut stocks = match method {
+            SaleMethod::Lifo => self
+                .db
+                .get_stock_history_descending(self.uid, self.id, ticker)?,
+            _ => self
+                .db
+                .get_stock_history_ascending(self.uid, self.id, ticker)?,
+        };
+        for stock in stocks.iter_mut() {
+            stock.info.remaining += previous
+                .iter()
+                .filter(|lot| lot.purchase_id == stock.id)
+                .map(|lot| lot.quantity)
+                .sum::<Quantity>();
+        }
+        // can't sell what you don't have
+        stocks.retain(|stock| !stock.info.remaining.is_zero());
+        if method == SaleMethod::HighestCost {
+            stocks.sort_by(|x, y| y.info.costbasis.cmp(&x.info.costbasis));
+        }
+
+        // shares sold at average cost are still the oldest held, so that
+        // they keep the holding period of the lots they came from
+        let average_cost = match method {
+            SaleMethod::AverageCost => {
+                let held: Quantity = stocks.iter().map(|stock| stock.info.remaining).sum();
+                stocks
+                    .iter()
+                    .map(|stock| stock.info.remaining.value_at(stock.info.costbasis))
+                    .sum::<Money>()
+                    .checked_div(held)
             }
-            "FIFO" => {
-                stocks = self
-                    .db
-                    .get_stock_history_descending(self.uid, self.id, ticker)
-                    .unwrap();
+            _ => None,
+        };
+        let lots = match method {
+            SaleMethod::SpecificIdentification => {
+                self.select_sale_lots(stocks, shares, previous)?
             }
             _ => {
-                panic!("Unrecognized input!");
+                let mut num_shares_remaining_to_allocate = shares;
+                let mut lots = Vec::new();
+                for stock in stocks {
+                    // if there are no shares to allocate, we are done here and
+                    // all sales are accounted for
+                    if num_shares_remaining_to_allocate.is_zero() {
+                        break;
+                    }
+                    let num_shares_allocated =
+                        std::cmp::min(stock.info.remaining, num_shares_remaining_to_allocate);
+                    num_shares_remaining_to_allocate -= num_shares_allocated;
+                    lots.push((stock, num_shares_allocated));
+                }
+                lots
             }
-        }
-
-        let mut num_shares_remaining_to_allocate = record.info.shares;
-        let mut num_shares_allocated: f32;
-        for mut stock in stocks {
-            let purchase_id = stock.id;
+        };
+        Ok((lots, average_cost))
+    }
 
-            // can't sell what you don't have
-            if stock.info.remaining == 0.0 {
+    // Takes the shares of the sale from the lots planned for it, at the cost
+    // planned for them if any.
+    fn apply_sale_lots(
+        &self,
+        sale_id: u32,
+        method: SaleMethod,
+        (lots, cost): SalePlan,
+    ) -> Result<()> {
+        self.db
+            .set_stock_sale_method(self.uid, self.id, sale_id, method)?;
+        for (stock, num_shares_allocated) in lots {
+            if num_shares_allocated.is_zero() {
                 continue;
             }
-
-            if stock.info.remaining > num_shares_remaining_to_allocate {
-                stock.info.remaining = stock.info.remaining - num_shares_remaining_to_allocate;
-                num_shares_allocated = num_shares_remaining_to_allocate;
-            } else {
-                num_shares_allocated = stock.info.remaining;
-                stock.info.remaining = 0.0;
-            }
-            self.db
-                .update_stock_remaining(self.uid, self.id, stock.id, stock.info.remaining)
-                .unwrap();
-            self.db
-                .add_stock_sale_allocation(
+            self.db.update_stock_remaining(
+                self.uid,
+                self.id,
+                stock.id,
+                stock.info.remaining - num_shares_allocated,
+            )?;
+            let allocation = self.db.add_stock_sale_allocation(
+                self.uid,
+                self.id,
+                stock.id,
+                sale_id,
+                num_shares_allocated,
+            )?;
+            if let Some(cost) = cost {
+                self.db.set_stock_sale_allocation_basis(
                     self.uid,
                     self.id,
-                    purchase_id,
-                    record.id,
-                    num_shares_allocated,
+                    allocation,
+                    num_shares_allocated.value_at(cost),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // Asks how many shares of the sale came from each open lot.
+    fn select_sale_lots(
+        &self,
+        stocks: Vec<StockRecord>,
+        shares: Quantity,
+        previous: &[SaleAllocationInfo],
+    ) -> Result<Vec<(StockRecord, Quantity)>> {
+        let held: Quantity = stocks.iter().map(|stock| stock.info.remaining).sum();
+        let shares = std::cmp::min(shares, held);
+        loop {
+            println!("Select the lots the {} shares sold came from:", shares);
+            let mut unallocated = shares;
+            let mut lots = Vec::new();
+            for stock in stocks.iter() {
+                if unallocated.is_zero() {
+                    break;
+                }
+                let purchase = self
+                    .db
+                    .get_ledger_entry(self.uid, self.id, stock.info.ledger_id)?;
+                let most = std::cmp::min(stock.info.remaining, unallocated);
+                let default = previous
+                    .iter()
+                    .filter(|lot| lot.purchase_id == stock.id)
+                    .map(|lot| lot.quantity)
+                    .sum::<Quantity>();
+                let quantity = CustomType::<Quantity>::new(
+                    format!(
+                        "Shares from the lot bought {} ({} left at ${}):",
+                        purchase.info.date, stock.info.remaining, stock.info.costbasis
+                    )
+                    .as_str(),
                 )
+                .with_placeholder("0.00")
+                .with_default(std::cmp::min(default, most))
+                .with_validator(move |quantity: &Quantity| {
+                    if quantity.is_negative() || *quantity > most {
+                        Ok(Validation::Invalid(
+                            format!("Please enter at most {} shares!", most).into(),
+                        ))
+                    } else {
+                        Ok(Validation::Valid)
+                    }
+                })
+                .with_error_message("Please enter a valid amount!")
+                .prompt()
                 .unwrap();
-            num_shares_remaining_to_allocate =
-                num_shares_remaining_to_allocate - num_shares_allocated;
-
-            // if there are no shares to allocate, we are done here and all sales
-            // are accounted for
-            if num_shares_remaining_to_allocate == 0.0 {
-                break;
+                unallocated -= quantity;
+                lots.push((stock.clone(), quantity));
+            }
+            if unallocated.is_zero() {
+                return Ok(lots);
             }
+            println!(
+                "{} shares were not taken from any lot! Please select the lots again.",
+                unallocated
+            );
         }
     }
 
-    fn deallocate_sale_stock(&self, sale_id: u32) {
-        let stock_allocation_records = self
+    fn deallocate_sale_stock(&self, sale_id: u32) -> Result<()> {
+        let stock_allocation_records = match self
             .db
             .get_stock_sale_allocation_for_sale_id(self.uid, self.id, sale_id)
-            .unwrap();
+        {
+            Ok(records) => records,
+            Err(rusqlite::Error::QueryReturnedNoRows) => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
         for record in stock_allocation_records {
             // add shares back to ledger
-            let _ = self
-                .db
-                .add_to_stock_remaining(
-                    self.uid,
-                    self.id,
-                    record.info.purchase_id,
-                    record.info.quantity,
-                )
-                .unwrap();
+            let _ = self.db.add_to_stock_remaining(
+                self.uid,
+                self.id,
+                record.info.purchase_id,
+                record.info.quantity,
+            )?;
             self.db
-                .remove_stock_sale_allocation(self.uid, self.id, record.id);
+                .remove_stock_sale_allocation(self.uid, self.id, record.id)?;
         }
+        Ok(())
     }
 
-    pub fn allocate_stock_split(&self, record: StockSplitRecord) {
+    pub fn allocate_stock_split(&self, record: StockSplitRecord) -> Result<()> {
+        // undo the split factor, which is never zero for a recorded split
+        let unsplit = |value: Quantity| {
+            value.checked_div(record.info.split).ok_or_else(|| {
+                FintoolError::Validation(format!("stock split {} has a zero ratio", record.id))
+            })
+        };
         if record.txn_opt.is_none() {
             panic!(
                 "Expected ledger data matching stock split id: {}",
@@ -940,55 +1318,45 @@ impl VariableAccount {
 
         let ticker = self
             .db
-            .get_participant(self.uid, self.id, split_txn.participant)
-            .unwrap();
-        let stock_purchase_records = self.db.get_stocks(self.uid, self.id, ticker).unwrap();
+            .get_participant(self.uid, self.id, split_txn.participant)?;
+        let stock_purchase_records = self.db.get_stocks(self.uid, self.id, ticker)?;
 
-        let mut sales_to_update: Vec<(u32, f32)> = Vec::new();
+        let mut sales_to_update: Vec<(u32, Quantity)> = Vec::new();
         for stock in stock_purchase_records {
             // update shares so it looks like we have always purchased those
-            self.db
-                .update_stock_shares_purchased(
-                    self.uid,
-                    self.id,
-                    stock.id,
-                    stock.info.shares * record.info.split,
-                )
-                .unwrap();
-            self.db
-                .update_stock_remaining(
-                    self.uid,
-                    self.id,
-                    stock.id,
-                    stock.info.remaining * record.info.split,
-                )
-                .unwrap();
-            self.db
-                .update_cost_basis(
-                    self.uid,
-                    self.id,
-                    stock.id,
-                    stock.info.costbasis / record.info.split,
-                )
-                .unwrap();
-            self.db
-                .add_stock_split_allocation(
-                    self.uid,
-                    self.id,
-                    StockSplitAllocationInfo {
-                        stock_split_id: record.id,
-                        stock_purchase_id: stock.id,
-                    },
-                )
-                .unwrap();
+            self.db.update_stock_shares_purchased(
+                self.uid,
+                self.id,
+                stock.id,
+                stock.info.shares * record.info.split,
+            )?;
+            self.db.update_stock_remaining(
+                self.uid,
+                self.id,
+                stock.id,
+                stock.info.remaining * record.info.split,
+            )?;
+            self.db.update_cost_basis(
+                self.uid,
+                self.id,
+                stock.id,
+                unsplit(stock.info.costbasis)?,
+            )?;
+            self.db.add_stock_split_allocation(
+                self.uid,
+                self.id,
+                StockSplitAllocationInfo {
+                    stock_split_id: record.id,
+                    stock_purchase_id: stock.id,
+                },
+            )?;
 
             // if stock was part of sale, we need to increase number of stocks sold by factor
             let sale_allocations_opt = self
                 .db
                 .check_and_get_stock_sale_allocation_record_matching_from_purchase_id(
                     self.uid, self.id, stock.id,
-                )
-                .unwrap();
+                )?;
             if sale_allocations_opt.is_none() {
                 // no sale allocations founds
                 continue;
@@ -1001,8 +1369,7 @@ impl VariableAccount {
                         self.uid,
                         self.id,
                         sale_allocation.info.sale_id,
-                    )
-                    .unwrap();
+                    )?;
                 if sale_txn_opt.is_none() {
                     panic!(
                         "Stock stale record not found for sale id: {}",
@@ -1021,14 +1388,12 @@ impl VariableAccount {
                 if sale_txn.date > split_txn.date {
                     continue;
                 }
-                self.db
-                    .update_stock_sale_allocation_quantity(
-                        self.uid,
-                        self.id,
-                        sale_allocation.id,
-                        sale_allocation.info.quantity * record.info.split,
-                    )
-                    .unwrap();
+                self.db.update_stock_sale_allocation_quantity(
+                    self.uid,
+                    self.id,
+                    sale_allocation.id,
+                    sale_allocation.info.quantity * record.info.split,
+                )?;
                 sales_to_update.push((stock_sale.id, stock_sale.info.shares));
             }
         }
@@ -1037,18 +1402,27 @@ impl VariableAccount {
             sales_to_update.sort_by(|a, b| (a.0).cmp(&b.0));
             sales_to_update.dedup_by(|a, b| a.0 == b.0);
             for sale in sales_to_update {
-                self.db
-                    .update_stock_shares_sold(self.uid, self.id, sale.0, sale.1 * record.info.split)
-                    .unwrap();
+                self.db.update_stock_shares_sold(
+                    self.uid,
+                    self.id,
+                    sale.0,
+                    sale.1 * record.info.split,
+                )?;
             }
         }
+        Ok(())
     }
 
-    fn deallocate_stock_split(&self, record: StockSplitRecord) {
+    fn deallocate_stock_split(&self, record: StockSplitRecord) -> Result<()> {
+        // undo the split factor, which is never zero for a recorded split
+        let unsplit = |value: Quantity| {
+            value.checked_div(record.info.split).ok_or_else(|| {
+                FintoolError::Validation(format!("stock split {} has a zero ratio", record.id))
+            })
+        };
         let mut stock_split_alloc_records = self
             .db
-            .get_stock_split_allocation_for_stock_split_id(self.uid, self.id, record.id)
-            .unwrap();
+            .get_stock_split_allocation_for_stock_split_id(self.uid, self.id, record.id)?;
         // remove the highest ids first
         stock_split_alloc_records.sort_by(|a, b| (b.id).cmp(&a.id));
 
@@ -1070,11 +1444,10 @@ impl VariableAccount {
                     self.uid,
                     self.id,
                     alloc_record.info.stock_purchase_id,
-                )
-                .unwrap()
+                )?
                 .expect("Stock record not returned");
 
-            let updated_shares = stock_purchase.info.remaining / record.info.split;
+            let updated_shares = unsplit(stock_purchase.info.remaining)?;
             let updated_costbasis = stock_purchase.info.costbasis * record.info.split;
 
             let _ = self.db.update_stock_remaining(
@@ -1103,8 +1476,7 @@ impl VariableAccount {
                     self.uid,
                     self.id,
                     alloc_record.info.stock_purchase_id,
-                )
-                .unwrap();
+                )?;
             if sale_allocations_opt.is_some() {
                 let sale_allocations = sale_allocations_opt.unwrap();
                 for sale_allocation in sale_allocations {
@@ -1114,8 +1486,7 @@ impl VariableAccount {
                             self.uid,
                             self.id,
                             sale_allocation.info.sale_id,
-                        )
-                        .unwrap();
+                        )?;
                     if stock_sale_opt.is_none() {
                         panic!(
                             "Stock stale record not found for sale id: {}",
@@ -1135,33 +1506,29 @@ impl VariableAccount {
                     if sale_txn.date > split_txn.date {
                         continue;
                     }
-                    self.db
-                        .update_stock_sale_allocation_quantity(
-                            self.uid,
-                            self.id,
-                            sale_allocation.id,
-                            sale_allocation.info.quantity / record.info.split,
-                        )
-                        .unwrap();
+                    self.db.update_stock_sale_allocation_quantity(
+                        self.uid,
+                        self.id,
+                        sale_allocation.id,
+                        unsplit(sale_allocation.info.quantity)?,
+                    )?;
                     sales_to_update.push((stock_sale.id, stock_sale.info.shares));
                 }
             }
             self.db
-                .remove_stock_split_allocation(self.uid, self.id, alloc_record.id)
-                .unwrap();
+                .remove_stock_split_allocation(self.uid, self.id, alloc_record.id)?;
         }
         if !sales_to_update.is_empty() {
             sales_to_update.sort_by(|a, b| (a.0).cmp(&b.0));
             sales_to_update.dedup_by(|a, b| a.0 == b.0);
             for sale in sales_to_update {
                 self.db
-                    .update_stock_shares_sold(self.uid, self.id, sale.0, sale.1 / record.info.split)
-                    .unwrap();
+                    .update_stock_shares_sold(self.uid, self.id, sale.0, unsplit(sale.1)?)?;
             }
         }
         self.db
-            .remove_stock_split(self.uid, self.id, record.info.ledger_id)
-            .unwrap();
+            .remove_stock_split(self.uid, self.id, record.info.ledger_id)?;
+        Ok(())
     }
 
     pub fn confirm_public_ticker(&self, ticker: String) -> bool {
@@ -1175,19 +1542,18 @@ impl VariableAccount {
         }
     }
 
-    pub fn get_current_value(&self) -> f32 {
+    pub fn get_current_value(&self) -> Result<Money> {
         let today = Local::now().date_naive();
-        return self
+        let fixed = self
             .db
-            .get_cumulative_total_of_ledger_on_date(self.uid, self.id, today)
-            .unwrap()
-            .unwrap()
-            + self.get_value_of_positions_on_day(&today);
+            .get_cumulative_total_of_ledger_on_date(self.uid, self.id, today)?
+            .unwrap_or(Money::ZERO);
+        Ok(fixed + self.get_value_of_positions_on_day(&today))
         // return self.fixed.get_current_value() + self.get_value_of_positions_on_day(&today);
     }
 
     pub fn time_weighted_return(&self, period_start: NaiveDate, period_end: NaiveDate) -> f32 {
-        let mut cf: f32 = 0.0;
+        let mut cf = Money::ZERO;
         let mut hps: Vec<f32> = Vec::new();
         let mut hp: f32;
         let mut vf;
@@ -1210,11 +1576,8 @@ impl VariableAccount {
 
         // let starting_fixed_value = self.fixed.get_value_on_day(period_start);
 
-        let starting_variable_value = self.get_value_of_positions_on_day(
-            &period_start
-                .checked_sub_days(Days::new(1))
-                .expect("Invalid date!"),
-        );
+        let starting_variable_value = self
+            .get_value_of_positions_on_day(&TradingCalendar::previous_trading_day(period_start));
 
         vi = starting_fixed_value + starting_variable_value;
 
@@ -1228,15 +1591,28 @@ impl VariableAccount {
         );
         if let Some(transactions) = external_transactions {
             if !transactions.is_empty() {
-                vf = 0.0;
-                for txn in transactions {
+                vf = Money::ZERO;
+                for (i, txn) in transactions.iter().enumerate() {
                     let end_period = NaiveDate::parse_from_str(&txn.info.date, "%Y-%m-%d")
                         .expect(format!("Invalid date format: {}", txn.info.date).as_str());
-                    cf = match txn.info.transfer_type {
-                        TransferType::DepositFromExternalAccount => txn.info.amount,
-                        TransferType::WithdrawalToExternalAccount => -txn.info.amount,
-                        _ => 0.0,
-                    };
+                    cf = cf
+                        + match txn.info.transfer_type {
+                            TransferType::DepositFromExternalAccount => txn.info.amount,
+                            TransferType::WithdrawalToExternalAccount => -txn.info.amount,
+                            _ => Money::ZERO,
+                        };
+
+                    // cash flows that no market close separates belong to the
+                    // same sub-period, since the positions are valued identically
+                    if let Some(next_txn) = transactions.get(i + 1) {
+                        let next_date = NaiveDate::parse_from_str(&next_txn.info.date, "%Y-%m-%d")
+                            .expect(
+                                format!("Invalid date format: {}", next_txn.info.date).as_str(),
+                            );
+                        if TradingCalendar::trading_days_between(end_period, next_date) == 0 {
+                            continue;
+                        }
+                    }
                     let final_fixed_value_opt = self
                         .db
                         .get_cumulative_total_of_ledger_on_date(self.uid, self.id, end_period)
@@ -1252,10 +1628,11 @@ impl VariableAccount {
                     let final_variable_value = self.get_value_of_positions_on_day(&end_period);
                     // println!("Day: {}, Fixed: {}, Variable: {}", end_period.to_string(), final_fixed_value, final_variable_value);
                     vf = final_fixed_value + final_variable_value;
-                    hp = (vf - (cf + vi)) / (cf + vi);
+                    hp = ((vf - (cf + vi)).to_f64() / (cf + vi).to_f64()) as f32;
                     hps.push(hp);
 
                     vi = vf;
+                    cf = Money::ZERO;
                 }
             }
         }
@@ -1275,7 +1652,7 @@ impl VariableAccount {
         let final_variable_value = self.get_value_of_positions_on_day(&period_end);
         // println!("Day: {}, Fixed: {}, Variable: {}", period_end.to_string(), final_fixed_value, final_variable_value);
         vf = final_fixed_value + final_variable_value;
-        hp = (vf - vi) / vi;
+        hp = ((vf - vi).to_f64() / vi.to_f64()) as f32;
         hps.push(hp);
 
         let hp1 = hps.pop().expect("No valid cash flow periods!");
@@ -1285,22 +1662,26 @@ impl VariableAccount {
         return rate;
     }
 
-    pub fn annualized_rate_of_return(&self, period_start: NaiveDate, period_end: NaiveDate) -> f32 {
+    pub fn annualized_rate_of_return(
+        &self,
+        period_start: NaiveDate,
+        period_end: NaiveDate,
+    ) -> Result<f32> {
         let days = period_start.num_days_from_ce() - period_end.num_days_from_ce();
-        let end_value_opt = self.get_account_value_on_day(&period_end);
+        let end_value_opt = self.get_account_value_on_day(&period_end)?;
         if end_value_opt.is_none() {
-            return f32::NAN;
+            return Ok(f32::NAN);
         }
         let end_value = end_value_opt.unwrap();
 
-        let start_value_opt = self.get_account_value_on_day(&period_start);
+        let start_value_opt = self.get_account_value_on_day(&period_start)?;
         if start_value_opt.is_none() {
-            return f32::NAN;
+            return Ok(f32::NAN);
         }
         let start_value = start_value_opt.unwrap();
 
-        let cr = (end_value - start_value) / start_value;
-        return (1. + cr).powf(365. / (days as f32)) - 1.;
+        let cr = ((end_value - start_value).to_f64() / start_value.to_f64()) as f32;
+        Ok((1. + cr).powf(365. / (days as f32)) - 1.)
     }
 
     pub fn money_weighted_return(&self, period_start: NaiveDate, period_end: NaiveDate) -> f32 {
@@ -1358,7 +1739,7 @@ impl VariableAccount {
                 .num_days() as f32
                 / 365.25;
             let cf = CashFlow {
-                amount: amount,
+                amount: amount.to_f64() as f32,
                 t: t,
             };
             cfs.push(cf);
@@ -1367,7 +1748,7 @@ impl VariableAccount {
         let final_value = self.get_value_of_positions_on_day(&period_end);
         let final_t = (period_end - period_start).num_days() as f32 / 365.25;
         cfs.push(CashFlow {
-            amount: final_value,
+            amount: final_value.to_f64() as f32,
             t: final_t,
         });
 
@@ -1376,108 +1757,148 @@ impl VariableAccount {
         return irr(&cfs).unwrap();
     }
 
-    pub fn get_positions(&self) -> Option<Vec<(String, f32)>> {
-        return self.db.get_positions(self.uid, self.id).unwrap();
+    pub fn get_positions(&self) -> Result<Option<Vec<(String, Quantity)>>> {
+        Ok(self.db.get_positions(self.uid, self.id)?)
     }
 
-    pub fn get_value_of_positions_on_day(&self, day: &NaiveDate) -> f32 {
-        let mut value: f32 = 0.0;
+    pub fn get_value_of_positions_on_day(&self, day: &NaiveDate) -> Money {
+        let mut value = Money::ZERO;
         if let Some(buffer) = self.buffer.as_ref() {
             for e in buffer {
-                let mut owned_shares = e
-                    .history
-                    .iter()
-                    .filter(|x| x.date <= *day)
-                    .collect::<Vec<&SharesOwned>>();
-                if owned_shares.is_empty() {
-                    // if no shares owned before date, then just continue 0
-                    continue;
-                }
-                owned_shares.sort_by(|x, y| (x.date).cmp(&y.date));
-                let most_recently_owned = owned_shares.last().unwrap();
-                let quotes = e
-                    .quotes
-                    .iter()
-                    .filter(|x| {
-                        let date = OffsetDateTime::from_unix_timestamp(x.timestamp as i64)
-                            .unwrap()
-                            .date();
-                        let ndate = NaiveDate::from_ymd_opt(
-                            date.year(),
-                            date.month() as u32,
-                            date.day() as u32,
-                        )
-                        .unwrap();
-                        ndate < *day
-                    })
-                    .collect::<Vec<&Quote>>();
-                let quote_opt = quotes.last();
-                if quote_opt.is_none() {
-                    continue;
-                }
-                let quote = quote_opt.unwrap();
-                let partial_value = (quote.close * most_recently_owned.shares as f64) as f32;
-                value = value + partial_value
+                value = value + Self::value_of_position_on_day(e, day);
             }
         }
         return value;
     }
 
-    pub fn get_account_value_on_day(&self, day: &NaiveDate) -> Option<f32> {
-        let mut value: f32 = 0.0;
-        if let Some(buffer) = self.buffer.as_ref() {
-            for e in buffer {
-                let mut owned_shares = e
-                    .history
-                    .iter()
-                    .filter(|x| x.date <= *day)
-                    .collect::<Vec<&SharesOwned>>();
-                if owned_shares.is_empty() {
-                    // if no shares owned before date, then just continue 0
-                    continue;
-                }
-                owned_shares.sort_by(|x, y| (x.date).cmp(&y.date));
-                let most_recently_owned = owned_shares.last().unwrap();
-                let quote = e
-                    .quotes
-                    .iter()
-                    .filter(|x| {
-                        let date = OffsetDateTime::from_unix_timestamp(x.timestamp as i64)
-                            .unwrap()
-                            .date();
-                        let ndate = NaiveDate::from_ymd_opt(
-                            date.year(),
-                            date.month() as u32,
-                            date.day() as u32,
-                        )
+    pub fn get_value_of_position_on_day(&self, ticker: &str, day: &NaiveDate) -> Money {
+        self.buffer
+            .iter()
+            .flatten()
+            .filter(|e| e.ticker == ticker)
+            .map(|e| Self::value_of_position_on_day(e, day))
+            .sum()
+    }
+
+    fn value_of_position_on_day(e: &StockData, day: &NaiveDate) -> Money {
+        let mut owned_shares = e
+            .history
+            .iter()
+            .filter(|x| x.date <= *day)
+            .collect::<Vec<&SharesOwned>>();
+        if owned_shares.is_empty() {
+            // if no shares owned before date, then just continue 0
+            return Money::ZERO;
+        }
+        owned_shares.sort_by(|x, y| (x.date).cmp(&y.date));
+        let most_recently_owned = owned_shares.last().unwrap();
+        let quotes = e
+            .quotes
+            .iter()
+            .filter(|x| {
+                let date = OffsetDateTime::from_unix_timestamp(x.timestamp as i64)
+                    .unwrap()
+                    .date();
+                let ndate =
+                    NaiveDate::from_ymd_opt(date.year(), date.month() as u32, date.day() as u32)
                         .unwrap();
-                        ndate < *day
-                    })
-                    .last()
-                    .expect(
-                        format!("No quote matching date {}", most_recently_owned.date).as_str(),
-                    );
-                let partial_value = (quote.close * most_recently_owned.shares as f64) as f32;
-                // println!("\tTicker: {}, Shares: {}, Price: {}, Total : {}", e.ticker, most_recently_owned.shares, quote.close, partial_value);
-                // println!("\t\tMost recent date: {}", OffsetDateTime::from_unix_timestamp(quote.timestamp as i64).unwrap().date());
-                value = value + partial_value
-            }
+                ndate < *day
+            })
+            .collect::<Vec<&Quote>>();
+        let quote_opt = quotes.last();
+        if quote_opt.is_none() {
+            return Money::ZERO;
         }
+        let quote = quote_opt.unwrap();
+        most_recently_owned
+            .shares
+            .value_at(Quantity::from_f64(quote.close))
+    }
+
+    pub fn get_account_value_on_day(&self, day: &NaiveDate) -> Result<Option<Money>> {
         let fixed_value = self
             .db
-            .get_cumulative_total_of_ledger_on_date(self.uid, self.id, *day)
-            .unwrap();
-        // let fixed_value = self.fixed.get_value_on_day(*day);
-        if let Some(fixed) = fixed_value {
-            value = value + fixed;
-        } else {
-            return None;
+            .get_cumulative_total_of_ledger_on_date(self.uid, self.id, *day)?;
+        Ok(fixed_value.map(|fixed| fixed + self.get_value_of_positions_on_day(day)))
+    }
+
+    // Prints the dividends, capital gain distributions and interest each
+    // holding has paid, how much of it was reinvested and its yield over the
+    // last twelve months at the holding's current value.
+    pub fn report_income(&self) -> Result<()> {
+        let distributions = self.db.get_distributions(self.uid, self.id)?;
+        if distributions.is_empty() {
+            println!("\nNo distributions found!");
+            return Ok(());
         }
-        // value = value + fixed_value;
-        return Some(value);
+
+        let today = Local::now().date_naive();
+        let year_ago = today.checked_sub_months(Months::new(12)).unwrap();
+        let mut tickers: Vec<String> = distributions.iter().map(|d| d.ticker.clone()).collect();
+        tickers.sort();
+        tickers.dedup();
+
+        println!(
+            "\n{:<10} {:>12} {:>14} {:>12} {:>12} {:>12} {:>8}",
+            "Holding", "Dividends", "Capital Gains", "Interest", "Total", "Reinvested", "Yield"
+        );
+        let mut totals = [Money::ZERO; 5];
+        for ticker in tickers {
+            let paid = distributions.iter().filter(|d| d.ticker == ticker);
+            let by_kind = |kind: DistributionType| -> Money {
+                paid.clone()
+                    .filter(|d| d.kind == kind)
+                    .map(|d| d.amount)
+                    .sum()
+            };
+            let row = [
+                by_kind(DistributionType::Dividend),
+                by_kind(DistributionType::CapitalGain),
+                by_kind(DistributionType::Interest),
+                paid.clone().map(|d| d.amount).sum(),
+                paid.clone().map(|d| d.reinvested).sum(),
+            ];
+            let trailing: Money = paid
+                .clone()
+                .filter(|d| {
+                    NaiveDate::parse_from_str(&d.date, "%Y-%m-%d")
+                        .is_ok_and(|date| date > year_ago && date <= today)
+                })
+                .map(|d| d.amount)
+                .sum();
+            let value = self.get_value_of_position_on_day(&ticker, &today);
+            let yield_pct = if value.is_zero() {
+                "-".to_string()
+            } else {
+                format!("{:.2}%", trailing.to_f64() / value.to_f64() * 100.0)
+            };
+            println!(
+                "{:<10} {:>12} {:>14} {:>12} {:>12} {:>12} {:>8}",
+                ticker,
+                row[0].to_string(),
+                row[1].to_string(),
+                row[2].to_string(),
+                row[3].to_string(),
+                row[4].to_string(),
+                yield_pct
+            );
+            for (total, amount) in totals.iter_mut().zip(row) {
+                *total += amount;
+            }
+        }
+        println!(
+            "{:<10} {:>12} {:>14} {:>12} {:>12} {:>12}",
+            "Total",
+            totals[0].to_string(),
+            totals[1].to_string(),
+            totals[2].to_string(),
+            totals[3].to_string(),
+            totals[4].to_string()
+        );
+        Ok(())
     }
 
-    pub fn manually_record_stock_close_price(&self) {
+    pub fn manually_record_stock_close_price(&self) -> Result<()> {
         let ticker = Text::new("What ticker are you recording for?")
             .with_autocomplete(ParticipantAutoCompleter {
                 uid: self.uid,
@@ -1497,10 +1918,10 @@ impl VariableAccount {
             ticker.clone(),
             ParticipantType::Payee,
             false,
-        );
+        )?;
 
         let date = DateSelect::new("Enter date to record:").prompt().unwrap();
-        let close_price = CustomType::<f32>::new(
+        let close_price = CustomType::<Quantity>::new(
             format!(
                 "Enter close price per unit share on {}:",
                 date.to_string().clone()
@@ -1514,7 +1935,8 @@ impl VariableAccount {
             price_per_unit_share: close_price,
             date: date.to_string(),
         };
-        self.db.add_stock_price(self.uid, self.id, info).unwrap();
+        self.db.add_stock_price(self.uid, self.id, info)?;
+        Ok(())
     }
 
     fn convert_stock_price_record_to_quotes(
@@ -1532,14 +1954,141 @@ impl VariableAccount {
                 timestamp: timestamp,
                 // right now, the user doesn't store this information in the database
                 // because it might not be available, so set it to the known value.
-                open: r.info.price_per_unit_share as f64,
-                high: r.info.price_per_unit_share as f64,
-                low: r.info.price_per_unit_share as f64,
-                close: r.info.price_per_unit_share as f64,
+                open: r.info.price_per_unit_share.to_f64(),
+                high: r.info.price_per_unit_share.to_f64(),
+                low: r.info.price_per_unit_share.to_f64(),
+                close: r.info.price_per_unit_share.to_f64(),
                 volume: 0,
-                adjclose: r.info.price_per_unit_share as f64,
+                adjclose: r.info.price_per_unit_share.to_f64(),
             });
         }
         return quotes;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::gains::HoldingTerm;
+    use crate::types::accounts::{AccountInfo, AccountType};
+
+    #[test]
+    fn average_cost_sales_keep_the_holding_period_of_the_oldest_lots() {
+        let db = DbConn::new(":memory:").unwrap();
+        let uid = db.add_user("user".to_string(), true).unwrap();
+        let aid = db
+            .add_account(
+                uid,
+                &AccountInfo {
+                    atype: AccountType::Investment,
+                    name: "Brokerage".to_string(),
+                    has_stocks: true,
+                    has_bank: true,
+                    has_ledger: true,
+                    has_budget: false,
+                },
+            )
+            .unwrap();
+        let pid = db
+            .check_and_add_participant(uid, aid, "ACME".to_string(), ParticipantType::Both, false)
+            .unwrap();
+        let cid = db.add_category(uid, aid, "TRADE".to_string()).unwrap();
+        let record = |date: &str, shares: f64, price: f64, transfer_type: TransferType| {
+            let shares = Quantity::from_f64(shares);
+            let price = Quantity::from_f64(price);
+            let lid = db
+                .add_ledger_entry(
+                    uid,
+                    aid,
+                    LedgerInfo {
+                        date: date.to_string(),
+                        amount: shares.value_at(price),
+                        transfer_type,
+                        participant: pid,
+                        category_id: cid,
+                        description: String::new(),
+                    },
+                )
+                .unwrap();
+            StockInfo {
+                shares,
+                costbasis: price,
+                remaining: shares,
+                ledger_id: lid,
+            }
+        };
+
+        // a long-term lot at $10 and a short-term lot at $20
+        let long = db
+            .add_stock_purchase(
+                uid,
+                aid,
+                record(
+                    "2019-01-02",
+                    10.0,
+                    10.0,
+                    TransferType::WithdrawalToInternalAccount,
+                ),
+            )
+            .unwrap();
+        let short = db
+            .add_stock_purchase(
+                uid,
+                aid,
+                record(
+                    "2021-03-01",
+                    10.0,
+                    20.0,
+                    TransferType::WithdrawalToInternalAccount,
+                ),
+            )
+            .unwrap();
+        let sale = db
+            .add_stock_sale(
+                uid,
+                aid,
+                record(
+                    "2021-06-01",
+                    10.0,
+                    30.0,
+                    TransferType::DepositFromInternalAccount,
+                ),
+            )
+            .unwrap();
+
+        let account = VariableAccount {
+            id: aid,
+            uid,
+            db: db.clone(),
+            fixed: FixedAccount::new(uid, aid, db.clone()),
+            buffer: None,
+            open_date: NaiveDate::from_ymd_opt(2019, 1, 1).unwrap(),
+        };
+        let sale = db
+            .check_and_get_stock_sale_record_matching_from_sale_id(uid, aid, sale)
+            .unwrap()
+            .unwrap();
+        account
+            .allocate_sale_stock(sale, SaleMethod::AverageCost)
+            .unwrap();
+
+        // the ten shares sold are the long-term lot's, at the $15 average
+        let gains = db.get_realized_gains(uid, Some(aid)).unwrap();
+        assert_eq!(gains.len(), 1);
+        assert_eq!(gains[0].acquired, "2019-01-02");
+        assert_eq!(gains[0].shares, Quantity::from_f64(10.0));
+        assert_eq!(gains[0].term, HoldingTerm::Long);
+        assert_eq!(gains[0].basis, Money::from_cents(15_000));
+        assert_eq!(gains[0].proceeds, Money::from_cents(30_000));
+
+        let remaining = |id: u32| {
+            db.get_stock_history_ascending(uid, aid, "ACME".to_string())
+                .unwrap()
+                .into_iter()
+                .find(|stock| stock.id == id)
+                .map_or(Quantity::ZERO, |stock| stock.info.remaining)
+        };
+        assert_eq!(remaining(long), Quantity::ZERO);
+        assert_eq!(remaining(short), Quantity::from_f64(10.0));
+    }
+}
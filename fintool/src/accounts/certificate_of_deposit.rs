@@ -15,7 +15,7 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use chrono::{Days, Local, Months, NaiveDate, NaiveTime};
-use csv::ReaderBuilder;
+use fin_import::qif::QifAccountType;
 use inquire::Confirm;
 use inquire::CustomType;
 use inquire::DateSelect;
@@ -52,11 +52,17 @@ use shared_lib::LedgerEntry;
 use std::collections::HashMap;
 use std::path::Path;
 
+use super::base::export::{select_export_format, write_export_file};
+use super::base::import::{
+    apply_merges, confirm_import, read_import_file, save_import_settings, select_import_format,
+    ImportedEntry,
+};
 #[cfg(feature = "ratatui_support")]
 use crate::app::app::{App, DisplayValue, LineChart};
 #[cfg(feature = "ratatui_support")]
 use crate::app::screen::ledger_table_constraint_len_calculator;
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::tui::get_analysis_period_dates;
 use crate::tui::query_user_for_analysis_period;
 use crate::types::accounts::AccountInfo;
@@ -70,7 +76,7 @@ use crate::types::participants;
 use crate::types::participants::ParticipantType;
 #[cfg(feature = "ratatui_support")]
 use crate::ui::{centered_rect, float_range};
-use shared_lib::{FlatLedgerEntry, TransferType};
+use shared_lib::{Money, TransferType};
 
 use super::base::fixed_account::FixedAccount;
 use super::base::Account;
@@ -107,7 +113,7 @@ struct FilePathHelper {
 }
 
 impl CertificateOfDepositAccount {
-    pub fn new(uid: u32, id: u32, db: &DbConn) -> Self {
+    pub fn new(uid: u32, id: u32, db: &DbConn) -> Result<Self> {
         let mut acct: CertificateOfDepositAccount = Self {
             uid: uid,
             id: id,
@@ -115,15 +121,15 @@ impl CertificateOfDepositAccount {
             fixed: FixedAccount::new(uid, id, db.clone()),
             open_date: Local::now().date_naive(),
         };
-        let mut ledger = acct.get_ledger();
+        let mut ledger = acct.get_ledger()?;
         if !ledger.is_empty() {
             ledger.sort_by(|l1, l2| (&l1.info.date).cmp(&l2.info.date));
-            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d").unwrap();
+            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d")?;
         }
-        acct
+        Ok(acct)
     }
 
-    pub fn get_linechart(&self, app: &mut App) -> Option<LineChart> {
+    pub fn get_linechart(&self, app: &mut App) -> Result<Option<LineChart>> {
         let (start, end) = (app.analysis_start, app.analysis_end);
         let starting_amount_opt = self
             .db
@@ -147,7 +153,7 @@ impl CertificateOfDepositAccount {
                 id: 0,
                 info: LedgerInfo {
                     date: start.checked_add_days(Days::new(1)).unwrap().to_string(),
-                    amount: 0.0,
+                    amount: Money::ZERO,
                     transfer_type: TransferType::ZeroSumChange,
                     participant: 0,
                     category_id: 0,
@@ -155,12 +161,12 @@ impl CertificateOfDepositAccount {
                 },
             }]
         };
-        entries.append(&mut self.get_ledger_within_dates(start, end));
-        if !(entries.len() == 1) {
+        entries.append(&mut self.get_ledger_within_dates(start, end)?);
+        Ok(if !(entries.len() == 1) {
             entries.reverse();
             let last = entries.pop().unwrap();
 
-            let mut aggregate: f64 = last.info.amount as f64;
+            let mut aggregate: f64 = last.info.amount.to_f64();
             let starting_date = NaiveDate::parse_from_str(&last.info.date, "%Y-%m-%d").unwrap();
             let mut min_total = aggregate;
             let mut max_total = aggregate;
@@ -180,11 +186,11 @@ impl CertificateOfDepositAccount {
                     aggregate = match record.info.transfer_type {
                         TransferType::DepositFromExternalAccount
                         | TransferType::DepositFromInternalAccount => {
-                            aggregate + record.info.amount as f64
+                            aggregate + record.info.amount.to_f64()
                         }
                         TransferType::WithdrawalToExternalAccount
                         | TransferType::WithdrawalToInternalAccount => {
-                            aggregate - record.info.amount as f64
+                            aggregate - record.info.amount.to_f64()
                         }
                         TransferType::ZeroSumChange => aggregate,
                     };
@@ -222,12 +228,12 @@ impl CertificateOfDepositAccount {
             })
         } else {
             None
-        }
+        })
     }
 }
 
 impl AccountCreation for CertificateOfDepositAccount {
-    fn create(uid: u32, name: String, _db: &DbConn) -> AccountRecord {
+    fn create(uid: u32, name: String, _db: &DbConn) -> Result<AccountRecord> {
         let has_bank = true;
         let has_stocks = false;
         let has_ledger = false;
@@ -242,13 +248,13 @@ impl AccountCreation for CertificateOfDepositAccount {
             has_budget: has_budget,
         };
 
-        let aid = _db.add_account(uid, &account).unwrap();
+        let aid = _db.add_account(uid, &account)?;
 
-        let mut cd: CertificateOfDepositAccount = CertificateOfDepositAccount::new(uid, aid, _db);
+        let mut cd: CertificateOfDepositAccount = CertificateOfDepositAccount::new(uid, aid, _db)?;
 
-        let principal = CustomType::<f32>::new("Enter principal:")
+        let principal = CustomType::<Money>::new("Enter principal:")
             .with_placeholder("10000.00")
-            .with_default(10000.00)
+            .with_default(Money::from_cents(10000_00))
             .with_error_message("Please type a valid amount!")
             .prompt()
             .unwrap();
@@ -278,8 +284,7 @@ impl AccountCreation for CertificateOfDepositAccount {
             length_months: length,
         };
 
-        _db.add_certificate_of_deposit(uid, aid, cd_info.clone())
-            .unwrap();
+        _db.add_certificate_of_deposit(uid, aid, cd_info.clone())?;
 
         let initialize_ledger = Confirm::new("Initialize ledger with principal?")
             .prompt()
@@ -290,7 +295,7 @@ impl AccountCreation for CertificateOfDepositAccount {
                 .prompt()
                 .unwrap();
             let input = if link {
-                cd.fixed.link_transaction(None)
+                cd.fixed.link_transaction(None)?
             } else {
                 None
             };
@@ -313,19 +318,19 @@ impl AccountCreation for CertificateOfDepositAccount {
                     peer.1,
                     ParticipantType::Payer,
                     peer.0.is_some(),
-                ),
+                )?,
                 category_id: _db.check_and_add_category(
                     uid,
                     aid,
                     "Deposit".to_ascii_uppercase().to_string(),
-                ),
+                )?,
                 description: format!(
                     "Open {} APY {} month CD with ${}",
                     cd_info.apy, cd_info.length_months, cd_info.principal
                 ),
             };
 
-            let lid = _db.add_ledger_entry(uid, aid, initial.clone()).unwrap();
+            let lid = _db.add_ledger_entry(uid, aid, initial.clone())?;
             if peer.0.is_some() {
                 peer.0.unwrap().link(
                     cd.id,
@@ -333,19 +338,16 @@ impl AccountCreation for CertificateOfDepositAccount {
                         id: lid,
                         info: initial,
                     },
-                );
+                )?;
             }
         }
 
-        return AccountRecord {
-            id: aid,
-            info: account,
-        };
+        return Ok(AccountRecord::owned(uid, aid, account));
     }
 }
 
 impl AccountOperations for CertificateOfDepositAccount {
-    fn record(&mut self) {
+    fn record(&mut self) -> Result<()> {
         const RECORD_OPTIONS: [&'static str; 3] = ["Deposit", "Withdrawal", "None"];
         loop {
             let action = Select::new(
@@ -357,13 +359,13 @@ impl AccountOperations for CertificateOfDepositAccount {
             .to_string();
             match action.as_str() {
                 "Deposit" => {
-                    self.fixed.deposit(None, false);
+                    self.fixed.deposit(None, false)?;
                 }
                 "Withdrawal" => {
-                    self.fixed.withdrawal(None, false);
+                    self.fixed.withdrawal(None, false)?;
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!");
@@ -373,12 +375,17 @@ impl AccountOperations for CertificateOfDepositAccount {
                 .prompt()
                 .unwrap();
             if !record_again {
-                return;
+                return Ok(());
             }
         }
     }
 
-    fn import(&mut self) {
+    fn import(&mut self) -> Result<()> {
+        let format = match select_import_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
+
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -398,7 +405,9 @@ impl AccountOperations for CertificateOfDepositAccount {
         let mut bad_path;
         let mut csv: String = String::new();
         loop {
-            csv = rl.readline("Enter path to CSV file: ").unwrap();
+            csv = rl
+                .readline(&format!("Enter path to {} file: ", format))
+                .unwrap();
             bad_path = match Path::new(&csv).try_exists() {
                 Ok(true) => false,
                 Ok(false) => {
@@ -415,26 +424,28 @@ impl AccountOperations for CertificateOfDepositAccount {
             } else {
                 let try_again = Confirm::new("Continue import?").prompt().unwrap();
                 if !try_again {
-                    return;
+                    return Ok(());
                 }
             }
         }
         fp = Path::new(&csv);
 
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(fp)
-            .unwrap();
-
-        let mut ledger_entries = Vec::new();
-        for result in rdr.deserialize::<LedgerEntry>() {
-            ledger_entries.push(result.unwrap());
+        let batch = read_import_file(&self.db, self.uid, self.id, format, fp)?;
+        if !confirm_import(&self.db, self.uid, self.id, &batch)? {
+            return Ok(());
         }
-        ledger_entries.sort_by(|x, y| {
-            (NaiveDate::parse_from_str(&x.date, "%Y-%m-%d").unwrap())
-                .cmp(&NaiveDate::parse_from_str(&y.date, "%Y-%m-%d").unwrap())
-        });
-        for rcrd in ledger_entries {
+
+        // write every entry or, should one fail, none of them
+        let transaction = self.db.begin_transaction()?;
+        apply_merges(&self.db, self.uid, self.id, &batch.merges)?;
+        save_import_settings(&self.db, self.uid, self.id, &batch)?;
+        let ledger_entries = batch.entries;
+        for ImportedEntry {
+            entry: rcrd,
+            external_id,
+            ..
+        } in ledger_entries
+        {
             let ptype = if rcrd.transfer_type == TransferType::WithdrawalToExternalAccount {
                 ParticipantType::Payee
             } else if rcrd.transfer_type == TransferType::WithdrawalToInternalAccount {
@@ -445,8 +456,7 @@ impl AccountOperations for CertificateOfDepositAccount {
                 ParticipantType::Payer
             };
             let entry: LedgerInfo = LedgerInfo {
-                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")
-                    .unwrap()
+                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")?
                     .format("%Y-%m-%d")
                     .to_string(),
                 amount: rcrd.amount,
@@ -457,19 +467,25 @@ impl AccountOperations for CertificateOfDepositAccount {
                     rcrd.participant,
                     ptype,
                     false,
-                ),
+                )?,
                 category_id: self.db.check_and_add_category(
                     self.uid,
                     self.id,
                     rcrd.category.to_ascii_uppercase(),
-                ),
+                )?,
                 description: rcrd.description,
             };
-            let _lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry).unwrap();
+            let lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry)?;
+            if let Some(external_id) = external_id {
+                self.db
+                    .add_external_transaction_id(self.uid, self.id, lid, &external_id)?;
+            }
         }
+        transaction.commit()?;
+        Ok(())
     }
 
-    fn modify(&mut self) {
+    fn modify(&mut self) -> Result<()> {
         const MODIFY_OPTIONS: [&'static str; 7] = [
             "APY",
             "Ledger",
@@ -486,19 +502,14 @@ impl AccountOperations for CertificateOfDepositAccount {
                     .unwrap();
             match modify_choice {
                 "APY" => {
-                    let cd = self
-                        .db
-                        .get_certificate_of_deposit(self.uid, self.id)
-                        .unwrap();
+                    let cd = self.db.get_certificate_of_deposit(self.uid, self.id)?;
                     let updated_apy = CustomType::<f32>::new("Enter annual percentage yield:")
                         .with_placeholder("3.00")
                         .with_default(cd.info.apy)
                         .with_error_message("Please type a valid percentage!")
                         .prompt()
                         .unwrap();
-                    self.db
-                        .update_cd_apy(self.uid, self.id, updated_apy)
-                        .unwrap();
+                    self.db.update_cd_apy(self.uid, self.id, updated_apy)?;
                 }
                 "Ledger" => {
                     loop {
@@ -507,32 +518,26 @@ impl AccountOperations for CertificateOfDepositAccount {
                             break;
                         }
                         let selected_record = record_or_none.unwrap();
-                        let updated_record = self.fixed.modify(selected_record.clone());
+                        let updated_record = self.fixed.modify(selected_record.clone())?;
                         // record 0 should always be the initial of the account.
                         // if the date of the deposit changed, then so should the maturity date
                         if updated_record.id == 0 {
-                            let cd = self
-                                .db
-                                .get_certificate_of_deposit(self.uid, self.id)
-                                .unwrap();
+                            let cd = self.db.get_certificate_of_deposit(self.uid, self.id)?;
                             if selected_record.info.date != updated_record.info.date {
                                 let new_date_nv = NaiveDate::parse_from_str(
                                     &updated_record.info.date.as_str(),
                                     "%Y-%m-%d",
-                                )
-                                .unwrap();
+                                )?;
                                 let updated_maturity_date = new_date_nv
                                     .checked_add_months(Months::new(cd.info.length_months))
                                     .unwrap()
                                     .format("%Y-%m-%d")
                                     .to_string();
-                                self.db
-                                    .update_cd_maturity_date(
-                                        self.uid,
-                                        self.id,
-                                        updated_maturity_date,
-                                    )
-                                    .unwrap();
+                                self.db.update_cd_maturity_date(
+                                    self.uid,
+                                    self.id,
+                                    updated_maturity_date,
+                                )?;
                             }
 
                             let go_again = Confirm::new("Modify additional records? (y/n)")
@@ -545,10 +550,7 @@ impl AccountOperations for CertificateOfDepositAccount {
                     }
                 }
                 "Length" => {
-                    let cd = self
-                        .db
-                        .get_certificate_of_deposit(self.uid, self.id)
-                        .unwrap();
+                    let cd = self.db.get_certificate_of_deposit(self.uid, self.id)?;
                     let updated_length =
                         CustomType::<u32>::new("Enter length (in months) to maturity:")
                             .with_placeholder("12")
@@ -558,30 +560,26 @@ impl AccountOperations for CertificateOfDepositAccount {
                             .unwrap();
                     let len_difference = (updated_length as i32) - (cd.info.length_months as i32);
                     let updated_maturity_date = if len_difference > 0 {
-                        NaiveDate::parse_from_str(&cd.info.maturity_date.as_str(), "%Y-%m-%d")
-                            .unwrap()
+                        NaiveDate::parse_from_str(&cd.info.maturity_date.as_str(), "%Y-%m-%d")?
                             .checked_add_months(Months::new(len_difference as u32))
                             .unwrap()
                             .format("%Y-%m-%d")
                             .to_string()
                     } else {
-                        NaiveDate::parse_from_str(&cd.info.maturity_date.as_str(), "%Y-%m-%d")
-                            .unwrap()
+                        NaiveDate::parse_from_str(&cd.info.maturity_date.as_str(), "%Y-%m-%d")?
                             .checked_sub_months(Months::new((0 - len_difference) as u32))
                             .unwrap()
                             .format("%Y-%m-%d")
                             .to_string()
                     };
                     self.db
-                        .update_cd_length(self.uid, self.id, updated_length)
-                        .unwrap();
+                        .update_cd_length(self.uid, self.id, updated_length)?;
                     self.db
-                        .update_cd_maturity_date(self.uid, self.id, updated_maturity_date)
-                        .unwrap();
+                        .update_cd_maturity_date(self.uid, self.id, updated_maturity_date)?;
                 }
                 "Categories" => {
                     loop {
-                        let records = self.db.get_categories(self.uid, self.id).unwrap();
+                        let records = self.db.get_categories(self.uid, self.id)?;
                         let mut choices: Vec<String> = records
                             .iter()
                             .map(|x| x.category.name.clone())
@@ -606,25 +604,20 @@ impl AccountOperations for CertificateOfDepositAccount {
                                     .prompt()
                                     .unwrap()
                                     .to_string();
-                                self.db
-                                    .update_category_name(
-                                        self.uid,
-                                        self.id,
-                                        chosen_category,
-                                        new_name,
-                                    )
-                                    .unwrap();
+                                self.db.update_category_name(
+                                    self.uid,
+                                    self.id,
+                                    chosen_category,
+                                    new_name,
+                                )?;
                             }
                             "Remove" => {
                                 // check if category is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_category(
-                                        self.uid,
-                                        self.id,
-                                        chosen_category.clone(),
-                                    )
-                                    .unwrap();
+                                let is_referenced = self.db.check_if_ledger_references_category(
+                                    self.uid,
+                                    self.id,
+                                    chosen_category.clone(),
+                                )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -633,13 +626,11 @@ impl AccountOperations for CertificateOfDepositAccount {
                                             "{} | {} | {} | {} ",
                                             record.info.date,
                                             chosen_category.clone(),
-                                            self.db
-                                                .get_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.participant
-                                                )
-                                                .unwrap(),
+                                            self.db.get_participant(
+                                                self.uid,
+                                                self.id,
+                                                record.info.participant
+                                            )?,
                                             record.info.amount
                                         );
                                         print!("\t{}", v);
@@ -655,7 +646,7 @@ impl AccountOperations for CertificateOfDepositAccount {
                                         self.uid,
                                         self.id,
                                         chosen_category.clone(),
-                                    );
+                                    )?;
                                 }
                             }
                             "None" => {
@@ -688,8 +679,7 @@ impl AccountOperations for CertificateOfDepositAccount {
                                 panic!("Unrecognized input: {}", selected_ptype);
                             }
                         };
-                        let participants =
-                            self.db.get_participants(self.uid, self.id, ptype).unwrap();
+                        let participants = self.db.get_participants(self.uid, self.id, ptype)?;
                         let mut people = participants
                             .iter()
                             .map(|x| x.participant.name.clone())
@@ -719,27 +709,23 @@ impl AccountOperations for CertificateOfDepositAccount {
                                     .prompt()
                                     .unwrap()
                                     .to_string();
-                                self.db
-                                    .update_participant_name(
-                                        self.uid,
-                                        self.id,
-                                        ptype,
-                                        chosen_person.clone(),
-                                        new_name,
-                                    )
-                                    .unwrap();
+                                self.db.update_participant_name(
+                                    self.uid,
+                                    self.id,
+                                    ptype,
+                                    chosen_person.clone(),
+                                    new_name,
+                                )?;
                             }
                             "Remove" => {
                                 // check if participant is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_participant(
+                                let is_referenced =
+                                    self.db.check_if_ledger_references_participant(
                                         self.uid,
                                         self.id,
                                         ptype,
                                         chosen_person.clone(),
-                                    )
-                                    .unwrap();
+                                    )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -747,13 +733,11 @@ impl AccountOperations for CertificateOfDepositAccount {
                                         let v = format!(
                                             "{} | {} | {} | {} ",
                                             record.info.date,
-                                            self.db
-                                                .get_category_name(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.category_id
-                                                )
-                                                .unwrap(),
+                                            self.db.get_category_name(
+                                                self.uid,
+                                                self.id,
+                                                record.info.category_id
+                                            )?,
                                             chosen_person.clone(),
                                             record.info.amount
                                         );
@@ -767,42 +751,34 @@ impl AccountOperations for CertificateOfDepositAccount {
                                 if delete {
                                     match ptype {
                                         ParticipantType::Payee => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         ParticipantType::Payer => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         _ => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                     }
                                 }
@@ -823,22 +799,18 @@ impl AccountOperations for CertificateOfDepositAccount {
                     }
                 }
                 "Principal" => {
-                    let cd = self
-                        .db
-                        .get_certificate_of_deposit(self.uid, self.id)
-                        .unwrap();
-                    let updated_principal = CustomType::<f32>::new("Enter principal:")
+                    let cd = self.db.get_certificate_of_deposit(self.uid, self.id)?;
+                    let updated_principal = CustomType::<Money>::new("Enter principal:")
                         .with_placeholder("10000.00")
                         .with_default(cd.info.principal)
                         .with_error_message("Please type a valid amount!")
                         .prompt()
                         .unwrap();
                     self.db
-                        .update_cd_principal(self.uid, self.id, updated_principal)
-                        .unwrap();
+                        .update_cd_principal(self.uid, self.id, updated_principal)?;
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!")
@@ -851,9 +823,14 @@ impl AccountOperations for CertificateOfDepositAccount {
                 break;
             }
         }
+        Ok(())
     }
 
-    fn export(&self) {
+    fn export(&self) -> Result<()> {
+        let format = match select_export_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -869,33 +846,38 @@ impl AccountOperations for CertificateOfDepositAccount {
         let mut rl = Editor::with_config(config).unwrap();
         rl.set_helper(Some(g));
 
-        let mut wtr =
-            csv::Writer::from_path(rl.readline("Enter path to CSV file: ").unwrap()).unwrap();
-        let ledger = self.get_ledger();
+        let path = rl
+            .readline(&format!("Enter path to {} file: ", format))
+            .unwrap();
+        let mut entries = Vec::new();
+        let ledger = self.get_ledger()?;
         if !ledger.is_empty() {
             for record in ledger {
                 let csv_ledger_record: shared_lib::LedgerEntry = LedgerEntry {
                     date: record.info.date,
                     amount: record.info.amount,
                     transfer_type: record.info.transfer_type,
-                    participant: self
-                        .db
-                        .get_participant(self.uid, self.id, record.info.participant)
-                        .unwrap(),
-                    category: self
-                        .db
-                        .get_category_name(self.uid, self.id, record.info.category_id)
-                        .unwrap(),
+                    participant: self.db.get_participant(
+                        self.uid,
+                        self.id,
+                        record.info.participant,
+                    )?,
+                    category: self.db.get_category_name(
+                        self.uid,
+                        self.id,
+                        record.info.category_id,
+                    )?,
                     description: record.info.description,
                     stock_info: None,
                 };
-                let flattened = FlatLedgerEntry::from(csv_ledger_record);
-                wtr.serialize(flattened).unwrap();
+                entries.push(csv_ledger_record);
             }
         }
+        write_export_file(format, QifAccountType::Bank, Path::new(&path), entries)?;
+        Ok(())
     }
 
-    fn report(&self) {
+    fn report(&self) -> Result<()> {
         const REPORT_OPTIONS: [&'static str; 3] = ["Total Value", "Simple Growth Rate", "None"];
         let choice: String =
             Select::new("What would you like to report: ", REPORT_OPTIONS.to_vec())
@@ -904,7 +886,7 @@ impl AccountOperations for CertificateOfDepositAccount {
                 .to_string();
         match choice.as_str() {
             "Total Value" => {
-                let value = self.fixed.get_current_value();
+                let value = self.fixed.get_current_value()?;
                 println!("\tTotal Account Value: {}", value);
             }
             "Simple Growth Rate" => {
@@ -914,15 +896,18 @@ impl AccountOperations for CertificateOfDepositAccount {
                 println!("\tRate of return: {}%", rate);
             }
             "None" => {
-                return;
+                return Ok(());
             }
             _ => {
                 panic!("Unrecognized input!");
             }
         }
+        Ok(())
     }
 
-    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Option<u32> {
+    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Result<Option<u32>> {
+        let transaction = self.db.begin_transaction()?;
+
         let from_account;
         let to_account;
 
@@ -938,18 +923,16 @@ impl AccountOperations for CertificateOfDepositAccount {
                     self.uid,
                     self.id,
                     "Withdrawal".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payee,
                     true,
-                );
+                )?;
                 (
                     TransferType::WithdrawalToExternalAccount,
                     format!(
@@ -966,18 +949,16 @@ impl AccountOperations for CertificateOfDepositAccount {
                     self.uid,
                     self.id,
                     "Deposit".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payer,
                     true,
-                );
+                )?;
                 (
                     TransferType::DepositFromExternalAccount,
                     format!(
@@ -987,7 +968,7 @@ impl AccountOperations for CertificateOfDepositAccount {
                 )
             }
             _ => {
-                return None;
+                return Ok(None);
             }
         };
 
@@ -1002,16 +983,12 @@ impl AccountOperations for CertificateOfDepositAccount {
 
         let (from_ledger_id, to_ledger_id) = match new_ttype {
             TransferType::WithdrawalToExternalAccount => (
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
                 entry.id,
             ),
             TransferType::DepositFromExternalAccount => (
                 entry.id,
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
             ),
             _ => {
                 panic!("Unrecognized input!")
@@ -1025,11 +1002,12 @@ impl AccountOperations for CertificateOfDepositAccount {
             to_ledger: to_ledger_id,
         };
 
-        return Some(
-            self.db
-                .add_account_transaction(self.uid, transaction_record)
-                .unwrap(),
-        );
+        let id = self
+            .db
+            .add_account_transaction(self.uid, transaction_record)?;
+        transaction.commit()?;
+
+        return Ok(Some(id));
     }
 }
 
@@ -1037,26 +1015,32 @@ impl AccountData for CertificateOfDepositAccount {
     fn get_id(&self) -> u32 {
         return self.id;
     }
-    fn get_name(&self) -> String {
-        return self.db.get_account_name(self.uid, self.id).unwrap();
+    fn get_uid(&self) -> u32 {
+        return self.uid;
+    }
+    fn get_name(&self) -> Result<String> {
+        Ok(self.db.get_account_name(self.uid, self.id)?)
     }
-    fn get_ledger(&self) -> Vec<LedgerRecord> {
-        return self.db.get_ledger(self.uid, self.id).unwrap();
+    fn get_ledger(&self) -> Result<Vec<LedgerRecord>> {
+        Ok(self.db.get_ledger(self.uid, self.id)?)
     }
-    fn get_ledger_within_dates(&self, start: NaiveDate, end: NaiveDate) -> Vec<LedgerRecord> {
-        return self
+    fn get_ledger_within_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<LedgerRecord>> {
+        Ok(self
             .db
-            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)
-            .unwrap();
+            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)?)
     }
-    fn get_displayable_ledger(&self) -> Vec<crate::types::ledger::DisplayableLedgerRecord> {
-        return self.db.get_displayable_ledger(self.uid, self.id).unwrap();
+    fn get_displayable_ledger(&self) -> Result<Vec<crate::types::ledger::DisplayableLedgerRecord>> {
+        Ok(self.db.get_displayable_ledger(self.uid, self.id)?)
     }
-    fn get_value(&self) -> f32 {
-        return self.fixed.get_current_value();
+    fn get_value(&self) -> Result<Money> {
+        self.fixed.get_current_value()
     }
-    fn get_value_on_day(&self, day: NaiveDate) -> f32 {
-        return self.fixed.get_value_on_day(day);
+    fn get_value_on_day(&self, day: NaiveDate) -> Result<Money> {
+        self.fixed.get_value_on_day(day)
     }
     fn get_open_date(&self) -> NaiveDate {
         return self.open_date;
@@ -1065,12 +1049,12 @@ impl AccountData for CertificateOfDepositAccount {
 
 #[cfg(feature = "ratatui_support")]
 impl AccountUI for CertificateOfDepositAccount {
-    fn populate_page_cache_f32(&self, app: &mut App) {
+    fn populate_page_cache_f32(&self, app: &mut App) -> Result<()> {
         let mut kv: HashMap<String, DisplayValue> = HashMap::new();
 
         kv.insert(
             KEY_TOTAL_VALUE.into(),
-            DisplayValue::Float(self.get_value()),
+            DisplayValue::Money(self.get_value()?),
         );
         kv.insert(
             KEY_GROWTH.into(),
@@ -1086,9 +1070,10 @@ impl AccountUI for CertificateOfDepositAccount {
         );
 
         app.page_cache_f32 = Some(kv);
-        app.ledger_entries = Some(self.get_displayable_ledger());
-        app.linechart_cache = self.get_linechart(app);
+        app.ledger_entries = Some(self.get_displayable_ledger()?);
+        app.linechart_cache = self.get_linechart(app)?;
         app.barchart_cache = None;
+        Ok(())
     }
 
     fn render(&self, frame: &mut Frame, area: Rect, app: &mut App) {
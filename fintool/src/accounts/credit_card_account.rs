@@ -15,7 +15,7 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use chrono::{Days, Local, Months, NaiveDate};
-use csv::ReaderBuilder;
+use fin_import::qif::QifAccountType;
 use inquire::Confirm;
 use inquire::CustomType;
 use inquire::Select;
@@ -47,7 +47,7 @@ use rustyline::Helper;
 use rustyline::Highlighter;
 use rustyline::Hinter;
 use rustyline::Validator;
-use shared_lib::{FlatLedgerEntry, LedgerEntry};
+use shared_lib::LedgerEntry;
 use std::collections::HashMap;
 use std::env::current_exe;
 use std::hash::Hash;
@@ -55,12 +55,18 @@ use std::iter::zip;
 use std::path::Path;
 use std::rc;
 
+use super::base::export::{select_export_format, write_export_file};
+use super::base::import::{
+    apply_merges, confirm_import, read_import_file, save_import_settings, select_import_format,
+    ImportedEntry,
+};
 use crate::accounts::base::budget::Budget;
 #[cfg(feature = "ratatui_support")]
 use crate::app::app::{App, BarChartData, DisplayValue};
 #[cfg(feature = "ratatui_support")]
 use crate::app::screen::ledger_table_constraint_len_calculator;
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::tui::query_user_for_analysis_period;
 use crate::types::accounts::AccountInfo;
 use crate::types::accounts::AccountRecord;
@@ -73,7 +79,7 @@ use crate::types::ledger::LedgerRecord;
 use crate::types::participants;
 use crate::types::participants::ParticipantType;
 use crate::{tui::get_analysis_period_dates, types::ledger::Expenditure};
-use shared_lib::TransferType;
+use shared_lib::{Money, TransferType};
 
 use super::base::charge_account::ChargeAccount;
 use super::base::Account;
@@ -117,7 +123,7 @@ struct FilePathHelper {
 }
 
 impl CreditCardAccount {
-    pub fn new(uid: u32, id: u32, db: &DbConn) -> Self {
+    pub fn new(uid: u32, id: u32, db: &DbConn) -> Result<Self> {
         let mut acct: CreditCardAccount = Self {
             uid: uid,
             id: id,
@@ -127,16 +133,16 @@ impl CreditCardAccount {
             budget: None,
         };
 
-        let mut ledger = acct.get_ledger();
+        let mut ledger = acct.get_ledger()?;
         if !ledger.is_empty() {
             ledger.sort_by(|l1, l2| (&l1.info.date).cmp(&l2.info.date));
-            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d").unwrap();
+            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d")?;
         }
         if acct.has_budget() {
             acct.budget = Some(Budget::new(acct.uid, acct.id, &acct.db));
         }
 
-        acct
+        Ok(acct)
     }
 
     pub fn get_barchart_data(&self, app: &mut App) -> Option<BarChartData> {
@@ -175,7 +181,7 @@ impl CreditCardAccount {
                 // remove any expenditures that don't map to a budget category, place in to misc category
                 let mut misc_expenditures = Expenditure {
                     category: "Misc".to_string(),
-                    amount: 0.0,
+                    amount: Money::ZERO,
                 };
                 expenditures.retain(|expenditure| {
                     if budget
@@ -197,8 +203,8 @@ impl CreditCardAccount {
                 });
 
                 let mut labels: Vec<String> = Vec::new();
-                let mut budget_dataset: HashMap<String, (f32, u64)> = HashMap::new();
-                let mut expenditure_dataset: HashMap<String, (f32, u64)> = HashMap::new();
+                let mut budget_dataset: HashMap<String, (Money, u64)> = HashMap::new();
+                let mut expenditure_dataset: HashMap<String, (Money, u64)> = HashMap::new();
                 for elem in zip(budget, expenditures) {
                     let budget_value = super::base::budget::scale_budget_value_to_analysis_period(
                         elem.0.item.value,
@@ -208,25 +214,30 @@ impl CreditCardAccount {
                     let expenditure_value = elem.1.amount;
 
                     labels.push(elem.1.category.clone());
-                    budget_dataset
-                        .insert(elem.1.category.clone(), (budget_value, budget_value as u64));
+                    budget_dataset.insert(
+                        elem.1.category.clone(),
+                        (budget_value, budget_value.to_f64() as u64),
+                    );
                     expenditure_dataset.insert(
                         elem.1.category.clone(),
-                        (expenditure_value, expenditure_value as u64),
+                        (expenditure_value, expenditure_value.to_f64() as u64),
                     );
                 }
 
-                if misc_expenditures.amount > 0.0 {
+                if misc_expenditures.amount.is_positive() {
                     let label: String = "Misc".into();
                     labels.push(label.clone());
-                    budget_dataset.insert(label.clone(), (0.0, 0));
+                    budget_dataset.insert(label.clone(), (Money::ZERO, 0));
                     expenditure_dataset.insert(
                         label,
-                        (misc_expenditures.amount, misc_expenditures.amount as u64),
+                        (
+                            misc_expenditures.amount,
+                            misc_expenditures.amount.to_f64() as u64,
+                        ),
                     );
                 }
 
-                let mut bars: HashMap<String, HashMap<String, (f32, u64)>> = HashMap::new();
+                let mut bars: HashMap<String, HashMap<String, (Money, u64)>> = HashMap::new();
                 bars.insert(KEY_BARCHART_BUDGET.into(), budget_dataset);
                 bars.insert(KEY_BARCHART_EXPENDITURES.into(), expenditure_dataset);
 
@@ -260,17 +271,17 @@ impl CreditCardAccount {
                 }
 
                 let mut labels: Vec<String> = Vec::new();
-                let mut expenditure_dataset: HashMap<String, (f32, u64)> = HashMap::new();
+                let mut expenditure_dataset: HashMap<String, (Money, u64)> = HashMap::new();
                 for elem in expenditures {
                     let expenditure_value = elem.amount;
                     labels.push(elem.category.clone());
                     expenditure_dataset.insert(
                         elem.category.clone(),
-                        (expenditure_value, expenditure_value as u64),
+                        (expenditure_value, expenditure_value.to_f64() as u64),
                     );
                 }
 
-                let mut bars: HashMap<String, HashMap<String, (f32, u64)>> = HashMap::new();
+                let mut bars: HashMap<String, HashMap<String, (Money, u64)>> = HashMap::new();
                 bars.insert("Expenditures".into(), expenditure_dataset);
 
                 return Some(BarChartData {
@@ -285,7 +296,7 @@ impl CreditCardAccount {
 }
 
 impl AccountCreation for CreditCardAccount {
-    fn create(uid: u32, name: String, _db: &DbConn) -> AccountRecord {
+    fn create(uid: u32, name: String, _db: &DbConn) -> Result<AccountRecord> {
         let has_bank = false;
         let has_stocks = false;
         let has_ledger = false;
@@ -300,11 +311,11 @@ impl AccountCreation for CreditCardAccount {
             has_budget: has_budget,
         };
 
-        let aid = _db.add_account(uid, &account).unwrap();
+        let aid = _db.add_account(uid, &account)?;
 
-        let credit_limit = CustomType::<f32>::new("Enter credit limit:")
+        let credit_limit = CustomType::<Money>::new("Enter credit limit:")
             .with_placeholder("3000.00")
-            .with_default(3000.00)
+            .with_default(Money::from_cents(3000_00))
             .with_error_message("Please type a valid amount!")
             .prompt()
             .unwrap();
@@ -320,28 +331,25 @@ impl AccountCreation for CreditCardAccount {
             statement_due_date: statement_due_date,
         };
 
-        _db.add_credit_card(uid, aid, cc).unwrap();
+        _db.add_credit_card(uid, aid, cc)?;
 
         let add_budget = Confirm::new("Would you like to associate a budget to this account?")
             .with_default(false)
             .prompt()
             .unwrap();
         if add_budget {
-            let x = Self::new(uid, aid, _db);
+            let x = Self::new(uid, aid, _db)?;
             let budget = Budget::new(uid, aid, _db);
-            budget.create_budget();
+            budget.create_budget()?;
             x.set_budget();
         }
 
-        return AccountRecord {
-            id: aid,
-            info: account,
-        };
+        return Ok(AccountRecord::owned(uid, aid, account));
     }
 }
 
 impl AccountOperations for CreditCardAccount {
-    fn record(&mut self) {
+    fn record(&mut self) -> Result<()> {
         const RECORD_OPTIONS: [&'static str; 4] = ["Charge", "Payment", "Budget", "None"];
         loop {
             let action = Select::new(
@@ -353,10 +361,10 @@ impl AccountOperations for CreditCardAccount {
             .to_string();
             match action.as_str() {
                 "Payment" => {
-                    self.charge.pay(None, false);
+                    self.charge.pay(None, false)?;
                 }
                 "Charge" => {
-                    self.charge.charge(None, false);
+                    self.charge.charge(None, false)?;
                 }
                 "Budget" => {
                     if self.budget.is_none() {
@@ -371,11 +379,11 @@ impl AccountOperations for CreditCardAccount {
                         self.budget = Some(budget);
                     }
                     if let Some(budget) = &self.budget {
-                        budget.record();
+                        budget.record()?;
                     }
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!");
@@ -385,12 +393,17 @@ impl AccountOperations for CreditCardAccount {
                 .prompt()
                 .unwrap();
             if !record_again {
-                return;
+                return Ok(());
             }
         }
     }
 
-    fn import(&mut self) {
+    fn import(&mut self) -> Result<()> {
+        let format = match select_import_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
+
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -411,10 +424,13 @@ impl AccountOperations for CreditCardAccount {
         let mut csv: String = String::new();
         loop {
             csv = rl
-                .readline("Enter path to CSV file (or none to quit): ")
+                .readline(&format!(
+                    "Enter path to {} file (or none to quit): ",
+                    format
+                ))
                 .unwrap();
             if csv.to_string() == "none" {
-                return;
+                return Ok(());
             }
             bad_path = match Path::new(&csv).try_exists() {
                 Ok(true) => false,
@@ -432,26 +448,28 @@ impl AccountOperations for CreditCardAccount {
             } else {
                 let try_again = Confirm::new("Continue import?").prompt().unwrap();
                 if !try_again {
-                    return;
+                    return Ok(());
                 }
             }
         }
         fp = Path::new(&csv);
 
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(fp)
-            .unwrap();
-
-        let mut ledger_expenditures = Vec::new();
-        for result in rdr.deserialize::<LedgerEntry>() {
-            ledger_expenditures.push(result.unwrap());
+        let batch = read_import_file(&self.db, self.uid, self.id, format, fp)?;
+        if !confirm_import(&self.db, self.uid, self.id, &batch)? {
+            return Ok(());
         }
-        ledger_expenditures.sort_by(|x, y| {
-            (NaiveDate::parse_from_str(&x.date, "%Y-%m-%d").unwrap())
-                .cmp(&NaiveDate::parse_from_str(&y.date, "%Y-%m-%d").unwrap())
-        });
-        for rcrd in ledger_expenditures {
+
+        // write every entry or, should one fail, none of them
+        let transaction = self.db.begin_transaction()?;
+        apply_merges(&self.db, self.uid, self.id, &batch.merges)?;
+        save_import_settings(&self.db, self.uid, self.id, &batch)?;
+        let ledger_expenditures = batch.entries;
+        for ImportedEntry {
+            entry: rcrd,
+            external_id,
+            ..
+        } in ledger_expenditures
+        {
             let ptype = if rcrd.transfer_type == TransferType::WithdrawalToExternalAccount {
                 ParticipantType::Payee
             } else if rcrd.transfer_type == TransferType::WithdrawalToInternalAccount {
@@ -462,8 +480,7 @@ impl AccountOperations for CreditCardAccount {
                 ParticipantType::Payer
             };
             let entry: LedgerInfo = LedgerInfo {
-                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")
-                    .unwrap()
+                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")?
                     .format("%Y-%m-%d")
                     .to_string(),
                 amount: rcrd.amount,
@@ -474,19 +491,25 @@ impl AccountOperations for CreditCardAccount {
                     rcrd.participant,
                     ptype,
                     false,
-                ),
+                )?,
                 category_id: self.db.check_and_add_category(
                     self.uid,
                     self.id,
                     rcrd.category.to_ascii_uppercase(),
-                ),
+                )?,
                 description: rcrd.description,
             };
-            let _lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry).unwrap();
+            let lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry)?;
+            if let Some(external_id) = external_id {
+                self.db
+                    .add_external_transaction_id(self.uid, self.id, lid, &external_id)?;
+            }
         }
+        transaction.commit()?;
+        Ok(())
     }
 
-    fn modify(&mut self) {
+    fn modify(&mut self) -> Result<()> {
         const MODIFY_OPTIONS: [&'static str; 6] = [
             "Ledger",
             "Credit Line",
@@ -512,7 +535,7 @@ impl AccountOperations for CreditCardAccount {
             match modify_choice {
                 "Budget" => {
                     if let Some(budget) = &self.budget {
-                        budget.modify();
+                        budget.modify()?;
                     } else {
                         let add_budget = Confirm::new("A budget for this account does not exist, would you like to create one (y/n)?")
                             .with_default(false)
@@ -520,7 +543,7 @@ impl AccountOperations for CreditCardAccount {
                             .unwrap();
                         if add_budget {
                             let budget = Budget::new(self.uid, self.id, &self.db);
-                            budget.create_budget();
+                            budget.create_budget()?;
                             self.budget = Some(budget);
                             self.set_budget();
                         }
@@ -532,7 +555,7 @@ impl AccountOperations for CreditCardAccount {
                         break;
                     }
                     let selected_record = record_or_none.unwrap();
-                    self.charge.modify(selected_record);
+                    self.charge.modify(selected_record)?;
                     let go_again = Confirm::new("Modify additional records? (y/n)")
                         .prompt()
                         .unwrap();
@@ -541,19 +564,19 @@ impl AccountOperations for CreditCardAccount {
                     }
                 },
                 "Credit Line" => {
-                    let credit_card = self.db.get_credit_card(self.uid, self.id).unwrap();
-                    let updated_credit_line = CustomType::<f32>::new("Enter updated credit line:")
-                        .with_default(credit_card.info.credit_line)
-                        .with_placeholder("1000.00")
-                        .with_error_message("Enter a valid credit line!")
-                        .prompt()
-                        .unwrap();
+                    let credit_card = self.db.get_credit_card(self.uid, self.id)?;
+                    let updated_credit_line =
+                        CustomType::<Money>::new("Enter updated credit line:")
+                            .with_default(credit_card.info.credit_line)
+                            .with_placeholder("1000.00")
+                            .with_error_message("Enter a valid credit line!")
+                            .prompt()
+                            .unwrap();
                     self.db
-                        .update_credit_line(self.uid, self.id, updated_credit_line)
-                        .unwrap();
+                        .update_credit_line(self.uid, self.id, updated_credit_line)?;
                 }
                 "Statement Due Date" => {
-                    let credit_card = self.db.get_credit_card(self.uid, self.id).unwrap();
+                    let credit_card = self.db.get_credit_card(self.uid, self.id)?;
                     let updated_statement_due_date =
                         CustomType::<u32>::new("Enter updated statement due date:")
                             .with_default(credit_card.info.statement_due_date)
@@ -561,13 +584,15 @@ impl AccountOperations for CreditCardAccount {
                             .with_error_message("Enter a statement due date!")
                             .prompt()
                             .unwrap();
-                    self.db
-                        .update_statement_due_date(self.uid, self.id, updated_statement_due_date)
-                        .unwrap();
+                    self.db.update_statement_due_date(
+                        self.uid,
+                        self.id,
+                        updated_statement_due_date,
+                    )?;
                 }
                 "Categories" => {
                     loop {
-                        let records = self.db.get_categories(self.uid, self.id).unwrap();
+                        let records = self.db.get_categories(self.uid, self.id)?;
                         let mut choices: Vec<String> = records
                             .iter()
                             .map(|x| x.category.name.clone())
@@ -597,18 +622,15 @@ impl AccountOperations for CreditCardAccount {
                                     self.id,
                                     chosen_category,
                                     new_name,
-                                );
+                                )?;
                             }
                             "Remove" => {
                                 // check if category is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_category(
-                                        self.uid,
-                                        self.id,
-                                        chosen_category.clone(),
-                                    )
-                                    .unwrap();
+                                let is_referenced = self.db.check_if_ledger_references_category(
+                                    self.uid,
+                                    self.id,
+                                    chosen_category.clone(),
+                                )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -617,13 +639,11 @@ impl AccountOperations for CreditCardAccount {
                                             "{} | {} | {} | {} ",
                                             record.info.date,
                                             chosen_category.clone(),
-                                            self.db
-                                                .get_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.participant
-                                                )
-                                                .unwrap(),
+                                            self.db.get_participant(
+                                                self.uid,
+                                                self.id,
+                                                record.info.participant
+                                            )?,
                                             record.info.amount
                                         );
                                         print!("\t{}", v);
@@ -639,7 +659,7 @@ impl AccountOperations for CreditCardAccount {
                                         self.uid,
                                         self.id,
                                         chosen_category.clone(),
-                                    );
+                                    )?;
                                 }
                             }
                             "None" => {
@@ -672,8 +692,7 @@ impl AccountOperations for CreditCardAccount {
                                 panic!("Unrecognized input: {}", selected_ptype);
                             }
                         };
-                        let participants =
-                            self.db.get_participants(self.uid, self.id, ptype).unwrap();
+                        let participants = self.db.get_participants(self.uid, self.id, ptype)?;
                         let mut people = participants
                             .iter()
                             .map(|x| x.participant.name.clone())
@@ -703,27 +722,23 @@ impl AccountOperations for CreditCardAccount {
                                     .prompt()
                                     .unwrap()
                                     .to_string();
-                                self.db
-                                    .update_participant_name(
-                                        self.uid,
-                                        self.id,
-                                        ptype,
-                                        chosen_person.clone(),
-                                        new_name,
-                                    )
-                                    .unwrap();
+                                self.db.update_participant_name(
+                                    self.uid,
+                                    self.id,
+                                    ptype,
+                                    chosen_person.clone(),
+                                    new_name,
+                                )?;
                             }
                             "Remove" => {
                                 // check if participant is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_participant(
+                                let is_referenced =
+                                    self.db.check_if_ledger_references_participant(
                                         self.uid,
                                         self.id,
                                         ptype,
                                         chosen_person.clone(),
-                                    )
-                                    .unwrap();
+                                    )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -731,13 +746,11 @@ impl AccountOperations for CreditCardAccount {
                                         let v = format!(
                                             "{} | {} | {} | {} ",
                                             record.info.date,
-                                            self.db
-                                                .get_category_name(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.category_id
-                                                )
-                                                .unwrap(),
+                                            self.db.get_category_name(
+                                                self.uid,
+                                                self.id,
+                                                record.info.category_id
+                                            )?,
                                             chosen_person.clone(),
                                             record.info.amount
                                         );
@@ -751,42 +764,34 @@ impl AccountOperations for CreditCardAccount {
                                 if delete {
                                     match ptype {
                                         ParticipantType::Payee => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         ParticipantType::Payer => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         _ => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                     }
                                 }
@@ -807,7 +812,7 @@ impl AccountOperations for CreditCardAccount {
                     }
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!")
@@ -820,9 +825,14 @@ impl AccountOperations for CreditCardAccount {
                 break;
             }
         }
+        Ok(())
     }
 
-    fn export(&self) {
+    fn export(&self) -> Result<()> {
+        let format = match select_export_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -838,33 +848,43 @@ impl AccountOperations for CreditCardAccount {
         let mut rl = Editor::with_config(config).unwrap();
         rl.set_helper(Some(g));
 
-        let mut wtr =
-            csv::Writer::from_path(rl.readline("Enter path to CSV file: ").unwrap()).unwrap();
-        let ledger = self.get_ledger();
+        let path = rl
+            .readline(&format!("Enter path to {} file: ", format))
+            .unwrap();
+        let mut entries = Vec::new();
+        let ledger = self.get_ledger()?;
         if !ledger.is_empty() {
             for record in ledger {
                 let csv_ledger_record: shared_lib::LedgerEntry = LedgerEntry {
                     date: record.info.date,
                     amount: record.info.amount,
                     transfer_type: record.info.transfer_type,
-                    participant: self
-                        .db
-                        .get_participant(self.uid, self.id, record.info.participant)
-                        .unwrap(),
-                    category: self
-                        .db
-                        .get_category_name(self.uid, self.id, record.info.category_id)
-                        .unwrap(),
+                    participant: self.db.get_participant(
+                        self.uid,
+                        self.id,
+                        record.info.participant,
+                    )?,
+                    category: self.db.get_category_name(
+                        self.uid,
+                        self.id,
+                        record.info.category_id,
+                    )?,
                     description: record.info.description,
                     stock_info: None,
                 };
-                let flattened = FlatLedgerEntry::from(csv_ledger_record);
-                wtr.serialize(flattened).unwrap();
+                entries.push(csv_ledger_record);
             }
         }
+        write_export_file(
+            format,
+            QifAccountType::CreditCard,
+            Path::new(&path),
+            entries,
+        )?;
+        Ok(())
     }
 
-    fn report(&self) {
+    fn report(&self) -> Result<()> {
         const REPORT_OPTIONS: [&'static str; 5] = [
             "Current Balance",
             "Credit Line",
@@ -879,16 +899,16 @@ impl AccountOperations for CreditCardAccount {
                 .to_string();
         match choice.as_str() {
             "Current Balance" => {
-                let value = self.charge.get_current_balance();
+                let value = self.charge.get_current_balance()?;
                 println!("\tCurrent Balance: {}", value);
             }
             "Credit Line" => {
-                println!("\tCredit Line: {}", self.charge.get_credit_line());
+                println!("\tCredit Line: {}", self.charge.get_credit_line()?);
             }
             "Remaining Credit" => {
                 println!(
                     "\tRemaining credit: {}",
-                    self.charge.get_remaining_in_credit_line()
+                    self.charge.get_remaining_in_credit_line()?
                 );
             }
             "Spend Analyzer" => {
@@ -896,8 +916,7 @@ impl AccountOperations for CreditCardAccount {
                 let expenses_wrapped = self
                     .charge
                     .db
-                    .get_expenditures_between_dates(self.uid, self.id, start, end)
-                    .unwrap();
+                    .get_expenditures_between_dates(self.uid, self.id, start, end)?;
                 if expenses_wrapped.is_some() {
                     let mut expenses = expenses_wrapped.unwrap();
                     expenses.sort_by(|x, y| {
@@ -914,15 +933,18 @@ impl AccountOperations for CreditCardAccount {
                 }
             }
             "None" => {
-                return;
+                return Ok(());
             }
             _ => {
                 panic!("Unrecognized input!");
             }
         }
+        Ok(())
     }
 
-    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Option<u32> {
+    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Result<Option<u32>> {
+        let transaction = self.db.begin_transaction()?;
+
         let from_account;
         let to_account;
 
@@ -938,18 +960,16 @@ impl AccountOperations for CreditCardAccount {
                     self.uid,
                     self.id,
                     "Withdrawal".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payee,
                     true,
-                );
+                )?;
                 (
                     TransferType::WithdrawalToExternalAccount,
                     format!(
@@ -966,18 +986,16 @@ impl AccountOperations for CreditCardAccount {
                     self.uid,
                     self.id,
                     "Deposit".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payer,
                     true,
-                );
+                )?;
                 (
                     TransferType::DepositFromExternalAccount,
                     format!(
@@ -987,7 +1005,7 @@ impl AccountOperations for CreditCardAccount {
                 )
             }
             _ => {
-                return None;
+                return Ok(None);
             }
         };
 
@@ -1002,16 +1020,12 @@ impl AccountOperations for CreditCardAccount {
 
         let (from_ledger_id, to_ledger_id) = match new_ttype {
             TransferType::WithdrawalToExternalAccount => (
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
                 entry.id,
             ),
             TransferType::DepositFromExternalAccount => (
                 entry.id,
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
             ),
             _ => {
                 panic!("Unrecognized input!")
@@ -1025,11 +1039,12 @@ impl AccountOperations for CreditCardAccount {
             to_ledger: to_ledger_id,
         };
 
-        return Some(
-            self.db
-                .add_account_transaction(self.uid, transaction_record)
-                .unwrap(),
-        );
+        let id = self
+            .db
+            .add_account_transaction(self.uid, transaction_record)?;
+        transaction.commit()?;
+
+        return Ok(Some(id));
     }
 }
 
@@ -1037,26 +1052,32 @@ impl AccountData for CreditCardAccount {
     fn get_id(&self) -> u32 {
         return self.id;
     }
-    fn get_name(&self) -> String {
-        return self.db.get_account_name(self.uid, self.id).unwrap();
+    fn get_uid(&self) -> u32 {
+        return self.uid;
+    }
+    fn get_name(&self) -> Result<String> {
+        Ok(self.db.get_account_name(self.uid, self.id)?)
     }
-    fn get_ledger(&self) -> Vec<LedgerRecord> {
-        return self.db.get_ledger(self.uid, self.id).unwrap();
+    fn get_ledger(&self) -> Result<Vec<LedgerRecord>> {
+        Ok(self.db.get_ledger(self.uid, self.id)?)
     }
-    fn get_ledger_within_dates(&self, start: NaiveDate, end: NaiveDate) -> Vec<LedgerRecord> {
-        return self
+    fn get_ledger_within_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<LedgerRecord>> {
+        Ok(self
             .db
-            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)
-            .unwrap();
+            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)?)
     }
-    fn get_displayable_ledger(&self) -> Vec<crate::types::ledger::DisplayableLedgerRecord> {
-        return self.db.get_displayable_ledger(self.uid, self.id).unwrap();
+    fn get_displayable_ledger(&self) -> Result<Vec<crate::types::ledger::DisplayableLedgerRecord>> {
+        Ok(self.db.get_displayable_ledger(self.uid, self.id)?)
     }
-    fn get_value(&self) -> f32 {
-        return self.charge.get_current_balance();
+    fn get_value(&self) -> Result<Money> {
+        self.charge.get_current_balance()
     }
-    fn get_value_on_day(&self, day: NaiveDate) -> f32 {
-        return self.charge.get_balance_on_day(day);
+    fn get_value_on_day(&self, day: NaiveDate) -> Result<Money> {
+        self.charge.get_balance_on_day(day)
     }
     fn get_open_date(&self) -> NaiveDate {
         return self.open_date;
@@ -1065,20 +1086,20 @@ impl AccountData for CreditCardAccount {
 
 #[cfg(feature = "ratatui_support")]
 impl AccountUI for CreditCardAccount {
-    fn populate_page_cache_f32(&self, app: &mut App) {
+    fn populate_page_cache_f32(&self, app: &mut App) -> Result<()> {
         let mut kv: HashMap<String, DisplayValue> = HashMap::new();
 
         kv.insert(
             KEY_TOTAL_VALUE.into(),
-            DisplayValue::Float(self.get_value()),
+            DisplayValue::Money(self.get_value()?),
         );
         kv.insert(
             KEY_REMAINING_CREDIT.into(),
-            DisplayValue::Float(self.charge.get_remaining_in_credit_line()),
+            DisplayValue::Money(self.charge.get_remaining_in_credit_line()?),
         );
         kv.insert(
             KEY_CREDIT_LINE.into(),
-            DisplayValue::Float(self.charge.get_credit_line()),
+            DisplayValue::Money(self.charge.get_credit_line()?),
         );
         kv.insert(
             KEY_DAYS_UNTIL_DUE.into(),
@@ -1090,9 +1111,10 @@ impl AccountUI for CreditCardAccount {
         );
 
         app.page_cache_f32 = Some(kv);
-        app.ledger_entries = Some(self.get_displayable_ledger());
+        app.ledger_entries = Some(self.get_displayable_ledger()?);
         app.linechart_cache = None;
         app.barchart_cache = self.get_barchart_data(app);
+        Ok(())
     }
     fn render(&self, frame: &mut Frame, area: Rect, app: &mut App) {
         let chunk = Layout::default()
@@ -16,6 +16,7 @@
 -----------------------------------------------------------------------*/
 use chrono::{Datelike, Days, Local, NaiveDate, NaiveTime};
 use core::f64;
+use fin_import::qif::QifAccountType;
 use inquire::Confirm;
 use inquire::Select;
 use inquire::Text;
@@ -45,23 +46,31 @@ use rustyline::Helper;
 use rustyline::Highlighter;
 use rustyline::Hinter;
 use rustyline::Validator;
-use shared_lib::{FlatLedgerEntry, LedgerEntry};
+use shared_lib::LedgerEntry;
 use std::collections::HashMap;
 use std::path::Path;
 #[cfg(feature = "timer")]
 use std::time::{Duration, Instant};
 
+use super::base::export::{select_export_format, write_export_file};
+use super::base::import::{
+    apply_merges, confirm_import, read_import_file, save_import_settings, select_import_format,
+    ImportedEntry,
+};
 #[cfg(feature = "ratatui_support")]
 use crate::app::app::{App, DisplayValue, LineChart};
 #[cfg(feature = "ratatui_support")]
 use crate::app::screen::ledger_table_constraint_len_calculator;
 use crate::database::DbConn;
+use crate::error::{FintoolError, Result};
 use crate::tui::get_analysis_period_dates;
 use crate::tui::query_user_for_analysis_period;
+use crate::tui::tui_gains::report_gains;
 use crate::types::accounts::AccountInfo;
 use crate::types::accounts::AccountRecord;
 use crate::types::accounts::AccountTransaction;
 use crate::types::accounts::AccountType;
+use crate::types::investments::SaleMethod;
 use crate::types::investments::StockInfo;
 use crate::types::investments::StockRecord;
 use crate::types::investments::StockSplitInfo;
@@ -72,9 +81,8 @@ use crate::types::ledger::LedgerInfo;
 use crate::types::ledger::LedgerRecord;
 use crate::types::participants::ParticipantType;
 use crate::types::stock_prices::StockPriceInfo;
-use csv::ReaderBuilder;
 use rustyline::Editor;
-use shared_lib::TransferType;
+use shared_lib::{Money, Quantity, TransferType};
 
 use super::base::variable_account::VariableAccount;
 use super::base::Account;
@@ -109,7 +117,7 @@ struct FilePathHelper {
 }
 
 impl AccountCreation for InvestmentAccountManager {
-    fn create(uid: u32, name: String, _db: &DbConn) -> AccountRecord {
+    fn create(uid: u32, name: String, _db: &DbConn) -> Result<AccountRecord> {
         let has_bank = true;
         let has_stocks = true;
         let has_ledger = false;
@@ -124,8 +132,8 @@ impl AccountCreation for InvestmentAccountManager {
             has_budget: has_budget,
         };
 
-        let aid = _db.add_account(uid, &account).unwrap();
-        let acct = Self::new(uid, aid, _db);
+        let aid = _db.add_account(uid, &account)?;
+        let acct = Self::new(uid, aid, _db)?;
 
         let initialize_account =
             Confirm::new("Would you like to open the account with an initial deposit?")
@@ -133,22 +141,19 @@ impl AccountCreation for InvestmentAccountManager {
                 .unwrap();
 
         if initialize_account {
-            acct.variable.fixed.deposit(None, false);
+            acct.variable.fixed.deposit(None, false)?;
         }
 
-        return AccountRecord {
-            id: aid,
-            info: account,
-        };
+        return Ok(AccountRecord::owned(uid, aid, account));
     }
 }
 
 impl InvestmentAccountManager {
-    pub fn new(uid: u32, id: u32, db: &DbConn) -> Self {
-        let mut ledger = db.get_ledger(uid, id).unwrap();
+    pub fn new(uid: u32, id: u32, db: &DbConn) -> Result<Self> {
+        let mut ledger = db.get_ledger(uid, id)?;
         let open_date = if !ledger.is_empty() {
             ledger.sort_by(|l1, l2| (&l1.info.date).cmp(&l2.info.date));
-            NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d").unwrap()
+            NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d")?
         } else {
             Local::now().date_naive()
         };
@@ -157,21 +162,21 @@ impl InvestmentAccountManager {
             uid: uid,
             id: id,
             db: db.clone(),
-            variable: VariableAccount::new(uid, id, db, open_date),
+            variable: VariableAccount::new(uid, id, db, open_date)?,
             open_date: open_date,
         };
 
-        acct
+        Ok(acct)
     }
 
-    pub fn get_linechart(&self, app: &mut App) -> Option<LineChart> {
+    pub fn get_linechart(&self, app: &mut App) -> Result<Option<LineChart>> {
         let (start, end) = (app.analysis_start, app.analysis_end);
-        let mut ledger = self.get_ledger_within_dates(start, end);
+        let mut ledger = self.get_ledger_within_dates(start, end)?;
         ledger.push(LedgerRecord {
             id: 0,
             info: LedgerInfo {
                 date: Local::now().date_naive().to_string(),
-                amount: 0.0,
+                amount: Money::ZERO,
                 transfer_type: TransferType::ZeroSumChange,
                 participant: 0,
                 category_id: 0,
@@ -207,13 +212,13 @@ impl InvestmentAccountManager {
                     .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
                     .and_utc()
                     .timestamp_millis() as f64;
-                let mut aggregate = tpi_starting_amount as f64;
+                let mut aggregate = tpi_starting_amount.to_f64();
                 let mut dataset = vec![(timestamp, aggregate)];
                 transactions.push(LedgerRecord {
                     id: 0,
                     info: LedgerInfo {
                         date: Local::now().date_naive().to_string(),
-                        amount: 0.0,
+                        amount: Money::ZERO,
                         transfer_type: TransferType::ZeroSumChange,
                         participant: 0,
                         category_id: 0,
@@ -235,10 +240,10 @@ impl InvestmentAccountManager {
                             let tstamp = dt.and_utc().timestamp_millis() as f64;
                             aggregate = match record.info.transfer_type {
                                 TransferType::DepositFromExternalAccount => {
-                                    aggregate + record.info.amount as f64
+                                    aggregate + record.info.amount.to_f64()
                                 }
                                 TransferType::WithdrawalToExternalAccount => {
-                                    aggregate - record.info.amount as f64
+                                    aggregate - record.info.amount.to_f64()
                                 }
                                 _ => aggregate,
                             };
@@ -269,103 +274,115 @@ impl InvestmentAccountManager {
             None
         };
 
-        if let Some(time_period_investments) = time_period_investments_opt {
-            let mut date = start;
-            let mut total_account_values = Vec::new();
-            while date < end {
-                let value = self.variable.get_account_value_on_day(&date.clone());
-                if value.is_none() {
-                    break;
-                } else {
-                    use crate::accounts::base::AnalysisPeriod;
-
-                    let tstamp = NaiveDate::parse_from_str(&date.to_string(), "%Y-%m-%d")
-                        .expect(format!("Unexpected data: {}", date).as_str())
-                        .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
-                        .and_utc()
-                        .timestamp_millis() as f64;
-
-                    let partial_value = self.variable.get_account_value_on_day(&date);
-                    let mut aggregate = 0.0;
-                    if partial_value.is_none() {
-                        aggregate = aggregate;
-                    } else {
-                        aggregate = partial_value.unwrap() as f64;
-                    }
-                    max_total = if aggregate > max_total {
-                        aggregate
-                    } else {
-                        max_total
-                    };
-                    min_total = if aggregate < min_total {
-                        aggregate
-                    } else {
-                        min_total
-                    };
-                    tstamp_max = if tstamp > tstamp_max {
-                        tstamp
+        Ok(
+            if let Some(time_period_investments) = time_period_investments_opt {
+                let mut date = start;
+                let mut total_account_values = Vec::new();
+                while date < end {
+                    let value = self.variable.get_account_value_on_day(&date.clone())?;
+                    if value.is_none() {
+                        break;
                     } else {
-                        tstamp_max
-                    };
-                    total_account_values.push((tstamp, aggregate));
-
-                    date = match app.analysis_period {
-                        AnalysisPeriod::OneDay | AnalysisPeriod::OneWeek => {
-                            date.checked_add_days(Days::new(1)).unwrap()
-                        }
-                        AnalysisPeriod::OneMonth => date.checked_add_days(Days::new(2)).unwrap(),
-                        AnalysisPeriod::OneYear
-                        | AnalysisPeriod::ThreeMonths
-                        | AnalysisPeriod::SixMonths
-                        | AnalysisPeriod::YTD => date.checked_add_days(Days::new(7)).unwrap(),
-                        AnalysisPeriod::TwoYears => date.checked_add_days(Days::new(20)).unwrap(),
-                        AnalysisPeriod::FiveYears => date.checked_add_days(Days::new(50)).unwrap(),
-                        AnalysisPeriod::TenYears => date.checked_add_days(Days::new(100)).unwrap(),
-                        AnalysisPeriod::Custom | AnalysisPeriod::AllTime => {
-                            let diff = (end.num_days_from_ce() - start.num_days_from_ce()) as u32;
-                            let days_to_add: u32 = if diff <= 365 {
-                                1
-                            } else if diff <= (365 * 2) {
-                                2
-                            } else if diff <= (365 * 5) {
-                                5
-                            } else {
-                                10
-                            };
-                            date.checked_add_days(Days::new(days_to_add as u64))
-                                .unwrap()
+                        use crate::accounts::base::AnalysisPeriod;
+
+                        let tstamp = NaiveDate::parse_from_str(&date.to_string(), "%Y-%m-%d")
+                            .expect(format!("Unexpected data: {}", date).as_str())
+                            .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+                            .and_utc()
+                            .timestamp_millis() as f64;
+
+                        let partial_value = self.variable.get_account_value_on_day(&date)?;
+                        let mut aggregate = 0.0;
+                        if partial_value.is_none() {
+                            aggregate = aggregate;
+                        } else {
+                            aggregate = partial_value.unwrap().to_f64();
                         }
-                    };
+                        max_total = if aggregate > max_total {
+                            aggregate
+                        } else {
+                            max_total
+                        };
+                        min_total = if aggregate < min_total {
+                            aggregate
+                        } else {
+                            min_total
+                        };
+                        tstamp_max = if tstamp > tstamp_max {
+                            tstamp
+                        } else {
+                            tstamp_max
+                        };
+                        total_account_values.push((tstamp, aggregate));
+
+                        date = match app.analysis_period {
+                            AnalysisPeriod::OneDay | AnalysisPeriod::OneWeek => {
+                                date.checked_add_days(Days::new(1)).unwrap()
+                            }
+                            AnalysisPeriod::OneMonth => {
+                                date.checked_add_days(Days::new(2)).unwrap()
+                            }
+                            AnalysisPeriod::OneYear
+                            | AnalysisPeriod::ThreeMonths
+                            | AnalysisPeriod::SixMonths
+                            | AnalysisPeriod::YTD => date.checked_add_days(Days::new(7)).unwrap(),
+                            AnalysisPeriod::TwoYears => {
+                                date.checked_add_days(Days::new(20)).unwrap()
+                            }
+                            AnalysisPeriod::FiveYears => {
+                                date.checked_add_days(Days::new(50)).unwrap()
+                            }
+                            AnalysisPeriod::TenYears => {
+                                date.checked_add_days(Days::new(100)).unwrap()
+                            }
+                            AnalysisPeriod::Custom | AnalysisPeriod::AllTime => {
+                                let diff =
+                                    (end.num_days_from_ce() - start.num_days_from_ce()) as u32;
+                                let days_to_add: u32 = if diff <= 365 {
+                                    1
+                                } else if diff <= (365 * 2) {
+                                    2
+                                } else if diff <= (365 * 5) {
+                                    5
+                                } else {
+                                    10
+                                };
+                                date.checked_add_days(Days::new(days_to_add as u64))
+                                    .unwrap()
+                            }
+                        };
+                    }
                 }
-            }
 
-            Some(LineChart {
-                datasets: vec![time_period_investments, total_account_values],
-                y_max: max_total,
-                y_min: min_total,
-                y_step: (max_total - min_total) / 5.0,
-                x_max: tstamp_max,
-                x_min: tstamp_min,
-                x_labels: vec![start.to_string(), end.to_string()],
-                y_labels: float_range(min_total, max_total, (max_total - min_total) / 5.0)
-                    .into_iter()
-                    .map(|x| format!("{:.2}", x))
-                    .collect(),
-            })
-        } else {
-            None
-        }
+                Some(LineChart {
+                    datasets: vec![time_period_investments, total_account_values],
+                    y_max: max_total,
+                    y_min: min_total,
+                    y_step: (max_total - min_total) / 5.0,
+                    x_max: tstamp_max,
+                    x_min: tstamp_min,
+                    x_labels: vec![start.to_string(), end.to_string()],
+                    y_labels: float_range(min_total, max_total, (max_total - min_total) / 5.0)
+                        .into_iter()
+                        .map(|x| format!("{:.2}", x))
+                        .collect(),
+                })
+            } else {
+                None
+            },
+        )
     }
 }
 
 impl AccountOperations for InvestmentAccountManager {
-    fn record(&mut self) {
-        const RECORD_OPTIONS: [&'static str; 7] = [
+    fn record(&mut self) -> Result<()> {
+        const RECORD_OPTIONS: [&'static str; 8] = [
             "Deposit",
             "Withdrawal",
             "Purchase",
             "Sale",
             "Stock Split",
+            "Distribution",
             "Stock Price",
             "None",
         ];
@@ -379,31 +396,35 @@ impl AccountOperations for InvestmentAccountManager {
             .to_string();
             match action.as_str() {
                 "Deposit" => {
-                    self.variable.fixed.deposit(None, false);
+                    self.variable.fixed.deposit(None, false)?;
                     // update ledger
-                    self.get_ledger();
+                    self.get_ledger()?;
                 }
                 "Withdrawal" => {
-                    self.variable.fixed.withdrawal(None, false);
-                    self.get_ledger();
+                    self.variable.fixed.withdrawal(None, false)?;
+                    self.get_ledger()?;
                 }
                 "Purchase" => {
-                    self.variable.purchase_stock(None, false);
-                    self.get_ledger();
+                    self.variable.purchase_stock(None, false)?;
+                    self.get_ledger()?;
                 }
                 "Sale" => {
-                    self.variable.sell_stock(None, false);
-                    self.get_ledger();
+                    self.variable.sell_stock(None, false)?;
+                    self.get_ledger()?;
                 }
                 "Stock Split" => {
-                    self.variable.split_stock(None, false);
-                    self.get_ledger();
+                    self.variable.split_stock(None, false)?;
+                    self.get_ledger()?;
+                }
+                "Distribution" => {
+                    self.variable.record_distribution()?;
+                    self.get_ledger()?;
                 }
                 "Stock Price" => {
-                    self.variable.manually_record_stock_close_price();
+                    self.variable.manually_record_stock_close_price()?;
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!");
@@ -414,12 +435,17 @@ impl AccountOperations for InvestmentAccountManager {
                 .prompt()
                 .unwrap();
             if !record_again {
-                return;
+                return Ok(());
             }
         }
     }
 
-    fn import(&mut self) {
+    fn import(&mut self) -> Result<()> {
+        let format = match select_import_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
+
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -439,9 +465,11 @@ impl AccountOperations for InvestmentAccountManager {
         let mut bad_path;
         let mut csv: String = String::new();
         loop {
-            csv = rl.readline("Enter path to CSV file: ").unwrap();
+            csv = rl
+                .readline(&format!("Enter path to {} file: ", format))
+                .unwrap();
             if csv.to_string() == "none" {
-                return;
+                return Ok(());
             }
             bad_path = match Path::new(&csv).try_exists() {
                 Ok(true) => false,
@@ -459,27 +487,27 @@ impl AccountOperations for InvestmentAccountManager {
             } else {
                 let try_again = Confirm::new("Continue import?").prompt().unwrap();
                 if !try_again {
-                    return;
+                    return Ok(());
                 }
             }
         }
         fp = Path::new(&csv);
 
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(fp)
-            .unwrap();
-
-        let mut ledger_entries = Vec::new();
-        for result in rdr.deserialize::<LedgerEntry>() {
-            ledger_entries.push(result.unwrap());
+        let batch = read_import_file(&self.db, self.uid, self.id, format, fp)?;
+        if !confirm_import(&self.db, self.uid, self.id, &batch)? {
+            return Ok(());
         }
-        ledger_entries.sort_by(|x, y| {
-            (NaiveDate::parse_from_str(&x.date, "%Y-%m-%d").unwrap())
-                .cmp(&NaiveDate::parse_from_str(&y.date, "%Y-%m-%d").unwrap())
-        });
 
-        for entry in ledger_entries {
+        // write every entry or, should one fail, none of them
+        let transaction = self.db.begin_transaction()?;
+        apply_merges(&self.db, self.uid, self.id, &batch.merges)?;
+        save_import_settings(&self.db, self.uid, self.id, &batch)?;
+        let ledger_entries = batch.entries;
+
+        for ImportedEntry {
+            entry, external_id, ..
+        } in ledger_entries
+        {
             let ptype = if entry.transfer_type
                 == shared_lib::TransferType::WithdrawalToExternalAccount
             {
@@ -502,12 +530,15 @@ impl AccountOperations for InvestmentAccountManager {
                 if s.is_buy {
                     if s.is_split {
                         // if split, check that we own this symbol
-                        let symbols_owned = self.db.get_stock_tickers(self.uid, self.id).unwrap();
+                        let symbols_owned = self.db.get_stock_tickers(self.uid, self.id)?;
                         let symbol_found = symbols_owned
                             .iter()
                             .any(|i| *i == entry.participant.clone());
                         if !symbol_found {
-                            panic!("Attempting to register split of symbol not owned by account!");
+                            return Err(FintoolError::Import(format!(
+                                "Attempting to register split of symbol {} not owned by account!",
+                                entry.participant
+                            )));
                         }
 
                         txn = LedgerInfo {
@@ -520,33 +551,39 @@ impl AccountOperations for InvestmentAccountManager {
                                 entry.participant.clone(),
                                 ptype,
                                 false,
-                            ),
+                            )?,
                             category_id: self.db.check_and_add_category(
                                 self.uid,
                                 self.id,
                                 entry.category.to_ascii_uppercase(),
-                            ),
+                            )?,
                             description: entry.description,
                         };
 
-                        lid = self
-                            .db
-                            .add_ledger_entry(self.uid, self.id, txn.clone())
-                            .unwrap();
+                        lid = self.db.add_ledger_entry(self.uid, self.id, txn.clone())?;
 
                         // get total shares for ticker and divide by split
-                        let stocks_owned = self
-                            .db
-                            .get_stocks(self.uid, self.id, entry.participant.clone())
-                            .unwrap();
-                        let all_shares: f32 = stocks_owned.iter().map(|x| x.info.remaining).sum();
+                        let stocks_owned =
+                            self.db
+                                .get_stocks(self.uid, self.id, entry.participant.clone())?;
+                        let all_shares: Quantity =
+                            stocks_owned.iter().map(|x| x.info.remaining).sum();
                         // lpl takes the split and adds the difference to your account
                         // i.e., if the split is 3:1, it will take your 1 part and add 2 parts
-                        let split_factor = (s.shares + all_shares) / all_shares;
-                        let stock_split_id = self
-                            .db
-                            .add_stock_split(self.uid, self.id, split_factor.clone(), lid)
-                            .unwrap();
+                        let split_factor = (s.shares + all_shares)
+                            .checked_div(all_shares)
+                            .ok_or_else(|| {
+                                FintoolError::Import(format!(
+                                    "no shares of {} are held to split",
+                                    entry.participant
+                                ))
+                            })?;
+                        let stock_split_id = self.db.add_stock_split(
+                            self.uid,
+                            self.id,
+                            split_factor.clone(),
+                            lid,
+                        )?;
 
                         let stock_split_record = StockSplitRecord {
                             id: stock_split_id,
@@ -557,7 +594,7 @@ impl AccountOperations for InvestmentAccountManager {
                             txn_opt: Some(txn),
                         };
 
-                        self.variable.allocate_stock_split(stock_split_record);
+                        self.variable.allocate_stock_split(stock_split_record)?;
                     } else {
                         // if buy, confirm it is a public ticker
                         let public_ticker = self
@@ -570,11 +607,10 @@ impl AccountOperations for InvestmentAccountManager {
                             entry.participant.clone(),
                             ptype,
                             false,
-                        );
+                        )?;
 
                         txn = LedgerInfo {
-                            date: NaiveDate::parse_from_str(entry.date.as_str(), "%Y-%m-%d")
-                                .unwrap()
+                            date: NaiveDate::parse_from_str(entry.date.as_str(), "%Y-%m-%d")?
                                 .format("%Y-%m-%d")
                                 .to_string(),
                             amount: entry.amount,
@@ -584,11 +620,11 @@ impl AccountOperations for InvestmentAccountManager {
                                 self.uid,
                                 self.id,
                                 entry.category.to_ascii_uppercase(),
-                            ),
+                            )?,
                             description: entry.description,
                         };
 
-                        lid = self.db.add_ledger_entry(self.uid, self.id, txn).unwrap();
+                        lid = self.db.add_ledger_entry(self.uid, self.id, txn)?;
 
                         let my_s: crate::types::investments::StockInfo = StockInfo {
                             shares: s.shares,
@@ -597,7 +633,13 @@ impl AccountOperations for InvestmentAccountManager {
                             ledger_id: lid,
                         };
 
-                        self.db.add_stock_purchase(self.uid, self.id, my_s).unwrap();
+                        self.db.add_stock_purchase(self.uid, self.id, my_s)?;
+                        self.variable.link_imported_reinvestment(
+                            lid,
+                            &entry.participant,
+                            &entry.date,
+                            &entry.category,
+                        )?;
 
                         if !public_ticker {
                             let stock_price_info = StockPriceInfo {
@@ -607,18 +649,20 @@ impl AccountOperations for InvestmentAccountManager {
                             };
 
                             self.db
-                                .add_stock_price(self.uid, self.id, stock_price_info)
-                                .unwrap();
+                                .add_stock_price(self.uid, self.id, stock_price_info)?;
                         }
                     }
                 } else {
                     // if sale, check that we own this symbol
-                    let symbols_owned = self.db.get_stock_tickers(self.uid, self.id).unwrap();
+                    let symbols_owned = self.db.get_stock_tickers(self.uid, self.id)?;
                     let symbol_found = symbols_owned
                         .iter()
                         .any(|i| *i == entry.participant.clone());
                     if !symbol_found {
-                        panic!("Attempting to register sale of symbol not owned by account!");
+                        return Err(FintoolError::Import(format!(
+                            "Attempting to register sale of symbol {} not owned by account!",
+                            entry.participant
+                        )));
                     }
 
                     txn = LedgerInfo {
@@ -631,19 +675,16 @@ impl AccountOperations for InvestmentAccountManager {
                             entry.participant.clone(),
                             ptype,
                             false,
-                        ),
+                        )?,
                         category_id: self.db.check_and_add_category(
                             self.uid,
                             self.id,
                             entry.category.to_ascii_uppercase(),
-                        ),
+                        )?,
                         description: entry.description,
                     };
 
-                    lid = self
-                        .db
-                        .add_ledger_entry(self.uid, self.id, txn.clone())
-                        .unwrap();
+                    lid = self.db.add_ledger_entry(self.uid, self.id, txn.clone())?;
 
                     let my_s: crate::types::investments::StockInfo = StockInfo {
                         shares: s.shares,
@@ -651,24 +692,20 @@ impl AccountOperations for InvestmentAccountManager {
                         remaining: s.remaining,
                         ledger_id: lid,
                     };
-                    let sale_id = self
-                        .db
-                        .add_stock_sale(self.uid, self.id, my_s.clone())
-                        .unwrap();
+                    let sale_id = self.db.add_stock_sale(self.uid, self.id, my_s.clone())?;
                     self.variable.allocate_sale_stock(
                         StockRecord {
                             id: sale_id,
                             info: my_s,
                             txn_opt: Some(txn),
                         },
-                        "LIFO".to_string(),
-                    );
+                        SaleMethod::Fifo,
+                    )?;
                 }
             } else {
                 // this is just a normal ledger transaction
                 let txn: LedgerInfo = LedgerInfo {
-                    date: NaiveDate::parse_from_str(entry.date.as_str(), "%Y-%m-%d")
-                        .unwrap()
+                    date: NaiveDate::parse_from_str(entry.date.as_str(), "%Y-%m-%d")?
                         .format("%Y-%m-%d")
                         .to_string(),
                     amount: entry.amount,
@@ -679,22 +716,33 @@ impl AccountOperations for InvestmentAccountManager {
                         entry.participant,
                         ptype,
                         false,
-                    ),
+                    )?,
                     category_id: self.db.check_and_add_category(
                         self.uid,
                         self.id,
                         entry.category.to_ascii_uppercase(),
-                    ),
+                    )?,
                     description: entry.description,
                 };
 
-                lid = self.db.add_ledger_entry(self.uid, self.id, txn).unwrap();
+                lid = self.db.add_ledger_entry(self.uid, self.id, txn.clone())?;
+                self.variable.record_imported_distribution(
+                    lid,
+                    txn.transfer_type,
+                    &entry.category,
+                )?;
+            }
+            if let Some(external_id) = external_id {
+                self.db
+                    .add_external_transaction_id(self.uid, self.id, lid, &external_id)?;
             }
         }
+        transaction.commit()?;
         self.variable.initialize_buffer();
+        Ok(())
     }
 
-    fn modify(&mut self) {
+    fn modify(&mut self) -> Result<()> {
         const MODIFY_OPTIONS: [&'static str; 4] = ["Ledger", "Categories", "Participant", "None"];
         loop {
             let modify_choice =
@@ -708,7 +756,7 @@ impl AccountOperations for InvestmentAccountManager {
                         break;
                     }
                     let selected_record = record_or_none.unwrap();
-                    self.variable.fixed.modify(selected_record);
+                    self.variable.fixed.modify(selected_record)?;
                     let go_again = Confirm::new("Modify additional records? (y/n)")
                         .prompt()
                         .unwrap();
@@ -718,7 +766,7 @@ impl AccountOperations for InvestmentAccountManager {
                 },
                 "Categories" => {
                     loop {
-                        let records = self.db.get_categories(self.uid, self.id).unwrap();
+                        let records = self.db.get_categories(self.uid, self.id)?;
                         let mut choices: Vec<String> = records
                             .iter()
                             .map(|x| x.category.name.clone())
@@ -748,18 +796,15 @@ impl AccountOperations for InvestmentAccountManager {
                                     self.id,
                                     chosen_category,
                                     new_name,
-                                );
+                                )?;
                             }
                             "Remove" => {
                                 // check if category is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_category(
-                                        self.uid,
-                                        self.id,
-                                        chosen_category.clone(),
-                                    )
-                                    .unwrap();
+                                let is_referenced = self.db.check_if_ledger_references_category(
+                                    self.uid,
+                                    self.id,
+                                    chosen_category.clone(),
+                                )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -768,13 +813,11 @@ impl AccountOperations for InvestmentAccountManager {
                                             "{} | {} | {} | {} ",
                                             record.info.date,
                                             chosen_category.clone(),
-                                            self.db
-                                                .get_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.participant
-                                                )
-                                                .unwrap(),
+                                            self.db.get_participant(
+                                                self.uid,
+                                                self.id,
+                                                record.info.participant
+                                            )?,
                                             record.info.amount
                                         );
                                         print!("\t{}", v);
@@ -790,7 +833,7 @@ impl AccountOperations for InvestmentAccountManager {
                                         self.uid,
                                         self.id,
                                         chosen_category.clone(),
-                                    );
+                                    )?;
                                 }
                             }
                             "None" => {
@@ -823,8 +866,7 @@ impl AccountOperations for InvestmentAccountManager {
                                 panic!("Unrecognized input: {}", selected_ptype);
                             }
                         };
-                        let participants =
-                            self.db.get_participants(self.uid, self.id, ptype).unwrap();
+                        let participants = self.db.get_participants(self.uid, self.id, ptype)?;
                         let mut people = participants
                             .iter()
                             .map(|x| x.participant.name.clone())
@@ -854,27 +896,23 @@ impl AccountOperations for InvestmentAccountManager {
                                     .prompt()
                                     .unwrap()
                                     .to_string();
-                                self.db
-                                    .update_participant_name(
-                                        self.uid,
-                                        self.id,
-                                        ptype,
-                                        chosen_person.clone(),
-                                        new_name,
-                                    )
-                                    .unwrap();
+                                self.db.update_participant_name(
+                                    self.uid,
+                                    self.id,
+                                    ptype,
+                                    chosen_person.clone(),
+                                    new_name,
+                                )?;
                             }
                             "Remove" => {
                                 // check if participant is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_participant(
+                                let is_referenced =
+                                    self.db.check_if_ledger_references_participant(
                                         self.uid,
                                         self.id,
                                         ptype,
                                         chosen_person.clone(),
-                                    )
-                                    .unwrap();
+                                    )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -882,13 +920,11 @@ impl AccountOperations for InvestmentAccountManager {
                                         let v = format!(
                                             "{} | {} | {} | {} ",
                                             record.info.date,
-                                            self.db
-                                                .get_category_name(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.category_id
-                                                )
-                                                .unwrap(),
+                                            self.db.get_category_name(
+                                                self.uid,
+                                                self.id,
+                                                record.info.category_id
+                                            )?,
                                             chosen_person.clone(),
                                             record.info.amount
                                         );
@@ -902,42 +938,34 @@ impl AccountOperations for InvestmentAccountManager {
                                 if delete {
                                     match ptype {
                                         ParticipantType::Payee => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         ParticipantType::Payer => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         _ => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                     }
                                 }
@@ -958,7 +986,7 @@ impl AccountOperations for InvestmentAccountManager {
                     }
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!")
@@ -971,9 +999,14 @@ impl AccountOperations for InvestmentAccountManager {
                 break;
             }
         }
+        Ok(())
     }
 
-    fn export(&self) {
+    fn export(&self) -> Result<()> {
+        let format = match select_export_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -989,9 +1022,11 @@ impl AccountOperations for InvestmentAccountManager {
         let mut rl = Editor::with_config(config).unwrap();
         rl.set_helper(Some(g));
 
-        let mut wtr =
-            csv::Writer::from_path(rl.readline("Enter path to CSV file: ").unwrap()).unwrap();
-        let ledger = self.get_ledger();
+        let path = rl
+            .readline(&format!("Enter path to {} file: ", format))
+            .unwrap();
+        let mut entries = Vec::new();
+        let ledger = self.get_ledger()?;
         if !ledger.is_empty() {
             for record in ledger {
                 let stock_record_opt = match record.info.transfer_type {
@@ -1001,13 +1036,12 @@ impl AccountOperations for InvestmentAccountManager {
                             .db
                             .check_and_get_stock_split_record_matching_from_ledger_id(
                                 self.uid, self.id, record.id,
-                            )
-                            .unwrap();
+                            )?;
                         if let Some(ss_record) = stock_split_opt {
                             Some(shared_lib::StockInfo {
-                                shares: 0.0,
-                                costbasis: 0.0,
-                                remaining: 0.0,
+                                shares: Quantity::ZERO,
+                                costbasis: Quantity::ZERO,
+                                remaining: Quantity::ZERO,
                                 is_buy: true,
                                 is_split: true,
                             })
@@ -1021,13 +1055,12 @@ impl AccountOperations for InvestmentAccountManager {
                             .db
                             .check_and_get_stock_sale_record_matching_from_ledger_id(
                                 self.uid, self.id, record.id,
-                            )
-                            .unwrap();
+                            )?;
                         if let Some(stock_sale) = stock_sale_opt {
                             Some(shared_lib::StockInfo {
                                 shares: stock_sale.info.shares,
                                 costbasis: stock_sale.info.costbasis,
-                                remaining: 0.0,
+                                remaining: Quantity::ZERO,
                                 is_buy: false,
                                 is_split: false,
                             })
@@ -1041,13 +1074,12 @@ impl AccountOperations for InvestmentAccountManager {
                             .db
                             .check_and_get_stock_purchase_record_matching_from_ledger_id(
                                 self.uid, self.id, self.id,
-                            )
-                            .unwrap();
+                            )?;
                         if let Some(purchase) = purchase_opt {
                             Some(shared_lib::StockInfo {
                                 shares: purchase.info.shares,
                                 costbasis: purchase.info.costbasis,
-                                remaining: 0.0,
+                                remaining: Quantity::ZERO,
                                 is_buy: false,
                                 is_split: false,
                             })
@@ -1063,28 +1095,38 @@ impl AccountOperations for InvestmentAccountManager {
                     date: record.info.date,
                     amount: record.info.amount,
                     transfer_type: record.info.transfer_type,
-                    participant: self
-                        .db
-                        .get_participant(self.uid, self.id, record.info.participant)
-                        .unwrap(),
-                    category: self
-                        .db
-                        .get_category_name(self.uid, self.id, record.info.category_id)
-                        .unwrap(),
+                    participant: self.db.get_participant(
+                        self.uid,
+                        self.id,
+                        record.info.participant,
+                    )?,
+                    category: self.db.get_category_name(
+                        self.uid,
+                        self.id,
+                        record.info.category_id,
+                    )?,
                     description: record.info.description,
                     stock_info: stock_record_opt,
                 };
-                let flattened = FlatLedgerEntry::from(csv_ledger_record);
-                wtr.serialize(flattened).unwrap();
+                entries.push(csv_ledger_record);
             }
         }
+        write_export_file(
+            format,
+            QifAccountType::Investment,
+            Path::new(&path),
+            entries,
+        )?;
+        Ok(())
     }
 
-    fn report(&self) {
-        const REPORT_OPTIONS: [&'static str; 4] = [
+    fn report(&self) -> Result<()> {
+        const REPORT_OPTIONS: [&'static str; 6] = [
             "Positions",
             "Total Value",
             "Time-Weighted Rate of Return",
+            "Income",
+            "Capital Gains",
             "None",
         ];
         let choice = Select::new("What would you like to report: ", REPORT_OPTIONS.to_vec())
@@ -1093,7 +1135,7 @@ impl AccountOperations for InvestmentAccountManager {
             .to_string();
         match choice.as_str() {
             "Positions" => {
-                let positions_wrapped = self.variable.get_positions();
+                let positions_wrapped = self.variable.get_positions()?;
                 if positions_wrapped.is_some() {
                     let positions = positions_wrapped.unwrap();
                     println!("\nPositions:");
@@ -1105,11 +1147,11 @@ impl AccountOperations for InvestmentAccountManager {
                 }
             }
             "Total Value" => {
-                let value = self.variable.get_current_value();
+                let value = self.variable.get_current_value()?;
                 println!("\tTotal Account Value: {}", value);
                 println!(
                     "\t\tFixed Account Value: {}",
-                    self.variable.fixed.get_current_value()
+                    self.variable.fixed.get_current_value()?
                 );
                 let today = Local::now().date_naive();
                 println!(
@@ -1123,16 +1165,25 @@ impl AccountOperations for InvestmentAccountManager {
                 let twr = self.variable.time_weighted_return(period_start, period_end);
                 println!("\tRate of return: {}%", twr);
             }
+            "Income" => {
+                self.variable.report_income()?;
+            }
+            "Capital Gains" => {
+                report_gains(&self.db, self.uid, Some(self.id))?;
+            }
             "None" => {
-                return;
+                return Ok(());
             }
             _ => {
                 panic!("Unrecognized input!");
             }
         }
+        Ok(())
     }
 
-    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Option<u32> {
+    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Result<Option<u32>> {
+        let transaction = self.db.begin_transaction()?;
+
         let from_account;
         let to_account;
 
@@ -1148,18 +1199,16 @@ impl AccountOperations for InvestmentAccountManager {
                     self.uid,
                     self.id,
                     "Withdrawal".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payee,
                     true,
-                );
+                )?;
                 (
                     TransferType::WithdrawalToExternalAccount,
                     format!(
@@ -1176,18 +1225,16 @@ impl AccountOperations for InvestmentAccountManager {
                     self.uid,
                     self.id,
                     "Deposit".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payer,
                     true,
-                );
+                )?;
                 (
                     TransferType::DepositFromExternalAccount,
                     format!(
@@ -1197,7 +1244,7 @@ impl AccountOperations for InvestmentAccountManager {
                 )
             }
             _ => {
-                return None;
+                return Ok(None);
             }
         };
 
@@ -1212,16 +1259,12 @@ impl AccountOperations for InvestmentAccountManager {
 
         let (from_ledger_id, to_ledger_id) = match new_ttype {
             TransferType::WithdrawalToExternalAccount => (
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
                 entry.id,
             ),
             TransferType::DepositFromExternalAccount => (
                 entry.id,
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
             ),
             _ => {
                 panic!("Unrecognized input!")
@@ -1235,11 +1278,12 @@ impl AccountOperations for InvestmentAccountManager {
             to_ledger: to_ledger_id,
         };
 
-        return Some(
-            self.db
-                .add_account_transaction(self.uid, transaction_record)
-                .unwrap(),
-        );
+        let id = self
+            .db
+            .add_account_transaction(self.uid, transaction_record)?;
+        transaction.commit()?;
+
+        return Ok(Some(id));
     }
 }
 
@@ -1247,35 +1291,37 @@ impl AccountData for InvestmentAccountManager {
     fn get_id(&self) -> u32 {
         return self.id;
     }
-    fn get_name(&self) -> String {
-        return self.db.get_account_name(self.uid, self.id).unwrap();
+    fn get_uid(&self) -> u32 {
+        return self.uid;
     }
-    fn get_ledger(&self) -> Vec<LedgerRecord> {
+    fn get_name(&self) -> Result<String> {
+        Ok(self.db.get_account_name(self.uid, self.id)?)
+    }
+    fn get_ledger(&self) -> Result<Vec<LedgerRecord>> {
         // let ledger = self.db.get_ledger(self.uid, self.id).unwrap();
         // return ledger;
-        return self.variable.fixed.ledger.clone();
+        Ok(self.variable.fixed.ledger.clone())
     }
-    fn get_ledger_within_dates(&self, start: NaiveDate, end: NaiveDate) -> Vec<LedgerRecord> {
+    fn get_ledger_within_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<LedgerRecord>> {
         // let ledger = self
         //     .db
         //     .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)
         //     .unwrap();
-        return self
-            .get_ledger()
-            .iter()
-            .filter(|x| {
-                NaiveDate::parse_from_str(&x.info.date, "%Y-%m-%d").expect("Unable to parse date!")
-                    >= start
-            })
-            .filter(|y| {
-                NaiveDate::parse_from_str(&y.info.date, "%Y-%m-%d").expect("Unable to parse date!")
-                    <= end
-            })
-            .cloned()
-            .collect();
+        let mut ledger = Vec::new();
+        for record in self.get_ledger()? {
+            let date = NaiveDate::parse_from_str(&record.info.date, "%Y-%m-%d")?;
+            if date >= start && date <= end {
+                ledger.push(record);
+            }
+        }
+        Ok(ledger)
     }
-    fn get_displayable_ledger(&self) -> Vec<crate::types::ledger::DisplayableLedgerRecord> {
-        return self.db.get_displayable_ledger(self.uid, self.id).unwrap();
+    fn get_displayable_ledger(&self) -> Result<Vec<crate::types::ledger::DisplayableLedgerRecord>> {
+        Ok(self.db.get_displayable_ledger(self.uid, self.id)?)
         // return self.get_ledger().clone().iter().map(|x| {
         //     DisplayableLedgerRecord {
         //         id : x.id.to_string(),
@@ -1291,15 +1337,14 @@ impl AccountData for InvestmentAccountManager {
         //     }
         // })
     }
-    fn get_value(&self) -> f32 {
-        return self.variable.get_current_value();
+    fn get_value(&self) -> Result<Money> {
+        self.variable.get_current_value()
     }
-    fn get_value_on_day(&self, day: NaiveDate) -> f32 {
-        if let Some(value) = self.variable.get_account_value_on_day(&day) {
-            value
-        } else {
-            0.0
-        }
+    fn get_value_on_day(&self, day: NaiveDate) -> Result<Money> {
+        Ok(self
+            .variable
+            .get_account_value_on_day(&day)?
+            .unwrap_or(Money::ZERO))
     }
     fn get_open_date(&self) -> NaiveDate {
         return self.open_date;
@@ -1433,12 +1478,12 @@ impl InvestmentAccountManager {
 
 #[cfg(feature = "ratatui_support")]
 impl AccountUI for InvestmentAccountManager {
-    fn populate_page_cache_f32(&self, app: &mut App) {
+    fn populate_page_cache_f32(&self, app: &mut App) -> Result<()> {
         let mut kv: HashMap<String, DisplayValue> = HashMap::new();
 
         kv.insert(
             KEY_TOTAL_VALUE.into(),
-            DisplayValue::Float(self.get_value()),
+            DisplayValue::Money(self.get_value()?),
         );
         kv.insert(
             KEY_GROWTH.into(),
@@ -1449,9 +1494,10 @@ impl AccountUI for InvestmentAccountManager {
         );
 
         app.page_cache_f32 = Some(kv);
-        app.ledger_entries = Some(self.get_displayable_ledger());
-        app.linechart_cache = self.get_linechart(app);
+        app.ledger_entries = Some(self.get_displayable_ledger()?);
+        app.linechart_cache = self.get_linechart(app)?;
         app.barchart_cache = None;
+        Ok(())
     }
 
     fn render(&self, frame: &mut Frame, area: Rect, app: &mut App) {
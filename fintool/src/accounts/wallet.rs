@@ -17,7 +17,7 @@
 use chrono::Date;
 use chrono::Local;
 use chrono::NaiveDate;
-use csv::ReaderBuilder;
+use fin_import::qif::QifAccountType;
 use inquire::Confirm;
 use inquire::Select;
 use inquire::Text;
@@ -47,13 +47,18 @@ use rustyline::Helper;
 use rustyline::Highlighter;
 use rustyline::Hinter;
 use rustyline::Validator;
-use shared_lib::{FlatLedgerEntry, LedgerEntry};
+use shared_lib::LedgerEntry;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::iter::zip;
 use std::path::Path;
 use std::rc;
 
+use super::base::export::{select_export_format, write_export_file};
+use super::base::import::{
+    apply_merges, confirm_import, read_import_file, save_import_settings, select_import_format,
+    ImportedEntry,
+};
 use crate::accounts::base::budget::Budget;
 use crate::accounts::base::KEY_TOTAL_VALUE;
 #[cfg(feature = "ratatui_support")]
@@ -61,6 +66,7 @@ use crate::app::app::{App, BarChartData, DisplayValue};
 #[cfg(feature = "ratatui_support")]
 use crate::app::screen::ledger_table_constraint_len_calculator;
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::tui::get_analysis_period_dates;
 use crate::tui::query_user_for_analysis_period;
 use crate::types::accounts::AccountInfo;
@@ -71,7 +77,7 @@ use crate::types::ledger::LedgerInfo;
 use crate::types::ledger::LedgerRecord;
 use crate::types::participants;
 use crate::types::participants::ParticipantType;
-use shared_lib::TransferType;
+use shared_lib::{Money, TransferType};
 
 use super::base::fixed_account::FixedAccount;
 use super::base::liquid_account::LiquidAccount;
@@ -111,7 +117,7 @@ struct FilePathHelper {
 }
 
 impl Wallet {
-    pub fn new(uid: u32, id: u32, db: &DbConn) -> Self {
+    pub fn new(uid: u32, id: u32, db: &DbConn) -> Result<Self> {
         let mut acct: Wallet = Self {
             uid: uid,
             id: id,
@@ -121,15 +127,15 @@ impl Wallet {
             budget: None,
         };
 
-        let mut ledger = acct.get_ledger();
+        let mut ledger = acct.get_ledger()?;
         if !ledger.is_empty() {
             ledger.sort_by(|l1, l2| (&l1.info.date).cmp(&l2.info.date));
-            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d").unwrap();
+            acct.open_date = NaiveDate::parse_from_str(&ledger[0].info.date, "%Y-%m-%d")?;
         }
         if acct.has_budget() {
             acct.budget = Some(Budget::new(uid, id, db));
         }
-        acct
+        Ok(acct)
     }
     pub fn get_barchart_data(&self, app: &mut App) -> Option<BarChartData> {
         if let Some(mut expenditures) = self
@@ -166,7 +172,7 @@ impl Wallet {
                 // remove any expenditures that don't map to a budget category, place in to misc category
                 let mut misc_expenditures = Expenditure {
                     category: "Misc".to_string(),
-                    amount: 0.0,
+                    amount: Money::ZERO,
                 };
                 expenditures.retain(|expenditure| {
                     if budget
@@ -188,8 +194,8 @@ impl Wallet {
                 });
 
                 let mut labels: Vec<String> = Vec::new();
-                let mut budget_dataset: HashMap<String, (f32, u64)> = HashMap::new();
-                let mut expenditure_dataset: HashMap<String, (f32, u64)> = HashMap::new();
+                let mut budget_dataset: HashMap<String, (Money, u64)> = HashMap::new();
+                let mut expenditure_dataset: HashMap<String, (Money, u64)> = HashMap::new();
                 for elem in zip(budget, expenditures) {
                     let budget_value = super::base::budget::scale_budget_value_to_analysis_period(
                         elem.0.item.value,
@@ -199,25 +205,30 @@ impl Wallet {
                     let expenditure_value = elem.1.amount;
 
                     labels.push(elem.1.category.clone());
-                    budget_dataset
-                        .insert(elem.1.category.clone(), (budget_value, budget_value as u64));
+                    budget_dataset.insert(
+                        elem.1.category.clone(),
+                        (budget_value, budget_value.to_f64() as u64),
+                    );
                     expenditure_dataset.insert(
                         elem.1.category.clone(),
-                        (expenditure_value, expenditure_value as u64),
+                        (expenditure_value, expenditure_value.to_f64() as u64),
                     );
                 }
 
-                if misc_expenditures.amount > 0.0 {
+                if misc_expenditures.amount.is_positive() {
                     let label: String = "Misc".into();
                     labels.push(label.clone());
-                    budget_dataset.insert(label.clone(), (0.0, 0));
+                    budget_dataset.insert(label.clone(), (Money::ZERO, 0));
                     expenditure_dataset.insert(
                         label,
-                        (misc_expenditures.amount, misc_expenditures.amount as u64),
+                        (
+                            misc_expenditures.amount,
+                            misc_expenditures.amount.to_f64() as u64,
+                        ),
                     );
                 }
 
-                let mut bars: HashMap<String, HashMap<String, (f32, u64)>> = HashMap::new();
+                let mut bars: HashMap<String, HashMap<String, (Money, u64)>> = HashMap::new();
                 bars.insert(KEY_BARCHART_BUDGET.into(), budget_dataset);
                 bars.insert(KEY_BARCHART_EXPENDITURES.into(), expenditure_dataset);
 
@@ -251,17 +262,17 @@ impl Wallet {
                 }
 
                 let mut labels: Vec<String> = Vec::new();
-                let mut expenditure_dataset: HashMap<String, (f32, u64)> = HashMap::new();
+                let mut expenditure_dataset: HashMap<String, (Money, u64)> = HashMap::new();
                 for elem in expenditures {
                     let expenditure_value = elem.amount;
                     labels.push(elem.category.clone());
                     expenditure_dataset.insert(
                         elem.category.clone(),
-                        (expenditure_value, expenditure_value as u64),
+                        (expenditure_value, expenditure_value.to_f64() as u64),
                     );
                 }
 
-                let mut bars: HashMap<String, HashMap<String, (f32, u64)>> = HashMap::new();
+                let mut bars: HashMap<String, HashMap<String, (Money, u64)>> = HashMap::new();
                 bars.insert("Expenditures".into(), expenditure_dataset);
 
                 return Some(BarChartData {
@@ -276,7 +287,7 @@ impl Wallet {
 }
 
 impl AccountCreation for Wallet {
-    fn create(uid: u32, name: String, _db: &DbConn) -> AccountRecord {
+    fn create(uid: u32, name: String, _db: &DbConn) -> Result<AccountRecord> {
         let has_bank = true;
         let has_stocks = false;
         let has_ledger = false;
@@ -291,8 +302,8 @@ impl AccountCreation for Wallet {
             has_budget: has_budget,
         };
 
-        let aid = _db.add_account(uid, &account).unwrap();
-        let acct = Self::new(uid, aid, _db);
+        let aid = _db.add_account(uid, &account)?;
+        let acct = Self::new(uid, aid, _db)?;
 
         let initialize_account =
             Confirm::new("Would you like to open the account with an initial deposit?")
@@ -300,7 +311,7 @@ impl AccountCreation for Wallet {
                 .unwrap();
 
         if initialize_account {
-            acct.fixed.deposit(None, false);
+            acct.fixed.deposit(None, false)?;
         }
 
         let add_budget = Confirm::new("Would you like to associate a budget to this account?")
@@ -308,21 +319,18 @@ impl AccountCreation for Wallet {
             .prompt()
             .unwrap();
         if add_budget {
-            let x = Self::new(uid, aid, _db);
+            let x = Self::new(uid, aid, _db)?;
             let budget = Budget::new(uid, aid, _db);
-            budget.create_budget();
+            budget.create_budget()?;
             x.set_budget();
         }
 
-        return AccountRecord {
-            id: aid,
-            info: account,
-        };
+        return Ok(AccountRecord::owned(uid, aid, account));
     }
 }
 
 impl AccountOperations for Wallet {
-    fn record(&mut self) {
+    fn record(&mut self) -> Result<()> {
         const RECORD_OPTIONS: [&'static str; 3] = ["Deposit", "Withdrawal", "None"];
         loop {
             let action = Select::new(
@@ -334,13 +342,13 @@ impl AccountOperations for Wallet {
             .to_string();
             match action.as_str() {
                 "Deposit" => {
-                    self.fixed.deposit(None, false);
+                    self.fixed.deposit(None, false)?;
                 }
                 "Withdrawal" => {
-                    self.fixed.withdrawal(None, false);
+                    self.fixed.withdrawal(None, false)?;
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!");
@@ -350,12 +358,17 @@ impl AccountOperations for Wallet {
                 .prompt()
                 .unwrap();
             if !record_again {
-                return;
+                return Ok(());
             }
         }
     }
 
-    fn import(&mut self) {
+    fn import(&mut self) -> Result<()> {
+        let format = match select_import_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
+
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -375,9 +388,11 @@ impl AccountOperations for Wallet {
         let mut bad_path;
         let mut csv: String = String::new();
         loop {
-            csv = rl.readline("Enter path to CSV file: ").unwrap();
+            csv = rl
+                .readline(&format!("Enter path to {} file: ", format))
+                .unwrap();
             if csv.to_string() == "none" {
-                return;
+                return Ok(());
             }
             bad_path = match Path::new(&csv).try_exists() {
                 Ok(true) => false,
@@ -395,26 +410,28 @@ impl AccountOperations for Wallet {
             } else {
                 let try_again = Confirm::new("Continue import?").prompt().unwrap();
                 if !try_again {
-                    return;
+                    return Ok(());
                 }
             }
         }
         fp = Path::new(&csv);
 
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(fp)
-            .unwrap();
-
-        let mut ledger_entries = Vec::new();
-        for result in rdr.deserialize::<LedgerEntry>() {
-            ledger_entries.push(result.unwrap());
+        let batch = read_import_file(&self.db, self.uid, self.id, format, fp)?;
+        if !confirm_import(&self.db, self.uid, self.id, &batch)? {
+            return Ok(());
         }
-        ledger_entries.sort_by(|x, y| {
-            (NaiveDate::parse_from_str(&x.date, "%Y-%m-%d").unwrap())
-                .cmp(&NaiveDate::parse_from_str(&y.date, "%Y-%m-%d").unwrap())
-        });
-        for rcrd in ledger_entries {
+
+        // write every entry or, should one fail, none of them
+        let transaction = self.db.begin_transaction()?;
+        apply_merges(&self.db, self.uid, self.id, &batch.merges)?;
+        save_import_settings(&self.db, self.uid, self.id, &batch)?;
+        let ledger_entries = batch.entries;
+        for ImportedEntry {
+            entry: rcrd,
+            external_id,
+            ..
+        } in ledger_entries
+        {
             let ptype = if rcrd.transfer_type == TransferType::WithdrawalToExternalAccount {
                 ParticipantType::Payee
             } else if rcrd.transfer_type == TransferType::WithdrawalToInternalAccount {
@@ -425,8 +442,7 @@ impl AccountOperations for Wallet {
                 ParticipantType::Payer
             };
             let entry: LedgerInfo = LedgerInfo {
-                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")
-                    .unwrap()
+                date: NaiveDate::parse_from_str(rcrd.date.as_str(), "%Y-%m-%d")?
                     .format("%Y-%m-%d")
                     .to_string(),
                 amount: rcrd.amount,
@@ -437,19 +453,25 @@ impl AccountOperations for Wallet {
                     rcrd.participant,
                     ptype,
                     false,
-                ),
+                )?,
                 category_id: self.db.check_and_add_category(
                     self.uid,
                     self.id,
                     rcrd.category.to_ascii_uppercase(),
-                ),
+                )?,
                 description: rcrd.description,
             };
-            let _lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry).unwrap();
+            let lid: u32 = self.db.add_ledger_entry(self.uid, self.id, entry)?;
+            if let Some(external_id) = external_id {
+                self.db
+                    .add_external_transaction_id(self.uid, self.id, lid, &external_id)?;
+            }
         }
+        transaction.commit()?;
+        Ok(())
     }
 
-    fn modify(&mut self) {
+    fn modify(&mut self) -> Result<()> {
         const MODIFY_OPTIONS: [&'static str; 4] = ["Ledger", "Categories", "People", "None"];
         const MODIFY_OPTIONS_WITH_BUDGET: [&'static str; 5] =
             ["Ledger", "Categories", "People", "Budget", "None"];
@@ -468,7 +490,7 @@ impl AccountOperations for Wallet {
                         break;
                     }
                     let selected_record = record_or_none.unwrap();
-                    self.fixed.modify(selected_record);
+                    self.fixed.modify(selected_record)?;
                     let go_again = Confirm::new("Modify additional records? (y/n)")
                         .prompt()
                         .unwrap();
@@ -478,7 +500,7 @@ impl AccountOperations for Wallet {
                 },
                 "Categories" => {
                     loop {
-                        let records = self.db.get_categories(self.uid, self.id).unwrap();
+                        let records = self.db.get_categories(self.uid, self.id)?;
                         let mut choices: Vec<String> = records
                             .iter()
                             .map(|x| x.category.name.clone())
@@ -508,18 +530,15 @@ impl AccountOperations for Wallet {
                                     self.id,
                                     chosen_category,
                                     new_name,
-                                );
+                                )?;
                             }
                             "Remove" => {
                                 // check if category is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_category(
-                                        self.uid,
-                                        self.id,
-                                        chosen_category.clone(),
-                                    )
-                                    .unwrap();
+                                let is_referenced = self.db.check_if_ledger_references_category(
+                                    self.uid,
+                                    self.id,
+                                    chosen_category.clone(),
+                                )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -528,13 +547,11 @@ impl AccountOperations for Wallet {
                                             "{} | {} | {} | {} ",
                                             record.info.date,
                                             chosen_category.clone(),
-                                            self.db
-                                                .get_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.participant
-                                                )
-                                                .unwrap(),
+                                            self.db.get_participant(
+                                                self.uid,
+                                                self.id,
+                                                record.info.participant
+                                            )?,
                                             record.info.amount
                                         );
                                         print!("\t{}", v);
@@ -550,7 +567,7 @@ impl AccountOperations for Wallet {
                                         self.uid,
                                         self.id,
                                         chosen_category.clone(),
-                                    );
+                                    )?;
                                 }
                             }
                             "None" => {
@@ -583,8 +600,7 @@ impl AccountOperations for Wallet {
                                 panic!("Unrecognized input: {}", selected_ptype);
                             }
                         };
-                        let participants =
-                            self.db.get_participants(self.uid, self.id, ptype).unwrap();
+                        let participants = self.db.get_participants(self.uid, self.id, ptype)?;
                         let mut people = participants
                             .iter()
                             .map(|x| x.participant.name.clone())
@@ -614,27 +630,23 @@ impl AccountOperations for Wallet {
                                     .prompt()
                                     .unwrap()
                                     .to_string();
-                                self.db
-                                    .update_participant_name(
-                                        self.uid,
-                                        self.id,
-                                        ptype,
-                                        chosen_person.clone(),
-                                        new_name,
-                                    )
-                                    .unwrap();
+                                self.db.update_participant_name(
+                                    self.uid,
+                                    self.id,
+                                    ptype,
+                                    chosen_person.clone(),
+                                    new_name,
+                                )?;
                             }
                             "Remove" => {
                                 // check if participant is referenced by any current ledger
-                                let is_referenced = self
-                                    .db
-                                    .check_if_ledger_references_participant(
+                                let is_referenced =
+                                    self.db.check_if_ledger_references_participant(
                                         self.uid,
                                         self.id,
                                         ptype,
                                         chosen_person.clone(),
-                                    )
-                                    .unwrap();
+                                    )?;
                                 if is_referenced.is_some() {
                                     let matched_records = is_referenced.unwrap();
                                     println!("The following records were found:");
@@ -642,13 +654,11 @@ impl AccountOperations for Wallet {
                                         let v = format!(
                                             "{} | {} | {} | {} ",
                                             record.info.date,
-                                            self.db
-                                                .get_category_name(
-                                                    self.uid,
-                                                    self.id,
-                                                    record.info.category_id
-                                                )
-                                                .unwrap(),
+                                            self.db.get_category_name(
+                                                self.uid,
+                                                self.id,
+                                                record.info.category_id
+                                            )?,
                                             chosen_person.clone(),
                                             record.info.amount
                                         );
@@ -662,42 +672,34 @@ impl AccountOperations for Wallet {
                                 if delete {
                                     match ptype {
                                         ParticipantType::Payee => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         ParticipantType::Payer => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                         _ => {
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payee,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
-                                            self.db
-                                                .remove_participant(
-                                                    self.uid,
-                                                    self.id,
-                                                    ParticipantType::Payer,
-                                                    chosen_person.clone(),
-                                                )
-                                                .unwrap();
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payee,
+                                                chosen_person.clone(),
+                                            )?;
+                                            self.db.remove_participant(
+                                                self.uid,
+                                                self.id,
+                                                ParticipantType::Payer,
+                                                chosen_person.clone(),
+                                            )?;
                                         }
                                     }
                                 }
@@ -719,7 +721,7 @@ impl AccountOperations for Wallet {
                 }
                 "Budget" => {
                     if let Some(budget) = &self.budget {
-                        budget.modify();
+                        budget.modify()?;
                     } else {
                         let add_budget = Confirm::new("A budget for this account does not exist, would you like to create one (y/n)?")
                             .with_default(false)
@@ -727,13 +729,13 @@ impl AccountOperations for Wallet {
                             .unwrap();
                         if add_budget {
                             let budget = Budget::new(self.uid, self.id, &self.db);
-                            budget.create_budget();
+                            budget.create_budget()?;
                             self.budget = Some(budget);
                         }
                     }
                 }
                 "None" => {
-                    return;
+                    return Ok(());
                 }
                 _ => {
                     panic!("Unrecognized input!")
@@ -747,9 +749,14 @@ impl AccountOperations for Wallet {
                 break;
             }
         }
+        Ok(())
     }
 
-    fn export(&self) {
+    fn export(&self) -> Result<()> {
+        let format = match select_export_format() {
+            Some(format) => format,
+            None => return Ok(()),
+        };
         let g = FilePathHelper {
             completer: FilenameCompleter::new(),
             highlighter: MatchingBracketHighlighter::new(),
@@ -765,33 +772,38 @@ impl AccountOperations for Wallet {
         let mut rl = Editor::with_config(config).unwrap();
         rl.set_helper(Some(g));
 
-        let mut wtr =
-            csv::Writer::from_path(rl.readline("Enter path to CSV file: ").unwrap()).unwrap();
-        let ledger = self.get_ledger();
+        let path = rl
+            .readline(&format!("Enter path to {} file: ", format))
+            .unwrap();
+        let mut entries = Vec::new();
+        let ledger = self.get_ledger()?;
         if !ledger.is_empty() {
             for record in ledger {
                 let csv_ledger_record: shared_lib::LedgerEntry = LedgerEntry {
                     date: record.info.date,
                     amount: record.info.amount,
                     transfer_type: record.info.transfer_type,
-                    participant: self
-                        .db
-                        .get_participant(self.uid, self.id, record.info.participant)
-                        .unwrap(),
-                    category: self
-                        .db
-                        .get_category_name(self.uid, self.id, record.info.category_id)
-                        .unwrap(),
+                    participant: self.db.get_participant(
+                        self.uid,
+                        self.id,
+                        record.info.participant,
+                    )?,
+                    category: self.db.get_category_name(
+                        self.uid,
+                        self.id,
+                        record.info.category_id,
+                    )?,
                     description: record.info.description,
                     stock_info: None,
                 };
-                let flattened = FlatLedgerEntry::from(csv_ledger_record);
-                wtr.serialize(flattened).unwrap();
+                entries.push(csv_ledger_record);
             }
         }
+        write_export_file(format, QifAccountType::Cash, Path::new(&path), entries)?;
+        Ok(())
     }
 
-    fn report(&self) {
+    fn report(&self) -> Result<()> {
         const REPORT_OPTIONS: [&'static str; 3] = ["Total Value", "Simple Growth Rate", "None"];
         let choice: String =
             Select::new("What would you like to report: ", REPORT_OPTIONS.to_vec())
@@ -800,7 +812,7 @@ impl AccountOperations for Wallet {
                 .to_string();
         match choice.as_str() {
             "Total Value" => {
-                let value = self.fixed.get_current_value();
+                let value = self.fixed.get_current_value()?;
                 println!("\tTotal Account Value: {}", value);
             }
             "Simple Growth Rate" => {
@@ -810,15 +822,18 @@ impl AccountOperations for Wallet {
                 println!("\tRate of return: {}%", rate);
             }
             "None" => {
-                return;
+                return Ok(());
             }
             _ => {
                 panic!("Unrecognized input!");
             }
         }
+        Ok(())
     }
 
-    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Option<u32> {
+    fn link(&self, transacting_account: u32, entry: LedgerRecord) -> Result<Option<u32>> {
+        let transaction = self.db.begin_transaction()?;
+
         let from_account;
         let to_account;
 
@@ -834,18 +849,16 @@ impl AccountOperations for Wallet {
                     self.uid,
                     self.id,
                     "Withdrawal".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payee,
                     true,
-                );
+                )?;
                 (
                     TransferType::WithdrawalToExternalAccount,
                     format!(
@@ -862,18 +875,16 @@ impl AccountOperations for Wallet {
                     self.uid,
                     self.id,
                     "Deposit".to_ascii_uppercase(),
-                );
-                transacting_account_name = self
-                    .db
-                    .get_account_name(self.uid, transacting_account)
-                    .unwrap();
+                )?;
+                transacting_account_name =
+                    self.db.get_account_name(self.uid, transacting_account)?;
                 pid = self.db.check_and_add_participant(
                     self.uid,
                     self.id,
                     transacting_account_name.clone(),
                     ParticipantType::Payer,
                     true,
-                );
+                )?;
                 (
                     TransferType::DepositFromExternalAccount,
                     format!(
@@ -883,7 +894,7 @@ impl AccountOperations for Wallet {
                 )
             }
             _ => {
-                return None;
+                return Ok(None);
             }
         };
 
@@ -898,16 +909,12 @@ impl AccountOperations for Wallet {
 
         let (from_ledger_id, to_ledger_id) = match new_ttype {
             TransferType::WithdrawalToExternalAccount => (
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
                 entry.id,
             ),
             TransferType::DepositFromExternalAccount => (
                 entry.id,
-                self.db
-                    .add_ledger_entry(self.uid, self.id, linked_entry)
-                    .unwrap(),
+                self.db.add_ledger_entry(self.uid, self.id, linked_entry)?,
             ),
             _ => {
                 panic!("Unrecognized input!")
@@ -921,11 +928,12 @@ impl AccountOperations for Wallet {
             to_ledger: to_ledger_id,
         };
 
-        return Some(
-            self.db
-                .add_account_transaction(self.uid, transaction_record)
-                .unwrap(),
-        );
+        let id = self
+            .db
+            .add_account_transaction(self.uid, transaction_record)?;
+        transaction.commit()?;
+
+        return Ok(Some(id));
     }
 }
 
@@ -933,27 +941,33 @@ impl AccountData for Wallet {
     fn get_id(&self) -> u32 {
         return self.id;
     }
-    fn get_name(&self) -> String {
-        return self.db.get_account_name(self.uid, self.id).unwrap();
+    fn get_uid(&self) -> u32 {
+        return self.uid;
+    }
+    fn get_name(&self) -> Result<String> {
+        Ok(self.db.get_account_name(self.uid, self.id)?)
     }
-    fn get_ledger(&self) -> Vec<LedgerRecord> {
-        return self.db.get_ledger(self.uid, self.id).unwrap();
+    fn get_ledger(&self) -> Result<Vec<LedgerRecord>> {
+        Ok(self.db.get_ledger(self.uid, self.id)?)
     }
-    fn get_ledger_within_dates(&self, start: NaiveDate, end: NaiveDate) -> Vec<LedgerRecord> {
-        return self
+    fn get_ledger_within_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<LedgerRecord>> {
+        Ok(self
             .db
-            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)
-            .unwrap();
+            .get_ledger_entries_within_timestamps(self.uid, self.id, start, end)?)
     }
-    fn get_displayable_ledger(&self) -> Vec<crate::types::ledger::DisplayableLedgerRecord> {
-        return self.db.get_displayable_ledger(self.uid, self.id).unwrap();
+    fn get_displayable_ledger(&self) -> Result<Vec<crate::types::ledger::DisplayableLedgerRecord>> {
+        Ok(self.db.get_displayable_ledger(self.uid, self.id)?)
     }
 
-    fn get_value(&self) -> f32 {
-        return self.fixed.get_current_value();
+    fn get_value(&self) -> Result<Money> {
+        self.fixed.get_current_value()
     }
-    fn get_value_on_day(&self, day: NaiveDate) -> f32 {
-        return self.fixed.get_value_on_day(day);
+    fn get_value_on_day(&self, day: NaiveDate) -> Result<Money> {
+        self.fixed.get_value_on_day(day)
     }
     fn get_open_date(&self) -> NaiveDate {
         return self.open_date;
@@ -1049,17 +1063,18 @@ impl Wallet {
 
 #[cfg(feature = "ratatui_support")]
 impl AccountUI for Wallet {
-    fn populate_page_cache_f32(&self, app: &mut App) {
+    fn populate_page_cache_f32(&self, app: &mut App) -> Result<()> {
         let mut kv: HashMap<String, DisplayValue> = HashMap::new();
 
         kv.insert(
             KEY_TOTAL_VALUE.into(),
-            DisplayValue::Float(self.get_value()),
+            DisplayValue::Money(self.get_value()?),
         );
         app.page_cache_f32 = Some(kv);
-        app.ledger_entries = Some(self.get_displayable_ledger());
+        app.ledger_entries = Some(self.get_displayable_ledger()?);
         app.linechart_cache = None;
         app.barchart_cache = self.get_barchart_data(app);
+        Ok(())
     }
 
     fn render(&self, frame: &mut Frame, area: Rect, app: &mut App) {
@@ -1104,13 +1119,13 @@ impl Account for Wallet {
 }
 
 impl LiquidAccount for Wallet {
-    fn get_positive_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32 {
-        let ledger = self.get_ledger_within_dates(start, end);
+    fn get_positive_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money> {
+        let ledger = self.get_ledger_within_dates(start, end)?;
         if ledger.is_empty() {
-            return 0.0;
+            return Ok(Money::ZERO);
         }
 
-        let mut amt = 0.0;
+        let mut amt = Money::ZERO;
         for txn in ledger {
             if !txn.info.transfer_type.is_deposit() {
                 continue;
@@ -1120,14 +1135,10 @@ impl LiquidAccount for Wallet {
                 .db
                 .check_and_get_account_transaction_record_matching_to_ledger_id(
                     self.uid, self.id, txn.id,
-                )
-                .unwrap()
+                )?
             {
                 // if linked, checked to see that the account is not another liquid account (if liquid, then skip because cash is still available)
-                let peer_account = self
-                    .db
-                    .get_account(self.uid, link.info.from_account)
-                    .unwrap();
+                let peer_account = self.db.get_account(self.uid, link.info.from_account)?;
                 if !peer_account.is_liquid_account() {
                     continue;
                 }
@@ -1135,16 +1146,16 @@ impl LiquidAccount for Wallet {
             amt = amt + txn.info.amount;
         }
 
-        amt
+        Ok(amt)
     }
 
-    fn get_negative_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32 {
-        let ledger = self.get_ledger_within_dates(start, end);
+    fn get_negative_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money> {
+        let ledger = self.get_ledger_within_dates(start, end)?;
         if ledger.is_empty() {
-            return 0.0;
+            return Ok(Money::ZERO);
         }
 
-        let mut amt = 0.0;
+        let mut amt = Money::ZERO;
         for txn in ledger {
             if !txn.info.transfer_type.is_withdrawal() {
                 continue;
@@ -1154,14 +1165,10 @@ impl LiquidAccount for Wallet {
                 .db
                 .check_and_get_account_transaction_record_matching_to_ledger_id(
                     self.uid, self.id, txn.id,
-                )
-                .unwrap()
+                )?
             {
                 // if linked, checked to see that the account is not another liquid account (if liquid, then skip because cash is still available)
-                let peer_account = self
-                    .db
-                    .get_account(self.uid, link.info.from_account)
-                    .unwrap();
+                let peer_account = self.db.get_account(self.uid, link.info.from_account)?;
                 if !peer_account.is_liquid_account() {
                     continue;
                 }
@@ -1169,10 +1176,10 @@ impl LiquidAccount for Wallet {
             amt = amt + txn.info.amount;
         }
 
-        amt
+        Ok(amt)
     }
 
-    fn get_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> f32 {
-        return self.get_positive_cash_flow(start, end) - self.get_negative_cash_flow(start, end);
+    fn get_cash_flow(&self, start: NaiveDate, end: NaiveDate) -> Result<Money> {
+        Ok(self.get_positive_cash_flow(start, end)? - self.get_negative_cash_flow(start, end)?)
     }
 }
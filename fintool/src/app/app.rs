@@ -18,12 +18,18 @@ use std::collections::HashMap;
 -----------------------------------------------------------------------*/
 use chrono::{Datelike, Local, NaiveDate};
 use ratatui::widgets::{Chart, ScrollbarState, TableState};
+use shared_lib::Money;
 
 use crate::accounts::base::AnalysisPeriod;
 use crate::app::screen::PALETTES;
+use crate::config::{Config, Profile};
 use crate::database::DbConn;
+use crate::error::Result;
 use crate::tui::decode_and_init_account_type;
-use crate::types::accounts::AccountType;
+use crate::tui::tui_encryption::open_database;
+use crate::tui::tui_sharing::account_label;
+use crate::types::account_members::AccountAccess;
+use crate::types::accounts::{AccountRecord, AccountType};
 use crate::types::ledger::{DisplayableLedgerRecord, LedgerRecord};
 use crate::{accounts, is_account_type};
 use crate::{accounts::base::Account, app::screen::TabMenu};
@@ -35,6 +41,7 @@ const ITEM_HEIGHT: usize = 2;
 #[derive(Debug)]
 pub enum DisplayValue {
     Float(f32),
+    Money(Money),
     UInt(u32),
     Text(String),
 }
@@ -46,6 +53,12 @@ impl DisplayValue {
             _ => None,
         }
     }
+    pub fn as_money(&self) -> Option<Money> {
+        match self {
+            DisplayValue::Money(m) => Some(*m),
+            _ => None,
+        }
+    }
     pub fn as_text(&self) -> Option<String> {
         match self {
             DisplayValue::Text(s) => Some(s.clone()),
@@ -62,7 +75,12 @@ impl DisplayValue {
 
 pub struct App {
     pub key_input: String,
+    // set once a user name has been accepted and the password is being typed
+    pub login_name: Option<String>,
     pub invalid_input: bool,
+    pub error_message: Option<String>,
+    // shown like an error, for news such as a schema upgrade
+    pub notice_message: Option<String>,
     pub current_screen: CurrentScreen,
     pub selected_atype_tab: AccountType,
     pub accounts_for_type: Vec<String>,
@@ -71,9 +89,14 @@ pub struct App {
     pub account_index_to_restore: usize,
     pub currently_selected: Option<CurrentlySelecting>,
     pub db: DbConn,
+    pub config: Config,
+    pub profiles: Vec<Profile>,
+    pub selected_profile: usize,
     pub user_id: Option<u32>,
     pub account: Option<Box<dyn Account>>,
     pub accounts: Vec<Box<dyn Account>>,
+    // how the user may use each loaded account, and how much of it they own
+    pub account_records: Vec<AccountRecord>,
     pub ledger_table_state: TableState,
     pub ledger_table_colors: LedgerColors,
     pub ledger_entries: Option<Vec<DisplayableLedgerRecord>>,
@@ -90,10 +113,18 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(db: &DbConn) -> App {
+    pub fn new(
+        db: &DbConn,
+        config: Config,
+        profiles: Vec<Profile>,
+        selected_profile: usize,
+    ) -> App {
         App {
             key_input: String::new(),
+            login_name: None,
             invalid_input: false,
+            error_message: None,
+            notice_message: upgrade_notice(db),
             current_screen: CurrentScreen::Login,
             selected_page_tab: Pages::Main,
             selected_atype_tab: AccountType::Bank,
@@ -102,9 +133,13 @@ impl App {
             account_index_to_restore: 0,
             currently_selected: Some(CurrentlySelecting::MainTabs),
             db: db.clone(),
+            config,
+            profiles,
+            selected_profile,
             user_id: None,
             account: None,
             accounts: Vec::new(),
+            account_records: Vec::new(),
             ledger_table_state: TableState::default().with_selected(0),
             ledger_table_colors: LedgerColors::new(&PALETTES[1]),
             ledger_entries: None,
@@ -121,6 +156,34 @@ impl App {
         }
     }
 
+    // the profile that switch_profile would open, if there is another one
+    pub fn next_profile(&self) -> Option<&Profile> {
+        if self.profiles.len() < 2 {
+            return None;
+        }
+        self.profiles
+            .get((self.selected_profile + 1) % self.profiles.len())
+    }
+
+    // Opens the next database in the profile list. Only offered on the login
+    // screen, so there is no loaded user to tear down. Encrypted databases
+    // prompt for their passphrase on the terminal.
+    pub fn switch_profile(&mut self) -> Result<()> {
+        if self.next_profile().is_none() {
+            return Ok(());
+        }
+        let next = (self.selected_profile + 1) % self.profiles.len();
+        let path = self.profiles[next].path.clone();
+        let db = open_database(&path)?;
+        self.config.install_quote_provider(&db)?;
+        self.notice_message = upgrade_notice(&db);
+        self.db = db;
+        self.selected_profile = next;
+        self.key_input = String::new();
+        self.invalid_input = false;
+        Ok(())
+    }
+
     pub fn advance_currently_selecting(&mut self) {
         if let Some(selecting) = &self.currently_selected {
             self.currently_selected = Some(selecting.next());
@@ -175,12 +238,54 @@ impl App {
         self.selected_account_tab = self.accounts_for_type.clone().len() - 1
     }
 
-    pub fn validate_user(&mut self, username: String) -> Option<u32> {
-        let users: Vec<String> = self.db.get_users().unwrap();
-        if users.contains(&username) {
-            return Some(self.db.get_user_id(username).unwrap());
-        } else {
-            return None;
+    // the user's access to the selected account
+    pub fn selected_account_access(&self) -> Option<AccountAccess> {
+        let account = self.account.as_ref()?;
+        self.account_records
+            .iter()
+            .find(|record| record.owner == account.get_uid() && record.id == account.get_id())
+            .map(|record| record.access)
+    }
+
+    pub fn validate_user(&self, username: String) -> Result<Option<u32>> {
+        let users: Vec<String> = self.db.get_users()?;
+        if !users.contains(&username) {
+            return Ok(None);
+        }
+        Ok(Some(self.db.get_user_id(username)?))
+    }
+
+    // the user, when they exist and have not chosen a password yet
+    pub fn passwordless_user(&self, username: String) -> Result<Option<u32>> {
+        match self.validate_user(username)? {
+            Some(id) if !self.db.has_password(id)? => Ok(Some(id)),
+            _ => Ok(None),
+        }
+    }
+
+    // names of the accounts on the selected account type tab
+    pub fn refresh_accounts_for_type(&mut self) {
+        let names = self
+            .accounts
+            .iter()
+            .filter(|x| is_account_type(x, self.selected_atype_tab))
+            .map(|x| {
+                match self
+                    .account_records
+                    .iter()
+                    .find(|record| record.owner == x.get_uid() && record.id == x.get_id())
+                {
+                    Some(record) => Ok(account_label(&self.db, record)),
+                    None => x.get_name(),
+                }
+            })
+            .collect::<Result<Vec<String>>>();
+        match names {
+            Ok(names) => self.accounts_for_type = names,
+            Err(error) => {
+                self.accounts_for_type = Vec::new();
+                self.error_message = Some(format!("Unable to list accounts: {}", error));
+            }
         }
     }
 
@@ -196,7 +301,9 @@ impl App {
             if let Some(&original_index) = matching_indexes.get(self.selected_account_tab) {
                 let account = self.accounts.remove(original_index);
                 self.account_index_to_restore = original_index;
-                account.populate_page_cache_f32(self);
+                if let Err(error) = account.populate_page_cache_f32(self) {
+                    self.error_message = Some(format!("Unable to load account: {}", error));
+                }
                 Some(account)
             } else {
                 None
@@ -215,7 +322,9 @@ impl App {
 
     pub fn update_account(&mut self) {
         self.account = if let Some(account) = self.account.take() {
-            account.populate_page_cache_f32(self);
+            if let Err(error) = account.populate_page_cache_f32(self) {
+                self.error_message = Some(format!("Unable to load account: {}", error));
+            }
             Some(account)
         } else {
             None
@@ -257,6 +366,11 @@ impl App {
     }
 }
 
+fn upgrade_notice(db: &DbConn) -> Option<String> {
+    let notes = db.upgrade_notes();
+    (!notes.is_empty()).then(|| notes.join("\n"))
+}
+
 #[derive(Debug, Clone)]
 pub struct LineChart {
     pub datasets: Vec<Vec<(f64, f64)>>,
@@ -275,5 +389,5 @@ pub struct BarChartData {
     // hashmap for each datset, each entry is comprised of
     // the category (label), and the value (display value)
     // and numeric value
-    pub groups: HashMap<String, HashMap<String, (f32, u64)>>,
+    pub groups: HashMap<String, HashMap<String, (Money, u64)>>,
 }
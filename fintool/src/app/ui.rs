@@ -31,6 +31,8 @@ use ratatui::{
     },
     Frame,
 };
+use shared_lib::stocks;
+use shared_lib::Money;
 use time::Month;
 
 use super::app::App;
@@ -49,7 +51,8 @@ use crate::{
 use crate::{
     accounts::{self, as_liquid_account, bank_account::BankAccount},
     app::screen::{Pages, UserLoadedState},
-    tui::tui_accounts::{get_total_assets, get_total_liabilities},
+    error::{FintoolError, Result},
+    tui::tui_accounts::{get_total_assets, get_total_liabilities, share_of_value},
     types::accounts::AccountType,
 };
 
@@ -67,7 +70,7 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
     let current_keys_hint = {
         match app.current_screen {
             CurrentScreen::Login => Span::styled (
-                "(Ctrl-c) to quit / (:) Create User / (⏎) Login / (Ctrl-l) Show Conditions / (Ctrl-w) Show Warranty / (⌫) Exit Conditions / Warranty",
+                "(Ctrl-c) to quit / (:) Create User / (⏎) Login / (Esc) Back to User Name / (Ctrl-p) Switch Database / (Ctrl-r) Restore Snapshot / (Ctrl-u) Manage Users / (Ctrl-l) Show Conditions / (Ctrl-w) Show Warranty / (⌫) Exit Conditions / Warranty",
                 Style::default().fg(Color::LightBlue).bg(Color::Black),
             ),
             CurrentScreen::Landing => {
@@ -80,14 +83,14 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
                     }
                     CurrentlySelecting::Account => {
                         Span::styled (
-                        "(q) to quit / (⌫) Deselect / (a) Analyze / (e) Edit Account / (r) Record Entry / (m) Modify Ledger / (i) Import / (j) Advance Row / (k) Retreat Row / (G) Go to Last / (H) Go to First",
+                        "(q) to quit / (⌫) Deselect / (a) Analyze / (e) Edit Account / (s) Share Account / (r) Record Entry / (m) Modify Ledger / (i) Import / (u) Undo / (U) Redo / (y) Entry History / (j) Advance Row / (k) Retreat Row / (G) Go to Last / (H) Go to First",
                         Style::default().fg(Color::LightBlue),
                         )
                     }
                     CurrentlySelecting::MainTabs => {
                         if Pages::Main == app.selected_page_tab {
                             Span::styled (
-                            "(q) to quit /  (◀︎) Move Tab Left / (▶︎) Move Tab Right / (⏎) Select / (⌫) Deselect / (m) Modify Labels",
+                            "(q) to quit /  (◀︎) Move Tab Left / (▶︎) Move Tab Right / (⏎) Select / (⌫) Deselect / (m) Modify Labels / (p) Reset Password / (g) Capital Gains / (x) Export Journal / (z) Export Archive / (Z) Import Archive",
                             Style::default().fg(Color::LightBlue),
                             )
                         } else {
@@ -102,7 +105,23 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         }
     };
 
-    let key_notes_footer = Paragraph::new(Line::from(current_keys_hint)).block(
+    let mut footer_line = Line::from(current_keys_hint);
+    // quotes that were served from the cache because the provider could not
+    // be reached
+    let stale_quotes = stocks::stale_quotes();
+    if !stale_quotes.is_empty() {
+        let tickers = stale_quotes
+            .iter()
+            .map(|(ticker, date)| format!("{} ({})", ticker, date))
+            .collect::<Vec<String>>()
+            .join(", ");
+        footer_line.push_span(Span::styled(
+            format!(" -- Offline, stale quotes: {}", tickers),
+            Style::default().fg(tailwind::AMBER.c400),
+        ));
+    }
+
+    let key_notes_footer = Paragraph::new(footer_line).block(
         Block::default()
             .borders(Borders::ALL)
             .style(Style::new().bg(tailwind::SLATE.c900)),
@@ -149,7 +168,10 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
                     .style(Style::default().bg(tailwind::EMERALD.c950))
             } else {
                 Block::default()
-                    .title(" Login ")
+                    .title(format!(
+                        " Login: {} ",
+                        app.profiles[app.selected_profile].name
+                    ))
                     .borders(Borders::ALL)
                     .style(Style::default().bg(tailwind::EMERALD.c950))
             };
@@ -165,9 +187,15 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
                     Style::default().fg(tailwind::EMERALD.c50),
                 )
             } else {
-                // prompt for user name
-                let mut content = "Username: ".to_string();
-                content.push_str(&app.key_input.as_str());
+                // prompt for user name, then password
+                let content = match &app.login_name {
+                    Some(name) => format!(
+                        "Username: {}\nPassword: {}",
+                        name,
+                        "*".repeat(app.key_input.chars().count())
+                    ),
+                    None => format!("Username: {}", app.key_input),
+                };
                 let username_text =
                     Text::styled(content, Style::default().fg(tailwind::EMERALD.c50));
                 username_text
@@ -191,19 +219,6 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
                 )
                 .wrap(Wrap { trim: false });
             frame.render_widget(license_paragraph, middle_chunks[1]);
-
-            // display error message when user does not exist
-            if app.invalid_input {
-                let error_footer = Paragraph::new(Line::from(
-                    "Unrecognized user id! -- (q) to quit / (:) Create User / (⏎) Login",
-                ))
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .style(tailwind::RED.c500),
-                );
-                frame.render_widget(error_footer, footer_chunks);
-            }
         }
 
         if let UserLoadedState::Loading = app.user_load_state {
@@ -399,6 +414,32 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
             render_cash_flow_chart(app, frame, quadrant_3);
         }
     }
+
+    // failed operations, and notices, are reported on top of whatever screen
+    // is showing until the next key press
+    if let Some(notice) = &app.notice_message {
+        render_message(frame, " Notice ", notice, tailwind::EMERALD.c50);
+    }
+    if let Some(error) = &app.error_message {
+        render_message(frame, " Error ", error, tailwind::RED.c500);
+    }
+}
+
+fn render_message(frame: &mut Frame, title: &str, message: &str, color: Color) {
+    let paragraph = Paragraph::new(Text::styled(
+        message.to_string(),
+        Style::default().fg(color),
+    ))
+    .block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .style(Style::default().bg(tailwind::SLATE.c900)),
+    )
+    .wrap(Wrap { trim: false });
+    let area = centered_rect(60, 25, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
 }
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
@@ -444,6 +485,107 @@ fn render_account_tabs(
     frame.render_widget(atype_tabs, area);
 }
 
+struct NetWorthFigures {
+    assets: Money,
+    liabilities: Money,
+    liquid_assets: Money,
+    ytd_growth: f32,
+    dollar_change_y2y: Money,
+    y2y_growth: f32,
+    fiveyr_growth: f32,
+}
+// the figures shown in the net worth summary, valued as of today
+fn get_net_worth_figures(app: &App) -> Result<NetWorthFigures> {
+    let (assets, liabilities, ytd_growth, dollar_change_y2y, y2y_growth, fiveyr_growth) =
+        if !app.accounts.is_empty() {
+            (
+                get_total_assets(&app.accounts, &app.account_records)?,
+                get_total_liabilities(&app.accounts, &app.account_records)?,
+                {
+                    let (ytd_start_date, ytd_end_date) = get_analysis_period_dates(
+                        NaiveDate::from_num_days_from_ce_opt(0)
+                            .expect("Unable to convert to NaiveDate!"),
+                        &accounts::base::AnalysisPeriod::YTD,
+                    );
+                    get_net_worth_growth(
+                        &app.accounts,
+                        &app.account_records,
+                        ytd_start_date,
+                        ytd_end_date,
+                    )?
+                },
+                {
+                    let (y2y_start_date, y2yd_end_date) = get_analysis_period_dates(
+                        NaiveDate::from_num_days_from_ce_opt(0)
+                            .expect("Unable to convert to NaiveDate!"),
+                        &accounts::base::AnalysisPeriod::OneYear,
+                    );
+                    let dollar_change = get_dollar_change_y2y(
+                        &app.accounts,
+                        &app.account_records,
+                        y2y_start_date,
+                        y2yd_end_date,
+                    )?;
+                    dollar_change
+                },
+                {
+                    let (y2y_start_date, y2yd_end_date) = get_analysis_period_dates(
+                        NaiveDate::from_num_days_from_ce_opt(0)
+                            .expect("Unable to convert to NaiveDate!"),
+                        &accounts::base::AnalysisPeriod::OneYear,
+                    );
+                    let y2y_growth = get_net_worth_growth(
+                        &app.accounts,
+                        &app.account_records,
+                        y2y_start_date,
+                        y2yd_end_date,
+                    )?;
+                    y2y_growth
+                },
+                {
+                    let (fiveyr_start_date, fiveyr_end_date) = get_analysis_period_dates(
+                        NaiveDate::from_num_days_from_ce_opt(0)
+                            .expect("Unable to convert to NaiveDate!"),
+                        &accounts::base::AnalysisPeriod::FiveYears,
+                    );
+                    get_compound_annual_growth_rate(
+                        &app.accounts,
+                        &app.account_records,
+                        fiveyr_start_date,
+                        fiveyr_end_date,
+                    )?
+                },
+            )
+        } else {
+            (Money::ZERO, Money::ZERO, 0., Money::ZERO, 0., 0.)
+        };
+    let mut liquid_accounts = Vec::new();
+    for account in &app.accounts {
+        let acct_record = app.db.get_account(account.get_uid(), account.get_id())?;
+        if !acct_record.is_liquid_account() {
+            // skip any non-liquid accounts
+            continue;
+        }
+        liquid_accounts.push(account);
+    }
+
+    let mut liquid_assets = Money::ZERO;
+    for account in liquid_accounts {
+        liquid_assets =
+            liquid_assets + share_of_value(&app.account_records, account, account.get_value()?);
+    }
+
+    Ok(NetWorthFigures {
+        assets,
+        liabilities,
+        liquid_assets,
+        ytd_growth,
+        dollar_change_y2y,
+        y2y_growth,
+        fiveyr_growth,
+    })
+}
+
 fn render_net_worth(app: &App, frame: &mut Frame, area: Rect) {
     let net_worth_areas = Layout::default()
         .direction(Direction::Vertical)
@@ -491,72 +633,24 @@ fn render_net_worth(app: &App, frame: &mut Frame, area: Rect) {
     let y2y_growth_area = growth_areas_split[1];
     let fiveyr_growth_area = growth_areas_split[2];
 
-    let (assets, liabilities, ytd_growth, dollar_change_y2y, y2y_growth, fiveyr_growth) = if !app
-        .accounts
-        .is_empty()
-    {
-        (
-            get_total_assets(&app.accounts),
-            get_total_liabilities(&app.accounts),
-            {
-                let (ytd_start_date, ytd_end_date) = get_analysis_period_dates(
-                    NaiveDate::from_num_days_from_ce_opt(0)
-                        .expect("Unable to convert to NaiveDate!"),
-                    &accounts::base::AnalysisPeriod::YTD,
-                );
-                get_net_worth_growth(&app.accounts, ytd_start_date, ytd_end_date)
-            },
-            {
-                let (y2y_start_date, y2yd_end_date) = get_analysis_period_dates(
-                    NaiveDate::from_num_days_from_ce_opt(0)
-                        .expect("Unable to convert to NaiveDate!"),
-                    &accounts::base::AnalysisPeriod::OneYear,
-                );
-                let dollar_change =
-                    get_dollar_change_y2y(&app.accounts, y2y_start_date, y2yd_end_date);
-                dollar_change
-            },
-            {
-                let (y2y_start_date, y2yd_end_date) = get_analysis_period_dates(
-                    NaiveDate::from_num_days_from_ce_opt(0)
-                        .expect("Unable to convert to NaiveDate!"),
-                    &accounts::base::AnalysisPeriod::OneYear,
-                );
-                let y2y_growth = get_net_worth_growth(&app.accounts, y2y_start_date, y2yd_end_date);
-                y2y_growth
-            },
-            {
-                let (fiveyr_start_date, fiveyr_end_date) = get_analysis_period_dates(
-                    NaiveDate::from_num_days_from_ce_opt(0)
-                        .expect("Unable to convert to NaiveDate!"),
-                    &accounts::base::AnalysisPeriod::FiveYears,
-                );
-                get_compound_annual_growth_rate(&app.accounts, fiveyr_start_date, fiveyr_end_date)
-            },
-        )
-    } else {
-        (0., 0., 0., 0., 0., 0.)
+    let figures = match get_net_worth_figures(app) {
+        Ok(figures) => figures,
+        Err(error) => {
+            render_error_filler(frame, area, &error);
+            return;
+        }
     };
+    let NetWorthFigures {
+        assets,
+        liabilities,
+        liquid_assets,
+        ytd_growth,
+        dollar_change_y2y,
+        y2y_growth,
+        fiveyr_growth,
+    } = figures;
     let net_worth = assets - liabilities;
 
-    let mut liquid_accounts = Vec::new();
-    for account in &app.accounts {
-        let acct_record = app
-            .db
-            .get_account(app.user_id.unwrap(), account.get_id())
-            .unwrap();
-        if !acct_record.is_liquid_account() {
-            // skip any non-liquid accounts
-            continue;
-        }
-        liquid_accounts.push(account);
-    }
-
-    let mut liquid_assets = 0.0;
-    for account in liquid_accounts {
-        liquid_assets = liquid_assets + account.get_value();
-    }
-
     let net_worth_widget = Paragraph::new(Text::styled(
         format!("$ {:.2}", net_worth),
         Style::default()
@@ -759,6 +853,15 @@ fn render_net_worth(app: &App, frame: &mut Frame, area: Rect) {
     frame.render_widget(fiveyr_growth_widget, fiveyr_growth_area);
 }
 
+fn get_net_worth_on_day(app: &App, day: NaiveDate) -> Result<f64> {
+    let mut aggregate = 0.0;
+    for acct in &app.accounts {
+        aggregate +=
+            share_of_value(&app.account_records, acct, acct.get_value_on_day(day)?).to_f64();
+    }
+    Ok(aggregate)
+}
+
 fn render_net_worth_chart(app: &App, frame: &mut Frame, area: Rect) {
     if app.accounts.is_empty() {
         render_no_data_filler(app, frame, area);
@@ -783,11 +886,13 @@ fn render_net_worth_chart(app: &App, frame: &mut Frame, area: Rect) {
     let mut min_total = f64::MAX;
     let mut max_total = f64::MIN;
     while date < today {
-        let aggregate: f64 = app
-            .accounts
-            .iter()
-            .map(|acct| acct.get_value_on_day(date) as f64)
-            .sum();
+        let aggregate = match get_net_worth_on_day(app, date) {
+            Ok(aggregate) => aggregate,
+            Err(error) => {
+                render_error_filler(frame, area, &error);
+                return;
+            }
+        };
         let timestamp = date
             .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
             .and_utc()
@@ -801,11 +906,13 @@ fn render_net_worth_chart(app: &App, frame: &mut Frame, area: Rect) {
         date = NaiveDate::from_ymd_opt(date.year() + 1, 12, 31).unwrap();
     }
     // get for today
-    let aggregate: f64 = app
-        .accounts
-        .iter()
-        .map(|acct| acct.get_value_on_day(today) as f64)
-        .sum();
+    let aggregate = match get_net_worth_on_day(app, today) {
+        Ok(aggregate) => aggregate,
+        Err(error) => {
+            render_error_filler(frame, area, &error);
+            return;
+        }
+    };
     let timestamp = today
         .and_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
         .and_utc()
@@ -854,64 +961,104 @@ fn render_net_worth_chart(app: &App, frame: &mut Frame, area: Rect) {
     frame.render_widget(net_worth_chart, area);
 }
 
-fn render_asset_investment_ratio_chart(app: &App, frame: &mut Frame, area: Rect) {
-    if app.accounts.is_empty() {
-        render_no_data_filler(app, frame, area);
-        return;
-    }
+struct AssetAllocation {
+    total_assets: Money,
+    cash: Money,
+    liquid_investment: Money,
+    long_term_investments: Money,
+    retirement: Money,
+    health: Money,
+}
 
-    let total_assets = get_total_assets(&app.accounts);
-    let mut cash: f32 = 0.0;
-    let mut liquid_investment: f32 = 0.0;
-    let mut long_term_investments: f32 = 0.0;
-    let mut retirement: f32 = 0.0;
-    let mut health: f32 = 0.0;
+// the user's share of assets by kind of account: total, cash, liquid
+// investments, long term investments, retirement and health savings
+fn get_asset_allocation(app: &App) -> Result<AssetAllocation> {
+    let total_assets = get_total_assets(&app.accounts, &app.account_records)?;
+    let mut cash = Money::ZERO;
+    let mut liquid_investment = Money::ZERO;
+    let mut long_term_investments = Money::ZERO;
+    let mut retirement = Money::ZERO;
+    let mut health = Money::ZERO;
 
     for account in &app.accounts {
         match account.kind() {
             AccountType::Bank | AccountType::Wallet => {
-                cash = cash + account.get_value();
+                cash = cash + share_of_value(&app.account_records, account, account.get_value()?);
             }
             AccountType::CD => {
-                liquid_investment = liquid_investment + account.get_value();
+                liquid_investment = liquid_investment
+                    + share_of_value(&app.account_records, account, account.get_value()?);
             }
             AccountType::Investment => {
-                long_term_investments = long_term_investments + account.get_value();
+                long_term_investments = long_term_investments
+                    + share_of_value(&app.account_records, account, account.get_value()?);
             }
             AccountType::RetirementRothIra | AccountType::Retirement401k => {
-                retirement = retirement + account.get_value();
+                retirement = retirement
+                    + share_of_value(&app.account_records, account, account.get_value()?);
             }
             AccountType::HealthSavingsAccount => {
-                health = health + account.get_value();
+                health =
+                    health + share_of_value(&app.account_records, account, account.get_value()?);
             }
             _ => {}
         }
     }
+    Ok(AssetAllocation {
+        total_assets,
+        cash,
+        liquid_investment,
+        long_term_investments,
+        retirement,
+        health,
+    })
+}
+
+fn render_asset_investment_ratio_chart(app: &App, frame: &mut Frame, area: Rect) {
+    if app.accounts.is_empty() {
+        render_no_data_filler(app, frame, area);
+        return;
+    }
+
+    let AssetAllocation {
+        total_assets,
+        cash,
+        liquid_investment,
+        long_term_investments,
+        retirement,
+        health,
+    } = match get_asset_allocation(app) {
+        Ok(allocation) => allocation,
+        Err(error) => {
+            render_error_filler(frame, area, &error);
+            return;
+        }
+    };
 
     let mut data: Vec<(String, f64, Color)> = Vec::new();
     data.push((
         "Cash".to_string(),
-        (cash / total_assets * 100.).into(),
+        cash.to_f64() / total_assets.to_f64() * 100.,
         tailwind::AMBER.c500,
     ));
     data.push((
         "Liquid Investments".to_string(),
-        (liquid_investment / total_assets * 100.) as f64,
+        liquid_investment.to_f64() / total_assets.to_f64() * 100.,
         tailwind::FUCHSIA.c500,
     ));
     data.push((
         "Long Term Investments".to_string(),
-        (long_term_investments / total_assets * 100.) as f64,
+        long_term_investments.to_f64() / total_assets.to_f64() * 100.,
         tailwind::INDIGO.c500,
     ));
     data.push((
         "Retirement".to_string(),
-        (retirement / total_assets * 100.) as f64,
+        retirement.to_f64() / total_assets.to_f64() * 100.,
         tailwind::LIME.c500,
     ));
     data.push((
         "Health".to_string(),
-        (health / total_assets * 100.) as f64,
+        health.to_f64() / total_assets.to_f64() * 100.,
         tailwind::ORANGE.c500,
     ));
 
@@ -952,7 +1099,7 @@ fn render_cash_flow_chart(app: &App, frame: &mut Frame, area: Rect) {
     for account in &app.accounts {
         let acct_record = app
             .db
-            .get_account(app.user_id.unwrap(), account.get_id())
+            .get_account(account.get_uid(), account.get_id())
             .unwrap();
         if !acct_record.is_liquid_account() {
             // skip any non-liquid accounts
@@ -973,10 +1120,21 @@ fn render_cash_flow_chart(app: &App, frame: &mut Frame, area: Rect) {
             NaiveDate::from_ymd_opt(today.year_ce().1 as i32, today.month(), 1).unwrap();
         let mut last_date = today;
         for j in (0..=11).rev() {
-            monthly_positive_cash_flow[j] = monthly_positive_cash_flow[j]
-                + (account.get_positive_cash_flow(start_of_month, last_date) as f64);
-            monthly_negative_cash_flow[j] = monthly_negative_cash_flow[j]
-                + (account.get_negative_cash_flow(start_of_month, last_date) as f64);
+            let cash_flow = account
+                .get_positive_cash_flow(start_of_month, last_date)
+                .and_then(|positive| {
+                    let negative = account.get_negative_cash_flow(start_of_month, last_date)?;
+                    Ok((positive, negative))
+                });
+            let (positive, negative) = match cash_flow {
+                Ok(cash_flow) => cash_flow,
+                Err(error) => {
+                    render_error_filler(frame, area, &error);
+                    return;
+                }
+            };
+            monthly_positive_cash_flow[j] = monthly_positive_cash_flow[j] + positive.to_f64();
+            monthly_negative_cash_flow[j] = monthly_negative_cash_flow[j] + negative.to_f64();
             last_date = start_of_month.checked_sub_days(Days::new(1)).unwrap();
             start_of_month = start_of_month.checked_sub_months(Months::new(1)).unwrap();
             if i == 0 {
@@ -1022,6 +1180,34 @@ fn render_cash_flow_chart(app: &App, frame: &mut Frame, area: Rect) {
     frame.render_widget(chart, area);
 }
 
+fn render_error_filler(frame: &mut Frame, area: Rect, error: &FintoolError) {
+    let error_widget = Paragraph::new(Text::styled(
+        format!("Unable to display: {}", error),
+        Style::default().fg(tailwind::ROSE.c500),
+    ))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("")
+            .style(Style::default().bg(tailwind::SLATE.c900))
+            .padding(Padding::new(
+                0,
+                0,
+                (if area.height > 4 {
+                    area.height / 2 - 2
+                } else {
+                    0
+                }),
+                0,
+            )),
+    )
+    .wrap(Wrap { trim: true })
+    .centered()
+    .bold();
+
+    frame.render_widget(error_widget, area);
+}
+
 fn render_no_data_filler(app: &App, frame: &mut Frame, area: Rect) {
     let cash_flow_chart = Paragraph::new(Text::styled(
         format!("No data to display!"),
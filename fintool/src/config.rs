@@ -0,0 +1,259 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+#[cfg(not(debug_assertions))]
+use directories::ProjectDirs;
+use rusqlite::OpenFlags;
+use shared_lib::quote_cache::CachedQuoteProvider;
+use shared_lib::quotes::{CsvQuoteProvider, MockQuoteProvider, QuoteProvider, YahooQuoteProvider};
+use shared_lib::stocks;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::accounts::base::import::DEFAULT_DUPLICATE_WINDOW_DAYS;
+use crate::database::DbConn;
+use crate::error::{FintoolError, Result};
+use crate::types::stock_prices::ManualQuoteProvider;
+
+pub const CONFIG_FILE_NAME: &str = "fintool.conf";
+const DATABASE_FILE_NAME: &str = "finances.db";
+const DATABASE_ENV: &str = "FINTOOL_DB";
+const QUOTE_PROVIDER_ENV: &str = "FINTOOL_QUOTE_PROVIDER";
+const PROFILE_KEY_PREFIX: &str = "profile.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuoteSource {
+    // live quotes from Yahoo! Finance, kept in the quote cache
+    Yahoo,
+    // prices recorded by hand in the stock_prices table
+    Manual,
+    // a local price file with ticker, date and close columns
+    Csv(PathBuf),
+    // a fixed price for every ticker, for running without a network
+    Mock(f64),
+}
+
+// a named database that can be opened from the login screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub quote_source: QuoteSource,
+    pub profiles: Vec<Profile>,
+    pub default_profile: Option<String>,
+    pub duplicate_window_days: i64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            quote_source: QuoteSource::Yahoo,
+            profiles: Vec::new(),
+            default_profile: None,
+            duplicate_window_days: DEFAULT_DUPLICATE_WINDOW_DAYS,
+        }
+    }
+}
+
+// Directory holding the configuration file and, unless told otherwise, the
+// database.
+pub fn data_dir() -> PathBuf {
+    #[cfg(debug_assertions)]
+    {
+        // Dev/debug mode → keep database in repo
+        let db_dir = Path::new("db");
+        std::fs::create_dir_all(db_dir).expect("Failed to create dev db directory");
+        db_dir.to_path_buf()
+    }
+
+    #[cfg(not(debug_assertions))]
+    {
+        // Release mode → packaged app database
+        if let Some(proj_dirs) = ProjectDirs::from("com", "aeberhard21", "Fintool") {
+            let data_dir = proj_dirs.data_dir(); // ~/Library/Application Support/com.aeberhard21.Fintool
+            std::fs::create_dir_all(data_dir).expect("Failed to create data directory");
+            data_dir.to_path_buf()
+        } else {
+            panic!("Unable to locate application directory!");
+        }
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    data_dir().join(CONFIG_FILE_NAME)
+}
+
+impl Config {
+    // The configuration file is a list of `key = value` lines; blank lines and
+    // lines starting with '#' are ignored. A missing file leaves every setting
+    // at its default. FINTOOL_QUOTE_PROVIDER overrides `quote_provider`.
+    //
+    // Databases are listed as `profile.<name> = <path>` and the one opened at
+    // startup is chosen with `default_profile = <name>`.
+    //
+    // `duplicate_window_days` sets how many days apart an imported entry and
+    // an existing one may be dated and still be offered as a duplicate.
+    pub fn load(path: &Path) -> Result<Config> {
+        let mut settings: Vec<(String, String)> = Vec::new();
+        if path.exists() {
+            let contents = std::fs::read_to_string(path)?;
+            for (n, line) in contents.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (key, value) = match line.split_once('=') {
+                    Some(setting) => setting,
+                    None => {
+                        return Err(FintoolError::Validation(format!(
+                            "{}:{}: expected `key = value`",
+                            path.display(),
+                            n + 1
+                        )))
+                    }
+                };
+                settings.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        if let Ok(provider) = std::env::var(QUOTE_PROVIDER_ENV) {
+            settings.push(("quote_provider".to_string(), provider));
+        }
+
+        let setting = |key: &str| {
+            settings
+                .iter()
+                .rev()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+        };
+
+        let mut config = Config::default();
+        let config_dir = path.parent().unwrap_or(Path::new(""));
+        for (key, value) in settings.iter() {
+            if let Some(name) = key.strip_prefix(PROFILE_KEY_PREFIX) {
+                // relative paths are resolved against the configuration file
+                let profile = Profile {
+                    name: name.to_string(),
+                    path: config_dir.join(value),
+                };
+                match config.profiles.iter_mut().find(|p| p.name == profile.name) {
+                    Some(existing) => *existing = profile,
+                    None => config.profiles.push(profile),
+                }
+            }
+        }
+        config.default_profile = setting("default_profile");
+
+        if let Some(days) = setting("duplicate_window_days") {
+            config.duplicate_window_days = match days.parse::<i64>() {
+                Ok(days) if days >= 0 => days,
+                _ => {
+                    return Err(FintoolError::Validation(format!(
+                        "invalid duplicate_window_days '{}'",
+                        days
+                    )))
+                }
+            };
+        }
+
+        if let Some(provider) = setting("quote_provider") {
+            config.quote_source = match provider.to_lowercase().as_str() {
+                "yahoo" => QuoteSource::Yahoo,
+                "manual" => QuoteSource::Manual,
+                "csv" => {
+                    let file = setting("quote_file").ok_or(FintoolError::Validation(
+                        "`quote_provider = csv` requires `quote_file`".to_string(),
+                    ))?;
+                    QuoteSource::Csv(config_dir.join(file))
+                }
+                "mock" => {
+                    let price = setting("mock_price").unwrap_or("100.00".to_string());
+                    QuoteSource::Mock(price.parse::<f64>().map_err(|_| {
+                        FintoolError::Validation(format!("invalid mock_price '{}'", price))
+                    })?)
+                }
+                _ => {
+                    return Err(FintoolError::Validation(format!(
+                        "unknown quote_provider '{}'",
+                        provider
+                    )))
+                }
+            };
+        }
+
+        Ok(config)
+    }
+
+    // Returns the databases to offer on the login screen and the index of the
+    // one to open first. A path given with --db, or else in FINTOOL_DB, takes
+    // precedence over the default profile and is added to the list if it is
+    // not already one of the profiles.
+    pub fn database_profiles(&self, db_override: Option<PathBuf>) -> Result<(Vec<Profile>, usize)> {
+        let mut profiles = self.profiles.clone();
+        if profiles.is_empty() {
+            profiles.push(Profile {
+                name: "default".to_string(),
+                path: data_dir().join(DATABASE_FILE_NAME),
+            });
+        }
+
+        let db_override = db_override.or(std::env::var_os(DATABASE_ENV).map(PathBuf::from));
+        if let Some(path) = db_override {
+            if let Some(i) = profiles.iter().position(|p| p.path == path) {
+                return Ok((profiles, i));
+            }
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or(path.display().to_string());
+            profiles.insert(0, Profile { name, path });
+            return Ok((profiles, 0));
+        }
+
+        match &self.default_profile {
+            Some(name) => match profiles.iter().position(|p| &p.name == name) {
+                Some(i) => Ok((profiles, i)),
+                None => Err(FintoolError::Validation(format!(
+                    "default_profile '{}' is not a configured profile",
+                    name
+                ))),
+            },
+            None => Ok((profiles, 0)),
+        }
+    }
+
+    // Installs the configured quote source for every lookup made through
+    // shared_lib::stocks, including the get_stock_value SQL functions.
+    pub fn install_quote_provider(&self, db: &DbConn) -> Result<()> {
+        let provider: Arc<dyn QuoteProvider> = match &self.quote_source {
+            QuoteSource::Yahoo => Arc::new(CachedQuoteProvider::with_connection(
+                db.open_connection(OpenFlags::default())?,
+                Arc::new(YahooQuoteProvider::new()?),
+            )?),
+            QuoteSource::Manual => Arc::new(ManualQuoteProvider::open(db)?),
+            QuoteSource::Csv(file) => Arc::new(CsvQuoteProvider::from_path(file)?),
+            QuoteSource::Mock(price) => {
+                Arc::new(MockQuoteProvider::new().with_default_close(*price))
+            }
+        };
+        stocks::set_quote_provider(provider);
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::Local;
+use rusqlite::Connection;
+use std::io::Write;
+
+use super::audit::{audited_tables, table_columns};
+use super::DbConn;
+use crate::error::{FintoolError, Result};
+
+const ARCHIVE_FORMAT: &str = "fintool-archive";
+// raised whenever the archived tables or columns change, so that an older
+// fintool refuses an archive it could only partly restore
+const ARCHIVE_VERSION: u32 = 4;
+
+// The members of an account are other users of the database, who do not come
+// along with an archive.
+const UNARCHIVED_TABLES: [&str; 1] = ["account_members"];
+
+impl DbConn {
+    // Writes everything the user owns as one JSON document, keyed by table.
+    // The user's id is left out of the rows so that they can be restored under
+    // another, and their password hash is left out so that the file does not
+    // carry their credential. Returns the number of rows written.
+    pub fn write_archive<W: Write>(&self, uid: u32, mut writer: W) -> Result<usize> {
+        let conn_lock = self.conn.lock().unwrap();
+        let header: String = conn_lock.query_row(
+            "SELECT json_object(
+                'format', (?1),
+                'version', (?2),
+                'exported', (?3),
+                'user', json_object('name', name)
+            ) FROM users WHERE id = (?4)",
+            rusqlite::params![
+                ARCHIVE_FORMAT,
+                ARCHIVE_VERSION,
+                Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                uid
+            ],
+            |row| row.get(0),
+        )?;
+        // one row to a line, so that archives can be compared with diff
+        let header = header.strip_suffix('}').unwrap_or(&header);
+        write!(writer, "{},\n\"tables\": {{", header)?;
+
+        let mut count = 0;
+        for (i, table) in archived_tables(&conn_lock)?.iter().enumerate() {
+            let fields = table_columns(&conn_lock, table)?
+                .iter()
+                .filter(|c| *c != "uid")
+                .map(|c| format!("'{c}', \"{c}\""))
+                .collect::<Vec<String>>()
+                .join(", ");
+            let rows = conn_lock
+                .prepare(&format!(
+                    "SELECT json_object({fields}) FROM \"{table}\" WHERE uid = (?1) ORDER BY rowid"
+                ))?
+                .query_map([uid], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let separator = if i == 0 { "" } else { "," };
+            write!(writer, "{}\n\"{}\": [", separator, table)?;
+            for (j, row) in rows.iter().enumerate() {
+                let separator = if j == 0 { "" } else { "," };
+                write!(writer, "{}\n  {}", separator, row)?;
+            }
+            write!(writer, "{}]", if rows.is_empty() { "" } else { "\n" })?;
+            count += rows.len();
+        }
+        writeln!(writer, "\n}}}}")?;
+        writer.flush()?;
+        Ok(count)
+    }
+
+    // the name of the archived user, to suggest for the user it is restored as
+    pub fn get_archive_user_name(&self, archive: &str) -> Result<String> {
+        let conn_lock = self.conn.lock().unwrap();
+        check_archive(&conn_lock, archive)?;
+        let name =
+            conn_lock.query_row("SELECT json_extract(?1, '$.user.name')", [archive], |row| {
+                row.get(0)
+            })?;
+        Ok(name)
+    }
+
+    // Restores an archive as a new user of this database, who logs in with the
+    // given password, and returns their id. Every other id is only unique per
+    // user, so the rows keep theirs and nothing that refers to them needs to be
+    // rewritten. The archive is restored entirely or not at all, and not
+    // recorded as a change that can be undone.
+    pub fn import_archive(&self, archive: &str, name: &str, password: &str) -> Result<u32> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(FintoolError::Validation(
+                "user names may not be empty".to_string(),
+            ));
+        }
+        if self.get_user_id(name.to_string()).is_ok() {
+            return Err(FintoolError::Validation(format!(
+                "a user named {} already exists",
+                name
+            )));
+        }
+        check_archive(&self.conn.lock().unwrap(), archive)?;
+
+        let transaction = self.begin_transaction()?;
+        self.conn.lock().unwrap().execute_batch(
+            "UPDATE temp.audit_context SET recording = 0;
+            PRAGMA defer_foreign_keys = ON;",
+        )?;
+        let result = self.restore_archive(archive, name);
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("UPDATE temp.audit_context SET recording = 1", ())?;
+        let uid = result?;
+        // archives do not carry a credential, and any that older versions
+        // wrote is not trusted
+        self.set_password(uid, password)?;
+        transaction.commit()?;
+        Ok(uid)
+    }
+
+    fn restore_archive(&self, archive: &str, name: &str) -> Result<u32> {
+        let uid = self.add_user(name.to_string(), false)?;
+        let conn_lock = self.conn.lock().unwrap();
+
+        let archived = archived_tables(&conn_lock)?;
+        let tables = conn_lock
+            .prepare("SELECT key FROM json_each(?1, '$.tables')")?
+            .query_map([archive], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for table in tables {
+            if !archived.contains(&table) {
+                return Err(FintoolError::Validation(format!(
+                    "the archive holds an unknown table {}",
+                    table
+                )));
+            }
+            let path = format!("$.tables.\"{}\"", table);
+            let known = table_columns(&conn_lock, &table)?;
+            let columns = conn_lock
+                .prepare(
+                    "SELECT DISTINCT c.key FROM json_each(?1, ?2) AS r, json_each(r.value) AS c",
+                )?
+                .query_map([archive, path.as_str()], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            if let Some(column) = columns.iter().find(|c| *c == "uid" || !known.contains(c)) {
+                return Err(FintoolError::Validation(format!(
+                    "the archive holds an unknown column {} of {}",
+                    column, table
+                )));
+            }
+
+            // the new user starts out with rows of their own, such as their
+            // id counters, which the archived ones replace
+            conn_lock.execute(
+                &format!("DELETE FROM \"{}\" WHERE uid = (?1)", table),
+                [uid],
+            )?;
+            if columns.is_empty() {
+                continue;
+            }
+            let sql = format!(
+                "INSERT INTO \"{}\" (uid, {}) SELECT (?2), {} FROM json_each(?1, ?3) AS r",
+                table,
+                columns
+                    .iter()
+                    .map(|c| format!("\"{}\"", c))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                columns
+                    .iter()
+                    .map(|c| format!("json_extract(r.value, '$.\"{}\"')", c))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+            conn_lock.execute(&sql, rusqlite::params![archive, uid, path])?;
+        }
+        Ok(uid)
+    }
+}
+
+fn archived_tables(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let tables = audited_tables(conn)?
+        .into_iter()
+        .filter(|table| !UNARCHIVED_TABLES.contains(&table.as_str()))
+        .collect();
+    Ok(tables)
+}
+
+fn check_archive(conn: &Connection, archive: &str) -> Result<()> {
+    let (format, version): (Option<String>, Option<i64>) = conn.query_row(
+        "SELECT
+            CASE WHEN json_valid(?1) THEN json_extract(?1, '$.format') END,
+            CASE WHEN json_valid(?1) and json_type(?1, '$.version') = 'integer'
+                THEN json_extract(?1, '$.version') END",
+        [archive],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    if format.as_deref() != Some(ARCHIVE_FORMAT) {
+        return Err(FintoolError::Validation(
+            "the file is not a fintool archive".to_string(),
+        ));
+    }
+    match version {
+        Some(version) if version >= 1 && version <= ARCHIVE_VERSION as i64 => Ok(()),
+        _ => Err(FintoolError::Validation(format!(
+            "archives of version {} cannot be read, only up to version {}",
+            version.map_or("unknown".to_string(), |v| v.to_string()),
+            ARCHIVE_VERSION
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::accounts::{AccountInfo, AccountType};
+    use crate::types::ledger::LedgerInfo;
+    use crate::types::participants::ParticipantType;
+    use shared_lib::{Money, TransferType};
+
+    // rows of the user in each archived table
+    fn row_counts(db: &DbConn, uid: u32) -> Vec<(String, u32)> {
+        let conn_lock = db.conn.lock().unwrap();
+        archived_tables(&conn_lock)
+            .unwrap()
+            .into_iter()
+            .map(|table| {
+                let count = conn_lock
+                    .query_row(
+                        &format!("SELECT COUNT(*) FROM \"{}\" WHERE uid = (?1)", table),
+                        [uid],
+                        |row| row.get(0),
+                    )
+                    .unwrap();
+                (table, count)
+            })
+            .collect()
+    }
+
+    fn archive_of_user() -> (DbConn, u32, String) {
+        let db = DbConn::new(":memory:").unwrap();
+        let uid = db.add_user("alice".to_string(), true).unwrap();
+        db.set_password(uid, "password1").unwrap();
+        let aid = db
+            .add_account(
+                uid,
+                &AccountInfo {
+                    atype: AccountType::Bank,
+                    name: "Checking".to_string(),
+                    has_stocks: false,
+                    has_bank: true,
+                    has_ledger: true,
+                    has_budget: false,
+                },
+            )
+            .unwrap();
+        let pid = db
+            .check_and_add_participant(
+                uid,
+                aid,
+                "Employer".to_string(),
+                ParticipantType::Both,
+                false,
+            )
+            .unwrap();
+        let cid = db.add_category(uid, aid, "PAY".to_string()).unwrap();
+        db.add_ledger_entry(
+            uid,
+            aid,
+            LedgerInfo {
+                date: "2024-01-02".to_string(),
+                amount: Money::from_cents(100_000),
+                transfer_type: TransferType::DepositFromExternalAccount,
+                participant: pid,
+                category_id: cid,
+                description: "salary".to_string(),
+            },
+        )
+        .unwrap();
+
+        let mut archive = Vec::new();
+        db.write_archive(uid, &mut archive).unwrap();
+        (db, uid, String::from_utf8(archive).unwrap())
+    }
+
+    #[test]
+    fn archives_restore_under_a_new_user() {
+        let (source, uid, archive) = archive_of_user();
+        assert!(!archive.contains("password_hash"));
+        assert!(!archive.contains("\"uid\""));
+
+        let db = DbConn::new(":memory:").unwrap();
+        let admin = db.add_user("admin".to_string(), true).unwrap();
+        let admin_rows = row_counts(&db, admin);
+        assert_eq!(db.get_archive_user_name(&archive).unwrap(), "alice");
+
+        // a password is required
+        assert!(db.import_archive(&archive, "restored", "short").is_err());
+        assert!(db.get_user_id("restored".to_string()).is_err());
+
+        let restored = db
+            .import_archive(&archive, "restored", "password2")
+            .unwrap();
+        assert_ne!(restored, admin);
+        assert_eq!(db.get_user_id("restored".to_string()).unwrap(), restored);
+        assert_eq!(row_counts(&db, restored), row_counts(&source, uid));
+        assert!(row_counts(&source, uid).iter().any(|(_, count)| *count > 0));
+        assert_eq!(db.authenticate("restored", "password2").unwrap(), restored);
+        assert!(db.authenticate("restored", "password1").is_err());
+        assert_eq!(
+            db.get_owned_accounts(restored).unwrap()[0].info.name,
+            "Checking"
+        );
+
+        // nothing is written as another user, and the name is now taken
+        assert_eq!(row_counts(&db, admin), admin_rows);
+        assert!(db
+            .import_archive(&archive, "restored", "password2")
+            .is_err());
+    }
+
+    #[test]
+    fn unknown_tables_and_columns_are_refused() {
+        let db = DbConn::new(":memory:").unwrap();
+        let archive = |tables: &str| {
+            format!(
+                "{{\"format\": \"{}\", \"version\": {}, \"user\": {{\"name\": \"alice\"}}, \"tables\": {}}}",
+                ARCHIVE_FORMAT, ARCHIVE_VERSION, tables
+            )
+        };
+        let cases = [
+            (archive("{\"bogus\": []}"), "unknown table bogus"),
+            (
+                archive("{\"accounts\": [{\"bogus\": 1}]}"),
+                "unknown column bogus of accounts",
+            ),
+            (
+                archive("{\"accounts\": [{\"uid\": 1}]}"),
+                "unknown column uid of accounts",
+            ),
+            (
+                archive("{}").replace(ARCHIVE_FORMAT, "other"),
+                "not a fintool archive",
+            ),
+            (
+                archive("{}").replace(
+                    &format!("\"version\": {}", ARCHIVE_VERSION),
+                    &format!("\"version\": {}", ARCHIVE_VERSION + 1),
+                ),
+                "cannot be read",
+            ),
+        ];
+        for (archive, expected) in cases {
+            let error = db
+                .import_archive(&archive, "alice", "password1")
+                .unwrap_err();
+            assert!(
+                matches!(&error, FintoolError::Validation(msg) if msg.contains(expected)),
+                "{}: {}",
+                expected,
+                error
+            );
+            assert!(db.get_users().unwrap().is_empty(), "{}", expected);
+        }
+    }
+}
@@ -0,0 +1,733 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::Local;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, Value, ValueRef};
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+use super::DbConn;
+use crate::error::{FintoolError, Result};
+
+// Tables holding something other than user data are not recorded. Every other
+// table with a `uid` column and a primary key is.
+const UNAUDITED_TABLES: [&str; 4] = ["users", "user_ids", "change_sets", "change_log"];
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+// change sets kept for each user; older ones, and the changes logged before
+// the oldest kept, are pruned as new ones are started
+const CHANGE_SETS_TO_KEEP: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl TryFrom<u32> for ChangeKind {
+    type Error = FromSqlError;
+
+    fn try_from(value: u32) -> std::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ChangeKind::Insert),
+            1 => Ok(ChangeKind::Update),
+            2 => Ok(ChangeKind::Delete),
+            _ => Err(FromSqlError::OutOfRange(value as i64)),
+        }
+    }
+}
+
+impl FromSql for ChangeKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        u32::column_result(value).and_then(ChangeKind::try_from)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeSetState {
+    Applied,
+    Undone,
+    // undone and then replaced by a newer change, so it can no longer be redone
+    Discarded,
+}
+
+impl TryFrom<u32> for ChangeSetState {
+    type Error = FromSqlError;
+
+    fn try_from(value: u32) -> std::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ChangeSetState::Applied),
+            1 => Ok(ChangeSetState::Undone),
+            2 => Ok(ChangeSetState::Discarded),
+            _ => Err(FromSqlError::OutOfRange(value as i64)),
+        }
+    }
+}
+
+impl FromSql for ChangeSetState {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        u32::column_result(value).and_then(ChangeSetState::try_from)
+    }
+}
+
+// the changes made by one user action, undone and redone as a unit
+#[derive(Debug, Clone)]
+pub struct ChangeSet {
+    pub id: u32,
+    pub uid: u32,
+    pub timestamp: String,
+    pub description: String,
+}
+
+// a column of a changed row as (column, before, after)
+pub type FieldChange = (String, Option<String>, Option<String>);
+
+// one recorded change of a row along with the action that made it
+#[derive(Debug, Clone)]
+pub struct ChangeRecord {
+    pub timestamp: String,
+    pub kind: ChangeKind,
+    pub description: Option<String>,
+    pub state: Option<ChangeSetState>,
+    pub fields: Vec<FieldChange>,
+}
+
+// a row of the change log, as needed to apply it again in either direction
+struct LoggedChange {
+    table: String,
+    kind: ChangeKind,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+// Attributes every change made through the connection to a change set until
+// dropped.
+pub struct ChangeSetGuard {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Drop for ChangeSetGuard {
+    fn drop(&mut self) {
+        let conn_lock = match self.conn.lock() {
+            Ok(conn_lock) => conn_lock,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let _ = conn_lock.execute("UPDATE temp.audit_context SET change_set = NULL", ());
+    }
+}
+
+impl DbConn {
+    pub fn create_change_log_tables(&self) -> rusqlite::Result<()> {
+        let sql = "
+            CREATE TABLE IF NOT EXISTS change_sets (
+                id          INTEGER NOT NULL PRIMARY KEY,
+                uid         INTEGER NOT NULL,
+                timestamp   TEXT NOT NULL,
+                description TEXT NOT NULL,
+                state       INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS change_log (
+                id          INTEGER NOT NULL PRIMARY KEY,
+                change_set  INTEGER,
+                uid         INTEGER NOT NULL,
+                timestamp   TEXT NOT NULL,
+                table_name  TEXT NOT NULL,
+                operation   INTEGER NOT NULL,
+                row_key     TEXT NOT NULL,
+                before      TEXT,
+                after       TEXT,
+                FOREIGN KEY (change_set) REFERENCES change_sets(id) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS change_log_by_set ON change_log(change_set);";
+        self.conn.lock().unwrap().execute_batch(sql)
+    }
+
+    // Records inserts, updates and deletes of the audited tables into
+    // change_log, with the row as JSON before and after the change. The
+    // triggers are TEMP, and so exist only on this connection, because they
+    // read the current change set from a TEMP table; other programs editing
+    // the file are not recorded but are not broken by the triggers either.
+    pub fn install_audit_triggers(&self) -> rusqlite::Result<()> {
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute_batch(
+            "CREATE TEMP TABLE IF NOT EXISTS audit_context (
+                change_set  INTEGER,
+                recording   BOOL NOT NULL
+            );
+            INSERT INTO temp.audit_context (change_set, recording)
+                SELECT NULL, 1 WHERE NOT EXISTS (SELECT 1 FROM temp.audit_context);",
+        )?;
+
+        for table in audited_tables(&conn_lock)? {
+            let columns = table_columns(&conn_lock, &table)?;
+            let key = primary_key(&conn_lock, &table)?;
+            let row = |alias: &str, columns: &[String]| {
+                let fields = columns
+                    .iter()
+                    .map(|c| format!("'{c}', {alias}.\"{c}\""))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!("json_object({})", fields)
+            };
+
+            for (kind, event, uid, row_key, before, after, condition) in [
+                (
+                    ChangeKind::Insert,
+                    "INSERT",
+                    "NEW.uid",
+                    row("NEW", &key),
+                    "NULL".to_string(),
+                    row("NEW", &columns),
+                    String::new(),
+                ),
+                (
+                    ChangeKind::Update,
+                    "UPDATE",
+                    "NEW.uid",
+                    row("OLD", &key),
+                    row("OLD", &columns),
+                    row("NEW", &columns),
+                    // statements that rewrite a row with its own values are not changes
+                    format!(
+                        " AND {} IS NOT {}",
+                        row("OLD", &columns),
+                        row("NEW", &columns)
+                    ),
+                ),
+                (
+                    ChangeKind::Delete,
+                    "DELETE",
+                    "OLD.uid",
+                    row("OLD", &key),
+                    row("OLD", &columns),
+                    "NULL".to_string(),
+                    String::new(),
+                ),
+            ] {
+                let name = format!("audit_{}_{}", table, event.to_lowercase());
+                conn_lock.execute_batch(&format!(
+                    "DROP TRIGGER IF EXISTS temp.{name};
+                    CREATE TEMP TRIGGER {name} AFTER {event} ON main.\"{table}\"
+                    WHEN (SELECT recording FROM audit_context){condition}
+                    BEGIN
+                        INSERT INTO change_log (change_set, uid, timestamp, table_name, operation, row_key, before, after)
+                        VALUES (
+                            (SELECT change_set FROM audit_context),
+                            {uid},
+                            strftime('%Y-%m-%d %H:%M:%S', 'now', 'localtime'),
+                            '{table}',
+                            {kind},
+                            {row_key},
+                            {before},
+                            {after}
+                        );
+                    END;",
+                    kind = kind as u32,
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Starts a new change set for the user. Any change sets that were undone
+    // can no longer be redone once something else has changed.
+    pub fn begin_change_set(
+        &self,
+        uid: u32,
+        description: &str,
+    ) -> rusqlite::Result<ChangeSetGuard> {
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(
+            "UPDATE change_sets SET state = (?1) WHERE uid = (?2) and state = (?3)",
+            rusqlite::params![
+                ChangeSetState::Discarded as u32,
+                uid,
+                ChangeSetState::Undone as u32
+            ],
+        )?;
+        conn_lock.execute(
+            "INSERT INTO change_sets (uid, timestamp, description, state) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                uid,
+                Local::now().format(TIMESTAMP_FORMAT).to_string(),
+                description,
+                ChangeSetState::Applied as u32
+            ],
+        )?;
+        let change_set = conn_lock.last_insert_rowid();
+        prune_change_log(&conn_lock, uid, CHANGE_SETS_TO_KEEP)?;
+        conn_lock.execute(
+            "UPDATE temp.audit_context SET change_set = (?1)",
+            [change_set],
+        )?;
+        Ok(ChangeSetGuard {
+            conn: self.conn.clone(),
+        })
+    }
+
+    // Reverts the user's most recent change set that changed anything.
+    // Returns None when there is nothing left to undo.
+    pub fn undo(&self, uid: u32) -> Result<Option<ChangeSet>> {
+        let change_set = self.find_change_set(
+            uid,
+            ChangeSetState::Applied,
+            "EXISTS (SELECT 1 FROM change_log WHERE change_set = s.id) ORDER BY s.id DESC",
+        )?;
+        match change_set {
+            Some(change_set) => {
+                self.replay_change_set(&change_set, true)?;
+                Ok(Some(change_set))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Reapplies the change set undone most recently, provided nothing has
+    // changed since.
+    pub fn redo(&self, uid: u32) -> Result<Option<ChangeSet>> {
+        let change_set =
+            self.find_change_set(uid, ChangeSetState::Undone, "1 ORDER BY s.id ASC")?;
+        match change_set {
+            Some(change_set) => {
+                self.replay_change_set(&change_set, false)?;
+                Ok(Some(change_set))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // every recorded change of a ledger entry, oldest first
+    pub fn get_ledger_history(
+        &self,
+        uid: u32,
+        aid: u32,
+        lid: u32,
+    ) -> rusqlite::Result<Vec<ChangeRecord>> {
+        let sql = "
+            SELECT
+                c.timestamp, c.operation, s.description, s.state, c.before, c.after
+            FROM change_log as c
+            LEFT JOIN change_sets as s ON
+                c.change_set = s.id
+            WHERE
+                c.table_name = 'ledgers' and
+                c.uid = (?1) and
+                json_extract(c.row_key, '$.aid') = (?2) and
+                json_extract(c.row_key, '$.id') = (?3)
+            ORDER BY c.id";
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params![uid, aid, lid], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, ChangeKind>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<ChangeSetState>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut history = Vec::new();
+        for (timestamp, kind, description, state, before, after) in rows {
+            history.push(ChangeRecord {
+                timestamp,
+                kind,
+                description,
+                state,
+                fields: diff_fields(&conn_lock, before.as_deref(), after.as_deref())?,
+            });
+        }
+        Ok(history)
+    }
+
+    fn find_change_set(
+        &self,
+        uid: u32,
+        state: ChangeSetState,
+        condition: &str,
+    ) -> rusqlite::Result<Option<ChangeSet>> {
+        let sql = format!(
+            "SELECT s.id, s.uid, s.timestamp, s.description FROM change_sets as s
+            WHERE s.uid = (?1) and s.state = (?2) and {} LIMIT 1",
+            condition
+        );
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(&sql)?;
+        let mut rows = stmt.query_map(rusqlite::params![uid, state as u32], |row| {
+            Ok(ChangeSet {
+                id: row.get(0)?,
+                uid: row.get(1)?,
+                timestamp: row.get(2)?,
+                description: row.get(3)?,
+            })
+        })?;
+        rows.next().transpose()
+    }
+
+    // Applies the inverse of every change in the set, newest first, when
+    // undoing and the changes themselves, oldest first, when redoing. Nothing
+    // done here is recorded.
+    fn replay_change_set(&self, change_set: &ChangeSet, undo: bool) -> Result<()> {
+        let transaction = self.begin_transaction()?;
+        let result = {
+            let conn_lock = self.conn.lock().unwrap();
+            conn_lock.execute_batch(
+                "UPDATE temp.audit_context SET recording = 0;
+                PRAGMA defer_foreign_keys = ON;",
+            )?;
+            let result = replay_changes(&conn_lock, change_set.id, undo);
+            conn_lock.execute("UPDATE temp.audit_context SET recording = 1", ())?;
+            result
+        };
+        result?;
+
+        let state = if undo {
+            ChangeSetState::Undone
+        } else {
+            ChangeSetState::Applied
+        };
+        self.conn.lock().unwrap().execute(
+            "UPDATE change_sets SET state = (?1) WHERE id = (?2)",
+            rusqlite::params![state as u32, change_set.id],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+// Removes all but the user's newest `keep` change sets from the log, along with
+// the changes made outside of any change set before the oldest one kept.
+fn prune_change_log(conn: &Connection, uid: u32, keep: u32) -> rusqlite::Result<()> {
+    let oldest_kept: Option<(u32, String)> = conn
+        .query_row(
+            "SELECT id, timestamp FROM change_sets WHERE uid = (?1) ORDER BY id DESC LIMIT 1 OFFSET (?2)",
+            rusqlite::params![uid, keep.saturating_sub(1)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (id, timestamp) = match oldest_kept {
+        Some(oldest_kept) => oldest_kept,
+        None => return Ok(()),
+    };
+    conn.execute(
+        "DELETE FROM change_log WHERE uid = (?1) and
+            (change_set < (?2) or (change_set IS NULL and timestamp < (?3)))",
+        rusqlite::params![uid, id, timestamp],
+    )?;
+    conn.execute(
+        "DELETE FROM change_sets WHERE uid = (?1) and id < (?2)",
+        rusqlite::params![uid, id],
+    )?;
+    Ok(())
+}
+
+fn replay_changes(conn: &Connection, change_set: u32, undo: bool) -> Result<()> {
+    let sql = format!(
+        "SELECT table_name, operation, before, after FROM change_log WHERE change_set = (?1) ORDER BY id {}",
+        if undo { "DESC" } else { "ASC" }
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let changes = stmt
+        .query_map([change_set], |row| {
+            Ok(LoggedChange {
+                table: row.get(0)?,
+                kind: row.get(1)?,
+                before: row.get(2)?,
+                after: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for change in changes {
+        let (from, to) = if undo {
+            (change.after, change.before)
+        } else {
+            (change.before, change.after)
+        };
+        match (from, to) {
+            (None, Some(row)) => insert_row(conn, &change.table, &row)?,
+            (Some(row), None) => delete_row(conn, &change.table, &row)?,
+            (Some(from), Some(to)) => update_row(conn, &change.table, &from, &to)?,
+            (None, None) => {
+                return Err(FintoolError::Validation(format!(
+                    "{:?} of {} was recorded without a row",
+                    change.kind, change.table
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn audited_tables(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM main.sqlite_master WHERE type = 'table' and name NOT LIKE 'sqlite_%'",
+    )?;
+    let tables = stmt
+        .query_map((), |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut audited = Vec::new();
+    for table in tables {
+        if UNAUDITED_TABLES.contains(&table.as_str()) {
+            continue;
+        }
+        if table_columns(conn, &table)?.iter().any(|c| c == "uid")
+            && !primary_key(conn, &table)?.is_empty()
+        {
+            audited.push(table);
+        }
+    }
+    Ok(audited)
+}
+
+pub(super) fn table_columns(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1) ORDER BY cid")?;
+    let columns = stmt
+        .query_map([table], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>();
+    columns
+}
+
+fn primary_key(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt =
+        conn.prepare("SELECT name FROM pragma_table_info(?1) WHERE pk > 0 ORDER BY pk")?;
+    let key = stmt
+        .query_map([table], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>();
+    key
+}
+
+fn extract(column: &str, parameter: u32) -> String {
+    format!("json_extract(?{}, '$.\"{}\"')", parameter, column)
+}
+
+fn matches_key(key: &[String], parameter: u32) -> String {
+    key.iter()
+        .map(|c| format!("\"{}\" = {}", c, extract(c, parameter)))
+        .collect::<Vec<String>>()
+        .join(" and ")
+}
+
+// Each replayed change must find the row exactly as it was left, otherwise
+// something that was not recorded has changed it since.
+fn expect_one_row(table: &str, changed: usize) -> Result<()> {
+    if changed != 1 {
+        return Err(FintoolError::Validation(format!(
+            "a row of {} has changed since it was recorded",
+            table
+        )));
+    }
+    Ok(())
+}
+
+fn insert_row(conn: &Connection, table: &str, row: &str) -> Result<()> {
+    let columns = table_columns(conn, table)?;
+    let sql = format!(
+        "INSERT INTO \"{}\" ({}) VALUES ({})",
+        table,
+        columns
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<String>>()
+            .join(", "),
+        columns
+            .iter()
+            .map(|c| extract(c, 1))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    conn.execute(&sql, [row])?;
+    Ok(())
+}
+
+fn delete_row(conn: &Connection, table: &str, row: &str) -> Result<()> {
+    let key = primary_key(conn, table)?;
+    let sql = format!("DELETE FROM \"{}\" WHERE {}", table, matches_key(&key, 1));
+    expect_one_row(table, conn.execute(&sql, [row])?)
+}
+
+fn update_row(conn: &Connection, table: &str, from: &str, to: &str) -> Result<()> {
+    let columns = table_columns(conn, table)?;
+    let key = primary_key(conn, table)?;
+    let sql = format!(
+        "UPDATE \"{}\" SET {} WHERE {}",
+        table,
+        columns
+            .iter()
+            .map(|c| format!("\"{}\" = {}", c, extract(c, 2)))
+            .collect::<Vec<String>>()
+            .join(", "),
+        matches_key(&key, 1)
+    );
+    expect_one_row(table, conn.execute(&sql, [from, to])?)
+}
+
+// lists the value of every column before and after a change
+fn diff_fields(
+    conn: &Connection,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> rusqlite::Result<Vec<FieldChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT key, json_extract(?1, '$.\"' || key || '\"'), json_extract(?2, '$.\"' || key || '\"')
+        FROM json_each(COALESCE(?2, ?1))",
+    )?;
+    let fields = stmt
+        .query_map([before, after], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                display_value(row.get(1)?),
+                display_value(row.get(2)?),
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>();
+    fields
+}
+
+fn display_value(value: Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Integer(i) => Some(i.to_string()),
+        Value::Real(f) => Some(f.to_string()),
+        Value::Text(s) => Some(s),
+        Value::Blob(b) => Some(format!("<{} bytes>", b.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::accounts::{AccountInfo, AccountType};
+
+    fn setup() -> (DbConn, u32, u32) {
+        let db = DbConn::new(":memory:").unwrap();
+        let uid = db.add_user("user".to_string(), true).unwrap();
+        let aid = db
+            .add_account(
+                uid,
+                &AccountInfo {
+                    atype: AccountType::Bank,
+                    name: "Checking".to_string(),
+                    has_stocks: false,
+                    has_bank: true,
+                    has_ledger: true,
+                    has_budget: false,
+                },
+            )
+            .unwrap();
+        (db, uid, aid)
+    }
+
+    fn category_names(db: &DbConn, uid: u32, aid: u32) -> Vec<String> {
+        db.get_categories(uid, aid)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.category.name)
+            .collect()
+    }
+
+    #[test]
+    fn decodes_stored_kinds_and_states() {
+        let kinds = [
+            (0, Some(ChangeKind::Insert)),
+            (1, Some(ChangeKind::Update)),
+            (2, Some(ChangeKind::Delete)),
+            (3, None),
+        ];
+        for (value, expected) in kinds {
+            assert_eq!(ChangeKind::try_from(value).ok(), expected, "{}", value);
+        }
+        let states = [
+            (0, Some(ChangeSetState::Applied)),
+            (1, Some(ChangeSetState::Undone)),
+            (2, Some(ChangeSetState::Discarded)),
+            (7, None),
+        ];
+        for (value, expected) in states {
+            assert_eq!(ChangeSetState::try_from(value).ok(), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn undoes_and_redoes_a_change_set() {
+        let (db, uid, aid) = setup();
+        {
+            let _changes = db.begin_change_set(uid, "Add category").unwrap();
+            db.add_category(uid, aid, "RENT".to_string()).unwrap();
+        }
+        assert_eq!(category_names(&db, uid, aid), vec!["RENT".to_string()]);
+
+        let undone = db.undo(uid).unwrap().expect("nothing was undone");
+        assert_eq!(undone.description, "Add category");
+        assert!(category_names(&db, uid, aid).is_empty());
+
+        db.redo(uid).unwrap().expect("nothing was redone");
+        assert_eq!(category_names(&db, uid, aid), vec!["RENT".to_string()]);
+        assert!(db.redo(uid).unwrap().is_none());
+    }
+
+    #[test]
+    fn unreadable_log_entries_fail_the_undo() {
+        let (db, uid, aid) = setup();
+        {
+            let _changes = db.begin_change_set(uid, "Add category").unwrap();
+            db.add_category(uid, aid, "RENT".to_string()).unwrap();
+        }
+        db.conn
+            .lock()
+            .unwrap()
+            .execute("UPDATE change_log SET before = NULL, after = NULL", ())
+            .unwrap();
+
+        assert!(db.undo(uid).is_err());
+        // the failed undo changed nothing
+        assert_eq!(category_names(&db, uid, aid), vec!["RENT".to_string()]);
+    }
+
+    #[test]
+    fn prunes_all_but_the_newest_change_sets() {
+        let (db, uid, aid) = setup();
+        for i in 0..5 {
+            let _changes = db.begin_change_set(uid, &format!("Add {}", i)).unwrap();
+            db.add_category(uid, aid, format!("CATEGORY {}", i))
+                .unwrap();
+        }
+
+        let conn_lock = db.conn.lock().unwrap();
+        prune_change_log(&conn_lock, uid, 2).unwrap();
+        let descriptions = conn_lock
+            .prepare("SELECT description FROM change_sets ORDER BY id")
+            .unwrap()
+            .query_map((), |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(descriptions, vec!["Add 3".to_string(), "Add 4".to_string()]);
+        let logged: u32 = conn_lock
+            .query_row(
+                "SELECT COUNT(DISTINCT change_set) FROM change_log WHERE change_set IS NOT NULL",
+                (),
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(logged, 2);
+    }
+}
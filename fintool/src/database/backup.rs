@@ -0,0 +1,332 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::{Local, NaiveDateTime};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use shared_lib::money::{Money, Quantity};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::encryption::open_keyed;
+use super::{DbConn, CURRENT_DATABASE_SCHEMA_VERSION};
+use crate::error::{FintoolError, Result};
+
+const SNAPSHOT_DIR: &str = "snapshots";
+const SNAPSHOT_EXTENSION: &str = "db";
+const SNAPSHOT_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+// number of snapshots kept for each database, oldest are removed first
+const SNAPSHOTS_TO_KEEP: usize = 10;
+// reason of the snapshots taken before a schema upgrade, which are kept apart
+// from the rotation since they are the only copies readable by older builds
+pub(super) const SCHEMA_SNAPSHOT_PREFIX: &str = "schema-v";
+// pages copied at a time, other connections may use the database in between
+const BACKUP_PAGES_PER_STEP: std::ffi::c_int = 100;
+
+// A copy of the database written with the backup API. Snapshots live
+// next to the database in `snapshots/` and are named
+// `<database>-<timestamp>-<reason>.db`, so that listing them needs nothing but
+// the directory.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken: NaiveDateTime,
+    pub reason: String,
+}
+
+// net worth of each user at the time a snapshot was taken
+pub struct SnapshotSummary {
+    pub net_worth: Vec<(String, Money)>,
+}
+
+impl DbConn {
+    // Takes a snapshot of the open database and removes the oldest ones beyond
+    // SNAPSHOTS_TO_KEEP, never those taken before a schema upgrade. In-memory
+    // databases have nothing to snapshot.
+    pub fn snapshot(&self, reason: &str) -> Result<Option<Snapshot>> {
+        let conn_lock = self.conn.lock().unwrap();
+        let snapshot = take_snapshot(&conn_lock, self.passphrase.as_deref(), reason)?;
+        rotate_snapshots(&conn_lock)?;
+        Ok(snapshot)
+    }
+
+    // newest first
+    pub fn list_snapshots(&self) -> Result<Vec<Snapshot>> {
+        let conn_lock = self.conn.lock().unwrap();
+        list_snapshots(&conn_lock)
+    }
+
+    // Replaces the contents of the open database with the snapshot. The current
+    // contents are snapshotted first so that a restore can itself be undone.
+    // Snapshots taken before a schema upgrade are migrated once restored.
+    pub fn restore_snapshot(&self, snapshot: &Snapshot) -> Result<()> {
+        let version = {
+            let conn = self.open_sibling(&snapshot.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            Self::get_schema_version(&conn)?
+        };
+        if version > CURRENT_DATABASE_SCHEMA_VERSION {
+            return Err(FintoolError::Validation(format!(
+                "snapshot schema version {} is newer than the version supported by this build ({})",
+                version, CURRENT_DATABASE_SCHEMA_VERSION
+            )));
+        }
+
+        {
+            let mut conn_lock = self.conn.lock().unwrap();
+            // rotation waits until after the restore so that it cannot remove
+            // the snapshot being restored
+            take_snapshot(&conn_lock, self.passphrase.as_deref(), "pre-restore")?;
+            // replacing the database is not itself a change to be recorded
+            conn_lock.execute("UPDATE temp.audit_context SET recording = 0", ())?;
+            let source = self.open_sibling(&snapshot.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            Backup::new(&source, &mut conn_lock)?.run_to_completion(
+                BACKUP_PAGES_PER_STEP,
+                Duration::ZERO,
+                None,
+            )?;
+            conn_lock.pragma_update(None, "foreign_keys", "off")?;
+            Self::migrate(&conn_lock, self.passphrase.as_deref())?;
+            Self::allow_foreign_keys(&conn_lock)?;
+            conn_lock.execute("UPDATE temp.audit_context SET recording = 1", ())?;
+            rotate_snapshots(&conn_lock)?;
+        }
+        self.create_tables()?;
+        self.install_audit_triggers()?;
+        self.install_access_triggers()?;
+        Ok(())
+    }
+
+    // Reads the snapshot without opening it as the live database. Holdings are
+    // valued at the latest close, as on the landing page.
+    pub fn summarize_snapshot(&self, snapshot: &Snapshot) -> Result<SnapshotSummary> {
+        let conn = self.open_sibling(&snapshot.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+        let mut stmt = conn.prepare("SELECT id, name FROM users ORDER BY id")?;
+        let users = stmt
+            .query_map((), |row| {
+                Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut net_worth = Vec::new();
+        for (uid, name) in users {
+            // credit card balances are negative in the ledger and so are
+            // already counted as liabilities
+            let cash: Money = conn.query_row(
+                "SELECT COALESCE(SUM(CASE
+                    WHEN transfer_type == 0 or transfer_type = 2 THEN -amount    -- withdrawal
+                    WHEN transfer_type == 1 or transfer_type = 3 THEN amount     -- deposit
+                    ELSE 0
+                END), 0) FROM ledgers WHERE uid = (?1)",
+                [uid],
+                |row| row.get(0),
+            )?;
+
+            let mut stmt = conn.prepare(
+                "SELECT p.name, SUM(sp.remaining)
+                FROM stock_purchases as sp
+                INNER JOIN ledgers as l ON
+                    sp.lid = l.id and
+                    sp.aid = l.aid and
+                    sp.uid = l.uid
+                INNER JOIN people as p ON
+                    l.pid = p.id and
+                    l.aid = p.aid and
+                    l.uid = p.uid
+                WHERE sp.uid = (?1)
+                GROUP BY p.name",
+            )?;
+            let positions = stmt
+                .query_map([uid], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Quantity>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let mut holdings = Money::ZERO;
+            for (ticker, shares) in positions {
+                if shares.is_zero() {
+                    continue;
+                }
+                // holdings without a quote, such as delisted tickers or any
+                // while offline, are left out rather than losing the summary
+                if let Ok(close) = shared_lib::stocks::get_stock_at_close(ticker) {
+                    holdings += Money::from_f64(close) * shares;
+                }
+            }
+            net_worth.push((name, cash + holdings));
+        }
+
+        Ok(SnapshotSummary { net_worth })
+    }
+}
+
+fn snapshot_dir(db_path: &Path) -> PathBuf {
+    db_path.parent().unwrap_or(Path::new("")).join(SNAPSHOT_DIR)
+}
+
+fn db_stem(db_path: &Path) -> String {
+    db_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn db_path(conn: &Connection) -> Option<PathBuf> {
+    match conn.path() {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => None,
+    }
+}
+
+// keep reasons usable as part of a file name
+fn sanitize_reason(reason: &str) -> String {
+    reason
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+// Copies the database behind the connection into the snapshot directory. The
+// backup API copies a consistent image a few pages at a time, so the database
+// can stay open, and in use by other connections, while it runs. SQLCipher
+// only copies between databases keyed alike, so the snapshot of an encrypted
+// database is keyed with its passphrase before the copy.
+pub(super) fn take_snapshot(
+    conn: &Connection,
+    passphrase: Option<&str>,
+    reason: &str,
+) -> Result<Option<Snapshot>> {
+    let db_path = match db_path(conn) {
+        Some(db_path) => db_path,
+        None => return Ok(None),
+    };
+    let dir = snapshot_dir(&db_path);
+    fs::create_dir_all(&dir)?;
+
+    let taken = Local::now().naive_local();
+    let reason = sanitize_reason(reason);
+    let path = dir.join(format!(
+        "{}-{}-{}.{}",
+        db_stem(&db_path),
+        taken.format(SNAPSHOT_TIMESTAMP_FORMAT),
+        reason,
+        SNAPSHOT_EXTENSION
+    ));
+    let mut snapshot = open_keyed(&path, OpenFlags::default(), passphrase)?;
+    Backup::new(conn, &mut snapshot)?.run_to_completion(
+        BACKUP_PAGES_PER_STEP,
+        Duration::ZERO,
+        None,
+    )?;
+
+    Ok(Some(Snapshot {
+        path,
+        taken,
+        reason,
+    }))
+}
+
+pub(super) fn list_snapshots(conn: &Connection) -> Result<Vec<Snapshot>> {
+    let db_path = match db_path(conn) {
+        Some(db_path) => db_path,
+        None => return Ok(Vec::new()),
+    };
+    let dir = snapshot_dir(&db_path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = format!("{}-", db_stem(&db_path));
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SNAPSHOT_EXTENSION) {
+            continue;
+        }
+        let stem = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) => stem,
+            None => continue,
+        };
+        // snapshots of other databases kept in the same directory do not parse
+        let (timestamp, reason) = match stem
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split_once('-'))
+        {
+            Some(parts) => parts,
+            None => continue,
+        };
+        if let Ok(taken) = NaiveDateTime::parse_from_str(timestamp, SNAPSHOT_TIMESTAMP_FORMAT) {
+            snapshots.push(Snapshot {
+                path: path.clone(),
+                taken,
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    snapshots.sort_by_key(|snapshot| Reverse(snapshot.taken));
+    Ok(snapshots)
+}
+
+pub(super) fn rotate_snapshots(conn: &Connection) -> Result<()> {
+    let rotated = list_snapshots(conn)?
+        .into_iter()
+        .filter(|snapshot| !snapshot.reason.starts_with(SCHEMA_SNAPSHOT_PREFIX));
+    for snapshot in rotated.skip(SNAPSHOTS_TO_KEEP) {
+        fs::remove_file(&snapshot.path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_keeps_schema_snapshots() {
+        let dir = std::env::temp_dir().join(format!("fintool-backup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let db = DbConn::new(dir.join("rotation.db")).unwrap();
+
+        {
+            let conn_lock = db.conn.lock().unwrap();
+            take_snapshot(&conn_lock, None, &format!("{}1", SCHEMA_SNAPSHOT_PREFIX)).unwrap();
+        }
+        for i in 0..SNAPSHOTS_TO_KEEP + 2 {
+            db.snapshot(&format!("change-{}", i)).unwrap();
+        }
+
+        let snapshots = db.list_snapshots().unwrap();
+        assert_eq!(snapshots.len(), SNAPSHOTS_TO_KEEP + 1);
+        assert!(snapshots
+            .iter()
+            .any(|snapshot| snapshot.reason == format!("{}1", SCHEMA_SNAPSHOT_PREFIX)));
+        assert!(!snapshots
+            .iter()
+            .any(|snapshot| snapshot.reason == "change-0" || snapshot.reason == "change-1"));
+
+        drop(db);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -16,11 +16,12 @@
 -----------------------------------------------------------------------*/
 use super::DbConn;
 use rusqlite::{Error, Result};
+use shared_lib::Money;
 
 #[derive(Debug, Clone)]
 pub struct BudgetItem {
     pub category_id: u32,
-    pub value: f32,
+    pub value: Money,
 }
 
 #[derive(Debug, Clone)]
@@ -41,26 +42,19 @@ impl DbConn {
             FOREIGN KEY(aid,uid) references accounts(id,uid) ON DELETE CASCADE ON UPDATE CASCADE
             FOREIGN KEY(cid,uid,aid) references categories(id, uid, aid) ON DELETE CASCADE ON UPDATE CASCADE
         )";
-        self.conn
-            .lock()
-            .unwrap()
-            .execute(sql, ())
-            .expect("Unable to initialize budgets table!");
+        self.conn.lock().unwrap().execute(sql, ())?;
         Ok(())
     }
 
     pub fn add_budget_item(&self, uid: u32, aid: u32, item: BudgetItem) -> Result<u32> {
-        let id = self.get_next_budget_item_id(uid, aid).unwrap();
+        let id = self.get_next_budget_item_id(uid, aid)?;
         let p = rusqlite::params!(id, item.category_id, item.value, aid, uid);
         let sql = "INSERT INTO budgets (id, cid, value, aid, uid ) VALUES (?1, ?2, ?3, ?4, ?5)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!(
-                    "Unable to add budget item {} for account {}: {}",
-                    item.category_id, aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -88,11 +82,10 @@ impl DbConn {
                                 value: row.get(1)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for item in items {
-                    budget_items.push(item.unwrap());
+                    budget_items.push(item?);
                 }
                 Ok(Some(budget_items))
             }
@@ -117,8 +110,7 @@ impl DbConn {
                 true => {
                     stmt = conn_lock.prepare(sql)?;
                     categories_wrapped = stmt
-                        .query_map(p, |row| Ok(row.get(0)?))
-                        .unwrap()
+                        .query_map(p, |row| Ok(row.get(0)?))?
                         .collect::<Vec<_>>();
                 }
                 false => return Ok(None),
@@ -126,7 +118,7 @@ impl DbConn {
         }
 
         for category_wrapped in categories_wrapped {
-            categories.push(category_wrapped.unwrap())
+            categories.push(category_wrapped?)
         }
 
         Ok(Some(categories))
@@ -151,10 +143,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(()),
             Err(error) => {
-                panic!(
-                    "Unable to update budget item {} for account {}: {}",
-                    updated_item.id, aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -166,10 +155,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => {}
             Err(error) => {
-                panic!(
-                    "Unable to update budget item {} for account {}: {}",
-                    cid, aid, error
-                );
+                return Err(error);
             }
         }
         let sql = "UPDATE budgets SET id = id-1 WHERE id > ?3 and uid = ?1 and aid = ?2";
@@ -16,9 +16,10 @@
 -----------------------------------------------------------------------*/
 use super::DbConn;
 use rusqlite;
+use shared_lib::Money;
 
 pub struct CdRecord {
-    pub principal: f32,
+    pub principal: Money,
     pub apy: f32,
     pub open_date: String,
     pub length: u32,
@@ -27,7 +28,7 @@ pub struct CdRecord {
 impl DbConn {
     pub fn create_cd_table(&self) -> rusqlite::Result<()> {
         let sql: &str = "CREATE TABLE IF NOT EXISTS cds (
-            principal   REAL NOT NULL, 
+            principal   INTEGER NOT NULL, 
             apy         REAL NOT NULL, 
             open_date   STRINT NOT NULL, 
             length      INTEGER NOT NULL,
@@ -40,7 +41,7 @@ impl DbConn {
         match conn_lock.execute(sql, ()) {
             Ok(_) => {}
             Err(error) => {
-                panic!("Unable to create table 'CDs' because {}", error);
+                return Err(error);
             }
         }
         Ok(())
@@ -80,12 +81,7 @@ impl DbConn {
                 });
                 return account;
             }
-            false => {
-                panic!(
-                    "Unable to retrieve certificate of deposit accounts for {}!",
-                    aid
-                );
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 }
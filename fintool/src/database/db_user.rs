@@ -14,8 +14,23 @@
   You should have received a copy of the GNU General Public License
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use rusqlite::{params, Error, OptionalExtension};
+
 use super::DbConn;
-use rusqlite::{params, Error};
+use crate::error::FintoolError;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+// consecutive failed logins after which the user is locked out
+const MAX_FAILED_LOGINS: u32 = 5;
+const LOCKOUT_MINUTES: i64 = 15;
+const LOCKOUT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// an Argon2 hash, with the default parameters, of a password nobody uses
+const DUMMY_PASSWORD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$WN5V4DC34aGH+5M6nDtnVw$RhQ5V00S04YCSPfILSzCf9U8fZpnFyRVblTVS60XVqk";
 
 impl DbConn {
     pub fn create_users_id_table(&self) -> rusqlite::Result<()> {
@@ -25,12 +40,7 @@ impl DbConn {
         )";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("{}", error);
-            }
-        }
+        rs?;
         let sql: &str = "SELECT * FROM user_ids";
         let mut stmt = conn_lock.prepare(sql)?;
         let exists = stmt.exists(())?;
@@ -39,7 +49,7 @@ impl DbConn {
             match conn_lock.execute(sql, [0]) {
                 Ok(_rows_inserted) => {}
                 Err(error) => {
-                    panic!("Unable to initialize user_ids table: {}", error);
+                    return Err(error);
                 }
             }
         }
@@ -58,45 +68,36 @@ impl DbConn {
                 conn_lock.execute(sql, ())?;
                 Ok(id)
             }
-            false => {
-                panic!("The next user ID within table 'info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
     pub fn create_user_table(&self) -> rusqlite::Result<()> {
         let sql: &str;
         sql = "CREATE TABLE IF NOT EXISTS users (
-                id          INTEGER NOT NULL PRIMARY KEY, 
-                name        TEXT    NOT NULL,
-                admin       BOOL    NOT NULL
+                id              INTEGER NOT NULL PRIMARY KEY, 
+                name            TEXT    NOT NULL,
+                admin           BOOL    NOT NULL,
+                password_hash   TEXT,
+                failed_logins   INTEGER NOT NULL DEFAULT 0,
+                locked_until    TEXT
             )";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create users table: {}!", error);
-            }
-        }
+        rs?;
         Ok(())
     }
 
     pub fn add_user(&self, name: String, admin: bool) -> rusqlite::Result<u32, Error> {
         let sql: &str = "INSERT INTO users (id, name, admin) VALUES ( ?1, ?2, ?3)";
-        let id = self.get_next_user_id().unwrap();
+        let id = self.get_next_user_id()?;
         let p = rusqlite::params![id, name, admin];
         {
             let conn_lock = self.conn.lock().unwrap();
             let rs = conn_lock.execute(sql, p);
-            match rs {
-                Ok(_rows_inserted) => {}
-                Err(error) => {
-                    panic!("Unable to allocate user: '{}'!", error);
-                }
-            }
+            rs?;
         }
-        self.initialize_user_account_table(id).unwrap();
+        self.initialize_user_account_table(id)?;
         Ok(id)
     }
 
@@ -109,14 +110,12 @@ impl DbConn {
         match exists {
             true => {
                 let sql: &str = "SELECT name from users";
-                let mut rs: rusqlite::Statement<'_> = conn_lock.prepare(sql).unwrap();
-                let names: Vec<Result<String, Error>> = rs
-                    .query_map([], |row| Ok(row.get(0)?))
-                    .unwrap()
-                    .collect::<Vec<_>>();
+                let mut rs: rusqlite::Statement<'_> = conn_lock.prepare(sql)?;
+                let names: Vec<Result<String, Error>> =
+                    rs.query_map([], |row| Ok(row.get(0)?))?.collect::<Vec<_>>();
 
                 for name in names {
-                    users.push(name.unwrap());
+                    users.push(name?);
                 }
                 return Ok(users);
             }
@@ -141,16 +140,20 @@ impl DbConn {
                         return Ok(id);
                     }
                     Err(err) => {
-                        panic!("Unable to retrieve id for user {}: {}", &name, err);
+                        return Err(err);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find user {}!", name);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
+    pub fn get_user_name(&self, uid: u32) -> rusqlite::Result<String> {
+        let sql: &str = "SELECT name FROM users WHERE id = (?1)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.query_row(sql, (&uid,), |row| row.get::<_, String>(0))
+    }
+
     pub fn is_admin(&self, uid: u32) -> rusqlite::Result<bool, Error> {
         let sql: &str = "SELECT admin FROM users WHERE id = (?1)";
         let conn_lock = self.conn.lock().unwrap();
@@ -161,9 +164,282 @@ impl DbConn {
                 let admin = stmt.query_row((&uid,), |row| row.get::<_, bool>(0))?;
                 Ok(admin)
             }
-            false => {
-                panic!("Unable to find user {}!", uid);
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
+        }
+    }
+
+    // Stores an Argon2 hash of the password, with a fresh salt, and clears any
+    // lockout.
+    pub fn set_password(&self, uid: u32, password: &str) -> crate::error::Result<()> {
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(FintoolError::Validation(format!(
+                "passwords must be at least {} characters long",
+                MIN_PASSWORD_LENGTH
+            )));
+        }
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| FintoolError::Validation(format!("unable to hash password: {}", e)))?
+            .to_string();
+
+        let sql = "UPDATE users SET password_hash = (?1), failed_logins = 0, locked_until = NULL WHERE id = (?2)";
+        let conn_lock = self.conn.lock().unwrap();
+        if conn_lock.execute(sql, params![hash, uid])? == 0 {
+            return Err(FintoolError::Database(Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+
+    // users created before passwords were introduced have none until they
+    // choose one
+    pub fn has_password(&self, uid: u32) -> rusqlite::Result<bool> {
+        let sql = "SELECT password_hash IS NOT NULL FROM users WHERE id = (?1)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.query_row(sql, [uid], |row| row.get::<_, bool>(0))
+    }
+
+    pub fn password_admin_exists(&self) -> rusqlite::Result<bool> {
+        let sql = "SELECT EXISTS (SELECT 1 FROM users WHERE admin AND password_hash IS NOT NULL)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.query_row(sql, [], |row| row.get::<_, bool>(0))
+    }
+
+    // Only while no administrator has a password, as right after upgrading,
+    // may a passwordless administrator choose one without authenticating.
+    // Everyone else has theirs set by an administrator.
+    pub fn can_claim_password(&self, uid: u32) -> rusqlite::Result<bool> {
+        Ok(!self.has_password(uid)? && self.is_admin(uid)? && !self.password_admin_exists()?)
+    }
+
+    pub fn claim_password(&self, uid: u32, password: &str) -> crate::error::Result<()> {
+        if !self.can_claim_password(uid)? {
+            return Err(FintoolError::Authentication(
+                "an administrator must set this password".to_string(),
+            ));
+        }
+        self.set_password(uid, password)
+    }
+
+    // Returns the id of the user when the password matches. After
+    // MAX_FAILED_LOGINS consecutive failures the user is locked out for
+    // LOCKOUT_MINUTES, during which even the right password is refused.
+    // Every failure reads the same so that it does not reveal which names
+    // exist.
+    pub fn authenticate(&self, name: &str, password: &str) -> crate::error::Result<u32> {
+        let invalid = || {
+            FintoolError::Authentication(
+                "invalid user name or password, or too many failed attempts".to_string(),
+            )
+        };
+
+        let sql =
+            "SELECT id, password_hash, failed_logins, locked_until FROM users WHERE name = (?1)";
+        let conn_lock = self.conn.lock().unwrap();
+        let user = conn_lock
+            .query_row(sql, [name], |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })
+            .optional()?;
+
+        // the password is checked even when there is no hash to check it
+        // against, so that the time taken does not reveal which names exist
+        let hash = user.as_ref().and_then(|(_, hash, _, _)| hash.as_deref());
+        let verified = match PasswordHash::new(hash.unwrap_or(DUMMY_PASSWORD_HASH)) {
+            Ok(parsed) => Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok(),
+            Err(_) => false,
+        } && hash.is_some();
+
+        let (uid, hash, failed_logins, locked_until) = match user {
+            Some(user) => user,
+            None => return Err(invalid()),
+        };
+
+        let now = Local::now().naive_local();
+        if let Some(locked_until) = locked_until {
+            let locked_until =
+                NaiveDateTime::parse_from_str(&locked_until, LOCKOUT_TIMESTAMP_FORMAT)?;
+            if now < locked_until {
+                return Err(invalid());
+            }
+        }
+
+        if hash.is_none() {
+            return Err(invalid());
+        }
+
+        if verified {
+            conn_lock.execute(
+                "UPDATE users SET failed_logins = 0, locked_until = NULL WHERE id = (?1)",
+                [uid],
+            )?;
+            return Ok(uid);
+        }
+
+        if failed_logins + 1 >= MAX_FAILED_LOGINS {
+            let locked_until = now + TimeDelta::minutes(LOCKOUT_MINUTES);
+            conn_lock.execute(
+                "UPDATE users SET failed_logins = 0, locked_until = (?1) WHERE id = (?2)",
+                params![
+                    locked_until.format(LOCKOUT_TIMESTAMP_FORMAT).to_string(),
+                    uid
+                ],
+            )?;
+        } else {
+            conn_lock.execute(
+                "UPDATE users SET failed_logins = failed_logins + 1 WHERE id = (?1)",
+                [uid],
+            )?;
+        }
+        Err(invalid())
+    }
+
+    pub fn admin_exists(&self) -> rusqlite::Result<bool> {
+        let sql = "SELECT * FROM users WHERE admin";
+        let conn_lock = self.conn.lock().unwrap();
+        let exists = conn_lock.prepare(sql)?.exists(());
+        exists
+    }
+
+    // users log in by name, so names must stay unique
+    pub fn rename_user(&self, uid: u32, name: &str) -> crate::error::Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(FintoolError::Validation(
+                "user names may not be empty".to_string(),
+            ));
+        }
+        if let Ok(existing) = self.get_user_id(name.to_string()) {
+            if existing != uid {
+                return Err(FintoolError::Validation(format!(
+                    "a user named {} already exists",
+                    name
+                )));
             }
         }
+        let sql = "UPDATE users SET name = (?1) WHERE id = (?2)";
+        let conn_lock = self.conn.lock().unwrap();
+        if conn_lock.execute(sql, params![name, uid])? == 0 {
+            return Err(FintoolError::Database(Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+
+    // Grants or revokes administrator rights. There must always be an
+    // administrator left to manage the users.
+    pub fn set_admin(&self, uid: u32, admin: bool) -> crate::error::Result<()> {
+        if !admin && self.is_last_admin(uid)? {
+            return Err(FintoolError::Validation(
+                "the last administrator cannot be demoted".to_string(),
+            ));
+        }
+        let sql = "UPDATE users SET admin = (?1) WHERE id = (?2)";
+        let conn_lock = self.conn.lock().unwrap();
+        if conn_lock.execute(sql, params![admin, uid])? == 0 {
+            return Err(FintoolError::Database(Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+
+    fn is_last_admin(&self, uid: u32) -> rusqlite::Result<bool> {
+        let sql = "SELECT COUNT(*) FROM users WHERE admin and id != (?1)";
+        let others: u32 = {
+            let conn_lock = self.conn.lock().unwrap();
+            conn_lock.query_row(sql, [uid], |row| row.get(0))?
+        };
+        Ok(others == 0 && self.is_admin(uid)?)
+    }
+
+    // Removes the user along with everything they own. Their accounts are
+    // either deleted too or, when an heir is given, transferred to the heir
+    // first. Accounts others shared with the user are left to their owners.
+    pub fn delete_user(&self, uid: u32, heir: Option<u32>) -> crate::error::Result<()> {
+        if self.is_last_admin(uid)? {
+            return Err(FintoolError::Validation(
+                "the last administrator cannot be deleted".to_string(),
+            ));
+        }
+        if heir == Some(uid) {
+            return Err(FintoolError::Validation(
+                "accounts cannot be transferred to the user being deleted".to_string(),
+            ));
+        }
+
+        let tx = self.begin_transaction()?;
+        if let Some(heir) = heir {
+            let aids: Vec<u32> = self
+                .get_owned_accounts(uid)?
+                .iter()
+                .map(|account| account.id)
+                .collect();
+            // every account moves, so no transfer is left behind
+            self.transfer_accounts(uid, &aids, heir, false)?;
+        }
+        {
+            let conn_lock = self.conn.lock().unwrap();
+            // transfers do not cascade with their accounts, and the rest of the
+            // account data does
+            conn_lock.execute("DELETE FROM account_transactions WHERE uid = (?1)", [uid])?;
+            conn_lock.execute("DELETE FROM accounts WHERE uid = (?1)", [uid])?;
+            conn_lock.execute("DELETE FROM labels WHERE uid = (?1)", [uid])?;
+            conn_lock.execute("DELETE FROM account_ids WHERE uid = (?1)", [uid])?;
+            // the history of the deleted rows can no longer be undone
+            conn_lock.execute("DELETE FROM change_log WHERE uid = (?1)", [uid])?;
+            conn_lock.execute("DELETE FROM change_sets WHERE uid = (?1)", [uid])?;
+            conn_lock.execute("DELETE FROM users WHERE id = (?1)", [uid])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(db: &DbConn, name: &str, password: &str) -> String {
+        db.authenticate(name, password).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn only_the_first_administrator_claims_a_password() {
+        let db = DbConn::new(":memory:").unwrap();
+        let admin = db.add_user("admin".to_string(), true).unwrap();
+        let user = db.add_user("user".to_string(), false).unwrap();
+
+        assert!(db.claim_password(user, "password1").is_err());
+        db.claim_password(admin, "password1").unwrap();
+        assert!(db.claim_password(admin, "password2").is_err());
+        assert_eq!(db.authenticate("admin", "password1").unwrap(), admin);
+
+        let other_admin = db.add_user("other".to_string(), true).unwrap();
+        assert!(db.claim_password(other_admin, "password1").is_err());
+        assert!(!db.has_password(user).unwrap());
+    }
+
+    #[test]
+    fn login_failures_read_the_same() {
+        let db = DbConn::new(":memory:").unwrap();
+        let uid = db.add_user("user".to_string(), true).unwrap();
+        db.add_user("passwordless".to_string(), false).unwrap();
+        db.set_password(uid, "password1").unwrap();
+
+        let expected = failure(&db, "nobody", "password1");
+        assert_eq!(failure(&db, "passwordless", "password1"), expected);
+        for _ in 0..MAX_FAILED_LOGINS {
+            assert_eq!(failure(&db, "user", "wrong"), expected);
+        }
+        // locked out, even with the right password
+        assert_eq!(failure(&db, "user", "password1"), expected);
+
+        // unknown names cost a verification as well
+        assert!(PasswordHash::new(DUMMY_PASSWORD_HASH).is_ok());
     }
 }
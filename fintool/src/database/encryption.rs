@@ -0,0 +1,313 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use rusqlite::{Connection, DatabaseName, ErrorCode, OpenFlags};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::DbConn;
+use crate::error::{FintoolError, Result};
+
+// Encryption is provided by SQLCipher, which encrypts every page of the file
+// with a key derived from the passphrase. Plain databases are opened without a
+// key and behave exactly as before.
+
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+// every unencrypted SQLite database starts with this header, while SQLCipher
+// files start with the random salt of the key
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+const CONVERTED_DB: &str = "converted";
+const CONVERTED_EXTENSION: &str = "converting";
+
+// Returns whether the file at the path is an encrypted database. Files that do
+// not exist yet, or are empty, are not.
+pub fn is_encrypted(db_path: &Path) -> Result<bool> {
+    if !db_path.exists() {
+        return Ok(false);
+    }
+    let mut header = Vec::with_capacity(SQLITE_HEADER.len());
+    fs::File::open(db_path)?
+        .take(SQLITE_HEADER.len() as u64)
+        .read_to_end(&mut header)?;
+    Ok(!header.is_empty() && header != SQLITE_HEADER)
+}
+
+// Opens a connection and, for encrypted databases, applies the key. SQLCipher
+// only checks the key when the first page is read.
+pub(super) fn open_keyed(
+    db_path: &Path,
+    flags: OpenFlags,
+    passphrase: Option<&str>,
+) -> rusqlite::Result<Connection> {
+    let conn = Connection::open_with_flags(db_path, flags)?;
+    if let Some(passphrase) = passphrase {
+        conn.pragma_update(None, "key", passphrase)?;
+    }
+    Ok(conn)
+}
+
+// Reads the schema of the database to make sure the passphrase opens it.
+pub fn check_passphrase(db_path: &Path, passphrase: Option<&str>) -> Result<()> {
+    let conn = open_keyed(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY, passphrase)?;
+    match conn.query_row("SELECT COUNT(*) FROM sqlite_master", (), |row| {
+        row.get::<_, u32>(0)
+    }) {
+        Ok(_) => Ok(()),
+        Err(error) if error.sqlite_error_code() == Some(ErrorCode::NotADatabase) => Err(
+            FintoolError::Authentication(format!("incorrect passphrase for {}", db_path.display())),
+        ),
+        Err(error) => Err(error.into()),
+    }
+}
+
+impl DbConn {
+    // Opens another connection to the database behind this one, keyed the
+    // same way, for readers that must not wait on the shared connection.
+    pub fn open_connection(&self, flags: OpenFlags) -> rusqlite::Result<Connection> {
+        let path = {
+            let conn_lock = self.conn.lock().unwrap();
+            conn_lock.path().map(PathBuf::from)
+        };
+        match path {
+            Some(path) if !path.as_os_str().is_empty() => self.open_sibling(&path, flags),
+            // in-memory databases cannot be shared between connections
+            _ => Connection::open_in_memory_with_flags(flags),
+        }
+    }
+
+    // opens another file, such as a snapshot, that is keyed like this database
+    pub(super) fn open_sibling(
+        &self,
+        path: &Path,
+        flags: OpenFlags,
+    ) -> rusqlite::Result<Connection> {
+        open_keyed(path, flags, self.passphrase.as_deref())
+    }
+
+    // Encrypts the database and its snapshots in place. The database must not
+    // be open.
+    pub fn encrypt_file(db_path: &Path, passphrase: &str) -> Result<()> {
+        if is_encrypted(db_path)? {
+            return Err(FintoolError::Validation(format!(
+                "{} is already encrypted",
+                db_path.display()
+            )));
+        }
+        if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+            return Err(FintoolError::Validation(format!(
+                "passphrases must be at least {} characters long",
+                MIN_PASSPHRASE_LENGTH
+            )));
+        }
+        for path in files_to_convert(db_path, passphrase, true)? {
+            convert(&path, None, Some(passphrase))?;
+        }
+        Ok(())
+    }
+
+    // Decrypts the database and its snapshots in place. The database must not
+    // be open.
+    pub fn decrypt_file(db_path: &Path, passphrase: &str) -> Result<()> {
+        if !is_encrypted(db_path)? {
+            return Err(FintoolError::Validation(format!(
+                "{} is not encrypted",
+                db_path.display()
+            )));
+        }
+        for path in files_to_convert(db_path, passphrase, false)? {
+            convert(&path, Some(passphrase), None)?;
+        }
+        Ok(())
+    }
+}
+
+// Returns the database files that still have to be encrypted, or decrypted,
+// after making sure that every file opens: encrypted ones with the passphrase
+// and the others without a key. Checking them all before converting any means
+// a wrong passphrase, or a snapshot keyed differently, changes nothing.
+fn files_to_convert(db_path: &Path, passphrase: &str, encrypt: bool) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for path in database_files(db_path)? {
+        let encrypted = is_encrypted(&path)?;
+        check_passphrase(&path, encrypted.then_some(passphrase))?;
+        if encrypted != encrypt {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+// the database followed by each of its snapshots
+fn database_files(db_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![db_path.to_path_buf()];
+    // the database is only read to find where its snapshots are kept
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    paths.extend(
+        super::backup::list_snapshots(&conn)?
+            .into_iter()
+            .map(|snapshot| snapshot.path),
+    );
+    Ok(paths)
+}
+
+// Copies the database into a new file with the other key and moves it over
+// the original, so that an interrupted conversion leaves the original intact.
+// An empty key attaches an unencrypted database.
+fn convert(db_path: &Path, from: Option<&str>, to: Option<&str>) -> Result<()> {
+    check_passphrase(db_path, from)?;
+    let converted_path = db_path.with_extension(CONVERTED_EXTENSION);
+    if converted_path.exists() {
+        fs::remove_file(&converted_path)?;
+    }
+
+    {
+        // attached databases are opened with the flags of the connection, which
+        // must allow the converted file to be created
+        let conn = open_keyed(db_path, OpenFlags::default(), from)?;
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS {} KEY ?2", CONVERTED_DB),
+            (converted_path.to_string_lossy(), to.unwrap_or("")),
+        )?;
+        conn.query_row(
+            &format!("SELECT sqlcipher_export('{}')", CONVERTED_DB),
+            (),
+            |_| Ok(()),
+        )?;
+        // sqlcipher_export copies the contents but not the header
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        conn.pragma_update(
+            Some(DatabaseName::Attached(CONVERTED_DB)),
+            "user_version",
+            version,
+        )?;
+        conn.execute(&format!("DETACH DATABASE {}", CONVERTED_DB), ())?;
+    }
+
+    fs::rename(&converted_path, db_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse";
+
+    // a database with a user and a snapshot of it, in a directory of its own
+    fn database_with_snapshot(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "fintool-encryption-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("encryption.db");
+        let db = DbConn::new(&db_path).unwrap();
+        db.add_user("user".to_string(), true).unwrap();
+        db.snapshot("test").unwrap();
+        (dir, db_path)
+    }
+
+    fn snapshot_paths(db_path: &Path) -> Vec<PathBuf> {
+        database_files(db_path).unwrap().split_off(1)
+    }
+
+    #[test]
+    fn encrypts_and_decrypts_the_database_and_its_snapshots() {
+        let (dir, db_path) = database_with_snapshot("round-trip");
+        assert_eq!(snapshot_paths(&db_path).len(), 1);
+
+        DbConn::encrypt_file(&db_path, PASSPHRASE).unwrap();
+        for path in database_files(&db_path).unwrap() {
+            assert!(is_encrypted(&path).unwrap());
+            check_passphrase(&path, Some(PASSPHRASE)).unwrap();
+        }
+        let db = DbConn::open(&db_path, Some(PASSPHRASE)).unwrap();
+        assert_eq!(db.get_users().unwrap(), vec!["user".to_string()]);
+        drop(db);
+
+        DbConn::decrypt_file(&db_path, PASSPHRASE).unwrap();
+        for path in database_files(&db_path).unwrap() {
+            assert!(!is_encrypted(&path).unwrap());
+        }
+        let db = DbConn::new(&db_path).unwrap();
+        assert_eq!(db.get_users().unwrap(), vec!["user".to_string()]);
+        drop(db);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn snapshots_of_an_encrypted_database_are_encrypted() {
+        let (dir, db_path) = database_with_snapshot("snapshot");
+        DbConn::encrypt_file(&db_path, PASSPHRASE).unwrap();
+
+        let db = DbConn::open(&db_path, Some(PASSPHRASE)).unwrap();
+        db.add_user("other".to_string(), false).unwrap();
+        let snapshot = db.snapshot("encrypted").unwrap().unwrap();
+        assert!(is_encrypted(&snapshot.path).unwrap());
+        check_passphrase(&snapshot.path, Some(PASSPHRASE)).unwrap();
+
+        // and restore under the same key
+        db.restore_snapshot(&snapshot).unwrap();
+        assert_eq!(
+            db.get_users().unwrap(),
+            vec!["user".to_string(), "other".to_string()]
+        );
+        drop(db);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn wrong_passphrase_decrypts_nothing() {
+        let (dir, db_path) = database_with_snapshot("wrong-passphrase");
+        DbConn::encrypt_file(&db_path, PASSPHRASE).unwrap();
+
+        assert!(matches!(
+            DbConn::decrypt_file(&db_path, "wrong passphrase"),
+            Err(FintoolError::Authentication(_))
+        ));
+        for path in database_files(&db_path).unwrap() {
+            assert!(is_encrypted(&path).unwrap());
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn differently_keyed_snapshot_converts_nothing() {
+        let (dir, db_path) = database_with_snapshot("mixed-keys");
+        DbConn::encrypt_file(&db_path, PASSPHRASE).unwrap();
+        // re-key the snapshot so it no longer matches the database
+        let snapshot = snapshot_paths(&db_path).remove(0);
+        convert(&snapshot, Some(PASSPHRASE), None).unwrap();
+        convert(&snapshot, None, Some("another passphrase")).unwrap();
+
+        assert!(DbConn::decrypt_file(&db_path, PASSPHRASE).is_err());
+        assert!(is_encrypted(&db_path).unwrap());
+        check_passphrase(&db_path, Some(PASSPHRASE)).unwrap();
+
+        // the same goes for encrypting with a passphrase the snapshot lacks
+        convert(&db_path, Some(PASSPHRASE), None).unwrap();
+        assert!(DbConn::encrypt_file(&db_path, PASSPHRASE).is_err());
+        assert!(!is_encrypted(&db_path).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,481 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::{Months, NaiveDate};
+use shared_lib::{Money, Quantity, TransferType};
+use std::collections::HashMap;
+use std::io::Write;
+use strum::Display;
+
+use super::DbConn;
+use crate::error::{FintoolError, Result};
+
+// Shares held for more than a year are taxed as long-term gains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+pub enum HoldingTerm {
+    #[strum(to_string = "Short-term")]
+    Short,
+    #[strum(to_string = "Long-term")]
+    Long,
+}
+
+impl HoldingTerm {
+    pub fn of(acquired: &str, sold: NaiveDate) -> Result<HoldingTerm> {
+        let acquired = NaiveDate::parse_from_str(acquired, "%Y-%m-%d").map_err(|_| {
+            FintoolError::Validation(format!("lot acquired on an unreadable date '{}'", acquired))
+        })?;
+        Ok(match acquired.checked_add_months(Months::new(12)) {
+            Some(anniversary) if sold > anniversary => HoldingTerm::Long,
+            _ => HoldingTerm::Short,
+        })
+    }
+}
+
+// the part of a lot a sale closed
+#[derive(Debug, Clone)]
+pub struct RealizedGain {
+    pub account: String,
+    pub ticker: String,
+    pub shares: Quantity,
+    pub acquired: String,
+    pub sold: String,
+    pub proceeds: Money,
+    pub basis: Money,
+    pub gain: Money,
+    pub term: HoldingTerm,
+}
+
+// what is left of a lot, at the latest price of its ticker
+#[derive(Debug, Clone)]
+pub struct UnrealizedGain {
+    pub account: String,
+    pub ticker: String,
+    pub shares: Quantity,
+    pub acquired: String,
+    pub basis: Money,
+    // None when no price can be found for the ticker
+    pub value: Option<Money>,
+    pub gain: Option<Money>,
+    pub term: HoldingTerm,
+}
+
+impl DbConn {
+    // Returns every lot, or part of a lot, closed by a sale in the account, or
+    // in every account of the user when aid is None, in the order sold. Shares
+    // moved out of an account without a trade were not sold and are left out.
+    pub fn get_realized_gains(&self, uid: u32, aid: Option<u32>) -> Result<Vec<RealizedGain>> {
+        let sql = "
+            SELECT
+                a.name,
+                p.name,
+                ssa.quantity,
+                pl.date,
+                sl.date,
+                sl.amount,
+                ss.shares,
+                sp.costbasis,
+                ssa.basis
+            FROM stock_sale_allocation AS ssa
+            INNER JOIN stock_purchases AS sp ON
+                ssa.purchase_id = sp.id and
+                ssa.aid = sp.aid and
+                ssa.uid = sp.uid
+            INNER JOIN stock_sales AS ss ON
+                ssa.sale_id = ss.id and
+                ssa.aid = ss.aid and
+                ssa.uid = ss.uid
+            INNER JOIN ledgers AS pl ON
+                sp.lid = pl.id and
+                sp.aid = pl.aid and
+                sp.uid = pl.uid
+            INNER JOIN ledgers AS sl ON
+                ss.lid = sl.id and
+                ss.aid = sl.aid and
+                ss.uid = sl.uid
+            INNER JOIN people AS p ON
+                pl.pid = p.id and
+                pl.aid = p.aid and
+                pl.uid = p.uid
+            INNER JOIN accounts AS a ON
+                ssa.aid = a.id and
+                ssa.uid = a.uid
+            WHERE
+                ssa.uid = (?1) and
+                ((?2) IS NULL or ssa.aid = (?2)) and
+                sl.transfer_type != (?3)
+            ORDER BY sl.date, a.name, p.name, pl.date";
+        let (closed, splits) = {
+            let conn_lock = self.conn.lock().unwrap();
+            let mut stmt = conn_lock.prepare(sql)?;
+            let moved = TransferType::ZeroSumChange as u32;
+            let rows = stmt.query_map((uid, aid, moved), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Quantity>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Money>(5)?,
+                    row.get::<_, Quantity>(6)?,
+                    row.get::<_, Quantity>(7)?,
+                    row.get::<_, Option<Money>>(8)?,
+                ))
+            })?;
+            let closed = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let mut stmt = conn_lock.prepare(
+                "
+                SELECT a.name, p.name, l.date, s.split
+                FROM stock_splits AS s
+                INNER JOIN ledgers AS l ON
+                    s.lid = l.id and
+                    s.aid = l.aid and
+                    s.uid = l.uid
+                INNER JOIN people AS p ON
+                    l.pid = p.id and
+                    l.aid = p.aid and
+                    l.uid = p.uid
+                INNER JOIN accounts AS a ON
+                    s.aid = a.id and
+                    s.uid = a.uid
+                WHERE
+                    s.uid = (?1) and
+                    ((?2) IS NULL or s.aid = (?2))",
+            )?;
+            let rows = stmt.query_map((uid, aid), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Quantity>(3)?,
+                ))
+            })?;
+            let splits = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+            (closed, splits)
+        };
+
+        let mut gains = Vec::new();
+        for (account, ticker, shares, acquired, sold, amount, sold_shares, costbasis, basis) in
+            closed
+        {
+            // splits on or after the sale restate its shares, so they are
+            // undone to report the shares sold that day
+            let split = splits
+                .iter()
+                .filter(|(a, t, date, _)| *a == account && *t == ticker && *date >= sold)
+                .fold(Quantity::ONE, |factor, (_, _, _, split)| factor * *split);
+            let shares_then = shares.checked_div(split).ok_or_else(|| {
+                FintoolError::Validation(format!("a split of {} has a zero ratio", ticker))
+            })?;
+            // the restated price of the sale, as the split leaves what it
+            // brought in unchanged
+            let price = amount.checked_div(sold_shares).unwrap_or(Quantity::ZERO);
+            let proceeds = shares.value_at(price);
+            let basis = basis.unwrap_or_else(|| shares.value_at(costbasis));
+            let sold_on = NaiveDate::parse_from_str(&sold, "%Y-%m-%d").map_err(|_| {
+                FintoolError::Validation(format!("sale on an unreadable date '{}'", sold))
+            })?;
+            gains.push(RealizedGain {
+                term: HoldingTerm::of(&acquired, sold_on)?,
+                account,
+                ticker,
+                shares: shares_then,
+                acquired,
+                sold,
+                proceeds,
+                basis,
+                gain: proceeds - basis,
+            });
+        }
+        Ok(gains)
+    }
+
+    // Returns the shares left of every lot in the account, or in every account
+    // of the user when aid is None, valued at the latest manually recorded
+    // price of the ticker or else its latest close.
+    pub fn get_unrealized_gains(
+        &self,
+        uid: u32,
+        aid: Option<u32>,
+        today: NaiveDate,
+    ) -> Result<Vec<UnrealizedGain>> {
+        let sql = "
+            SELECT
+                a.name,
+                p.name,
+                sp.remaining,
+                pl.date,
+                sp.costbasis,
+                (
+                    SELECT prices.price
+                    FROM stock_prices AS prices
+                    INNER JOIN people AS pp ON
+                        prices.stock_ticker_peer_id = pp.id and
+                        prices.aid = pp.aid and
+                        prices.uid = pp.uid
+                    WHERE
+                        prices.uid = sp.uid and
+                        prices.aid = sp.aid and
+                        pp.name = p.name
+                    ORDER BY prices.date DESC
+                    LIMIT 1
+                )
+            FROM stock_purchases AS sp
+            INNER JOIN ledgers AS pl ON
+                sp.lid = pl.id and
+                sp.aid = pl.aid and
+                sp.uid = pl.uid
+            INNER JOIN people AS p ON
+                pl.pid = p.id and
+                pl.aid = p.aid and
+                pl.uid = p.uid
+            INNER JOIN accounts AS a ON
+                sp.aid = a.id and
+                sp.uid = a.uid
+            WHERE
+                sp.uid = (?1) and
+                ((?2) IS NULL or sp.aid = (?2)) and
+                sp.remaining > 0
+            ORDER BY a.name, p.name, pl.date";
+        let lots = {
+            let conn_lock = self.conn.lock().unwrap();
+            let mut stmt = conn_lock.prepare(sql)?;
+            let rows = stmt.query_map((uid, aid), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Quantity>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Quantity>(4)?,
+                    row.get::<_, Option<Quantity>>(5)?,
+                ))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        // quotes are fetched once for each ticker
+        let mut closes: HashMap<String, Option<Quantity>> = HashMap::new();
+        let mut gains = Vec::new();
+        for (account, ticker, shares, acquired, costbasis, recorded_price) in lots {
+            let price = match recorded_price {
+                Some(price) => Some(price),
+                None => *closes.entry(ticker.clone()).or_insert_with(|| {
+                    shared_lib::stocks::get_stock_at_close(ticker.clone())
+                        .ok()
+                        .map(Quantity::from_f64)
+                }),
+            };
+            let basis = shares.value_at(costbasis);
+            let value = price.map(|price| shares.value_at(price));
+            gains.push(UnrealizedGain {
+                term: HoldingTerm::of(&acquired, today)?,
+                account,
+                ticker,
+                shares,
+                acquired,
+                basis,
+                value,
+                gain: value.map(|value| value - basis),
+            });
+        }
+        Ok(gains)
+    }
+}
+
+// Writes the closed lots as CSV in the columns of IRS Form 8949, short-term
+// sales (Part I) before long-term sales (Part II). Adjustment codes and
+// amounts are left for the filer to fill in.
+pub fn write_form_8949<W: Write>(gains: &[RealizedGain], writer: W) -> Result<usize> {
+    let mut wtr = csv::Writer::from_writer(writer);
+    wtr.write_record([
+        "Part",
+        "(a) Description of property",
+        "(b) Date acquired",
+        "(c) Date sold or disposed of",
+        "(d) Proceeds",
+        "(e) Cost or other basis",
+        "(f) Code",
+        "(g) Amount of adjustment",
+        "(h) Gain or (loss)",
+        "Account",
+    ])?;
+    let mut sorted: Vec<&RealizedGain> = gains.iter().collect();
+    sorted.sort_by_key(|gain| gain.term);
+    for gain in sorted.iter() {
+        wtr.write_record([
+            match gain.term {
+                HoldingTerm::Short => "I".to_string(),
+                HoldingTerm::Long => "II".to_string(),
+            },
+            format!("{} sh. {}", gain.shares, gain.ticker),
+            form_date(&gain.acquired),
+            form_date(&gain.sold),
+            gain.proceeds.to_string(),
+            gain.basis.to_string(),
+            String::new(),
+            String::new(),
+            gain.gain.to_string(),
+            gain.account.clone(),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(sorted.len())
+}
+
+// Form 8949 dates are written MM/DD/YYYY.
+fn form_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|date| date.format("%m/%d/%Y").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::accounts::{AccountInfo, AccountType};
+    use crate::types::investments::StockInfo;
+    use crate::types::ledger::LedgerInfo;
+    use crate::types::participants::ParticipantType;
+
+    #[test]
+    fn a_later_split_leaves_the_sale_as_it_was() {
+        let db = DbConn::new(":memory:").unwrap();
+        let uid = db.add_user("user".to_string(), true).unwrap();
+        let aid = db
+            .add_account(
+                uid,
+                &AccountInfo {
+                    atype: AccountType::Investment,
+                    name: "Brokerage".to_string(),
+                    has_stocks: true,
+                    has_bank: true,
+                    has_ledger: true,
+                    has_budget: false,
+                },
+            )
+            .unwrap();
+        let pid = db
+            .check_and_add_participant(uid, aid, "ACME".to_string(), ParticipantType::Both, false)
+            .unwrap();
+        let cid = db.add_category(uid, aid, "TRADE".to_string()).unwrap();
+        let entry = |date: &str, amount: Money, transfer_type: TransferType| {
+            db.add_ledger_entry(
+                uid,
+                aid,
+                LedgerInfo {
+                    date: date.to_string(),
+                    amount,
+                    transfer_type,
+                    participant: pid,
+                    category_id: cid,
+                    description: String::new(),
+                },
+            )
+            .unwrap()
+        };
+
+        // buy 10 shares at $10 and sell 5 of them at $20
+        let ten = Quantity::from_f64(10.0);
+        let five = Quantity::from_f64(5.0);
+        let bought = entry(
+            "2020-01-02",
+            Money::from_cents(10_000),
+            TransferType::WithdrawalToInternalAccount,
+        );
+        let lot = db
+            .add_stock_purchase(
+                uid,
+                aid,
+                StockInfo {
+                    shares: ten,
+                    costbasis: ten,
+                    remaining: five,
+                    ledger_id: bought,
+                },
+            )
+            .unwrap();
+        let sold = entry(
+            "2020-06-01",
+            Money::from_cents(10_000),
+            TransferType::DepositFromInternalAccount,
+        );
+        let sale = db
+            .add_stock_sale(
+                uid,
+                aid,
+                StockInfo {
+                    shares: five,
+                    costbasis: Quantity::from_f64(20.0),
+                    remaining: Quantity::ZERO,
+                    ledger_id: sold,
+                },
+            )
+            .unwrap();
+        let allocation = db
+            .add_stock_sale_allocation(uid, aid, lot, sale, five)
+            .unwrap();
+
+        let expected = |shares: f64| {
+            let gains = db.get_realized_gains(uid, Some(aid)).unwrap();
+            assert_eq!(gains.len(), 1);
+            assert_eq!(gains[0].shares, Quantity::from_f64(shares));
+            assert_eq!(gains[0].proceeds, Money::from_cents(10_000));
+            assert_eq!(gains[0].basis, Money::from_cents(5_000));
+            assert_eq!(gains[0].gain, Money::from_cents(5_000));
+        };
+        expected(5.0);
+
+        // a 2:1 split afterwards restates the lot and the sale as
+        // allocate_stock_split does
+        let split = Quantity::from_f64(2.0);
+        db.update_stock_shares_purchased(uid, aid, lot, ten * split)
+            .unwrap();
+        db.update_stock_remaining(uid, aid, lot, five * split)
+            .unwrap();
+        db.update_cost_basis(uid, aid, lot, ten.checked_div(split).unwrap())
+            .unwrap();
+        db.update_stock_sale_allocation_quantity(uid, aid, allocation, five * split)
+            .unwrap();
+        db.update_stock_shares_sold(uid, aid, sale, five * split)
+            .unwrap();
+        let split_on = entry("2022-01-03", Money::ZERO, TransferType::ZeroSumChange);
+        db.add_stock_split(uid, aid, split, split_on).unwrap();
+        expected(5.0);
+    }
+
+    #[test]
+    fn holding_terms() {
+        let sold = NaiveDate::from_ymd_opt(2021, 6, 1).unwrap();
+        let cases = [
+            ("2020-05-31", HoldingTerm::Long),
+            ("2020-06-01", HoldingTerm::Short),
+            ("2021-01-04", HoldingTerm::Short),
+        ];
+        for (acquired, term) in cases {
+            assert_eq!(
+                HoldingTerm::of(acquired, sold).unwrap(),
+                term,
+                "{}",
+                acquired
+            );
+        }
+        // an unreadable date is refused rather than taken as short-term
+        assert!(matches!(
+            HoldingTerm::of("06/01/2020", sold),
+            Err(FintoolError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,479 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use shared_lib::{Money, Quantity, TransferType};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use strum::{Display, EnumIter};
+
+use super::DbConn;
+use crate::error::Result;
+use crate::types::accounts::{AccountRecord, AccountType};
+use crate::types::ledger::LedgerRecord;
+
+const CURRENCY: &str = "USD";
+const CAPITAL_GAINS_ACCOUNT: &str = "Income:CapitalGains";
+const FEES_ACCOUNT: &str = "Expenses:Fees";
+const UNCATEGORIZED: &str = "Uncategorized";
+
+// the plain-text accounting tools a journal can be written for
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum JournalSyntax {
+    Beancount,
+    #[strum(to_string = "hledger")]
+    Hledger,
+}
+
+impl JournalSyntax {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            JournalSyntax::Beancount => "beancount",
+            JournalSyntax::Hledger => "journal",
+        }
+    }
+}
+
+// shares bought together, at the price paid for each
+struct Lot {
+    cost: Quantity,
+    date: String,
+}
+
+struct Posting {
+    account: String,
+    // number and commodity; None leaves the amount for the accounting tool to
+    // work out so that the transaction balances
+    amount: Option<(String, String)>,
+    lot: Option<Lot>,
+    // price per share a sale was made at
+    price: Option<Quantity>,
+}
+
+impl Posting {
+    fn cash(account: &str, amount: Money) -> Self {
+        Posting {
+            account: account.to_string(),
+            amount: Some((amount.to_string(), CURRENCY.to_string())),
+            lot: None,
+            price: None,
+        }
+    }
+
+    fn balancing(account: &str) -> Self {
+        Posting {
+            account: account.to_string(),
+            amount: None,
+            lot: None,
+            price: None,
+        }
+    }
+
+    fn shares(
+        account: &str,
+        ticker: &str,
+        shares: Quantity,
+        lot: Option<Lot>,
+        price: Option<Quantity>,
+    ) -> Self {
+        Posting {
+            account: account.to_string(),
+            amount: Some((shares.to_string(), commodity(ticker))),
+            lot,
+            price,
+        }
+    }
+}
+
+struct JournalTransaction {
+    date: String,
+    payee: String,
+    narration: String,
+    postings: Vec<Posting>,
+}
+
+impl DbConn {
+    // Writes a balanced double-entry journal of every account the user owns
+    // and returns the number of transactions in it. Transfers between the
+    // accounts become one transaction with a posting to each; other entries
+    // post against an income or expense account named after their category.
+    // Stock purchases and sales move shares at their cost basis, with the gain
+    // on a sale booked to Income:CapitalGains. Stock splits are left out: they
+    // change no balance, as fintool restates the lots they split.
+    pub fn write_journal<W: Write>(
+        &self,
+        uid: u32,
+        syntax: JournalSyntax,
+        mut writer: W,
+    ) -> Result<usize> {
+        let accounts = self.get_owned_accounts(uid)?;
+        let names: HashMap<u32, String> = accounts
+            .iter()
+            .map(|account| (account.id, journal_account(account)))
+            .collect();
+
+        // transfers are written once, from the ledger of the account the money
+        // left
+        let mut transfers: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut received: HashSet<(u32, u32)> = HashSet::new();
+        for transfer in self.get_account_transactions(uid)? {
+            let info = transfer.info;
+            transfers.insert((info.from_account, info.from_ledger), info.to_account);
+            received.insert((info.to_account, info.to_ledger));
+        }
+
+        let mut transactions = Vec::new();
+        for account in accounts.iter() {
+            let mut ledger = self.get_ledger(uid, account.id)?;
+            ledger.sort_by_key(|record| record.id);
+            for record in ledger {
+                if record.info.transfer_type == TransferType::ZeroSumChange
+                    || received.contains(&(account.id, record.id))
+                {
+                    continue;
+                }
+                let to_account = transfers
+                    .get(&(account.id, record.id))
+                    .and_then(|aid| names.get(aid));
+                transactions.push(self.journal_transaction(
+                    uid,
+                    account,
+                    &names[&account.id],
+                    to_account,
+                    record,
+                )?);
+            }
+        }
+        transactions.sort_by(|a, b| a.date.cmp(&b.date));
+
+        let user = self.get_user_name(uid)?;
+        match syntax {
+            JournalSyntax::Beancount => write_beancount(&user, &transactions, &mut writer)?,
+            JournalSyntax::Hledger => write_hledger(&user, &transactions, &mut writer)?,
+        }
+        writer.flush()?;
+        Ok(transactions.len())
+    }
+
+    fn journal_transaction(
+        &self,
+        uid: u32,
+        account: &AccountRecord,
+        name: &str,
+        to_account: Option<&String>,
+        record: LedgerRecord,
+    ) -> Result<JournalTransaction> {
+        let aid = account.id;
+        let info = record.info;
+        let payee = self.get_participant(uid, aid, info.participant)?;
+        let signed = if info.transfer_type.is_deposit() {
+            info.amount
+        } else {
+            -info.amount
+        };
+
+        let mut postings = Vec::new();
+        let purchase = match info.transfer_type {
+            TransferType::WithdrawalToInternalAccount if account.info.has_stocks => self
+                .check_and_get_stock_purchase_record_matching_from_ledger_id(uid, aid, record.id)?,
+            _ => None,
+        };
+        let sale = match info.transfer_type {
+            TransferType::DepositFromInternalAccount if account.info.has_stocks => {
+                self.check_and_get_stock_sale_record_matching_from_ledger_id(uid, aid, record.id)?
+            }
+            _ => None,
+        };
+
+        if let Some(to_account) = to_account {
+            postings.push(Posting::cash(name, signed));
+            postings.push(Posting::cash(to_account, -signed));
+        } else if let Some(purchase) = purchase {
+            let shares = purchase.info.shares;
+            let cost = purchase.info.costbasis;
+            let lot = Lot {
+                cost,
+                date: info.date.clone(),
+            };
+            postings.push(Posting::shares(name, &payee, shares, Some(lot), None));
+            postings.push(Posting::cash(name, -info.amount));
+            // commissions, or rounding of the price paid
+            if shares.value_at(cost) != info.amount {
+                postings.push(Posting::balancing(FEES_ACCOUNT));
+            }
+        } else if let Some(sale) = sale {
+            let price = sale.info.costbasis;
+            postings.push(Posting::cash(name, info.amount));
+            let lots = self.get_sale_lots(uid, aid, sale.id)?;
+            if lots.is_empty() {
+                // the lots sold were not recorded; leave it to the tool to pick
+                postings.push(Posting::shares(
+                    name,
+                    &payee,
+                    -sale.info.shares,
+                    None,
+                    Some(price),
+                ));
+            }
+            for (quantity, lot) in lots {
+                postings.push(Posting::shares(
+                    name,
+                    &payee,
+                    -quantity,
+                    Some(lot),
+                    Some(price),
+                ));
+            }
+            postings.push(Posting::balancing(CAPITAL_GAINS_ACCOUNT));
+        } else {
+            let category = self.get_category_name(uid, aid, info.category_id)?;
+            let root = if info.transfer_type.is_deposit() {
+                "Income"
+            } else {
+                "Expenses"
+            };
+            postings.push(Posting::cash(name, signed));
+            postings.push(Posting::cash(&category_account(root, &category), -signed));
+        }
+
+        Ok(JournalTransaction {
+            date: info.date,
+            payee,
+            narration: info.description,
+            postings,
+        })
+    }
+
+    // the purchases a sale drew its shares from, oldest first
+    fn get_sale_lots(&self, uid: u32, aid: u32, sale_id: u32) -> Result<Vec<(Quantity, Lot)>> {
+        let sql = "SELECT a.quantity, p.costbasis, l.date FROM stock_sale_allocation as a
+            INNER JOIN stock_purchases as p ON p.uid = a.uid and p.aid = a.aid and p.id = a.purchase_id
+            INNER JOIN ledgers as l ON l.uid = p.uid and l.aid = p.aid and l.id = p.lid
+            WHERE a.sale_id = (?1) and a.uid = (?2) and a.aid = (?3)
+            ORDER BY l.date, p.id";
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        let lots = stmt
+            .query_map(rusqlite::params![sale_id, uid, aid], |row| {
+                Ok((
+                    row.get::<_, Quantity>(0)?,
+                    Lot {
+                        cost: row.get(1)?,
+                        date: row.get(2)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(lots)
+    }
+}
+
+fn journal_account(account: &AccountRecord) -> String {
+    let (root, kind) = match account.info.atype {
+        AccountType::Bank => ("Assets", "Bank"),
+        AccountType::Investment => ("Assets", "Investment"),
+        AccountType::CreditCard => ("Liabilities", "CreditCard"),
+        AccountType::Wallet => ("Assets", "Wallet"),
+        AccountType::CD => ("Assets", "CD"),
+        AccountType::Retirement401k => ("Assets", "Retirement401k"),
+        AccountType::RetirementRothIra => ("Assets", "RothIRA"),
+        AccountType::HealthSavingsAccount => ("Assets", "HSA"),
+    };
+    let name = account_component(&account.info.name).unwrap_or("Unnamed".to_string());
+    format!("{}:{}:{}", root, kind, name)
+}
+
+// Categories written as `Parent:Child` become sub-accounts.
+fn category_account(root: &str, category: &str) -> String {
+    let components: Vec<String> = category.split(':').filter_map(account_component).collect();
+    if components.is_empty() {
+        format!("{}:{}", root, UNCATEGORIZED)
+    } else {
+        format!("{}:{}", root, components.join(":"))
+    }
+}
+
+// Beancount only accepts letters, digits and dashes in the parts of an account
+// name, the first being a capital letter or a digit.
+fn account_component(name: &str) -> Option<String> {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect()
+        })
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join("-"))
+    }
+}
+
+// Beancount commodities are upper case and start with a letter.
+fn commodity(ticker: &str) -> String {
+    let mut symbol: String = ticker
+        .trim()
+        .to_ascii_uppercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "'._-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if !symbol.starts_with(|c: char| c.is_ascii_uppercase()) {
+        symbol.insert(0, 'X');
+    }
+    symbol
+}
+
+// the date each account is first posted to, which Beancount must open it by
+fn account_openings(transactions: &[JournalTransaction]) -> BTreeMap<&str, &str> {
+    let mut openings: BTreeMap<&str, &str> = BTreeMap::new();
+    for transaction in transactions {
+        for posting in transaction.postings.iter() {
+            let opened = openings
+                .entry(posting.account.as_str())
+                .or_insert(transaction.date.as_str());
+            if transaction.date.as_str() < *opened {
+                *opened = transaction.date.as_str();
+            }
+        }
+    }
+    openings
+}
+
+fn beancount_string(value: &str) -> String {
+    let value = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(['\n', '\r'], " ");
+    format!("\"{}\"", value)
+}
+
+fn write_beancount<W: Write>(
+    user: &str,
+    transactions: &[JournalTransaction],
+    writer: &mut W,
+) -> Result<()> {
+    writeln!(writer, "option \"title\" {}", beancount_string(user))?;
+    writeln!(writer, "option \"operating_currency\" \"{}\"", CURRENCY)?;
+    // sales whose lots were not recorded reduce the oldest lots first
+    writeln!(writer, "option \"booking_method\" \"FIFO\"")?;
+    writeln!(writer)?;
+    for (account, date) in account_openings(transactions) {
+        writeln!(writer, "{} open {}", date, account)?;
+    }
+
+    for transaction in transactions {
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{} * {} {}",
+            transaction.date,
+            beancount_string(&transaction.payee),
+            beancount_string(&transaction.narration)
+        )?;
+        for posting in transaction.postings.iter() {
+            let (number, commodity) = match &posting.amount {
+                Some(amount) => amount,
+                None => {
+                    writeln!(writer, "  {}", posting.account)?;
+                    continue;
+                }
+            };
+            let mut amount = format!("{} {}", number, commodity);
+            if commodity != CURRENCY {
+                match &posting.lot {
+                    Some(lot) => amount += &format!(" {{{} {}, {}}}", lot.cost, CURRENCY, lot.date),
+                    None => amount += " {}",
+                }
+            }
+            if let Some(price) = posting.price {
+                amount += &format!(" @ {} {}", price, CURRENCY);
+            }
+            writeln!(writer, "  {:<48}  {}", posting.account, amount)?;
+        }
+    }
+    Ok(())
+}
+
+// hledger descriptions end at a ';', which starts a comment
+fn hledger_text(value: &str) -> String {
+    value.replace(';', ",").replace(['\n', '\r', '|'], " ")
+}
+
+// hledger quotes commodity symbols holding anything but letters
+fn hledger_commodity(commodity: &str) -> String {
+    if commodity.chars().all(|c| c.is_alphabetic()) {
+        commodity.to_string()
+    } else {
+        format!("\"{}\"", commodity)
+    }
+}
+
+fn write_hledger<W: Write>(
+    user: &str,
+    transactions: &[JournalTransaction],
+    writer: &mut W,
+) -> Result<()> {
+    writeln!(writer, "; books of {}", hledger_text(user))?;
+    writeln!(writer)?;
+    for account in account_openings(transactions).keys() {
+        writeln!(writer, "account {}", account)?;
+    }
+
+    for transaction in transactions {
+        writeln!(writer)?;
+        let mut description = hledger_text(&transaction.payee);
+        if !transaction.narration.is_empty() {
+            description += &format!(" | {}", hledger_text(&transaction.narration));
+        }
+        writeln!(writer, "{} * {}", transaction.date, description)?;
+        for posting in transaction.postings.iter() {
+            let (number, commodity) = match &posting.amount {
+                Some(amount) => amount,
+                None => {
+                    writeln!(writer, "    {}", posting.account)?;
+                    continue;
+                }
+            };
+            let mut amount = format!("{} {}", number, hledger_commodity(commodity));
+            // hledger does not track lots, so shares are priced at their cost
+            // basis and the gain on a sale is left over for Income:CapitalGains
+            let price = match &posting.lot {
+                Some(lot) => Some(lot.cost),
+                None => posting.price,
+            };
+            if let Some(price) = price {
+                amount += &format!(" @ {} {}", price, CURRENCY);
+            }
+            match &posting.lot {
+                Some(lot) if posting.price.is_some() => writeln!(
+                    writer,
+                    "    {:<48}  {}  ; bought {}",
+                    posting.account, amount, lot.date
+                )?,
+                _ => writeln!(writer, "    {:<48}  {}", posting.account, amount)?,
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,485 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use regex::Regex;
+use rusqlite::{ffi, Connection, Transaction};
+use std::path::PathBuf;
+
+use super::backup::{rotate_snapshots, take_snapshot, SCHEMA_SNAPSHOT_PREFIX};
+use super::{DbConn, CURRENT_DATABASE_SCHEMA_VERSION};
+use crate::error::FintoolError;
+use crate::types::investments::SaleMethod;
+
+pub struct Migration {
+    // schema version the database is at once this step has been applied
+    pub version: i32,
+    pub description: &'static str,
+    pub apply: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+// Ordered registry of schema changes. Entry N upgrades a database from
+// version N to version N+1, so new steps are only ever appended to the end.
+// The create_*_table functions must always describe the latest schema, since
+// a freshly created database is stamped with the current version and never
+// walks through this list.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "store amounts as integer cents and shares/prices as integer millionths",
+        apply: convert_real_columns_to_fixed_point,
+    },
+    Migration {
+        version: 2,
+        description: "add password hashes and login lockout to users",
+        apply: add_user_credentials,
+    },
+    Migration {
+        version: 3,
+        description: "record the method each stock sale was allocated with",
+        apply: add_stock_sale_method,
+    },
+    Migration {
+        version: 4,
+        description: "record the average cost of shares sold at average cost",
+        apply: add_stock_sale_allocation_basis,
+    },
+];
+
+const CENTS: i64 = 100;
+const MILLIONTHS: i64 = 1_000_000;
+
+fn convert_real_columns_to_fixed_point(tx: &Transaction) -> rusqlite::Result<()> {
+    convert_to_fixed_point(tx, "ledgers", &[("amount", CENTS)])?;
+    convert_to_fixed_point(
+        tx,
+        "stock_purchases",
+        &[
+            ("shares", MILLIONTHS),
+            ("costbasis", MILLIONTHS),
+            ("remaining", MILLIONTHS),
+        ],
+    )?;
+    convert_to_fixed_point(
+        tx,
+        "stock_sales",
+        &[("shares", MILLIONTHS), ("price", MILLIONTHS)],
+    )?;
+    convert_to_fixed_point(tx, "stock_sale_allocation", &[("quantity", MILLIONTHS)])?;
+    convert_to_fixed_point(tx, "stock_splits", &[("split", MILLIONTHS)])?;
+    convert_to_fixed_point(tx, "stock_prices", &[("price", MILLIONTHS)])?;
+    convert_to_fixed_point(tx, "budgets", &[("value", CENTS)])?;
+    convert_to_fixed_point(tx, "credit_cards", &[("credit_line", CENTS)])?;
+    convert_to_fixed_point(tx, "certificate_of_deposits", &[("principal", CENTS)])?;
+    convert_to_fixed_point(tx, "cds", &[("principal", CENTS)])?;
+    convert_to_fixed_point(tx, "plan_401ks", &[("contribution_limit", CENTS)])?;
+    convert_to_fixed_point(tx, "hsas", &[("contribution_limit", CENTS)])?;
+    convert_to_fixed_point(tx, "roth_iras", &[("contribution_limit", CENTS)])?;
+    Ok(())
+}
+
+// Existing users are left without a password and are asked to choose one the
+// next time they log in.
+fn add_user_credentials(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE users ADD COLUMN password_hash TEXT;
+        ALTER TABLE users ADD COLUMN failed_logins INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE users ADD COLUMN locked_until TEXT;",
+    )
+}
+
+// Before the method was recorded, the sale menu's FIFO walked the lots newest
+// first and the importers, which passed LIFO, walked them oldest first, so
+// neither label can be trusted. The method of each earlier sale is instead
+// worked out from the lots it was allocated.
+fn add_stock_sale_method(tx: &Transaction) -> rusqlite::Result<()> {
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' and name = 'stock_sales'",
+        (),
+        |row| row.get(0),
+    )?;
+    if !exists {
+        // the table will be created in the new layout
+        return Ok(());
+    }
+    tx.execute_batch("ALTER TABLE stock_sales ADD COLUMN method INTEGER NOT NULL DEFAULT 0;")?;
+    derive_stock_sale_methods(tx)
+}
+
+// Earlier average cost sales spread their shares across every lot, so their
+// basis is already the lots' own and is left unset.
+fn add_stock_sale_allocation_basis(tx: &Transaction) -> rusqlite::Result<()> {
+    let exists: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' and name = 'stock_sale_allocation'",
+        (),
+        |row| row.get(0),
+    )?;
+    if !exists {
+        // the table will be created in the new layout
+        return Ok(());
+    }
+    tx.execute_batch("ALTER TABLE stock_sale_allocation ADD COLUMN basis INTEGER;")
+}
+
+// user, account and id of a stock sale
+type SaleKey = (u32, u32, u32);
+
+// Lots allocated newest first make a sale LIFO and oldest first FIFO. A sale of
+// a single lot, or of lots bought the same day, shows no order: it is LIFO when
+// it took the newest of lots bought on different days. Any other sale is left
+// FIFO, the method the importers sell with, rather than labelled with a method
+// nothing shows was chosen.
+fn derive_stock_sale_methods(tx: &Transaction) -> rusqlite::Result<()> {
+    let mut stmt = tx.prepare(
+        "SELECT a.uid, a.aid, a.sale_id, l.date, l.pid
+        FROM stock_sale_allocation AS a
+        INNER JOIN stock_purchases AS p ON p.uid = a.uid and p.aid = a.aid and p.id = a.purchase_id
+        INNER JOIN ledgers AS l ON l.uid = p.uid and l.aid = p.aid and l.id = p.lid
+        ORDER BY a.uid, a.aid, a.sale_id, a.id",
+    )?;
+    let allocations = stmt
+        .query_map((), |row| {
+            Ok((
+                (
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, u32>(2)?,
+                ),
+                row.get::<_, String>(3)?,
+                row.get::<_, u32>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    // the purchase dates of each sale's lots, in the order they were allocated,
+    // along with the sale's ticker
+    let mut sales: Vec<(SaleKey, Vec<String>, u32)> = Vec::new();
+    for (sale, date, pid) in allocations {
+        match sales.last_mut() {
+            Some((last, dates, _)) if *last == sale => dates.push(date),
+            _ => sales.push((sale, vec![date], pid)),
+        }
+    }
+
+    tx.execute(
+        "UPDATE stock_sales SET method = (?1)",
+        [SaleMethod::Fifo as u32],
+    )?;
+    for ((uid, aid, sale_id), dates, pid) in sales {
+        let (first, last) = (&dates[0], &dates[dates.len() - 1]);
+        let lifo = if first != last {
+            first > last
+        } else {
+            let (oldest, newest): (String, String) = tx.query_row(
+                "SELECT MIN(l.date), MAX(l.date)
+                FROM stock_purchases AS p
+                INNER JOIN ledgers AS l ON l.uid = p.uid and l.aid = p.aid and l.id = p.lid
+                WHERE p.uid = (?1) and p.aid = (?2) and l.pid = (?3)",
+                (uid, aid, pid),
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            *first == newest && oldest != newest
+        };
+        if lifo {
+            tx.execute(
+                "UPDATE stock_sales SET method = (?1) WHERE uid = (?2) and aid = (?3) and id = (?4)",
+                (SaleMethod::Lifo as u32, uid, aid, sale_id),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// Scales the listed columns of a table to integers. SQLite cannot change the
+// declared type of a column, so tables that declared any of them as REAL are
+// rebuilt under the new declaration and copied across; otherwise the values
+// are rescaled in place.
+fn convert_to_fixed_point(
+    tx: &Transaction,
+    table: &str,
+    columns: &[(&str, i64)],
+) -> rusqlite::Result<()> {
+    let mut stmt =
+        tx.prepare("SELECT sql FROM sqlite_master WHERE type = 'table' and name = (?1)")?;
+    let create_sql: String = match stmt.query_row((table,), |row| row.get(0)) {
+        Ok(sql) => sql,
+        // the table has not been created yet and will start out in the new layout
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(()),
+        Err(error) => return Err(error),
+    };
+
+    let mut new_sql = create_sql.clone();
+    for (column, _) in columns {
+        let declaration = Regex::new(&format!(r"(?i)(\b{}\s+)REAL\b", column)).unwrap();
+        new_sql = declaration.replace(&new_sql, "${1}INTEGER").to_string();
+    }
+
+    if new_sql == create_sql {
+        for (column, scale) in columns {
+            tx.execute(
+                &format!(
+                    "UPDATE {table} SET {column} = CAST(ROUND({column} * {scale}) AS INTEGER)"
+                ),
+                (),
+            )?;
+        }
+        return Ok(());
+    }
+
+    let staging = format!("{}_fixed_point", table);
+    let header = Regex::new(&format!(r#"(?i)^CREATE TABLE\s+["'`]?{}["'`]?"#, table)).unwrap();
+    let new_sql = header
+        .replace(&new_sql, format!("CREATE TABLE {}", staging).as_str())
+        .to_string();
+    tx.execute(&new_sql, ())?;
+
+    let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt
+        .query_map((), |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<String>, _>>()?;
+    let selection = names
+        .iter()
+        .map(
+            |name| match columns.iter().find(|(column, _)| column == name) {
+                Some((column, scale)) => format!("CAST(ROUND({} * {}) AS INTEGER)", column, scale),
+                None => name.clone(),
+            },
+        )
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    tx.execute(
+        &format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {}",
+            staging,
+            names.join(", "),
+            selection,
+            table
+        ),
+        (),
+    )?;
+    tx.execute(&format!("DROP TABLE {}", table), ())?;
+    tx.execute(&format!("ALTER TABLE {} RENAME TO {}", staging, table), ())?;
+    Ok(())
+}
+
+impl DbConn {
+    pub(super) fn is_new_database(conn: &Connection) -> rusqlite::Result<bool> {
+        let count: u32 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+            (),
+            |row| row.get(0),
+        )?;
+        Ok(count == 0)
+    }
+
+    // Returns a note of each step taken, for the caller to show the user.
+    pub(super) fn migrate(
+        conn: &Connection,
+        passphrase: Option<&str>,
+    ) -> rusqlite::Result<Vec<String>> {
+        let version = Self::get_schema_version(conn)?;
+
+        if version > CURRENT_DATABASE_SCHEMA_VERSION {
+            return Err(rusqlite::Error::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_CANTOPEN),
+                Some(format!(
+                    "database schema version {} is newer than the version supported by this build ({}); please upgrade fintool",
+                    version, CURRENT_DATABASE_SCHEMA_VERSION
+                )),
+            ));
+        }
+
+        if version == CURRENT_DATABASE_SCHEMA_VERSION {
+            return Ok(Vec::new());
+        }
+
+        let mut notes = Vec::new();
+        if let Some(snapshot) = Self::backup_before_migration(conn, passphrase, version)? {
+            notes.push(format!(
+                "Backed up database to {} before upgrading.",
+                snapshot.display()
+            ));
+        }
+
+        for migration in MIGRATIONS.iter().skip(version as usize) {
+            let expected = Self::get_schema_version(conn)? + 1;
+            if migration.version != expected {
+                return Err(rusqlite::Error::SqliteFailure(
+                    ffi::Error::new(ffi::SQLITE_INTERNAL),
+                    Some(format!(
+                        "schema migration to version {} is out of order, expected version {}",
+                        migration.version, expected
+                    )),
+                ));
+            }
+
+            let tx = conn.unchecked_transaction()?;
+            (migration.apply)(&tx)?;
+            Self::check_foreign_keys(&tx)?;
+            Self::set_schema_version(&tx, migration.version)?;
+            tx.commit()?;
+
+            notes.push(format!(
+                "Upgraded database to schema version {}: {}",
+                migration.version, migration.description
+            ));
+        }
+
+        Ok(notes)
+    }
+
+    // Migrations run with foreign key enforcement off so that tables can be
+    // rebuilt; verify nothing was left dangling before committing the step.
+    fn check_foreign_keys(tx: &Transaction) -> rusqlite::Result<()> {
+        let mut stmt = tx.prepare("PRAGMA foreign_key_check")?;
+        let violation = stmt.exists(())?;
+        if violation {
+            return Err(rusqlite::Error::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_CONSTRAINT_FOREIGNKEY),
+                Some("schema migration left foreign key violations".to_string()),
+            ));
+        }
+        Ok(())
+    }
+
+    // Upgrading without a backup is refused; the user can free up space or
+    // fix permissions and try again with the database untouched. Returns
+    // where the backup was written, if anywhere.
+    fn backup_before_migration(
+        conn: &Connection,
+        passphrase: Option<&str>,
+        version: i32,
+    ) -> rusqlite::Result<Option<PathBuf>> {
+        let snapshot = take_snapshot(
+            conn,
+            passphrase,
+            &format!("{}{}", SCHEMA_SNAPSHOT_PREFIX, version),
+        )
+        .and_then(|snapshot| rotate_snapshots(conn).map(|_| snapshot));
+        match snapshot {
+            Ok(Some(snapshot)) => Ok(Some(snapshot.path)),
+            // in-memory databases have nothing to back up
+            Ok(None) => Ok(None),
+            Err(FintoolError::Database(error)) => Err(error),
+            Err(error) => Err(rusqlite::Error::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_IOERR),
+                Some(format!(
+                    "unable to back up database before upgrading: {}",
+                    error
+                )),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::accounts::{AccountInfo, AccountType};
+    use crate::types::investments::StockInfo;
+    use crate::types::ledger::LedgerInfo;
+    use crate::types::participants::ParticipantType;
+    use shared_lib::{Money, Quantity, TransferType};
+
+    #[test]
+    fn derives_the_method_of_earlier_sales_from_their_lots() {
+        let db = DbConn::new(":memory:").unwrap();
+        let uid = db.add_user("user".to_string(), true).unwrap();
+        let aid = db
+            .add_account(
+                uid,
+                &AccountInfo {
+                    atype: AccountType::Investment,
+                    name: "Brokerage".to_string(),
+                    has_stocks: true,
+                    has_bank: true,
+                    has_ledger: true,
+                    has_budget: false,
+                },
+            )
+            .unwrap();
+        let pid = db
+            .check_and_add_participant(uid, aid, "ACME".to_string(), ParticipantType::Both, false)
+            .unwrap();
+        let cid = db.add_category(uid, aid, "TRADE".to_string()).unwrap();
+        let shares = Quantity::from_f64(10.0);
+        let record = |date: &str| {
+            let lid = db
+                .add_ledger_entry(
+                    uid,
+                    aid,
+                    LedgerInfo {
+                        date: date.to_string(),
+                        amount: Money::from_cents(10000),
+                        transfer_type: TransferType::WithdrawalToInternalAccount,
+                        participant: pid,
+                        category_id: cid,
+                        description: String::new(),
+                    },
+                )
+                .unwrap();
+            StockInfo {
+                shares,
+                costbasis: Quantity::from_f64(10.0),
+                remaining: shares,
+                ledger_id: lid,
+            }
+        };
+        let lots: Vec<u32> = ["2020-01-01", "2021-01-01", "2022-01-01"]
+            .iter()
+            .map(|date| db.add_stock_purchase(uid, aid, record(date)).unwrap())
+            .collect();
+
+        // the lots of each sale, in the order they were allocated
+        let cases = [
+            (vec![lots[2], lots[1]], SaleMethod::Lifo),
+            (vec![lots[0], lots[1]], SaleMethod::Fifo),
+            (vec![lots[0]], SaleMethod::Fifo),
+            (vec![lots[2]], SaleMethod::Lifo),
+            // neither the oldest nor the newest lot
+            (vec![lots[1]], SaleMethod::Fifo),
+            // no lots were recorded
+            (vec![], SaleMethod::Fifo),
+        ];
+        let mut sales = Vec::new();
+        for (allocated, expected) in cases.iter() {
+            let sale = db.add_stock_sale(uid, aid, record("2023-01-01")).unwrap();
+            // whatever the sale was labelled before is not trusted
+            db.set_stock_sale_method(uid, aid, sale, SaleMethod::Lifo)
+                .unwrap();
+            for lot in allocated {
+                db.add_stock_sale_allocation(uid, aid, *lot, sale, Quantity::from_f64(1.0))
+                    .unwrap();
+            }
+            sales.push((sale, *expected));
+        }
+
+        {
+            let mut conn_lock = db.conn.lock().unwrap();
+            let tx = conn_lock.transaction().unwrap();
+            derive_stock_sale_methods(&tx).unwrap();
+            tx.commit().unwrap();
+        }
+        for (sale, expected) in sales {
+            assert_eq!(
+                db.get_stock_sale_method(uid, aid, sale).unwrap(),
+                expected,
+                "sale {}",
+                sale
+            );
+        }
+    }
+}
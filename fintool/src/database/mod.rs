@@ -16,107 +16,159 @@
 -----------------------------------------------------------------------*/
 use chrono::NaiveDate;
 use rusqlite::functions::FunctionFlags;
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+pub mod archive;
+pub mod audit;
+pub mod backup;
 pub mod budget;
 pub mod db_cd;
-mod db_user;
-
-const CURRENT_DATABASE_SCHEMA_VERSION: i32 = 0;
+pub mod db_user;
+pub mod encryption;
+pub mod gains;
+pub mod journal;
+mod migrations;
+mod transaction;
+
+const CURRENT_DATABASE_SCHEMA_VERSION: i32 = migrations::MIGRATIONS.len() as i32;
 pub const SQLITE_WILDCARD: &str = "%";
 
 #[derive(Clone)]
 pub struct DbConn {
     pub conn: Arc<Mutex<Connection>>,
+    // kept for encrypted databases so that further connections, to the
+    // database or to its snapshots, can be keyed too
+    passphrase: Option<Arc<str>>,
+    // what was done to bring the schema up to date when the database was
+    // opened
+    upgrade_notes: Vec<String>,
 }
 
 impl DbConn {
     pub fn new(db_path: impl AsRef<Path>) -> Result<Self, rusqlite::Error> {
+        Self::open(db_path, None)
+    }
+
+    // Opens the database, encrypted with the passphrase if one is given. New
+    // databases created this way are encrypted from the start.
+    pub fn open(
+        db_path: impl AsRef<Path>,
+        passphrase: Option<&str>,
+    ) -> Result<Self, rusqlite::Error> {
         // the ? returns early if error, otherwise ok
-        let rs = Connection::open(db_path);
-        let conn;
-        match rs {
-            Ok(rs_conn) => {
-                conn = Self {
-                    conn: Arc::new(Mutex::new(rs_conn)),
-                };
-                conn.initialize_database();
-            }
-            Err(error) => {
-                panic!("unable to open db: {}", error)
-            }
+        let mut conn = Self {
+            conn: Arc::new(Mutex::new(encryption::open_keyed(
+                db_path.as_ref(),
+                OpenFlags::default(),
+                passphrase,
+            )?)),
+            passphrase: passphrase.map(Arc::from),
+            upgrade_notes: Vec::new(),
         };
+        conn.upgrade_notes = conn.initialize_database()?;
         Ok(conn)
     }
 
-    fn initialize_database(&self) -> Result<(), rusqlite::Error> {
-        Self::allow_foreign_keys(&self.conn.lock().unwrap());
-        Self::create_user_account_info_table(self);
-        Self::create_accounts_id_table(self);
-        Self::create_users_id_table(self);
-        Self::create_user_table(self);
-        Self::create_accounts_table(self);
-        Self::create_budget_categories_table(self);
-        Self::create_people_table(self);
-        Self::create_ledger_table(self);
-        Self::create_investment_purchase_table(self);
-        Self::create_investment_sale_table(self);
-        Self::create_investment_sale_allocation_table(self);
-        Self::create_cd_table(self);
-        Self::create_budget_table(self);
-        Self::create_account_transaction_table(self);
-        Self::create_stock_split_table(self);
-        Self::create_stock_split_allocation_table(self);
-        Self::create_credit_card_accounts_table(self);
-        Self::create_certificate_of_deposits_table(self);
-        Self::create_roth_ira_accounts_table(self);
-        Self::create_labels_table(self);
-        Self::create_label_allocations_table(self);
-        Self::create_hsa_accounts_table(self);
-        Self::create_401k_accounts_table(self);
-        Self::create_stock_prices_table(self);
-
-        let conn_lock = &self.conn.lock().unwrap();
-
-        Self::set_schema_version(conn_lock, CURRENT_DATABASE_SCHEMA_VERSION);
+    // Printing while the user interface is up would garble it, so the notes
+    // of any schema upgrade are left for the caller to show.
+    pub fn upgrade_notes(&self) -> &[String] {
+        &self.upgrade_notes
+    }
+
+    fn initialize_database(&self) -> Result<Vec<String>, rusqlite::Error> {
+        let is_new_database;
+        let mut upgrade_notes = Vec::new();
+        {
+            let conn_lock = self.conn.lock().unwrap();
+            is_new_database = Self::is_new_database(&conn_lock)?;
+            // existing databases are brought up to date before foreign keys
+            // are enforced, so that migrations are free to rebuild tables
+            if !is_new_database {
+                upgrade_notes = Self::migrate(&conn_lock, self.passphrase.as_deref())?;
+            }
+            Self::allow_foreign_keys(&conn_lock)?;
+        }
+        self.create_tables()?;
+
+        let conn_lock = self.conn.lock().unwrap();
+
+        if is_new_database {
+            Self::set_schema_version(&conn_lock, CURRENT_DATABASE_SCHEMA_VERSION)?;
+        }
 
         // register custom functions
-        let _result = conn_lock
-            .create_scalar_function(
-                "get_stock_value",
-                1,
-                FunctionFlags::SQLITE_INNOCUOUS,
-                |ctx| {
-                    let ticker: String = ctx.get(0)?;
-                    match shared_lib::stocks::get_stock_at_close(ticker) {
-                        Ok(price) => Ok(price),
-                        Err(e) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
-                    }
-                },
-            )
-            .unwrap();
-
-        let _result = conn_lock
-            .create_scalar_function(
-                "get_stock_value_on_day",
-                2,
-                FunctionFlags::SQLITE_DETERMINISTIC,
-                |ctx| {
-                    let ticker: String = ctx.get(0)?;
-                    let date: String = ctx.get(1)?;
-                    match shared_lib::stocks::get_stock_quote(
-                        ticker,
-                        NaiveDate::parse_from_str(date.as_str(), "%Y-%m-%d").unwrap(),
-                    ) {
-                        Ok(value) => Ok(value.close),
-                        Err(e) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
-                    }
-                },
-            )
-            .unwrap();
+        conn_lock.create_scalar_function(
+            "get_stock_value",
+            1,
+            FunctionFlags::SQLITE_INNOCUOUS,
+            |ctx| {
+                let ticker: String = ctx.get(0)?;
+                match shared_lib::stocks::get_stock_at_close(ticker) {
+                    Ok(price) => Ok(price),
+                    Err(e) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+                }
+            },
+        )?;
+
+        conn_lock.create_scalar_function(
+            "get_stock_value_on_day",
+            2,
+            FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| {
+                let ticker: String = ctx.get(0)?;
+                let date: String = ctx.get(1)?;
+                match shared_lib::stocks::get_stock_quote(
+                    ticker,
+                    NaiveDate::parse_from_str(date.as_str(), "%Y-%m-%d")
+                        .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e)))?,
+                ) {
+                    Ok(value) => Ok(value.close),
+                    Err(e) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+                }
+            },
+        )?;
+        drop(conn_lock);
+
+        self.install_audit_triggers()?;
+        self.install_access_triggers()?;
+
+        Ok(upgrade_notes)
+    }
 
+    fn create_tables(&self) -> Result<(), rusqlite::Error> {
+        self.create_user_account_info_table()?;
+        self.create_accounts_id_table()?;
+        self.create_users_id_table()?;
+        self.create_user_table()?;
+        self.create_accounts_table()?;
+        self.create_account_members_table()?;
+        self.create_budget_categories_table()?;
+        self.create_people_table()?;
+        self.create_ledger_table()?;
+        self.create_investment_purchase_table()?;
+        self.create_investment_sale_table()?;
+        self.create_investment_sale_allocation_table()?;
+        self.create_cd_table()?;
+        self.create_budget_table()?;
+        self.create_account_transaction_table()?;
+        self.create_stock_split_table()?;
+        self.create_stock_split_allocation_table()?;
+        self.create_stock_distributions_table()?;
+        self.create_stock_reinvestments_table()?;
+        self.create_credit_card_accounts_table()?;
+        self.create_certificate_of_deposits_table()?;
+        self.create_roth_ira_accounts_table()?;
+        self.create_labels_table()?;
+        self.create_label_allocations_table()?;
+        self.create_hsa_accounts_table()?;
+        self.create_401k_accounts_table()?;
+        self.create_stock_prices_table()?;
+        self.create_external_accounts_table()?;
+        self.create_external_transactions_table()?;
+        self.create_csv_mappings_table()?;
+        self.create_change_log_tables()?;
         Ok(())
     }
 
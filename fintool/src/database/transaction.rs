@@ -0,0 +1,141 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use rusqlite::Connection;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::DbConn;
+
+const SAVEPOINT_PREFIX: &str = "fintool_operation";
+
+// numbers the savepoints, so that each transaction releases or rolls back its
+// own however they nest
+static NEXT_SAVEPOINT: AtomicU64 = AtomicU64::new(0);
+
+// Groups every statement issued through the shared connection between
+// DbConn::begin_transaction and commit() into one unit. Dropping the guard
+// without committing (an early return, a `?` or a panic) rolls the unit back.
+// Savepoints are used rather than BEGIN so that operations can nest, e.g. a
+// withdrawal that links a deposit into another account.
+pub struct DbTransaction {
+    conn: Arc<Mutex<Connection>>,
+    savepoint: String,
+    committed: bool,
+}
+
+impl DbTransaction {
+    pub fn commit(mut self) -> rusqlite::Result<()> {
+        {
+            let conn_lock = self.conn.lock().unwrap();
+            conn_lock.execute_batch(&format!("RELEASE {}", self.savepoint))?;
+        }
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for DbTransaction {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        // a panic while a statement was running poisons the lock, but the
+        // connection itself is still usable and must not be left mid-transaction
+        let conn_lock = match self.conn.lock() {
+            Ok(conn_lock) => conn_lock,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let _ = conn_lock.execute_batch(&format!(
+            "ROLLBACK TO {savepoint}; RELEASE {savepoint}",
+            savepoint = self.savepoint
+        ));
+    }
+}
+
+impl DbConn {
+    pub fn begin_transaction(&self) -> rusqlite::Result<DbTransaction> {
+        let savepoint = format!(
+            "{}_{}",
+            SAVEPOINT_PREFIX,
+            NEXT_SAVEPOINT.fetch_add(1, Ordering::Relaxed)
+        );
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute_batch(&format!("SAVEPOINT {}", savepoint))?;
+        Ok(DbTransaction {
+            conn: self.conn.clone(),
+            savepoint,
+            committed: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users(db: &DbConn) -> Vec<String> {
+        db.get_users().unwrap()
+    }
+
+    #[test]
+    fn dropping_without_commit_rolls_back() {
+        let db = DbConn::new(":memory:").unwrap();
+        {
+            let _transaction = db.begin_transaction().unwrap();
+            db.add_user("dropped".to_string(), true).unwrap();
+        }
+        assert!(users(&db).is_empty());
+
+        let transaction = db.begin_transaction().unwrap();
+        db.add_user("kept".to_string(), true).unwrap();
+        transaction.commit().unwrap();
+        assert_eq!(users(&db), vec!["kept"]);
+    }
+
+    #[test]
+    fn nested_transactions_roll_back_their_own_changes() {
+        let db = DbConn::new(":memory:").unwrap();
+
+        // the inner one is undone, the outer one kept
+        let outer = db.begin_transaction().unwrap();
+        db.add_user("outer".to_string(), true).unwrap();
+        {
+            let _inner = db.begin_transaction().unwrap();
+            db.add_user("inner".to_string(), false).unwrap();
+        }
+        assert_eq!(users(&db), vec!["outer"]);
+        outer.commit().unwrap();
+        assert_eq!(users(&db), vec!["outer"]);
+
+        // a committed inner one is still undone with the outer one
+        {
+            let _outer = db.begin_transaction().unwrap();
+            let inner = db.begin_transaction().unwrap();
+            db.add_user("committed".to_string(), false).unwrap();
+            inner.commit().unwrap();
+        }
+        assert_eq!(users(&db), vec!["outer"]);
+
+        // an outer one dropped first takes the inner one with it
+        let outer = db.begin_transaction().unwrap();
+        let inner = db.begin_transaction().unwrap();
+        db.add_user("abandoned".to_string(), false).unwrap();
+        drop(outer);
+        assert!(inner.commit().is_err());
+        assert_eq!(users(&db), vec!["outer"]);
+    }
+}
@@ -0,0 +1,90 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use fin_import::ConvertError;
+use shared_lib::quotes::QuoteError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum FintoolError {
+    Database(rusqlite::Error),
+    Validation(String),
+    Import(String),
+    QuoteProvider(String),
+    Authentication(String),
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, FintoolError>;
+
+impl fmt::Display for FintoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FintoolError::Database(error) => write!(f, "Database error: {}", error),
+            FintoolError::Validation(msg) => write!(f, "Invalid input: {}", msg),
+            FintoolError::Import(msg) => write!(f, "Import failed: {}", msg),
+            FintoolError::QuoteProvider(msg) => write!(f, "Unable to retrieve quote: {}", msg),
+            FintoolError::Authentication(msg) => write!(f, "Login failed: {}", msg),
+            FintoolError::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for FintoolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FintoolError::Database(error) => Some(error),
+            FintoolError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for FintoolError {
+    fn from(error: rusqlite::Error) -> Self {
+        FintoolError::Database(error)
+    }
+}
+
+impl From<std::io::Error> for FintoolError {
+    fn from(error: std::io::Error) -> Self {
+        FintoolError::Io(error)
+    }
+}
+
+impl From<csv::Error> for FintoolError {
+    fn from(error: csv::Error) -> Self {
+        FintoolError::Import(error.to_string())
+    }
+}
+
+impl From<ConvertError> for FintoolError {
+    fn from(error: ConvertError) -> Self {
+        FintoolError::Import(error.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for FintoolError {
+    fn from(error: chrono::ParseError) -> Self {
+        FintoolError::Validation(error.to_string())
+    }
+}
+
+impl From<QuoteError> for FintoolError {
+    fn from(error: QuoteError) -> Self {
+        FintoolError::QuoteProvider(error.to_string())
+    }
+}
@@ -15,8 +15,6 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use chrono::{Datelike, Local, NaiveDate};
-#[cfg(not(debug_assertions))]
-use directories::ProjectDirs;
 #[cfg(feature = "ratatui_support")]
 use ratatui::{
     backend::{Backend, CrosstermBackend},
@@ -50,34 +48,101 @@ use crate::app::app::App;
 use crate::app::screen::{CurrentScreen, CurrentlySelecting, Pages, UserLoadedState};
 #[cfg(feature = "ratatui_support")]
 use crate::app::ui;
+use crate::config::Config;
+#[cfg(feature = "ratatui_support")]
+use crate::config::Profile;
+use crate::database::encryption::is_encrypted;
 use crate::database::DbConn;
+use crate::tui::tui_archive::{export_archive, import_archive};
+use crate::tui::tui_encryption::{decrypt_database, encrypt_database, open_database};
+use crate::tui::tui_gains::report_gains;
+use crate::tui::tui_history::{
+    redo_last_change, select_ledger_history, show_ledger_history, undo_last_change,
+};
+use crate::tui::tui_journal::export_journal;
 use crate::tui::tui_license::license_banner;
-use crate::tui::tui_user::create_user;
+use crate::tui::tui_sharing::manage_account_members;
+use crate::tui::tui_snapshots::restore_snapshot;
+use crate::tui::tui_user::{
+    authenticate_admin, create_user, manage_users, reset_password, set_initial_password,
+};
 use crate::tui::*;
+use crate::types::account_members::AccountAccess;
 use crate::types::accounts::AccountType;
 
 mod accounts;
 #[cfg(feature = "ratatui_support")]
 mod app;
+mod config;
 mod database;
+mod error;
 mod tui;
 mod types;
 
+// what to do with the selected database instead of opening it
+enum DatabaseCommand {
+    Encrypt,
+    Decrypt,
+}
+
 fn main() -> Result<(), std::io::Error> {
+    let (db_override, command) = parse_args();
+
+    let config_path = config::config_path();
+    let config = match Config::load(&config_path) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Unable to read {}: {}", config_path.display(), error);
+            std::process::exit(1);
+        }
+    };
+    let (profiles, selected_profile) = match config.database_profiles(db_override) {
+        Ok(profiles) => profiles,
+        Err(error) => {
+            eprintln!("Unable to select a database: {}", error);
+            std::process::exit(1);
+        }
+    };
+
     let mut _db: DbConn;
-    let db = db_path();
+    let db = profiles[selected_profile].path.clone();
+
+    if let Some(command) = command {
+        let result = match command {
+            DatabaseCommand::Encrypt => encrypt_database(&db),
+            DatabaseCommand::Decrypt => decrypt_database(&db),
+        };
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     match db.try_exists() {
         Ok(_) => {
             // nothing to do
-            _db = DbConn::new(db.clone()).unwrap();
+            _db = match open_database(&db) {
+                Ok(conn) => conn,
+                Err(error) => {
+                    eprintln!("Unable to open database {}: {}", db.display(), error);
+                    std::process::exit(1);
+                }
+            };
         }
         Err(_) => {
             panic!("Unable to verify existence of the database!");
         }
     }
 
+    if let Err(error) = config.install_quote_provider(&_db) {
+        eprintln!("Unable to apply {}: {}", config_path.display(), error);
+        std::process::exit(1);
+    }
+    accounts::base::import::set_duplicate_window(config.duplicate_window_days);
+
     #[cfg(feature = "ratatui_support")]
-    init_and_run_app(&mut _db)?;
+    init_and_run_app(&mut _db, config, profiles, selected_profile)?;
 
     #[cfg(not(feature = "ratatui_support"))]
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
@@ -89,30 +154,74 @@ fn main() -> Result<(), std::io::Error> {
     Ok(())
 }
 
-fn db_path() -> PathBuf {
-    #[cfg(debug_assertions)]
-    {
-        // Dev/debug mode → keep database in repo
-        let db_dir = Path::new("db");
-        std::fs::create_dir_all(db_dir).expect("Failed to create dev db directory");
-        return db_dir.join("finances.db");
-    }
+const USAGE: &str = "Usage: fintool [--db <path>] [--encrypt | --decrypt]
+
+Options:
+  --db <path>    open the database at <path> instead of the default profile
+  --encrypt      encrypt the database and its snapshots with a passphrase, or
+                 create it encrypted if it does not exist, then exit
+  --decrypt      store the database and its snapshots unencrypted, then exit
+  -h, --help     print this message
 
-    #[cfg(not(debug_assertions))]
-    {
-        // Release mode → packaged app database
-        if let Some(proj_dirs) = ProjectDirs::from("com", "aeberhard21", "Fintool") {
-            let data_dir = proj_dirs.data_dir(); // ~/Library/Application Support/com.aeberhard21.Fintool
-            std::fs::create_dir_all(data_dir).expect("Failed to create data directory");
-            return data_dir.join("finances.db");
-        } else {
-            panic!("Unable to locate application directory!");
+The FINTOOL_DB environment variable may be used in place of --db.";
+
+// returns the database path given on the command line, if any, and the
+// command to run on the database
+fn parse_args() -> (Option<PathBuf>, Option<DatabaseCommand>) {
+    let mut db = None;
+    let mut command = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--encrypt" | "--decrypt" => {
+                if command.is_some() {
+                    eprintln!("--encrypt and --decrypt may not be combined\n\n{}", USAGE);
+                    std::process::exit(2);
+                }
+                command = Some(match arg.as_str() {
+                    "--encrypt" => DatabaseCommand::Encrypt,
+                    _ => DatabaseCommand::Decrypt,
+                });
+            }
+            "--db" => match args.next() {
+                Some(path) => db = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("--db requires a path\n\n{}", USAGE);
+                    std::process::exit(2);
+                }
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => match arg.strip_prefix("--db=") {
+                Some(path) => db = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("Unrecognized argument '{}'\n\n{}", arg, USAGE);
+                    std::process::exit(2);
+                }
+            },
         }
     }
+    (db, command)
 }
 
 #[cfg(feature = "ratatui_support")]
-fn init_and_run_app(_db: &mut DbConn) -> io::Result<bool> {
+fn init_and_run_app(
+    _db: &mut DbConn,
+    config: Config,
+    profiles: Vec<Profile>,
+    selected_profile: usize,
+) -> io::Result<bool> {
+    // hand the terminal back before reporting a panic so that the message is
+    // readable and the shell is not left in raw mode
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+        default_hook(info);
+    }));
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -121,7 +230,7 @@ fn init_and_run_app(_db: &mut DbConn) -> io::Result<bool> {
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
-    let mut app = App::new(_db);
+    let mut app = App::new(_db, config, profiles, selected_profile);
     let res = run_app(&mut terminal, &mut app)?;
 
     // restore terminal
@@ -154,6 +263,7 @@ where
         }
 
         if let UserLoadedState::Loading = app.user_load_state {
+            app.db.set_acting_user(app.user_id).unwrap();
             while app.user_load_state != UserLoadedState::Loaded {
                 let mut profiles_loaded = 0;
                 let account_records = app.db.get_user_accounts(app.user_id.unwrap()).unwrap();
@@ -168,18 +278,34 @@ where
                 }
 
                 let mut accounts = Vec::new();
-                for record in account_records {
-                    let acct = decode_and_init_account_type(app.user_id.unwrap(), &app.db, &record);
+                let mut loaded_records = Vec::new();
+                for record in account_records.into_iter() {
+                    let acct = decode_and_init_account_type(&app.db, &record);
                     profiles_loaded = profiles_loaded + 1;
                     app.load_profile_progress =
                         (profiles_loaded as f64 / number_of_accounts as f64);
 
                     // force update to progress bar
                     terminal.draw(|f| ui::ui(f, app))?;
-                    accounts.push(acct);
+                    // an account that cannot be opened, e.g. one holding
+                    // stocks without cached quotes while offline, is left
+                    // out rather than keeping the others from loading
+                    match acct {
+                        Ok(acct) => {
+                            accounts.push(acct);
+                            loaded_records.push(record);
+                        }
+                        Err(error) => {
+                            app.error_message = Some(format!(
+                                "Unable to load account {}: {}",
+                                record.info.name, error
+                            ));
+                        }
+                    }
                 }
                 // app.accounts = Some(accounts);
                 app.accounts = accounts;
+                app.account_records = loaded_records;
 
                 terminal.draw(|f: &mut ratatui::Frame<'_>| ui::ui(f, app))?;
 
@@ -197,6 +323,10 @@ where
                 // Skip events that are not KeyEventKind::Press
                 continue;
             }
+            if app.error_message.take().is_some() || app.notice_message.take().is_some() {
+                // the key press only dismisses the message
+                continue;
+            }
             match app.current_screen {
                 CurrentScreen::Login => match (key.modifiers, key.code) {
                     (_, KeyCode::Enter) => {
@@ -204,14 +334,54 @@ where
                             app.display_license_conditions = false;
                             app.display_license_warranty = false;
                         } else {
-                            if let Some(id) = app.validate_user(app.key_input.to_string()) {
-                                app.user_id = Some(id);
-                                app.user_load_state = UserLoadedState::Loading;
+                            let input = std::mem::take(&mut app.key_input);
+                            if let Some(name) = app.login_name.take() {
+                                // every name is asked for a password, so that
+                                // the prompt reveals neither which names exist
+                                // nor which users have yet to choose one
+                                match app.passwordless_user(name.clone()) {
+                                    Ok(Some(id)) => {
+                                        suspend_tui(terminal)?;
+
+                                        match set_initial_password(&app.db, id, &name) {
+                                            Ok(()) => {
+                                                app.user_id = Some(id);
+                                                app.user_load_state = UserLoadedState::Loading;
+                                            }
+                                            Err(error) => {
+                                                app.error_message = Some(error.to_string());
+                                            }
+                                        }
+
+                                        resume_tui(terminal)?
+                                    }
+                                    Ok(None) => match app.db.authenticate(&name, &input) {
+                                        Ok(id) => {
+                                            app.user_id = Some(id);
+                                            app.user_load_state = UserLoadedState::Loading;
+                                        }
+                                        Err(error) => {
+                                            app.error_message = Some(error.to_string());
+                                        }
+                                    },
+                                    Err(error) => {
+                                        app.error_message = Some(error.to_string());
+                                    }
+                                }
                             } else {
-                                app.invalid_input = true;
+                                app.login_name = Some(input);
                             }
                         }
                     }
+                    (_, KeyCode::Esc) => {
+                        // back to the user name
+                        app.login_name = None;
+                        app.key_input = String::new();
+                    }
+                    (_, KeyCode::Char(value)) if app.login_name.is_some() => {
+                        // passwords may contain any character, including shortcuts
+                        app.key_input.push(value);
+                    }
                     (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => {
                         suspend_tui(terminal)?;
                         return Ok(true);
@@ -222,10 +392,70 @@ where
                     (KeyModifiers::CONTROL, KeyCode::Char('w')) => {
                         app.display_license_warranty = true;
                     }
+                    (KeyModifiers::CONTROL, KeyCode::Char('p')) => {
+                        // the passphrase of an encrypted database is asked for
+                        // on the terminal
+                        let prompt = app
+                            .next_profile()
+                            .is_some_and(|profile| is_encrypted(&profile.path).unwrap_or(false));
+                        if prompt {
+                            suspend_tui(terminal)?;
+                        }
+                        if let Err(error) = app.switch_profile() {
+                            app.error_message =
+                                Some(format!("Unable to switch databases: {}", error));
+                        }
+                        if prompt {
+                            resume_tui(terminal)?
+                        }
+                    }
+                    (KeyModifiers::CONTROL, KeyCode::Char('r')) => {
+                        suspend_tui(terminal)?;
+
+                        match authenticate_admin(&app.db) {
+                            Ok(true) => {
+                                if let Err(error) = restore_snapshot(&app.db) {
+                                    app.error_message =
+                                        Some(format!("Unable to restore snapshot: {}", error));
+                                }
+                            }
+                            Ok(false) => {
+                                app.error_message = Some(
+                                    "Only an administrator may restore a snapshot.".to_string(),
+                                );
+                            }
+                            Err(error) => {
+                                app.error_message = Some(error.to_string());
+                            }
+                        }
+
+                        resume_tui(terminal)?
+                    }
+                    (KeyModifiers::CONTROL, KeyCode::Char('u')) => {
+                        suspend_tui(terminal)?;
+
+                        match authenticate_admin(&app.db) {
+                            Ok(true) => {
+                                if let Err(error) = manage_users(&app.db) {
+                                    app.error_message =
+                                        Some(format!("Unable to manage users: {}", error));
+                                }
+                            }
+                            Ok(false) => {
+                                app.error_message =
+                                    Some("Only an administrator may manage users.".to_string());
+                            }
+                            Err(error) => {
+                                app.error_message = Some(error.to_string());
+                            }
+                        }
+
+                        resume_tui(terminal)?
+                    }
                     (_, KeyCode::Char(':')) => {
                         suspend_tui(terminal)?;
 
-                        create_user(&mut app.db);
+                        create_user(&mut app.db, false);
 
                         resume_tui(terminal)?
                     }
@@ -254,8 +484,10 @@ where
                         app.account_index_to_restore = 0;
                         app.currently_selected = Some(CurrentlySelecting::MainTabs);
                         app.user_id = None;
+                        app.db.set_acting_user(None).unwrap();
                         app.account = None;
                         app.accounts = Vec::new();
+                        app.account_records = Vec::new();
                         app.analysis_period = accounts::base::AnalysisPeriod::YTD;
                         app.analysis_start =
                             NaiveDate::from_ymd_opt(Local::now().year(), 1, 1).unwrap();
@@ -277,39 +509,13 @@ where
                                 // get accounts for filter
                                 app.restore_account();
                                 app.advance_account_type();
-                                app.accounts_for_type = if !app.accounts.is_empty() {
-                                    let filtered_accounts = app
-                                        .accounts
-                                        .iter()
-                                        .filter(|x| is_account_type(x, app.selected_atype_tab))
-                                        .collect::<Vec<&Box<dyn Account>>>();
-                                    let names = filtered_accounts
-                                        .iter()
-                                        .map(|x| x.get_name())
-                                        .collect::<Vec<String>>();
-                                    names
-                                } else {
-                                    Vec::new()
-                                };
+                                app.refresh_accounts_for_type();
                                 app.get_account();
                             }
                             Some(CurrentlySelecting::MainTabs) => {
                                 // moving to accounts tab
                                 if let Pages::Main = app.selected_page_tab {
-                                    app.accounts_for_type = if !app.accounts.is_empty() {
-                                        let filtered_accounts = app
-                                            .accounts
-                                            .iter()
-                                            .filter(|x| is_account_type(x, app.selected_atype_tab))
-                                            .collect::<Vec<&Box<dyn Account>>>();
-                                        let names = filtered_accounts
-                                            .iter()
-                                            .map(|x| x.get_name())
-                                            .collect::<Vec<String>>();
-                                        names
-                                    } else {
-                                        Vec::new()
-                                    };
+                                    app.refresh_accounts_for_type();
                                     app.get_account();
                                 }
                                 app.advance_page_tab();
@@ -326,20 +532,7 @@ where
                         Some(CurrentlySelecting::AccountTypeTabs) => {
                             app.restore_account();
                             app.retreat_account_type();
-                            app.accounts_for_type = if !app.accounts.is_empty() {
-                                let filtered_accounts = app
-                                    .accounts
-                                    .iter()
-                                    .filter(|x| is_account_type(x, app.selected_atype_tab))
-                                    .collect::<Vec<&Box<dyn Account>>>();
-                                let names = filtered_accounts
-                                    .iter()
-                                    .map(|x| x.get_name())
-                                    .collect::<Vec<String>>();
-                                names
-                            } else {
-                                Vec::new()
-                            };
+                            app.refresh_accounts_for_type();
                             app.get_account();
                         }
                         Some(CurrentlySelecting::MainTabs) => {
@@ -354,20 +547,7 @@ where
                                 CurrentlySelecting::AccountTabs => {
                                     app.restore_account();
                                     app.retreat_currently_selecting();
-                                    app.accounts_for_type = if !app.accounts.is_empty() {
-                                        let filtered_accounts = app
-                                            .accounts
-                                            .iter()
-                                            .filter(|x| is_account_type(x, app.selected_atype_tab))
-                                            .collect::<Vec<&Box<dyn Account>>>();
-                                        let names = filtered_accounts
-                                            .iter()
-                                            .map(|x| x.get_name())
-                                            .collect::<Vec<String>>();
-                                        names
-                                    } else {
-                                        Vec::new()
-                                    };
+                                    app.refresh_accounts_for_type();
                                     app.get_account();
                                     // upon move out of account type, reset default tab to the first one
                                     app.selected_account_tab = 0;
@@ -402,6 +582,22 @@ where
                             }
                         }
                     }
+                    (_, KeyCode::Char('m') | KeyCode::Char('r') | KeyCode::Char('i'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::Account))
+                            && app.selected_account_access() == Some(AccountAccess::Read) =>
+                    {
+                        app.error_message =
+                            Some("You have read-only access to this account.".to_string());
+                    }
+                    (_, KeyCode::Char('e'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::Account))
+                            && app
+                                .selected_account_access()
+                                .is_some_and(|access| access != AccountAccess::Owner) =>
+                    {
+                        app.error_message =
+                            Some("Only the owner may rename a shared account.".to_string());
+                    }
                     (_, KeyCode::Char('c')) => {
                         if let Some(select_mode) = &app.currently_selected {
                             match select_mode {
@@ -409,33 +605,44 @@ where
                                 | CurrentlySelecting::AccountTabs => {
                                     app.restore_account();
 
-                                    suspend_tui(terminal)?;
+                                    let _changes = match app
+                                        .db
+                                        .begin_change_set(app.user_id.unwrap(), "Create account")
+                                    {
+                                        Ok(changes) => changes,
+                                        Err(error) => {
+                                            app.error_message = Some(format!(
+                                                "Unable to create account: {}",
+                                                error
+                                            ));
+                                            continue;
+                                        }
+                                    };
 
+                                    suspend_tui(terminal)?;
                                     let new_account = create_account(
                                         app.user_id.unwrap(),
                                         app.selected_atype_tab,
                                         &app.db,
                                     );
 
-                                    let account = new_account.unwrap();
-                                    app.accounts.push(account.0);
-
                                     resume_tui(terminal)?;
 
-                                    app.accounts_for_type = if !app.accounts.is_empty() {
-                                        let filtered_accounts = app
-                                            .accounts
-                                            .iter()
-                                            .filter(|x| is_account_type(x, app.selected_atype_tab))
-                                            .collect::<Vec<&Box<dyn Account>>>();
-                                        let names = filtered_accounts
-                                            .iter()
-                                            .map(|x| x.get_name())
-                                            .collect::<Vec<String>>();
-                                        names
-                                    } else {
-                                        Vec::new()
-                                    };
+                                    match new_account {
+                                        Ok((account, record)) => {
+                                            app.accounts.push(account);
+                                            app.account_records.push(record);
+                                        }
+                                        Err(error) => {
+                                            app.error_message = Some(format!(
+                                                "Unable to create account: {}",
+                                                error
+                                            ));
+                                            continue;
+                                        }
+                                    }
+
+                                    app.refresh_accounts_for_type();
 
                                     app.skip_to_last_account();
                                     app.get_account();
@@ -452,25 +659,32 @@ where
                                     suspend_tui(terminal)?;
 
                                     if let Some(acct) = &app.account {
-                                        rename_account(
+                                        let uid = app.user_id.unwrap();
+                                        let renamed = begin_account_change_set(
                                             &app.db,
-                                            app.user_id.unwrap(),
-                                            acct.get_id(),
-                                        );
+                                            uid,
+                                            "Rename",
+                                            acct.as_ref(),
+                                        )
+                                        .and_then(|_changes| {
+                                            rename_account(&app.db, uid, acct.get_id())
+                                        });
+                                        if let Err(error) = renamed {
+                                            app.error_message = Some(format!(
+                                                "Unable to rename account: {}",
+                                                error
+                                            ));
+                                        }
                                     } else {
                                         // no account selected so just ignore keystroke.
                                         continue;
                                     }
 
-                                    // update accounts for type
-                                    app.accounts_for_type = app
-                                        .db
-                                        .get_user_accounts_by_type(
-                                            app.user_id.unwrap(),
-                                            app.selected_atype_tab,
-                                        )
-                                        .unwrap()
-                                        .unwrap();
+                                    // update accounts for type, which include those
+                                    // shared with the user
+                                    app.restore_account();
+                                    app.refresh_accounts_for_type();
+                                    app.get_account();
 
                                     resume_tui(terminal)?;
                                 }
@@ -478,6 +692,97 @@ where
                             }
                         }
                     }
+                    (_, KeyCode::Char('p'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::MainTabs)) =>
+                    {
+                        let uid = app.user_id.unwrap();
+                        if app.db.is_admin(uid).unwrap() {
+                            suspend_tui(terminal)?;
+
+                            if let Err(error) = reset_password(&app.db) {
+                                app.error_message =
+                                    Some(format!("Unable to reset password: {}", error));
+                            }
+
+                            resume_tui(terminal)?;
+                        } else {
+                            app.error_message =
+                                Some("Only an administrator may reset passwords.".to_string());
+                        }
+                    }
+                    (_, KeyCode::Char('x'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::MainTabs)) =>
+                    {
+                        suspend_tui(terminal)?;
+
+                        if let Err(error) = export_journal(&app.db, app.user_id.unwrap()) {
+                            app.error_message =
+                                Some(format!("Unable to export journal: {}", error));
+                        }
+
+                        resume_tui(terminal)?;
+                    }
+                    (_, KeyCode::Char('g'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::MainTabs)) =>
+                    {
+                        suspend_tui(terminal)?;
+
+                        if let Err(error) = report_gains(&app.db, app.user_id.unwrap(), None) {
+                            app.error_message =
+                                Some(format!("Unable to report capital gains: {}", error));
+                        }
+
+                        resume_tui(terminal)?;
+                    }
+                    (_, KeyCode::Char('z'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::MainTabs)) =>
+                    {
+                        suspend_tui(terminal)?;
+
+                        if let Err(error) = export_archive(&app.db, app.user_id.unwrap()) {
+                            app.error_message =
+                                Some(format!("Unable to export archive: {}", error));
+                        }
+
+                        resume_tui(terminal)?;
+                    }
+                    (_, KeyCode::Char('Z'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::MainTabs)) =>
+                    {
+                        let uid = app.user_id.unwrap();
+                        if app.db.is_admin(uid).unwrap() {
+                            suspend_tui(terminal)?;
+
+                            if let Err(error) = import_archive(&app.db) {
+                                app.error_message =
+                                    Some(format!("Unable to import archive: {}", error));
+                            }
+
+                            resume_tui(terminal)?;
+                        } else {
+                            app.error_message =
+                                Some("Only an administrator may import archives.".to_string());
+                        }
+                    }
+                    (_, KeyCode::Char('s'))
+                        if matches!(app.currently_selected, Some(CurrentlySelecting::Account)) =>
+                    {
+                        if app.selected_account_access() == Some(AccountAccess::Owner) {
+                            let acct = app.account.as_ref().unwrap();
+                            let (uid, aid) = (acct.get_uid(), acct.get_id());
+                            suspend_tui(terminal)?;
+
+                            if let Err(error) = manage_account_members(&app.db, uid, aid) {
+                                app.error_message =
+                                    Some(format!("Unable to share account: {}", error));
+                            }
+
+                            resume_tui(terminal)?;
+                        } else {
+                            app.error_message =
+                                Some("Only the owner may share an account.".to_string());
+                        }
+                    }
                     (_, KeyCode::Char('m')) => {
                         // modify ledger
                         if let Some(select_mode) = &app.currently_selected {
@@ -487,7 +792,15 @@ where
                                         suspend_tui(terminal)?;
 
                                         if let Some(uid) = app.user_id {
-                                            modify_labels(uid, &app.db);
+                                            match app.db.begin_change_set(uid, "Modify labels") {
+                                                Ok(_changes) => modify_labels(uid, &app.db),
+                                                Err(error) => {
+                                                    app.error_message = Some(format!(
+                                                        "Unable to modify labels: {}",
+                                                        error
+                                                    ))
+                                                }
+                                            }
                                         } else {
                                             panic!("Unable to unwrap user ID!");
                                         }
@@ -499,7 +812,20 @@ where
                                     suspend_tui(terminal)?;
 
                                     if let Some(acct) = &mut app.account {
-                                        acct.modify();
+                                        let uid = app.user_id.unwrap();
+                                        let modified = begin_account_change_set(
+                                            &app.db,
+                                            uid,
+                                            "Modify",
+                                            acct.as_ref(),
+                                        )
+                                        .and_then(|_changes| acct.modify());
+                                        if let Err(error) = modified {
+                                            app.error_message = Some(format!(
+                                                "Unable to modify account: {}",
+                                                error
+                                            ));
+                                        }
                                     } else {
                                         app.invalid_input = true;
                                     }
@@ -518,7 +844,20 @@ where
                                     suspend_tui(terminal)?;
 
                                     if let Some(acct) = &mut app.account {
-                                        acct.record();
+                                        let uid = app.user_id.unwrap();
+                                        let recorded = begin_account_change_set(
+                                            &app.db,
+                                            uid,
+                                            "Record",
+                                            acct.as_ref(),
+                                        )
+                                        .and_then(|_changes| acct.record());
+                                        if let Err(error) = recorded {
+                                            app.error_message = Some(format!(
+                                                "Unable to record transaction: {}",
+                                                error
+                                            ));
+                                        }
                                     } else {
                                         app.invalid_input = true;
                                     }
@@ -538,7 +877,22 @@ where
                                     suspend_tui(terminal)?;
 
                                     if let Some(acct) = &mut app.account {
-                                        acct.import();
+                                        let uid = app.user_id.unwrap();
+                                        let imported = begin_account_change_set(
+                                            &app.db,
+                                            uid,
+                                            "Import",
+                                            acct.as_ref(),
+                                        )
+                                        .and_then(|_changes| {
+                                            app.db.snapshot("import").and_then(|_| acct.import())
+                                        });
+                                        if let Err(error) = imported {
+                                            app.error_message = Some(format!(
+                                                "Unable to import transactions: {}",
+                                                error
+                                            ));
+                                        }
                                     } else {
                                         app.invalid_input = true;
                                     }
@@ -550,6 +904,73 @@ where
                             }
                         }
                     }
+                    (_, KeyCode::Char('u')) => {
+                        // undo the last change
+                        match undo_last_change(&app.db, app.user_id.unwrap()) {
+                            Ok(Some(_)) => app.update_account(),
+                            Ok(None) => app.error_message = Some("Nothing to undo.".to_string()),
+                            Err(error) => {
+                                app.error_message = Some(format!("Unable to undo: {}", error))
+                            }
+                        }
+                    }
+                    (KeyModifiers::SHIFT, KeyCode::Char('U')) => {
+                        // redo the last undone change
+                        match redo_last_change(&app.db, app.user_id.unwrap()) {
+                            Ok(Some(_)) => app.update_account(),
+                            Ok(None) => app.error_message = Some("Nothing to redo.".to_string()),
+                            Err(error) => {
+                                app.error_message = Some(format!("Unable to redo: {}", error))
+                            }
+                        }
+                    }
+                    (_, KeyCode::Char('y')) => {
+                        // history of the selected ledger entry
+                        if let Some(CurrentlySelecting::Account) = &app.currently_selected {
+                            let entry = match (&app.account, app.ledger_table_state.selected()) {
+                                (Some(acct), Some(row)) => app
+                                    .ledger_entries
+                                    .as_ref()
+                                    .and_then(|entries| entries.get(row))
+                                    .map(|entry| (acct.get_uid(), acct.get_id(), entry.id.clone())),
+                                _ => None,
+                            };
+                            if let Some((uid, aid, lid)) = entry {
+                                suspend_tui(terminal)?;
+
+                                if let Err(error) = show_ledger_history(
+                                    &app.db,
+                                    uid,
+                                    aid,
+                                    lid.parse::<u32>().expect("Invalid ledger id!"),
+                                ) {
+                                    app.error_message =
+                                        Some(format!("Unable to show history: {}", error));
+                                }
+
+                                resume_tui(terminal)?;
+                            } else if let (Some(acct), Some(entries)) =
+                                (&app.account, app.ledger_entries.clone())
+                            {
+                                // no entry is selected, so ask which one
+                                suspend_tui(terminal)?;
+
+                                if let Err(error) = select_ledger_history(
+                                    &app.db,
+                                    acct.get_uid(),
+                                    acct.get_id(),
+                                    entries,
+                                ) {
+                                    app.error_message =
+                                        Some(format!("Unable to show history: {}", error));
+                                }
+
+                                resume_tui(terminal)?;
+                            } else {
+                                app.invalid_input = true;
+                            }
+                        }
+                    }
                     (_, KeyCode::Char('a')) => {
                         // analysis period
                         suspend_tui(terminal)?;
@@ -34,9 +34,17 @@ use crate::accounts::investment_account_manager::InvestmentAccountManager;
 use crate::accounts::retirement_401k_plan::Retirement401kPlan;
 use crate::accounts::roth_ira::RothIraAccount;
 use crate::accounts::wallet::Wallet;
+use crate::database::audit::ChangeSetGuard;
 use crate::database::DbConn;
+use crate::error::{FintoolError, Result};
+use crate::tui::tui_archive::{export_archive, import_archive};
+use crate::tui::tui_gains::report_gains;
+use crate::tui::tui_history::*;
+use crate::tui::tui_journal::export_journal;
 use crate::tui::tui_license::get_gnu_gpl_conditions;
 use crate::tui::tui_license::get_gnu_gpl_warranty;
+use crate::tui::tui_sharing::*;
+use crate::tui::tui_snapshots::restore_snapshot;
 use crate::tui::tui_user::*;
 use crate::types::accounts::AccountType;
 use crate::types::accounts::*;
@@ -45,7 +53,14 @@ use chrono::NaiveDate;
 use inquire::*;
 
 pub mod tui_accounts;
+pub mod tui_archive;
+pub mod tui_encryption;
+pub mod tui_gains;
+pub mod tui_history;
+pub mod tui_journal;
 pub mod tui_license;
+pub mod tui_sharing;
+pub mod tui_snapshots;
 pub mod tui_user;
 
 pub fn menu(_db: &mut DbConn) {
@@ -59,8 +74,17 @@ pub fn menu(_db: &mut DbConn) {
         menu_options = vec![
             "Create User",
             "Change User",
+            "Reset Password",
+            "Manage Users",
             "Access Account(s)",
             "Modify Labels",
+            "Report Capital Gains",
+            "Export Journal",
+            "Export Archive",
+            "Import Archive",
+            "Undo",
+            "Redo",
+            "Restore Snapshot",
             "Show Conditions",
             "Show Warranty",
             "Exit",
@@ -70,6 +94,11 @@ pub fn menu(_db: &mut DbConn) {
             "Change User",
             "Access Account(s)",
             "Modify Labels",
+            "Report Capital Gains",
+            "Export Journal",
+            "Export Archive",
+            "Undo",
+            "Redo",
             "Show Conditions",
             "Show Warranty",
             "Exit",
@@ -86,17 +115,74 @@ pub fn menu(_db: &mut DbConn) {
 
         match command.as_str() {
             "Create User" => {
-                create_user(_db);
+                create_user(_db, true);
+            }
+            "Reset Password" => {
+                if let Err(error) = reset_password(_db) {
+                    println!("Unable to reset password: {}", error);
+                }
             }
             "Change User" => {
                 uid = tui_set_user(_db);
             }
+            "Manage Users" => {
+                if let Err(error) = manage_users(_db) {
+                    println!("Unable to manage users: {}", error);
+                }
+                // the administrator may have deleted themselves
+                if _db.get_user_name(uid).is_err() {
+                    uid = tui_set_user(_db);
+                }
+            }
             "Access Account(s)" => {
-                access_account(uid, _db);
+                if let Err(error) = access_account(uid, _db) {
+                    println!("Unable to access account: {}", error);
+                }
+            }
+            "Modify Labels" => match _db.begin_change_set(uid, "Modify labels") {
+                Ok(_changes) => modify_labels(uid, _db),
+                Err(error) => println!("Unable to modify labels: {}", error),
+            },
+            "Report Capital Gains" => {
+                if let Err(error) = report_gains(_db, uid, None) {
+                    println!("Unable to report capital gains: {}", error);
+                }
+            }
+            "Export Journal" => {
+                if let Err(error) = export_journal(_db, uid) {
+                    println!("Unable to export journal: {}", error);
+                }
             }
-            "Modify Labels" => {
-                modify_labels(uid, _db);
+            "Export Archive" => {
+                if let Err(error) = export_archive(_db, uid) {
+                    println!("Unable to export archive: {}", error);
+                }
             }
+            "Import Archive" => {
+                if let Err(error) = import_archive(_db) {
+                    println!("Unable to import archive: {}", error);
+                }
+            }
+            "Undo" => match undo_last_change(_db, uid) {
+                Ok(Some(message)) => println!("{}", message),
+                Ok(None) => println!("Nothing to undo."),
+                Err(error) => println!("Unable to undo: {}", error),
+            },
+            "Redo" => match redo_last_change(_db, uid) {
+                Ok(Some(message)) => println!("{}", message),
+                Ok(None) => println!("Nothing to redo."),
+                Err(error) => println!("Unable to redo: {}", error),
+            },
+            "Restore Snapshot" => match restore_snapshot(_db) {
+                // the selected user may not exist in the restored database
+                Ok(true) => {
+                    uid = tui_set_user(_db);
+                }
+                Ok(false) => {}
+                Err(error) => {
+                    println!("Unable to restore snapshot: {}", error);
+                }
+            },
             "Show Conditions" => {
                 let conditions = get_gnu_gpl_conditions();
                 println!("\n\n{}\n\n", conditions);
@@ -116,15 +202,19 @@ pub fn menu(_db: &mut DbConn) {
     }
 }
 
-fn access_account(uid: u32, db: &mut DbConn) {
+fn access_account(uid: u32, db: &mut DbConn) -> Result<()> {
     const ACCOUNT_OPTIONS: [&'static str; 4] =
         ["Create Account", "Select Account", "Edit Account", "Exit"];
-    let mut accounts: Vec<AccountRecord> = db.get_user_accounts(uid).unwrap();
+    let mut accounts: Vec<AccountRecord> = db.get_user_accounts(uid)?;
     let mut acct: Box<dyn Account>;
     let mut choice;
     let mut new_account;
-    const ACCT_ACTIONS: [&'static str; 6] =
-        ["Import", "Export", "Modify", "Record", "Report", "None"];
+    const ACCT_ACTIONS: [&'static str; 7] = [
+        "Import", "Export", "Modify", "Record", "Report", "History", "None",
+    ];
+    // for members who may only look at an account shared with them
+    const READ_ONLY_ACCT_ACTIONS: [&'static str; 4] = ["Export", "Report", "History", "None"];
+    let mut access;
 
     let mut accounts_is_empty = accounts.is_empty();
 
@@ -140,13 +230,21 @@ fn access_account(uid: u32, db: &mut DbConn) {
 
         match choice.as_str() {
             "Create Account" => {
-                let user_input = prompt_and_create_new_account(uid, db);
+                let _changes = db.begin_change_set(uid, "Create account")?;
+                let user_input = match prompt_and_create_new_account(uid, db) {
+                    Ok(user_input) => user_input,
+                    Err(error) => {
+                        println!("Unable to create account: {}", error);
+                        None
+                    }
+                };
                 if user_input.is_none() && accounts_is_empty {
                     break;
                 } else if user_input.is_none() {
                     continue;
                 }
                 (acct, new_account) = user_input.unwrap();
+                access = new_account.access;
                 accounts.push(new_account);
                 accounts_is_empty = false;
                 // acct.record();
@@ -155,19 +253,20 @@ fn access_account(uid: u32, db: &mut DbConn) {
                     .prompt()
                     .unwrap()
                     .to_string();
-                match selected_menu_item.as_str() {
-                    "Record" => {
-                        acct.record();
-                    }
-                    "Import" => {
-                        acct.import();
-                    }
+                let _changes =
+                    begin_account_change_set(db, uid, &selected_menu_item, acct.as_ref())?;
+                let result = match selected_menu_item.as_str() {
+                    "Record" => acct.record(),
+                    "Import" => db.snapshot("import").and_then(|_| acct.import()),
                     "None" => {
                         continue;
                     }
                     _ => {
                         panic!("Invalid menu option!");
                     }
+                };
+                if let Err(error) = result {
+                    println!("Unable to complete action: {}", error);
                 }
 
                 let more = Confirm::new("More actions?").prompt().unwrap();
@@ -179,8 +278,9 @@ fn access_account(uid: u32, db: &mut DbConn) {
                 let mut account_map: HashMap<String, AccountRecord> = HashMap::new();
                 let mut account_names: Vec<String> = Vec::new();
                 for account in accounts.iter() {
-                    account_names.push(account.info.name.clone());
-                    account_map.insert(account.info.name.clone(), account.clone());
+                    let label = account_label(db, account);
+                    account_names.push(label.clone());
+                    account_map.insert(label, account.clone());
                 }
 
                 // add none clause
@@ -197,14 +297,17 @@ fn access_account(uid: u32, db: &mut DbConn) {
                 let acctx = account_map
                     .get(&selected_account)
                     .expect("Account not found!");
-                acct = decode_and_init_account_type(uid, db, acctx);
+                acct = decode_and_init_account_type(db, acctx)?;
+                access = acctx.access;
                 // acct.info();
             }
             "Edit Account" => {
-                const MODIFY_ACCT_ACTIONS: [&'static str; 3] = ["Rename", "Remove", "None"];
+                const MODIFY_ACCT_ACTIONS: [&'static str; 4] =
+                    ["Rename", "Share", "Remove", "None"];
                 let mut account_map: HashMap<String, AccountRecord> = HashMap::new();
                 let mut account_names: Vec<String> = Vec::new();
-                for account in accounts.iter() {
+                // only the owner may rename, share or remove an account
+                for account in accounts.iter().filter(|a| !a.is_shared_with_user()) {
                     account_names.push(account.info.name.clone());
                     account_map.insert(account.info.name.clone(), account.clone());
                 }
@@ -222,7 +325,7 @@ fn access_account(uid: u32, db: &mut DbConn) {
                 let acctx = account_map
                     .get(&selected_account)
                     .expect("Account not found!");
-                acct = decode_and_init_account_type(uid, db, acctx);
+                acct = decode_and_init_account_type(db, acctx)?;
 
                 let selected_action =
                     Select::new("What would you like to do:", MODIFY_ACCT_ACTIONS.to_vec())
@@ -230,16 +333,30 @@ fn access_account(uid: u32, db: &mut DbConn) {
                         .unwrap();
 
                 let id = acct.get_id();
+                let _changes = begin_account_change_set(db, uid, &selected_action, acct.as_ref())?;
 
                 match selected_action {
                     "Rename" => {
                         // acct
-                        rename_account(db, uid, id);
-                        return;
+                        if let Err(error) = rename_account(db, uid, id) {
+                            println!("Unable to rename account: {}", error);
+                        }
+                        return Ok(());
+                    }
+                    "Share" => {
+                        if let Err(error) = manage_account_members(db, uid, id) {
+                            println!("Unable to share account: {}", error);
+                        }
+                        return Ok(());
                     }
                     "Remove" => {
-                        db.remove_account(uid, id).unwrap();
-                        return;
+                        let removed = db
+                            .snapshot("remove-account")
+                            .and_then(|_| db.remove_account(uid, id).map_err(FintoolError::from));
+                        if let Err(error) = removed {
+                            println!("Unable to remove account: {}", error);
+                        }
+                        return Ok(());
                     }
                     "None" => {
                         continue;
@@ -250,40 +367,42 @@ fn access_account(uid: u32, db: &mut DbConn) {
                 }
             }
             "Exit" => {
-                return;
+                return Ok(());
             }
             _ => {
                 panic!("Invalid option!");
             }
         }
 
+        let actions = if access.can_write() {
+            ACCT_ACTIONS.to_vec()
+        } else {
+            READ_ONLY_ACCT_ACTIONS.to_vec()
+        };
         loop {
-            let selected_menu_item = Select::new("Select action:", ACCT_ACTIONS.to_vec())
+            let selected_menu_item = Select::new("Select action:", actions.clone())
                 .prompt()
                 .unwrap()
                 .to_string();
-            match selected_menu_item.as_str() {
-                "Import" => {
-                    acct.import();
-                }
-                "Export" => {
-                    acct.export();
-                }
-                "Modify" => {
-                    acct.modify();
-                }
-                "Record" => {
-                    acct.record();
-                }
-                "Report" => {
-                    acct.report();
-                }
+            let _changes = begin_account_change_set(db, uid, &selected_menu_item, acct.as_ref())?;
+            let result = match selected_menu_item.as_str() {
+                "Import" => db.snapshot("import").and_then(|_| acct.import()),
+                "Export" => acct.export(),
+                "Modify" => acct.modify(),
+                "Record" => acct.record(),
+                "Report" => acct.report(),
+                "History" => acct.get_displayable_ledger().and_then(|ledger| {
+                    select_ledger_history(db, acct.get_uid(), acct.get_id(), ledger)
+                }),
                 "None" => {
                     break;
                 }
                 _ => {
                     panic!("Invalid menu option!");
                 }
+            };
+            if let Err(error) = result {
+                println!("Unable to complete action: {}", error);
             }
             let more = Confirm::new("More actions?").prompt().unwrap();
             if !more {
@@ -291,27 +410,41 @@ fn access_account(uid: u32, db: &mut DbConn) {
             }
         }
 
-        accounts = db.get_user_accounts(uid).unwrap();
+        accounts = db.get_user_accounts(uid)?;
     }
+    Ok(())
 }
 
-pub fn decode_and_init_account_type(
+// change sets made through an account are described by the action and the
+// account's name, e.g. "Record: Checking"
+pub fn begin_account_change_set(
+    db: &DbConn,
     uid: u32,
+    action: &str,
+    acct: &dyn Account,
+) -> Result<ChangeSetGuard> {
+    let name = acct.get_name()?;
+    Ok(db.begin_change_set(uid, &format!("{}: {}", action, name))?)
+}
+
+// Shared accounts are opened as their owner, whose rows hold the account.
+pub fn decode_and_init_account_type(
     db: &DbConn,
     account: &AccountRecord,
-) -> Box<dyn Account> {
-    match account.info.atype {
-        AccountType::Bank => Box::new(BankAccount::new(uid, account.id, db)),
-        AccountType::Investment => Box::new(InvestmentAccountManager::new(uid, account.id, db)),
-        AccountType::CreditCard => Box::new(CreditCardAccount::new(uid, account.id, db)),
-        AccountType::CD => Box::new(CertificateOfDepositAccount::new(uid, account.id, db)),
-        AccountType::Wallet => Box::new(Wallet::new(uid, account.id, db)),
-        AccountType::RetirementRothIra => Box::new(RothIraAccount::new(uid, account.id, db)),
+) -> Result<Box<dyn Account>> {
+    let uid = account.owner;
+    Ok(match account.info.atype {
+        AccountType::Bank => Box::new(BankAccount::new(uid, account.id, db)?),
+        AccountType::Investment => Box::new(InvestmentAccountManager::new(uid, account.id, db)?),
+        AccountType::CreditCard => Box::new(CreditCardAccount::new(uid, account.id, db)?),
+        AccountType::CD => Box::new(CertificateOfDepositAccount::new(uid, account.id, db)?),
+        AccountType::Wallet => Box::new(Wallet::new(uid, account.id, db)?),
+        AccountType::RetirementRothIra => Box::new(RothIraAccount::new(uid, account.id, db)?),
         AccountType::HealthSavingsAccount => {
-            Box::new(HealthSavingsAccount::new(uid, account.id, db))
+            Box::new(HealthSavingsAccount::new(uid, account.id, db)?)
         }
-        AccountType::Retirement401k => Box::new(Retirement401kPlan::new(uid, account.id, db)),
-    }
+        AccountType::Retirement401k => Box::new(Retirement401kPlan::new(uid, account.id, db)?),
+    })
 }
 
 pub fn select_analysis_period() -> AnalysisPeriod {
@@ -398,7 +531,7 @@ pub fn get_analysis_period_dates(
 pub fn prompt_and_create_new_account(
     uid: u32,
     db: &DbConn,
-) -> (Option<(Box<dyn Account>, AccountRecord)>) {
+) -> Result<Option<(Box<dyn Account>, AccountRecord)>> {
     let mut account_types = AccountType::iter()
         .map(AccountType::to_menu_selection)
         .collect::<Vec<String>>();
@@ -412,54 +545,54 @@ pub fn prompt_and_create_new_account(
     .to_string();
 
     if selected_account_type == "None".to_string() {
-        return None;
+        return Ok(None);
     }
 
     let account_type: AccountType = selected_account_type
         .parse()
         .expect("Unrecognized account type! Must match the Account Type enumeration!");
-    return create_account(uid, account_type, db);
+    return create_account(uid, account_type, db).map(Some);
 }
 
 pub fn create_account(
     uid: u32,
     atype: AccountType,
     db: &DbConn,
-) -> (Option<(Box<dyn Account>, AccountRecord)>) {
+) -> Result<(Box<dyn Account>, AccountRecord)> {
     let name: String = name_account(uid, db);
     let new_account: AccountRecord;
     let acct: Box<dyn Account>;
 
     match atype {
         AccountType::Bank => {
-            new_account = BankAccount::create(uid, name, db);
+            new_account = BankAccount::create(uid, name, db)?;
         }
         AccountType::Investment => {
-            new_account = InvestmentAccountManager::create(uid, name, db);
+            new_account = InvestmentAccountManager::create(uid, name, db)?;
         }
         AccountType::CreditCard => {
-            new_account = CreditCardAccount::create(uid, name, db);
+            new_account = CreditCardAccount::create(uid, name, db)?;
         }
         AccountType::CD => {
-            new_account = CertificateOfDepositAccount::create(uid, name, db);
+            new_account = CertificateOfDepositAccount::create(uid, name, db)?;
         }
         AccountType::Wallet => {
-            new_account = Wallet::create(uid, name, db);
+            new_account = Wallet::create(uid, name, db)?;
         }
         AccountType::RetirementRothIra => {
-            new_account = RothIraAccount::create(uid, name, db);
+            new_account = RothIraAccount::create(uid, name, db)?;
         }
         AccountType::HealthSavingsAccount => {
-            new_account = HealthSavingsAccount::create(uid, name, db);
+            new_account = HealthSavingsAccount::create(uid, name, db)?;
         }
         AccountType::Retirement401k => {
-            new_account = Retirement401kPlan::create(uid, name, db);
+            new_account = Retirement401kPlan::create(uid, name, db)?;
         }
     }
 
-    acct = decode_and_init_account_type(uid, db, &new_account);
+    acct = decode_and_init_account_type(db, &new_account)?;
 
-    return Some((acct, new_account));
+    return Ok((acct, new_account));
 }
 
 pub fn name_account(uid: u32, db: &DbConn) -> String {
@@ -483,7 +616,7 @@ pub fn name_account(uid: u32, db: &DbConn) -> String {
     return name;
 }
 
-pub fn rename_account(db: &DbConn, uid: u32, id: u32) {
+pub fn rename_account(db: &DbConn, uid: u32, id: u32) -> Result<()> {
     let mut name;
     loop {
         name = Text::new("Enter new name (or Enter to maintain current name):")
@@ -493,14 +626,15 @@ pub fn rename_account(db: &DbConn, uid: u32, id: u32) {
             .to_string();
 
         if name.len() == 0 {
-            return;
-        } else if db.account_with_name_exists(uid, name.clone()).unwrap() {
+            return Ok(());
+        } else if db.account_with_name_exists(uid, name.clone())? {
             println!("Account with name {} already exists!", name);
         } else {
             break;
         }
     }
-    db.rename_account(uid, id, name).unwrap();
+    db.rename_account(uid, id, name)?;
+    Ok(())
 }
 
 pub fn modify_labels(uid: u32, db: &DbConn) {
@@ -20,6 +20,7 @@ use crate::accounts::base::liquid_account::{self, LiquidAccount};
 #[cfg(feature = "ratatui_support")]
 use crate::accounts::base::AnalysisPeriod;
 use crate::database::{self, *};
+use crate::error::Result;
 // use crate::tui::tui_budgets::create_budget;
 use crate::tui::BankAccount;
 use crate::types::accounts::AccountType;
@@ -28,6 +29,7 @@ use crate::Account;
 #[cfg(feature = "ratatui_support")]
 use chrono::NaiveDate;
 use inquire::*;
+use shared_lib::Money;
 
 pub fn select_account_by_type(
     _uid: u32,
@@ -68,93 +70,127 @@ pub fn select_account_by_filter(_uid: u32, _db: &mut DbConn, filter: AccountFilt
     return aid;
 }
 
+// The part of the account's value that belongs to the user: all of it unless
+// the account is shared and split by ownership.
 #[cfg(feature = "ratatui_support")]
-pub fn get_total_assets(accounts: &Vec<Box<dyn Account>>) -> f32 {
-    let mut assets = 0.0;
+pub fn share_of_value(
+    records: &[AccountRecord],
+    account: &Box<dyn Account>,
+    value: Money,
+) -> Money {
+    match records
+        .iter()
+        .find(|record| record.owner == account.get_uid() && record.id == account.get_id())
+    {
+        Some(record) => record.share_of(value),
+        None => value,
+    }
+}
+
+#[cfg(feature = "ratatui_support")]
+pub fn get_total_assets(
+    accounts: &Vec<Box<dyn Account>>,
+    records: &[AccountRecord],
+) -> Result<Money> {
+    let mut assets = Money::ZERO;
     for account in accounts {
         match account.kind() {
             AccountType::CreditCard => assets = assets,
-            _ => assets = assets + account.get_value(),
+            _ => assets = assets + share_of_value(records, account, account.get_value()?),
         }
     }
-    return assets;
+    Ok(assets)
 }
 
 #[cfg(feature = "ratatui_support")]
-pub fn get_total_liabilities(accounts: &Vec<Box<dyn Account>>) -> f32 {
-    let mut liabilities = 0.0;
+pub fn get_total_liabilities(
+    accounts: &Vec<Box<dyn Account>>,
+    records: &[AccountRecord],
+) -> Result<Money> {
+    let mut liabilities = Money::ZERO;
     for account in accounts {
         match account.kind() {
-            AccountType::CreditCard => liabilities = liabilities + account.get_value(),
+            AccountType::CreditCard => {
+                liabilities = liabilities + share_of_value(records, account, account.get_value()?)
+            }
             _ => liabilities = liabilities,
         }
     }
-    return liabilities;
+    Ok(liabilities)
 }
 
 #[cfg(feature = "ratatui_support")]
 pub fn get_dollar_change_y2y(
     accounts: &Vec<Box<dyn Account>>,
+    records: &[AccountRecord],
     start_date: NaiveDate,
     end_date: NaiveDate,
-) -> f32 {
-    let mut starting_value = 0.0;
-    let mut ending_value = 0.0;
+) -> Result<Money> {
+    let mut starting_value = Money::ZERO;
+    let mut ending_value = Money::ZERO;
     for account in accounts {
         match account.kind() {
             AccountType::CreditCard => starting_value = starting_value,
             _ => {
-                starting_value = starting_value + account.get_value_on_day(start_date);
-                ending_value = ending_value + account.get_value_on_day(end_date);
+                starting_value = starting_value
+                    + share_of_value(records, account, account.get_value_on_day(start_date)?);
+                ending_value = ending_value
+                    + share_of_value(records, account, account.get_value_on_day(end_date)?);
             }
         }
     }
 
-    return (ending_value - starting_value);
+    Ok(ending_value - starting_value)
 }
 
 #[cfg(feature = "ratatui_support")]
 pub fn get_net_worth_growth(
     accounts: &Vec<Box<dyn Account>>,
+    records: &[AccountRecord],
     start_date: NaiveDate,
     end_date: NaiveDate,
-) -> f32 {
-    let mut starting_value = 0.0;
-    let mut ending_value = 0.0;
+) -> Result<f32> {
+    let mut starting_value = Money::ZERO;
+    let mut ending_value = Money::ZERO;
     for account in accounts {
         match account.kind() {
             AccountType::CreditCard => starting_value = starting_value,
             _ => {
-                starting_value = starting_value + account.get_value_on_day(start_date);
-                ending_value = ending_value + account.get_value_on_day(end_date);
+                starting_value = starting_value
+                    + share_of_value(records, account, account.get_value_on_day(start_date)?);
+                ending_value = ending_value
+                    + share_of_value(records, account, account.get_value_on_day(end_date)?);
             }
         }
     }
 
-    return (ending_value - starting_value) / (starting_value) * 100.;
+    Ok(((ending_value - starting_value).to_f64() / starting_value.to_f64() * 100.) as f32)
 }
 
 #[cfg(feature = "ratatui_support")]
 pub fn get_compound_annual_growth_rate(
     accounts: &Vec<Box<dyn Account>>,
+    records: &[AccountRecord],
     start_date: NaiveDate,
     end_date: NaiveDate,
-) -> f32 {
-    let mut starting_value = 0.0;
-    let mut ending_value = 0.0;
+) -> Result<f32> {
+    let mut starting_value = Money::ZERO;
+    let mut ending_value = Money::ZERO;
     for account in accounts {
         match account.kind() {
             AccountType::CreditCard => starting_value = starting_value,
             _ => {
-                starting_value = starting_value + account.get_value_on_day(start_date);
-                ending_value = ending_value + account.get_value_on_day(end_date);
+                starting_value = starting_value
+                    + share_of_value(records, account, account.get_value_on_day(start_date)?);
+                ending_value = ending_value
+                    + share_of_value(records, account, account.get_value_on_day(end_date)?);
             }
         }
     }
 
-    return (f32::powf(
-        ending_value / starting_value,
+    Ok((f32::powf(
+        (ending_value.to_f64() / starting_value.to_f64()) as f32,
         1.0 / (((end_date - start_date).num_days() as f32) / 365.25),
     ) - 1.)
-        * 100.;
+        * 100.)
 }
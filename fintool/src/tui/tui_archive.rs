@@ -0,0 +1,54 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use inquire::Text;
+use std::fs::{self, File};
+use std::io::BufWriter;
+
+use crate::database::DbConn;
+use crate::error::Result;
+use crate::tui::tui_user::prompt_new_password;
+
+// Writes everything the user owns to a JSON archive, from which it can be
+// restored into another database without losing anything.
+pub fn export_archive(db: &DbConn, uid: u32) -> Result<()> {
+    let path = Text::new("Enter path to archive file:")
+        .with_default(&format!("{}.json", db.get_user_name(uid)?))
+        .prompt()
+        .unwrap();
+
+    let count = db.write_archive(uid, BufWriter::new(File::create(&path)?))?;
+    println!("Wrote {} records to {}.", count, path);
+    Ok(())
+}
+
+// Restores an archive as a new user, who chooses a password for it since
+// archives do not carry one.
+pub fn import_archive(db: &DbConn) -> Result<()> {
+    let path = Text::new("Enter path to archive file:").prompt().unwrap();
+    let archive = fs::read_to_string(&path)?;
+    let name = Text::new("Enter name of the restored user:")
+        .with_default(&db.get_archive_user_name(&archive)?)
+        .prompt()
+        .unwrap();
+    let password = prompt_new_password(name.trim());
+
+    // the restore is not recorded as changes that could be undone
+    db.snapshot("import-archive")?;
+    db.import_archive(&archive, &name, &password)?;
+    println!("Restored {} from {}.", name.trim(), path);
+    Ok(())
+}
@@ -0,0 +1,109 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use crate::database::encryption::{check_passphrase, is_encrypted, MIN_PASSPHRASE_LENGTH};
+use crate::database::DbConn;
+use crate::error::{FintoolError, Result};
+use inquire::validator::Validation;
+use inquire::*;
+use std::path::Path;
+
+const PASSPHRASE_ATTEMPTS: u32 = 3;
+
+// Opens the database, asking for its passphrase first if it is encrypted.
+pub fn open_database(db_path: &Path) -> Result<DbConn> {
+    if !is_encrypted(db_path)? {
+        return Ok(DbConn::new(db_path)?);
+    }
+
+    let mut attempts = 0;
+    loop {
+        let passphrase = prompt_passphrase(db_path);
+        match check_passphrase(db_path, Some(&passphrase)) {
+            Ok(()) => return Ok(DbConn::open(db_path, Some(&passphrase))?),
+            Err(FintoolError::Authentication(msg)) => {
+                attempts += 1;
+                if attempts == PASSPHRASE_ATTEMPTS {
+                    return Err(FintoolError::Authentication(msg));
+                }
+                println!("Incorrect passphrase, please try again.");
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+// Encrypts an existing database, and its snapshots, with a new passphrase. A
+// database that does not exist yet is created encrypted.
+pub fn encrypt_database(db_path: &Path) -> Result<()> {
+    if is_encrypted(db_path)? {
+        return Err(FintoolError::Validation(format!(
+            "{} is already encrypted",
+            db_path.display()
+        )));
+    }
+    println!(
+        "The passphrase cannot be recovered. Without it, {} cannot be opened.",
+        db_path.display()
+    );
+    let passphrase = prompt_new_passphrase(db_path);
+    if db_path.exists() {
+        DbConn::encrypt_file(db_path, &passphrase)?;
+        println!("Encrypted {}.", db_path.display());
+    } else {
+        DbConn::open(db_path, Some(&passphrase))?;
+        println!("Created encrypted database {}.", db_path.display());
+    }
+    Ok(())
+}
+
+// Stores the database, and its snapshots, unencrypted again.
+pub fn decrypt_database(db_path: &Path) -> Result<()> {
+    let passphrase = prompt_passphrase(db_path);
+    DbConn::decrypt_file(db_path, &passphrase)?;
+    println!("Decrypted {}.", db_path.display());
+    Ok(())
+}
+
+fn prompt_passphrase(db_path: &Path) -> String {
+    Password::new(&format!("Passphrase for {}:", db_path.display()))
+        .with_display_mode(PasswordDisplayMode::Masked)
+        .without_confirmation()
+        .prompt()
+        .unwrap()
+}
+
+fn prompt_new_passphrase(db_path: &Path) -> String {
+    Password::new(&format!("New passphrase for {}:", db_path.display()))
+        .with_display_mode(PasswordDisplayMode::Masked)
+        .with_custom_confirmation_message("Confirm passphrase:")
+        .with_custom_confirmation_error_message("The passphrases do not match.")
+        .with_validator(|passphrase: &str| {
+            if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+                Ok(Validation::Invalid(
+                    format!(
+                        "Passphrases must be at least {} characters long.",
+                        MIN_PASSPHRASE_LENGTH
+                    )
+                    .into(),
+                ))
+            } else {
+                Ok(Validation::Valid)
+            }
+        })
+        .prompt()
+        .unwrap()
+}
@@ -0,0 +1,139 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::{Datelike, Local};
+use inquire::{Confirm, Select, Text};
+use shared_lib::Money;
+use std::fs::File;
+use std::io::BufWriter;
+
+use crate::database::gains::{write_form_8949, HoldingTerm, RealizedGain, UnrealizedGain};
+use crate::database::DbConn;
+use crate::error::Result;
+
+// Prints the gains realized in a tax year and the gains not yet realized on
+// the lots still held, for one account or, when aid is None, for every account
+// the user owns. The realized gains can then be written as a Form 8949 CSV.
+pub fn report_gains(db: &DbConn, uid: u32, aid: Option<u32>) -> Result<()> {
+    let today = Local::now().date_naive();
+    let realized = db.get_realized_gains(uid, aid)?;
+
+    let mut years: Vec<String> = realized
+        .iter()
+        .map(|gain| gain.sold[..4].to_string())
+        .collect();
+    years.sort();
+    years.dedup();
+    years.reverse();
+    years.push("All".to_string());
+    let year = Select::new("Select tax year of sales:", years)
+        .prompt()
+        .unwrap();
+    let realized: Vec<RealizedGain> = realized
+        .into_iter()
+        .filter(|gain| year == "All" || gain.sold.starts_with(&year))
+        .collect();
+
+    println!("\nRealized Gains ({}):", year);
+    if realized.is_empty() {
+        println!("\tNo sales found!");
+    } else {
+        println!(
+            "\t{:<16} {:<8} {:>12} {:<10} {:<10} {:>12} {:>12} {:>12} {:<10}",
+            "Account", "Holding", "Shares", "Acquired", "Sold", "Proceeds", "Basis", "Gain", "Term"
+        );
+        for gain in realized.iter() {
+            println!(
+                "\t{:<16} {:<8} {:>12} {:<10} {:<10} {:>12} {:>12} {:>12} {:<10}",
+                gain.account,
+                gain.ticker,
+                gain.shares.to_string(),
+                gain.acquired,
+                gain.sold,
+                gain.proceeds.to_string(),
+                gain.basis.to_string(),
+                gain.gain.to_string(),
+                gain.term.to_string()
+            );
+        }
+        for term in [HoldingTerm::Short, HoldingTerm::Long] {
+            let total: Money = realized
+                .iter()
+                .filter(|gain| gain.term == term)
+                .map(|gain| gain.gain)
+                .sum();
+            println!("\t{} gain: {}", term, total);
+        }
+    }
+
+    let unrealized: Vec<UnrealizedGain> = db.get_unrealized_gains(uid, aid, today)?;
+    println!("\nUnrealized Gains (as of {}):", today);
+    if unrealized.is_empty() {
+        println!("\tNo open lots found!");
+    } else {
+        println!(
+            "\t{:<16} {:<8} {:>12} {:<10} {:>12} {:>12} {:>12} {:<10}",
+            "Account", "Holding", "Shares", "Acquired", "Value", "Basis", "Gain", "Term"
+        );
+        for gain in unrealized.iter() {
+            println!(
+                "\t{:<16} {:<8} {:>12} {:<10} {:>12} {:>12} {:>12} {:<10}",
+                gain.account,
+                gain.ticker,
+                gain.shares.to_string(),
+                gain.acquired,
+                gain.value
+                    .map_or("-".to_string(), |value| value.to_string()),
+                gain.basis.to_string(),
+                gain.gain.map_or("-".to_string(), |gain| gain.to_string()),
+                gain.term.to_string()
+            );
+        }
+        for term in [HoldingTerm::Short, HoldingTerm::Long] {
+            let total: Money = unrealized
+                .iter()
+                .filter(|gain| gain.term == term)
+                .filter_map(|gain| gain.gain)
+                .sum();
+            println!("\t{} gain: {}", term, total);
+        }
+        if unrealized.iter().any(|gain| gain.value.is_none()) {
+            println!("\tHoldings without a price are left out of the totals.");
+        }
+    }
+
+    if realized.is_empty() {
+        return Ok(());
+    }
+    let export = Confirm::new("Would you like to export the realized gains as a Form 8949 CSV?")
+        .with_default(false)
+        .prompt()
+        .unwrap();
+    if export {
+        let default_year = if year == "All" {
+            today.year().to_string()
+        } else {
+            year
+        };
+        let path = Text::new("Enter path to CSV file:")
+            .with_default(&format!("form-8949-{}.csv", default_year))
+            .prompt()
+            .unwrap();
+        let count = write_form_8949(&realized, BufWriter::new(File::create(&path)?))?;
+        println!("Wrote {} sales to {}.", count, path);
+    }
+    Ok(())
+}
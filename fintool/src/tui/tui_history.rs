@@ -0,0 +1,131 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use crate::database::audit::{ChangeKind, ChangeRecord, ChangeSetState};
+use crate::database::DbConn;
+use crate::error::Result;
+use crate::types::ledger::DisplayableLedgerRecord;
+use inquire::*;
+
+// Undoes the user's last change and says what was undone, or None when there
+// was nothing to undo.
+pub fn undo_last_change(db: &DbConn, uid: u32) -> Result<Option<String>> {
+    Ok(db.undo(uid)?.map(|change_set| {
+        format!(
+            "Undid '{}' from {}.",
+            change_set.description, change_set.timestamp
+        )
+    }))
+}
+
+// Redoes the user's last undone change and says what was redone, or None when
+// there was nothing to redo.
+pub fn redo_last_change(db: &DbConn, uid: u32) -> Result<Option<String>> {
+    Ok(db.redo(uid)?.map(|change_set| {
+        format!(
+            "Redid '{}' from {}.",
+            change_set.description, change_set.timestamp
+        )
+    }))
+}
+
+// Lets the user pick one of the account's ledger entries and browse its history.
+pub fn select_ledger_history(
+    db: &DbConn,
+    uid: u32,
+    aid: u32,
+    entries: Vec<DisplayableLedgerRecord>,
+) -> Result<()> {
+    let mut options: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "{:>5}  {}  {:>12}  {}  {}",
+                entry.id,
+                entry.info.date,
+                entry.info.amount,
+                entry.info.participant,
+                entry.info.description
+            )
+        })
+        .collect();
+    options.push("None".to_string());
+
+    let selected = Select::new("Select ledger entry:", options)
+        .raw_prompt()
+        .unwrap();
+    match entries.get(selected.index) {
+        Some(entry) => {
+            let lid = entry.id.parse::<u32>().expect("Invalid ledger id!");
+            show_ledger_history(db, uid, aid, lid)
+        }
+        None => Ok(()),
+    }
+}
+
+// Lists every recorded change of the ledger entry, oldest first, and shows the
+// values before and after the one selected until the user is done.
+pub fn show_ledger_history(db: &DbConn, uid: u32, aid: u32, lid: u32) -> Result<()> {
+    let history = db.get_ledger_history(uid, aid, lid)?;
+    if history.is_empty() {
+        println!("No changes have been recorded for this entry.");
+        return Ok(());
+    }
+
+    let mut options: Vec<String> = history.iter().map(summarize_change).collect();
+    options.push("Done".to_string());
+
+    loop {
+        let selected = Select::new("Select change:", options.clone())
+            .raw_prompt()
+            .unwrap();
+        let change = match history.get(selected.index) {
+            Some(change) => change,
+            None => return Ok(()),
+        };
+
+        println!("\n{}", summarize_change(change));
+        for (column, before, after) in change.fields.iter() {
+            let before = before.as_deref().unwrap_or("");
+            let after = after.as_deref().unwrap_or("");
+            if change.kind == ChangeKind::Update && before == after {
+                println!("  {:<16} {}", column, after);
+            } else {
+                println!("  {:<16} {} -> {}", column, before, after);
+            }
+        }
+        println!();
+    }
+}
+
+fn summarize_change(change: &ChangeRecord) -> String {
+    let kind = match change.kind {
+        ChangeKind::Insert => "Created",
+        ChangeKind::Update => "Edited",
+        ChangeKind::Delete => "Removed",
+    };
+    let state = match change.state {
+        Some(ChangeSetState::Undone) | Some(ChangeSetState::Discarded) => " (undone)",
+        _ => "",
+    };
+    format!(
+        "{}  {:<8} {}{}",
+        change.timestamp,
+        kind,
+        change.description.as_deref().unwrap_or(""),
+        state
+    )
+}
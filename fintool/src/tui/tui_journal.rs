@@ -0,0 +1,41 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use inquire::{Select, Text};
+use std::fs::File;
+use std::io::BufWriter;
+use strum::IntoEnumIterator;
+
+use crate::database::journal::JournalSyntax;
+use crate::database::DbConn;
+use crate::error::Result;
+
+// Writes the books of every account the user owns as a Beancount or hledger
+// journal, to be checked against those tools or kept under version control.
+pub fn export_journal(db: &DbConn, uid: u32) -> Result<()> {
+    let syntaxes: Vec<JournalSyntax> = JournalSyntax::iter().collect();
+    let syntax = Select::new("Select journal syntax:", syntaxes)
+        .prompt()
+        .unwrap();
+    let path = Text::new("Enter path to journal file:")
+        .with_default(&format!("finances.{}", syntax.extension()))
+        .prompt()
+        .unwrap();
+
+    let count = db.write_journal(uid, syntax, BufWriter::new(File::create(&path)?))?;
+    println!("Wrote {} transactions to {}.", count, path);
+    Ok(())
+}
@@ -0,0 +1,165 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use crate::database::DbConn;
+use crate::error::Result;
+use crate::types::account_members::{AccountAccess, AccountMember};
+use crate::types::accounts::AccountRecord;
+use inquire::validator::Validation;
+use inquire::*;
+use shared_lib::Quantity;
+
+// Name of the account as listed for the user, which for accounts shared with
+// them includes whose account it is.
+pub fn account_label(db: &DbConn, account: &AccountRecord) -> String {
+    if account.is_shared_with_user() {
+        let owner = db
+            .get_user_name(account.owner)
+            .unwrap_or("another user".to_string());
+        format!("{} (shared by {})", account.info.name, owner)
+    } else {
+        account.info.name.clone()
+    }
+}
+
+// Lets the owner of an account add members, change their access and share of
+// the account, or remove them.
+pub fn manage_account_members(db: &DbConn, uid: u32, aid: u32) -> Result<()> {
+    let owner = db.get_user_name(uid)?;
+    loop {
+        let members = db.get_account_members(uid, aid)?;
+        if members.is_empty() {
+            println!("The account is not shared.");
+        } else {
+            for member in members.iter() {
+                println!("  {}", describe_member(member));
+            }
+            if members.iter().any(|member| member.ownership.is_some()) {
+                println!("  {} owns {}%", owner, db.get_owner_share(uid, aid)?);
+            }
+        }
+
+        let mut options = vec!["Add Member"];
+        if !members.is_empty() {
+            options.push("Change Member");
+            options.push("Remove Member");
+        }
+        options.push("Done");
+
+        match Select::new("What would you like to do:", options)
+            .prompt()
+            .unwrap()
+        {
+            "Add Member" => {
+                let candidates: Vec<String> = db
+                    .get_users()?
+                    .into_iter()
+                    .filter(|name| {
+                        *name != owner && !members.iter().any(|member| member.name == *name)
+                    })
+                    .collect();
+                if candidates.is_empty() {
+                    println!("There is no one else to share the account with.");
+                    continue;
+                }
+                let name = Select::new("Share with:", candidates).prompt().unwrap();
+                let member_uid = db.get_user_id(name)?;
+                let access = select_access(None);
+                let ownership = prompt_ownership(db.get_owner_share(uid, aid)?, None);
+                db.set_account_member(uid, aid, member_uid, access, ownership)?;
+            }
+            "Change Member" => {
+                let member = select_member(&members);
+                let access = select_access(Some(member.access));
+                // the member's current share is available to them again
+                let available =
+                    db.get_owner_share(uid, aid)? + member.ownership.unwrap_or(Quantity::ZERO);
+                let ownership = prompt_ownership(available, member.ownership);
+                db.set_account_member(uid, aid, member.uid, access, ownership)?;
+            }
+            "Remove Member" => {
+                let member = select_member(&members);
+                let confirmed =
+                    Confirm::new(&format!("Stop sharing the account with {}?", member.name))
+                        .with_default(false)
+                        .prompt()
+                        .unwrap();
+                if confirmed {
+                    db.remove_account_member(uid, aid, member.uid)?;
+                }
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
+fn describe_member(member: &AccountMember) -> String {
+    match member.ownership {
+        Some(ownership) => format!(
+            "{:<16} {:<12} owns {}%",
+            member.name, member.access, ownership
+        ),
+        None => format!("{:<16} {}", member.name, member.access),
+    }
+}
+
+fn select_member(members: &[AccountMember]) -> AccountMember {
+    let options: Vec<String> = members.iter().map(describe_member).collect();
+    let selected = Select::new("Select member:", options).raw_prompt().unwrap();
+    members[selected.index].clone()
+}
+
+fn select_access(current: Option<AccountAccess>) -> AccountAccess {
+    let options = vec![AccountAccess::Read, AccountAccess::ReadWrite];
+    let cursor = match current {
+        Some(AccountAccess::ReadWrite) => 1,
+        _ => 0,
+    };
+    Select::new("Access:", options)
+        .with_starting_cursor(cursor)
+        .prompt()
+        .unwrap()
+}
+
+// An empty answer leaves the account unsplit for the member, who then counts
+// all of it.
+fn prompt_ownership(available: Quantity, current: Option<Quantity>) -> Option<Quantity> {
+    let default = current
+        .map(|ownership| ownership.to_string())
+        .unwrap_or_default();
+    let answer = Text::new("Percentage of the account owned by the member (blank for none):")
+        .with_initial_value(&default)
+        .with_validator(move |input: &str| {
+            if input.trim().is_empty() {
+                return Ok(Validation::Valid);
+            }
+            match input.trim().parse::<Quantity>() {
+                Ok(ownership) if ownership > Quantity::ZERO && ownership <= available => {
+                    Ok(Validation::Valid)
+                }
+                _ => Ok(Validation::Invalid(
+                    format!(
+                        "Enter a percentage greater than 0 and at most {}.",
+                        available
+                    )
+                    .into(),
+                )),
+            }
+        })
+        .prompt()
+        .unwrap();
+    answer.trim().parse::<Quantity>().ok()
+}
@@ -0,0 +1,76 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use crate::database::DbConn;
+use crate::error::Result;
+use inquire::*;
+
+// Lists the snapshots of the open database, newest first, with the net worth
+// of each user at the time, and restores the one selected. Returns true when
+// the database was replaced.
+pub fn restore_snapshot(db: &DbConn) -> Result<bool> {
+    let snapshots = db.list_snapshots()?;
+    if snapshots.is_empty() {
+        println!("No snapshots have been taken of this database.");
+        return Ok(false);
+    }
+
+    let mut options: Vec<String> = Vec::new();
+    for snapshot in snapshots.iter() {
+        let summary = match db.summarize_snapshot(snapshot) {
+            Ok(summary) => summary
+                .net_worth
+                .iter()
+                .map(|(name, net_worth)| format!("{}: $ {:.2}", name, net_worth))
+                .collect::<Vec<String>>()
+                .join(", "),
+            Err(_) => "net worth unavailable".to_string(),
+        };
+        options.push(format!(
+            "{}  {:<16} {}",
+            snapshot.taken.format("%Y-%m-%d %H:%M:%S"),
+            snapshot.reason,
+            summary
+        ));
+    }
+    options.push("None".to_string());
+
+    let selected = Select::new("Select snapshot to restore:", options)
+        .raw_prompt()
+        .unwrap();
+    let snapshot = match snapshots.get(selected.index) {
+        Some(snapshot) => snapshot,
+        None => return Ok(false),
+    };
+
+    let confirmed = Confirm::new(&format!(
+        "Replace the database with the snapshot taken {}? The current contents will be snapshotted first.",
+        snapshot.taken.format("%Y-%m-%d %H:%M:%S")
+    ))
+    .with_default(false)
+    .prompt()
+    .unwrap();
+    if !confirmed {
+        return Ok(false);
+    }
+
+    db.restore_snapshot(snapshot)?;
+    println!(
+        "Restored snapshot taken {}.",
+        snapshot.taken.format("%Y-%m-%d %H:%M:%S")
+    );
+    Ok(true)
+}
@@ -14,10 +14,16 @@
   You should have received a copy of the GNU General Public License
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
+use crate::database::db_user::MIN_PASSWORD_LENGTH;
 use crate::database::DbConn;
+use crate::error::{FintoolError, Result};
+use inquire::validator::Validation;
 use inquire::*;
 
-pub fn create_user(_db: &mut DbConn) -> u32 {
+// Creates a user with a password. Only an administrator may create another
+// administrator, so unless one is already logged in, or there is none yet, an
+// administrator must sign off on the elevation.
+pub fn create_user(_db: &mut DbConn, admin_logged_in: bool) -> u32 {
     let mut name: String;
     loop {
         name = Text::new("Enter user name:").prompt().unwrap();
@@ -27,11 +33,27 @@ pub fn create_user(_db: &mut DbConn) -> u32 {
             break;
         }
     }
-    let admin: bool = Confirm::new("Elevate user to administrator:")
+    let mut admin: bool = Confirm::new("Elevate user to administrator:")
         .with_default(false)
         .prompt()
         .unwrap();
-    _db.add_user(name, admin).unwrap()
+    if admin && !admin_logged_in && _db.admin_exists().unwrap() {
+        match authenticate_admin(_db) {
+            Ok(true) => {}
+            Ok(false) => {
+                println!("Only an administrator may create another administrator.");
+                admin = false;
+            }
+            Err(error) => {
+                println!("{}", error);
+                admin = false;
+            }
+        }
+    }
+    let password = prompt_new_password(&name);
+    let id = _db.add_user(name, admin).unwrap();
+    _db.set_password(id, &password).unwrap();
+    id
 }
 
 pub fn create_admin(_db: &mut DbConn) -> u32 {
@@ -44,7 +66,10 @@ pub fn create_admin(_db: &mut DbConn) -> u32 {
             break;
         }
     }
-    _db.add_user(name, true).unwrap()
+    let password = prompt_new_password(&name);
+    let id = _db.add_user(name, true).unwrap();
+    _db.set_password(id, &password).unwrap();
+    id
 }
 
 pub fn tui_set_user(_db: &mut DbConn) -> u32 {
@@ -53,20 +78,289 @@ pub fn tui_set_user(_db: &mut DbConn) -> u32 {
     if users.is_empty() {
         id = create_admin(_db);
     } else {
-        let name: String = Select::new("Select current user:", users.to_vec())
-            .prompt()
-            .unwrap()
-            .to_string();
-        println!("Welcome {}!", name);
-        let rid = _db.get_user_id(name);
-        match rid {
-            Ok(rid) => {
-                id = rid;
-            }
-            Err(error) => {
-                panic!("Error is {}", error);
+        loop {
+            let name: String = Select::new("Select current user:", users.to_vec())
+                .prompt()
+                .unwrap()
+                .to_string();
+            match log_in(_db, &name) {
+                Ok(rid) => {
+                    println!("Welcome {}!", name);
+                    id = rid;
+                    break;
+                }
+                Err(error) => {
+                    println!("{}", error);
+                }
             }
         }
     }
+    _db.set_acting_user(Some(id)).unwrap();
     return id;
 }
+
+// Prompts for the user's password, then has them choose one if they have none
+// yet. Everyone is asked first so that the prompt does not reveal who has
+// no password.
+pub fn log_in(db: &DbConn, name: &str) -> Result<u32> {
+    let password = Password::new("Password:")
+        .with_display_mode(PasswordDisplayMode::Masked)
+        .without_confirmation()
+        .prompt()
+        .unwrap();
+    if db.get_users()?.iter().any(|user| user == name) {
+        let uid = db.get_user_id(name.to_string())?;
+        if !db.has_password(uid)? {
+            set_initial_password(db, uid, name)?;
+            return Ok(uid);
+        }
+    }
+    db.authenticate(name, &password)
+}
+
+// Users created before passwords existed get one at their first login. An
+// administrator has to approve it, except for the administrator who first
+// logs in after upgrading.
+pub fn set_initial_password(db: &DbConn, uid: u32, name: &str) -> Result<()> {
+    println!("{} does not have a password yet.", name);
+    if db.can_claim_password(uid)? {
+        let password = prompt_new_password(name);
+        return db.claim_password(uid, &password);
+    }
+    if !db.password_admin_exists()? {
+        return Err(FintoolError::Authentication(
+            "an administrator must log in and set their password first".to_string(),
+        ));
+    }
+    if !authenticate_admin(db)? {
+        return Err(FintoolError::Authentication(
+            "only an administrator may set this password".to_string(),
+        ));
+    }
+    let password = prompt_new_password(name);
+    db.set_password(uid, &password)
+}
+
+// asks for an administrator's credentials, returning whether they check out
+pub fn authenticate_admin(db: &DbConn) -> Result<bool> {
+    let name = Text::new("Administrator name:").prompt().unwrap();
+    let password = Password::new("Administrator password:")
+        .with_display_mode(PasswordDisplayMode::Masked)
+        .without_confirmation()
+        .prompt()
+        .unwrap();
+    let uid = db.authenticate(&name, &password)?;
+    Ok(db.is_admin(uid)?)
+}
+
+// Lets an administrator set a new password for any user, which also lifts a
+// lockout.
+pub fn reset_password(db: &DbConn) -> Result<()> {
+    let mut users = db.get_users()?;
+    users.push("None".to_string());
+    let name = Select::new("Select user:", users).prompt().unwrap();
+    if name == "None" {
+        return Ok(());
+    }
+    let uid = db.get_user_id(name.clone())?;
+    let password = prompt_new_password(&name);
+    db.set_password(uid, &password)?;
+    println!("Password for {} has been reset.", name);
+    Ok(())
+}
+
+pub fn prompt_new_password(name: &str) -> String {
+    Password::new(&format!("Enter password for {}:", name))
+        .with_display_mode(PasswordDisplayMode::Masked)
+        .with_custom_confirmation_message("Confirm password:")
+        .with_custom_confirmation_error_message("The passwords do not match.")
+        .with_validator(|password: &str| {
+            if password.chars().count() < MIN_PASSWORD_LENGTH {
+                Ok(Validation::Invalid(
+                    format!(
+                        "Passwords must be at least {} characters long.",
+                        MIN_PASSWORD_LENGTH
+                    )
+                    .into(),
+                ))
+            } else {
+                Ok(Validation::Valid)
+            }
+        })
+        .prompt()
+        .unwrap()
+}
+
+// Lets an administrator rename, delete, promote or demote users and transfer
+// accounts between them.
+pub fn manage_users(db: &DbConn) -> Result<()> {
+    loop {
+        let action = Select::new(
+            "What would you like to do:",
+            vec![
+                "Rename User",
+                "Change Administrator Rights",
+                "Transfer Accounts",
+                "Delete User",
+                "Done",
+            ],
+        )
+        .prompt()
+        .unwrap();
+
+        let outcome = match action {
+            "Rename User" => rename_user(db),
+            "Change Administrator Rights" => change_admin_rights(db),
+            "Transfer Accounts" => transfer_accounts(db),
+            "Delete User" => delete_user(db),
+            _ => return Ok(()),
+        };
+        if let Err(error) = outcome {
+            println!("{}", error);
+        }
+    }
+}
+
+// offers every user but the excluded one, returning None if the administrator
+// backs out
+fn select_user(db: &DbConn, prompt: &str, excluded: Option<u32>) -> Result<Option<u32>> {
+    let mut users = Vec::new();
+    for name in db.get_users()? {
+        let uid = db.get_user_id(name.clone())?;
+        if Some(uid) != excluded {
+            users.push(name);
+        }
+    }
+    if users.is_empty() {
+        println!("There are no other users.");
+        return Ok(None);
+    }
+    users.push("None".to_string());
+    let name = Select::new(prompt, users).prompt().unwrap();
+    if name == "None" {
+        return Ok(None);
+    }
+    Ok(Some(db.get_user_id(name)?))
+}
+
+fn rename_user(db: &DbConn) -> Result<()> {
+    let uid = match select_user(db, "Select user:", None)? {
+        Some(uid) => uid,
+        None => return Ok(()),
+    };
+    let name = db.get_user_name(uid)?;
+    let new_name = Text::new("Enter new user name:")
+        .with_initial_value(&name)
+        .prompt()
+        .unwrap();
+    db.rename_user(uid, &new_name)?;
+    println!("{} has been renamed to {}.", name, new_name.trim());
+    Ok(())
+}
+
+fn change_admin_rights(db: &DbConn) -> Result<()> {
+    let uid = match select_user(db, "Select user:", None)? {
+        Some(uid) => uid,
+        None => return Ok(()),
+    };
+    let name = db.get_user_name(uid)?;
+    let admin = db.is_admin(uid)?;
+    let question = if admin {
+        format!("Revoke administrator rights from {}?", name)
+    } else {
+        format!("Grant administrator rights to {}?", name)
+    };
+    if Confirm::new(&question)
+        .with_default(false)
+        .prompt()
+        .unwrap()
+    {
+        db.set_admin(uid, !admin)?;
+    }
+    Ok(())
+}
+
+fn transfer_accounts(db: &DbConn) -> Result<()> {
+    let from = match select_user(db, "Transfer accounts of:", None)? {
+        Some(uid) => uid,
+        None => return Ok(()),
+    };
+    let accounts = db.get_owned_accounts(from)?;
+    if accounts.is_empty() {
+        println!("{} does not own any accounts.", db.get_user_name(from)?);
+        return Ok(());
+    }
+    let names: Vec<String> = accounts
+        .iter()
+        .map(|account| account.info.name.clone())
+        .collect();
+    let selected = MultiSelect::new("Select accounts:", names)
+        .raw_prompt()
+        .unwrap();
+    if selected.is_empty() {
+        return Ok(());
+    }
+    let to = match select_user(db, "Transfer accounts to:", Some(from))? {
+        Some(uid) => uid,
+        None => return Ok(()),
+    };
+    let aids: Vec<u32> = selected
+        .iter()
+        .map(|option| accounts[option.index].id)
+        .collect();
+    let left_behind = db.transfers_left_behind(from, &aids)?;
+    if !left_behind.is_empty() {
+        println!("These transfers would no longer be linked to each other:");
+        for (moved, kept) in left_behind.iter() {
+            println!("  {} <-> {}", moved, kept);
+        }
+        let unlink = Confirm::new("Unlink them and transfer the accounts anyway?")
+            .with_default(false)
+            .prompt()
+            .unwrap();
+        if !unlink {
+            return Ok(());
+        }
+    }
+    db.snapshot("transfer-accounts")?;
+    db.transfer_accounts(from, &aids, to, true)?;
+    println!(
+        "Transferred {} account(s) to {}.",
+        aids.len(),
+        db.get_user_name(to)?
+    );
+    Ok(())
+}
+
+fn delete_user(db: &DbConn) -> Result<()> {
+    let uid = match select_user(db, "Select user to delete:", None)? {
+        Some(uid) => uid,
+        None => return Ok(()),
+    };
+    let name = db.get_user_name(uid)?;
+    let mut heir = None;
+    if !db.get_owned_accounts(uid)?.is_empty() {
+        let keep = Select::new(
+            &format!("What should happen to the accounts of {}?", name),
+            vec!["Transfer them to another user", "Delete them"],
+        )
+        .prompt()
+        .unwrap();
+        if keep == "Transfer them to another user" {
+            heir = match select_user(db, "Transfer accounts to:", Some(uid))? {
+                Some(heir) => Some(heir),
+                None => return Ok(()),
+            };
+        }
+    }
+    let confirmed = Confirm::new(&format!("Delete {}? This cannot be undone.", name))
+        .with_default(false)
+        .prompt()
+        .unwrap();
+    if confirmed {
+        db.snapshot("delete-user")?;
+        db.delete_user(uid, heir)?;
+        println!("{} has been deleted.", name);
+    }
+    Ok(())
+}
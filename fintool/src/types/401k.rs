@@ -15,6 +15,7 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use rusqlite::Result;
+use shared_lib::Money;
 
 use crate::database::DbConn;
 
@@ -26,14 +27,14 @@ pub struct Retirement401kRecord {
 
 #[derive(Clone)]
 pub struct Retirement401kInfo {
-    pub contribution_limit: f32,
+    pub contribution_limit: Money,
 }
 
 impl DbConn {
     pub fn create_401k_accounts_table(&self) -> Result<()> {
         let sql: &str = "CREATE TABLE IF NOT EXISTS plan_401ks ( 
                 id          INTEGER NOT NULL,
-                contribution_limit REAL NOT NULL,
+                contribution_limit INTEGER NOT NULL,
                 uid  INTEGER NOT NULL, 
                 aid INTEGER NOT NULL,
                 PRIMARY KEY (uid, aid, id),
@@ -42,14 +43,12 @@ impl DbConn {
             )";
 
         let conn_lock = self.conn.lock().unwrap();
-        conn_lock
-            .execute(sql, ())
-            .expect("Unable to initialize 401K accounts table!");
+        conn_lock.execute(sql, ())?;
         Ok(())
     }
 
     pub fn add_401k_account(&self, uid: u32, aid: u32, info: Retirement401kInfo) -> Result<u32> {
-        let id = self.get_next_401k_id(uid, aid).unwrap();
+        let id = self.get_next_401k_id(uid, aid)?;
         let p = rusqlite::params!(id, aid, uid, info.contribution_limit);
         let sql =
             "INSERT INTO plan_401ks (id, aid, uid, contribution_limit) VALUES (?1, ?2, ?3, ?4)";
@@ -57,7 +56,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add 401k account {}: {}", aid, error);
+                return Err(error);
             }
         }
     }
@@ -66,15 +65,15 @@ impl DbConn {
         &self,
         uid: u32,
         aid: u32,
-        new_contribution_lmit: f32,
-    ) -> Result<f32> {
+        new_contribution_lmit: Money,
+    ) -> Result<Money> {
         let p = rusqlite::params!(uid, aid, new_contribution_lmit);
         let sql = "UPDATE plan_401ks SET contribution_limit = (?3) WHERE uid = (?1) and aid = (?2)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_contribution_lmit),
             Err(error) => {
-                panic!("Unable to update hsa for 401k {}: {}!", aid, error);
+                return Err(error);
             }
         }
     }
@@ -99,16 +98,11 @@ impl DbConn {
                 match cc_wrap {
                     Ok(cc) => return Ok(cc),
                     Err(error) => {
-                        panic!(
-                            "Unable to retrieve 401k info for account {}: {}",
-                            aid, error
-                        )
+                        return Err(error);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find 401k matching account id: {}!", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 }
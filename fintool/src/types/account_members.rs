@@ -0,0 +1,253 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use rusqlite::Result;
+use shared_lib::Quantity;
+use strum::{Display, EnumIter, FromRepr};
+
+use crate::database::DbConn;
+
+// An account belongs to the user who created it, and every row of its data is
+// keyed by that user's id. Sharing adds other users as members of the account,
+// who then see and work on the owner's rows.
+
+// the whole of an account, as a percentage
+pub const FULL_OWNERSHIP: Quantity = Quantity::from_micros(100 * Quantity::ONE.micros());
+
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, FromRepr)]
+pub enum AccountAccess {
+    #[strum(to_string = "Read only")]
+    Read,
+    #[strum(to_string = "Read/write")]
+    ReadWrite,
+    #[strum(to_string = "Owner")]
+    Owner,
+}
+
+impl AccountAccess {
+    pub fn can_write(&self) -> bool {
+        match self {
+            AccountAccess::Read => false,
+            AccountAccess::ReadWrite | AccountAccess::Owner => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountMember {
+    pub uid: u32,
+    pub name: String,
+    pub access: AccountAccess,
+    // percentage of the account owned by the member, when the account is
+    // split; members without one count the whole account
+    pub ownership: Option<Quantity>,
+}
+
+impl DbConn {
+    pub fn create_account_members_table(&self) -> Result<()> {
+        let sql = "CREATE TABLE IF NOT EXISTS account_members (
+            uid         INTEGER NOT NULL,
+            aid         INTEGER NOT NULL,
+            member_uid  INTEGER NOT NULL,
+            access      INTEGER NOT NULL,
+            ownership   INTEGER,
+            PRIMARY KEY (uid, aid, member_uid),
+            FOREIGN KEY (uid, aid) REFERENCES accounts(uid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (member_uid) REFERENCES users(id) ON DELETE CASCADE
+        )";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, ())?;
+        Ok(())
+    }
+
+    // Adds the user as a member of the account, or changes the access and
+    // ownership of an existing member.
+    pub fn set_account_member(
+        &self,
+        uid: u32,
+        aid: u32,
+        member_uid: u32,
+        access: AccountAccess,
+        ownership: Option<Quantity>,
+    ) -> Result<()> {
+        let sql = "INSERT INTO account_members (uid, aid, member_uid, access, ownership)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (uid, aid, member_uid) DO UPDATE SET
+                access = excluded.access,
+                ownership = excluded.ownership";
+        let p = rusqlite::params![uid, aid, member_uid, access as u32, ownership];
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, p)?;
+        Ok(())
+    }
+
+    pub fn remove_account_member(&self, uid: u32, aid: u32, member_uid: u32) -> Result<()> {
+        let sql =
+            "DELETE FROM account_members WHERE uid = (?1) and aid = (?2) and member_uid = (?3)";
+        let p = rusqlite::params![uid, aid, member_uid];
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, p)?;
+        Ok(())
+    }
+
+    pub fn get_account_members(&self, uid: u32, aid: u32) -> Result<Vec<AccountMember>> {
+        let sql = "SELECT m.member_uid, u.name, m.access, m.ownership
+            FROM account_members as m
+            INNER JOIN users as u ON u.id = m.member_uid
+            WHERE m.uid = (?1) and m.aid = (?2)
+            ORDER BY u.name";
+        let p = rusqlite::params![uid, aid];
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        let members = stmt
+            .query_map(p, |row| {
+                Ok(AccountMember {
+                    uid: row.get(0)?,
+                    name: row.get(1)?,
+                    access: AccountAccess::from_repr(row.get::<_, u32>(2)? as usize)
+                        .unwrap_or(AccountAccess::Read),
+                    ownership: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(members)
+    }
+
+    // Refuses writes to the rows of accounts that the user working through
+    // this connection may only read, whichever path the write takes. Like the
+    // audit triggers these are TEMP, reading the user from a TEMP table, so
+    // they only apply to this connection. Until a user is set nothing is
+    // refused.
+    pub fn install_access_triggers(&self) -> Result<()> {
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute_batch(
+            "CREATE TEMP TABLE IF NOT EXISTS access_context (uid INTEGER);
+            INSERT INTO temp.access_context (uid)
+                SELECT NULL WHERE NOT EXISTS (SELECT 1 FROM temp.access_context);",
+        )?;
+
+        // account rows are keyed by owner and account, and the accounts
+        // themselves by owner and id
+        let mut stmt = conn_lock.prepare(
+            "SELECT m.name, CASE WHEN m.name = 'accounts' THEN 'id' ELSE 'aid' END
+            FROM main.sqlite_master AS m
+            WHERE m.type = 'table'
+                and EXISTS (SELECT 1 FROM pragma_table_info(m.name) WHERE name = 'uid')
+                and (m.name = 'accounts'
+                    or EXISTS (SELECT 1 FROM pragma_table_info(m.name) WHERE name = 'aid'))",
+        )?;
+        let tables = stmt
+            .query_map((), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        for (table, aid) in tables {
+            let read_only = |alias: &str| {
+                format!(
+                    "EXISTS (SELECT 1 FROM main.account_members AS m, temp.access_context AS c
+                        WHERE m.member_uid = c.uid and m.uid = {alias}.uid
+                            and m.aid = {alias}.\"{aid}\" and m.access = {access})",
+                    access = AccountAccess::Read as u32,
+                )
+            };
+            for (event, condition) in [
+                ("INSERT", read_only("NEW")),
+                (
+                    "UPDATE",
+                    format!("{} OR {}", read_only("OLD"), read_only("NEW")),
+                ),
+                ("DELETE", read_only("OLD")),
+            ] {
+                let name = format!("access_{}_{}", table, event.to_lowercase());
+                conn_lock.execute_batch(&format!(
+                    "DROP TRIGGER IF EXISTS temp.{name};
+                    CREATE TEMP TRIGGER {name} BEFORE {event} ON main.\"{table}\"
+                    WHEN {condition}
+                    BEGIN
+                        SELECT RAISE(ABORT, 'the account is shared read only');
+                    END;"
+                ))?;
+            }
+        }
+        Ok(())
+    }
+
+    // the logged in user, whose access to shared accounts the triggers enforce
+    pub fn set_acting_user(&self, uid: Option<u32>) -> Result<()> {
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute("UPDATE temp.access_context SET uid = (?1)", [uid])?;
+        Ok(())
+    }
+
+    // The owner's percentage of the account: whatever the members do not own.
+    pub fn get_owner_share(&self, uid: u32, aid: u32) -> Result<Quantity> {
+        let sql = "SELECT COALESCE(SUM(ownership), 0) FROM account_members WHERE uid = (?1) and aid = (?2)";
+        let p = rusqlite::params![uid, aid];
+        let conn_lock = self.conn.lock().unwrap();
+        let members: Quantity = conn_lock.query_row(sql, p, |row| row.get(0))?;
+        Ok(FULL_OWNERSHIP - members)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::accounts::{AccountInfo, AccountType};
+
+    #[test]
+    fn read_only_members_cannot_write() {
+        let db = DbConn::new(":memory:").unwrap();
+        let owner = db.add_user("owner".to_string(), true).unwrap();
+        let reader = db.add_user("reader".to_string(), false).unwrap();
+        let writer = db.add_user("writer".to_string(), false).unwrap();
+        let aid = db
+            .add_account(
+                owner,
+                &AccountInfo {
+                    atype: AccountType::Bank,
+                    name: "Checking".to_string(),
+                    has_stocks: false,
+                    has_bank: true,
+                    has_ledger: true,
+                    has_budget: false,
+                },
+            )
+            .unwrap();
+        db.set_account_member(owner, aid, reader, AccountAccess::Read, None)
+            .unwrap();
+        db.set_account_member(owner, aid, writer, AccountAccess::ReadWrite, None)
+            .unwrap();
+
+        let cases = [
+            (owner, "RENT", true),
+            (reader, "FOOD", false),
+            (writer, "FUEL", true),
+        ];
+        for (uid, category, allowed) in cases {
+            db.set_acting_user(Some(uid)).unwrap();
+            assert_eq!(
+                db.add_category(owner, aid, category.to_string()).is_ok(),
+                allowed,
+                "{}",
+                category
+            );
+        }
+        db.set_acting_user(Some(reader)).unwrap();
+        assert!(db.remove_account_member(owner, aid, writer).is_err());
+        assert_eq!(db.get_categories(owner, aid).unwrap().len(), 2);
+    }
+}
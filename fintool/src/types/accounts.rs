@@ -25,12 +25,15 @@ use ratatui::{
     Frame,
 };
 use rusqlite::{Error, Result};
+use shared_lib::{Money, Quantity};
 use strum::{Display, EnumIter, EnumString, FromRepr, IntoEnumIterator};
 
 #[cfg(feature = "ratatui_support")]
 use crate::app::screen::TabMenu;
 use crate::database::DbConn;
+use crate::error::FintoolError;
 
+use super::account_members::{AccountAccess, FULL_OWNERSHIP};
 use super::ledger;
 
 #[derive(Clone, Copy, Display, EnumIter, EnumString, FromRepr, Default)]
@@ -112,6 +115,12 @@ pub struct AccountInfo {
 pub struct AccountRecord {
     pub id: u32,
     pub info: AccountInfo,
+    // the user whose rows hold the account, which is not the user looking at
+    // it when the account is shared with them
+    pub owner: u32,
+    pub access: AccountAccess,
+    // percentage of the account's value that belongs to the user looking at it
+    pub ownership: Quantity,
 }
 
 impl AccountRecord {
@@ -121,6 +130,32 @@ impl AccountRecord {
             _ => false,
         }
     }
+
+    pub fn is_shared_with_user(&self) -> bool {
+        self.access != AccountAccess::Owner
+    }
+
+    // the part of a value of the account that belongs to the user looking at it
+    pub fn share_of(&self, value: Money) -> Money {
+        if self.ownership == FULL_OWNERSHIP {
+            value
+        } else {
+            self.ownership
+                .checked_div(FULL_OWNERSHIP)
+                .map_or(value, |share| value * share)
+        }
+    }
+
+    // a freshly created account, which only its owner can see
+    pub fn owned(uid: u32, id: u32, info: AccountInfo) -> Self {
+        AccountRecord {
+            id,
+            info,
+            owner: uid,
+            access: AccountAccess::Owner,
+            ownership: FULL_OWNERSHIP,
+        }
+    }
 }
 
 pub struct AccountTransaction {
@@ -148,12 +183,7 @@ impl DbConn {
         ";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create account_ids table: {}", error)
-            }
-        }
+        rs?;
         Ok(())
     }
 
@@ -167,15 +197,7 @@ impl DbConn {
         ";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!(
-                    "Unable to intialize account ids table for for user {}:\n\t{}",
-                    uid, error
-                );
-            }
-        }
+        rs?;
         Ok(())
     }
 
@@ -193,9 +215,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next account ID within table 'account_ids' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -212,9 +232,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next transaction ID within table 'account_ids' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -232,9 +250,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next label ID within table 'account_ids' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -253,12 +269,7 @@ impl DbConn {
             )";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create: {}", error)
-            }
-        }
+        rs?;
         Ok(())
     }
 
@@ -280,7 +291,7 @@ impl DbConn {
     }
 
     pub fn add_account(&self, uid: u32, info: &AccountInfo) -> Result<u32> {
-        let aid = self.get_next_account_id(uid).unwrap();
+        let aid = self.get_next_account_id(uid)?;
         let p = rusqlite::params![
             aid,
             info.atype as usize,
@@ -297,14 +308,11 @@ impl DbConn {
         match rs {
             Ok(_) => {
                 std::mem::drop(conn_lock);
-                self.initialize_user_account_info_table(uid, aid).unwrap();
+                self.initialize_user_account_info_table(uid, aid)?;
                 Ok(aid)
             }
             Err(error) => {
-                panic!(
-                    "Unable to add account {} for user {}: {}!",
-                    &info.name, &uid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -356,18 +364,13 @@ impl DbConn {
 
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create: {}", error)
-            }
-        }
+        rs?;
 
         Ok(())
     }
 
     pub fn add_account_transaction(&self, uid: u32, info: AccountTransaction) -> Result<u32> {
-        let tid = self.get_next_transaction_id(uid).unwrap();
+        let tid = self.get_next_transaction_id(uid)?;
         let p = rusqlite::params![
             tid,
             info.from_account,
@@ -382,10 +385,7 @@ impl DbConn {
         match rs {
             Ok(_) => Ok(tid),
             Err(error) => {
-                panic!(
-                    "Unable to add transaction between {} and {}!",
-                    &info.from_account, &info.to_account
-                );
+                return Err(error);
             }
         }
     }
@@ -416,7 +416,7 @@ impl DbConn {
                         },
                     })
                 });
-                Ok(Some(record.unwrap()))
+                Ok(Some(record?))
             }
             false => Ok(None),
         }
@@ -448,12 +448,33 @@ impl DbConn {
                         },
                     })
                 });
-                Ok(Some(record.unwrap()))
+                Ok(Some(record?))
             }
             false => Ok(None),
         }
     }
 
+    // every transfer recorded between the user's accounts
+    pub fn get_account_transactions(&self, uid: u32) -> Result<Vec<AccountTransactionRecord>> {
+        let sql = "SELECT id, from_account_id, to_account_id, from_ledger_id, to_ledger_id FROM account_transactions WHERE uid = (?1) ORDER BY id";
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        let records = stmt
+            .query_map(rusqlite::params![uid], |row| {
+                Ok(AccountTransactionRecord {
+                    id: row.get(0)?,
+                    info: AccountTransaction {
+                        from_account: row.get(1)?,
+                        to_account: row.get(2)?,
+                        from_ledger: row.get(3)?,
+                        to_ledger: row.get(4)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(records)
+    }
+
     pub fn remove_account_transaction(
         &self,
         uid: u32,
@@ -513,9 +534,47 @@ impl DbConn {
         Ok(ledger_id)
     }
 
+    // The accounts the user owns followed by those shared with them.
     pub fn get_user_accounts(&self, uid: u32) -> rusqlite::Result<Vec<AccountRecord>, Error> {
-        let sql: &str = "SELECT * FROM accounts WHERE uid = (?1)";
-        let p = rusqlite::params![uid];
+        let mut accounts = self.get_owned_accounts(uid)?;
+        let sql: &str = "SELECT a.id, a.type, a.name, a.stocks, a.bank, a.ledger, a.budget, a.uid, m.access, COALESCE(m.ownership, (?2))
+            FROM account_members as m
+            INNER JOIN accounts as a ON a.uid = m.uid and a.id = m.aid
+            WHERE m.member_uid = (?1)
+            ORDER BY a.uid, a.id";
+        let p = rusqlite::params![uid, FULL_OWNERSHIP];
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        let shared = stmt
+            .query_map(p, |row| {
+                Ok(AccountRecord {
+                    id: row.get(0)?,
+                    info: AccountInfo {
+                        atype: AccountType::from_repr(row.get::<_, u32>(1)? as usize).unwrap(),
+                        name: row.get(2)?,
+                        has_stocks: row.get(3)?,
+                        has_bank: row.get(4)?,
+                        has_ledger: row.get(5)?,
+                        has_budget: row.get(6)?,
+                    },
+                    owner: row.get(7)?,
+                    access: AccountAccess::from_repr(row.get::<_, u32>(8)? as usize)
+                        .unwrap_or(AccountAccess::Read),
+                    ownership: row.get(9)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, Error>>()?;
+        accounts.extend(shared);
+        Ok(accounts)
+    }
+
+    // Only the accounts whose rows are keyed by the user. Transfers can only be
+    // recorded between these, as both ledgers must belong to the same user.
+    pub fn get_owned_accounts(&self, uid: u32) -> rusqlite::Result<Vec<AccountRecord>, Error> {
+        let sql: &str = "SELECT a.id, a.type, a.name, a.stocks, a.bank, a.ledger, a.budget,
+                (?2) - COALESCE((SELECT SUM(m.ownership) FROM account_members as m WHERE m.uid = a.uid and m.aid = a.id), 0)
+            FROM accounts as a WHERE a.uid = (?1)";
+        let p = rusqlite::params![uid, FULL_OWNERSHIP];
         let conn_lock = self.conn.lock().unwrap();
         let mut stmt = conn_lock.prepare(sql)?;
         let exists = stmt.exists(p)?;
@@ -536,12 +595,14 @@ impl DbConn {
                                 has_ledger: row.get(5)?,
                                 has_budget: row.get(6)?,
                             },
+                            owner: uid,
+                            access: AccountAccess::Owner,
+                            ownership: row.get(7)?,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for name in names {
-                    accounts.push(name.unwrap())
+                    accounts.push(name?)
                 }
                 return Ok(accounts);
             }
@@ -566,11 +627,10 @@ impl DbConn {
             true => {
                 stmt = conn_lock.prepare(sql)?;
                 let names: Vec<Result<String, Error>> = stmt
-                    .query_map(p, |row| Ok(row.get(0)?))
-                    .unwrap()
+                    .query_map(p, |row| Ok(row.get(0)?))?
                     .collect::<Vec<_>>();
                 for name in names {
-                    accounts.push(name.unwrap())
+                    accounts.push(name?)
                 }
                 return Ok(Some(accounts));
             }
@@ -608,11 +668,10 @@ impl DbConn {
             true => {
                 stmt = conn_lock.prepare(sql)?;
                 let names: Vec<Result<String, Error>> = stmt
-                    .query_map(p, |row| Ok(row.get(0)?))
-                    .unwrap()
+                    .query_map(p, |row| Ok(row.get(0)?))?
                     .collect::<Vec<_>>();
                 for name in names {
-                    accounts.push(name.unwrap())
+                    accounts.push(name?)
                 }
                 return Ok(accounts);
             }
@@ -636,13 +695,11 @@ impl DbConn {
                         return Ok(id);
                     }
                     Err(err) => {
-                        panic!("Unable to retrieve id for account {}: {}", &aname, err);
+                        return Err(err);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find account matching {}", aname);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
     pub fn get_account_name(&self, uid: u32, aid: u32) -> rusqlite::Result<String, Error> {
@@ -659,18 +716,18 @@ impl DbConn {
                         return Ok(name);
                     }
                     Err(err) => {
-                        panic!("Unable to retrieve name for account {}: {}", &aid, err);
+                        return Err(err);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find account matching {}", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
     pub fn get_account(&self, uid: u32, aid: u32) -> rusqlite::Result<AccountRecord, Error> {
-        let sql: &str = "SELECT * from accounts WHERE id = (?1) and uid = (?2)";
-        let p = rusqlite::params![aid, uid];
+        let sql: &str = "SELECT a.id, a.type, a.name, a.stocks, a.bank, a.ledger, a.budget,
+                (?3) - COALESCE((SELECT SUM(m.ownership) FROM account_members as m WHERE m.uid = a.uid and m.aid = a.id), 0)
+            FROM accounts as a WHERE a.id = (?1) and a.uid = (?2)";
+        let p = rusqlite::params![aid, uid, FULL_OWNERSHIP];
         let conn_lock = self.conn.lock().unwrap();
         let mut stmt = conn_lock.prepare(sql)?;
         let exists = stmt.exists(p)?;
@@ -688,13 +745,14 @@ impl DbConn {
                             has_ledger: row.get(5)?,
                             has_budget: row.get(6)?,
                         },
+                        owner: uid,
+                        access: AccountAccess::Owner,
+                        ownership: row.get(7)?,
                     })
                 });
                 return acct;
             }
-            false => {
-                panic!("Unable to find account matching {}", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -724,12 +782,7 @@ impl DbConn {
         )";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create account_ids table: {}", error)
-            }
-        }
+        rs?;
         Ok(())
     }
 
@@ -743,17 +796,7 @@ impl DbConn {
         ";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!(
-                    "Unable to intialize user account info for user {} account {}:\n\t{}",
-                    uid,
-                    self.get_account_name(uid, aid).unwrap(),
-                    error
-                );
-            }
-        }
+        rs?;
         Ok(())
     }
 
@@ -771,11 +814,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!(
-                    "The next stock purchase ID within table 'user_account_info' does not exist."
-                );
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -793,9 +832,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next stock sale ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -813,9 +850,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next stock sale allocation ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -833,9 +868,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next category ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -853,9 +886,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next people ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -873,9 +904,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next budget ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -893,9 +922,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next ledger ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -912,9 +939,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next stock split ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -931,9 +956,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next stock split ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -951,11 +974,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!(
-                    "The next label allocation ID within table 'user_account_info' does not exist."
-                );
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -973,9 +992,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next credit card ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -993,9 +1010,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next Roth IRA ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1013,9 +1028,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next HSA ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1033,9 +1046,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next 401k ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1053,9 +1064,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next stock price ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1073,9 +1082,7 @@ impl DbConn {
                 conn_lock.execute(sql, p)?;
                 Ok(id)
             }
-            false => {
-                panic!("The next certificate of deposit ID within table 'user_account_info' does not exist.");
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1084,30 +1091,15 @@ impl DbConn {
         let p = rusqlite::params![uid, aid];
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to remove account: {}!", error);
-            }
-        }
+        rs?;
         let sql = "UPDATE accounts SET id = id -1 WHERE id > (?2) and uid = (?1)";
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to remove account: {}!", error);
-            }
-        }
+        rs?;
 
         let p = rusqlite::params![uid];
         let sql = "UPDATE account_ids SET next_account_id = next_account_id -1 WHERE uid = (?1)";
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to remove account: {}!", error);
-            }
-        }
+        rs?;
         Ok(aid)
     }
 
@@ -1121,12 +1113,292 @@ impl DbConn {
         let sql = "UPDATE accounts SET name = (?3) WHERE uid = (?1) and id = (?2)";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to name account: {}!", error);
+        rs?;
+        Ok(aid)
+    }
+
+    // Names the two sides of each transfer between one of the accounts and an
+    // account of the same user that is not among them, moved account first.
+    // Giving the accounts away would unlink these transfers.
+    pub fn transfers_left_behind(&self, uid: u32, aids: &[u32]) -> Result<Vec<(String, String)>> {
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(
+            "SELECT t.from_account_id, f.name, t.to_account_id, a.name
+            FROM account_transactions as t
+            INNER JOIN accounts as f ON f.uid = t.uid and f.id = t.from_account_id
+            INNER JOIN accounts as a ON a.uid = t.uid and a.id = t.to_account_id
+            WHERE t.uid = (?1)
+            ORDER BY t.id",
+        )?;
+        let rows = stmt.query_map((uid,), |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        let mut left_behind = Vec::new();
+        for row in rows {
+            let (from_aid, from_name, to_aid, to_name) = row?;
+            match (aids.contains(&from_aid), aids.contains(&to_aid)) {
+                (true, false) => left_behind.push((from_name, to_name)),
+                (false, true) => left_behind.push((to_name, from_name)),
+                _ => {}
             }
         }
-        Ok(aid)
+        Ok(left_behind)
+    }
+
+    // Gives accounts of one user to another and returns their new ids. Account
+    // ids are only unique per user, so each account is renumbered into the new
+    // owner's ids, which cascades to every row of its data. Transfers between
+    // two of the moved accounts move with them. Those with an account left
+    // behind, see transfers_left_behind, are refused unless the caller agrees
+    // to unlink them, keeping the ledger entries on both sides.
+    pub fn transfer_accounts(
+        &self,
+        from: u32,
+        aids: &[u32],
+        to: u32,
+        unlink: bool,
+    ) -> crate::error::Result<Vec<u32>> {
+        let left_behind = self.transfers_left_behind(from, aids)?.len();
+        if left_behind > 0 && !unlink {
+            return Err(FintoolError::Validation(format!(
+                "{} transfer(s) link these accounts to accounts that are not being transferred",
+                left_behind
+            )));
+        }
+
+        let tx = self.begin_transaction()?;
+        {
+            // rows point at accounts that have not been moved yet until the
+            // transfer is complete
+            let conn_lock = self.conn.lock().unwrap();
+            conn_lock.pragma_update(None, "defer_foreign_keys", "on")?;
+        }
+        let previous_owner = self.get_user_name(from)?;
+
+        // links and labels refer to ids of the previous owner, so they are taken
+        // out and put back once the accounts have moved
+        let (links, allocations) = {
+            let conn_lock = self.conn.lock().unwrap();
+            let mut links = Vec::new();
+            let mut stmt = conn_lock.prepare(
+                "SELECT from_account_id, from_ledger_id, to_account_id, to_ledger_id
+                FROM account_transactions WHERE uid = (?1)",
+            )?;
+            let rows = stmt.query_map((from,), |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, u32>(3)?,
+                ))
+            })?;
+            for row in rows {
+                let link = row?;
+                if aids.contains(&link.0) || aids.contains(&link.2) {
+                    links.push(link);
+                }
+            }
+
+            let mut allocations = Vec::new();
+            let mut stmt = conn_lock.prepare(
+                "SELECT a.aid, a.id, a.ledger_id, l.label
+                FROM label_allocations as a
+                INNER JOIN labels as l ON l.uid = a.uid and l.id = a.label_id
+                WHERE a.uid = (?1)",
+            )?;
+            let rows = stmt.query_map((from,), |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?;
+            for row in rows {
+                let allocation = row?;
+                if aids.contains(&allocation.0) {
+                    allocations.push(allocation);
+                }
+            }
+
+            for aid in aids {
+                conn_lock.execute(
+                    "DELETE FROM account_transactions WHERE uid = (?1) and (from_account_id = (?2) or to_account_id = (?2))",
+                    (from, aid),
+                )?;
+                conn_lock.execute(
+                    "DELETE FROM label_allocations WHERE uid = (?1) and aid = (?2)",
+                    (from, aid),
+                )?;
+            }
+            (links, allocations)
+        };
+
+        let mut new_aids = Vec::new();
+        let mut renamed = Vec::new();
+        for aid in aids {
+            let new_aid = self.get_next_account_id(to)?;
+            let name = self.get_account_name(from, *aid)?;
+            // the new owner may already have an account by that name
+            let mut new_name = name.clone();
+            if self.account_with_name_exists(to, new_name.clone())? {
+                new_name = format!("{} ({})", name, previous_owner);
+                renamed.push((name, new_name.clone()));
+            }
+            let conn_lock = self.conn.lock().unwrap();
+            conn_lock.execute(
+                "UPDATE accounts SET uid = (?3), id = (?4), name = (?5) WHERE uid = (?1) and id = (?2)",
+                rusqlite::params![from, aid, to, new_aid, new_name],
+            )?;
+            new_aids.push(new_aid);
+        }
+        let new_aid = |aid: u32| new_aids[aids.iter().position(|x| *x == aid).unwrap()];
+
+        {
+            let conn_lock = self.conn.lock().unwrap();
+            // the new owner no longer needs to be a member of their own account
+            conn_lock.execute(
+                "DELETE FROM account_members WHERE uid = (?1) and member_uid = (?1)",
+                (to,),
+            )?;
+            // transfers name the account on the other side as a participant
+            for (name, new_name) in renamed.iter() {
+                for aid in new_aids.iter() {
+                    conn_lock.execute(
+                        "UPDATE people SET name = (?4) WHERE uid = (?1) and aid = (?2) and is_account and name = (?3)",
+                        rusqlite::params![to, aid, name, new_name],
+                    )?;
+                }
+            }
+        }
+
+        for (from_aid, from_lid, to_aid, to_lid) in links {
+            if aids.contains(&from_aid) && aids.contains(&to_aid) {
+                let id = self.get_next_transaction_id(to)?;
+                let conn_lock = self.conn.lock().unwrap();
+                conn_lock.execute(
+                    "INSERT INTO account_transactions (id, from_account_id, from_ledger_id, to_account_id, to_ledger_id, uid) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    rusqlite::params![id, new_aid(from_aid), from_lid, new_aid(to_aid), to_lid, to],
+                )?;
+            }
+        }
+
+        for (aid, id, ledger_id, label) in allocations {
+            let label_id = self.check_and_add_label(to, label)?;
+            let conn_lock = self.conn.lock().unwrap();
+            conn_lock.execute(
+                "INSERT INTO label_allocations (id, uid, aid, ledger_id, label_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, to, new_aid(aid), ledger_id, label_id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(new_aids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ledger::LedgerInfo;
+    use crate::types::participants::ParticipantType;
+    use shared_lib::TransferType;
+
+    fn add_bank_account(db: &DbConn, uid: u32, name: &str) -> u32 {
+        db.add_account(
+            uid,
+            &AccountInfo {
+                atype: AccountType::Bank,
+                name: name.to_string(),
+                has_stocks: false,
+                has_bank: true,
+                has_ledger: true,
+                has_budget: false,
+            },
+        )
+        .unwrap()
+    }
+
+    // records a transfer of $10 between the accounts of the user
+    fn link(db: &DbConn, uid: u32, from: u32, to: u32) {
+        let mut entry = |aid: u32, other: u32, transfer_type: TransferType| {
+            let name = db.get_account_name(uid, other).unwrap();
+            let pid = db
+                .check_and_add_participant(uid, aid, name, ParticipantType::Both, true)
+                .unwrap();
+            let cid = db.add_category(uid, aid, "TRANSFER".to_string()).unwrap();
+            db.add_ledger_entry(
+                uid,
+                aid,
+                LedgerInfo {
+                    date: "2024-01-02".to_string(),
+                    amount: Money::from_cents(1_000),
+                    transfer_type,
+                    participant: pid,
+                    category_id: cid,
+                    description: String::new(),
+                },
+            )
+            .unwrap()
+        };
+        let from_ledger = entry(from, to, TransferType::WithdrawalToInternalAccount);
+        let to_ledger = entry(to, from, TransferType::DepositFromInternalAccount);
+        db.add_account_transaction(
+            uid,
+            AccountTransaction {
+                from_account: from,
+                to_account: to,
+                from_ledger,
+                to_ledger,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn transfers_left_behind_are_only_unlinked_when_agreed() {
+        let db = DbConn::new(":memory:").unwrap();
+        let from = db.add_user("from".to_string(), true).unwrap();
+        let to = db.add_user("to".to_string(), false).unwrap();
+        let checking = add_bank_account(&db, from, "Checking");
+        let savings = add_bank_account(&db, from, "Savings");
+        let other = add_bank_account(&db, from, "Other");
+        link(&db, from, checking, savings);
+        link(&db, from, other, checking);
+
+        assert_eq!(
+            db.transfers_left_behind(from, &[checking, savings])
+                .unwrap(),
+            vec![("Checking".to_string(), "Other".to_string())]
+        );
+        assert!(db
+            .transfers_left_behind(from, &[checking, savings, other])
+            .unwrap()
+            .is_empty());
+
+        // refused without agreement, leaving everything in place
+        assert!(matches!(
+            db.transfer_accounts(from, &[checking, savings], to, false),
+            Err(FintoolError::Validation(_))
+        ));
+        assert_eq!(db.get_account_transactions(from).unwrap().len(), 2);
+        assert_eq!(db.get_owned_accounts(from).unwrap().len(), 3);
+
+        let moved = db
+            .transfer_accounts(from, &[checking, savings], to, true)
+            .unwrap();
+        assert_eq!(db.get_owned_accounts(to).unwrap().len(), 2);
+        assert!(db.get_account_transactions(from).unwrap().is_empty());
+        let links = db.get_account_transactions(to).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(
+            (links[0].info.from_account, links[0].info.to_account),
+            (moved[0], moved[1])
+        );
     }
 }
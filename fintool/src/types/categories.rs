@@ -45,21 +45,19 @@ impl DbConn {
                 FOREIGN KEY(uid) REFERENCES users(id)
             )";
         let conn_lock = self.conn.lock().unwrap();
-        conn_lock
-            .execute(sql, ())
-            .expect("Unable to initialize categories table!");
+        conn_lock.execute(sql, ())?;
         Ok(())
     }
 
     pub fn add_category(&self, uid: u32, aid: u32, category: String) -> Result<u32> {
-        let id = self.get_next_category_id(uid, aid).unwrap();
+        let id = self.get_next_category_id(uid, aid)?;
         let p = rusqlite::params!(id, aid, category, uid);
         let sql = "INSERT INTO categories (id, aid, category, uid) VALUES (?1, ?2, ?3, ?4)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add {} for account {}: {}", category, aid, error);
+                return Err(error);
             }
         }
     }
@@ -78,10 +76,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new),
             Err(error) => {
-                panic!(
-                    "Unable to update category {} in account {}: {}!",
-                    old, aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -106,11 +101,10 @@ impl DbConn {
                             id: row.get(0)?,
                             category: Category { name: row.get(1)? },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for cat in cats {
-                    categories.push(cat.unwrap());
+                    categories.push(cat?);
                 }
             }
             false => {}
@@ -139,13 +133,11 @@ impl DbConn {
                         return Ok(name);
                     }
                     Err(err) => {
-                        panic!("Unable to retrieve id for account {}: {}", aid, err);
+                        return Err(err);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find account matching {}", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -170,54 +162,28 @@ impl DbConn {
                         return Ok(id);
                     }
                     Err(err) => {
-                        panic!("Unable to retrieve id for account {}: {}", aid, err);
+                        return Err(err);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find account matching {}", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
-    pub fn check_and_add_category(&self, uid: u32, aid: u32, name: String) -> u32 {
+    pub fn check_and_add_category(&self, uid: u32, aid: u32, name: String) -> Result<u32> {
         let sql = "SELECT id FROM categories WHERE aid = (?1) and category = (?2) and uid = (?3)";
         let cloned_conn = self.conn.clone();
         let conn_lock = cloned_conn.lock().unwrap();
         let p = rusqlite::params![aid, name, uid];
         {
-            let prepared_stmt = conn_lock.prepare(sql);
-            match prepared_stmt {
-                Ok(mut stmt) => {
-                    if let Ok(entry_found) = stmt.exists(p) {
-                        if entry_found {
-                            let id =
-                                stmt.query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0));
-                            return id.unwrap();
-                            // {
-                            //     return id.unwrap();
-                            // } else {
-                            //     panic!("Unable to query row!");
-                            // }
-                        } else {
-                            //
-                            // self.add_category(uid, aid, name).unwrap()
-                        }
-                    } else {
-                        panic!("Unable to determine if exists!");
-                    }
-                }
-                Err(e) => {
-                    panic!(
-                        "SQLITE error {} while executing searching for category {}.",
-                        e.to_string(),
-                        name
-                    );
-                }
+            let mut stmt = conn_lock.prepare(sql)?;
+            if stmt.exists(p)? {
+                let id = stmt.query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0))?;
+                return Ok(id);
             }
         }
         std::mem::drop(conn_lock);
-        self.add_category(uid, aid, name).unwrap()
+        self.add_category(uid, aid, name)
     }
 
     pub fn remove_category(
@@ -226,39 +192,21 @@ impl DbConn {
         aid: u32,
         name: String,
     ) -> rusqlite::Result<u32, rusqlite::Error> {
-        let id = self.get_category_id(aid, name.clone(), uid).unwrap();
+        let id = self.get_category_id(aid, name.clone(), uid)?;
         let p = rusqlite::params![uid, aid, name];
         let sql = "DELETE FROM categories WHERE uid = (?1) and aid = (?2) and category = (?3)";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to remove category item: {}!", error);
-            }
-        }
+        rs?;
         let p = rusqlite::params![id, uid, aid];
         let sql = "UPDATE categories SET id = id-1 WHERE id > ?1 and uid = ?2 and aid = ?3";
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to update category ids: {}!", error);
-            }
-        }
+        rs?;
 
         let p = rusqlite::params![uid, aid];
         let sql = "UPDATE user_account_info SET cid = cid - 1 WHERE uid = ?1 and aid = ?2";
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!(
-                    "Unable to update 'cid' value in 'user_account_info': {}!",
-                    error
-                );
-            }
-        }
+        rs?;
 
         Ok(id)
     }
@@ -282,8 +230,7 @@ impl Autocomplete for CategoryAutoCompleter {
                 .collect::<Vec<String>>()
         } else {
             self.db
-                .get_categories(self.uid, self.aid)
-                .unwrap()
+                .get_categories(self.uid, self.aid)?
                 .into_iter()
                 .map(|category| category.category.name)
                 .filter(|cname| cname.starts_with(input.to_ascii_uppercase().as_str()))
@@ -301,9 +248,7 @@ impl Autocomplete for CategoryAutoCompleter {
         Ok(match highlighted_suggestion {
             Some(suggestion) => Replacement::Some(suggestion),
             None => {
-                let suggestions = self
-                    .get_suggestions(input.to_ascii_uppercase().as_str())
-                    .unwrap();
+                let suggestions = self.get_suggestions(input.to_ascii_uppercase().as_str())?;
                 if suggestions.len() == 0 {
                     autocompletion::Replacement::None
                 } else {
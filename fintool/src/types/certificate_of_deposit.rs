@@ -15,6 +15,7 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use rusqlite::Result;
+use shared_lib::Money;
 
 use crate::database::DbConn;
 
@@ -27,7 +28,7 @@ pub struct CertificateOfDepositRecord {
 #[derive(Clone)]
 pub struct CertificateOfDepositInfo {
     pub apy: f32,
-    pub principal: f32,
+    pub principal: Money,
     pub maturity_date: String,
     pub length_months: u32,
 }
@@ -40,18 +41,14 @@ impl DbConn {
                 uid         INTEGER NOT NULL,
                 apy         REAL NOT NULL, 
                 maturity_date STRING NOT NULL,
-                principal   REAL NOT NULL, 
+                principal   INTEGER NOT NULL, 
                 length_months INTEGER NOT NULL,
                 PRIMARY KEY (uid, aid, id),
                 FOREIGN KEY(uid,aid) REFERENCES accounts(uid,id) ON DELETE CASCADE ON UPDATE CASCADE,
                 FOREIGN KEY(uid) REFERENCES users(id)
             )";
 
-        self.conn
-            .lock()
-            .unwrap()
-            .execute(sql, ())
-            .expect("Unable to initialize certificate_of_deposits table!");
+        self.conn.lock().unwrap().execute(sql, ())?;
         Ok(())
     }
 
@@ -61,7 +58,7 @@ impl DbConn {
         aid: u32,
         info: CertificateOfDepositInfo,
     ) -> Result<u32> {
-        let id = self.get_next_credit_card_id(uid, aid).unwrap();
+        let id = self.get_next_credit_card_id(uid, aid)?;
         let p = rusqlite::params!(
             id,
             aid,
@@ -76,10 +73,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!(
-                    "Unable to add certificate_of_deposit for account {}: {}",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -91,10 +85,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_apy),
             Err(error) => {
-                panic!(
-                    "Unable to update apy for certificate of deposit {}: {}!",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -111,15 +102,12 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_maturity_date),
             Err(error) => {
-                panic!(
-                    "Unable to update maturity date for certificate of deposit {}: {}!",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
 
-    pub fn update_cd_principal(&self, uid: u32, aid: u32, new_principal: f32) -> Result<f32> {
+    pub fn update_cd_principal(&self, uid: u32, aid: u32, new_principal: Money) -> Result<Money> {
         let p = rusqlite::params!(uid, aid, new_principal);
         let sql =
             "UPDATE certificate_of_deposits SET principal = (?3) WHERE uid = (?1) and aid = (?2)";
@@ -127,10 +115,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_principal),
             Err(error) => {
-                panic!(
-                    "Unable to update principal for certificate of deposit {}: {}!",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -142,10 +127,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_length),
             Err(error) => {
-                panic!(
-                    "Unable to update length for certificate of deposit {}: {}!",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -177,19 +159,11 @@ impl DbConn {
                 match cd_wrap {
                     Ok(cd) => return Ok(cd),
                     Err(error) => {
-                        panic!(
-                            "Unable to retrieve certificate of deposit info for account {}: {}",
-                            aid, error
-                        )
+                        return Err(error);
                     }
                 }
             }
-            false => {
-                panic!(
-                    "Unable to find certificate of deposit matching account id: {}!",
-                    aid
-                );
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 }
@@ -15,6 +15,7 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use rusqlite::Result;
+use shared_lib::Money;
 
 use crate::database::DbConn;
 
@@ -26,7 +27,7 @@ pub struct CreditCardRecord {
 
 #[derive(Clone)]
 pub struct CreditCardInfo {
-    pub credit_line: f32,
+    pub credit_line: Money,
     // this is the day of each month that
     // an owed amount is due
     pub statement_due_date: u32,
@@ -46,36 +47,31 @@ impl DbConn {
             )";
 
         let conn_lock = self.conn.lock().unwrap();
-        conn_lock
-            .execute(sql, ())
-            .expect("Unable to initialize credit_cards table!");
+        conn_lock.execute(sql, ())?;
         Ok(())
     }
 
     pub fn add_credit_card(&self, uid: u32, aid: u32, info: CreditCardInfo) -> Result<u32> {
-        let id = self.get_next_credit_card_id(uid, aid).unwrap();
+        let id = self.get_next_credit_card_id(uid, aid)?;
         let p = rusqlite::params!(id, aid, uid, info.credit_line, info.statement_due_date);
         let sql = "INSERT INTO credit_cards (id, aid, uid, credit_line, statement_due_date) VALUES (?1, ?2, ?3, ?4, ?5)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add credit card for account {}: {}", aid, error);
+                return Err(error);
             }
         }
     }
 
-    pub fn update_credit_line(&self, uid: u32, aid: u32, new_credit_line: f32) -> Result<f32> {
+    pub fn update_credit_line(&self, uid: u32, aid: u32, new_credit_line: Money) -> Result<Money> {
         let p = rusqlite::params!(uid, aid, new_credit_line);
         let sql = "UPDATE credit_cards SET credit_line = (?3) WHERE uid = (?1) and aid = (?2)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_credit_line),
             Err(error) => {
-                panic!(
-                    "Unable to update credit line for credit card {}: {}!",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -93,10 +89,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_statement_due_date),
             Err(error) => {
-                panic!(
-                    "Unable to update statement due date for credit card {}: {}!",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -122,16 +115,11 @@ impl DbConn {
                 match cc_wrap {
                     Ok(cc) => return Ok(cc),
                     Err(error) => {
-                        panic!(
-                            "Unable to retrieve credit card info for account {}: {}",
-                            aid, error
-                        )
+                        return Err(error);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find credit card matching account id: {}!", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 }
@@ -0,0 +1,106 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use fin_import::mapping::{CsvMapping, SignConvention};
+use rusqlite::{OptionalExtension, Result};
+
+use crate::database::DbConn;
+
+// The column mapping an account's CSV exports are read with, saved so that it
+// does not have to be set up again on every import.
+
+impl DbConn {
+    pub fn create_csv_mappings_table(&self) -> Result<()> {
+        let sql = "CREATE TABLE IF NOT EXISTS csv_mappings (
+            uid                 INTEGER NOT NULL,
+            aid                 INTEGER NOT NULL,
+            date_column         TEXT NOT NULL,
+            date_format         TEXT NOT NULL,
+            amount_column       TEXT,
+            debit_column        TEXT,
+            credit_column       TEXT,
+            negative_is_deposit BOOLEAN NOT NULL,
+            payee_column        TEXT,
+            category_column     TEXT,
+            description_column  TEXT,
+            PRIMARY KEY (uid, aid),
+            FOREIGN KEY (uid, aid) REFERENCES accounts(uid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid) REFERENCES users(id)
+        )";
+        self.conn.lock().unwrap().execute(sql, ())?;
+        Ok(())
+    }
+
+    pub fn get_csv_mapping(&self, uid: u32, aid: u32) -> Result<Option<CsvMapping>> {
+        let sql = "SELECT date_column, date_format, amount_column, debit_column, credit_column,
+                negative_is_deposit, payee_column, category_column, description_column
+            FROM csv_mappings WHERE uid = (?1) and aid = (?2)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock
+            .query_row(sql, (uid, aid), |row| {
+                let negative_is_deposit: bool = row.get(5)?;
+                Ok(CsvMapping {
+                    date_column: row.get(0)?,
+                    date_format: row.get(1)?,
+                    amount_column: row.get(2)?,
+                    debit_column: row.get(3)?,
+                    credit_column: row.get(4)?,
+                    sign_convention: if negative_is_deposit {
+                        SignConvention::NegativeIsDeposit
+                    } else {
+                        SignConvention::NegativeIsWithdrawal
+                    },
+                    payee_column: row.get(6)?,
+                    category_column: row.get(7)?,
+                    description_column: row.get(8)?,
+                })
+            })
+            .optional()
+    }
+
+    pub fn set_csv_mapping(&self, uid: u32, aid: u32, mapping: &CsvMapping) -> Result<()> {
+        let sql = "INSERT INTO csv_mappings (uid, aid, date_column, date_format, amount_column,
+                debit_column, credit_column, negative_is_deposit, payee_column, category_column,
+                description_column)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            ON CONFLICT (uid, aid) DO UPDATE SET
+                date_column = excluded.date_column,
+                date_format = excluded.date_format,
+                amount_column = excluded.amount_column,
+                debit_column = excluded.debit_column,
+                credit_column = excluded.credit_column,
+                negative_is_deposit = excluded.negative_is_deposit,
+                payee_column = excluded.payee_column,
+                category_column = excluded.category_column,
+                description_column = excluded.description_column";
+        let p = rusqlite::params![
+            uid,
+            aid,
+            mapping.date_column,
+            mapping.date_format,
+            mapping.amount_column,
+            mapping.debit_column,
+            mapping.credit_column,
+            mapping.sign_convention == SignConvention::NegativeIsDeposit,
+            mapping.payee_column,
+            mapping.category_column,
+            mapping.description_column
+        ];
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, p)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use rusqlite::{OptionalExtension, Result};
+use shared_lib::{DistributionType, Money};
+
+use crate::database::DbConn;
+
+// Dividends, capital gain distributions and interest a holding pays are
+// deposits from the holding. These tables remember which ledger entries are
+// distributions and which purchases reinvested them.
+
+#[derive(Debug, Clone)]
+pub struct DistributionRecord {
+    pub ledger_id: u32,
+    pub kind: DistributionType,
+    pub ticker: String,
+    pub date: String,
+    pub amount: Money,
+    // the part of the distribution that bought more of the holding
+    pub reinvested: Money,
+}
+
+impl DbConn {
+    pub fn create_stock_distributions_table(&self) -> Result<()> {
+        let sql = "CREATE TABLE IF NOT EXISTS stock_distributions (
+            uid         INTEGER NOT NULL,
+            aid         INTEGER NOT NULL,
+            lid         INTEGER NOT NULL,
+            kind        INTEGER NOT NULL,
+            PRIMARY KEY (uid, aid, lid),
+            FOREIGN KEY (uid, aid) REFERENCES accounts(uid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid, aid, lid) REFERENCES ledgers(uid, aid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid) REFERENCES users(id)
+        )";
+        self.conn.lock().unwrap().execute(sql, ())?;
+        Ok(())
+    }
+
+    pub fn create_stock_reinvestments_table(&self) -> Result<()> {
+        let sql = "CREATE TABLE IF NOT EXISTS stock_reinvestments (
+            uid             INTEGER NOT NULL,
+            aid             INTEGER NOT NULL,
+            lid             INTEGER NOT NULL,
+            distribution_lid INTEGER NOT NULL,
+            PRIMARY KEY (uid, aid, lid),
+            FOREIGN KEY (uid, aid) REFERENCES accounts(uid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid, aid, lid) REFERENCES ledgers(uid, aid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid, aid, distribution_lid) REFERENCES stock_distributions(uid, aid, lid) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid) REFERENCES users(id)
+        )";
+        self.conn.lock().unwrap().execute(sql, ())?;
+        Ok(())
+    }
+
+    pub fn add_stock_distribution(
+        &self,
+        uid: u32,
+        aid: u32,
+        lid: u32,
+        kind: DistributionType,
+    ) -> Result<()> {
+        let sql = "INSERT INTO stock_distributions (uid, aid, lid, kind) VALUES (?1, ?2, ?3, ?4)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, (uid, aid, lid, kind as u32))?;
+        Ok(())
+    }
+
+    // Records that the purchase at lid was paid for by the distribution.
+    pub fn add_stock_reinvestment(
+        &self,
+        uid: u32,
+        aid: u32,
+        lid: u32,
+        distribution_lid: u32,
+    ) -> Result<()> {
+        let sql = "INSERT INTO stock_reinvestments (uid, aid, lid, distribution_lid) VALUES (?1, ?2, ?3, ?4)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, (uid, aid, lid, distribution_lid))?;
+        Ok(())
+    }
+
+    // Returns the latest distribution of the ticker on the date that has not
+    // been reinvested yet.
+    pub fn get_unreinvested_distribution(
+        &self,
+        uid: u32,
+        aid: u32,
+        ticker: &str,
+        date: &str,
+    ) -> Result<Option<u32>> {
+        let sql = "
+            SELECT sd.lid
+            FROM stock_distributions AS sd
+            INNER JOIN ledgers AS l ON
+                sd.lid = l.id and
+                sd.aid = l.aid and
+                sd.uid = l.uid
+            INNER JOIN people AS p ON
+                l.pid = p.id and
+                l.aid = p.aid and
+                l.uid = p.uid
+            WHERE
+                sd.uid = (?1) and
+                sd.aid = (?2) and
+                p.name = (?3) and
+                l.date = (?4) and
+                NOT EXISTS (
+                    SELECT 1 FROM stock_reinvestments AS sr
+                    WHERE sr.uid = sd.uid and sr.aid = sd.aid and sr.distribution_lid = sd.lid
+                )
+            ORDER BY sd.lid DESC
+            LIMIT 1";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock
+            .query_row(sql, (uid, aid, ticker, date), |row| row.get(0))
+            .optional()
+    }
+
+    // Returns every distribution of the account, oldest first.
+    pub fn get_distributions(&self, uid: u32, aid: u32) -> Result<Vec<DistributionRecord>> {
+        let sql = "
+            SELECT
+                sd.lid,
+                sd.kind,
+                p.name,
+                l.date,
+                l.amount,
+                COALESCE((
+                    SELECT SUM(rl.amount)
+                    FROM stock_reinvestments AS sr
+                    INNER JOIN ledgers AS rl ON
+                        sr.lid = rl.id and
+                        sr.aid = rl.aid and
+                        sr.uid = rl.uid
+                    WHERE sr.uid = sd.uid and sr.aid = sd.aid and sr.distribution_lid = sd.lid
+                ), 0)
+            FROM stock_distributions AS sd
+            INNER JOIN ledgers AS l ON
+                sd.lid = l.id and
+                sd.aid = l.aid and
+                sd.uid = l.uid
+            INNER JOIN people AS p ON
+                l.pid = p.id and
+                l.aid = p.aid and
+                l.uid = p.uid
+            WHERE
+                sd.uid = (?1) and
+                sd.aid = (?2)
+            ORDER BY l.date, sd.lid";
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        let rows = stmt.query_map((uid, aid), |row| {
+            let kind: u32 = row.get(1)?;
+            Ok(DistributionRecord {
+                ledger_id: row.get(0)?,
+                kind: DistributionType::from_repr(kind as usize)
+                    .ok_or_else(|| rusqlite::Error::IntegralValueOutOfRange(1, kind as i64))?,
+                ticker: row.get(2)?,
+                date: row.get(3)?,
+                amount: row.get(4)?,
+                reinvested: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
@@ -0,0 +1,124 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use rusqlite::{OptionalExtension, Result};
+
+use crate::database::DbConn;
+
+// Financial institutions identify accounts and transactions in their own
+// downloads, such as the BANKACCTFROM and FITID of an OFX statement. These
+// tables remember which fintool account and ledger entry they correspond to.
+
+impl DbConn {
+    pub fn create_external_accounts_table(&self) -> Result<()> {
+        let sql = "CREATE TABLE IF NOT EXISTS external_accounts (
+            uid             INTEGER NOT NULL,
+            institution_id  TEXT NOT NULL,
+            account_id      TEXT NOT NULL,
+            aid             INTEGER NOT NULL,
+            PRIMARY KEY (uid, institution_id, account_id),
+            FOREIGN KEY (uid, aid) REFERENCES accounts(uid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid) REFERENCES users(id)
+        )";
+        self.conn.lock().unwrap().execute(sql, ())?;
+        Ok(())
+    }
+
+    pub fn create_external_transactions_table(&self) -> Result<()> {
+        let sql = "CREATE TABLE IF NOT EXISTS external_transactions (
+            uid         INTEGER NOT NULL,
+            aid         INTEGER NOT NULL,
+            lid         INTEGER NOT NULL,
+            external_id TEXT NOT NULL,
+            PRIMARY KEY (uid, aid, lid),
+            UNIQUE (uid, aid, external_id),
+            FOREIGN KEY (uid, aid, lid) REFERENCES ledgers(uid, aid, id) ON DELETE CASCADE ON UPDATE CASCADE,
+            FOREIGN KEY (uid) REFERENCES users(id)
+        )";
+        self.conn.lock().unwrap().execute(sql, ())?;
+        Ok(())
+    }
+
+    // Returns the account the institution's account has been linked to.
+    pub fn get_external_account(
+        &self,
+        uid: u32,
+        institution_id: &str,
+        account_id: &str,
+    ) -> Result<Option<u32>> {
+        let sql = "SELECT aid FROM external_accounts WHERE uid = (?1) and institution_id = (?2) and account_id = (?3)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock
+            .query_row(sql, (uid, institution_id, account_id), |row| row.get(0))
+            .optional()
+    }
+
+    pub fn set_external_account(
+        &self,
+        uid: u32,
+        institution_id: &str,
+        account_id: &str,
+        aid: u32,
+    ) -> Result<()> {
+        let sql = "INSERT INTO external_accounts (uid, institution_id, account_id, aid)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (uid, institution_id, account_id) DO UPDATE SET aid = excluded.aid";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, (uid, institution_id, account_id, aid))?;
+        Ok(())
+    }
+
+    pub fn add_external_transaction_id(
+        &self,
+        uid: u32,
+        aid: u32,
+        lid: u32,
+        external_id: &str,
+    ) -> Result<()> {
+        let sql = "INSERT INTO external_transactions (uid, aid, lid, external_id) VALUES (?1, ?2, ?3, ?4)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, (uid, aid, lid, external_id))?;
+        Ok(())
+    }
+
+    // Returns the external id the ledger entry was imported under.
+    pub fn get_external_transaction_id(
+        &self,
+        uid: u32,
+        aid: u32,
+        lid: u32,
+    ) -> Result<Option<String>> {
+        let sql = "SELECT external_id FROM external_transactions WHERE uid = (?1) and aid = (?2) and lid = (?3)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock
+            .query_row(sql, (uid, aid, lid), |row| row.get(0))
+            .optional()
+    }
+
+    // Returns the ledger entry already imported under the external id.
+    pub fn get_ledger_id_by_external_id(
+        &self,
+        uid: u32,
+        aid: u32,
+        external_id: &str,
+    ) -> Result<Option<u32>> {
+        let sql = "SELECT lid FROM external_transactions WHERE uid = (?1) and aid = (?2) and external_id = (?3)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock
+            .query_row(sql, (uid, aid, external_id), |row| row.get(0))
+            .optional()
+    }
+}
@@ -15,6 +15,7 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use rusqlite::Result;
+use shared_lib::Money;
 
 use crate::database::DbConn;
 
@@ -26,14 +27,14 @@ pub struct HsaRecord {
 
 #[derive(Clone)]
 pub struct HsaInfo {
-    pub contribution_limit: f32,
+    pub contribution_limit: Money,
 }
 
 impl DbConn {
     pub fn create_hsa_accounts_table(&self) -> Result<()> {
         let sql: &str = "CREATE TABLE IF NOT EXISTS hsas ( 
                 id          INTEGER NOT NULL,
-                contribution_limit REAL NOT NULL,
+                contribution_limit INTEGER NOT NULL,
                 uid  INTEGER NOT NULL, 
                 aid INTEGER NOT NULL,
                 PRIMARY KEY (uid, aid, id),
@@ -42,21 +43,19 @@ impl DbConn {
             )";
 
         let conn_lock = self.conn.lock().unwrap();
-        conn_lock
-            .execute(sql, ())
-            .expect("Unable to initialize HSAs table!");
+        conn_lock.execute(sql, ())?;
         Ok(())
     }
 
     pub fn add_hsa_account(&self, uid: u32, aid: u32, info: HsaInfo) -> Result<u32> {
-        let id = self.get_next_hsa_id(uid, aid).unwrap();
+        let id = self.get_next_hsa_id(uid, aid)?;
         let p = rusqlite::params!(id, aid, uid, info.contribution_limit);
         let sql = "INSERT INTO hsas (id, aid, uid, contribution_limit) VALUES (?1, ?2, ?3, ?4)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add HSA {}: {}", aid, error);
+                return Err(error);
             }
         }
     }
@@ -65,15 +64,15 @@ impl DbConn {
         &self,
         uid: u32,
         aid: u32,
-        new_contribution_lmit: f32,
-    ) -> Result<f32> {
+        new_contribution_lmit: Money,
+    ) -> Result<Money> {
         let p = rusqlite::params!(uid, aid, new_contribution_lmit);
         let sql = "UPDATE hsas SET contribution_limit = (?3) WHERE uid = (?1) and aid = (?2)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_contribution_lmit),
             Err(error) => {
-                panic!("Unable to update hsa for hsa {}: {}!", aid, error);
+                return Err(error);
             }
         }
     }
@@ -98,13 +97,11 @@ impl DbConn {
                 match cc_wrap {
                     Ok(cc) => return Ok(cc),
                     Err(error) => {
-                        panic!("Unable to retrieve hsa info for account {}: {}", aid, error)
+                        return Err(error);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find hsa matching account id: {}!", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 }
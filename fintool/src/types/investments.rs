@@ -18,10 +18,11 @@ use crate::database::DbConn;
 use chrono::{Days, NaiveDate};
 use rusqlite::{Error, Result};
 use shared_lib::stocks::get_stock_at_close;
-use shared_lib::TransferType;
+use shared_lib::{Money, Quantity, TransferType};
 use std::collections::VecDeque;
 use std::collections::{HashMap, HashSet};
 use std::time::{Duration, UNIX_EPOCH};
+use strum::{Display, EnumIter, FromRepr};
 use time::OffsetDateTime;
 use yahoo_finance_api::Quote;
 
@@ -30,9 +31,9 @@ use super::participants;
 
 #[derive(Debug, Clone)]
 pub struct StockInfo {
-    pub shares: f32,
-    pub costbasis: f32,
-    pub remaining: f32,
+    pub shares: Quantity,
+    pub costbasis: Quantity,
+    pub remaining: Quantity,
     pub ledger_id: u32,
 }
 
@@ -43,10 +44,31 @@ pub struct StockRecord {
     pub txn_opt: Option<LedgerInfo>,
 }
 
+// How the shares of a sale are matched to the lots they were bought in.
+#[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, FromRepr)]
+pub enum SaleMethod {
+    // oldest lots first
+    #[strum(to_string = "FIFO")]
+    Fifo,
+    // newest lots first
+    #[strum(to_string = "LIFO")]
+    Lifo,
+    // lots and quantities chosen by the user
+    #[strum(to_string = "Specific Identification")]
+    SpecificIdentification,
+    // the shares sold cost the average of the holding, as mutual funds
+    // report, and are taken from the oldest lots first
+    #[strum(to_string = "Average Cost")]
+    AverageCost,
+    // the most expensive lots first, to realize as little gain as possible
+    #[strum(to_string = "Highest Cost")]
+    HighestCost,
+}
+
 pub struct SaleAllocationInfo {
     pub purchase_id: u32,
     pub sale_id: u32,
-    pub quantity: f32,
+    pub quantity: Quantity,
 }
 
 pub struct SaleAllocationRecord {
@@ -56,7 +78,7 @@ pub struct SaleAllocationRecord {
 
 #[derive(Debug, Clone)]
 pub struct StockSplitInfo {
-    pub split: f32,
+    pub split: Quantity,
     pub ledger_id: u32,
 }
 
@@ -81,9 +103,9 @@ impl DbConn {
     pub fn create_investment_purchase_table(&self) -> Result<()> {
         let sql: &str = "CREATE TABLE IF NOT EXISTS stock_purchases (
             id          INTEGER NOT NULL,
-            shares      REAL NOT NULL,
-            costbasis   REAL NOT NULL,
-            remaining   REAL NOT NULL,
+            shares      INTEGER NOT NULL,
+            costbasis   INTEGER NOT NULL,
+            remaining   INTEGER NOT NULL,
             aid         INTEGER NOT NULL, 
             lid         INTEGER NOT NULL,
             uid         INTEGER NOT NULL, 
@@ -96,10 +118,7 @@ impl DbConn {
         match conn_lock.execute(sql, ()) {
             Ok(_) => {}
             Err(error) => {
-                panic!(
-                    "Unable to create table 'stock_purchases' because: {}",
-                    error
-                );
+                return Err(error);
             }
         }
         Ok(())
@@ -108,11 +127,12 @@ impl DbConn {
     pub fn create_investment_sale_table(&self) -> Result<()> {
         let sql: &str = "CREATE TABLE IF NOT EXISTS stock_sales (
             id          INTEGER NOT NULL,
-            shares      REAL NOT NULL,
-            price       REAL NOT NULL,
+            shares      INTEGER NOT NULL,
+            price       INTEGER NOT NULL,
             aid         INTEGER NOT NULL,
             lid         INTEGER NOT NULL,
             uid         INTEGER NOT NULL,
+            method      INTEGER NOT NULL DEFAULT 0,
             PRIMARY KEY (uid, aid, id),
             FOREIGN     KEY (uid,aid) REFERENCES accounts(uid,id) ON DELETE CASCADE ON UPDATE CASCADE,
             FOREIGN     KEY (uid, aid, lid) REFERENCES ledgers(uid, aid, id) ON DELETE CASCADE ON UPDATE CASCADE,
@@ -122,7 +142,7 @@ impl DbConn {
         match conn_lock.execute(sql, ()) {
             Ok(_) => {}
             Err(error) => {
-                panic!("Unable to create table 'investment' because: {}", error);
+                return Err(error);
             }
         }
         Ok(())
@@ -133,7 +153,8 @@ impl DbConn {
             id          INTEGER NOT NULL,
             purchase_id INTEGER NOT NULL, 
             sale_id     INTEGER NOT NULL,
-            quantity    REAL NOT NULL,
+            quantity    INTEGER NOT NULL,
+            basis       INTEGER,
             uid         INTEGER NOT NULL,
             aid         INTEGER NOT NULL,
             PRIMARY KEY (uid, aid, id),
@@ -146,10 +167,7 @@ impl DbConn {
         match conn_lock.execute(sql, ()) {
             Ok(_) => {}
             Err(error) => {
-                panic!(
-                    "Unable to create table 'stock_sale_allocation' because: {}",
-                    error
-                );
+                return Err(error);
             }
         }
         Ok(())
@@ -158,7 +176,7 @@ impl DbConn {
     pub fn create_stock_split_table(&self) -> Result<()> {
         let sql: &str = "CREATE TABLE IF NOT EXISTS stock_splits (
             id          INTEGER NOT NULL,
-            split       REAL NOT NULL,
+            split       INTEGER NOT NULL,
             aid         INTEGER NOT NULL,
             lid         INTEGER NOT NULL,
             uid         INTEGER NOT NULL,
@@ -171,7 +189,7 @@ impl DbConn {
         match conn_lock.execute(sql, ()) {
             Ok(_) => {}
             Err(error) => {
-                panic!("Unable to create table 'stock_splits' because: {}", error);
+                return Err(error);
             }
         }
         Ok(())
@@ -194,17 +212,14 @@ impl DbConn {
         match conn_lock.execute(sql, ()) {
             Ok(_) => {}
             Err(error) => {
-                panic!(
-                    "Unable to create 'stock_split_allocations_table' because :{}",
-                    error
-                );
+                return Err(error);
             }
         }
         Ok(())
     }
 
     pub fn add_stock_purchase(&self, uid: u32, aid: u32, record: StockInfo) -> Result<u32> {
-        let id = self.get_next_stock_purchase_id(uid, aid).unwrap();
+        let id = self.get_next_stock_purchase_id(uid, aid)?;
         let p = rusqlite::params!(
             id,
             record.shares,
@@ -219,10 +234,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!(
-                    "Unable to add purchase of stock for account {}: {}",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -254,7 +266,7 @@ impl DbConn {
                         txn_opt: None,
                     })
                 });
-                Ok(Some(record.unwrap()))
+                Ok(Some(record?))
             }
             false => Ok(None),
         }
@@ -279,7 +291,7 @@ impl DbConn {
                 l.transfer_type,
                 l.pid, 
                 l.cid,
-                l.desc,
+                l.desc
             FROM stock_purchases p 
             INNER JOIN ledgers l ON 
                 p.aid = l.aid and  
@@ -312,7 +324,7 @@ impl DbConn {
                         }),
                     })
                 });
-                Ok(Some(record.unwrap()))
+                Ok(Some(record?))
             }
             false => Ok(None),
         }
@@ -339,13 +351,13 @@ impl DbConn {
                         info: StockInfo {
                             shares: row.get(1)?,
                             costbasis: row.get(2)?,
-                            remaining: 0.0,
+                            remaining: Quantity::ZERO,
                             ledger_id: row.get(3)?,
                         },
                         txn_opt: None,
                     })
                 });
-                Ok(Some(record.unwrap()))
+                Ok(Some(record?))
             }
             false => Ok(None),
         }
@@ -369,7 +381,7 @@ impl DbConn {
                 l.transfer_type,
                 l.pid, 
                 l.cid,
-                l.desc,
+                l.desc
             FROM stock_sales s 
             INNER JOIN ledgers l ON 
                 s.aid = l.aid and  
@@ -390,7 +402,7 @@ impl DbConn {
                         info: StockInfo {
                             shares: row.get(1)?,
                             costbasis: row.get(2)?,
-                            remaining: 0.0,
+                            remaining: Quantity::ZERO,
                             ledger_id: row.get(3)?,
                         },
                         txn_opt: Some(LedgerInfo {
@@ -403,7 +415,7 @@ impl DbConn {
                         }),
                     })
                 });
-                Ok(Some(record.unwrap()))
+                Ok(Some(record?))
             }
             false => Ok(None),
         }
@@ -426,7 +438,7 @@ impl DbConn {
                 l.transfer_type,
                 l.pid, 
                 l.cid,
-                l.desc,
+                l.desc
             FROM stock_splits s 
             INNER JOIN ledgers l ON 
                 s.aid = l.aid and  
@@ -458,7 +470,7 @@ impl DbConn {
                         }),
                     })
                 });
-                Ok(Some(record.unwrap()))
+                Ok(Some(record?))
             }
             false => Ok(None),
         }
@@ -490,11 +502,10 @@ impl DbConn {
                                 quantity: row.get(3)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for record in records {
-                    sale_allocation_records.push(record.unwrap());
+                    sale_allocation_records.push(record?);
                 }
                 Ok(Some(sale_allocation_records))
             }
@@ -503,7 +514,7 @@ impl DbConn {
     }
 
     pub fn add_stock_sale(&self, uid: u32, aid: u32, sale_record: StockInfo) -> Result<u32> {
-        let id = self.get_next_stock_sale_id(uid, aid).unwrap();
+        let id = self.get_next_stock_sale_id(uid, aid)?;
         let p = rusqlite::params!(
             id,
             sale_record.shares,
@@ -517,27 +528,51 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add sale of stock for account {}: {}", aid, error);
+                return Err(error);
             }
         }
     }
 
+    // Remembers how the sale was allocated, so that it is allocated the same
+    // way when it is edited.
+    pub fn set_stock_sale_method(
+        &self,
+        uid: u32,
+        aid: u32,
+        sale_id: u32,
+        method: SaleMethod,
+    ) -> Result<()> {
+        let sql =
+            "UPDATE stock_sales SET method = (?1) WHERE id = (?2) and uid = (?3) and aid = (?4)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, (method as u32, sale_id, uid, aid))?;
+        Ok(())
+    }
+
+    pub fn get_stock_sale_method(&self, uid: u32, aid: u32, sale_id: u32) -> Result<SaleMethod> {
+        let sql = "SELECT method FROM stock_sales WHERE id = (?1) and uid = (?2) and aid = (?3)";
+        let conn_lock = self.conn.lock().unwrap();
+        let method: u32 = conn_lock.query_row(sql, (sale_id, uid, aid), |row| row.get(0))?;
+        SaleMethod::from_repr(method as usize)
+            .ok_or(rusqlite::Error::IntegralValueOutOfRange(0, method as i64))
+    }
+
     pub fn add_stock_sale_allocation(
         &self,
         uid: u32,
         aid: u32,
         buy_id: u32,
         sell_id: u32,
-        shares_allocated: f32,
+        shares_allocated: Quantity,
     ) -> Result<u32> {
-        let id = self.get_next_stock_sale_allocation_id(uid, aid).unwrap();
+        let id = self.get_next_stock_sale_allocation_id(uid, aid)?;
         let p = rusqlite::params!(id, buy_id, sell_id, shares_allocated, uid, aid);
         let sql = "INSERT INTO stock_sale_allocation (id, purchase_id, sale_id, quantity, uid, aid) VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add allocation of stock sale {}", error);
+                return Err(error);
             }
         }
     }
@@ -546,17 +581,17 @@ impl DbConn {
         &self,
         uid: u32,
         aid: u32,
-        split: f32,
+        split: Quantity,
         lid: u32,
     ) -> Result<u32, rusqlite::Error> {
-        let split_id = self.get_next_stock_split_id(uid, aid).unwrap();
+        let split_id = self.get_next_stock_split_id(uid, aid)?;
         let p = rusqlite::params!(split_id, split, aid, lid, uid);
         let sql = "INSERT INTO stock_splits (id, split, aid, lid, uid) VALUES (?1, ?2, ?3, ?4, ?5)";
         let conn_lock = self.conn.lock().unwrap();
         let row = match conn_lock.execute(sql, p) {
             Ok(_) => split_id,
             Err(error) => {
-                panic!("Unable to add allocation of stock sale {}", error)
+                return Err(error);
             }
         };
         Ok(row)
@@ -568,7 +603,7 @@ impl DbConn {
         aid: u32,
         allocation: StockSplitAllocationInfo,
     ) -> Result<u32> {
-        let id = self.get_next_stock_split_allocation_id(uid, aid).unwrap();
+        let id = self.get_next_stock_split_allocation_id(uid, aid)?;
         let p = rusqlite::params!(
             id,
             allocation.stock_purchase_id,
@@ -581,22 +616,22 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add allocation of stock split {}", error);
+                return Err(error);
             }
         }
     }
 
-    pub fn drop_stock_by_id(&self, uid: u32, aid: u32, id: u32) {
+    pub fn drop_stock_by_id(&self, uid: u32, aid: u32, id: u32) -> Result<(), rusqlite::Error> {
         let p = rusqlite::params![id, uid, aid];
         let sql = "DELETE FROM stock_purchases WHERE id = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
-        if exists {
-            stmt.execute(p).unwrap();
-        } else {
-            panic!("Stock id {} does not exist!", id);
+        let mut stmt = conn_lock.prepare(sql)?;
+        let exists = stmt.exists(p)?;
+        if !exists {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
         }
+        stmt.execute(p)?;
+        Ok(())
     }
 
     pub fn remove_stock_sale(
@@ -608,8 +643,8 @@ impl DbConn {
         let p = rusqlite::params![ledger_id, uid, aid];
         let id_sql = "SELECT id FROM stock_sales WHERE lid = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -622,7 +657,7 @@ impl DbConn {
         }
 
         let rm_sql = "DELETE FROM stock_sales WHERE lid = (?1) and uid = (?2) and aid = (?3)";
-        stmt = conn_lock.prepare(rm_sql).unwrap();
+        stmt = conn_lock.prepare(rm_sql)?;
         stmt.execute(p)?;
 
         let sql = "UPDATE stock_sales SET id = id-1 WHERE id > ?1 and uid = ?2 and aid = ?3";
@@ -660,8 +695,8 @@ impl DbConn {
         let id_sql =
             "SELECT id FROM stock_purchases WHERE lid = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -673,7 +708,7 @@ impl DbConn {
             }
         }
         let rm_sql = "DELETE FROM stock_purchases WHERE lid = (?1) and uid = (?2) and aid = (?3)";
-        stmt = conn_lock.prepare(rm_sql).unwrap();
+        stmt = conn_lock.prepare(rm_sql)?;
         stmt.execute(p)?;
 
         let sql = "UPDATE stock_purchases SET id = id-1 WHERE id > ?1 and uid = ?2 and aid = ?3";
@@ -710,8 +745,8 @@ impl DbConn {
         let p = rusqlite::params![ledger_id, uid, aid];
         let id_sql = "SELECT id FROM stock_splits WHERE lid = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -723,7 +758,7 @@ impl DbConn {
             }
         }
         let rm_sql = "DELETE FROM stock_splits WHERE lid = (?1) and uid = (?2) and aid = (?3)";
-        stmt = conn_lock.prepare(rm_sql).unwrap();
+        stmt = conn_lock.prepare(rm_sql)?;
         stmt.execute(p)?;
 
         let sql = "UPDATE stock_splits SET id = id-1 WHERE id > ?1 and uid = ?2 and aid = ?3";
@@ -761,8 +796,8 @@ impl DbConn {
         let id_sql =
             "SELECT id FROM stock_sale_allocation WHERE id = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -775,7 +810,7 @@ impl DbConn {
         }
         let rm_sql =
             "DELETE FROM stock_sale_allocation WHERE id = (?1) and uid = (?2) and aid = (?3)";
-        stmt = conn_lock.prepare(rm_sql).unwrap();
+        stmt = conn_lock.prepare(rm_sql)?;
         stmt.execute(p)?;
 
         let sql =
@@ -814,8 +849,8 @@ impl DbConn {
         let id_sql =
             "SELECT id FROM stock_split_allocations WHERE id = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -828,7 +863,7 @@ impl DbConn {
         }
         let rm_sql =
             "DELETE FROM stock_split_allocations WHERE id = (?1) and uid = (?2) and aid = (?3)";
-        stmt = conn_lock.prepare(rm_sql).unwrap();
+        stmt = conn_lock.prepare(rm_sql)?;
         stmt.execute(p)?;
 
         let sql =
@@ -867,8 +902,8 @@ impl DbConn {
         let id_sql =
             "SELECT id FROM stock_purchases WHERE lid = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -902,8 +937,8 @@ impl DbConn {
         let p = rusqlite::params![updated_info.ledger_id, uid, aid];
         let id_sql = "SELECT id FROM stock_sales WHERE lid = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -932,7 +967,7 @@ impl DbConn {
         uid: u32,
         aid: u32,
         id: u32,
-        updated_shares: f32,
+        updated_shares: Quantity,
     ) -> Result<u32> {
         let p = rusqlite::params![id, updated_shares, uid, aid];
         let sql = "UPDATE stock_purchases SET remaining = (?2) WHERE id = (?1) and uid = (?3) and aid = (?4)";
@@ -940,7 +975,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to update shares: {}", error);
+                return Err(error);
             }
         }
     }
@@ -950,7 +985,7 @@ impl DbConn {
         uid: u32,
         aid: u32,
         id: u32,
-        updated_shares: f32,
+        updated_shares: Quantity,
     ) -> Result<u32> {
         let p = rusqlite::params![id, updated_shares, uid, aid];
         let sql = "UPDATE stock_purchases SET shares = (?2) WHERE id = (?1) and uid = (?3) and aid = (?4)";
@@ -958,7 +993,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to update shares: {}", error);
+                return Err(error);
             }
         }
     }
@@ -968,7 +1003,7 @@ impl DbConn {
         uid: u32,
         aid: u32,
         id: u32,
-        updated_shares: f32,
+        updated_shares: Quantity,
     ) -> Result<u32> {
         let p = rusqlite::params![id, updated_shares, uid, aid];
         let sql =
@@ -977,7 +1012,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to update shares: {}", error);
+                return Err(error);
             }
         }
     }
@@ -987,7 +1022,7 @@ impl DbConn {
         uid: u32,
         aid: u32,
         id: u32,
-        shares_to_add: f32,
+        shares_to_add: Quantity,
     ) -> Result<u32> {
         let p = rusqlite::params![id, shares_to_add, uid, aid];
         let sql = "UPDATE stock_purchases SET remaining = remaining + (?2) WHERE id = (?1) and uid = (?3) and aid = (?4)";
@@ -995,7 +1030,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to update shares: {}", error);
+                return Err(error);
             }
         }
     }
@@ -1005,7 +1040,7 @@ impl DbConn {
         uid: u32,
         aid: u32,
         id: u32,
-        updated_costbasis: f32,
+        updated_costbasis: Quantity,
     ) -> Result<u32> {
         let p = rusqlite::params![id, updated_costbasis, uid, aid];
         let sql = "UPDATE stock_purchases SET costbasis = (?2) WHERE id = (?1) and uid = (?3) and aid = (?4)";
@@ -1013,17 +1048,33 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to update shares: {}", error);
+                return Err(error);
             }
         }
     }
 
+    // Records what the shares allocated cost in cents when it is not their
+    // lot's cost basis, as for shares sold at the average cost of a holding.
+    // It stays as it is when a split restates the quantity.
+    pub fn set_stock_sale_allocation_basis(
+        &self,
+        uid: u32,
+        aid: u32,
+        id: u32,
+        basis: Money,
+    ) -> Result<()> {
+        let sql = "UPDATE stock_sale_allocation SET basis = (?1) WHERE id = (?2) and uid = (?3) and aid = (?4)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(sql, (basis, id, uid, aid))?;
+        Ok(())
+    }
+
     pub fn update_stock_sale_allocation_quantity(
         &self,
         uid: u32,
         aid: u32,
         id: u32,
-        updated_quantity: f32,
+        updated_quantity: Quantity,
     ) -> Result<u32> {
         let p = rusqlite::params![id, updated_quantity, uid, aid];
         let sql = "UPDATE stock_sale_allocation SET quantity = (?2) WHERE id = (?1) and uid = (?3) and aid = (?4)";
@@ -1031,7 +1082,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to update shares: {}", error);
+                return Err(error);
             }
         }
     }
@@ -1048,19 +1099,16 @@ impl DbConn {
                 true => {
                     stmt = conn_lock.prepare(sql)?;
                     pids = stmt
-                        .query_map(p, |row| Ok(row.get(0)?))
-                        .unwrap()
+                        .query_map(p, |row| Ok(row.get(0)?))?
                         .collect::<Vec<_>>();
                 }
-                false => {
-                    panic!("A list of stocks do not exist for account: {}", aid);
-                }
+                false => return Err(rusqlite::Error::QueryReturnedNoRows),
             }
         }
 
         let mut stocks: Vec<String> = Vec::new();
         for pid in pids {
-            let ticker = self.get_participant(uid, aid, pid.unwrap()).unwrap();
+            let ticker = self.get_participant(uid, aid, pid?)?;
             stocks.push(ticker);
         }
 
@@ -1092,20 +1140,14 @@ impl DbConn {
                                 quantity: row.get(3)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for wrapped_record in wrapped_records {
-                    records.push(wrapped_record.unwrap());
+                    records.push(wrapped_record?);
                 }
                 Ok(records)
             }
-            false => {
-                panic!(
-                    "An allocation of stock sale does not exist for: {}",
-                    sale_id
-                );
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1133,20 +1175,14 @@ impl DbConn {
                                 stock_purchase_id: row.get(2)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for wrapped_record in wrapped_records {
-                    records.push(wrapped_record.unwrap());
+                    records.push(wrapped_record?);
                 }
                 Ok(records)
             }
-            false => {
-                panic!(
-                    "An allocation of stock splits does not exist for: {}",
-                    split_id
-                );
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1192,20 +1228,14 @@ impl DbConn {
                             },
                             txn_opt: None,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for ticker in tickers {
-                    stocks.push(ticker.unwrap());
+                    stocks.push(ticker?);
                 }
                 Ok(stocks)
             }
-            false => {
-                panic!(
-                    "A list of stocks do not exist for account {} and ticker {}",
-                    aid, ticker
-                );
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 
@@ -1239,9 +1269,9 @@ impl DbConn {
         let exists = stmt.exists(p)?;
         let mut stocks = Vec::new();
         let mut initial: StockInfo = StockInfo {
-            shares: 0.0,
-            costbasis: 0.0,
-            remaining: 0.0,
+            shares: Quantity::ZERO,
+            costbasis: Quantity::ZERO,
+            remaining: Quantity::ZERO,
             ledger_id: 0,
         };
 
@@ -1256,20 +1286,14 @@ impl DbConn {
                             remaining: row.get(3)?,
                             ledger_id: row.get(4)?,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for ticker in tickers {
-                    stocks.push(ticker.unwrap());
+                    stocks.push(ticker?);
                 }
                 // Ok(stocks)
             }
-            false => {
-                panic!(
-                    "A list of stocks do not exist for account {} and ticker {}",
-                    aid, ticker
-                );
-            }
+            false => return Err(rusqlite::Error::QueryReturnedNoRows),
         }
 
         let final_stocks = VecDeque::from(stocks);
@@ -1303,13 +1327,12 @@ impl DbConn {
                                 remaining: row.get(3)?,
                                 ledger_id: row.get(4)?,
                             })
-                        })
-                        .unwrap()
+                        })?
                         .collect::<Vec<_>>();
 
-                    let mut num_shares: f32 = 0.0;
+                    let mut num_shares = Quantity::ZERO;
                     for purchase in previously_purchased_stock {
-                        num_shares += purchase.unwrap().shares;
+                        num_shares += purchase?.shares;
                     }
 
                     initial.shares = num_shares;
@@ -1318,11 +1341,11 @@ impl DbConn {
                     // prior to any ownership of the requested stock
                     //
                     // so, we will return 0 and set just before start date.
-                    initial.shares = 0.0 as f32;
+                    initial.shares = Quantity::ZERO;
                 }
             }
             Err(error) => {
-                panic!("Unable to retrieve stock account information: {}", error);
+                return Err(error);
             }
         }
 
@@ -1377,20 +1400,14 @@ impl DbConn {
                             },
                             txn_opt: None,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for ticker in tickers {
-                    stocks.push(ticker.unwrap());
+                    stocks.push(ticker?);
                 }
                 // Ok(stocks)
             }
-            false => {
-                panic!(
-                    "A list of stocks do not exist for account {} and ticker {}",
-                    aid, ticker
-                );
-            }
+            false => return Err(rusqlite::Error::QueryReturnedNoRows),
         }
         Ok(stocks)
     }
@@ -1442,19 +1459,13 @@ impl DbConn {
                             },
                             txn_opt: None,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for ticker in tickers {
-                    stocks.push(ticker.unwrap());
+                    stocks.push(ticker?);
                 }
             }
-            false => {
-                panic!(
-                    "A list of stocks do not exist for account {} and ticker {}",
-                    aid, ticker
-                );
-            }
+            false => return Err(rusqlite::Error::QueryReturnedNoRows),
         }
         Ok(stocks)
     }
@@ -1463,11 +1474,12 @@ impl DbConn {
         &self,
         uid: u32,
         aid: u32,
-    ) -> rusqlite::Result<f32, rusqlite::Error> {
-        let mut sum: f32;
+    ) -> rusqlite::Result<Money, rusqlite::Error> {
         let p = rusqlite::params![uid, aid];
         let sql = "
-            SELECT SUM(get_stock_value(ticker) * shares) as total_value
+            -- shares are stored in millionths and quotes are in dollars, so scale the
+            -- product down to cents
+            SELECT COALESCE(CAST(ROUND(SUM(get_stock_value(ticker) * shares) / 10000.0) AS INTEGER), 0) as total_value
             FROM (
                 SELECT 
                     p.name as ticker,
@@ -1491,12 +1503,10 @@ impl DbConn {
         ";
         let conn_lock = self.conn.lock().unwrap();
         let mut stmt = conn_lock.prepare(sql)?;
-        if stmt.exists(p)? {
-            sum = stmt.query_row(p, |row| row.get(0))?;
-        } else {
-            panic!("Not found!");
+        if !stmt.exists(p)? {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
         }
-        Ok(sum)
+        stmt.query_row(p, |row| row.get(0))
     }
 
     pub fn get_portfolio_value_before_date(
@@ -1504,8 +1514,8 @@ impl DbConn {
         uid: u32,
         aid: u32,
         date: NaiveDate,
-    ) -> rusqlite::Result<Option<f32>, rusqlite::Error> {
-        let mut sum: f32 = 0.0;
+    ) -> rusqlite::Result<Option<Money>, rusqlite::Error> {
+        let mut sum = Money::ZERO;
         let p = rusqlite::params![aid, uid, date.format("%Y-%m-%d").to_string()];
         let sql =
             "WITH 
@@ -1584,7 +1594,7 @@ impl DbConn {
 
             -- Final query to get the cumulative shares owned for each ticker by the target date
             SELECT
-                SUM(get_stock_value_on_day(ticker, (?3)) * final_shares_owned)
+                COALESCE(CAST(ROUND(SUM(get_stock_value_on_day(ticker, (?3)) * final_shares_owned) / 10000.0) AS INTEGER), 0)
             FROM residual";
 
         let conn_lock = self.conn.lock().unwrap();
@@ -1605,7 +1615,7 @@ impl DbConn {
         &self,
         uid: u32,
         aid: u32,
-    ) -> rusqlite::Result<Option<Vec<(String, f32)>>, rusqlite::Error> {
+    ) -> rusqlite::Result<Option<Vec<(String, Quantity)>>, rusqlite::Error> {
         let p = rusqlite::params![uid, aid];
         let sql = "
             SELECT 
@@ -1629,17 +1639,16 @@ impl DbConn {
         let conn_lock = self.conn.lock().unwrap();
         let mut stmt = conn_lock.prepare(sql)?;
         let exists = stmt.exists(p)?;
-        let mut positions: Vec<(String, f32)> = Vec::new();
+        let mut positions: Vec<(String, Quantity)> = Vec::new();
         match exists {
             true => {
                 stmt = conn_lock.prepare(sql)?;
                 let rows = stmt
-                    .query_map(p, |row| Ok((row.get(0)?, row.get(1)?)))
-                    .unwrap()
+                    .query_map(p, |row| Ok((row.get(0)?, row.get(1)?)))?
                     .collect::<Vec<_>>();
 
                 for row in rows {
-                    positions.push(row.unwrap());
+                    positions.push(row?);
                 }
                 return Ok(Some(positions));
             }
@@ -1653,7 +1662,7 @@ impl DbConn {
         &self,
         aid: u32,
         uid: u32,
-    ) -> Result<Option<Vec<(String, String, f32)>>, rusqlite::Error> {
+    ) -> Result<Option<Vec<(String, String, Quantity)>>, rusqlite::Error> {
         let p = rusqlite::params![uid, aid];
         let sql = "
             WITH stock_ledger AS (
@@ -1722,17 +1731,16 @@ impl DbConn {
         let conn_lock = self.conn.lock().unwrap();
         let mut stmt = conn_lock.prepare(sql)?;
         let exists = stmt.exists(p)?;
-        let mut positions: Vec<(String, String, f32)> = Vec::new();
+        let mut positions: Vec<(String, String, Quantity)> = Vec::new();
         match exists {
             true => {
                 stmt = conn_lock.prepare(sql)?;
                 let rows = stmt
-                    .query_map(p, |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))
-                    .unwrap()
+                    .query_map(p, |row| Ok((row.get(0)?, row.get(1)?, row.get(3)?)))?
                     .collect::<Vec<_>>();
 
                 for row in rows {
-                    positions.push(row.unwrap());
+                    positions.push(row?);
                 }
                 return Ok(Some(positions));
             }
@@ -18,7 +18,7 @@ use crate::{database::DbConn, types::ledger};
 use inquire::{
     autocompletion::{self, Replacement},
     type_aliases::Suggester,
-    Autocomplete, CustomUserError,
+    Autocomplete, Confirm, CustomUserError, Text,
 };
 use rusqlite::{Error, Result};
 
@@ -45,18 +45,13 @@ impl DbConn {
             )";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create labels table: {}", error)
-            }
-        }
+        rs?;
 
         Ok(())
     }
 
     pub fn add_label(&self, uid: u32, label: String) -> Result<u32> {
-        let label_id = self.get_next_label_id(uid).unwrap();
+        let label_id = self.get_next_label_id(uid)?;
         let p = rusqlite::params![uid, label_id, label];
         let sql = "
             INSERT INTO labels (uid, id, label) VALUES (?1, ?2, ?3)
@@ -66,10 +61,7 @@ impl DbConn {
         match rs {
             Ok(id) => Ok(label_id),
             Err(error) => {
-                panic!(
-                    "Unable to add label {} for user {}: {}!",
-                    &label, &uid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -80,33 +72,14 @@ impl DbConn {
         let cloned_conn = self.conn.clone();
         let conn_lock = cloned_conn.lock().unwrap();
         {
-            let prepared_stmt = conn_lock.prepare(sql);
-            match prepared_stmt {
-                Ok(mut stmt) => {
-                    if let Ok(entry_found) = stmt.exists(p) {
-                        if entry_found {
-                            let id = stmt
-                                .query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0))
-                                .unwrap();
-                            return Ok(id);
-                        } else {
-                            // self.add_participant(uid, aid, ptype, name, is_account).unwrap()
-                        }
-                    } else {
-                        panic!("Unable to determine if exists!");
-                    }
-                }
-                Err(e) => {
-                    panic!(
-                        "SQLITE error {} while executing searching for label {}.",
-                        e.to_string(),
-                        label
-                    );
-                }
+            let mut stmt = conn_lock.prepare(sql)?;
+            if stmt.exists(p)? {
+                let id = stmt.query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0))?;
+                return Ok(id);
             }
         }
         std::mem::drop(conn_lock);
-        let id = self.add_label(uid, label).unwrap();
+        let id = self.add_label(uid, label)?;
         Ok(id)
     }
 
@@ -174,12 +147,11 @@ impl DbConn {
                             id: row.get(0)?,
                             label: row.get(1)?,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 return Ok(entries);
             }
@@ -203,12 +175,7 @@ impl DbConn {
         )";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create label allocations table: {}", error)
-            }
-        }
+        rs?;
 
         Ok(())
     }
@@ -220,7 +187,7 @@ impl DbConn {
         label_id: u32,
         ledger_id: u32,
     ) -> Result<u32, rusqlite::Error> {
-        let id = self.get_next_label_allocation_id(uid, aid).unwrap();
+        let id = self.get_next_label_allocation_id(uid, aid)?;
         let p = rusqlite::params![id, uid, aid, ledger_id, label_id];
         let sql =
         "
@@ -231,10 +198,7 @@ impl DbConn {
         match rs {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!(
-                    "Unable to add label mapping between {} and {}:{}!",
-                    label_id, aid, ledger_id
-                );
+                return Err(error);
             }
         }
     }
@@ -260,12 +224,11 @@ impl DbConn {
                             ledger_id: row.get(1)?,
                             label_id: row.get(2)?,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 return Ok(entries);
             }
@@ -328,8 +291,7 @@ impl Autocomplete for LabelAutoCompleter {
         let mut suggestions: Vec<String>;
         suggestions = self
             .db
-            .get_labels(self.uid)
-            .unwrap()
+            .get_labels(self.uid)?
             .into_iter()
             .map(|info| info.label)
             .filter(|label_name| label_name.starts_with(input.to_ascii_uppercase().as_str()))
@@ -346,9 +308,7 @@ impl Autocomplete for LabelAutoCompleter {
         Ok(match highlighted_suggestion {
             Some(suggestion) => Replacement::Some(suggestion),
             None => {
-                let suggestions = self
-                    .get_suggestions(input.to_ascii_uppercase().as_str())
-                    .unwrap();
+                let suggestions = self.get_suggestions(input.to_ascii_uppercase().as_str())?;
                 if suggestions.len() == 0 {
                     autocompletion::Replacement::None
                 } else {
@@ -358,3 +318,61 @@ impl Autocomplete for LabelAutoCompleter {
         })
     }
 }
+
+// The labels entered for a ledger entry, asked for before anything is written
+// so that the database is not locked while waiting on the user.
+pub struct LabelSelection {
+    pub keep_prior: bool,
+    pub labels: Vec<String>,
+}
+
+impl LabelSelection {
+    pub fn prompt(uid: u32, db: &DbConn, entry: &str, overwrite: bool) -> Self {
+        let keep_prior = if overwrite {
+            Confirm::new("Would you like to maintain all prior labels (y/n)?")
+                .prompt()
+                .unwrap()
+        } else {
+            true
+        };
+
+        let mut labels = Vec::new();
+        let add_label_prompt = Confirm::new(format!("Add labels to {} (y/n)?", entry).as_str())
+            .prompt()
+            .unwrap();
+        if add_label_prompt {
+            loop {
+                let label = Text::new("Enter label:")
+                    .with_autocomplete(LabelAutoCompleter {
+                        uid,
+                        db: db.clone(),
+                    })
+                    .prompt()
+                    .unwrap()
+                    .to_ascii_uppercase();
+                labels.push(label);
+
+                let continue_prompt = Confirm::new("Add more labels (y/n)?").prompt().unwrap();
+                if !continue_prompt {
+                    break;
+                }
+            }
+        }
+        Self { keep_prior, labels }
+    }
+
+    pub fn apply(self, uid: u32, aid: u32, db: &DbConn, ledger_id: u32) -> Result<()> {
+        if !self.keep_prior {
+            let mapped_labels =
+                db.check_and_get_label_mapping_matching_ledger_id(uid, aid, ledger_id)?;
+            for label in mapped_labels {
+                db.remove_label_mapping(uid, aid, label.id)?;
+            }
+        }
+        for label in self.labels {
+            let label_id = db.check_and_add_label(uid, label)?;
+            db.add_label_mapping(uid, aid, label_id, ledger_id)?;
+        }
+        Ok(())
+    }
+}
@@ -20,12 +20,12 @@ use chrono::NaiveDate;
 use inquire::autocompletion::Replacement;
 use inquire::*;
 use rusqlite::Result;
-use shared_lib::TransferType;
+use shared_lib::{Money, TransferType};
 
 #[derive(Clone, Debug)]
 pub struct LedgerInfo {
     pub date: String,
-    pub amount: f32,
+    pub amount: Money,
     pub transfer_type: TransferType,
     pub participant: u32,
     pub category_id: u32,
@@ -58,7 +58,7 @@ pub struct DisplayableLedgerRecord {
 #[derive(Debug, Clone)]
 pub struct Expenditure {
     pub category: String,
-    pub amount: f32,
+    pub amount: Money,
 }
 
 impl DbConn {
@@ -67,7 +67,7 @@ impl DbConn {
         sql = "CREATE TABLE IF NOT EXISTS ledgers (
                 id          INTEGER NOT NULL,
                 date        TEXT NOT NULL, 
-                amount      REAL NOT NULL, 
+                amount      INTEGER NOT NULL, 
                 transfer_type INTEGER NOT NULL, 
                 pid         INTEGER NOT NULL, 
                 cid         INTEGER NOT NULL,
@@ -82,12 +82,7 @@ impl DbConn {
             )";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, ());
-        match rs {
-            Ok(_) => {}
-            Err(error) => {
-                panic!("Unable to create: {}", error)
-            }
-        }
+        rs?;
         Ok(())
     }
 
@@ -98,7 +93,7 @@ impl DbConn {
         entry: LedgerInfo,
     ) -> rusqlite::Result<u32, rusqlite::Error> {
         let sql: &str;
-        let id = self.get_next_ledger_id(uid, aid).unwrap();
+        let id = self.get_next_ledger_id(uid, aid)?;
         sql = "INSERT INTO ledgers ( id, date, amount, transfer_type, pid, cid, desc, aid, uid) VALUES ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(
@@ -115,14 +110,7 @@ impl DbConn {
                 uid,
             ),
         );
-        match rs {
-            Ok(_usize) => {
-                // println!("Added statement");
-            }
-            Err(error) => {
-                panic!("Unable to add ledger: {}", error);
-            }
-        }
+        rs?;
         Ok(id)
     }
 
@@ -196,6 +184,25 @@ impl DbConn {
         Ok(id)
     }
 
+    pub fn get_ledger_entry(&self, uid: u32, aid: u32, id: u32) -> Result<LedgerRecord> {
+        let p = rusqlite::params![aid, id, uid];
+        let sql = "SELECT id, date, amount, transfer_type, pid, cid, desc FROM ledgers WHERE aid = (?1) and id = (?2) and uid = (?3)";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.query_row(sql, p, |row| {
+            Ok(LedgerRecord {
+                id: row.get(0)?,
+                info: LedgerInfo {
+                    date: row.get(1)?,
+                    amount: row.get(2)?,
+                    transfer_type: TransferType::from(row.get::<_, u32>(3)?),
+                    participant: row.get(4)?,
+                    category_id: row.get(5)?,
+                    description: row.get(6)?,
+                },
+            })
+        })
+    }
+
     pub fn get_ledger(
         &self,
         uid: u32,
@@ -222,12 +229,11 @@ impl DbConn {
                                 description: row.get(6)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 Ok(entries)
             }
@@ -275,7 +281,7 @@ impl DbConn {
                             id: row.get::<_, u32>(0)?.to_string(),
                             info: DisplayableLedgerInfo {
                                 date: row.get(1)?,
-                                amount: row.get::<_, f32>(2)?.to_string(),
+                                amount: row.get::<_, Money>(2)?.to_string(),
                                 transfer_type: format!(
                                     "{}",
                                     TransferType::from_repr(row.get::<_, u32>(3)? as usize)
@@ -287,12 +293,11 @@ impl DbConn {
                                 labels: row.get(7)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 Ok(entries)
             }
@@ -328,12 +333,11 @@ impl DbConn {
                                 description: row.get(6)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 Ok(entries)
             }
@@ -381,13 +385,12 @@ impl DbConn {
                             description: row.get(6)?,
                         },
                     })
-                })
-                .unwrap()
+                })?
                 .collect::<Vec<_>>();
 
             let mut records: Vec<LedgerRecord> = Vec::new();
             for wrapped_record in matched_record_wrap {
-                records.push(wrapped_record.unwrap());
+                records.push(wrapped_record?);
             }
 
             return Ok(Some(records));
@@ -455,13 +458,12 @@ impl DbConn {
                             description: row.get(6)?,
                         },
                     })
-                })
-                .unwrap()
+                })?
                 .collect::<Vec<_>>();
 
             let mut records: Vec<LedgerRecord> = Vec::new();
             for wrapped_record in matched_record_wrap {
-                records.push(wrapped_record.unwrap());
+                records.push(wrapped_record?);
             }
 
             return Ok(Some(records));
@@ -503,12 +505,11 @@ impl DbConn {
                                 description: row.get(6)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 Ok(entries)
             }
@@ -518,9 +519,12 @@ impl DbConn {
         }
     }
 
-    pub fn get_current_value(&self, uid: u32, aid: u32) -> rusqlite::Result<f32, rusqlite::Error> {
+    pub fn get_current_value(
+        &self,
+        uid: u32,
+        aid: u32,
+    ) -> rusqlite::Result<Money, rusqlite::Error> {
         let p = rusqlite::params![aid, uid];
-        let mut sum: f32 = 0.0;
         let sql: &str ="SELECT COALESCE(SUM(CASE
                 WHEN transfer_type == 0 or transfer_type = 2 THEN -amount    -- withdrawal
                 WHEN transfer_type == 1 or transfer_type = 3 THEN amount     -- deposit from external account
@@ -529,13 +533,10 @@ impl DbConn {
 
         let conn_lock = self.conn.lock().unwrap();
         let mut stmt = conn_lock.prepare(sql)?;
-        if stmt.exists(p)? {
-            sum = stmt.query_row(p, |row| row.get(0))?;
-        } else {
-            panic!("Not found!");
+        if !stmt.exists(p)? {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
         }
-
-        Ok(sum)
+        stmt.query_row(p, |row| row.get(0))
     }
 
     pub fn get_cumulative_total_of_ledger_before_date(
@@ -543,9 +544,9 @@ impl DbConn {
         uid: u32,
         aid: u32,
         end: NaiveDate,
-    ) -> rusqlite::Result<Option<f32>, rusqlite::Error> {
+    ) -> rusqlite::Result<Option<Money>, rusqlite::Error> {
         let p = rusqlite::params![aid, end.format("%Y-%m-%d").to_string(), uid];
-        let mut sum: f32 = 0.0;
+        let mut sum = Money::ZERO;
         let sql = "SELECT COALESCE(SUM(CASE
             WHEN transfer_type == 0 or transfer_type = 2 THEN -amount    -- withdrawal
             WHEN transfer_type == 1 or transfer_type = 3 THEN amount     -- deposit from external account
@@ -571,9 +572,9 @@ impl DbConn {
         uid: u32,
         aid: u32,
         end: NaiveDate,
-    ) -> rusqlite::Result<Option<f32>, rusqlite::Error> {
+    ) -> rusqlite::Result<Option<Money>, rusqlite::Error> {
         let p = rusqlite::params![aid, end.format("%Y-%m-%d").to_string(), uid];
-        let mut sum: f32 = 0.0;
+        let mut sum = Money::ZERO;
         let sql = "SELECT COALESCE(SUM(CASE
             WHEN transfer_type == 0 or transfer_type = 2 THEN -amount    -- withdrawal
             WHEN transfer_type == 1 or transfer_type = 3 THEN amount     -- deposit from external account
@@ -628,12 +629,11 @@ impl DbConn {
                                 description: row.get(6)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 Ok(Some(entries))
             }
@@ -681,12 +681,11 @@ impl DbConn {
                             category: row.get(0)?,
                             amount: row.get(1)?,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for row in rows {
-                    cumulative_expenses.push(row.unwrap());
+                    cumulative_expenses.push(row?);
                 }
                 return Ok(Some(cumulative_expenses));
             }
@@ -701,9 +700,9 @@ impl DbConn {
         uid: u32,
         aid: u32,
         end: NaiveDate,
-    ) -> rusqlite::Result<Option<f32>, rusqlite::Error> {
+    ) -> rusqlite::Result<Option<Money>, rusqlite::Error> {
         let p = rusqlite::params![aid, end.format("%Y-%m-%d").to_string(), uid];
-        let mut sum: f32 = 0.0;
+        let mut sum = Money::ZERO;
         let sql = "SELECT COALESCE(SUM(CASE
             WHEN transfer_type == 0 THEN -amount    -- withdrawal
             WHEN transfer_type == 1 THEN amount     -- deposit from external account
@@ -759,12 +758,11 @@ impl DbConn {
                             category: row.get(0)?,
                             amount: row.get(1)?,
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for row in rows {
-                    cumulative_expenses.push(row.unwrap());
+                    cumulative_expenses.push(row?);
                 }
                 return Ok(Some(cumulative_expenses));
             }
@@ -14,10 +14,14 @@
   You should have received a copy of the GNU General Public License
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
+pub mod account_members;
 pub mod accounts;
 pub mod categories;
 pub mod certificate_of_deposit;
 pub mod credit_card;
+pub mod csv_mappings;
+pub mod distributions;
+pub mod external_ids;
 pub mod hsa;
 pub mod investments;
 #[path = "401k.rs"]
@@ -73,11 +73,7 @@ impl DbConn {
                 FOREIGN KEY(uid) REFERENCES users(id)
             )";
 
-        self.conn
-            .lock()
-            .unwrap()
-            .execute(sql, ())
-            .expect("Unable to initialize people table!");
+        self.conn.lock().unwrap().execute(sql, ())?;
         Ok(())
     }
 
@@ -89,20 +85,14 @@ impl DbConn {
         name: String,
         is_account: bool,
     ) -> Result<u32> {
-        let id = self.get_next_people_id(uid, aid).unwrap();
+        let id = self.get_next_people_id(uid, aid)?;
         let p = rusqlite::params!(id, aid, ptype as u32, name, uid, is_account);
         let sql = "INSERT INTO people (id, aid, type, name, uid, is_account) VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!(
-                    "Unable to add {} {} for account {}: {}",
-                    ptype.to_string(),
-                    name,
-                    aid,
-                    error
-                );
+                return Err(error);
             }
         }
     }
@@ -113,65 +103,35 @@ impl DbConn {
         aid: u32,
         name: String,
         ptype: ParticipantType,
-    ) -> Option<u32> {
+    ) -> Result<Option<u32>> {
         let sql =
             "SELECT id FROM people WHERE aid = (?1) and name = (?2) and type = (?3) and uid = (?4)";
         let p = rusqlite::params![aid, name, ptype as u32, uid];
         let conn_lock = self.conn.lock().unwrap();
-        let prepared_stmt = conn_lock.prepare(sql);
-        match prepared_stmt {
-            Ok(mut stmt) => {
-                if let Ok(entry_found) = stmt.exists(p) {
-                    if entry_found {
-                        let id = stmt
-                            .query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0))
-                            .unwrap();
-                        return Some(id);
-                    } else {
-                        return None;
-                    }
-                } else {
-                    panic!("Unable to determine if exists!");
-                }
-            }
-            Err(e) => {
-                panic!(
-                    "SQLITE error {} while executing searching for person {}.",
-                    e.to_string(),
-                    name
-                );
-            }
+        let mut stmt = conn_lock.prepare(sql)?;
+        if stmt.exists(p)? {
+            let id = stmt.query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0))?;
+            Ok(Some(id))
+        } else {
+            Ok(None)
         }
     }
 
-    pub fn get_participant(&self, uid: u32, aid: u32, pid: u32) -> Option<String> {
+    // whether the account has a participant of any type by that name
+    pub fn participant_exists(&self, uid: u32, aid: u32, name: &str) -> Result<bool> {
+        let sql = "SELECT id FROM people WHERE aid = (?1) and name = (?2) and uid = (?3)";
+        let p = rusqlite::params![aid, name, uid];
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        stmt.exists(p)
+    }
+
+    pub fn get_participant(&self, uid: u32, aid: u32, pid: u32) -> Result<String> {
         let sql = "SELECT name FROM people WHERE aid = (?1) and id = (?2) and uid = (?3)";
         let p = rusqlite::params![aid, pid, uid];
         let conn = self.conn.lock().unwrap();
-        let prepared_stmt = conn.prepare(sql);
-        match prepared_stmt {
-            Ok(mut stmt) => {
-                if let Ok(entry_found) = stmt.exists(p) {
-                    if entry_found {
-                        let name: String = stmt
-                            .query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, String>(0))
-                            .unwrap();
-                        return Some(name);
-                    } else {
-                        return None;
-                    }
-                } else {
-                    panic!("Unable to determine if exists!");
-                }
-            }
-            Err(e) => {
-                panic!(
-                    "SQLITE error {} while executing searching for person {}.",
-                    e.to_string(),
-                    pid
-                );
-            }
-        }
+        let mut stmt = conn.prepare(sql)?;
+        stmt.query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, String>(0))
     }
 
     pub fn check_and_add_participant(
@@ -181,40 +141,20 @@ impl DbConn {
         name: String,
         ptype: ParticipantType,
         is_account: bool,
-    ) -> u32 {
+    ) -> Result<u32> {
         let sql = "SELECT id FROM people WHERE aid = (?1) and name = (?2) and (type = (?3) or type = (?4)) and uid = (?5)";
         let p = rusqlite::params![aid, name, ptype as u32, ParticipantType::Both as u32, uid];
         let cloned_conn = self.conn.clone();
         let conn_lock = cloned_conn.lock().unwrap();
         {
-            let prepared_stmt = conn_lock.prepare(sql);
-            match prepared_stmt {
-                Ok(mut stmt) => {
-                    if let Ok(entry_found) = stmt.exists(p) {
-                        if entry_found {
-                            let id = stmt
-                                .query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0))
-                                .unwrap();
-                            return id;
-                        } else {
-                            // self.add_participant(uid, aid, ptype, name, is_account).unwrap()
-                        }
-                    } else {
-                        panic!("Unable to determine if exists!");
-                    }
-                }
-                Err(e) => {
-                    panic!(
-                        "SQLITE error {} while executing searching for person {}.",
-                        e.to_string(),
-                        name
-                    );
-                }
+            let mut stmt = conn_lock.prepare(sql)?;
+            if stmt.exists(p)? {
+                let id = stmt.query_row(p, |row: &rusqlite::Row<'_>| row.get::<_, u32>(0))?;
+                return Ok(id);
             }
         }
         std::mem::drop(conn_lock);
         self.add_participant(uid, aid, ptype, name, is_account)
-            .unwrap()
     }
 
     pub fn get_participants(
@@ -250,11 +190,10 @@ impl DbConn {
                                 ptype: ParticipantType::from(row.get::<_, u32>(2)? as u32),
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
                 for participant in party {
-                    participants.push(participant.unwrap());
+                    participants.push(participant?);
                 }
             }
             false => {}
@@ -285,11 +224,10 @@ impl DbConn {
             true => {
                 stmt = conn_lock.prepare(sql)?;
                 let party = stmt
-                    .query_map(p, |row| Ok(row.get(0)?))
-                    .unwrap()
+                    .query_map(p, |row| Ok(row.get(0)?))?
                     .collect::<Vec<_>>();
                 for participant in party {
-                    participants.push(participant.unwrap());
+                    participants.push(participant?);
                 }
             }
             false => {}
@@ -326,11 +264,10 @@ impl DbConn {
             true => {
                 stmt = conn_lock.prepare(sql)?;
                 let party = stmt
-                    .query_map(p, |row| Ok(row.get(0)?))
-                    .unwrap()
+                    .query_map(p, |row| Ok(row.get(0)?))?
                     .collect::<Vec<_>>();
                 for participant in party {
-                    participants.push(participant.unwrap());
+                    participants.push(participant?);
                 }
             }
             false => {}
@@ -367,11 +304,10 @@ impl DbConn {
             true => {
                 stmt = conn_lock.prepare(sql)?;
                 let party = stmt
-                    .query_map(p, |row| Ok(row.get(0)?))
-                    .unwrap()
+                    .query_map(p, |row| Ok(row.get(0)?))?
                     .collect::<Vec<_>>();
                 for participant in party {
-                    participants.push(participant.unwrap());
+                    participants.push(participant?);
                 }
             }
             false => {}
@@ -405,10 +341,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new),
             Err(error) => {
-                panic!(
-                    "Unable to update participant {} in account {}: {}!",
-                    old, aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -420,44 +353,25 @@ impl DbConn {
         ptype: ParticipantType,
         name: String,
     ) -> rusqlite::Result<Option<u32>, rusqlite::Error> {
-        let id_opt = self.get_participant_id(uid, aid, name.clone(), ptype);
-        if id_opt.is_none() {
-            return Ok(None);
-        }
-        let id = id_opt.unwrap();
+        let id = match self.get_participant_id(uid, aid, name.clone(), ptype)? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
         let p = rusqlite::params![uid, aid, name, ptype as u32];
         let sql =
             "DELETE FROM people WHERE uid = (?1) and aid = (?2) and name = (?3) and type = (?4)";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to remove participant: {}!", error);
-            }
-        }
+        rs?;
         let p = rusqlite::params![id, uid, aid];
         let sql = "UPDATE people SET id = id-1 WHERE id > ?1 and uid = ?2 and aid = ?3";
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!("Unable to update participants ids: {}!", error);
-            }
-        }
+        rs?;
 
         let p = rusqlite::params![uid, aid];
         let sql = "UPDATE user_account_info SET pid = pid - 1 WHERE uid = ?1 and aid = ?2";
         let rs = conn_lock.execute(sql, p);
-        match rs {
-            Ok(_usize) => {}
-            Err(error) => {
-                panic!(
-                    "Unable to update 'pid' value in 'user_account_info': {}!",
-                    error
-                );
-            }
-        }
+        rs?;
 
         Ok(Some(id))
     }
@@ -488,8 +402,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                                     self.uid,
                                     self.aid,
                                     TransferType::WithdrawalToExternalAccount,
-                                )
-                                .unwrap()
+                                )?
                                 .into_iter()
                                 .filter(|name| {
                                     name.starts_with(input.to_ascii_uppercase().as_str())
@@ -501,8 +414,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                                     self.uid,
                                     self.aid,
                                     TransferType::WithdrawalToInternalAccount,
-                                )
-                                .unwrap()
+                                )?
                                 .into_iter()
                                 .filter(|name| {
                                     name.starts_with(input.to_ascii_uppercase().as_str())
@@ -517,7 +429,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                                     self.aid,
                                     TransferType::WithdrawalToExternalAccount,
                                 )
-                                .unwrap()
+                                ?
                                 .into_iter()
                                 .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                                 .collect();
@@ -528,7 +440,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                                     self.aid,
                                     TransferType::WithdrawalToInternalAccount,
                                 )
-                                .unwrap()
+                                ?
                                 .into_iter()
                                 .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                                 .collect();
@@ -542,8 +454,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                                 self.aid,
                                 TransferType::WithdrawalToExternalAccount,
                                 false,
-                            )
-                            .unwrap()
+                            )?
                             .into_iter()
                             .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                             .collect();
@@ -554,8 +465,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                                 self.aid,
                                 TransferType::WithdrawalToInternalAccount,
                                 false,
-                            )
-                            .unwrap()
+                            )?
                             .into_iter()
                             .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                             .collect();
@@ -573,8 +483,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                             self.aid,
                             TransferType::DepositFromExternalAccount,
                             false,
-                        )
-                        .unwrap()
+                        )?
                         .into_iter()
                         .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                         .collect();
@@ -585,8 +494,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                             self.aid,
                             TransferType::DepositFromInternalAccount,
                             false,
-                        )
-                        .unwrap()
+                        )?
                         .into_iter()
                         .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                         .collect();
@@ -602,8 +510,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                             self.aid,
                             TransferType::WithdrawalToExternalAccount,
                             false,
-                        )
-                        .unwrap()
+                        )?
                         .into_iter()
                         .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                         .collect();
@@ -614,8 +521,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                             self.aid,
                             TransferType::WithdrawalToInternalAccount,
                             false,
-                        )
-                        .unwrap()
+                        )?
                         .into_iter()
                         .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                         .collect();
@@ -626,8 +532,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                             self.aid,
                             TransferType::DepositFromInternalAccount,
                             false,
-                        )
-                        .unwrap()
+                        )?
                         .into_iter()
                         .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                         .collect();
@@ -638,8 +543,7 @@ impl Autocomplete for ParticipantAutoCompleter {
                             self.aid,
                             TransferType::DepositFromExternalAccount,
                             false,
-                        )
-                        .unwrap()
+                        )?
                         .into_iter()
                         .filter(|name| name.starts_with(input.to_ascii_uppercase().as_str()))
                         .collect();
@@ -651,11 +555,10 @@ impl Autocomplete for ParticipantAutoCompleter {
                 }
             };
         } else {
-            let current_account_name = self.db.get_account_name(self.uid, self.aid).unwrap();
+            let current_account_name = self.db.get_account_name(self.uid, self.aid)?;
             let mut x: Vec<String> = self
                 .db
-                .get_user_accounts(self.uid)
-                .unwrap()
+                .get_owned_accounts(self.uid)?
                 .iter()
                 .map(|acct| acct.info.name.clone())
                 .filter(|x| *x != current_account_name)
@@ -676,9 +579,7 @@ impl Autocomplete for ParticipantAutoCompleter {
         Ok(match highlighted_suggestion {
             Some(suggestion) => Replacement::Some(suggestion),
             None => {
-                let suggestions = self
-                    .get_suggestions(input.to_ascii_uppercase().as_str())
-                    .unwrap();
+                let suggestions = self.get_suggestions(input.to_ascii_uppercase().as_str())?;
                 if suggestions.len() == 0 {
                     autocompletion::Replacement::None
                 } else {
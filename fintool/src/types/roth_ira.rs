@@ -15,6 +15,7 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use rusqlite::Result;
+use shared_lib::Money;
 
 use crate::database::DbConn;
 
@@ -26,14 +27,14 @@ pub struct RothIraRecord {
 
 #[derive(Clone)]
 pub struct RothIraInfo {
-    pub contribution_limit: f32,
+    pub contribution_limit: Money,
 }
 
 impl DbConn {
     pub fn create_roth_ira_accounts_table(&self) -> Result<()> {
         let sql: &str = "CREATE TABLE IF NOT EXISTS roth_iras ( 
                 id          INTEGER NOT NULL,
-                contribution_limit REAL NOT NULL,
+                contribution_limit INTEGER NOT NULL,
                 uid  INTEGER NOT NULL, 
                 aid INTEGER NOT NULL,
                 PRIMARY KEY (uid, aid, id),
@@ -42,14 +43,12 @@ impl DbConn {
             )";
 
         let conn_lock = self.conn.lock().unwrap();
-        conn_lock
-            .execute(sql, ())
-            .expect("Unable to initialize credit_cards table!");
+        conn_lock.execute(sql, ())?;
         Ok(())
     }
 
     pub fn add_roth_ira_account(&self, uid: u32, aid: u32, info: RothIraInfo) -> Result<u32> {
-        let id = self.get_next_roth_ira_id(uid, aid).unwrap();
+        let id = self.get_next_roth_ira_id(uid, aid)?;
         let p = rusqlite::params!(id, aid, uid, info.contribution_limit);
         let sql =
             "INSERT INTO roth_iras (id, aid, uid, contribution_limit) VALUES (?1, ?2, ?3, ?4)";
@@ -57,7 +56,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!("Unable to add roth ira account {}: {}", aid, error);
+                return Err(error);
             }
         }
     }
@@ -66,18 +65,15 @@ impl DbConn {
         &self,
         uid: u32,
         aid: u32,
-        new_contribution_lmit: f32,
-    ) -> Result<f32> {
+        new_contribution_lmit: Money,
+    ) -> Result<Money> {
         let p = rusqlite::params!(uid, aid, new_contribution_lmit);
         let sql = "UPDATE roth_iras SET contribution_limit = (?3) WHERE uid = (?1) and aid = (?2)";
         let conn_lock = self.conn.lock().unwrap();
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(new_contribution_lmit),
             Err(error) => {
-                panic!(
-                    "Unable to update contribution limit for roth ira {}: {}!",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -102,16 +98,11 @@ impl DbConn {
                 match cc_wrap {
                     Ok(cc) => return Ok(cc),
                     Err(error) => {
-                        panic!(
-                            "Unable to retrieve roth ira info for account {}: {}",
-                            aid, error
-                        )
+                        return Err(error);
                     }
                 }
             }
-            false => {
-                panic!("Unable to find roth ira matching account id: {}!", aid);
-            }
+            false => Err(rusqlite::Error::QueryReturnedNoRows),
         }
     }
 }
@@ -15,14 +15,18 @@
   along with this program.  If not, see <https://www.gnu.org/licenses/>.
 -----------------------------------------------------------------------*/
 use chrono::NaiveDate;
-use rusqlite::{Error, Result};
+use rusqlite::{Connection, Error, OpenFlags, Result};
+use shared_lib::quotes::{quote_from_close, QuoteError, QuoteProvider};
+use shared_lib::Quantity;
+use std::sync::Mutex;
+use yahoo_finance_api::Quote;
 
 use crate::database::DbConn;
 
 #[derive(Debug, Clone)]
 pub struct StockPriceInfo {
     pub stock_ticker_peer_id: u32,
-    pub price_per_unit_share: f32,
+    pub price_per_unit_share: Quantity,
     pub date: String,
 }
 
@@ -37,7 +41,7 @@ impl DbConn {
         let sql = "CREATE TABLE IF NOT EXISTS stock_prices (
             id              INTEGER NOT NULL, 
             stock_ticker_peer_id INTEGER NOT NULL, 
-            price           REAL NOT NULL, 
+            price           INTEGER NOT NULL, 
             date            STRING NOT NULL, 
             aid             INTEGER NOT NULL, 
             uid             INTEGER NOT NULL,
@@ -50,14 +54,14 @@ impl DbConn {
         match conn_lock.execute(sql, ()) {
             Ok(_) => {}
             Err(error) => {
-                panic!("Unable to create table 'stock_prices' because: {}", error);
+                return Err(error);
             }
         }
         Ok(())
     }
 
     pub fn add_stock_price(&self, uid: u32, aid: u32, info: StockPriceInfo) -> Result<u32> {
-        let id = self.get_next_stock_price_id(uid, aid).unwrap();
+        let id = self.get_next_stock_price_id(uid, aid)?;
         let p = rusqlite::params!(
             id,
             info.stock_ticker_peer_id,
@@ -71,10 +75,7 @@ impl DbConn {
         match conn_lock.execute(sql, p) {
             Ok(_) => Ok(id),
             Err(error) => {
-                panic!(
-                    "Unable to add stock price record for account {}: {}",
-                    aid, error
-                );
+                return Err(error);
             }
         }
     }
@@ -110,12 +111,11 @@ impl DbConn {
                                 date: row.get(3)?,
                             },
                         })
-                    })
-                    .unwrap()
+                    })?
                     .collect::<Vec<_>>();
 
                 for entry in found_entries {
-                    entries.push(entry.unwrap());
+                    entries.push(entry?);
                 }
                 Ok(entries)
             }
@@ -132,8 +132,8 @@ impl DbConn {
         let p = rusqlite::params![stock_price_id, uid, aid];
         let id_sql = "SELECT id FROM stock_prices WHERE lid = (?1) and uid = (?2) and aid = (?3)";
         let conn_lock = self.conn.lock().unwrap();
-        let mut stmt = conn_lock.prepare(id_sql).unwrap();
-        let exists = stmt.exists(p).unwrap();
+        let mut stmt = conn_lock.prepare(id_sql)?;
+        let exists = stmt.exists(p)?;
         let id: u32;
         match exists {
             true => {
@@ -146,7 +146,7 @@ impl DbConn {
         }
 
         let rm_sql = "DELETE FROM stock_prices WHERE lid = (?1) and uid = (?2) and aid = (?3)";
-        stmt = conn_lock.prepare(rm_sql).unwrap();
+        stmt = conn_lock.prepare(rm_sql)?;
         stmt.execute(p)?;
 
         let sql = "UPDATE stock_prices SET id = id-1 WHERE id > ?1 and uid = ?2 and aid = ?3";
@@ -179,7 +179,7 @@ impl DbConn {
         uid: u32,
         aid: u32,
         price_record: StockPriceRecord,
-    ) -> u32 {
+    ) -> Result<u32> {
         let p = rusqlite::params![
             uid,
             aid,
@@ -191,9 +191,9 @@ impl DbConn {
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
         match rs {
-            Ok(_usize) => price_record.id,
+            Ok(_usize) => Ok(price_record.id),
             Err(error) => {
-                panic!("Unable to update stock price record: {}!", error);
+                return Err(error);
             }
         }
     }
@@ -203,17 +203,120 @@ impl DbConn {
         uid: u32,
         aid: u32,
         peer_id: u32,
-        split: f32,
-    ) -> u32 {
+        split: Quantity,
+    ) -> Result<u32> {
         let p = rusqlite::params![uid, aid, peer_id, split];
-        let sql = "UPDATE stock_prices SET price = price / (?4) WHERE uid = (?1) and aid = (?2) and stock_ticker_peer_id = (?3)";
+        let sql = "UPDATE stock_prices SET price = CAST(ROUND(price * 1000000.0 / (?4)) AS INTEGER) WHERE uid = (?1) and aid = (?2) and stock_ticker_peer_id = (?3)";
         let conn_lock = self.conn.lock().unwrap();
         let rs = conn_lock.execute(sql, p);
         match rs {
-            Ok(_usize) => peer_id,
+            Ok(_usize) => Ok(peer_id),
             Err(error) => {
-                panic!("Unable to update stock price record: {}!", error);
+                return Err(error);
             }
         }
     }
 }
+
+const MANUAL_QUOTES_SQL: &str = "
+    SELECT sp.date, sp.price
+    FROM stock_prices AS sp
+    INNER JOIN people AS p ON
+        sp.stock_ticker_peer_id = p.id and
+        sp.aid = p.aid and
+        sp.uid = p.uid
+    WHERE
+        p.name = (?1) and
+        sp.date >= (?2) and
+        sp.date < (?3)
+";
+
+// Serves the closing prices that users recorded by hand in the stock_prices
+// table. It holds its own read-only connection because quotes are requested
+// from within queries that are already running on the shared one.
+pub struct ManualQuoteProvider {
+    conn: Mutex<Connection>,
+}
+
+impl ManualQuoteProvider {
+    pub fn open(db: &DbConn) -> Result<Self> {
+        let conn = db.open_connection(OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn get_quotes(
+        &self,
+        ticker: &str,
+        start: &str,
+        end: &str,
+        order: &str,
+        limit: u32,
+    ) -> std::result::Result<Vec<Quote>, QuoteError> {
+        let sql = format!(
+            "{} ORDER BY sp.date {} LIMIT {}",
+            MANUAL_QUOTES_SQL, order, limit
+        );
+        let conn_lock = self.conn.lock().unwrap();
+        let rows = conn_lock
+            .prepare(&sql)
+            .and_then(|mut stmt| {
+                stmt.query_map(rusqlite::params![ticker, start, end], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Quantity>(1)?))
+                })?
+                .collect::<Result<Vec<_>>>()
+            })
+            .map_err(|e| QuoteError::Source(e.to_string()))?;
+
+        let mut quotes = Vec::new();
+        for (date, price) in rows {
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|e| QuoteError::Source(format!("'{}': {}", date, e)))?;
+            quotes.push(quote_from_close(date, price.to_f64()));
+        }
+        Ok(quotes)
+    }
+}
+
+impl QuoteProvider for ManualQuoteProvider {
+    fn latest_close(&self, ticker: &str) -> std::result::Result<f64, QuoteError> {
+        self.get_quotes(ticker, "0000-00-00", "9999-99-99", "DESC", 1)?
+            .first()
+            .map(|quote| quote.close)
+            .ok_or(QuoteError::NotFound(ticker.to_string()))
+    }
+
+    fn history(
+        &self,
+        ticker: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> std::result::Result<Vec<Quote>, QuoteError> {
+        self.get_quotes(
+            ticker,
+            &start.format("%Y-%m-%d").to_string(),
+            &end.format("%Y-%m-%d").to_string(),
+            "ASC",
+            u32::MAX,
+        )
+    }
+
+    fn quote_on_or_before(
+        &self,
+        ticker: &str,
+        date: NaiveDate,
+    ) -> std::result::Result<Quote, QuoteError> {
+        // the range end is exclusive, so compare against the following day
+        let end = date.succ_opt().expect("Invalid date!");
+        self.get_quotes(
+            ticker,
+            "0000-00-00",
+            &end.format("%Y-%m-%d").to_string(),
+            "DESC",
+            1,
+        )?
+        .pop()
+        .ok_or(QuoteError::NotFound(format!("{} on {}", ticker, date)))
+    }
+}
@@ -0,0 +1,432 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+// Days the NYSE closed outside of its regular holiday schedule, as
+// (year, month, day).
+const UNSCHEDULED_CLOSURES: [(i32, u32, u32); 15] = [
+    (1972, 12, 28), // funeral of Harry S. Truman
+    (1973, 1, 25),  // funeral of Lyndon B. Johnson
+    (1977, 7, 14),  // New York City blackout
+    (1985, 9, 27),  // Hurricane Gloria
+    (1994, 4, 27),  // funeral of Richard M. Nixon
+    (2001, 9, 11),  // September 11th attacks
+    (2001, 9, 12),  // still closed
+    (2001, 9, 13),  // still closed
+    (2001, 9, 14),  // still closed
+    (2004, 6, 11),  // funeral of Ronald W. Reagan
+    (2007, 1, 2),   // funeral of Gerald R. Ford
+    (2012, 10, 29), // Hurricane Sandy
+    (2012, 10, 30), // still closed
+    (2018, 12, 5),  // funeral of George H. W. Bush
+    (2025, 1, 9),   // funeral of Jimmy Carter
+];
+
+// The New York Stock Exchange trading calendar. Holidays follow the
+// exchange's observance rules: a holiday on a Saturday closes the market the
+// Friday before and one on a Sunday closes it the Monday after, except that
+// New Year's Day on a Saturday is not observed at all so that the last day of
+// the year stays open. The regular schedule is modelled from 1971 onward, when
+// Washington's Birthday and Memorial Day moved to Mondays. Early closes follow
+// the current schedule only.
+pub struct TradingCalendar;
+
+impl TradingCalendar {
+    pub fn is_trading_day(date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+            && !Self::is_holiday(date)
+            && !Self::is_unscheduled_closure(date)
+    }
+
+    // the last trading day strictly before the given date
+    pub fn previous_trading_day(date: NaiveDate) -> NaiveDate {
+        let mut day = date;
+        loop {
+            day = day.pred_opt().expect("Date out of range!");
+            if Self::is_trading_day(day) {
+                return day;
+            }
+        }
+    }
+
+    // the first trading day strictly after the given date
+    pub fn next_trading_day(date: NaiveDate) -> NaiveDate {
+        let mut day = date;
+        loop {
+            day = day.succ_opt().expect("Date out of range!");
+            if Self::is_trading_day(day) {
+                return day;
+            }
+        }
+    }
+
+    // the given date if the market was open, otherwise the trading day before it
+    pub fn trading_day_on_or_before(date: NaiveDate) -> NaiveDate {
+        if Self::is_trading_day(date) {
+            date
+        } else {
+            Self::previous_trading_day(date)
+        }
+    }
+
+    // Number of trading days after `start` up to and including `end`, i.e., the
+    // number of closing prices that separate the two dates. Zero when `end` is
+    // not after `start`.
+    pub fn trading_days_between(start: NaiveDate, end: NaiveDate) -> u32 {
+        let mut count = 0;
+        let mut day = start;
+        while day < end {
+            day = day.succ_opt().expect("Date out of range!");
+            if Self::is_trading_day(day) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // The dates on which the market is closed for a holiday in the given year,
+    // after applying the weekend observance rules, in calendar order.
+    pub fn holidays(year: i32) -> Vec<NaiveDate> {
+        let mut holidays: Vec<NaiveDate> = Vec::new();
+
+        // New Year's Day falling on a Saturday is not moved to December 31st
+        let new_years_day = ymd(year, 1, 1);
+        match new_years_day.weekday() {
+            Weekday::Sat => {}
+            Weekday::Sun => holidays.push(ymd(year, 1, 2)),
+            _ => holidays.push(new_years_day),
+        }
+
+        if year >= 1998 {
+            holidays.push(nth_weekday(year, 1, Weekday::Mon, 3)); // Martin Luther King, Jr. Day
+        }
+        holidays.push(nth_weekday(year, 2, Weekday::Mon, 3)); // Washington's Birthday
+        holidays.push(easter_sunday(year) - Days::new(2)); // Good Friday
+        holidays.push(last_weekday(year, 5, Weekday::Mon)); // Memorial Day
+        if year >= 2022 {
+            holidays.push(observed(ymd(year, 6, 19))); // Juneteenth
+        }
+        holidays.push(observed(ymd(year, 7, 4))); // Independence Day
+        holidays.push(nth_weekday(year, 9, Weekday::Mon, 1)); // Labor Day
+        holidays.push(nth_weekday(year, 11, Weekday::Thu, 4)); // Thanksgiving Day
+        holidays.push(observed(ymd(year, 12, 25))); // Christmas Day
+
+        holidays.sort();
+        holidays
+    }
+
+    pub fn is_holiday(date: NaiveDate) -> bool {
+        Self::holidays(date.year()).contains(&date)
+    }
+
+    // The dates on which the market closes at 1:00 p.m. in the given year, in
+    // calendar order: the day before Independence Day, the day after
+    // Thanksgiving and Christmas Eve. The days before Independence Day and
+    // Christmas only close early when they fall on Monday through Thursday,
+    // since on a Friday they are the observed holiday itself.
+    pub fn early_closes(year: i32) -> Vec<NaiveDate> {
+        let mut early_closes: Vec<NaiveDate> = Vec::new();
+        let before_weekday_holiday = |date: NaiveDate| {
+            matches!(
+                date.weekday(),
+                Weekday::Mon | Weekday::Tue | Weekday::Wed | Weekday::Thu
+            )
+        };
+
+        let independence_day_eve = ymd(year, 7, 3);
+        if before_weekday_holiday(independence_day_eve) {
+            early_closes.push(independence_day_eve);
+        }
+        early_closes.push(nth_weekday(year, 11, Weekday::Thu, 4) + Days::new(1));
+        let christmas_eve = ymd(year, 12, 24);
+        if before_weekday_holiday(christmas_eve) {
+            early_closes.push(christmas_eve);
+        }
+        early_closes
+    }
+
+    pub fn is_early_close(date: NaiveDate) -> bool {
+        Self::early_closes(date.year()).contains(&date)
+    }
+
+    fn is_unscheduled_closure(date: NaiveDate) -> bool {
+        UNSCHEDULED_CLOSURES.contains(&(date.year(), date.month(), date.day()))
+    }
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("Invalid holiday date!")
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("Invalid holiday date!")
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    // the fifth occurrence does not exist in every month
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+// moves a fixed-date holiday off of the weekend
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date.pred_opt().expect("Invalid holiday date!"),
+        Weekday::Sun => date.succ_opt().expect("Invalid holiday date!"),
+        _ => date,
+    }
+}
+
+// Anonymous Gregorian algorithm (Meeus/Jones/Butcher)
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    ymd(year, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn holidays_follow_observance_rules() {
+        let cases: [(i32, &[&str]); 8] = [
+            (
+                1997,
+                &[
+                    "1997-01-01",
+                    "1997-02-17",
+                    "1997-03-28",
+                    "1997-05-26",
+                    "1997-07-04",
+                    "1997-09-01",
+                    "1997-11-27",
+                    "1997-12-25",
+                ],
+            ),
+            (
+                2010,
+                &[
+                    "2010-01-01",
+                    "2010-01-18",
+                    "2010-02-15",
+                    "2010-04-02",
+                    "2010-05-31",
+                    "2010-07-05",
+                    "2010-09-06",
+                    "2010-11-25",
+                    "2010-12-24",
+                ],
+            ),
+            (
+                2015,
+                &[
+                    "2015-01-01",
+                    "2015-01-19",
+                    "2015-02-16",
+                    "2015-04-03",
+                    "2015-05-25",
+                    "2015-07-03",
+                    "2015-09-07",
+                    "2015-11-26",
+                    "2015-12-25",
+                ],
+            ),
+            (
+                2021,
+                &[
+                    "2021-01-01",
+                    "2021-01-18",
+                    "2021-02-15",
+                    "2021-04-02",
+                    "2021-05-31",
+                    "2021-07-05",
+                    "2021-09-06",
+                    "2021-11-25",
+                    "2021-12-24",
+                ],
+            ),
+            (
+                2022,
+                &[
+                    "2022-01-17",
+                    "2022-02-21",
+                    "2022-04-15",
+                    "2022-05-30",
+                    "2022-06-20",
+                    "2022-07-04",
+                    "2022-09-05",
+                    "2022-11-24",
+                    "2022-12-26",
+                ],
+            ),
+            (
+                2023,
+                &[
+                    "2023-01-02",
+                    "2023-01-16",
+                    "2023-02-20",
+                    "2023-04-07",
+                    "2023-05-29",
+                    "2023-06-19",
+                    "2023-07-04",
+                    "2023-09-04",
+                    "2023-11-23",
+                    "2023-12-25",
+                ],
+            ),
+            (
+                2024,
+                &[
+                    "2024-01-01",
+                    "2024-01-15",
+                    "2024-02-19",
+                    "2024-03-29",
+                    "2024-05-27",
+                    "2024-06-19",
+                    "2024-07-04",
+                    "2024-09-02",
+                    "2024-11-28",
+                    "2024-12-25",
+                ],
+            ),
+            (
+                2025,
+                &[
+                    "2025-01-01",
+                    "2025-01-20",
+                    "2025-02-17",
+                    "2025-04-18",
+                    "2025-05-26",
+                    "2025-06-19",
+                    "2025-07-04",
+                    "2025-09-01",
+                    "2025-11-27",
+                    "2025-12-25",
+                ],
+            ),
+        ];
+        for (year, expected) in cases {
+            let expected: Vec<NaiveDate> = expected.iter().map(|day| date(day)).collect();
+            assert_eq!(TradingCalendar::holidays(year), expected, "{}", year);
+        }
+    }
+
+    #[test]
+    fn early_closes_skip_observed_holidays() {
+        let cases: [(i32, &[&str]); 6] = [
+            (2019, &["2019-07-03", "2019-11-29", "2019-12-24"]),
+            (2020, &["2020-11-27", "2020-12-24"]),
+            (2021, &["2021-11-26"]),
+            (2023, &["2023-07-03", "2023-11-24"]),
+            (2024, &["2024-07-03", "2024-11-29", "2024-12-24"]),
+            (2025, &["2025-07-03", "2025-11-28", "2025-12-24"]),
+        ];
+        for (year, expected) in cases {
+            let expected: Vec<NaiveDate> = expected.iter().map(|day| date(day)).collect();
+            assert_eq!(TradingCalendar::early_closes(year), expected, "{}", year);
+            for day in expected {
+                assert!(TradingCalendar::is_early_close(day), "{}", day);
+                assert!(TradingCalendar::is_trading_day(day), "{}", day);
+            }
+        }
+    }
+
+    #[test]
+    fn trading_days() {
+        let cases = [
+            ("2024-07-03", true),
+            ("2024-07-06", false),
+            ("2024-07-07", false),
+            ("2020-07-03", false),
+            ("2021-12-31", true),
+            ("2022-01-03", true),
+            ("2001-09-12", false),
+            ("2012-10-29", false),
+            ("2018-12-05", false),
+            ("2025-01-09", false),
+            ("2025-01-10", true),
+        ];
+        for (day, expected) in cases {
+            assert_eq!(
+                TradingCalendar::is_trading_day(date(day)),
+                expected,
+                "{}",
+                day
+            );
+        }
+    }
+
+    #[test]
+    fn steps_between_trading_days() {
+        let previous = [
+            ("2021-07-06", "2021-07-02"),
+            ("2025-01-10", "2025-01-08"),
+            ("2001-09-17", "2001-09-10"),
+        ];
+        for (day, expected) in previous {
+            assert_eq!(
+                TradingCalendar::previous_trading_day(date(day)),
+                date(expected),
+                "{}",
+                day
+            );
+        }
+
+        let next = [("2022-12-23", "2022-12-27"), ("2023-12-29", "2024-01-02")];
+        for (day, expected) in next {
+            assert_eq!(
+                TradingCalendar::next_trading_day(date(day)),
+                date(expected),
+                "{}",
+                day
+            );
+        }
+
+        let between = [
+            ("2024-12-20", "2024-12-27", 4),
+            ("2024-12-20", "2024-12-20", 0),
+            ("2024-12-27", "2024-12-20", 0),
+            ("2001-09-10", "2001-09-17", 1),
+        ];
+        for (start, end, expected) in between {
+            assert_eq!(
+                TradingCalendar::trading_days_between(date(start), date(end)),
+                expected,
+                "{} to {}",
+                start,
+                end
+            );
+        }
+    }
+}
@@ -22,14 +22,21 @@ use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
 use std::fmt;
-use strum::{Display, EnumIter, FromRepr};
+use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
 
+pub mod calendar;
+pub mod money;
+#[cfg(feature = "sqlite")]
+pub mod quote_cache;
+pub mod quotes;
 pub mod stocks;
 
+pub use money::{Money, Quantity};
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct LedgerEntry {
     pub date: String,
-    pub amount: f32,
+    pub amount: Money,
     #[serde(deserialize_with = "deserialize_transfer_type")]
     pub transfer_type: TransferType,
     pub participant: String,
@@ -41,9 +48,9 @@ pub struct LedgerEntry {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StockInfo {
-    pub shares: f32,
-    pub costbasis: f32,
-    pub remaining: f32,
+    pub shares: Quantity,
+    pub costbasis: Quantity,
+    pub remaining: Quantity,
     pub is_buy: bool,
     pub is_split: bool,
 }
@@ -51,7 +58,7 @@ pub struct StockInfo {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FlatLedgerEntry {
     pub date: String,
-    pub amount: f32,
+    pub amount: Money,
     #[serde(serialize_with = "serialize_transfer_type")]
     pub transfer_type: TransferType,
     pub participant: String,
@@ -142,6 +149,29 @@ impl TransferType {
     }
 }
 
+// Income a holding pays out. It is recorded as a deposit from the holding,
+// under the category of its kind, and may be reinvested in the holding.
+#[derive(PartialEq, Clone, Copy, Debug, Display, FromRepr, EnumIter)]
+pub enum DistributionType {
+    Dividend,
+    #[strum(to_string = "Capital Gain Distribution")]
+    CapitalGain,
+    Interest,
+}
+
+// the category of a purchase that reinvests a distribution
+pub const REINVESTMENT_CATEGORY: &str = "REINVEST";
+
+impl DistributionType {
+    pub fn category(&self) -> String {
+        self.to_string().to_ascii_uppercase()
+    }
+
+    pub fn from_category(category: &str) -> Option<Self> {
+        DistributionType::iter().find(|kind| kind.category().eq_ignore_ascii_case(category.trim()))
+    }
+}
+
 pub fn deserialize_transfer_type<'de, D>(deserializer: D) -> Result<TransferType, D::Error>
 where
     D: Deserializer<'de>,
@@ -0,0 +1,650 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+// Money is stored as a whole number of cents.
+const MONEY_DECIMALS: u32 = 2;
+const MONEY_SCALE: i64 = 100;
+
+// Share counts, per-share prices and split ratios are stored as a whole
+// number of millionths, which covers the fractional shares handed out by
+// brokerages and mutual fund NAVs.
+const QUANTITY_DECIMALS: u32 = 6;
+const QUANTITY_SCALE: i64 = 1_000_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Quantity(i64);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError(String);
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid decimal value '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+// divide, rounding half away from zero
+fn div_round(numerator: i128, denominator: i128) -> i64 {
+    let half = denominator / 2;
+    let rounded = if (numerator < 0) != (denominator < 0) {
+        (numerator - half) / denominator
+    } else {
+        (numerator + half) / denominator
+    };
+    rounded as i64
+}
+
+// raise to i128 precision ahead of a rounded division
+fn widen(units: i64, scale: i64) -> i128 {
+    units as i128 * scale as i128
+}
+
+fn scaled_from_f64(value: f64, scale: i64) -> i64 {
+    (value * scale as f64).round() as i64
+}
+
+// Parses a decimal string such as "1234.5", "-0.01", "$1,234.56" or the
+// accounting style "(12.00)" into an integer number of 10^-decimals units.
+// Digits beyond the supported precision are rounded half away from zero.
+fn parse_scaled(input: &str, decimals: u32) -> Result<i64, ParseDecimalError> {
+    let err = || ParseDecimalError(input.to_string());
+    let mut s: String = input
+        .trim()
+        .chars()
+        .filter(|c| *c != ',' && *c != '$' && !c.is_whitespace())
+        .collect();
+
+    let mut negative = false;
+    if s.starts_with('(') && s.ends_with(')') {
+        negative = true;
+        s = s[1..s.len() - 1].to_string();
+    }
+    if let Some(rest) = s.strip_prefix('-') {
+        negative = !negative;
+        s = rest.to_string();
+    } else if let Some(rest) = s.strip_prefix('+') {
+        s = rest.to_string();
+    }
+
+    // tolerate values written in scientific notation by older exports
+    if s.contains(['e', 'E']) {
+        let value: f64 = s.parse().map_err(|_| err())?;
+        let scaled = scaled_from_f64(value, 10_i64.pow(decimals));
+        return Ok(if negative { -scaled } else { scaled });
+    }
+
+    let (whole, fraction) = match s.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (s.as_str(), ""),
+    };
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(err());
+    }
+
+    let whole: i128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| err())?
+    };
+    let mut units = whole * 10_i128.pow(decimals);
+    let mut digits = fraction.chars();
+    for place in (0..decimals).rev() {
+        let digit = digits.next().map_or(0, |c| c.to_digit(10).unwrap()) as i128;
+        units += digit * 10_i128.pow(place);
+    }
+    if let Some(next) = digits.next() {
+        if next.to_digit(10).unwrap() >= 5 {
+            units += 1;
+        }
+    }
+
+    let units = i64::try_from(units).map_err(|_| err())?;
+    Ok(if negative { -units } else { units })
+}
+
+fn format_scaled(
+    f: &mut fmt::Formatter<'_>,
+    units: i64,
+    scale: i64,
+    decimals: u32,
+    trim: bool,
+) -> fmt::Result {
+    let whole = units.unsigned_abs() / scale as u64;
+    let fraction = units.unsigned_abs() % scale as u64;
+    let mut digits = format!("{}.{:0width$}", whole, fraction, width = decimals as usize);
+    if trim {
+        while digits.ends_with('0') {
+            digits.pop();
+        }
+        if digits.ends_with('.') {
+            digits.pop();
+        }
+    }
+    // pad_integral honours width, fill and sign flags while leaving the
+    // digits alone, so "{:>10}" and "{:+}" behave as they do for floats
+    f.pad_integral(units >= 0, "", &digits)
+}
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    pub const fn cents(&self) -> i64 {
+        self.0
+    }
+
+    pub fn from_f64(value: f64) -> Self {
+        Money(scaled_from_f64(value, MONEY_SCALE))
+    }
+
+    // Only intended for ratios and charting; all bookkeeping stays in cents.
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / MONEY_SCALE as f64
+    }
+
+    pub fn abs(&self) -> Self {
+        Money(self.0.abs())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0 > 0
+    }
+
+    // amount per unit, e.g. the price paid per share; None when dividing by zero
+    pub fn checked_div(&self, rhs: Quantity) -> Option<Quantity> {
+        if rhs.is_zero() {
+            return None;
+        }
+        Some(Quantity(div_round(
+            self.0 as i128 * (QUANTITY_SCALE / MONEY_SCALE) as i128 * QUANTITY_SCALE as i128,
+            rhs.0 as i128,
+        )))
+    }
+}
+
+impl Quantity {
+    pub const ZERO: Quantity = Quantity(0);
+    pub const ONE: Quantity = Quantity(QUANTITY_SCALE);
+
+    pub const fn from_micros(micros: i64) -> Self {
+        Quantity(micros)
+    }
+
+    pub const fn micros(&self) -> i64 {
+        self.0
+    }
+
+    pub fn from_f64(value: f64) -> Self {
+        Quantity(scaled_from_f64(value, QUANTITY_SCALE))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / QUANTITY_SCALE as f64
+    }
+
+    pub fn abs(&self) -> Self {
+        Quantity(self.0.abs())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0 > 0
+    }
+
+    // value of this many shares at the given per-share price, rounded to the cent
+    pub fn value_at(&self, price: Quantity) -> Money {
+        Money(div_round(
+            self.0 as i128 * price.0 as i128,
+            (QUANTITY_SCALE as i128 * QUANTITY_SCALE as i128) / MONEY_SCALE as i128,
+        ))
+    }
+
+    // ratio of two quantities, e.g. a split factor; None when dividing by zero
+    pub fn checked_div(&self, rhs: Quantity) -> Option<Quantity> {
+        if rhs.is_zero() {
+            return None;
+        }
+        Some(Quantity(div_round(
+            widen(self.0, QUANTITY_SCALE),
+            rhs.0 as i128,
+        )))
+    }
+
+    pub fn to_money(&self) -> Money {
+        Money(div_round(
+            self.0 as i128,
+            (QUANTITY_SCALE / MONEY_SCALE) as i128,
+        ))
+    }
+}
+
+impl From<Money> for Quantity {
+    fn from(value: Money) -> Self {
+        Quantity(value.0 * (QUANTITY_SCALE / MONEY_SCALE))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+// scale an amount, e.g. by a split ratio or a number of shares
+impl Mul<Quantity> for Money {
+    type Output = Money;
+    fn mul(self, rhs: Quantity) -> Money {
+        Money(div_round(
+            self.0 as i128 * rhs.0 as i128,
+            QUANTITY_SCALE as i128,
+        ))
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |acc, x| acc + *x)
+    }
+}
+
+impl Add for Quantity {
+    type Output = Quantity;
+    fn add(self, rhs: Quantity) -> Quantity {
+        Quantity(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Quantity {
+    type Output = Quantity;
+    fn sub(self, rhs: Quantity) -> Quantity {
+        Quantity(self.0 - rhs.0)
+    }
+}
+
+impl Neg for Quantity {
+    type Output = Quantity;
+    fn neg(self) -> Quantity {
+        Quantity(-self.0)
+    }
+}
+
+impl AddAssign for Quantity {
+    fn add_assign(&mut self, rhs: Quantity) {
+        self.0 += rhs.0;
+    }
+}
+
+impl SubAssign for Quantity {
+    fn sub_assign(&mut self, rhs: Quantity) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Mul for Quantity {
+    type Output = Quantity;
+    fn mul(self, rhs: Quantity) -> Quantity {
+        Quantity(div_round(
+            self.0 as i128 * rhs.0 as i128,
+            QUANTITY_SCALE as i128,
+        ))
+    }
+}
+
+impl Sum for Quantity {
+    fn sum<I: Iterator<Item = Quantity>>(iter: I) -> Quantity {
+        iter.fold(Quantity::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a> Sum<&'a Quantity> for Quantity {
+    fn sum<I: Iterator<Item = &'a Quantity>>(iter: I) -> Quantity {
+        iter.fold(Quantity::ZERO, |acc, x| acc + *x)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_scaled(f, self.0, MONEY_SCALE, MONEY_DECIMALS, false)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_scaled(f, self.0, QUANTITY_SCALE, QUANTITY_DECIMALS, true)
+    }
+}
+
+impl FromStr for Money {
+    type Err = ParseDecimalError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_scaled(s, MONEY_DECIMALS).map(Money)
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = ParseDecimalError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_scaled(s, QUANTITY_DECIMALS).map(Quantity)
+    }
+}
+
+// Both types serialize as decimal strings so that CSV and JSON files hold
+// the exact value rather than a binary float approximation.
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+struct ScaledVisitor {
+    decimals: u32,
+}
+
+impl<'de> Visitor<'de> for ScaledVisitor {
+    type Value = i64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal number")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<i64, E> {
+        parse_scaled(value, self.decimals).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<i64, E> {
+        Ok(value * 10_i64.pow(self.decimals))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<i64, E> {
+        Ok(value as i64 * 10_i64.pow(self.decimals))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<i64, E> {
+        Ok(scaled_from_f64(value, 10_i64.pow(self.decimals)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(ScaledVisitor {
+                decimals: MONEY_DECIMALS,
+            })
+            .map(Money)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(ScaledVisitor {
+                decimals: QUANTITY_DECIMALS,
+            })
+            .map(Quantity)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sql {
+    use super::{Money, Quantity};
+    use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+    impl ToSql for Money {
+        fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+            Ok(ToSqlOutput::from(self.0))
+        }
+    }
+
+    impl FromSql for Money {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            match value {
+                ValueRef::Integer(cents) => Ok(Money(cents)),
+                _ => Err(FromSqlError::InvalidType),
+            }
+        }
+    }
+
+    impl ToSql for Quantity {
+        fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+            Ok(ToSqlOutput::from(self.0))
+        }
+    }
+
+    impl FromSql for Quantity {
+        fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+            match value {
+                ValueRef::Integer(micros) => Ok(Quantity(micros)),
+                _ => Err(FromSqlError::InvalidType),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(s: &str) -> Money {
+        s.parse().unwrap()
+    }
+
+    fn quantity(s: &str) -> Quantity {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn div_round_rounds_half_away_from_zero() {
+        let cases = [
+            (5, 2, 3),
+            (4, 2, 2),
+            (7, 3, 2),
+            (8, 3, 3),
+            (-5, 2, -3),
+            (5, -2, -3),
+            (-5, -2, 3),
+            (-7, 3, -2),
+            (-8, 3, -3),
+            (0, 7, 0),
+        ];
+        for (numerator, denominator, expected) in cases {
+            assert_eq!(
+                div_round(numerator, denominator),
+                expected,
+                "{} / {}",
+                numerator,
+                denominator
+            );
+        }
+    }
+
+    #[test]
+    fn parses_money() {
+        let cases = [
+            ("1234.5", 123450),
+            ("$1,234.56", 123456),
+            ("-0.01", -1),
+            ("+3", 300),
+            ("(12.00)", -1200),
+            ("(-12.00)", 1200),
+            (".5", 50),
+            ("0.005", 1),
+            ("-0.005", -1),
+            ("0.00499", 0),
+            ("1.5e2", 15000),
+        ];
+        for (input, cents) in cases {
+            assert_eq!(money(input), Money::from_cents(cents), "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        for input in ["", ".", "abc", "1.2.3", "--1", "1-", "12a.00"] {
+            assert!(input.parse::<Money>().is_err(), "{}", input);
+            assert!(input.parse::<Quantity>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn displays_values() {
+        assert_eq!(Money::from_cents(123456).to_string(), "1234.56");
+        assert_eq!(Money::from_cents(-1).to_string(), "-0.01");
+        assert_eq!(Money::from_cents(-1200).to_string(), "-12.00");
+        assert_eq!(Money::ZERO.to_string(), "0.00");
+        assert_eq!(format!("{:>8}", Money::from_cents(-150)), "   -1.50");
+        assert_eq!(format!("{:+}", Money::from_cents(150)), "+1.50");
+
+        assert_eq!(quantity("10").to_string(), "10");
+        assert_eq!(quantity("0.125").to_string(), "0.125");
+        assert_eq!(quantity("-2.500000").to_string(), "-2.5");
+        assert_eq!(quantity("1.0000005").to_string(), "1.000001");
+    }
+
+    #[test]
+    fn round_trips_through_strings() {
+        for input in ["0.00", "-0.01", "1234.56", "-98765.43"] {
+            assert_eq!(money(input).to_string(), input);
+        }
+        for input in ["0", "-0.000001", "3.14159", "-1500"] {
+            assert_eq!(quantity(input).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn arithmetic_with_negatives() {
+        assert_eq!(money("-10.00") + money("2.50"), money("-7.50"));
+        assert_eq!(money("2.50") - money("10.00"), money("-7.50"));
+        assert_eq!(-money("-7.50"), money("7.50"));
+        assert_eq!(money("-7.50").abs(), money("7.50"));
+        assert!(money("-0.01").is_negative());
+        assert!(!Money::ZERO.is_negative() && !Money::ZERO.is_positive());
+
+        // values are rounded to the cent, half away from zero
+        assert_eq!(money("10.01") * quantity("0.5"), money("5.01"));
+        assert_eq!(money("-10.01") * quantity("0.5"), money("-5.01"));
+        assert_eq!(quantity("-3").value_at(quantity("1.005")), money("-3.02"));
+        assert_eq!(quantity("2.5").value_at(quantity("-4")), money("-10.00"));
+        assert_eq!(quantity("-0.005").to_money(), money("-0.01"));
+    }
+
+    #[test]
+    fn checked_div_refuses_zero() {
+        assert_eq!(money("10.00").checked_div(Quantity::ZERO), None);
+        assert_eq!(quantity("10").checked_div(Quantity::ZERO), None);
+
+        assert_eq!(
+            money("10.00").checked_div(quantity("3")),
+            Some(quantity("3.333333"))
+        );
+        assert_eq!(
+            money("-10.00").checked_div(quantity("3")),
+            Some(quantity("-3.333333"))
+        );
+        assert_eq!(
+            money("20.00").checked_div(quantity("3")),
+            Some(quantity("6.666667"))
+        );
+        assert_eq!(
+            quantity("1").checked_div(quantity("-8")),
+            Some(quantity("-0.125"))
+        );
+        assert_eq!(
+            quantity("2").checked_div(quantity("3")),
+            Some(quantity("0.666667"))
+        );
+    }
+
+    #[test]
+    fn sums() {
+        let amounts = [money("1.10"), money("-0.10"), money("2.00")];
+        assert_eq!(amounts.iter().sum::<Money>(), money("3.00"));
+        assert_eq!(amounts.into_iter().sum::<Money>(), money("3.00"));
+        let shares = [quantity("0.5"), quantity("-0.25")];
+        assert_eq!(shares.iter().sum::<Quantity>(), quantity("0.25"));
+    }
+}
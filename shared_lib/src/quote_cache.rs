@@ -0,0 +1,600 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use yahoo_finance_api::Quote;
+
+use crate::quotes::{quote_from_close, timestamp_to_date, QuoteError, QuoteProvider};
+use crate::Quantity;
+
+// how long a latest close is reused before the provider is asked again
+const LATEST_CLOSE_TTL_SECONDS: i64 = 15 * 60;
+
+// how far back a single lookup searches for the last close before a date,
+// which comfortably spans any run of consecutive market closures
+const QUOTE_LOOKBACK_DAYS: u64 = 10;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// Keeps every close fetched from the wrapped provider in the quote_cache table
+// and only asks the provider for the dates it has not seen. For each ticker,
+// quote_cache_tickers records the contiguous range of dates that has been
+// fetched and the last live close.
+//
+// The cache has its own connection to the database, so it can be consulted
+// while a query on the application connection is evaluating get_stock_value.
+// Writes are best effort: if that connection is in the middle of a
+// transaction the database is locked and the close is simply fetched again
+// next time.
+pub struct CachedQuoteProvider {
+    provider: Arc<dyn QuoteProvider>,
+    conn: Mutex<Connection>,
+    stale: Mutex<HashMap<String, NaiveDate>>,
+}
+
+impl CachedQuoteProvider {
+    pub fn open(
+        db_path: impl AsRef<Path>,
+        provider: Arc<dyn QuoteProvider>,
+    ) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(db_path)?, provider)
+    }
+
+    // for connections that need setting up first, such as keying an
+    // encrypted database
+    pub fn with_connection(
+        conn: Connection,
+        provider: Arc<dyn QuoteProvider>,
+    ) -> rusqlite::Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS quote_cache (
+                ticker          TEXT NOT NULL,
+                date            TEXT NOT NULL,
+                close           INTEGER NOT NULL,
+                PRIMARY KEY (ticker, date)
+            );
+            CREATE TABLE IF NOT EXISTS quote_cache_tickers (
+                ticker          TEXT NOT NULL PRIMARY KEY,
+                first_date      TEXT,
+                end_date        TEXT,
+                latest_close    INTEGER,
+                latest_fetched  INTEGER
+            );",
+        )?;
+        Ok(Self {
+            provider,
+            conn: Mutex::new(conn),
+            stale: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // returns the fetched range [first_date, end_date) for a ticker
+    fn get_coverage(&self, ticker: &str) -> rusqlite::Result<Option<(NaiveDate, NaiveDate)>> {
+        let sql = "SELECT first_date, end_date FROM quote_cache_tickers WHERE ticker = (?1) and first_date IS NOT NULL";
+        let conn_lock = self.conn.lock().unwrap();
+        let range = conn_lock
+            .query_row(sql, [ticker], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .optional()?;
+        Ok(range.and_then(|(first, end)| {
+            Some((
+                NaiveDate::parse_from_str(&first, DATE_FORMAT).ok()?,
+                NaiveDate::parse_from_str(&end, DATE_FORMAT).ok()?,
+            ))
+        }))
+    }
+
+    fn store_history(
+        &self,
+        ticker: &str,
+        quotes: &[Quote],
+        first_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> rusqlite::Result<()> {
+        let mut conn_lock = self.conn.lock().unwrap();
+        let tx = conn_lock.transaction()?;
+        for quote in quotes {
+            tx.execute(
+                "INSERT OR REPLACE INTO quote_cache (ticker, date, close) VALUES (?1, ?2, ?3)",
+                rusqlite::params![
+                    ticker,
+                    timestamp_to_date(quote.timestamp)
+                        .format(DATE_FORMAT)
+                        .to_string(),
+                    Quantity::from_f64(quote.close),
+                ],
+            )?;
+        }
+        tx.execute(
+            "INSERT INTO quote_cache_tickers (ticker, first_date, end_date) VALUES (?1, ?2, ?3)
+            ON CONFLICT (ticker) DO UPDATE SET first_date = excluded.first_date, end_date = excluded.end_date",
+            rusqlite::params![
+                ticker,
+                first_date.format(DATE_FORMAT).to_string(),
+                end_date.format(DATE_FORMAT).to_string(),
+            ],
+        )?;
+        tx.commit()
+    }
+
+    fn store_latest_close(&self, ticker: &str, close: f64) -> rusqlite::Result<()> {
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock.execute(
+            "INSERT INTO quote_cache_tickers (ticker, latest_close, latest_fetched) VALUES (?1, ?2, ?3)
+            ON CONFLICT (ticker) DO UPDATE SET latest_close = excluded.latest_close, latest_fetched = excluded.latest_fetched",
+            rusqlite::params![ticker, Quantity::from_f64(close), Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    // returns the last live close and when it was fetched, in seconds
+    fn get_latest_close(&self, ticker: &str) -> rusqlite::Result<Option<(f64, i64)>> {
+        let sql = "SELECT latest_close, latest_fetched FROM quote_cache_tickers WHERE ticker = (?1) and latest_close IS NOT NULL";
+        let conn_lock = self.conn.lock().unwrap();
+        conn_lock
+            .query_row(sql, [ticker], |row| {
+                Ok((row.get::<_, Quantity>(0)?.to_f64(), row.get::<_, i64>(1)?))
+            })
+            .optional()
+    }
+
+    fn get_cached_history(
+        &self,
+        ticker: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> rusqlite::Result<Vec<Quote>> {
+        let sql = "SELECT date, close FROM quote_cache WHERE ticker = (?1) and date >= (?2) and date < (?3) ORDER BY date";
+        let conn_lock = self.conn.lock().unwrap();
+        let mut stmt = conn_lock.prepare(sql)?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![
+                    ticker,
+                    start.format(DATE_FORMAT).to_string(),
+                    end.format(DATE_FORMAT).to_string(),
+                ],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Quantity>(1)?)),
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(date, close)| {
+                let date = NaiveDate::parse_from_str(&date, DATE_FORMAT).ok()?;
+                Some(quote_from_close(date, close.to_f64()))
+            })
+            .collect())
+    }
+
+    fn get_last_cached_quote(&self, ticker: &str) -> rusqlite::Result<Option<Quote>> {
+        let sql =
+            "SELECT date, close FROM quote_cache WHERE ticker = (?1) ORDER BY date DESC LIMIT 1";
+        let conn_lock = self.conn.lock().unwrap();
+        let row = conn_lock
+            .query_row(sql, [ticker], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Quantity>(1)?))
+            })
+            .optional()?;
+        Ok(row.and_then(|(date, close)| {
+            let date = NaiveDate::parse_from_str(&date, DATE_FORMAT).ok()?;
+            Some(quote_from_close(date, close.to_f64()))
+        }))
+    }
+
+    fn mark_stale(&self, ticker: &str, date: NaiveDate) {
+        self.stale.lock().unwrap().insert(ticker.to_string(), date);
+    }
+
+    fn mark_fresh(&self, ticker: &str) {
+        self.stale.lock().unwrap().remove(ticker);
+    }
+}
+
+fn to_quote_error(error: rusqlite::Error) -> QuoteError {
+    QuoteError::Source(format!("quote cache: {}", error))
+}
+
+impl QuoteProvider for CachedQuoteProvider {
+    fn latest_close(&self, ticker: &str) -> Result<f64, QuoteError> {
+        let cached = self.get_latest_close(ticker).map_err(to_quote_error)?;
+        if let Some((close, fetched)) = cached {
+            if Utc::now().timestamp() - fetched < LATEST_CLOSE_TTL_SECONDS {
+                return Ok(close);
+            }
+        }
+
+        match self.provider.latest_close(ticker) {
+            Ok(close) => {
+                let _ = self.store_latest_close(ticker, close);
+                self.mark_fresh(ticker);
+                Ok(close)
+            }
+            Err(error) if error.is_unreachable() => {
+                if let Some((close, fetched)) = cached {
+                    let fetched_on = DateTime::from_timestamp(fetched, 0)
+                        .map(|fetched| fetched.date_naive())
+                        .unwrap_or(Local::now().date_naive());
+                    self.mark_stale(ticker, fetched_on);
+                    return Ok(close);
+                }
+                match self.get_last_cached_quote(ticker).map_err(to_quote_error)? {
+                    Some(quote) => {
+                        self.mark_stale(ticker, timestamp_to_date(quote.timestamp));
+                        Ok(quote.close)
+                    }
+                    None => Err(error),
+                }
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn history(
+        &self,
+        ticker: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Quote>, QuoteError> {
+        // today's close is not final until the market has closed, so the
+        // fetched range never extends past yesterday
+        let today = Local::now().date_naive();
+        let coverage = self.get_coverage(ticker).map_err(to_quote_error)?;
+
+        // the fetched range is kept contiguous, so any gap between it and the
+        // request is fetched as well
+        let missing = match coverage {
+            None => vec![(start, end)],
+            Some((first, last)) => {
+                let mut missing = Vec::new();
+                if start < first {
+                    missing.push((start, first));
+                }
+                if end > last {
+                    missing.push((last, end));
+                }
+                missing
+            }
+        };
+
+        let mut unreachable: Option<QuoteError> = None;
+        let (mut first, mut last) = coverage.unwrap_or((start, start));
+        for (from, to) in missing {
+            let quotes = match self.provider.history(ticker, from, to) {
+                Ok(quotes) => quotes,
+                Err(error) if error.is_unreachable() => {
+                    unreachable = Some(error);
+                    continue;
+                }
+                // the provider has nothing for these dates
+                Err(_) => Vec::new(),
+            };
+            first = first.min(from);
+            last = last.max(to.min(today));
+            let _ = self.store_history(ticker, &quotes, first, last);
+        }
+
+        let quotes = self
+            .get_cached_history(ticker, start, end)
+            .map_err(to_quote_error)?;
+        match unreachable {
+            None => {
+                self.mark_fresh(ticker);
+                Ok(quotes)
+            }
+            Some(error) => match quotes.last() {
+                Some(quote) => {
+                    self.mark_stale(ticker, timestamp_to_date(quote.timestamp));
+                    Ok(quotes)
+                }
+                None => Err(error),
+            },
+        }
+    }
+
+    fn quote_on_or_before(&self, ticker: &str, date: NaiveDate) -> Result<Quote, QuoteError> {
+        let window_start = date
+            .checked_sub_days(Days::new(QUOTE_LOOKBACK_DAYS))
+            .expect("Invalid date!");
+        let window_end = date.succ_opt().expect("Invalid date!");
+
+        // a close inside the fetched range is final, so there is no need to
+        // fetch the rest of the window
+        if let Some((first, last)) = self.get_coverage(ticker).map_err(to_quote_error)? {
+            if first <= date && window_end <= last {
+                let cached = self
+                    .get_cached_history(ticker, window_start, window_end)
+                    .map_err(to_quote_error)?;
+                if let Some(quote) = cached.last() {
+                    return Ok(quote.clone());
+                }
+            }
+        }
+
+        if let Some(quote) = self.history(ticker, window_start, window_end)?.pop() {
+            return Ok(quote);
+        }
+        self.provider.quote_on_or_before(ticker, date)
+    }
+
+    fn stale_quotes(&self) -> Vec<(String, NaiveDate)> {
+        let mut stale: Vec<(String, NaiveDate)> = self
+            .stale
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(ticker, date)| (ticker.clone(), *date))
+            .collect();
+        stale.sort();
+        stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quotes::MockQuoteProvider;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use yahoo_finance_api::YahooError;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    // Records what the cache asks for and can be cut off from the network.
+    #[derive(Default)]
+    struct Source {
+        prices: MockQuoteProvider,
+        unreachable: AtomicBool,
+        latest_requests: AtomicU32,
+        history_requests: Mutex<Vec<(NaiveDate, NaiveDate)>>,
+    }
+
+    impl Source {
+        fn check(&self) -> Result<(), QuoteError> {
+            match self.unreachable.load(Ordering::SeqCst) {
+                true => Err(QuoteError::Yahoo(YahooError::FetchFailed(
+                    "offline".to_string(),
+                ))),
+                false => Ok(()),
+            }
+        }
+
+        fn take_history_requests(&self) -> Vec<(NaiveDate, NaiveDate)> {
+            std::mem::take(&mut *self.history_requests.lock().unwrap())
+        }
+    }
+
+    impl QuoteProvider for Source {
+        fn latest_close(&self, ticker: &str) -> Result<f64, QuoteError> {
+            self.latest_requests.fetch_add(1, Ordering::SeqCst);
+            self.check()?;
+            self.prices.latest_close(ticker)
+        }
+
+        fn history(
+            &self,
+            ticker: &str,
+            start: NaiveDate,
+            end: NaiveDate,
+        ) -> Result<Vec<Quote>, QuoteError> {
+            self.history_requests.lock().unwrap().push((start, end));
+            self.check()?;
+            self.prices.history(ticker, start, end)
+        }
+    }
+
+    fn cache() -> (Arc<Source>, CachedQuoteProvider) {
+        let source = Arc::new(Source {
+            prices: MockQuoteProvider::new()
+                .with_close("ACME", date("2024-01-02"), 10.0)
+                .with_close("ACME", date("2024-01-03"), 11.0)
+                .with_close("ACME", date("2024-01-04"), 12.0)
+                .with_close("ACME", date("2024-01-05"), 13.0)
+                .with_close("ACME", date("2024-01-08"), 14.0),
+            ..Default::default()
+        });
+        let cache = CachedQuoteProvider::with_connection(
+            Connection::open_in_memory().unwrap(),
+            source.clone(),
+        )
+        .unwrap();
+        (source, cache)
+    }
+
+    fn closes(quotes: &[Quote]) -> Vec<f64> {
+        quotes.iter().map(|quote| quote.close).collect()
+    }
+
+    // moves the fetch time of the cached latest close back by some seconds
+    fn age_latest_close(cache: &CachedQuoteProvider, seconds: i64) {
+        cache
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE quote_cache_tickers SET latest_fetched = latest_fetched - (?1)",
+                [seconds],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn fetches_only_what_is_outside_the_coverage() {
+        let (source, cache) = cache();
+        let cases = [
+            // (start, end, requested from the source, coverage afterwards, closes)
+            (
+                "2024-01-03",
+                "2024-01-05",
+                vec![("2024-01-03", "2024-01-05")],
+                ("2024-01-03", "2024-01-05"),
+                vec![11.0, 12.0],
+            ),
+            (
+                "2024-01-04",
+                "2024-01-05",
+                vec![],
+                ("2024-01-03", "2024-01-05"),
+                vec![12.0],
+            ),
+            (
+                "2024-01-01",
+                "2024-01-03",
+                vec![("2024-01-01", "2024-01-03")],
+                ("2024-01-01", "2024-01-05"),
+                vec![10.0],
+            ),
+            // a request past the coverage fetches the gap up to it as well
+            (
+                "2024-01-08",
+                "2024-01-09",
+                vec![("2024-01-05", "2024-01-09")],
+                ("2024-01-01", "2024-01-09"),
+                vec![14.0],
+            ),
+            (
+                "2024-01-01",
+                "2024-01-09",
+                vec![],
+                ("2024-01-01", "2024-01-09"),
+                vec![10.0, 11.0, 12.0, 13.0, 14.0],
+            ),
+        ];
+        for (start, end, requested, coverage, expected) in cases {
+            let quotes = cache.history("ACME", date(start), date(end)).unwrap();
+            assert_eq!(closes(&quotes), expected, "{} to {}", start, end);
+            let requested: Vec<_> = requested
+                .into_iter()
+                .map(|(from, to)| (date(from), date(to)))
+                .collect();
+            assert_eq!(
+                source.take_history_requests(),
+                requested,
+                "{} to {}",
+                start,
+                end
+            );
+            assert_eq!(
+                cache.get_coverage("ACME").unwrap(),
+                Some((date(coverage.0), date(coverage.1))),
+                "{} to {}",
+                start,
+                end
+            );
+        }
+
+        // a lookup inside the coverage is answered from the cache
+        let quote = cache
+            .quote_on_or_before("ACME", date("2024-01-07"))
+            .unwrap();
+        assert_eq!(timestamp_to_date(quote.timestamp), date("2024-01-05"));
+        assert_eq!(quote.close, 13.0);
+        assert!(source.take_history_requests().is_empty());
+        assert!(cache.stale_quotes().is_empty());
+
+        // the coverage never reaches past today
+        let today = Local::now().date_naive();
+        let end = today.checked_add_days(Days::new(5)).unwrap();
+        cache.history("ACME", date("2024-01-01"), end).unwrap();
+        assert_eq!(
+            cache.get_coverage("ACME").unwrap(),
+            Some((date("2024-01-01"), today))
+        );
+    }
+
+    #[test]
+    fn reuses_the_latest_close_for_fifteen_minutes() {
+        let (source, cache) = cache();
+        let requests = || source.latest_requests.load(Ordering::SeqCst);
+
+        assert_eq!(cache.latest_close("ACME").unwrap(), 14.0);
+        assert_eq!(requests(), 1);
+        assert_eq!(cache.latest_close("ACME").unwrap(), 14.0);
+        assert_eq!(requests(), 1);
+
+        age_latest_close(&cache, LATEST_CLOSE_TTL_SECONDS - 60);
+        assert_eq!(cache.latest_close("ACME").unwrap(), 14.0);
+        assert_eq!(requests(), 1);
+
+        age_latest_close(&cache, 60);
+        assert_eq!(cache.latest_close("ACME").unwrap(), 14.0);
+        assert_eq!(requests(), 2);
+        assert!(cache.stale_quotes().is_empty());
+
+        // an unknown ticker is not cached
+        assert!(matches!(
+            cache.latest_close("OTHER"),
+            Err(QuoteError::NotFound(_))
+        ));
+        assert!(cache.get_latest_close("OTHER").unwrap().is_none());
+    }
+
+    #[test]
+    fn falls_back_to_the_cache_when_the_source_is_unreachable() {
+        let (source, cache) = cache();
+        cache
+            .history("ACME", date("2024-01-02"), date("2024-01-05"))
+            .unwrap();
+        source.unreachable.store(true, Ordering::SeqCst);
+
+        // without a live close the last cached close is used
+        assert_eq!(cache.latest_close("ACME").unwrap(), 12.0);
+        assert_eq!(
+            cache.stale_quotes(),
+            vec![("ACME".to_string(), date("2024-01-04"))]
+        );
+
+        // history outside the coverage returns what has been cached
+        let quotes = cache
+            .history("ACME", date("2024-01-03"), date("2024-01-09"))
+            .unwrap();
+        assert_eq!(closes(&quotes), vec![11.0, 12.0]);
+        assert_eq!(
+            cache.get_coverage("ACME").unwrap(),
+            Some((date("2024-01-02"), date("2024-01-05")))
+        );
+
+        // nothing cached at all
+        assert!(cache
+            .history("ACME", date("2023-01-02"), date("2023-01-05"))
+            .unwrap_err()
+            .is_unreachable());
+        assert!(cache.latest_close("OTHER").unwrap_err().is_unreachable());
+
+        // an expired live close is preferred over the history
+        source.unreachable.store(false, Ordering::SeqCst);
+        assert_eq!(cache.latest_close("ACME").unwrap(), 14.0);
+        assert!(cache.stale_quotes().is_empty());
+        age_latest_close(&cache, LATEST_CLOSE_TTL_SECONDS);
+        source.unreachable.store(true, Ordering::SeqCst);
+        assert_eq!(cache.latest_close("ACME").unwrap(), 14.0);
+        let (_, fetched) = cache.get_latest_close("ACME").unwrap().unwrap();
+        let fetched_on = DateTime::from_timestamp(fetched, 0).unwrap().date_naive();
+        assert_eq!(cache.stale_quotes(), vec![("ACME".to_string(), fetched_on)]);
+
+        // and the ticker is fresh again once the source answers
+        source.unreachable.store(false, Ordering::SeqCst);
+        cache
+            .history("ACME", date("2024-01-02"), date("2024-01-09"))
+            .unwrap();
+        assert!(cache.stale_quotes().is_empty());
+    }
+}
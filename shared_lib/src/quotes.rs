@@ -0,0 +1,474 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use time::OffsetDateTime;
+use yahoo_finance_api::{Quote, YahooConnector, YahooError};
+
+use crate::calendar::TradingCalendar;
+
+#[derive(Debug)]
+pub enum QuoteError {
+    Yahoo(YahooError),
+    NotFound(String),
+    Source(String),
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteError::Yahoo(error) => write!(f, "{}", error),
+            QuoteError::NotFound(msg) => write!(f, "no quote found for {}", msg),
+            QuoteError::Source(msg) => write!(f, "unable to read quotes: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QuoteError::Yahoo(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl QuoteError {
+    // true when the source could not be reached at all, as opposed to it
+    // answering that it has nothing for the request
+    pub fn is_unreachable(&self) -> bool {
+        matches!(
+            self,
+            QuoteError::Yahoo(YahooError::ConnectionFailed(_))
+                | QuoteError::Yahoo(YahooError::FetchFailed(_))
+        )
+    }
+}
+
+impl From<YahooError> for QuoteError {
+    fn from(error: YahooError) -> Self {
+        QuoteError::Yahoo(error)
+    }
+}
+
+// how many trading days a lookup may go back before giving up on a ticker
+const QUOTE_LOOKBACK_TRADING_DAYS: u32 = 10;
+
+// A source of closing prices. History is returned for the half-open range
+// [start, end) so that a single day is requested as (day, day + 1).
+pub trait QuoteProvider: Send + Sync {
+    fn latest_close(&self, ticker: &str) -> Result<f64, QuoteError>;
+
+    fn history(
+        &self,
+        ticker: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Quote>, QuoteError>;
+
+    // Returns the last quote on or before the requested date. Sources that
+    // can only be asked for a range walk back one trading day at a time, giving
+    // up after QUOTE_LOOKBACK_TRADING_DAYS or as soon as the source cannot be
+    // reached.
+    fn quote_on_or_before(&self, ticker: &str, date: NaiveDate) -> Result<Quote, QuoteError> {
+        let mut day = TradingCalendar::trading_day_on_or_before(date);
+        for _ in 0..QUOTE_LOOKBACK_TRADING_DAYS {
+            let next_day = day.succ_opt().expect("Invalid day!");
+            match self.history(ticker, day, next_day) {
+                Ok(quotes) => {
+                    if let Some(quote) = quotes.first() {
+                        return Ok(quote.to_owned());
+                    }
+                }
+                Err(error) if error.is_unreachable() => return Err(error),
+                Err(_) => {}
+            }
+            day = TradingCalendar::previous_trading_day(day);
+        }
+        Err(QuoteError::NotFound(format!("{} on {}", ticker, date)))
+    }
+
+    // tickers whose most recent answer was served from an out-of-date copy,
+    // with the date of the close that was used
+    fn stale_quotes(&self) -> Vec<(String, NaiveDate)> {
+        Vec::new()
+    }
+}
+
+pub fn date_to_timestamp(date: NaiveDate) -> u64 {
+    date.and_time(NaiveTime::MIN).and_utc().timestamp() as u64
+}
+
+pub fn timestamp_to_date(timestamp: u64) -> NaiveDate {
+    DateTime::from_timestamp(timestamp as i64, 0)
+        .expect("Quote timestamp out of range!")
+        .date_naive()
+}
+
+// Offline sources only know the closing price, so every field of the quote is
+// set to it.
+pub fn quote_from_close(date: NaiveDate, close: f64) -> Quote {
+    Quote {
+        timestamp: date_to_timestamp(date),
+        open: close,
+        high: close,
+        low: close,
+        volume: 0,
+        close,
+        adjclose: close,
+    }
+}
+
+pub struct YahooQuoteProvider {
+    connector: YahooConnector,
+}
+
+impl YahooQuoteProvider {
+    pub fn new() -> Result<Self, QuoteError> {
+        Ok(Self {
+            connector: YahooConnector::new()?,
+        })
+    }
+}
+
+impl QuoteProvider for YahooQuoteProvider {
+    fn latest_close(&self, ticker: &str) -> Result<f64, QuoteError> {
+        let rs = tokio_test::block_on(self.connector.get_latest_quotes(ticker, "1d"))?;
+        let quote = rs.last_quote()?;
+        Ok(quote.close)
+    }
+
+    fn history(
+        &self,
+        ticker: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Quote>, QuoteError> {
+        let start = OffsetDateTime::from_unix_timestamp(date_to_timestamp(start) as i64).unwrap();
+        let end = OffsetDateTime::from_unix_timestamp(date_to_timestamp(end) as i64).unwrap();
+        let rs = tokio_test::block_on(self.connector.get_quote_history(ticker, start, end))?;
+        Ok(rs.quotes()?)
+    }
+}
+
+// Closing prices held in memory, sorted by date for each ticker. Backs both the
+// CSV price file and the mock used when running without a network.
+#[derive(Default)]
+pub struct MockQuoteProvider {
+    closes: HashMap<String, Vec<(NaiveDate, f64)>>,
+    default_close: Option<f64>,
+}
+
+impl MockQuoteProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_close(mut self, ticker: &str, date: NaiveDate, close: f64) -> Self {
+        self.add_close(ticker, date, close);
+        self
+    }
+
+    // price reported for tickers, or dates, that have no recorded close
+    pub fn with_default_close(mut self, close: f64) -> Self {
+        self.default_close = Some(close);
+        self
+    }
+
+    pub fn add_close(&mut self, ticker: &str, date: NaiveDate, close: f64) {
+        let closes = self.closes.entry(ticker.to_uppercase()).or_default();
+        match closes.binary_search_by(|(d, _)| d.cmp(&date)) {
+            Ok(i) => closes[i].1 = close,
+            Err(i) => closes.insert(i, (date, close)),
+        }
+    }
+
+    fn closes(&self, ticker: &str) -> &[(NaiveDate, f64)] {
+        self.closes
+            .get(&ticker.to_uppercase())
+            .map(|closes| closes.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+impl QuoteProvider for MockQuoteProvider {
+    fn latest_close(&self, ticker: &str) -> Result<f64, QuoteError> {
+        match (self.closes(ticker).last(), self.default_close) {
+            (Some((_, close)), _) => Ok(*close),
+            (None, Some(close)) => Ok(close),
+            (None, None) => Err(QuoteError::NotFound(ticker.to_string())),
+        }
+    }
+
+    fn history(
+        &self,
+        ticker: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Quote>, QuoteError> {
+        let closes = self.closes(ticker);
+        if closes.is_empty() && self.default_close.is_none() {
+            return Err(QuoteError::NotFound(ticker.to_string()));
+        }
+        Ok(closes
+            .iter()
+            .filter(|(date, _)| *date >= start && *date < end)
+            .map(|(date, close)| quote_from_close(*date, *close))
+            .collect())
+    }
+
+    fn quote_on_or_before(&self, ticker: &str, date: NaiveDate) -> Result<Quote, QuoteError> {
+        let closes = self.closes(ticker);
+        let i = closes.partition_point(|(d, _)| *d <= date);
+        if i > 0 {
+            let (day, close) = closes[i - 1];
+            return Ok(quote_from_close(day, close));
+        }
+        match self.default_close {
+            Some(close) => Ok(quote_from_close(date, close)),
+            None => Err(QuoteError::NotFound(format!("{} on {}", ticker, date))),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PriceFileRow {
+    ticker: String,
+    date: String,
+    close: f64,
+}
+
+// Reads a price file with a `ticker,date,close` header and dates formatted as
+// YYYY-MM-DD.
+pub struct CsvQuoteProvider {
+    prices: MockQuoteProvider,
+}
+
+impl CsvQuoteProvider {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, QuoteError> {
+        let mut rdr = csv::Reader::from_path(path.as_ref())
+            .map_err(|e| QuoteError::Source(format!("{}: {}", path.as_ref().display(), e)))?;
+        let mut prices = MockQuoteProvider::new();
+        for result in rdr.deserialize() {
+            let row: PriceFileRow = result.map_err(|e| QuoteError::Source(e.to_string()))?;
+            let date = NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")
+                .map_err(|e| QuoteError::Source(format!("'{}': {}", row.date, e)))?;
+            prices.add_close(&row.ticker, date, row.close);
+        }
+        Ok(Self { prices })
+    }
+}
+
+impl QuoteProvider for CsvQuoteProvider {
+    fn latest_close(&self, ticker: &str) -> Result<f64, QuoteError> {
+        self.prices.latest_close(ticker)
+    }
+
+    fn history(
+        &self,
+        ticker: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Quote>, QuoteError> {
+        self.prices.history(ticker, start, end)
+    }
+
+    fn quote_on_or_before(&self, ticker: &str, date: NaiveDate) -> Result<Quote, QuoteError> {
+        self.prices.quote_on_or_before(ticker, date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    // Answers only for a range of days, as Yahoo does, so that lookups take the
+    // walk back of the trait.
+    struct RangeOnly {
+        prices: MockQuoteProvider,
+        unreachable: bool,
+        requests: AtomicU32,
+    }
+
+    impl RangeOnly {
+        fn new(prices: MockQuoteProvider, unreachable: bool) -> Self {
+            Self {
+                prices,
+                unreachable,
+                requests: AtomicU32::new(0),
+            }
+        }
+    }
+
+    impl QuoteProvider for RangeOnly {
+        fn latest_close(&self, ticker: &str) -> Result<f64, QuoteError> {
+            self.prices.latest_close(ticker)
+        }
+
+        fn history(
+            &self,
+            ticker: &str,
+            start: NaiveDate,
+            end: NaiveDate,
+        ) -> Result<Vec<Quote>, QuoteError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            if self.unreachable {
+                return Err(QuoteError::Yahoo(YahooError::FetchFailed(
+                    "offline".to_string(),
+                )));
+            }
+            self.prices.history(ticker, start, end)
+        }
+    }
+
+    #[test]
+    fn walks_back_ten_trading_days() {
+        // Jan 15, 2024 is Martin Luther King Jr. Day; the 2nd is ten trading
+        // days back from the 16th and eleven from the 17th
+        let prices = || MockQuoteProvider::new().with_close("ACME", date("2024-01-02"), 10.0);
+        let cases = [
+            ("2024-01-16", Some("2024-01-02"), 10),
+            ("2024-01-17", None, 10),
+            // a weekend or holiday starts from the trading day before it
+            ("2024-01-06", Some("2024-01-02"), 4),
+            ("2024-01-02", Some("2024-01-02"), 1),
+        ];
+        for (day, found, requests) in cases {
+            let provider = RangeOnly::new(prices(), false);
+            let quote = provider.quote_on_or_before("ACME", date(day));
+            match found {
+                Some(found) => {
+                    let quote = quote.unwrap();
+                    assert_eq!(timestamp_to_date(quote.timestamp), date(found), "{}", day);
+                    assert_eq!(quote.close, 10.0);
+                }
+                None => assert!(matches!(quote, Err(QuoteError::NotFound(_))), "{}", day),
+            }
+            assert_eq!(
+                provider.requests.load(Ordering::SeqCst),
+                requests,
+                "{}",
+                day
+            );
+        }
+
+        // a source that cannot be reached is not asked again
+        let provider = RangeOnly::new(prices(), true);
+        let error = provider
+            .quote_on_or_before("ACME", date("2024-01-16"))
+            .unwrap_err();
+        assert!(error.is_unreachable());
+        assert_eq!(provider.requests.load(Ordering::SeqCst), 1);
+        assert!(provider.stale_quotes().is_empty());
+    }
+
+    #[test]
+    fn history_covers_start_up_to_end() {
+        let provider = MockQuoteProvider::new()
+            .with_close("acme", date("2024-01-02"), 10.0)
+            .with_close("ACME", date("2024-01-04"), 12.0)
+            .with_close("ACME", date("2024-01-03"), 11.0);
+        let days = |start: &str, end: &str| -> Vec<(NaiveDate, f64)> {
+            provider
+                .history("Acme", date(start), date(end))
+                .unwrap()
+                .iter()
+                .map(|quote| (timestamp_to_date(quote.timestamp), quote.close))
+                .collect()
+        };
+        assert_eq!(
+            days("2024-01-02", "2024-01-04"),
+            vec![(date("2024-01-02"), 10.0), (date("2024-01-03"), 11.0)]
+        );
+        assert_eq!(
+            days("2024-01-04", "2024-01-05"),
+            vec![(date("2024-01-04"), 12.0)]
+        );
+        assert!(days("2024-01-04", "2024-01-04").is_empty());
+        assert_eq!(provider.latest_close("ACME").unwrap(), 12.0);
+        assert!(matches!(
+            provider.history("OTHER", date("2024-01-02"), date("2024-01-05")),
+            Err(QuoteError::NotFound(_))
+        ));
+
+        // the default close stands in for tickers and days without one
+        let provider = MockQuoteProvider::new().with_default_close(5.0);
+        assert!(provider
+            .history("ACME", date("2024-01-02"), date("2024-01-05"))
+            .unwrap()
+            .is_empty());
+        let quote = provider
+            .quote_on_or_before("ACME", date("2024-01-03"))
+            .unwrap();
+        assert_eq!(timestamp_to_date(quote.timestamp), date("2024-01-03"));
+        assert_eq!(quote.close, 5.0);
+        assert!(provider.stale_quotes().is_empty());
+    }
+
+    #[test]
+    fn reads_a_price_file() {
+        let dir = std::env::temp_dir().join(format!("fintool-quotes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("prices.csv");
+        std::fs::write(
+            &path,
+            "ticker,date,close\nACME,2024-01-03,11.5\nacme,2024-01-02,10.25\nXYZ,2024-01-02,3\n",
+        )
+        .unwrap();
+        let provider = CsvQuoteProvider::from_path(&path).unwrap();
+        assert_eq!(provider.latest_close("ACME").unwrap(), 11.5);
+        assert_eq!(provider.latest_close("xyz").unwrap(), 3.0);
+        let quote = provider
+            .quote_on_or_before("ACME", date("2024-01-07"))
+            .unwrap();
+        assert_eq!(timestamp_to_date(quote.timestamp), date("2024-01-03"));
+        assert_eq!(quote.close, 11.5);
+        assert_eq!(
+            provider
+                .history("ACME", date("2024-01-01"), date("2024-01-03"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(matches!(
+            provider.quote_on_or_before("ACME", date("2024-01-01")),
+            Err(QuoteError::NotFound(_))
+        ));
+
+        let bad = dir.join("bad.csv");
+        std::fs::write(&bad, "ticker,date,close\nACME,01/03/2024,11.5\n").unwrap();
+        assert!(matches!(
+            CsvQuoteProvider::from_path(&bad),
+            Err(QuoteError::Source(_))
+        ));
+        assert!(matches!(
+            CsvQuoteProvider::from_path(dir.join("missing.csv")),
+            Err(QuoteError::Source(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
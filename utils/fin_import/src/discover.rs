@@ -0,0 +1,111 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Deserialize;
+
+use shared_lib::LedgerEntry;
+use shared_lib::Money;
+use shared_lib::TransferType;
+
+use crate::{convert_csv_records, has_headers, ConvertError, ConvertedRecord, Converter};
+
+const DISCOVER_COLUMNS: [&str; 5] = [
+    "Trans. Date",
+    "Post Date",
+    "Description",
+    "Amount",
+    "Category",
+];
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiscoverRecord {
+    #[serde(rename = "Trans. Date")]
+    pub transaction_date: String,
+    #[serde(rename = "Post Date")]
+    pub post_date: String,
+    #[serde(rename = "Description")]
+    pub description: String,
+    #[serde(rename = "Amount")]
+    pub amount: Money,
+    #[serde(rename = "Category")]
+    pub category: String,
+}
+
+pub struct DiscoverConverter;
+
+impl Converter for DiscoverConverter {
+    fn id(&self) -> &'static str {
+        "discover"
+    }
+
+    fn name(&self) -> &'static str {
+        "Discover credit card"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        has_headers(contents, &DISCOVER_COLUMNS)
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        let re = Regex::new(
+            r"^(\s*|TST\*|SQ\*)([A-Za-z0-9*#_\-\.\/\'&,\(\)]+\s[A-Za-z0-9*#_\-\.\/\'&,\(\)]+)",
+        )
+        .unwrap();
+
+        convert_csv_records(contents, |txn: DiscoverRecord| {
+            let posted_date = NaiveDate::parse_from_str(&txn.transaction_date, "%m/%d/%Y")
+                .map_err(|e| {
+                    ConvertError::Unrecognized(format!("{}: {}", txn.transaction_date, e))
+                })?;
+
+            let (peer, ttype) = match txn.category.as_str() {
+                // credits from discover
+                "Awards and Rebate Credits" => (
+                    "Discover Financial Services".to_string(),
+                    TransferType::DepositFromInternalAccount,
+                ),
+                "Payments and Credits" => (
+                    "Checking Account".to_string(),
+                    TransferType::DepositFromExternalAccount,
+                ),
+                _ => {
+                    let x = re.captures(txn.description.as_str()).ok_or_else(|| {
+                        ConvertError::Unrecognized(format!(
+                            "{} did not produce a valid match for a participant!",
+                            txn.description
+                        ))
+                    })?;
+                    (
+                        x.get(2).unwrap().as_str().to_string(),
+                        TransferType::WithdrawalToExternalAccount,
+                    )
+                }
+            };
+
+            Ok(vec![LedgerEntry {
+                date: posted_date.format("%Y-%m-%d").to_string(),
+                amount: txn.amount.abs(),
+                transfer_type: ttype,
+                participant: peer,
+                category: txn.category,
+                description: txn.description,
+                stock_info: None,
+            }])
+        })
+    }
+}
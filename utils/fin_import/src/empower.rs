@@ -0,0 +1,95 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use shared_lib::LedgerEntry;
+use shared_lib::Money;
+use shared_lib::TransferType;
+
+use crate::{convert_csv_records, has_headers, ConvertError, ConvertedRecord, Converter};
+
+const EMPOWER_COLUMNS: [&str; 6] = [
+    "Date",
+    "Account",
+    "Description",
+    "Category",
+    "Tags",
+    "Amount",
+];
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct EmpowerRecord {
+    #[serde(rename = "Date")]
+    pub date: String,
+    #[serde(rename = "Account")]
+    pub account: String,
+    #[serde(rename = "Description")]
+    pub description: String,
+    #[serde(rename = "Category")]
+    pub category: String,
+    #[serde(rename = "Tags")]
+    pub tags: String,
+    #[serde(rename = "Amount")]
+    pub amount: Money,
+}
+
+pub struct EmpowerConverter;
+
+impl Converter for EmpowerConverter {
+    fn id(&self) -> &'static str {
+        "empower"
+    }
+
+    fn name(&self) -> &'static str {
+        "Empower retirement plan"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        has_headers(contents, &EMPOWER_COLUMNS)
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        convert_csv_records(contents, |txn: EmpowerRecord| {
+            let posted_date = NaiveDate::parse_from_str(&txn.date, "%Y-%m-%d")
+                .map_err(|e| ConvertError::Unrecognized(format!("{}: {}", txn.date, e)))?;
+
+            let (ttype, peer) = match txn.category.to_lowercase().as_str() {
+                "retirement contributions" => (
+                    TransferType::DepositFromExternalAccount,
+                    "Self".to_ascii_uppercase(),
+                ),
+                _ => {
+                    return Err(ConvertError::Unrecognized(format!(
+                        "Unrecognized category: {}",
+                        txn.category
+                    )))
+                }
+            };
+
+            Ok(vec![LedgerEntry {
+                date: posted_date.format("%Y-%m-%d").to_string(),
+                amount: txn.amount.abs(),
+                transfer_type: ttype,
+                participant: peer,
+                category: txn.category,
+                description: txn.description,
+                stock_info: None,
+            }])
+        })
+    }
+}
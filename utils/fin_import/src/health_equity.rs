@@ -0,0 +1,288 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use shared_lib::stocks::get_stock_quote;
+
+use shared_lib::LedgerEntry;
+use shared_lib::Money;
+use shared_lib::Quantity;
+use shared_lib::StockInfo;
+use shared_lib::TransferType;
+
+use crate::{convert_csv_records, has_headers, ConvertError, ConvertedRecord, Converter};
+
+const HEALTH_EQUITY_COLUMNS: [&str; 4] = ["Date", "Transaction", "Amount", "HSA Cash Balance"];
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct HealthEquityRecord {
+    #[serde(rename = "Date")]
+    pub date: String,
+    #[serde(rename = "Transaction")]
+    pub transaction: String,
+    #[serde(rename = "Amount", deserialize_with = "deserialize_accounting")]
+    pub amount: Money,
+    #[serde(
+        rename = "HSA Cash Balance",
+        deserialize_with = "deserialize_accounting"
+    )]
+    pub acct_balance: Money,
+    #[serde(rename = "Attachments")]
+    pub attachments: String,
+}
+
+pub struct HealthEquityConverter;
+
+impl Converter for HealthEquityConverter {
+    fn id(&self) -> &'static str {
+        "healthequity"
+    }
+
+    fn name(&self) -> &'static str {
+        "HealthEquity HSA"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        has_headers(contents, &HEALTH_EQUITY_COLUMNS)
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        let transaction_re = Regex::new(
+            r"^(Investment:|Employee Contribution|Employer Contribution|Incentive Contribution|Interest|Balance|From BenefitWallet:|Transfer from BenefitWallet)\s+(.+)"
+        ).unwrap();
+
+        convert_csv_records(contents, |txn: HealthEquityRecord| {
+            let mut cat: String = "BUY".to_string();
+            let mut description: String = "TEMPORARY".to_string();
+            let t_type: TransferType;
+            let mut peer = "SELF".to_string();
+            let mut stock: Option<StockInfo> = None;
+
+            let posted_date = NaiveDate::parse_from_str(&txn.date, "%m/%d/%Y")
+                .map_err(|e| ConvertError::Unrecognized(format!("{}: {}", txn.date, e)))?;
+
+            let amt = txn.amount.abs();
+
+            let x = transaction_re
+                .captures(txn.transaction.as_str())
+                .ok_or_else(|| {
+                    ConvertError::Unrecognized(format!(
+                        "{} did not produce a valid match for a transaction!",
+                        txn.transaction
+                    ))
+                })?;
+            let activity = x.get(1).unwrap().as_str().to_string();
+            let helper_data = x.get(2).unwrap().as_str().to_string();
+            match activity.as_str() {
+                "Investment:" => {
+                    let ticker = helper_data.clone();
+                    let quote = get_stock_quote(ticker.clone(), posted_date).map_err(|_| {
+                        ConvertError::Unrecognized(format!("Ticker not recognized: {}!", ticker))
+                    })?;
+                    let close = Quantity::from_f64(quote.close);
+                    let shares = amt.checked_div(close).ok_or_else(|| {
+                        ConvertError::Unrecognized(format!("No price available for {}!", ticker))
+                    })?;
+
+                    // purchase of stock
+                    stock = Some(StockInfo {
+                        shares,
+                        remaining: shares,
+                        costbasis: close,
+                        is_buy: true,
+                        is_split: false,
+                    });
+
+                    t_type = TransferType::WithdrawalToInternalAccount;
+                    description = format!(
+                        "Purchase {} shares of {} at ${} on {}.",
+                        shares, ticker, amt, txn.date
+                    );
+                    cat = "BUY".to_string();
+                    peer = helper_data;
+                }
+                "Employee Contribution" | "Incentive Contribution" => {
+                    t_type = TransferType::DepositFromExternalAccount;
+                    cat = "DEPOSIT".to_string();
+                    description =
+                        format!("Employee contribution of ${} on {}.", txn.amount, txn.date);
+                    peer = "SELF".to_string();
+                }
+                "Employer Contribution" => {
+                    t_type = TransferType::DepositFromExternalAccount;
+                    cat = "DEPOSIT".to_string();
+                    description =
+                        format!("Employee contribution of ${} on {}.", txn.amount, txn.date);
+                    peer = "EMPLOYER".to_string();
+                }
+                "Interest" => {
+                    t_type = TransferType::DepositFromInternalAccount;
+                    cat = "INTEREST".to_string();
+                    peer = "Health Equity".to_ascii_uppercase();
+                    description =
+                        format!("Employee contribution of ${} on {}.", txn.amount, txn.date);
+                }
+                "Balance" => {
+                    t_type = TransferType::DepositFromInternalAccount;
+                }
+                "Transfer from BenefitWallet" => {
+                    t_type = TransferType::DepositFromInternalAccount;
+                    cat = "DEPOSIT".to_string();
+                    peer = "Benefit Wallet".to_ascii_uppercase();
+                    description = format!(
+                        "Transfer of funds amounting to ${} on {}.",
+                        txn.amount, txn.date
+                    );
+                }
+                "From BenefitWallet:" => {
+                    let helper_data = match helper_data.as_str().find('(') {
+                        Some(index) => helper_data[..index].trim_end().to_string(),
+                        None => helper_data,
+                    };
+
+                    let helper_data = match helper_data.as_str().find("POSTED THROUGH") {
+                        Some(index) => helper_data[..index].trim_end().to_string(),
+                        None => helper_data,
+                    };
+
+                    match helper_data.as_str() {
+                        "Transfer" => {
+                            t_type = TransferType::DepositFromInternalAccount;
+                            cat = "DEPOSIT".to_string();
+                            peer = "Benefit Wallet".to_ascii_uppercase();
+                            description = format!(
+                                "Transfer of funds amounting to ${} on {}.",
+                                txn.amount, txn.date
+                            );
+                        }
+                        "HSA INVEST" => {
+                            t_type = TransferType::WithdrawalToInternalAccount;
+                            cat = "BUY".to_string();
+                            peer = "Investment Fund".to_ascii_uppercase();
+                            description = format!("Purchases of funds amounting to ${} on {}. Funds purchased not disclosed in data provided by HealthEquity.", txn.amount, txn.date);
+                        }
+                        "HSA CHECK DISBURSEMENT" => {
+                            t_type = TransferType::WithdrawalToExternalAccount;
+                            cat = "Check Disbursement".to_string();
+                            peer = "Self".to_ascii_uppercase();
+                            description = format!(
+                                "Distribution of funds amounting to ${} on {}.",
+                                txn.amount, txn.date
+                            );
+                        }
+                        "EMPLOYEE PAYROLL CONTRIBUTION" => {
+                            t_type = TransferType::DepositFromExternalAccount;
+                            cat = "CONTRIBUTION".to_string();
+                            peer = "Self".to_ascii_uppercase();
+                            description = format!(
+                                "Contribution of funds amounting to ${} on {}.",
+                                txn.amount, txn.date
+                            );
+                        }
+                        "EMPLOYEE PAYROLL DEBIT" => {
+                            t_type = TransferType::WithdrawalToExternalAccount;
+                            cat = "DEBIT".to_string();
+                            peer = "Self".to_ascii_uppercase();
+                            description = format!(
+                                "Debit of funds amounting to ${} on {}.",
+                                txn.amount, txn.date
+                            );
+                        }
+                        "PARTIAL MONTH INTEREST" | "INTEREST" => {
+                            t_type = TransferType::DepositFromInternalAccount;
+                            cat = "INTEREST".to_string();
+                            peer = "Health Equity".to_ascii_uppercase();
+                            description = format!(
+                                "Account interest amounting to ${} on {}.",
+                                txn.amount, txn.date
+                            );
+                        }
+                        "WELLNESS PAYROLL CREDIT" => {
+                            t_type = TransferType::DepositFromExternalAccount;
+                            cat = "Wellness Incentive".to_string();
+                            peer = "Employer".to_ascii_uppercase();
+                            description = format!(
+                                "Wellness payroll credit amounting to ${} on {}.",
+                                txn.amount, txn.date
+                            );
+                        }
+                        "EMPLOYER PAYROLL CONTRIBUTION" => {
+                            t_type = TransferType::DepositFromExternalAccount;
+                            cat = "CONTRIBUTION".to_string();
+                            peer = "Employer".to_ascii_uppercase();
+                            description = format!(
+                                "Employer contribution amounting to ${} on {}.",
+                                txn.amount, txn.date
+                            );
+                        }
+                        "Starting Balance" | "Transfer to HealthEquity" => {
+                            return Ok(Vec::new());
+                        }
+                        _ => {
+                            return Err(ConvertError::Unrecognized(format!(
+                                "Unrecognized helper data: {}",
+                                helper_data
+                            )));
+                        }
+                    }
+                }
+                _ => {
+                    return Err(ConvertError::Unrecognized(format!(
+                        "Unrecognized activity: {}",
+                        activity
+                    )));
+                }
+            }
+
+            if stock.is_some() {
+                peer = peer.to_ascii_uppercase();
+                cat = cat.to_ascii_uppercase();
+            }
+            Ok(vec![LedgerEntry {
+                date: posted_date.format("%Y-%m-%d").to_string(),
+                amount: amt,
+                transfer_type: t_type,
+                participant: peer,
+                category: cat,
+                description,
+                stock_info: stock,
+            }])
+        })
+    }
+}
+
+fn deserialize_accounting<'de, D>(deserializer: D) -> Result<Money, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+
+    // Trim whitespace
+    let s = s.trim();
+    let s = s.replace(",", "");
+
+    // Handle parentheses for negative numbers
+    let cleaned = if s.starts_with('(') && s.ends_with(')') {
+        let inner = &s[1..s.len() - 1].trim(); // remove parens and trim
+        format!("-{}", inner.trim_start_matches('$')) // remove dollar and add minus
+    } else {
+        s.trim_start_matches('$').to_string()
+    };
+
+    cleaned.parse::<Money>().map_err(serde::de::Error::custom)
+}
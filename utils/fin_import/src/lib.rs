@@ -0,0 +1,370 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::de::DeserializeOwned;
+use shared_lib::{FlatLedgerEntry, LedgerEntry};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+pub mod discover;
+pub mod empower;
+pub mod health_equity;
+pub mod lpl;
+pub mod mapping;
+pub mod ofx;
+pub mod qif;
+pub mod vfcu;
+
+// Reads the files a financial institution exports into ledger entries.
+pub trait Converter {
+    // short name used to pick the converter by hand
+    fn id(&self) -> &'static str;
+    // institution and kind of export, as shown to the user
+    fn name(&self) -> &'static str;
+    // whether the file looks like one of this converter's exports, judged by
+    // its headers and content
+    fn detect(&self, contents: &str) -> bool;
+    // Converts each record of the file on its own, so that a record that
+    // cannot be read is reported without losing the others.
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord>;
+    // every entry of the file, failing on the first record that cannot be read
+    fn convert(&self, contents: &str) -> Result<Vec<LedgerEntry>, ConvertError> {
+        let mut entries = Vec::new();
+        for record in self.convert_records(contents) {
+            entries.append(&mut record.entries?);
+        }
+        Ok(entries)
+    }
+}
+
+// The entries read from one record of a file, along with the line the record
+// starts on when it is known. Errors that concern the whole file have no line.
+pub struct ConvertedRecord {
+    pub line: Option<u64>,
+    pub entries: Result<Vec<LedgerEntry>, ConvertError>,
+}
+
+#[derive(Debug)]
+pub enum ConvertError {
+    Csv(csv::Error),
+    Ofx(serde_xml_rs::Error),
+    Qif { line: usize, message: String },
+    Unrecognized(String),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Csv(error) => write!(f, "unable to read CSV: {}", error),
+            ConvertError::Ofx(error) => write!(f, "unable to read OFX: {}", error),
+            ConvertError::Qif { line, message } => {
+                write!(f, "unable to read QIF at line {}: {}", line, message)
+            }
+            ConvertError::Unrecognized(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConvertError::Csv(error) => Some(error),
+            ConvertError::Ofx(error) => Some(error),
+            ConvertError::Qif { .. } | ConvertError::Unrecognized(_) => None,
+        }
+    }
+}
+
+impl From<csv::Error> for ConvertError {
+    fn from(error: csv::Error) -> Self {
+        ConvertError::Csv(error)
+    }
+}
+
+impl From<serde_xml_rs::Error> for ConvertError {
+    fn from(error: serde_xml_rs::Error) -> Self {
+        ConvertError::Ofx(error)
+    }
+}
+
+// Every supported export. A file is detected as at most one of them, so the
+// order they are tried in does not matter; the Visions exports share their
+// columns and are told apart by the classification and the descriptions.
+pub fn converters() -> Vec<Box<dyn Converter>> {
+    vec![
+        Box::new(ofx::OfxConverter),
+        Box::new(qif::QifConverter),
+        Box::new(vfcu::VfcuCheckingConverter),
+        Box::new(vfcu::VfcuCreditCardConverter),
+        Box::new(vfcu::VfcuCertificateOfDepositConverter),
+        Box::new(discover::DiscoverConverter),
+        Box::new(lpl::LplConverter),
+        Box::new(health_equity::HealthEquityConverter),
+        Box::new(empower::EmpowerConverter),
+    ]
+}
+
+pub fn find_converter(id: &str) -> Option<Box<dyn Converter>> {
+    converters()
+        .into_iter()
+        .find(|converter| converter.id() == id)
+}
+
+pub fn detect_converter(contents: &str) -> Option<Box<dyn Converter>> {
+    converters()
+        .into_iter()
+        .find(|converter| converter.detect(contents))
+}
+
+// Writes the entries in the CSV layout that fintool imports.
+pub fn write_entries<W: Write>(entries: Vec<LedgerEntry>, writer: W) -> Result<(), csv::Error> {
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(writer);
+    for entry in entries {
+        wtr.serialize(FlatLedgerEntry::from(entry))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Entry point of the single-format binaries: converts the file named on the
+// command line and prints the entries to standard output.
+pub fn run(converter: &dyn Converter) {
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 2 {
+        eprintln!("Only one argument is supported!");
+        process::exit(1);
+    }
+    let file = args.get(1).expect("File not provided!");
+
+    let contents = fs::read_to_string(file).unwrap_or_else(|e| {
+        eprintln!("Unable to read {}: {}", file, e);
+        process::exit(1);
+    });
+    let entries = converter.convert(&contents).unwrap_or_else(|e| {
+        eprintln!("Unable to convert {}: {}", file, e);
+        process::exit(1);
+    });
+    if let Err(e) = write_entries(entries, io::stdout()) {
+        eprintln!("Unable to write entries: {}", e);
+        process::exit(1);
+    }
+}
+
+// Returns the column names on the first line of a CSV export.
+pub fn csv_headers(contents: &str) -> Vec<String> {
+    let contents = contents.trim_start_matches('\u{feff}');
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(contents.as_bytes());
+    match rdr.headers() {
+        Ok(headers) => headers.iter().map(|h| h.trim().to_string()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub(crate) fn has_headers(contents: &str, expected: &[&str]) -> bool {
+    let headers = csv_headers(contents);
+    expected
+        .iter()
+        .all(|column| headers.iter().any(|header| header == column))
+}
+
+// Reads the records of a CSV export one at a time and converts each with the
+// closure, keeping the line it was read from.
+pub(crate) fn convert_csv_records<T, F>(contents: &str, mut convert: F) -> Vec<ConvertedRecord>
+where
+    T: DeserializeOwned,
+    F: FnMut(T) -> Result<Vec<LedgerEntry>, ConvertError>,
+{
+    let contents = contents.trim_start_matches('\u{feff}');
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(contents.as_bytes());
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(error) => {
+            return vec![ConvertedRecord {
+                line: None,
+                entries: Err(error.into()),
+            }]
+        }
+    };
+    rdr.records()
+        .map(|result| match result {
+            Ok(record) => ConvertedRecord {
+                line: record.position().map(|p| p.line()),
+                entries: record
+                    .deserialize::<T>(Some(&headers))
+                    .map_err(ConvertError::from)
+                    .and_then(&mut convert),
+            },
+            Err(error) => ConvertedRecord {
+                line: error.position().map(|p| p.line()),
+                entries: Err(error.into()),
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_lib::{Money, TransferType};
+
+    const OFX_SAMPLE: &str = "OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<BANKMSGSRSV1>
+<STMTTRNRS>
+<TRNUID>1
+<STATUS><CODE>0<SEVERITY>INFO</STATUS>
+<STMTRS>
+<CURDEF>USD
+<BANKACCTFROM><BANKID>123456789<ACCTID>0001<ACCTTYPE>CHECKING</BANKACCTFROM>
+<BANKTRANLIST>
+<DTSTART>20240101
+<DTEND>20240131
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20240105120000
+<TRNAMT>-12.34
+<FITID>A1
+<NAME>GROCER
+</STMTTRN>
+</BANKTRANLIST>
+<LEDGERBAL><BALAMT>100.00<DTASOF>20240131</LEDGERBAL>
+<AVAILBAL><BALAMT>100.00<DTASOF>20240131</AVAILBAL>
+</STMTRS>
+</STMTTRNRS>
+</BANKMSGSRSV1>
+</OFX>
+";
+
+    // A header row and a record of each export, along with the date, amount,
+    // direction, participant and category of the entry it converts to.
+    fn samples() -> Vec<(
+        &'static str,
+        &'static str,
+        (&'static str, i64, TransferType, &'static str, &'static str),
+    )> {
+        vec![
+            (
+                "ofx",
+                OFX_SAMPLE,
+                ("2024-01-05", 1_234, TransferType::WithdrawalToExternalAccount, "GROCER", "DEBIT"),
+            ),
+            (
+                "qif",
+                "!Type:Bank\nD1/5/2024\nT-12.34\nPGrocer\nLFood\n^\n",
+                ("2024-01-05", 1_234, TransferType::WithdrawalToExternalAccount, "Grocer", "Food"),
+            ),
+            (
+                "vfcu",
+                "Account Number,Post Date,Check,Description,Debit,Credit,Status,Classification\n\
+                123,01/05/2024,,Payroll ACME,,1000.00,Posted,Paycheck\n",
+                ("2024-01-05", 100_000, TransferType::DepositFromExternalAccount, "Payroll ACME", "Paycheck"),
+            ),
+            (
+                "vfcucc",
+                "Account Number,Post Date,Check,Description,Debit,Credit,Status\n\
+                456,01/05/2024,,Loan Advance Credit Card/AMAZON MKTPLACE,25.00,,Posted\n",
+                ("2024-01-05", 2_500, TransferType::WithdrawalToExternalAccount, "AMAZON MKTPLACE", "Charge"),
+            ),
+            (
+                "vfcucd",
+                "Account Number,Post Date,Check,Description,Debit,Credit,Status\n\
+                789,01/05/2024,,Deposit Dividend,,5.00,Posted\n",
+                ("2024-01-05", 500, TransferType::DepositFromInternalAccount, "Visions Federal Credit Union", "Dividend"),
+            ),
+            (
+                "discover",
+                "Trans. Date,Post Date,Description,Amount,Category\n\
+                01/05/2024,01/06/2024,AMAZON MKTPLACE,25.00,Merchandise\n",
+                ("2024-01-05", 2_500, TransferType::WithdrawalToExternalAccount, "AMAZON MKTPLACE", "Merchandise"),
+            ),
+            (
+                "lpl",
+                "Date,Activity,Symbol,Description,Quantity,Unit Price,Value,Held In,Account Nickname,Account Number\n\
+                01/05/2024,Buy,ACME,ACME CORP,10,$12.50,-$125.00,Cash,Brokerage,123\n",
+                ("2024-01-05", 12_500, TransferType::WithdrawalToInternalAccount, "ACME", "Buy"),
+            ),
+            (
+                "healthequity",
+                "Date,Transaction,Amount,HSA Cash Balance,Attachments\n\
+                01/05/2024,Employee Contribution PAYROLL,$100.00,\"$1,000.00\",\n",
+                ("2024-01-05", 10_000, TransferType::DepositFromExternalAccount, "SELF", "DEPOSIT"),
+            ),
+            (
+                "empower",
+                "Date,Account,Description,Category,Tags,Amount\n\
+                2024-01-05,401k,Contribution,Retirement Contributions,,100.00\n",
+                ("2024-01-05", 10_000, TransferType::DepositFromExternalAccount, "SELF", "Retirement Contributions"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn every_converter_has_a_sample() {
+        let mut ids: Vec<&str> = samples().iter().map(|(id, _, _)| *id).collect();
+        let mut expected: Vec<&str> = converters().iter().map(|c| c.id()).collect();
+        ids.sort();
+        expected.sort();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn converts_each_sample() {
+        for (id, contents, (date, cents, transfer_type, participant, category)) in samples() {
+            let converter = find_converter(id).unwrap();
+            assert!(
+                converter.detect(contents),
+                "{} does not detect its sample",
+                id
+            );
+            let entries = converter
+                .convert(contents)
+                .unwrap_or_else(|e| panic!("{}: {}", id, e));
+            assert_eq!(entries.len(), 1, "{}", id);
+            let entry = &entries[0];
+            assert_eq!(entry.date, date, "{}", id);
+            assert_eq!(entry.amount, Money::from_cents(cents), "{}", id);
+            assert_eq!(entry.transfer_type, transfer_type, "{}", id);
+            assert_eq!(entry.participant, participant, "{}", id);
+            assert_eq!(entry.category, category, "{}", id);
+        }
+    }
+
+    // detection must not depend on the order the converters are tried in
+    #[test]
+    fn each_sample_is_detected_by_one_converter() {
+        for (id, contents, _) in samples() {
+            let detected: Vec<&str> = converters()
+                .iter()
+                .filter(|converter| converter.detect(contents))
+                .map(|converter| converter.id())
+                .collect();
+            assert_eq!(detected, vec![id]);
+            assert_eq!(detect_converter(contents).map(|c| c.id()), Some(id));
+        }
+        assert!(detect_converter("Date,Memo\n01/05/2024,nothing\n").is_none());
+    }
+}
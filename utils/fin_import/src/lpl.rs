@@ -0,0 +1,195 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::str::FromStr;
+
+use shared_lib::LedgerEntry;
+use shared_lib::Money;
+use shared_lib::Quantity;
+use shared_lib::StockInfo;
+use shared_lib::TransferType;
+
+use crate::{convert_csv_records, has_headers, ConvertError, ConvertedRecord, Converter};
+
+const LPL_COLUMNS: [&str; 7] = [
+    "Date",
+    "Activity",
+    "Symbol",
+    "Description",
+    "Quantity",
+    "Unit Price",
+    "Value",
+];
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LplRecord {
+    #[serde(rename = "Date")]
+    pub date: String,
+    #[serde(rename = "Activity")]
+    pub activity: String,
+    #[serde(rename = "Symbol")]
+    pub symbol: String,
+    #[serde(rename = "Description")]
+    pub description: String,
+    #[serde(rename = "Quantity", deserialize_with = "deserialize_monetary")]
+    pub quantity: Quantity,
+    #[serde(rename = "Unit Price", deserialize_with = "deserialize_monetary")]
+    pub price: Quantity,
+    #[serde(rename = "Value", deserialize_with = "deserialize_monetary")]
+    pub amount: Money,
+    #[serde(rename = "Held In")]
+    pub held_in: String,
+    #[serde(rename = "Account Nickname")]
+    pub acct_name: String,
+    #[serde(rename = "Account Number")]
+    pub acct_number: String,
+}
+
+pub struct LplConverter;
+
+impl Converter for LplConverter {
+    fn id(&self) -> &'static str {
+        "lpl"
+    }
+
+    fn name(&self) -> &'static str {
+        "LPL Financial brokerage"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        has_headers(contents, &LPL_COLUMNS)
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        convert_csv_records(contents, |txn: LplRecord| {
+            let quantity = txn.quantity;
+            let price = txn.price;
+            let purchase = StockInfo {
+                shares: quantity,
+                costbasis: price,
+                remaining: quantity,
+                is_buy: true,
+                is_split: false,
+            };
+
+            let posted_date = NaiveDate::parse_from_str(&txn.date, "%m/%d/%Y")
+                .map_err(|e| ConvertError::Unrecognized(format!("{}: {}", txn.date, e)))?;
+
+            let (ttype, peer, stock_info) = match txn.activity.to_lowercase().as_str() {
+                "contribution" | "ach funds" | "deposit" | "journal" => (
+                    TransferType::DepositFromExternalAccount,
+                    "External Account".to_string(),
+                    None,
+                ),
+                "withdrawal" => (
+                    TransferType::WithdrawalToExternalAccount,
+                    "External Account".to_string(),
+                    None,
+                ),
+                "fee" => (
+                    TransferType::WithdrawalToExternalAccount,
+                    "LPL".to_string(),
+                    None,
+                ),
+                "credit int" | "interest" => (
+                    TransferType::DepositFromInternalAccount,
+                    "Insured Cash Account".to_string(),
+                    None,
+                ),
+                // LPL takes interest money and categorizes it for reinvestment
+                // immediately
+                "reinvest interest" | "interest reinvest" => (
+                    TransferType::WithdrawalToInternalAccount,
+                    "Insured Cash Account".to_string(),
+                    None,
+                ),
+                "cash dividend" | "long term cap gain" | "short term cap gain" => (
+                    TransferType::DepositFromInternalAccount,
+                    txn.symbol.clone(),
+                    None,
+                ),
+                "lt cap gain reinvest" | "st cap gain reinvest" | "dividend reinvest" | "buy" => (
+                    TransferType::WithdrawalToInternalAccount,
+                    txn.symbol.clone(),
+                    Some(purchase),
+                ),
+                "sell" => (
+                    TransferType::DepositFromInternalAccount,
+                    txn.symbol.clone(),
+                    Some(StockInfo {
+                        shares: quantity.abs(),
+                        costbasis: price,
+                        remaining: Quantity::ZERO,
+                        is_buy: false,
+                        is_split: false,
+                    }),
+                ),
+                "stock dividend/split" => (
+                    TransferType::ZeroSumChange,
+                    txn.symbol.clone(),
+                    Some(StockInfo {
+                        is_split: true,
+                        ..purchase
+                    }),
+                ),
+                _ => {
+                    return Err(ConvertError::Unrecognized(format!(
+                        "Unrecognized activity type: {}",
+                        txn.activity
+                    )))
+                }
+            };
+
+            Ok(vec![LedgerEntry {
+                date: posted_date.format("%Y-%m-%d").to_string(),
+                amount: txn.amount.abs(),
+                transfer_type: ttype,
+                participant: peer,
+                category: txn.activity,
+                description: txn.description,
+                stock_info,
+            }])
+        })
+    }
+}
+
+fn deserialize_monetary<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+
+    // Trim whitespace
+    let s = s.trim();
+    let s = s.replace(",", "");
+
+    // Handle parentheses for negative numbers
+    let cleaned = if s.starts_with('-') && s.len() < 2 {
+        "0".to_string()
+    } else if s.starts_with('-') {
+        let inner = &s[1..s.len()].trim(); // remove parens and trim
+        format!("-{}", inner.trim_start_matches('$')) // remove dollar and add minus
+    } else {
+        s.trim_start_matches('$').to_string()
+    };
+
+    cleaned.parse::<T>().map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,82 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+
+use fin_import::{converters, detect_converter, find_converter, write_entries, Converter};
+
+fn usage() -> ! {
+    eprintln!("Usage: fin-import [--format <format>] <file>");
+    eprintln!("       fin-import --list");
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut format: Option<String> = None;
+    let mut file: Option<String> = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--list" => {
+                for converter in converters() {
+                    println!("{:<14}{}", converter.id(), converter.name());
+                }
+                return;
+            }
+            "--format" => format = Some(args.next().unwrap_or_else(|| usage())),
+            _ if file.is_none() => file = Some(arg),
+            _ => usage(),
+        }
+    }
+    let file = file.unwrap_or_else(|| usage());
+
+    let contents = fs::read_to_string(&file).unwrap_or_else(|e| {
+        eprintln!("Unable to read {}: {}", file, e);
+        process::exit(1);
+    });
+
+    let converter: Box<dyn Converter> = match format {
+        Some(id) => find_converter(&id).unwrap_or_else(|| {
+            eprintln!(
+                "Unknown format: {}! Run with --list to see the formats.",
+                id
+            );
+            process::exit(1);
+        }),
+        None => detect_converter(&contents).unwrap_or_else(|| {
+            eprintln!(
+                "Unable to detect the format of {}! Pass it with --format.",
+                file
+            );
+            process::exit(1);
+        }),
+    };
+    eprintln!("Reading {} as {}.", file, converter.name());
+
+    let entries = converter.convert(&contents).unwrap_or_else(|e| {
+        eprintln!("Unable to convert {}: {}", file, e);
+        process::exit(1);
+    });
+    if let Err(e) = write_entries(entries, io::stdout()) {
+        eprintln!("Unable to write entries: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,234 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, StringRecord};
+
+use shared_lib::LedgerEntry;
+use shared_lib::Money;
+use shared_lib::TransferType;
+
+use crate::{has_headers, ConvertError, ConvertedRecord, Converter};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignConvention {
+    // negative amounts leave the account, as most banks export them
+    NegativeIsWithdrawal,
+    // negative amounts are paid into the account, as most credit cards
+    // export charges as positive amounts
+    NegativeIsDeposit,
+}
+
+// Describes the columns of an arbitrary CSV export, so that an institution
+// without a converter of its own can still be imported. The amount is read
+// from the amount column when it holds a value and from the debit and credit
+// columns otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvMapping {
+    pub date_column: String,
+    // chrono format of the dates, such as %m/%d/%Y
+    pub date_format: String,
+    pub amount_column: Option<String>,
+    pub debit_column: Option<String>,
+    pub credit_column: Option<String>,
+    pub sign_convention: SignConvention,
+    pub payee_column: Option<String>,
+    pub category_column: Option<String>,
+    pub description_column: Option<String>,
+}
+
+impl CsvMapping {
+    pub fn columns(&self) -> Vec<&str> {
+        [
+            Some(&self.date_column),
+            self.amount_column.as_ref(),
+            self.debit_column.as_ref(),
+            self.credit_column.as_ref(),
+            self.payee_column.as_ref(),
+            self.category_column.as_ref(),
+            self.description_column.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|column| column.as_str())
+        .collect()
+    }
+
+    // returns the amount and whether it leaves the account
+    fn amount(
+        &self,
+        record: &StringRecord,
+        headers: &StringRecord,
+    ) -> Result<(Money, bool), ConvertError> {
+        if let Some(amount) = read_amount(record, headers, self.amount_column.as_deref())? {
+            let withdrawal = match self.sign_convention {
+                SignConvention::NegativeIsWithdrawal => amount.is_negative(),
+                SignConvention::NegativeIsDeposit => !amount.is_negative(),
+            };
+            return Ok((amount.abs(), withdrawal));
+        }
+        if let Some(debit) = read_amount(record, headers, self.debit_column.as_deref())? {
+            if !debit.is_zero() {
+                return Ok((debit.abs(), true));
+            }
+        }
+        if let Some(credit) = read_amount(record, headers, self.credit_column.as_deref())? {
+            return Ok((credit.abs(), false));
+        }
+        Err(ConvertError::Unrecognized("no amount found".to_string()))
+    }
+
+    fn convert_record(
+        &self,
+        record: &StringRecord,
+        headers: &StringRecord,
+    ) -> Result<LedgerEntry, ConvertError> {
+        let date = read_field(record, headers, Some(&self.date_column));
+        let date = NaiveDate::parse_from_str(&date, &self.date_format).map_err(|e| {
+            ConvertError::Unrecognized(format!(
+                "date {} does not match {}: {}",
+                date, self.date_format, e
+            ))
+        })?;
+        let (amount, withdrawal) = self.amount(record, headers)?;
+        let transfer_type = if withdrawal {
+            TransferType::WithdrawalToExternalAccount
+        } else {
+            TransferType::DepositFromExternalAccount
+        };
+
+        let description = read_field(record, headers, self.description_column.as_ref());
+        let mut payee = read_field(record, headers, self.payee_column.as_ref());
+        if payee.is_empty() {
+            payee = if description.is_empty() {
+                "Unknown".to_string()
+            } else {
+                description.clone()
+            };
+        }
+        let mut category = read_field(record, headers, self.category_column.as_ref());
+        if category.is_empty() {
+            category = if withdrawal { "Withdrawal" } else { "Deposit" }.to_string();
+        }
+
+        Ok(LedgerEntry {
+            date: date.format("%Y-%m-%d").to_string(),
+            amount,
+            transfer_type,
+            participant: payee,
+            category,
+            description,
+            stock_info: None,
+        })
+    }
+}
+
+impl Converter for CsvMapping {
+    fn id(&self) -> &'static str {
+        "mapping"
+    }
+
+    fn name(&self) -> &'static str {
+        "saved CSV column mapping"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        has_headers(contents, &self.columns())
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        let whole_file = |error: ConvertError| {
+            vec![ConvertedRecord {
+                line: None,
+                entries: Err(error),
+            }]
+        };
+        let contents = contents.trim_start_matches('\u{feff}');
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .flexible(true)
+            .from_reader(contents.as_bytes());
+        let headers = match rdr.headers() {
+            Ok(headers) => headers.clone(),
+            Err(error) => return whole_file(error.into()),
+        };
+        for column in self.columns() {
+            if column_index(&headers, column).is_none() {
+                return whole_file(ConvertError::Unrecognized(format!(
+                    "The file has no column named {}",
+                    column
+                )));
+            }
+        }
+
+        rdr.records()
+            .filter_map(|result| match result {
+                // blank lines hold no transaction
+                Ok(record) if record.iter().all(|field| field.trim().is_empty()) => None,
+                Ok(record) => Some(ConvertedRecord {
+                    line: record.position().map(|p| p.line()),
+                    entries: self
+                        .convert_record(&record, &headers)
+                        .map(|entry| vec![entry]),
+                }),
+                Err(error) => Some(ConvertedRecord {
+                    line: error.position().map(|p| p.line()),
+                    entries: Err(error.into()),
+                }),
+            })
+            .collect()
+    }
+}
+
+fn column_index(headers: &StringRecord, column: &str) -> Option<usize> {
+    headers.iter().position(|header| header.trim() == column)
+}
+
+fn read_field(record: &StringRecord, headers: &StringRecord, column: Option<&String>) -> String {
+    column
+        .and_then(|column| column_index(headers, column))
+        .and_then(|index| record.get(index))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+// Reads an amount as institutions write them: with currency signs, thousands
+// separators and parentheses around negative amounts.
+fn read_amount(
+    record: &StringRecord,
+    headers: &StringRecord,
+    column: Option<&str>,
+) -> Result<Option<Money>, ConvertError> {
+    let column = match column {
+        Some(column) => column.to_string(),
+        None => return Ok(None),
+    };
+    let text = read_field(record, headers, Some(&column));
+    let mut cleaned = text.replace([',', '$', ' '], "");
+    if cleaned.starts_with('(') && cleaned.ends_with(')') {
+        cleaned = format!("-{}", &cleaned[1..cleaned.len() - 1]);
+    }
+    if cleaned.is_empty() || cleaned == "-" {
+        return Ok(None);
+    }
+    cleaned.parse::<Money>().map(Some).map_err(|e| {
+        ConvertError::Unrecognized(format!(
+            "{} in column {} is not an amount: {}",
+            text, column, e
+        ))
+    })
+}
@@ -0,0 +1,259 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use regex::Regex;
+use shared_lib::LedgerEntry;
+
+use crate::{ConvertError, ConvertedRecord, Converter};
+
+pub mod ofx_defs;
+use ofx_defs::OFX;
+
+// Bank and brokerage statements downloaded as OFX or QFX files.
+pub struct OfxConverter;
+
+impl Converter for OfxConverter {
+    fn id(&self) -> &'static str {
+        "ofx"
+    }
+
+    fn name(&self) -> &'static str {
+        "OFX/QFX statement"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        let head = contents.trim_start_matches('\u{feff}').trim_start();
+        head.starts_with("OFXHEADER") || contents.to_ascii_uppercase().contains("<OFX>")
+    }
+
+    // the file is one XML document, which is read whole or not at all
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        let entries = parse_statements(contents)
+            .map(|statements| {
+                statements
+                    .into_iter()
+                    .flat_map(|statement| statement.entries)
+                    .map(|statement_entry| statement_entry.entry)
+                    .collect()
+            })
+            .map_err(ConvertError::from);
+        vec![ConvertedRecord {
+            line: None,
+            entries,
+        }]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatementKind {
+    Bank,
+    Investment,
+}
+
+// The account a statement belongs to, as the financial institution knows it:
+// BANKACCTFROM for bank statements and INVACCTFROM for investment statements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementAccount {
+    pub kind: StatementKind,
+    // BANKID or BROKERID
+    pub institution_id: String,
+    pub account_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatementEntry {
+    // the institution's id for the transaction, which stays the same across
+    // downloads of overlapping statements
+    pub fitid: String,
+    pub entry: LedgerEntry,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub account: StatementAccount,
+    pub entries: Vec<StatementEntry>,
+}
+
+// Reads every statement in an OFX or QFX file.
+pub fn parse_statements(ofx_text: &str) -> Result<Vec<Statement>, serde_xml_rs::Error> {
+    let xml = ofx_to_xml(ofx_text);
+    let ofx: OFX = serde_xml_rs::from_str(xml.as_str())?;
+
+    let mut statements = Vec::new();
+    if let Some(msg) = ofx.bank_sign_on_msg {
+        let response = msg.statement_transaction_response.statement_response;
+        statements.push(Statement {
+            account: StatementAccount {
+                kind: StatementKind::Bank,
+                institution_id: response.bank_acct_from.bank_id.to_string(),
+                account_id: response.bank_acct_from.account_id,
+            },
+            entries: response
+                .bank_transaction_list
+                .statement_transaction
+                .into_iter()
+                .map(|txn| StatementEntry {
+                    fitid: txn.financial_institution_transaction_id.clone(),
+                    entry: LedgerEntry::from(txn),
+                })
+                .collect(),
+        });
+    }
+
+    if let Some(msg) = ofx.investment_sign_on_msg {
+        let response = msg
+            .investment_statement_transaction_response
+            .investment_statement_response;
+        let invtran = response.investment_transaction_list;
+
+        let mut entries = Vec::new();
+        for txn in invtran.buy_stock.unwrap_or_default() {
+            let fitid = txn
+                .investment_buy
+                .investment_transaction
+                .financial_institution_transaction_id
+                .clone();
+            entries.push(StatementEntry {
+                fitid,
+                entry: LedgerEntry::from(txn),
+            });
+        }
+        for txn in invtran.sell_stock.unwrap_or_default() {
+            let fitid = txn
+                .investment_sell
+                .investment_transaction
+                .financial_institution_transaction_id
+                .clone();
+            entries.push(StatementEntry {
+                fitid,
+                entry: LedgerEntry::from(txn),
+            });
+        }
+        for txn in invtran.buy_mf.unwrap_or_default() {
+            let fitid = txn
+                .investment_buy
+                .investment_transaction
+                .financial_institution_transaction_id
+                .clone();
+            entries.push(StatementEntry {
+                fitid,
+                entry: LedgerEntry::from(txn),
+            });
+        }
+        for txn in invtran.sell_mf.unwrap_or_default() {
+            let fitid = txn
+                .investment_sell
+                .investment_transaction
+                .financial_institution_transaction_id
+                .clone();
+            entries.push(StatementEntry {
+                fitid,
+                entry: LedgerEntry::from(txn),
+            });
+        }
+
+        for txn in invtran.income.unwrap_or_default() {
+            let fitid = txn
+                .investment_transaction
+                .financial_institution_transaction_id
+                .clone();
+            entries.push(StatementEntry {
+                fitid,
+                entry: LedgerEntry::from(txn),
+            });
+        }
+        // a reinvestment is recorded as the income and the purchase it paid
+        // for, which need ids of their own
+        for txn in invtran.reinvest.unwrap_or_default() {
+            let fitid = &txn
+                .investment_transaction
+                .financial_institution_transaction_id;
+            entries.push(StatementEntry {
+                fitid: fitid.clone(),
+                entry: txn.distribution(),
+            });
+            entries.push(StatementEntry {
+                fitid: format!("{}:reinvest", fitid),
+                entry: txn.purchase(),
+            });
+        }
+
+        statements.push(Statement {
+            account: StatementAccount {
+                kind: StatementKind::Investment,
+                institution_id: response.investment_account_from.broker_id,
+                account_id: response.investment_account_from.account_id,
+            },
+            entries,
+        });
+    }
+
+    Ok(statements)
+}
+
+/// Convert raw OFX (SGML-ish) into well-formed XML string.
+pub fn ofx_to_xml(ofx_text: &str) -> String {
+    // Drop the header lines that precede the OFX body.
+    let start = ofx_text.find('<').unwrap_or(0);
+    let body = &ofx_text[start..];
+
+    // OFX 1.x, which QFX files use, is SGML and leaves out the end tag of
+    // every element that holds a value. Close those elements so the body
+    // parses as XML; OFX 2.x files are already XML and pass through unchanged.
+    let token = Regex::new(r"<(/?)([^>]+)>([^<]*)").unwrap();
+    let tokens: Vec<(bool, &str, &str)> = token
+        .captures_iter(body)
+        .map(|caps| {
+            (
+                !caps.get(1).unwrap().as_str().is_empty(),
+                caps.get(2).unwrap().as_str().trim(),
+                caps.get(3).unwrap().as_str().trim(),
+            )
+        })
+        .collect();
+
+    let mut xml = String::with_capacity(body.len());
+    for (i, (closing, name, text)) in tokens.iter().enumerate() {
+        if *closing {
+            xml.push_str(&format!("</{}>", name));
+        } else if name.starts_with('?') || name.starts_with('!') {
+            // processing instructions and declarations
+            xml.push_str(&format!("<{}>", name));
+        } else {
+            xml.push_str(&format!("<{}>{}", name, escape_text(text)));
+            let closed_next = matches!(tokens.get(i + 1), Some((true, next, _)) if next == name);
+            if !text.is_empty() && !closed_next {
+                xml.push_str(&format!("</{}>", name));
+            }
+        }
+        xml.push('\n');
+    }
+    xml
+}
+
+// SGML files may hold a bare '&', which XML only allows as an entity.
+fn escape_text(text: &str) -> String {
+    const ENTITIES: [&str; 6] = ["amp;", "lt;", "gt;", "quot;", "apos;", "#"];
+    let mut escaped = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        if c == '&' && !ENTITIES.iter().any(|e| text[i + 1..].starts_with(e)) {
+            escaped.push_str("&amp;");
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
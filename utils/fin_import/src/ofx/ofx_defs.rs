@@ -19,7 +19,10 @@ use std::str::FromStr;
 use serde::{de, Deserialize, Deserializer};
 // use serde_xml_rs::from_str;
 use chrono::{Date, DateTime, FixedOffset, NaiveDate, NaiveDateTime};
-use shared_lib::{LedgerEntry, TransferType, TransferTypeIter};
+use shared_lib::{
+    DistributionType, LedgerEntry, Money, Quantity, TransferType, TransferTypeIter,
+    REINVESTMENT_CATEGORY,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct OFX {
@@ -110,8 +113,8 @@ pub struct StatementTransaction {
     pub transaction_type: OfxTransactionType,
     #[serde(rename = "DTPOSTED", deserialize_with = "deserialize_date")]
     pub date_posted: String,
-    #[serde(rename = "TRNAMT")]
-    pub transaction_amount: f32,
+    #[serde(rename = "TRNAMT", deserialize_with = "deserialize_from_str")]
+    pub transaction_amount: Money,
     #[serde(rename = "FITID")]
     pub financial_institution_transaction_id: String,
     #[serde(rename = "CHECKNUM")]
@@ -135,20 +138,24 @@ impl From<StatementTransaction> for shared_lib::LedgerEntry {
                 "DEBIT".to_string(),
             )
         } else {
-            // CHECK type, need to determine amount
-            let direction = if txn.transaction_amount < 0.0 {
+            // CHECK and the other types, need to determine amount
+            let direction = if txn.transaction_amount.is_negative() {
                 TransferType::WithdrawalToExternalAccount
             } else {
                 TransferType::DepositFromExternalAccount
             };
-            (direction, "CHECK".to_string())
+            let category = match &txn.transaction_type {
+                OfxTransactionType::OTHER(trntype) => trntype.clone(),
+                _ => "CHECK".to_string(),
+            };
+            (direction, category)
         };
-        let amt = f32::abs(txn.transaction_amount);
+        let amt = txn.transaction_amount.abs();
         shared_lib::LedgerEntry {
             date: txn.date_posted.clone(),
             amount: amt,
             transfer_type: ttype,
-            participant: format!("\"{}\"", txn.name),
+            participant: txn.name.clone(),
             category: category.clone(),
             description: format!("{} of {} on {}.", category, amt, txn.date_posted),
             stock_info: None,
@@ -158,16 +165,16 @@ impl From<StatementTransaction> for shared_lib::LedgerEntry {
 
 #[derive(Debug, Deserialize)]
 pub struct LedgerBalance {
-    #[serde(rename = "BALAMT")]
-    pub balance_amount: f32,
+    #[serde(rename = "BALAMT", deserialize_with = "deserialize_from_str")]
+    pub balance_amount: Money,
     #[serde(rename = "DTASOF")]
     pub date_time_as_of: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AvailableBalance {
-    #[serde(rename = "BALAMT")]
-    pub balance_amount: f32,
+    #[serde(rename = "BALAMT", deserialize_with = "deserialize_from_str")]
+    pub balance_amount: Money,
     #[serde(rename = "DTASOF", deserialize_with = "deserialize_date")]
     pub date_time_as_of: String,
 }
@@ -230,6 +237,8 @@ pub struct InvestmentTransactionList {
     pub sell_mf: Option<Vec<SellMutualFund>>,
     #[serde(rename = "INCOME")]
     pub income: Option<Vec<Income>>,
+    #[serde(rename = "REINVEST")]
+    pub reinvest: Option<Vec<Reinvest>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -256,7 +265,7 @@ impl From<BuyStock> for shared_lib::LedgerEntry {
                 .investment_transaction
                 .date_of_trade
                 .clone(),
-            amount: f32::abs(txn.investment_buy.total.clone()),
+            amount: txn.investment_buy.total.abs(),
             transfer_type: shared_lib::TransferType::WithdrawalToInternalAccount,
             participant: txn.investment_buy.security_identifer.unique_id.clone(),
             category: "BUY".to_string(),
@@ -294,7 +303,7 @@ impl From<BuyMutualFund> for shared_lib::LedgerEntry {
                 .investment_transaction
                 .date_of_trade
                 .clone(),
-            amount: f32::abs(txn.investment_buy.total.clone()),
+            amount: txn.investment_buy.total.abs(),
             transfer_type: shared_lib::TransferType::WithdrawalToInternalAccount,
             participant: txn.investment_buy.security_identifer.unique_id.clone(),
             category: "BUY".to_string(),
@@ -332,7 +341,7 @@ impl From<SellStock> for shared_lib::LedgerEntry {
                 .investment_transaction
                 .date_of_trade
                 .clone(),
-            amount: f32::abs(txn.investment_sell.total.clone()),
+            amount: txn.investment_sell.total.abs(),
             transfer_type: shared_lib::TransferType::WithdrawalToInternalAccount,
             participant: txn.investment_sell.security_identifer.unique_id.clone(),
             category: "SELL".to_string(),
@@ -370,7 +379,7 @@ impl From<SellMutualFund> for shared_lib::LedgerEntry {
                 .investment_transaction
                 .date_of_trade
                 .clone(),
-            amount: f32::abs(txn.investment_sell.total.clone()),
+            amount: txn.investment_sell.total.abs(),
             transfer_type: shared_lib::TransferType::WithdrawalToInternalAccount,
             participant: txn.investment_sell.security_identifer.unique_id.clone(),
             category: "SELL".to_string(),
@@ -398,14 +407,18 @@ pub struct InvestmentBuy {
     pub investment_transaction: InvestmentTransaction,
     #[serde(rename = "SECID")]
     pub security_identifer: SecurityId,
-    #[serde(rename = "UNITS")]
-    pub units: f32,
-    #[serde(rename = "UNITPRICE")]
-    pub unit_price: f32,
-    #[serde(rename = "FEES")]
-    pub fees: Option<f32>,
-    #[serde(rename = "TOTAL")]
-    pub total: f32,
+    #[serde(rename = "UNITS", deserialize_with = "deserialize_from_str")]
+    pub units: Quantity,
+    #[serde(rename = "UNITPRICE", deserialize_with = "deserialize_from_str")]
+    pub unit_price: Quantity,
+    #[serde(
+        rename = "FEES",
+        default,
+        deserialize_with = "deserialize_optional_from_str"
+    )]
+    pub fees: Option<Money>,
+    #[serde(rename = "TOTAL", deserialize_with = "deserialize_from_str")]
+    pub total: Money,
     #[serde(rename = "SUBACCTSEC")]
     pub sub_account_security: String,
     #[serde(rename = "SUBACCTFUND")]
@@ -418,14 +431,14 @@ pub struct InvestmentSell {
     pub investment_transaction: InvestmentTransaction,
     #[serde(rename = "SECID")]
     pub security_identifer: SecurityId,
-    #[serde(rename = "UNITS")]
-    pub units: f32,
-    #[serde(rename = "UNITPRICE")]
-    pub unit_price: f32,
-    #[serde(rename = "FEES")]
-    pub fees: f32,
-    #[serde(rename = "TOTAL")]
-    pub total: f32,
+    #[serde(rename = "UNITS", deserialize_with = "deserialize_from_str")]
+    pub units: Quantity,
+    #[serde(rename = "UNITPRICE", deserialize_with = "deserialize_from_str")]
+    pub unit_price: Quantity,
+    #[serde(rename = "FEES", deserialize_with = "deserialize_from_str")]
+    pub fees: Money,
+    #[serde(rename = "TOTAL", deserialize_with = "deserialize_from_str")]
+    pub total: Money,
     #[serde(rename = "SUBACCTSEC")]
     pub sub_account_security: String,
     #[serde(rename = "SUBACCTFUND")]
@@ -458,14 +471,113 @@ pub struct Income {
     pub security_identifer: SecurityId,
     #[serde(rename = "INCOMETYPE")]
     pub income_type: String,
-    #[serde(rename = "TOTAL")]
-    pub total: f32,
+    #[serde(rename = "TOTAL", deserialize_with = "deserialize_from_str")]
+    pub total: Money,
     #[serde(rename = "SUBACCTSEC")]
     pub sub_account_security: String,
     #[serde(rename = "SUBACCTFUND")]
     pub sub_account_fund: String,
 }
 
+impl From<Income> for shared_lib::LedgerEntry {
+    fn from(txn: Income) -> Self {
+        shared_lib::LedgerEntry {
+            date: txn.investment_transaction.date_of_trade.clone(),
+            amount: txn.total.abs(),
+            transfer_type: shared_lib::TransferType::DepositFromInternalAccount,
+            participant: txn.security_identifer.unique_id.clone(),
+            category: income_category(&txn.income_type),
+            description: format!(
+                "{} from {} on {}",
+                income_category(&txn.income_type),
+                txn.security_identifer.unique_id,
+                txn.investment_transaction.date_of_trade
+            ),
+            stock_info: None,
+        }
+    }
+}
+
+// Income that is used to buy more of the security that paid it.
+#[derive(Debug, Deserialize)]
+pub struct Reinvest {
+    #[serde(rename = "INVTRAN")]
+    pub investment_transaction: InvestmentTransaction,
+    #[serde(rename = "SECID")]
+    pub security_identifer: SecurityId,
+    #[serde(rename = "INCOMETYPE")]
+    pub income_type: String,
+    #[serde(rename = "TOTAL", deserialize_with = "deserialize_from_str")]
+    pub total: Money,
+    #[serde(rename = "SUBACCTSEC")]
+    pub sub_account_security: String,
+    #[serde(rename = "UNITS", deserialize_with = "deserialize_from_str")]
+    pub units: Quantity,
+    #[serde(rename = "UNITPRICE", deserialize_with = "deserialize_from_str")]
+    pub unit_price: Quantity,
+    #[serde(
+        rename = "FEES",
+        default,
+        deserialize_with = "deserialize_optional_from_str"
+    )]
+    pub fees: Option<Money>,
+}
+
+impl Reinvest {
+    // the income, deposited from the security
+    pub fn distribution(&self) -> shared_lib::LedgerEntry {
+        shared_lib::LedgerEntry {
+            date: self.investment_transaction.date_of_trade.clone(),
+            amount: self.total.abs(),
+            transfer_type: shared_lib::TransferType::DepositFromInternalAccount,
+            participant: self.security_identifer.unique_id.clone(),
+            category: income_category(&self.income_type),
+            description: format!(
+                "{} from {} on {}",
+                income_category(&self.income_type),
+                self.security_identifer.unique_id,
+                self.investment_transaction.date_of_trade
+            ),
+            stock_info: None,
+        }
+    }
+
+    // the shares the income bought
+    pub fn purchase(&self) -> shared_lib::LedgerEntry {
+        shared_lib::LedgerEntry {
+            date: self.investment_transaction.date_of_trade.clone(),
+            amount: self.total.abs(),
+            transfer_type: shared_lib::TransferType::WithdrawalToInternalAccount,
+            participant: self.security_identifer.unique_id.clone(),
+            category: REINVESTMENT_CATEGORY.to_string(),
+            description: format!(
+                "Reinvest in {} shares of {} for ${} per share on {}",
+                self.units,
+                self.security_identifer.unique_id,
+                self.unit_price,
+                self.investment_transaction.date_of_trade
+            ),
+            stock_info: Some(shared_lib::StockInfo {
+                shares: self.units,
+                costbasis: self.unit_price,
+                remaining: self.units,
+                is_buy: true,
+                is_split: false,
+            }),
+        }
+    }
+}
+
+// The category of an INCOMETYPE: DIV, INTEREST, CGLONG, CGSHORT or MISC.
+fn income_category(income_type: &str) -> String {
+    match income_type.trim().to_ascii_uppercase().as_str() {
+        "DIV" => DistributionType::Dividend.category(),
+        "INTEREST" => DistributionType::Interest.category(),
+        "CGLONG" | "CGSHORT" => DistributionType::CapitalGain.category(),
+        other => other.to_string(),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct InvestmentPositionList {
     #[serde(rename = "POSSTOCK")]
@@ -494,24 +606,24 @@ pub struct InvestmentPosition {
     pub held_in_account: String,
     #[serde(rename = "POSTYPE")]
     pub position_type: String,
-    #[serde(rename = "UNITS")]
-    pub units: f32,
-    #[serde(rename = "UNITPRICE")]
-    pub unit_price: f32,
-    #[serde(rename = "MKTVAL")]
-    pub market_value: f32,
+    #[serde(rename = "UNITS", deserialize_with = "deserialize_from_str")]
+    pub units: Quantity,
+    #[serde(rename = "UNITPRICE", deserialize_with = "deserialize_from_str")]
+    pub unit_price: Quantity,
+    #[serde(rename = "MKTVAL", deserialize_with = "deserialize_from_str")]
+    pub market_value: Money,
     #[serde(rename = "DTPRICEASOF", deserialize_with = "deserialize_date")]
     pub date_time_price_as_of: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct InvestmentBalance {
-    #[serde(rename = "AVAILCASH")]
-    pub available_cash: f32,
-    #[serde(rename = "MARGINBALANCE")]
-    pub margin_balance: f32,
-    #[serde(rename = "SHORTBALANCE")]
-    pub short_balance: f32,
+    #[serde(rename = "AVAILCASH", deserialize_with = "deserialize_from_str")]
+    pub available_cash: Money,
+    #[serde(rename = "MARGINBALANCE", deserialize_with = "deserialize_from_str")]
+    pub margin_balance: Money,
+    #[serde(rename = "SHORTBALANCE", deserialize_with = "deserialize_from_str")]
+    pub short_balance: Money,
 }
 
 #[derive(Debug, Deserialize)]
@@ -599,10 +711,13 @@ enum SEVERITY {
 }
 
 #[derive(Clone, Debug)]
-enum OfxTransactionType {
+pub enum OfxTransactionType {
     CREDIT,
     DEBIT,
     CHECK,
+    // INT, DIV, FEE, XFER, POS and the like, whose direction follows the sign
+    // of the amount
+    OTHER(String),
 }
 
 fn deserialize_datetime<'de, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
@@ -637,9 +752,28 @@ where
         "CREDIT" => Ok(OfxTransactionType::CREDIT),
         "DEBIT" => Ok(OfxTransactionType::DEBIT),
         "CHECK" => Ok(OfxTransactionType::CHECK),
-        _ => Err(de::Error::unknown_variant(
-            trntype.as_str(),
-            &["CREDIT", "DEBIT"],
-        )),
+        "" => Err(de::Error::invalid_length(0, &"a transaction type")),
+        other => Ok(OfxTransactionType::OTHER(other.to_string())),
     }
 }
+
+// Amounts are read from their text, since the XML deserializer cannot tell
+// Money and Quantity what kind of value to expect.
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    s.trim().parse::<T>().map_err(de::Error::custom)
+}
+
+fn deserialize_optional_from_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    deserialize_from_str(deserializer).map(Some)
+}
@@ -0,0 +1,926 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::NaiveDate;
+use std::io::{self, Write};
+
+use shared_lib::DistributionType;
+use shared_lib::LedgerEntry;
+use shared_lib::Money;
+use shared_lib::Quantity;
+use shared_lib::StockInfo;
+use shared_lib::TransferType;
+use shared_lib::REINVESTMENT_CATEGORY;
+
+use crate::{ConvertError, ConvertedRecord, Converter};
+
+// The kinds of account a QIF file holds transactions for. Other asset and
+// liability accounts are read like bank accounts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QifAccountType {
+    Bank,
+    CreditCard,
+    Cash,
+    Investment,
+}
+
+impl QifAccountType {
+    fn header(&self) -> &'static str {
+        match self {
+            QifAccountType::Bank => "!Type:Bank",
+            QifAccountType::CreditCard => "!Type:CCard",
+            QifAccountType::Cash => "!Type:Cash",
+            QifAccountType::Investment => "!Type:Invst",
+        }
+    }
+
+    // Returns the account type named by a `!Type:` header, or None for the
+    // lists (categories, classes, securities, prices...) that are not read.
+    fn from_header(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "bank" | "oth a" | "oth l" => Some(QifAccountType::Bank),
+            "ccard" => Some(QifAccountType::CreditCard),
+            "cash" => Some(QifAccountType::Cash),
+            "invst" => Some(QifAccountType::Investment),
+            _ => None,
+        }
+    }
+}
+
+// the lines of one transaction, up to its closing '^'
+struct QifRecord {
+    account_type: QifAccountType,
+    // line of the file the transaction starts on
+    line: usize,
+    fields: Vec<(char, String)>,
+}
+
+impl QifRecord {
+    fn get(&self, code: char) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn error(&self, message: String) -> ConvertError {
+        ConvertError::Qif {
+            line: self.line,
+            message,
+        }
+    }
+
+    fn date(&self) -> Result<String, ConvertError> {
+        let value = self
+            .get('D')
+            .ok_or_else(|| self.error("date is missing".to_string()))?;
+        parse_date(value)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .ok_or_else(|| self.error(format!("date {} is not valid", value)))
+    }
+
+    fn money(&self, code: char) -> Result<Option<Money>, ConvertError> {
+        match self.get(code) {
+            Some(value) => value
+                .parse::<Money>()
+                .map(Some)
+                .map_err(|e| self.error(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    fn quantity(&self, code: char) -> Result<Option<Quantity>, ConvertError> {
+        match self.get(code) {
+            Some(value) => value
+                .parse::<Quantity>()
+                .map(Some)
+                .map_err(|e| self.error(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    // the amount, written as T or, by newer versions of Quicken, also as U
+    fn amount(&self) -> Result<Option<Money>, ConvertError> {
+        match self.money('T')? {
+            Some(amount) => Ok(Some(amount)),
+            None => self.money('U'),
+        }
+    }
+}
+
+pub struct QifConverter;
+
+impl Converter for QifConverter {
+    fn id(&self) -> &'static str {
+        "qif"
+    }
+
+    fn name(&self) -> &'static str {
+        "Quicken Interchange Format (QIF)"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        let first = contents
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .find(|line| !line.is_empty());
+        match first {
+            Some(line) => ["!type:", "!account", "!option:"]
+                .iter()
+                .any(|header| line.starts_with(header)),
+            None => false,
+        }
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        read_records(contents)
+            .into_iter()
+            .map(|record| ConvertedRecord {
+                line: Some(record.line as u64),
+                entries: match record.account_type {
+                    QifAccountType::Investment => convert_investment(&record),
+                    _ => convert_cash(&record),
+                },
+            })
+            .collect()
+    }
+}
+
+// Splits the file into its transactions. Records of the lists that are not
+// read, and of the account list introduced by `!Account`, are left out.
+fn read_records(contents: &str) -> Vec<QifRecord> {
+    let mut records = Vec::new();
+    let mut account_type: Option<QifAccountType> = None;
+    let mut fields: Vec<(char, String)> = Vec::new();
+    let mut start = 0;
+    for (n, line) in contents.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with('!') {
+            let lower = line.to_lowercase();
+            if let Some(name) = lower.strip_prefix("!type:") {
+                account_type = QifAccountType::from_header(name);
+            } else if lower.starts_with("!account") {
+                account_type = None;
+            }
+            // options such as !Option:AutoSwitch change nothing here
+            continue;
+        }
+        if fields.is_empty() {
+            start = n + 1;
+        }
+        if line.trim() == "^" {
+            if let Some(account_type) = account_type {
+                if !fields.is_empty() {
+                    records.push(QifRecord {
+                        account_type,
+                        line: start,
+                        fields: std::mem::take(&mut fields),
+                    });
+                }
+            }
+            fields.clear();
+            continue;
+        }
+        let mut chars = line.chars();
+        let code = chars.next().unwrap().to_ascii_uppercase();
+        fields.push((code, chars.as_str().trim().to_string()));
+    }
+    if let Some(account_type) = account_type {
+        if !fields.is_empty() {
+            records.push(QifRecord {
+                account_type,
+                line: start,
+                fields,
+            });
+        }
+    }
+    records
+}
+
+// Reads a bank, credit card or cash transaction. A split transaction becomes
+// one entry per split, each with its own category, amount and memo.
+fn convert_cash(record: &QifRecord) -> Result<Vec<LedgerEntry>, ConvertError> {
+    let date = record.date()?;
+    let payee = record.get('P').unwrap_or("");
+    let memo = record.get('M').unwrap_or("");
+
+    let mut splits: Vec<(String, String, Option<Money>)> = Vec::new();
+    for (code, value) in record.fields.iter() {
+        match code {
+            'S' => splits.push((value.clone(), String::new(), None)),
+            'E' => {
+                if let Some(split) = splits.last_mut() {
+                    split.1 = value.clone();
+                }
+            }
+            '$' => {
+                if let Some(split) = splits.last_mut() {
+                    split.2 = Some(
+                        value
+                            .parse::<Money>()
+                            .map_err(|e| record.error(e.to_string()))?,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if splits.is_empty() {
+        let amount = record
+            .amount()?
+            .ok_or_else(|| record.error("amount is missing".to_string()))?;
+        let category = record.get('L').unwrap_or("");
+        return Ok(vec![cash_entry(&date, amount, payee, category, memo)]);
+    }
+
+    let mut entries = Vec::new();
+    for (category, split_memo, amount) in splits {
+        let amount =
+            amount.ok_or_else(|| record.error(format!("split {} has no amount", category)))?;
+        let description = if split_memo.is_empty() {
+            memo
+        } else {
+            split_memo.as_str()
+        };
+        entries.push(cash_entry(&date, amount, payee, &category, description));
+    }
+    Ok(entries)
+}
+
+fn cash_entry(date: &str, amount: Money, payee: &str, category: &str, memo: &str) -> LedgerEntry {
+    let (category, transfer) = parse_category(category);
+    let participant = if payee.is_empty() {
+        transfer.clone().unwrap_or_default()
+    } else {
+        payee.to_string()
+    };
+    LedgerEntry {
+        date: date.to_string(),
+        amount: amount.abs(),
+        transfer_type: if amount.is_negative() {
+            TransferType::WithdrawalToExternalAccount
+        } else {
+            TransferType::DepositFromExternalAccount
+        },
+        participant,
+        category: if transfer.is_some() {
+            "Transfer".to_string()
+        } else {
+            category
+        },
+        description: memo.to_string(),
+        stock_info: None,
+    }
+}
+
+// Splits a category field into the category, without its class, and the
+// account named by a transfer such as `[Savings]`.
+fn parse_category(category: &str) -> (String, Option<String>) {
+    let category = category.trim();
+    if let Some(account) = category
+        .strip_prefix('[')
+        .and_then(|rest| rest.split(']').next())
+    {
+        return (String::new(), Some(account.to_string()));
+    }
+    let category = match category.split_once('/') {
+        Some((category, _class)) => category,
+        None => category,
+    };
+    (category.to_string(), None)
+}
+
+// Reads an investment transaction. Purchases carry the shares bought, sales
+// the shares sold; dividends, interest and capital gain distributions are
+// deposits from the security. A reinvestment becomes both the distribution
+// and the purchase it paid for, so that the income is recorded and the cash
+// of the account is left as it was. Shares moved in or out of the account
+// without a trade change its holdings but not its cash.
+fn convert_investment(record: &QifRecord) -> Result<Vec<LedgerEntry>, ConvertError> {
+    let date = record.date()?;
+    let action = record
+        .get('N')
+        .ok_or_else(|| record.error("action is missing".to_string()))?;
+    let security = record.get('Y').unwrap_or("").to_string();
+    let payee = record.get('P').unwrap_or("").to_string();
+    let quantity = record.quantity('Q')?;
+    let mut price = record.quantity('I')?;
+    let amount = match record.amount()? {
+        Some(amount) => amount.abs(),
+        None => match (quantity, price) {
+            (Some(quantity), Some(price)) => quantity.abs().value_at(price),
+            _ => return Err(record.error("amount is missing".to_string())),
+        },
+    };
+    if price.is_none() {
+        price = quantity.and_then(|quantity| Quantity::from(amount).checked_div(quantity.abs()));
+    }
+
+    let shares = || {
+        quantity
+            .map(|quantity| quantity.abs())
+            .ok_or_else(|| record.error(format!("{} has no quantity", action)))
+    };
+    let action = action.to_lowercase();
+    let distribution = distribution_type(&action);
+    let description = record.get('M').unwrap_or("").to_string();
+
+    if let (Some(kind), true) = (distribution, action.starts_with("reinv")) {
+        let shares = shares()?;
+        return Ok(vec![
+            LedgerEntry {
+                date: date.clone(),
+                amount,
+                transfer_type: TransferType::DepositFromInternalAccount,
+                participant: security.clone(),
+                category: kind.category(),
+                description: description.clone(),
+                stock_info: None,
+            },
+            LedgerEntry {
+                date,
+                amount,
+                transfer_type: TransferType::WithdrawalToInternalAccount,
+                participant: security,
+                category: REINVESTMENT_CATEGORY.to_string(),
+                description,
+                stock_info: Some(StockInfo {
+                    shares,
+                    costbasis: price.unwrap_or(Quantity::ZERO),
+                    remaining: shares,
+                    is_buy: true,
+                    is_split: false,
+                }),
+            },
+        ]);
+    }
+
+    let (transfer_type, participant, stock_info) = match action.as_str() {
+        "buy" | "buyx" => {
+            let shares = shares()?;
+            (
+                TransferType::WithdrawalToInternalAccount,
+                security,
+                Some(StockInfo {
+                    shares,
+                    costbasis: price.unwrap_or(Quantity::ZERO),
+                    remaining: shares,
+                    is_buy: true,
+                    is_split: false,
+                }),
+            )
+        }
+        "sell" | "sellx" => (
+            TransferType::DepositFromInternalAccount,
+            security,
+            Some(StockInfo {
+                shares: shares()?,
+                costbasis: price.unwrap_or(Quantity::ZERO),
+                remaining: Quantity::ZERO,
+                is_buy: false,
+                is_split: false,
+            }),
+        ),
+        // the price of shares moved in is their cost basis
+        "shrsin" | "shrsout" => {
+            let shares = shares()?;
+            let is_buy = action == "shrsin";
+            (
+                TransferType::ZeroSumChange,
+                security,
+                Some(StockInfo {
+                    shares,
+                    costbasis: price.unwrap_or(Quantity::ZERO),
+                    remaining: if is_buy { shares } else { Quantity::ZERO },
+                    is_buy,
+                    is_split: false,
+                }),
+            )
+        }
+        "div" | "divx" | "intinc" | "intincx" | "cglong" | "cglongx" | "cgmid" | "cgmidx"
+        | "cgshort" | "cgshortx" => (
+            TransferType::DepositFromInternalAccount,
+            if security.is_empty() { payee } else { security },
+            None,
+        ),
+        "xin" | "contribx" | "miscinc" | "miscincx" => (
+            TransferType::DepositFromExternalAccount,
+            if payee.is_empty() {
+                "External Account".to_string()
+            } else {
+                payee
+            },
+            None,
+        ),
+        "xout" | "withdrwx" | "miscexp" | "miscexpx" => (
+            TransferType::WithdrawalToExternalAccount,
+            if payee.is_empty() {
+                "External Account".to_string()
+            } else {
+                payee
+            },
+            None,
+        ),
+        _ => return Err(record.error(format!("investment action {} is not supported", action))),
+    };
+
+    let (category, _) = parse_category(record.get('L').unwrap_or(""));
+    let category = match distribution {
+        Some(kind) => kind.category(),
+        None if category.is_empty() => action.clone(),
+        None => category,
+    };
+    Ok(vec![LedgerEntry {
+        date,
+        amount: if transfer_type == TransferType::ZeroSumChange {
+            Money::ZERO
+        } else {
+            amount
+        },
+        transfer_type,
+        participant,
+        category,
+        description,
+        stock_info,
+    }])
+}
+
+fn distribution_type(action: &str) -> Option<DistributionType> {
+    match action.trim_end_matches('x') {
+        "div" | "reinvdiv" => Some(DistributionType::Dividend),
+        "intinc" | "reinvint" => Some(DistributionType::Interest),
+        "cglong" | "cgmid" | "cgshort" | "reinvlg" | "reinvmd" | "reinvsh" => {
+            Some(DistributionType::CapitalGain)
+        }
+        _ => None,
+    }
+}
+
+// Reads the dates Quicken writes, such as 1/5/2024, 01/05/24, 1/ 5'24 and
+// 2024-01-05. Two digit years before 70 are taken to be in this century.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == '\'' { '/' } else { c })
+        .collect();
+    let parts: Vec<&str> = value.split(['/', '-', '.']).collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let numbers: Vec<u32> = parts
+        .iter()
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    let (year, month, day) = if parts[0].len() == 4 {
+        (numbers[0], numbers[1], numbers[2])
+    } else {
+        (numbers[2], numbers[0], numbers[1])
+    };
+    let year = match year {
+        0..=69 => 2000 + year,
+        70..=99 => 1900 + year,
+        _ => year,
+    };
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+// Writes the entries as a QIF file for the given kind of account and returns
+// how many were left out. QIF records a stock split as a ratio rather than as
+// the shares received, so splits are not written.
+pub fn write_qif<W: Write>(
+    entries: &[LedgerEntry],
+    account_type: QifAccountType,
+    mut writer: W,
+) -> io::Result<usize> {
+    let mut skipped = 0;
+    writeln!(writer, "{}", account_type.header())?;
+    for entry in entries {
+        let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d")
+            .map(|date| date.format("%m/%d/%Y").to_string())
+            .unwrap_or(entry.date.clone());
+        // a '/' would start the class of the category
+        let category = entry.category.replace('/', "-");
+
+        if account_type == QifAccountType::Investment {
+            let (action, stock_info) = match (&entry.stock_info, &entry.transfer_type) {
+                (Some(stock_info), _) if stock_info.is_split => {
+                    skipped += 1;
+                    continue;
+                }
+                (Some(stock_info), TransferType::ZeroSumChange) if stock_info.is_buy => {
+                    ("ShrsIn", Some(stock_info))
+                }
+                (Some(stock_info), TransferType::ZeroSumChange) => ("ShrsOut", Some(stock_info)),
+                (Some(stock_info), _) if stock_info.is_buy => ("Buy", Some(stock_info)),
+                (Some(stock_info), _) => ("Sell", Some(stock_info)),
+                (None, TransferType::DepositFromInternalAccount) => {
+                    match DistributionType::from_category(&entry.category) {
+                        Some(DistributionType::Interest) => ("IntInc", None),
+                        Some(DistributionType::CapitalGain) => ("CGLong", None),
+                        _ => ("Div", None),
+                    }
+                }
+                (None, TransferType::DepositFromExternalAccount) => ("XIn", None),
+                (None, TransferType::ZeroSumChange) => {
+                    skipped += 1;
+                    continue;
+                }
+                (None, _) => ("XOut", None),
+            };
+            writeln!(writer, "D{}", date)?;
+            writeln!(writer, "N{}", action)?;
+            if stock_info.is_some() || ["Div", "IntInc", "CGLong"].contains(&action) {
+                writeln!(writer, "Y{}", entry.participant)?;
+            } else {
+                writeln!(writer, "P{}", entry.participant)?;
+            }
+            if let Some(stock_info) = stock_info {
+                writeln!(writer, "I{}", stock_info.costbasis)?;
+                writeln!(writer, "Q{}", stock_info.shares)?;
+            }
+            writeln!(writer, "T{}", entry.amount)?;
+        } else {
+            let amount = if entry.transfer_type.is_withdrawal() {
+                -entry.amount
+            } else {
+                entry.amount
+            };
+            writeln!(writer, "D{}", date)?;
+            writeln!(writer, "T{}", amount)?;
+            writeln!(writer, "P{}", entry.participant)?;
+        }
+        if !category.is_empty() {
+            writeln!(writer, "L{}", category)?;
+        }
+        if !entry.description.is_empty() {
+            writeln!(writer, "M{}", entry.description)?;
+        }
+        writeln!(writer, "^")?;
+    }
+    writer.flush()?;
+    Ok(skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(contents: &str) -> Vec<LedgerEntry> {
+        QifConverter
+            .convert(contents)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[test]
+    fn reads_records_of_the_accounts_only() {
+        let contents = "\u{feff}!Type:Cat\nNGroceries\nE\n^\n!Account\nNChecking\nTBank\n^\n\
+            !Type:Bank\nD1/5/2024\nT-12.50\n^\n\nD1/6/2024\nT3\n^\n!Type:Invst\nD1/7/2024\nNDiv\nT1\n";
+        let records = read_records(contents);
+        let read: Vec<(QifAccountType, usize, usize)> = records
+            .iter()
+            .map(|record| (record.account_type, record.line, record.fields.len()))
+            .collect();
+        assert_eq!(
+            read,
+            vec![
+                (QifAccountType::Bank, 10, 2),
+                (QifAccountType::Bank, 14, 2),
+                // the last record needs no closing '^'
+                (QifAccountType::Investment, 18, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_cash_records() {
+        let entries = convert(
+            "!Type:Bank\nD01/05'24\nT-1,234.56\nPGrocer\nLFood/Home\nMweekly\n^\n\
+            D2024-01-06\nU250.00\nLSalary\n^\nD1/ 7/24\nT-100\n[Savings]\nL[Savings]\n^\n",
+        );
+        let read: Vec<(&str, Money, &TransferType, &str, &str, &str)> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.date.as_str(),
+                    entry.amount,
+                    &entry.transfer_type,
+                    entry.participant.as_str(),
+                    entry.category.as_str(),
+                    entry.description.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            read,
+            vec![
+                (
+                    "2024-01-05",
+                    Money::from_cents(123_456),
+                    &TransferType::WithdrawalToExternalAccount,
+                    "Grocer",
+                    "Food",
+                    "weekly",
+                ),
+                (
+                    "2024-01-06",
+                    Money::from_cents(25_000),
+                    &TransferType::DepositFromExternalAccount,
+                    "",
+                    "Salary",
+                    "",
+                ),
+                (
+                    "2024-01-07",
+                    Money::from_cents(10_000),
+                    &TransferType::WithdrawalToExternalAccount,
+                    "Savings",
+                    "Transfer",
+                    "",
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_each_split_as_an_entry() {
+        let entries = convert(
+            "!Type:CCard\nD3/1/2024\nT-60\nPStore\nMshopping\n\
+            SFood\nEbread\n$-20\nSHousehold\n$-40\n^\n",
+        );
+        let read: Vec<(Money, &str, &str)> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.amount,
+                    entry.category.as_str(),
+                    entry.description.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            read,
+            vec![
+                (Money::from_cents(2_000), "Food", "bread"),
+                (Money::from_cents(4_000), "Household", "shopping"),
+            ]
+        );
+        assert!(entries.iter().all(|entry| entry.participant == "Store"));
+
+        let errors: Vec<String> = QifConverter
+            .convert_records("!Type:Bank\nD3/1/2024\nT-60\nSFood\n^\nD13/1/2024\nT1\n^\n")
+            .into_iter()
+            .filter_map(|record| record.entries.err().map(|e| e.to_string()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "unable to read QIF at line 2: split Food has no amount",
+                "unable to read QIF at line 6: date 13/1/2024 is not valid",
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_investment_records() {
+        let entries = convert(
+            "!Type:Invst\n\
+            D1/2/2024\nNBuy\nYACME\nI10\nQ5\nT50\n^\n\
+            D2/1/2024\nNReinvDiv\nYACME\nI12.5\nQ2\nT25\n^\n\
+            D3/1/2024\nNShrsIn\nYACME\nI8\nQ10\nT80\n^\n\
+            D4/1/2024\nNShrsOut\nYACME\nQ3\nT36\n^\n\
+            D5/1/2024\nNSell\nYACME\nI15\nQ4\nT60\n^\n\
+            D6/1/2024\nNIntInc\nPBank\nT1.25\n^\n",
+        );
+        let read: Vec<(&str, Money, &TransferType, &str)> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.date.as_str(),
+                    entry.amount,
+                    &entry.transfer_type,
+                    entry.category.as_str(),
+                )
+            })
+            .collect();
+        let dividend = DistributionType::Dividend.category();
+        let interest = DistributionType::Interest.category();
+        assert_eq!(
+            read,
+            vec![
+                (
+                    "2024-01-02",
+                    Money::from_cents(5_000),
+                    &TransferType::WithdrawalToInternalAccount,
+                    "buy",
+                ),
+                // the income the reinvestment paid for comes before the shares
+                (
+                    "2024-02-01",
+                    Money::from_cents(2_500),
+                    &TransferType::DepositFromInternalAccount,
+                    dividend.as_str(),
+                ),
+                (
+                    "2024-02-01",
+                    Money::from_cents(2_500),
+                    &TransferType::WithdrawalToInternalAccount,
+                    REINVESTMENT_CATEGORY,
+                ),
+                // shares moved in or out do not touch the cash
+                (
+                    "2024-03-01",
+                    Money::ZERO,
+                    &TransferType::ZeroSumChange,
+                    "shrsin",
+                ),
+                (
+                    "2024-04-01",
+                    Money::ZERO,
+                    &TransferType::ZeroSumChange,
+                    "shrsout",
+                ),
+                (
+                    "2024-05-01",
+                    Money::from_cents(6_000),
+                    &TransferType::DepositFromInternalAccount,
+                    "sell",
+                ),
+                (
+                    "2024-06-01",
+                    Money::from_cents(125),
+                    &TransferType::DepositFromInternalAccount,
+                    interest.as_str(),
+                ),
+            ]
+        );
+
+        let stocks: Vec<Option<(Quantity, Quantity, Quantity, bool)>> = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .stock_info
+                    .as_ref()
+                    .map(|stock| (stock.shares, stock.costbasis, stock.remaining, stock.is_buy))
+            })
+            .collect();
+        let q = Quantity::from_f64;
+        assert_eq!(
+            stocks,
+            vec![
+                Some((q(5.0), q(10.0), q(5.0), true)),
+                None,
+                Some((q(2.0), q(12.5), q(2.0), true)),
+                Some((q(10.0), q(8.0), q(10.0), true)),
+                // the price is worked out from the amount when it is missing
+                Some((q(3.0), q(12.0), Quantity::ZERO, false)),
+                Some((q(4.0), q(15.0), Quantity::ZERO, false)),
+                None,
+            ]
+        );
+        assert_eq!(entries[6].participant, "Bank");
+    }
+
+    #[test]
+    fn written_files_read_back() {
+        let entry = |amount: i64,
+                     transfer_type: TransferType,
+                     participant: &str,
+                     category: &str,
+                     stock_info: Option<(f64, f64, bool)>| LedgerEntry {
+            date: "2024-01-05".to_string(),
+            amount: Money::from_cents(amount),
+            transfer_type,
+            participant: participant.to_string(),
+            category: category.to_string(),
+            description: "memo".to_string(),
+            stock_info: stock_info.map(|(shares, price, is_buy)| StockInfo {
+                shares: Quantity::from_f64(shares),
+                costbasis: Quantity::from_f64(price),
+                remaining: if is_buy {
+                    Quantity::from_f64(shares)
+                } else {
+                    Quantity::ZERO
+                },
+                is_buy,
+                is_split: false,
+            }),
+        };
+        let cases = [
+            (
+                QifAccountType::Bank,
+                vec![
+                    entry(
+                        1_999,
+                        TransferType::WithdrawalToExternalAccount,
+                        "Grocer",
+                        "Food",
+                        None,
+                    ),
+                    entry(
+                        250_000,
+                        TransferType::DepositFromExternalAccount,
+                        "Employer",
+                        "Salary",
+                        None,
+                    ),
+                ],
+            ),
+            (
+                QifAccountType::Investment,
+                vec![
+                    entry(
+                        5_000,
+                        TransferType::WithdrawalToInternalAccount,
+                        "ACME",
+                        "buy",
+                        Some((5.0, 10.0, true)),
+                    ),
+                    entry(
+                        6_000,
+                        TransferType::DepositFromInternalAccount,
+                        "ACME",
+                        "sell",
+                        Some((4.0, 15.0, false)),
+                    ),
+                    entry(
+                        0,
+                        TransferType::ZeroSumChange,
+                        "ACME",
+                        "shrsin",
+                        Some((2.0, 8.0, true)),
+                    ),
+                    entry(
+                        125,
+                        TransferType::DepositFromInternalAccount,
+                        "ACME",
+                        &DistributionType::Dividend.category(),
+                        None,
+                    ),
+                    entry(
+                        10_000,
+                        TransferType::DepositFromExternalAccount,
+                        "Checking",
+                        "xin",
+                        None,
+                    ),
+                ],
+            ),
+        ];
+        for (account_type, entries) in cases {
+            let mut written = Vec::new();
+            assert_eq!(write_qif(&entries, account_type, &mut written).unwrap(), 0);
+            let written = String::from_utf8(written).unwrap();
+            assert!(QifConverter.detect(&written));
+            let records = read_records(&written);
+            assert_eq!(records.len(), entries.len(), "{}", written);
+            assert!(records
+                .iter()
+                .all(|record| record.account_type == account_type));
+
+            let read = convert(&written);
+            assert_eq!(read.len(), entries.len(), "{}", written);
+            for (read, entry) in read.iter().zip(entries.iter()) {
+                assert_eq!(read.date, entry.date);
+                assert_eq!(read.amount, entry.amount);
+                assert_eq!(read.transfer_type, entry.transfer_type);
+                assert_eq!(read.participant, entry.participant);
+                assert_eq!(read.category, entry.category);
+                assert_eq!(read.description, entry.description);
+                assert_eq!(
+                    read.stock_info.as_ref().map(|s| (
+                        s.shares,
+                        s.costbasis,
+                        s.remaining,
+                        s.is_buy
+                    )),
+                    entry.stock_info.as_ref().map(|s| (
+                        s.shares,
+                        s.costbasis,
+                        s.remaining,
+                        s.is_buy
+                    )),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,371 @@
+/* ------------------------------------------------------------------------
+  Copyright (C) 2025  Andrew J. Eberhard
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+-----------------------------------------------------------------------*/
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Deserialize;
+
+use shared_lib::LedgerEntry;
+use shared_lib::Money;
+use shared_lib::TransferType;
+
+use crate::{convert_csv_records, has_headers, ConvertError, ConvertedRecord, Converter};
+
+// Visions Federal Credit Union exports checking, credit card and certificate
+// of deposit accounts with the same columns; only checking accounts carry a
+// classification.
+const VFCU_COLUMNS: [&str; 7] = [
+    "Account Number",
+    "Post Date",
+    "Check",
+    "Description",
+    "Debit",
+    "Credit",
+    "Status",
+];
+const CLASSIFICATION: &str = "Classification";
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct VfcuRecord {
+    #[serde(rename = "Account Number")]
+    pub acct: String,
+    #[serde(rename = "Post Date")]
+    pub posted_date: String,
+    #[serde(rename = "Check")]
+    pub check_number: Option<u32>,
+    #[serde(rename = "Description")]
+    pub description: String,
+    #[serde(rename = "Debit")]
+    pub debit: Option<Money>,
+    #[serde(rename = "Credit")]
+    pub credit: Option<Money>,
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "Classification")]
+    pub classification: Option<String>,
+}
+
+impl VfcuRecord {
+    // moves credit/debit into one amount, returning whether it was a debit
+    fn amount(&self) -> Result<(Money, bool), ConvertError> {
+        match (self.debit, self.credit) {
+            (Some(debit), None) => Ok((debit, true)),
+            (None, Some(credit)) => Ok((credit, false)),
+            (Some(_), Some(_)) => Err(ConvertError::Unrecognized(format!(
+                "Transaction cannot have debit and credit: {}",
+                self.description
+            ))),
+            (None, None) => Err(ConvertError::Unrecognized(format!(
+                "Transaction must have at least a credit or a debit: {}",
+                self.description
+            ))),
+        }
+    }
+
+    fn date(&self) -> Result<String, ConvertError> {
+        let posted_date = NaiveDate::parse_from_str(&self.posted_date, "%m/%d/%Y")
+            .map_err(|e| ConvertError::Unrecognized(format!("{}: {}", self.posted_date, e)))?;
+        Ok(posted_date.format("%Y-%m-%d").to_string())
+    }
+
+    fn to_ledger_entry(
+        &self,
+        amount: Money,
+        ttype: TransferType,
+        peer: String,
+        cat: String,
+    ) -> Result<LedgerEntry, ConvertError> {
+        Ok(LedgerEntry {
+            date: self.date()?,
+            amount,
+            transfer_type: ttype,
+            participant: peer,
+            category: cat,
+            description: self.description.clone(),
+            stock_info: None,
+        })
+    }
+}
+
+fn is_vfcu_export(contents: &str, classified: bool) -> bool {
+    has_headers(contents, &VFCU_COLUMNS) && has_headers(contents, &[CLASSIFICATION]) == classified
+}
+
+fn direction(debit_not_credit: bool) -> TransferType {
+    if debit_not_credit {
+        TransferType::WithdrawalToExternalAccount
+    } else {
+        TransferType::DepositFromExternalAccount
+    }
+}
+
+pub struct VfcuCheckingConverter;
+
+impl Converter for VfcuCheckingConverter {
+    fn id(&self) -> &'static str {
+        "vfcu"
+    }
+
+    fn name(&self) -> &'static str {
+        "Visions FCU checking/savings"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        is_vfcu_export(contents, true)
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        let re = Regex::new(r"^(Deposit|Withdrawal|Check)").unwrap();
+        let dividend_re = Regex::new(r"Deposit\s+Dividend").unwrap();
+
+        convert_csv_records(contents, |txn: VfcuRecord| {
+            let (amt, debit_not_credit) = txn.amount()?;
+            let (ttype, peer, cat) = match txn.classification.clone() {
+                Some(classification) => classify_checking(&txn, classification, debit_not_credit)?,
+                None => {
+                    let x = re.captures(txn.description.as_str()).ok_or_else(|| {
+                        ConvertError::Unrecognized(format!(
+                            "Unrecognized transaction: {}",
+                            txn.description
+                        ))
+                    })?;
+                    match x.get(1).unwrap().as_str() {
+                        "Deposit" => {
+                            let ttype = if dividend_re.is_match(txn.description.as_str()) {
+                                TransferType::DepositFromInternalAccount
+                            } else {
+                                TransferType::DepositFromExternalAccount
+                            };
+                            (ttype, "Misc".to_string(), "Deposit".to_string())
+                        }
+                        "Withdrawal" => (
+                            TransferType::WithdrawalToExternalAccount,
+                            "Misc".to_string(),
+                            "Withdrawal".to_string(),
+                        ),
+                        _ => (
+                            TransferType::WithdrawalToExternalAccount,
+                            String::new(),
+                            "Check".to_string(),
+                        ),
+                    }
+                }
+            };
+            Ok(vec![txn.to_ledger_entry(amt, ttype, peer, cat)?])
+        })
+    }
+}
+
+fn classify_checking(
+    txn: &VfcuRecord,
+    classification: String,
+    debit_not_credit: bool,
+) -> Result<(TransferType, String, String), ConvertError> {
+    let classified = match classification.as_str() {
+        "Transfer" => {
+            let re = Regex::new(r"(to|from)\s([A-Za-z0-9\s]+)").unwrap();
+            match re.captures(txn.description.as_str()) {
+                Some(x) => match x.get(1).unwrap().as_str() {
+                    "to" => (
+                        TransferType::WithdrawalToExternalAccount,
+                        x.get(2).unwrap().as_str().to_string(),
+                        "Withdrawal".to_string(),
+                    ),
+                    _ => (
+                        TransferType::DepositFromExternalAccount,
+                        x.get(2).unwrap().as_str().to_string(),
+                        "Deposit".to_string(),
+                    ),
+                },
+                None => (
+                    direction(!debit_not_credit),
+                    "Misc".to_string(),
+                    txn.description.clone(),
+                ),
+            }
+        }
+        // dividends are treated the same as interest
+        "Interest Income" | "Dividend &amp; Cap Gains" => (
+            TransferType::DepositFromInternalAccount,
+            "Visions FCU".to_string(),
+            "Interest".to_string(),
+        ),
+        "Cash" | "Investments" => (
+            TransferType::WithdrawalToExternalAccount,
+            txn.description.clone(),
+            classification,
+        ),
+        "Paycheck" | "Income" => (
+            TransferType::DepositFromExternalAccount,
+            txn.description.clone(),
+            classification,
+        ),
+        "Check" => (
+            TransferType::WithdrawalToExternalAccount,
+            "Check".to_string(),
+            String::new(),
+        ),
+        "Food &amp; Dining" => (
+            direction(debit_not_credit),
+            txn.description.clone(),
+            "Food and Dining".to_string(),
+        ),
+        "Credit Card Payment" => (
+            TransferType::WithdrawalToExternalAccount,
+            txn.description.clone(),
+            "Credit Card Payment".to_string(),
+        ),
+        "Mortgage &amp; Rent" => (
+            direction(debit_not_credit),
+            txn.description.clone(),
+            "Mortgage & Rent".to_string(),
+        ),
+        "Financial" | "Travel" => (
+            direction(debit_not_credit),
+            txn.description.clone(),
+            classification,
+        ),
+        "Federal Tax" | "State Tax" => (
+            direction(debit_not_credit),
+            "IRS".to_string(),
+            classification,
+        ),
+        "Television" => (
+            direction(debit_not_credit),
+            "Utilities".to_string(),
+            classification,
+        ),
+        _ => {
+            return Err(ConvertError::Unrecognized(format!(
+                "Unrecognized classification type: {}",
+                classification
+            )))
+        }
+    };
+    Ok(classified)
+}
+
+pub struct VfcuCreditCardConverter;
+
+impl Converter for VfcuCreditCardConverter {
+    fn id(&self) -> &'static str {
+        "vfcucc"
+    }
+
+    fn name(&self) -> &'static str {
+        "Visions FCU credit card"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        is_vfcu_export(contents, false) && contents.contains("Loan Advance")
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        let re_loan = Regex::new(r"^Loan Advance Credit Card[\sA-Za-z]*\/(\s*|FSP*|TST\*|SQ\*)([A-Za-z0-9*#_\-\.\/\'&,]+\s[A-Za-z0-9*#_\-\.\/\'&,]+)").unwrap();
+        let re_payments = [
+            Regex::new(r"Payments\s+Transfer\s+\-\s+From\s+([A-Za-z0-9\s]+)\/").unwrap(),
+            Regex::new(
+                r"Recurring\s+Loan\s+Advance\s+Bill\s+Payment\s+-\s+#\d+\/([A-Za-z\*\s\.]+)\/",
+            )
+            .unwrap(),
+            Regex::new(r"Loan\s+Advance\s+Bill\s+Payment\s+#\d+\/([A-Za-z\*\s\.]+)\/").unwrap(),
+        ];
+
+        convert_csv_records(contents, |txn: VfcuRecord| {
+            let (amt, _) = txn.amount()?;
+            let description = txn.description.as_str();
+
+            let (ttype, peer, cat) = if let Some(x) = re_loan.captures(description) {
+                (
+                    TransferType::WithdrawalToExternalAccount,
+                    x.get(2).unwrap().as_str().to_string(),
+                    "Charge".to_string(),
+                )
+            } else if let Some(x) = re_payments.iter().find_map(|re| re.captures(description)) {
+                (
+                    TransferType::DepositFromExternalAccount,
+                    x.get(1).unwrap().as_str().to_string(),
+                    "Payment".to_string(),
+                )
+            } else {
+                return Err(ConvertError::Unrecognized(format!(
+                    "Statement could not be matched for charge or payment: {}",
+                    txn.description
+                )));
+            };
+            Ok(vec![txn.to_ledger_entry(amt, ttype, peer, cat)?])
+        })
+    }
+}
+
+pub struct VfcuCertificateOfDepositConverter;
+
+impl Converter for VfcuCertificateOfDepositConverter {
+    fn id(&self) -> &'static str {
+        "vfcucd"
+    }
+
+    fn name(&self) -> &'static str {
+        "Visions FCU certificate of deposit"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        is_vfcu_export(contents, false) && !contents.contains("Loan Advance")
+    }
+
+    fn convert_records(&self, contents: &str) -> Vec<ConvertedRecord> {
+        let re = Regex::new(r"Deposit\s+(Transfer|[A-Za-z0-9.]+)").unwrap();
+        let deposit_re = Regex::new(r"Deposit\s+Transfer\s+From\s+([A-Za-z0-9]+)").unwrap();
+
+        convert_csv_records(contents, |txn: VfcuRecord| {
+            let (amt, _) = txn.amount()?;
+
+            let (ttype, peer, cat) = match re.captures(txn.description.as_str()) {
+                Some(x) if x.get(1).unwrap().as_str() == "Transfer" => {
+                    let peer = deposit_re
+                        .captures(txn.description.as_str())
+                        .ok_or_else(|| {
+                            ConvertError::Unrecognized(format!(
+                                "No match for peer of transfer: {}",
+                                txn.description
+                            ))
+                        })?
+                        .get(1)
+                        .unwrap()
+                        .as_str()
+                        .to_string();
+                    (
+                        TransferType::DepositFromExternalAccount,
+                        peer,
+                        "Deposit".to_string(),
+                    )
+                }
+                Some(_) => (
+                    TransferType::DepositFromInternalAccount,
+                    "Visions Federal Credit Union".to_string(),
+                    "Dividend".to_string(),
+                ),
+                // assume this is a withdrawal if deposit did not match
+                None => (
+                    TransferType::WithdrawalToExternalAccount,
+                    "Unknown".to_string(),
+                    "Withdrawal".to_string(),
+                ),
+            };
+            Ok(vec![txn.to_ledger_entry(amt, ttype, peer, cat)?])
+        })
+    }
+}